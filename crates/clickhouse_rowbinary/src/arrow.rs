@@ -0,0 +1,1077 @@
+//! Conversions between `RowBinary` data and Arrow record batches.
+//!
+//! Batches are decoded with [`RowBinaryValueReader::read_columns`] and
+//! encoded with [`RowBinaryValueWriter::write_columns`], so columns move
+//! between Arrow arrays and `RowBinary` without building
+//! [`Value`](crate::Value)s. [`RecordBatch`] is the type
+//! `arrow::record_batch::RecordBatch` re-exports.
+//!
+//! | `ClickHouse` type | Arrow type |
+//! | --- | --- |
+//! | `Bool` | `Boolean` |
+//! | `UInt8`..`UInt64`, `Int8`..`Int64` | `UInt8`..`UInt64`, `Int8`..`Int64` |
+//! | `Float32`, `Float64` | `Float32`, `Float64` |
+//! | `String` | `Utf8` |
+//! | `FixedString(N)` | `FixedSizeBinary(N)` |
+//! | `Date`, `Date32` | `Date32` |
+//! | `DateTime` | `Timestamp(Second)` |
+//! | `DateTime64(P)` | `Timestamp` of the coarsest unit holding `P` digits |
+//! | `Decimal(P, S)` up to 128 bits | `Decimal128(P, S)` |
+//! | `Enum8`, `Enum16` | `Int8`, `Int16` holding the enum values |
+//! | `Array(T)` | `List` |
+//! | `Tuple(...)` | `Struct` with fields named after the elements, or `1`, `2`, ... |
+//! | `Map(K, V)` | `Map` with `key` and `value` entry fields |
+//!
+//! `Nullable` columns and elements become nullable fields, `LowCardinality`
+//! columns are stored as their inner type, and timestamps keep the column
+//! timezone. Other types, such as `UUID`, `IPv4`, `IPv6`, 128-bit and
+//! wider integers and `Decimal256`, are rejected with
+//! [`Error::UnsupportedType`]. `String` values read into Arrow must be
+//! valid UTF-8; `LargeUtf8`, `Binary` and `LargeBinary` arrays can be
+//! written as `String` columns too, and nested arrays are matched by shape
+//! whatever their field names.
+
+use std::{
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use arrow_array::{
+    Array, ArrayRef, ArrowPrimitiveType, BooleanArray, FixedSizeBinaryArray, ListArray, MapArray,
+    PrimitiveArray, RecordBatch, StringArray, StructArray,
+    cast::AsArray,
+    types::{
+        Date32Type, Decimal128Type, Float32Type, Float64Type, Int8Type, Int16Type, Int32Type,
+        Int64Type, TimestampMicrosecondType, TimestampMillisecondType, TimestampNanosecondType,
+        TimestampSecondType, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
+    },
+};
+use arrow_buffer::{OffsetBuffer, ScalarBuffer};
+use arrow_schema::{
+    ArrowError, DataType, Field as ArrowField, FieldRef, Fields, Schema as ArrowSchema, TimeUnit,
+};
+
+use crate::{
+    error::{Error, Result},
+    io::{read_uvarint, write_uvarint},
+    rowbinary::{
+        Collect, ColumnValue, DecodeColumn, EncodeColumn, FromColumnValue, Row, RowBinaryFormat,
+        RowBinaryValueReader, RowBinaryValueWriter, Schema, Values, WriteStats, collected,
+        column_type, read_collected, storage_type,
+    },
+    types::{DATETIME64_MAX_PRECISION, DecimalSize, TupleItem, TypeDesc},
+};
+
+impl From<ArrowError> for Error {
+    fn from(err: ArrowError) -> Self {
+        Error::Arrow(err.to_string())
+    }
+}
+
+/// Returns the Arrow schema of record batches holding rows of `schema`.
+///
+/// # Errors
+///
+/// Returns [`Error::UnsupportedType`] when a column type has no Arrow
+/// mapping.
+pub fn arrow_schema(schema: &Schema) -> Result<ArrowSchema> {
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| arrow_field(&field.name, &field.ty))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(ArrowSchema::new(fields))
+}
+
+/// Decodes up to `max_rows` rows from `reader` into a record batch.
+///
+/// Returns `None` once the stream has no more rows.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a column type has no Arrow mapping,
+/// a `String` is not valid UTF-8, or [`RowBinaryValueReader::read_columns`]
+/// fails.
+pub fn read_record_batch<R: Read>(
+    reader: &mut RowBinaryValueReader<R>,
+    max_rows: usize,
+) -> Result<Option<RecordBatch>> {
+    let arrow = Arc::new(arrow_schema(reader.schema())?);
+    let columns = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| collector(&field.ty))
+        .collect::<Result<Vec<_>>>()?;
+    match read_collected(reader, columns, max_rows)? {
+        Some(arrays) => Ok(Some(RecordBatch::try_new(arrow, arrays)?)),
+        None => Ok(None),
+    }
+}
+
+/// Encodes the rows of `batch` with `writer`.
+///
+/// Batch columns are matched to the writer's schema fields by name;
+/// columns the schema does not name are ignored. Call
+/// [`RowBinaryValueWriter::write_header`] before writing the first batch.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a schema field has no batch
+/// column, a column does not hold the Arrow type of its field, a
+/// non-`Nullable` column holds nulls, a value does not fit its column, or
+/// [`RowBinaryValueWriter::write_columns`] fails.
+pub fn write_record_batch<W: Write>(
+    writer: &mut RowBinaryValueWriter<W>,
+    batch: &RecordBatch,
+) -> Result<WriteStats> {
+    let columns = writer
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            batch
+                .column_by_name(&field.name)
+                .map(|array| ArrayColumn(array.as_ref()))
+                .ok_or_else(|| Error::UnknownColumn(field.name.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let columns = columns
+        .iter()
+        .map(|column| column as &dyn EncodeColumn)
+        .collect::<Vec<_>>();
+    writer.write_columns(&columns)
+}
+
+/// Converts decoded `rows` of `schema` into a record batch.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a column type has no Arrow mapping
+/// or a row does not match `schema`.
+pub fn to_record_batch(rows: &[Row], schema: &Schema) -> Result<RecordBatch> {
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_rows(rows)?;
+    let payload = writer.into_inner();
+    let mut reader = RowBinaryValueReader::with_schema(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinary,
+        schema.clone(),
+    )?;
+    match read_record_batch(&mut reader, rows.len())? {
+        Some(batch) => Ok(batch),
+        None => Ok(RecordBatch::new_empty(Arc::new(arrow_schema(schema)?))),
+    }
+}
+
+/// Converts the rows of `batch` into decoded rows of `schema`.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] under the conditions of
+/// [`write_record_batch`].
+pub fn record_batch_to_rows(batch: &RecordBatch, schema: &Schema) -> Result<Vec<Row>> {
+    let payload = record_batch_to_rowbinary(batch, schema)?;
+    RowBinaryValueReader::with_schema(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinary,
+        schema.clone(),
+    )?
+    .rows()
+    .collect()
+}
+
+/// Encodes the rows of `batch` as a `RowBinary` insert payload for
+/// `schema`.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] under the conditions of
+/// [`write_record_batch`].
+pub fn record_batch_to_rowbinary(batch: &RecordBatch, schema: &Schema) -> Result<Vec<u8>> {
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    write_record_batch(&mut writer, batch)?;
+    Ok(writer.into_inner())
+}
+
+/// Returns the Arrow field named `name` holding values of `ty`.
+fn arrow_field(name: &str, ty: &TypeDesc) -> Result<ArrowField> {
+    let (nullable, ty) = column_type(ty);
+    Ok(ArrowField::new(name, arrow_type(ty)?, nullable))
+}
+
+/// Returns the struct fields of a tuple, named after its elements or by
+/// their 1-based position.
+fn tuple_fields(items: &[TupleItem]) -> Result<Fields> {
+    items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| match &item.name {
+            Some(name) => arrow_field(name, &item.ty),
+            None => arrow_field(&(index + 1).to_string(), &item.ty),
+        })
+        .collect()
+}
+
+/// Returns the entries field of a map with `key` and `value` types.
+fn map_entries(key: &TypeDesc, value: &TypeDesc) -> Result<ArrowField> {
+    let fields = Fields::from(vec![arrow_field("key", key)?, arrow_field("value", value)?]);
+    Ok(ArrowField::new("entries", DataType::Struct(fields), false))
+}
+
+/// Returns the Arrow type of non-null values of `ty`.
+fn arrow_type(ty: &TypeDesc) -> Result<DataType> {
+    Ok(match ty {
+        TypeDesc::Bool => DataType::Boolean,
+        TypeDesc::UInt8 => DataType::UInt8,
+        TypeDesc::UInt16 => DataType::UInt16,
+        TypeDesc::UInt32 => DataType::UInt32,
+        TypeDesc::UInt64 => DataType::UInt64,
+        TypeDesc::Int8 | TypeDesc::Enum8(_) => DataType::Int8,
+        TypeDesc::Int16 | TypeDesc::Enum16(_) => DataType::Int16,
+        TypeDesc::Int32 => DataType::Int32,
+        TypeDesc::Int64 => DataType::Int64,
+        TypeDesc::Float32 => DataType::Float32,
+        TypeDesc::Float64 => DataType::Float64,
+        TypeDesc::String => DataType::Utf8,
+        TypeDesc::FixedString { length } => DataType::FixedSizeBinary(
+            i32::try_from(*length).map_err(|_| Error::Overflow("FixedString length"))?,
+        ),
+        TypeDesc::Date | TypeDesc::Date32 => DataType::Date32,
+        TypeDesc::DateTime { timezone } => {
+            DataType::Timestamp(TimeUnit::Second, timezone.as_deref().map(Arc::from))
+        }
+        TypeDesc::DateTime64 {
+            precision,
+            timezone,
+        } => DataType::Timestamp(
+            timestamp_unit(*precision)?.0,
+            timezone.as_deref().map(Arc::from),
+        ),
+        TypeDesc::Array(inner) => DataType::List(Arc::new(arrow_field("item", inner)?)),
+        TypeDesc::Tuple(items) if !items.is_empty() => DataType::Struct(tuple_fields(items)?),
+        TypeDesc::Map { key, value } => DataType::Map(Arc::new(map_entries(key, value)?), false),
+        ty => match decimal_parts(ty) {
+            Some((precision, scale)) => DataType::Decimal128(precision, scale),
+            None => return Err(Error::UnsupportedType(ty.type_name())),
+        },
+    })
+}
+
+/// Returns the precision and scale of decimals stored in at most 128 bits.
+fn decimal_parts(ty: &TypeDesc) -> Option<(u8, i8)> {
+    let (precision, scale) = match ty {
+        TypeDesc::Decimal32 { scale } => (9, *scale),
+        TypeDesc::Decimal64 { scale } => (18, *scale),
+        TypeDesc::Decimal128 { scale } => (38, *scale),
+        TypeDesc::Decimal {
+            precision,
+            scale,
+            size: DecimalSize::Bits32 | DecimalSize::Bits64 | DecimalSize::Bits128,
+        } => (*precision, *scale),
+        _ => return None,
+    };
+    Some((precision, i8::try_from(scale).ok()?))
+}
+
+/// Returns the Arrow unit of `DateTime64(precision)` values and the number
+/// of units per tick.
+fn timestamp_unit(precision: u8) -> Result<(TimeUnit, i64)> {
+    if precision > DATETIME64_MAX_PRECISION {
+        return Err(Error::InvalidValue("DateTime64 precision must be <= 9"));
+    }
+    let (unit, digits) = match precision {
+        0 => (TimeUnit::Second, 0),
+        1..=3 => (TimeUnit::Millisecond, 3),
+        4..=6 => (TimeUnit::Microsecond, 6),
+        _ => (TimeUnit::Nanosecond, DATETIME64_MAX_PRECISION),
+    };
+    Ok((unit, 10_i64.pow(u32::from(digits - precision))))
+}
+
+/// Collects values decoded as `T` into a primitive array of `data_type`.
+fn primitive<A, T>(
+    nullable: bool,
+    data_type: DataType,
+    convert: impl Fn(T) -> Result<A::Native> + 'static,
+) -> Box<dyn Collect<ArrayRef>>
+where
+    A: ArrowPrimitiveType,
+    T: FromColumnValue + 'static,
+{
+    collected(nullable, move |values: Values<T>| {
+        let array = values
+            .into_options()
+            .map(|value| value.map(&convert).transpose())
+            .collect::<Result<PrimitiveArray<A>>>()?;
+        Ok(Arc::new(array.with_data_type(data_type)) as ArrayRef)
+    })
+}
+
+/// Returns the column that values of `ty` are decoded into.
+fn collector(ty: &TypeDesc) -> Result<Box<dyn Collect<ArrayRef>>> {
+    let (nullable, inner) = column_type(ty);
+    let data_type = arrow_type(inner)?;
+    Ok(match inner {
+        TypeDesc::Array(inner) => Box::new(ListColumn {
+            field: Arc::new(arrow_field("item", inner)?),
+            offsets: vec![0],
+            values: collector(inner)?,
+        }),
+        TypeDesc::Tuple(items) => Box::new(StructColumn {
+            fields: tuple_fields(items)?,
+            len: 0,
+            items: items
+                .iter()
+                .map(|item| collector(&item.ty))
+                .collect::<Result<_>>()?,
+        }),
+        TypeDesc::Map { key, value } => Box::new(MapColumn {
+            entries: Arc::new(map_entries(key, value)?),
+            offsets: vec![0],
+            keys: collector(key)?,
+            values: collector(value)?,
+        }),
+        TypeDesc::Bool => collected(nullable, |values: Values<bool>| {
+            Ok(Arc::new(values.into_options().collect::<BooleanArray>()) as ArrayRef)
+        }),
+        TypeDesc::UInt8 => primitive::<UInt8Type, u8>(nullable, data_type, Ok),
+        TypeDesc::UInt16 => primitive::<UInt16Type, u16>(nullable, data_type, Ok),
+        TypeDesc::UInt32 => primitive::<UInt32Type, u32>(nullable, data_type, Ok),
+        TypeDesc::UInt64 => primitive::<UInt64Type, u64>(nullable, data_type, Ok),
+        TypeDesc::Int8 | TypeDesc::Enum8(_) => primitive::<Int8Type, i8>(nullable, data_type, Ok),
+        TypeDesc::Int16 | TypeDesc::Enum16(_) => {
+            primitive::<Int16Type, i16>(nullable, data_type, Ok)
+        }
+        TypeDesc::Int32 => primitive::<Int32Type, i32>(nullable, data_type, Ok),
+        TypeDesc::Int64 => primitive::<Int64Type, i64>(nullable, data_type, Ok),
+        TypeDesc::Float32 => primitive::<Float32Type, f32>(nullable, data_type, Ok),
+        TypeDesc::Float64 => primitive::<Float64Type, f64>(nullable, data_type, Ok),
+        TypeDesc::String => collected(nullable, |values: Values<String>| {
+            Ok(Arc::new(values.into_options().collect::<StringArray>()) as ArrayRef)
+        }),
+        TypeDesc::FixedString { length } => {
+            let size = i32::try_from(*length).map_err(|_| Error::Overflow("FixedString length"))?;
+            collected(nullable, move |values: Values<Vec<u8>>| {
+                let array = FixedSizeBinaryArray::try_from_sparse_iter_with_size(
+                    values.into_options(),
+                    size,
+                )?;
+                Ok(Arc::new(array) as ArrayRef)
+            })
+        }
+        TypeDesc::Date => {
+            primitive::<Date32Type, u16>(nullable, data_type, |days| Ok(i32::from(days)))
+        }
+        TypeDesc::Date32 => primitive::<Date32Type, i32>(nullable, data_type, Ok),
+        TypeDesc::DateTime { .. } => {
+            primitive::<TimestampSecondType, u32>(nullable, data_type, |seconds| {
+                Ok(i64::from(seconds))
+            })
+        }
+        TypeDesc::DateTime64 { precision, .. } => {
+            let (unit, factor) = timestamp_unit(*precision)?;
+            let scale = move |ticks: i64| {
+                ticks
+                    .checked_mul(factor)
+                    .ok_or(Error::Overflow("DateTime64 outside Arrow timestamp range"))
+            };
+            match unit {
+                TimeUnit::Second => {
+                    primitive::<TimestampSecondType, i64>(nullable, data_type, scale)
+                }
+                TimeUnit::Millisecond => {
+                    primitive::<TimestampMillisecondType, i64>(nullable, data_type, scale)
+                }
+                TimeUnit::Microsecond => {
+                    primitive::<TimestampMicrosecondType, i64>(nullable, data_type, scale)
+                }
+                TimeUnit::Nanosecond => {
+                    primitive::<TimestampNanosecondType, i64>(nullable, data_type, scale)
+                }
+            }
+        }
+        ty => match decimal_storage(ty) {
+            Some(DecimalSize::Bits32) => {
+                primitive::<Decimal128Type, i32>(nullable, data_type, |value| Ok(i128::from(value)))
+            }
+            Some(DecimalSize::Bits64) => {
+                primitive::<Decimal128Type, i64>(nullable, data_type, |value| Ok(i128::from(value)))
+            }
+            _ => primitive::<Decimal128Type, i128>(nullable, data_type, Ok),
+        },
+    })
+}
+
+/// Returns the storage size of a decimal type with an Arrow mapping.
+fn decimal_storage(ty: &TypeDesc) -> Option<DecimalSize> {
+    match ty {
+        TypeDesc::Decimal32 { .. } => Some(DecimalSize::Bits32),
+        TypeDesc::Decimal64 { .. } => Some(DecimalSize::Bits64),
+        TypeDesc::Decimal128 { .. } => Some(DecimalSize::Bits128),
+        TypeDesc::Decimal { size, .. } => Some(*size),
+        _ => None,
+    }
+}
+
+/// Returns the error for a nested column read as a `ty` column.
+fn nested_mismatch(ty: &TypeDesc, actual: &str) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: actual.to_string(),
+    }
+}
+
+/// Reads the element count that precedes `Array` and `Map` values.
+fn read_len(reader: &mut dyn Read) -> Result<usize> {
+    let len = read_uvarint(reader)?.ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "unexpected EOF while reading length",
+        ))
+    })?;
+    usize::try_from(len).map_err(|_| Error::Overflow("array length too large"))
+}
+
+/// Returns the list offset after `len` decoded elements.
+fn list_offset(len: usize) -> Result<i32> {
+    i32::try_from(len).map_err(|_| Error::Overflow("Arrow list holds more than 2^31 elements"))
+}
+
+/// Returns the number of elements before the list at `index`.
+fn element_count(offsets: &[i32], index: usize) -> usize {
+    usize::try_from(offsets[index]).unwrap_or(0)
+}
+
+/// `Array` column decoded into a list array.
+struct ListColumn {
+    field: FieldRef,
+    offsets: Vec<i32>,
+    values: Box<dyn Collect<ArrayRef>>,
+}
+
+impl DecodeColumn for ListColumn {
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        match storage_type(ty) {
+            TypeDesc::Array(inner) => self.values.check_type(inner),
+            _ => Err(nested_mismatch(ty, "List")),
+        }
+    }
+
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()> {
+        let TypeDesc::Array(inner) = storage_type(ty) else {
+            return Err(Error::Internal("List column without Array type"));
+        };
+        for _ in 0..read_len(reader)? {
+            self.values.read_value(inner, reader)?;
+        }
+        self.offsets.push(list_offset(self.values.len())?);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.offsets.truncate(len + 1);
+        self.values.truncate(element_count(&self.offsets, len));
+    }
+}
+
+impl Collect<ArrayRef> for ListColumn {
+    fn finish(self: Box<Self>) -> Result<ArrayRef> {
+        let offsets = OffsetBuffer::new(ScalarBuffer::from(self.offsets));
+        let array = ListArray::try_new(self.field, offsets, self.values.finish()?, None)?;
+        Ok(Arc::new(array))
+    }
+}
+
+/// `Tuple` column decoded into a struct array.
+struct StructColumn {
+    fields: Fields,
+    len: usize,
+    items: Vec<Box<dyn Collect<ArrayRef>>>,
+}
+
+impl DecodeColumn for StructColumn {
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        match storage_type(ty) {
+            TypeDesc::Tuple(items) if items.len() == self.items.len() => self
+                .items
+                .iter()
+                .zip(items)
+                .try_for_each(|(column, item)| column.check_type(&item.ty)),
+            _ => Err(nested_mismatch(ty, "Struct")),
+        }
+    }
+
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()> {
+        let TypeDesc::Tuple(items) = storage_type(ty) else {
+            return Err(Error::Internal("Struct column without Tuple type"));
+        };
+        for (column, item) in self.items.iter_mut().zip(items) {
+            column.read_value(&item.ty, reader)?;
+        }
+        self.len += 1;
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+        for column in &mut self.items {
+            column.truncate(len);
+        }
+    }
+}
+
+impl Collect<ArrayRef> for StructColumn {
+    fn finish(self: Box<Self>) -> Result<ArrayRef> {
+        let columns = self
+            .items
+            .into_iter()
+            .map(Collect::finish)
+            .collect::<Result<Vec<_>>>()?;
+        Ok(Arc::new(StructArray::try_new(self.fields, columns, None)?))
+    }
+}
+
+/// `Map` column decoded into a map array.
+struct MapColumn {
+    entries: FieldRef,
+    offsets: Vec<i32>,
+    keys: Box<dyn Collect<ArrayRef>>,
+    values: Box<dyn Collect<ArrayRef>>,
+}
+
+impl DecodeColumn for MapColumn {
+    fn len(&self) -> usize {
+        self.offsets.len() - 1
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        match storage_type(ty) {
+            TypeDesc::Map { key, value } => {
+                self.keys.check_type(key)?;
+                self.values.check_type(value)
+            }
+            _ => Err(nested_mismatch(ty, "Map")),
+        }
+    }
+
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()> {
+        let TypeDesc::Map { key, value } = storage_type(ty) else {
+            return Err(Error::Internal("Map column without Map type"));
+        };
+        for _ in 0..read_len(reader)? {
+            self.keys.read_value(key, reader)?;
+            self.values.read_value(value, reader)?;
+        }
+        self.offsets.push(list_offset(self.keys.len())?);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.offsets.truncate(len + 1);
+        let count = element_count(&self.offsets, len);
+        self.keys.truncate(count);
+        self.values.truncate(count);
+    }
+}
+
+impl Collect<ArrayRef> for MapColumn {
+    fn finish(self: Box<Self>) -> Result<ArrayRef> {
+        let DataType::Struct(fields) = self.entries.data_type() else {
+            return Err(Error::Internal("Map entries field is not a struct"));
+        };
+        let entries = StructArray::try_new(
+            fields.clone(),
+            vec![self.keys.finish()?, self.values.finish()?],
+            None,
+        )?;
+        let offsets = OffsetBuffer::new(ScalarBuffer::from(self.offsets));
+        let array = MapArray::try_new(self.entries, offsets, entries, None, false)?;
+        Ok(Arc::new(array))
+    }
+}
+
+/// Returns whether arrays of `actual` can be written where `expected`, an
+/// [`arrow_type`], is read, comparing nested types by shape only.
+fn type_matches(expected: &DataType, actual: &DataType) -> bool {
+    match (expected, actual) {
+        (DataType::Utf8, actual) => matches!(
+            actual,
+            DataType::Utf8 | DataType::LargeUtf8 | DataType::Binary | DataType::LargeBinary
+        ),
+        (DataType::Timestamp(unit, _), DataType::Timestamp(actual, _)) => unit == actual,
+        (DataType::List(expected), DataType::List(actual))
+        | (DataType::Map(expected, _), DataType::Map(actual, _)) => {
+            type_matches(expected.data_type(), actual.data_type())
+        }
+        (DataType::Struct(expected), DataType::Struct(actual)) => {
+            expected.len() == actual.len()
+                && expected.iter().zip(actual).all(|(expected, actual)| {
+                    type_matches(expected.data_type(), actual.data_type())
+                })
+        }
+        (expected, actual) => expected == actual,
+    }
+}
+
+/// Arrow array encoded as a `RowBinary` column.
+struct ArrayColumn<'a>(&'a dyn Array);
+
+impl EncodeColumn for ArrayColumn<'_> {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        let (nullable, inner) = column_type(ty);
+        let expected = arrow_type(inner)?;
+        let actual = self.0.data_type();
+        if !type_matches(&expected, actual) {
+            return Err(Error::TypeMismatch {
+                expected: ty.type_name(),
+                actual: actual.to_string(),
+            });
+        }
+        if !nullable && self.0.null_count() > 0 {
+            return Err(Error::InvalidValue("null value in a non-Nullable column"));
+        }
+        Ok(())
+    }
+
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()> {
+        write_element(self.0, index, ty, writer)
+    }
+}
+
+/// Encodes the value at `index` of `array` as `ty`, with its null flag
+/// when `ty` is `Nullable`.
+fn write_element(
+    array: &dyn Array,
+    index: usize,
+    ty: &TypeDesc,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    match storage_type(ty) {
+        TypeDesc::Nullable(_) if array.is_null(index) => {
+            writer.write_all(&[1])?;
+            Ok(())
+        }
+        TypeDesc::Nullable(inner) => {
+            writer.write_all(&[0])?;
+            write_scalar(array, index, storage_type(inner), writer)
+        }
+        _ if array.is_null(index) => {
+            Err(Error::InvalidValue("null value in a non-Nullable column"))
+        }
+        ty => write_scalar(array, index, ty, writer),
+    }
+}
+
+/// Encodes the `Array`, `Tuple` or `Map` value at `index` of `array`.
+fn write_nested(
+    array: &dyn Array,
+    index: usize,
+    ty: &TypeDesc,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    match ty {
+        TypeDesc::Array(inner) => {
+            let list = array.as_list::<i32>();
+            let range = list.value_offsets()[index]..list.value_offsets()[index + 1];
+            write_uvarint(u64::try_from(range.len()).unwrap_or(0), writer)?;
+            for element in range {
+                let element = usize::try_from(element).unwrap_or(0);
+                write_element(list.values().as_ref(), element, inner, writer)?;
+            }
+            Ok(())
+        }
+        TypeDesc::Tuple(items) => {
+            let tuple = array.as_struct();
+            for (column, item) in tuple.columns().iter().zip(items) {
+                write_element(column.as_ref(), index, &item.ty, writer)?;
+            }
+            Ok(())
+        }
+        TypeDesc::Map { key, value } => {
+            let map = array.as_map();
+            let range = map.value_offsets()[index]..map.value_offsets()[index + 1];
+            write_uvarint(u64::try_from(range.len()).unwrap_or(0), writer)?;
+            for entry in range {
+                let entry = usize::try_from(entry).unwrap_or(0);
+                write_element(map.keys().as_ref(), entry, key, writer)?;
+                write_element(map.values().as_ref(), entry, value, writer)?;
+            }
+            Ok(())
+        }
+        _ => Err(Error::Internal("nested Arrow value of a scalar type")),
+    }
+}
+
+/// Encodes the value at `index` of an array that
+/// [`ArrayColumn::check_type`] accepted for `ty`.
+fn write_scalar(
+    array: &dyn Array,
+    index: usize,
+    ty: &TypeDesc,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    match ty {
+        TypeDesc::Array(_) | TypeDesc::Tuple(_) | TypeDesc::Map { .. } => {
+            write_nested(array, index, ty, writer)
+        }
+        TypeDesc::Bool => array.as_boolean().value(index).write_to(ty, writer),
+        TypeDesc::UInt8 => array
+            .as_primitive::<UInt8Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::UInt16 => array
+            .as_primitive::<UInt16Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::UInt32 => array
+            .as_primitive::<UInt32Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::UInt64 => array
+            .as_primitive::<UInt64Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Int8 | TypeDesc::Enum8(_) => array
+            .as_primitive::<Int8Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Int16 | TypeDesc::Enum16(_) => array
+            .as_primitive::<Int16Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Int32 => array
+            .as_primitive::<Int32Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Int64 => array
+            .as_primitive::<Int64Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Float32 => array
+            .as_primitive::<Float32Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Float64 => array
+            .as_primitive::<Float64Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::String => {
+            let bytes = match array.data_type() {
+                DataType::LargeUtf8 => array.as_string::<i64>().value(index).as_bytes(),
+                DataType::Binary => array.as_binary::<i32>().value(index),
+                DataType::LargeBinary => array.as_binary::<i64>().value(index),
+                _ => array.as_string::<i32>().value(index).as_bytes(),
+            };
+            bytes.write_to(ty, writer)
+        }
+        TypeDesc::FixedString { .. } => array
+            .as_fixed_size_binary()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::Date => u16::try_from(array.as_primitive::<Date32Type>().value(index))
+            .map_err(|_| Error::Overflow("date outside Date range"))?
+            .write_to(ty, writer),
+        TypeDesc::Date32 => array
+            .as_primitive::<Date32Type>()
+            .value(index)
+            .write_to(ty, writer),
+        TypeDesc::DateTime { .. } => u32::try_from(timestamp(array, index))
+            .map_err(|_| Error::Overflow("timestamp outside DateTime range"))?
+            .write_to(ty, writer),
+        TypeDesc::DateTime64 { precision, .. } => {
+            let (_, factor) = timestamp_unit(*precision)?;
+            timestamp(array, index)
+                .div_euclid(factor)
+                .write_to(ty, writer)
+        }
+        ty => {
+            let value = array.as_primitive::<Decimal128Type>().value(index);
+            match decimal_storage(ty) {
+                Some(DecimalSize::Bits32) => i32::try_from(value)
+                    .map_err(|_| Error::Overflow("decimal outside Decimal32 range"))?
+                    .write_to(ty, writer),
+                Some(DecimalSize::Bits64) => i64::try_from(value)
+                    .map_err(|_| Error::Overflow("decimal outside Decimal64 range"))?
+                    .write_to(ty, writer),
+                _ => value.write_to(ty, writer),
+            }
+        }
+    }
+}
+
+/// Returns the value at `index` of a timestamp array in its own unit.
+fn timestamp(array: &dyn Array, index: usize) -> i64 {
+    match array.data_type() {
+        DataType::Timestamp(TimeUnit::Millisecond, _) => array
+            .as_primitive::<TimestampMillisecondType>()
+            .value(index),
+        DataType::Timestamp(TimeUnit::Microsecond, _) => array
+            .as_primitive::<TimestampMicrosecondType>()
+            .value(index),
+        DataType::Timestamp(TimeUnit::Nanosecond, _) => {
+            array.as_primitive::<TimestampNanosecondType>().value(index)
+        }
+        _ => array.as_primitive::<TimestampSecondType>().value(index),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use arrow_array::{
+        Array, ArrayRef, Int32Array, LargeStringArray, RecordBatch, StringArray, UInt32Array,
+        cast::AsArray,
+        types::{Date32Type, Decimal128Type, TimestampMillisecondType, TimestampSecondType},
+    };
+    use arrow_schema::{DataType, TimeUnit};
+
+    use super::{
+        arrow_schema, read_record_batch, record_batch_to_rowbinary, record_batch_to_rows,
+        to_record_batch, write_record_batch,
+    };
+    use crate::{
+        error::Error,
+        rowbinary::{Row, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[
+            ("id", "UInt32"),
+            ("name", "Nullable(String)"),
+            ("tag", "LowCardinality(String)"),
+            ("code", "FixedString(2)"),
+            ("day", "Date"),
+            ("at", "DateTime('UTC')"),
+            ("tick", "DateTime64(2)"),
+            ("price", "Decimal(10, 2)"),
+            ("ok", "Bool"),
+            ("kind", "Enum8('a' = 1, 'b' = 2)"),
+        ])
+        .unwrap()
+    }
+
+    fn row(id: u32, name: Option<&str>) -> Row {
+        vec![
+            Value::UInt32(id),
+            Value::Nullable(name.map(|name| Box::new(Value::String(name.into())))),
+            Value::String(b"t".to_vec()),
+            Value::FixedString(b"ab".to_vec()),
+            Value::Date(19_000),
+            Value::DateTime(1_700_000_000),
+            Value::DateTime64(123),
+            Value::Decimal64(1_050),
+            Value::Bool(id.is_multiple_of(2)),
+            Value::Enum8(2),
+        ]
+    }
+
+    #[test]
+    fn maps_column_types() {
+        let arrow = arrow_schema(&schema()).unwrap();
+        let types = arrow
+            .fields()
+            .iter()
+            .map(|field| (field.data_type().clone(), field.is_nullable()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                (DataType::UInt32, false),
+                (DataType::Utf8, true),
+                (DataType::Utf8, false),
+                (DataType::FixedSizeBinary(2), false),
+                (DataType::Date32, false),
+                (
+                    DataType::Timestamp(TimeUnit::Second, Some("UTC".into())),
+                    false
+                ),
+                (DataType::Timestamp(TimeUnit::Millisecond, None), false),
+                (DataType::Decimal128(10, 2), false),
+                (DataType::Boolean, false),
+                (DataType::Int8, false),
+            ]
+        );
+        let nested = Schema::from_type_strings(&[
+            ("ids", "Array(Nullable(UInt8))"),
+            ("pair", "Tuple(String, n UInt32)"),
+            ("attrs", "Map(String, UInt64)"),
+        ])
+        .unwrap();
+        let arrow = arrow_schema(&nested).unwrap();
+        let types = arrow
+            .fields()
+            .iter()
+            .map(|field| field.data_type().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            types,
+            [
+                "List(UInt8)",
+                "Struct(\"1\": non-null Utf8, \"n\": non-null UInt32)",
+                "Map(\"entries\": non-null Struct(\"key\": non-null Utf8, \"value\": non-null UInt64), unsorted)",
+            ]
+        );
+        let uuid = Schema::from_type_strings(&[("id", "UUID")]).unwrap();
+        assert!(matches!(
+            arrow_schema(&uuid),
+            Err(Error::UnsupportedType(name)) if name == "UUID"
+        ));
+    }
+
+    #[test]
+    fn converts_nested_columns_to_record_batches_and_back() {
+        let schema = Schema::from_type_strings(&[
+            ("ids", "Array(Nullable(UInt8))"),
+            ("pair", "Tuple(name String, n UInt32)"),
+            ("attrs", "Map(String, Array(UInt64))"),
+        ])
+        .unwrap();
+        let rows = vec![
+            vec![
+                Value::Array(vec![
+                    Value::Nullable(Some(Box::new(Value::UInt8(1)))),
+                    Value::Nullable(None),
+                ]),
+                Value::Tuple(vec![Value::String(b"a".to_vec()), Value::UInt32(1)]),
+                Value::Map(vec![(
+                    Value::String(b"k".to_vec()),
+                    Value::Array(vec![Value::UInt64(7), Value::UInt64(8)]),
+                )]),
+            ],
+            vec![
+                Value::Array(Vec::new()),
+                Value::Tuple(vec![Value::String(b"b".to_vec()), Value::UInt32(2)]),
+                Value::Map(Vec::new()),
+            ],
+        ];
+        let batch = to_record_batch(&rows, &schema).unwrap();
+        let ids = batch.column(0).as_list::<i32>();
+        assert_eq!(ids.value_offsets(), [0, 2, 2]);
+        assert!(ids.values().is_null(1));
+        assert_eq!(batch.column(1).as_struct().column_names(), ["name", "n"]);
+        assert_eq!(batch.column(2).as_map().value_offsets(), [0, 1, 1]);
+        assert_eq!(record_batch_to_rows(&batch, &schema).unwrap(), rows);
+    }
+
+    #[test]
+    fn converts_rows_to_record_batches_and_back() {
+        let rows = vec![row(1, Some("a")), row(2, None)];
+        let batch = to_record_batch(&rows, &schema()).unwrap();
+        assert_eq!(batch.num_rows(), 2);
+        let names = batch.column(1).as_string::<i32>();
+        assert_eq!(names.value(0), "a");
+        assert!(names.is_null(1));
+        assert_eq!(
+            batch.column(4).as_primitive::<Date32Type>().value(0),
+            19_000
+        );
+        assert_eq!(
+            batch
+                .column(5)
+                .as_primitive::<TimestampSecondType>()
+                .value(0),
+            1_700_000_000
+        );
+        assert_eq!(
+            batch
+                .column(6)
+                .as_primitive::<TimestampMillisecondType>()
+                .value(0),
+            1_230
+        );
+        assert_eq!(
+            batch.column(7).as_primitive::<Decimal128Type>().value(0),
+            1_050
+        );
+        assert_eq!(record_batch_to_rows(&batch, &schema()).unwrap(), rows);
+
+        let empty = to_record_batch(&[], &schema()).unwrap();
+        assert_eq!(empty.num_rows(), 0);
+        assert_eq!(empty.schema().fields().len(), 10);
+    }
+
+    #[test]
+    fn reads_record_batches_of_at_most_max_rows() {
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+        writer
+            .write_rows([row(1, None), row(2, None), row(3, Some("c"))])
+            .unwrap();
+        let payload = writer.into_inner();
+        let mut reader = RowBinaryValueReader::with_schema(
+            payload.as_slice(),
+            RowBinaryFormat::RowBinary,
+            schema(),
+        )
+        .unwrap();
+        let sizes = std::iter::from_fn(|| read_record_batch(&mut reader, 2).unwrap())
+            .map(|batch| batch.num_rows())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [2, 1]);
+    }
+
+    #[test]
+    fn writes_record_batch_columns_by_name() {
+        let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+        let batch = RecordBatch::try_from_iter([
+            (
+                "name",
+                Arc::new(LargeStringArray::from(vec!["a", "b"])) as ArrayRef,
+            ),
+            ("extra", Arc::new(Int32Array::from(vec![0, 0])) as ArrayRef),
+            ("id", Arc::new(UInt32Array::from(vec![1, 2])) as ArrayRef),
+        ])
+        .unwrap();
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+        let stats = write_record_batch(&mut writer, &batch).unwrap();
+        assert_eq!(stats.rows, 2);
+        assert_eq!(
+            writer.into_inner(),
+            [1, 0, 0, 0, 1, b'a', 2, 0, 0, 0, 1, b'b']
+        );
+
+        let missing =
+            RecordBatch::try_from_iter([("id", Arc::new(UInt32Array::from(vec![1])) as ArrayRef)])
+                .unwrap();
+        assert!(matches!(
+            record_batch_to_rowbinary(&missing, &schema),
+            Err(Error::UnknownColumn(name)) if name == "name"
+        ));
+        let columns = |id: ArrayRef| {
+            RecordBatch::try_from_iter([
+                ("id", id),
+                ("name", Arc::new(StringArray::from(vec!["a"])) as ArrayRef),
+            ])
+            .unwrap()
+        };
+        let wrong_type = columns(Arc::new(Int32Array::from(vec![1])));
+        assert!(matches!(
+            record_batch_to_rowbinary(&wrong_type, &schema),
+            Err(Error::TypeMismatch { .. })
+        ));
+        let null = columns(Arc::new(UInt32Array::from(vec![None])));
+        assert!(null.column(0).is_null(0));
+        assert!(matches!(
+            record_batch_to_rowbinary(&null, &schema),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+}
@@ -0,0 +1,43 @@
+//! Avro binary datum support.
+//!
+//! [`AvroWriter`] encodes each row as one Avro binary datum of a record
+//! schema derived from the row [`Schema`](crate::Schema), using the
+//! server's Avro type mapping. The schema's JSON text is available for
+//! registering with a schema registry. [`AvroReader`] decodes datums
+//! written with any record schema, matching its fields to columns by name:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{AvroReader, AvroWriter, Schema, Value};
+//! let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "Nullable(String)")])?;
+//! let mut writer = AvroWriter::new(Vec::new(), schema)?;
+//! assert_eq!(
+//!     writer.avro_schema(),
+//!     r#"{"type":"record","name":"row","fields":[{"name":"id","type":"int"},{"name":"name","type":["null","bytes"]}]}"#
+//! );
+//! writer.write_row(&[
+//!     Value::UInt32(7),
+//!     Value::Nullable(Some(Box::new(Value::String(b"x".to_vec())))),
+//! ])?;
+//! let payload = writer.into_inner();
+//! assert_eq!(payload, [14, 2, 2, b'x']);
+//!
+//! // A consumer that only needs the id reads the same datum.
+//! let writer_schema = r#"{"type":"record","name":"row","fields":[
+//!     {"name":"id","type":"int"},{"name":"name","type":["null","bytes"]}]}"#;
+//! let narrow = Schema::from_type_strings(&[("id", "UInt64")])?;
+//! let mut reader = AvroReader::new(payload.as_slice(), writer_schema, narrow)?;
+//! assert_eq!(reader.read_row()?, Some(vec![Value::UInt64(7)]));
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! Datums carry no framing, so each Kafka message or other record holds
+//! one row; container files and schema registry headers are left to the
+//! caller. Strings are written as `bytes`, and `Dynamic`, `JSON` and
+//! `AggregateFunction` columns have no Avro mapping.
+
+mod reader;
+mod schema;
+mod writer;
+
+pub use reader::AvroReader;
+pub use writer::AvroWriter;
@@ -0,0 +1,489 @@
+//! Avro datum reader.
+
+use std::{
+    io::{self, BufRead, Read},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use num_bigint::BigInt;
+use uuid::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    io::read_uvarint,
+    jsoneachrow::json::map_key,
+    rowbinary::{Row, Schema, default_value},
+    text::{decimal_parts, decimal_value, fixed_string, parse_scalar, text_type},
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+use super::schema::{AvroType, Logical, parse_schema};
+
+/// Reader that decodes Avro binary datums into rows of the given schema.
+///
+/// Datums are decoded with the writer's Avro schema and resolved against
+/// the row schema by field name: writer fields without a column are
+/// skipped and columns without a writer field take their default value.
+pub struct AvroReader<R: BufRead> {
+    inner: R,
+    schema: Schema,
+    /// Writer record fields with the schema column each one fills.
+    fields: Vec<(AvroType, Option<usize>)>,
+}
+
+impl<R: BufRead> AvroReader<R> {
+    /// Creates a reader for datums written with the Avro schema
+    /// `writer_schema`, given as JSON text.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the writer schema is malformed
+    /// or is not a record.
+    pub fn new(inner: R, writer_schema: &str, schema: Schema) -> Result<Self> {
+        let AvroType::Record(record) = parse_schema(writer_schema)? else {
+            return Err(Error::InvalidValue("Avro writer schema is not a record"));
+        };
+        let fields = record
+            .into_iter()
+            .map(|(name, ty)| {
+                let column = schema.fields().iter().position(|field| field.name == name);
+                (ty, column)
+            })
+            .collect();
+        Ok(Self {
+            inner,
+            schema,
+            fields,
+        })
+    }
+
+    /// Returns the schema rows are decoded with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next row, or returns `Ok(None)` at EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a datum is malformed or ends
+    /// early, a value does not fit its column type, or IO fails.
+    pub fn read_row(&mut self) -> Result<Option<Row>> {
+        if self.inner.fill_buf()?.is_empty() {
+            return Ok(None);
+        }
+        let mut row = vec![None; self.schema.len()];
+        for (avro, column) in &self.fields {
+            let datum = read_datum(avro, &mut self.inner)?;
+            if let Some(index) = *column {
+                row[index] = Some(convert(avro, &datum, &self.schema.fields()[index].ty)?);
+            }
+        }
+        self.schema
+            .fields()
+            .iter()
+            .zip(row)
+            .map(|(field, value)| value.map_or_else(|| default_value(&field.ty), Ok))
+            .collect::<Result<Row>>()
+            .map(Some)
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for AvroReader<R> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// Avro datum decoded with its writer schema.
+enum Datum {
+    Null,
+    Boolean(bool),
+    Long(i64),
+    Float(f64),
+    Bytes(Vec<u8>),
+    Enum(usize),
+    Array(Vec<Datum>),
+    Map(Vec<(Vec<u8>, Datum)>),
+    Record(Vec<Datum>),
+    Union(usize, Box<Datum>),
+}
+
+fn eof() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "unexpected EOF inside an Avro datum",
+    ))
+}
+
+fn read_long<R: Read>(input: &mut R) -> Result<i64> {
+    let zigzag = read_uvarint(input)?.ok_or_else(eof)?;
+    Ok((zigzag >> 1).cast_signed() ^ -(zigzag & 1).cast_signed())
+}
+
+fn read_index<R: Read>(input: &mut R, len: usize) -> Result<usize> {
+    usize::try_from(read_long(input)?)
+        .ok()
+        .filter(|&index| index < len)
+        .ok_or(Error::InvalidValue("Avro union or enum index out of range"))
+}
+
+fn read_fixed<R: Read>(input: &mut R, len: usize) -> Result<Vec<u8>> {
+    // The length comes from the input, so grow the buffer as bytes arrive.
+    let mut bytes = Vec::new();
+    input.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() != len {
+        return Err(eof());
+    }
+    Ok(bytes)
+}
+
+fn read_bytes<R: Read>(input: &mut R) -> Result<Vec<u8>> {
+    let len = usize::try_from(read_long(input)?)
+        .map_err(|_| Error::InvalidValue("negative Avro length"))?;
+    read_fixed(input, len)
+}
+
+/// Reads the blocks of an Avro array or map.
+fn read_blocks<R: Read, T>(
+    input: &mut R,
+    mut read_item: impl FnMut(&mut R) -> Result<T>,
+) -> Result<Vec<T>> {
+    let mut items = Vec::new();
+    loop {
+        let count = read_long(input)?;
+        if count == 0 {
+            return Ok(items);
+        }
+        if count < 0 {
+            // Negative counts are followed by the block size in bytes.
+            read_long(input)?;
+        }
+        for _ in 0..count.unsigned_abs() {
+            items.push(read_item(input)?);
+        }
+    }
+}
+
+fn read_datum<R: Read>(avro: &AvroType, input: &mut R) -> Result<Datum> {
+    Ok(match avro {
+        AvroType::Null => Datum::Null,
+        AvroType::Boolean => match read_fixed(input, 1)?[0] {
+            0 => Datum::Boolean(false),
+            1 => Datum::Boolean(true),
+            _ => return Err(Error::InvalidValue("invalid Avro boolean")),
+        },
+        AvroType::Int(_) | AvroType::Long(_) => Datum::Long(read_long(input)?),
+        AvroType::Float => {
+            let mut bytes = [0; 4];
+            input.read_exact(&mut bytes)?;
+            Datum::Float(f64::from(f32::from_le_bytes(bytes)))
+        }
+        AvroType::Double => {
+            let mut bytes = [0; 8];
+            input.read_exact(&mut bytes)?;
+            Datum::Float(f64::from_le_bytes(bytes))
+        }
+        AvroType::Bytes(_) | AvroType::String(_) => Datum::Bytes(read_bytes(input)?),
+        AvroType::Fixed { size, .. } => Datum::Bytes(read_fixed(input, *size)?),
+        AvroType::Enum(symbols) => Datum::Enum(read_index(input, symbols.len())?),
+        AvroType::Array(item) => Datum::Array(read_blocks(input, |input| read_datum(item, input))?),
+        AvroType::Map(value) => Datum::Map(read_blocks(input, |input| {
+            Ok((read_bytes(input)?, read_datum(value, input)?))
+        })?),
+        AvroType::Union(branches) => {
+            let index = read_index(input, branches.len())?;
+            Datum::Union(index, Box::new(read_datum(&branches[index], input)?))
+        }
+        AvroType::Record(fields) => Datum::Record(
+            fields
+                .iter()
+                .map(|(_, ty)| read_datum(ty, input))
+                .collect::<Result<_>>()?,
+        ),
+    })
+}
+
+fn mismatch(ty: &TypeDesc, avro: &AvroType) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: format!("Avro {}", avro.name()),
+    }
+}
+
+/// Converts a decoded datum with writer schema `avro` to a value of `ty`.
+fn convert(avro: &AvroType, datum: &Datum, ty: &TypeDesc) -> Result<Value> {
+    let ty = text_type(ty);
+    let ty = ty.as_ref();
+    Ok(match (avro, datum, ty) {
+        (AvroType::Union(branches), Datum::Union(index, datum), ty) => {
+            return convert(&branches[*index], datum, ty);
+        }
+        (_, Datum::Null, TypeDesc::Nullable(_)) => Value::Nullable(None),
+        (_, Datum::Null, TypeDesc::Variant(_)) => Value::VariantNull,
+        (_, Datum::Null, TypeDesc::Nothing) => Value::Nothing,
+        (_, Datum::Null, ty) => default_value(ty)?,
+        (avro, datum, TypeDesc::Nullable(inner)) => {
+            Value::Nullable(Some(Box::new(convert(avro, datum, inner)?)))
+        }
+        (avro, datum, TypeDesc::Variant(types)) => {
+            for (index, alternative) in types.iter().enumerate() {
+                if let Ok(value) = convert(avro, datum, alternative) {
+                    return Ok(Value::Variant {
+                        index: u8::try_from(index)
+                            .map_err(|_| Error::Overflow("too many variants"))?,
+                        value: Box::new(value),
+                    });
+                }
+            }
+            return Err(Error::InvalidValue(
+                "Avro value matches no variant alternative",
+            ));
+        }
+        (_, Datum::Boolean(value), TypeDesc::Bool) => Value::Bool(*value),
+        (AvroType::Int(logical) | AvroType::Long(logical), Datum::Long(value), ty) => {
+            let narrow = matches!(avro, AvroType::Int(_));
+            from_long(*value, narrow, *logical, ty)?.ok_or_else(|| mismatch(ty, avro))?
+        }
+        // Narrowing a double to a 32-bit float column rounds it.
+        #[allow(clippy::cast_possible_truncation)]
+        (_, Datum::Float(value), ty) => match ty {
+            TypeDesc::Float64 => Value::Float64(*value),
+            TypeDesc::Float32 => Value::Float32(*value as f32),
+            TypeDesc::Float16 => Value::Float16(*value as f32),
+            TypeDesc::BFloat16 => Value::BFloat16(*value as f32),
+            _ => return Err(mismatch(ty, avro)),
+        },
+        (_, Datum::Bytes(bytes), ty) => from_bytes(avro, bytes, ty)?,
+        (AvroType::Enum(symbols), Datum::Enum(index), ty) => {
+            let symbol = &symbols[*index];
+            match ty {
+                TypeDesc::String => Value::String(symbol.as_bytes().to_vec()),
+                TypeDesc::Enum8(_) | TypeDesc::Enum16(_) => parse_scalar(ty, symbol)?,
+                _ => return Err(mismatch(ty, avro)),
+            }
+        }
+        (AvroType::Array(item), Datum::Array(items), TypeDesc::Array(inner)) => Value::Array(
+            items
+                .iter()
+                .map(|datum| convert(item, datum, inner))
+                .collect::<Result<_>>()?,
+        ),
+        (AvroType::Array(item), Datum::Array(rows), TypeDesc::Nested(items)) => Value::Array(
+            rows.iter()
+                .map(|datum| convert_tuple(item, datum, items))
+                .collect::<Result<_>>()?,
+        ),
+        (AvroType::Map(values), Datum::Map(entries), TypeDesc::Map { key, value }) => Value::Map(
+            entries
+                .iter()
+                .map(|(name, datum)| Ok((map_key(key, name)?, convert(values, datum, value)?)))
+                .collect::<Result<_>>()?,
+        ),
+        (AvroType::Record(_), Datum::Record(_), TypeDesc::Tuple(items)) => {
+            convert_tuple(avro, datum, items)?
+        }
+        (avro, _, ty) => return Err(mismatch(ty, avro)),
+    })
+}
+
+/// Converts a record to a tuple, matching fields by name when every tuple
+/// element is named and by position otherwise.
+fn convert_tuple(avro: &AvroType, datum: &Datum, items: &[TupleItem]) -> Result<Value> {
+    let (AvroType::Record(fields), Datum::Record(values)) = (avro, datum) else {
+        return Err(mismatch(&TypeDesc::Tuple(items.to_vec()), avro));
+    };
+    if items.iter().all(|item| item.name.is_some()) {
+        return items
+            .iter()
+            .map(|item| {
+                let field = fields
+                    .iter()
+                    .position(|(name, _)| item.name.as_deref() == Some(name.as_str()));
+                match field {
+                    Some(index) => convert(&fields[index].1, &values[index], &item.ty),
+                    None => default_value(&item.ty),
+                }
+            })
+            .collect::<Result<_>>()
+            .map(Value::Tuple);
+    }
+    if fields.len() != items.len() {
+        return Err(Error::InvalidValue(
+            "Avro record length does not match the tuple",
+        ));
+    }
+    fields
+        .iter()
+        .zip(values)
+        .zip(items)
+        .map(|(((_, avro), datum), item)| convert(avro, datum, &item.ty))
+        .collect::<Result<_>>()
+        .map(Value::Tuple)
+}
+
+/// Scales `value` from `from` to `to` fractional decimal digits.
+fn rescale(value: i64, from: u32, to: u32) -> Result<i64> {
+    if to >= from {
+        value
+            .checked_mul(10_i64.pow(to - from))
+            .ok_or(Error::Overflow(
+                "Avro timestamp does not fit its column type",
+            ))
+    } else {
+        Ok(value.div_euclid(10_i64.pow(from - to)))
+    }
+}
+
+/// Converts an Avro `int` (`narrow`) or `long` to `ty`, or returns
+/// `Ok(None)` when `ty` does not hold integers.
+///
+/// Unsigned columns as wide as the Avro type take negative values as
+/// their bit pattern, the way the server writes them.
+#[allow(clippy::cast_precision_loss)]
+fn from_long(
+    value: i64,
+    narrow: bool,
+    logical: Option<Logical>,
+    ty: &TypeDesc,
+) -> Result<Option<Value>> {
+    let overflow = || Error::Overflow("Avro integer does not fit its column type");
+    let unsigned32 = || {
+        if narrow && value < 0 {
+            i32::try_from(value)
+                .map(i32::cast_unsigned)
+                .map_err(|_| overflow())
+        } else {
+            u32::try_from(value).map_err(|_| overflow())
+        }
+    };
+    let timestamp_digits = match logical {
+        Some(Logical::TimestampMillis) => Some(3),
+        Some(Logical::TimestampMicros) => Some(6),
+        Some(Logical::TimestampNanos) => Some(9),
+        _ => None,
+    };
+    Ok(Some(match ty {
+        TypeDesc::Bool => Value::Bool(value != 0),
+        TypeDesc::UInt8 => Value::UInt8(u8::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::UInt16 => Value::UInt16(u16::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::UInt32 => Value::UInt32(unsigned32()?),
+        TypeDesc::UInt64 if !narrow => Value::UInt64(value.cast_unsigned()),
+        TypeDesc::UInt64 => Value::UInt64(u64::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::UInt128 => Value::UInt128(u128::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::Int8 => Value::Int8(i8::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::Int16 => Value::Int16(i16::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::Int32 => Value::Int32(i32::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::Int64 | TypeDesc::Interval(_) => Value::Int64(value),
+        TypeDesc::Int128 => Value::Int128(i128::from(value)),
+        TypeDesc::Int256 | TypeDesc::UInt256 => parse_scalar(ty, &value.to_string())?,
+        TypeDesc::Float32 => Value::Float32(value as f32),
+        TypeDesc::Float64 => Value::Float64(value as f64),
+        TypeDesc::Date => Value::Date(u16::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::Date32 => Value::Date32(i32::try_from(value).map_err(|_| overflow())?),
+        TypeDesc::DateTime { .. } => match timestamp_digits {
+            Some(digits) => {
+                Value::DateTime(u32::try_from(rescale(value, digits, 0)?).map_err(|_| overflow())?)
+            }
+            None => Value::DateTime(unsigned32()?),
+        },
+        TypeDesc::DateTime64 { precision, .. } => Value::DateTime64(match timestamp_digits {
+            Some(digits) => rescale(value, digits, u32::from(*precision))?,
+            None => value,
+        }),
+        TypeDesc::Ipv4 => Value::Ipv4(Ipv4Addr::from(unsigned32()?)),
+        ty => {
+            let Some((scale, size)) = decimal_parts(ty) else {
+                return Ok(None);
+            };
+            decimal_value(
+                size,
+                &(BigInt::from(value) * BigInt::from(10).pow(u32::from(scale))),
+            )?
+        }
+    }))
+}
+
+/// Converts Avro `bytes`, `string` or `fixed` data to `ty`.
+fn from_bytes(avro: &AvroType, bytes: &[u8], ty: &TypeDesc) -> Result<Value> {
+    let (fixed, logical) = match avro {
+        AvroType::Fixed { logical, .. } => (true, *logical),
+        AvroType::Bytes(logical) | AvroType::String(logical) => (false, *logical),
+        _ => (false, None),
+    };
+    let width = |len: usize| (fixed && bytes.len() == len).then_some(bytes);
+    let text = || {
+        std::str::from_utf8(bytes)
+            .map_err(|_| Error::InvalidValue("Avro string is not valid UTF-8"))
+    };
+    Ok(match ty {
+        TypeDesc::String => Value::String(bytes.to_vec()),
+        TypeDesc::FixedString { length } => fixed_string(bytes, *length)?,
+        TypeDesc::Uuid => match width(16) {
+            Some(bytes) => Value::Uuid(Uuid::from_slice(bytes).map_err(|_| mismatch(ty, avro))?),
+            None => parse_scalar(ty, text()?)?,
+        },
+        TypeDesc::Enum8(_) | TypeDesc::Enum16(_) => parse_scalar(ty, text()?)?,
+        TypeDesc::Ipv6 | TypeDesc::Int128 | TypeDesc::UInt128 => {
+            let bytes: [u8; 16] = width(16)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| mismatch(ty, avro))?;
+            match ty {
+                TypeDesc::Ipv6 => Value::Ipv6(Ipv6Addr::from(bytes)),
+                TypeDesc::Int128 => Value::Int128(i128::from_le_bytes(bytes)),
+                _ => Value::UInt128(u128::from_le_bytes(bytes)),
+            }
+        }
+        TypeDesc::Int256 | TypeDesc::UInt256 => {
+            let bytes: [u8; 32] = width(32)
+                .and_then(|bytes| bytes.try_into().ok())
+                .ok_or_else(|| mismatch(ty, avro))?;
+            if matches!(ty, TypeDesc::Int256) {
+                Value::Int256(bytes)
+            } else {
+                Value::UInt256(bytes)
+            }
+        }
+        ty => {
+            let (Some((scale, size)), Some(Logical::Decimal { scale: from })) =
+                (decimal_parts(ty), logical)
+            else {
+                return Err(mismatch(ty, avro));
+            };
+            let mut mantissa = BigInt::from_signed_bytes_be(bytes);
+            if scale >= from {
+                mantissa *= BigInt::from(10).pow(u32::from(scale - from));
+            } else {
+                let divisor = BigInt::from(10).pow(u32::from(from - scale));
+                if &mantissa % &divisor != BigInt::ZERO {
+                    return Err(Error::InvalidValue(
+                        "Avro decimal has more fractional digits than its column",
+                    ));
+                }
+                mantissa /= divisor;
+            }
+            decimal_value(size, &mantissa)?
+        }
+    })
+}
@@ -0,0 +1,621 @@
+//! Avro schemas: parsing writer schemas and deriving them from columns.
+
+use std::{
+    borrow::Cow,
+    collections::{HashMap, HashSet},
+};
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::json::{Json, parse as parse_json, write_string},
+    rowbinary::Schema,
+    text::{decimal_parts, is_composite, text_type},
+    types::{DecimalSize, TupleItem, TypeDesc},
+};
+
+/// Logical type annotating an Avro primitive or fixed type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Logical {
+    Date,
+    TimestampMillis,
+    TimestampMicros,
+    TimestampNanos,
+    Decimal { scale: u8 },
+    Uuid,
+}
+
+/// Resolved Avro schema with named type references inlined.
+#[derive(Clone, Debug, PartialEq)]
+pub(crate) enum AvroType {
+    Null,
+    Boolean,
+    Int(Option<Logical>),
+    Long(Option<Logical>),
+    Float,
+    Double,
+    Bytes(Option<Logical>),
+    String(Option<Logical>),
+    Fixed {
+        size: usize,
+        logical: Option<Logical>,
+    },
+    Enum(Vec<String>),
+    Array(Box<AvroType>),
+    Map(Box<AvroType>),
+    Union(Vec<AvroType>),
+    Record(Vec<(String, AvroType)>),
+}
+
+impl AvroType {
+    /// Returns the Avro type name used in error messages.
+    pub(crate) fn name(&self) -> &'static str {
+        match self {
+            AvroType::Null => "null",
+            AvroType::Boolean => "boolean",
+            AvroType::Int(_) => "int",
+            AvroType::Long(_) => "long",
+            AvroType::Float => "float",
+            AvroType::Double => "double",
+            AvroType::Bytes(_) => "bytes",
+            AvroType::String(_) => "string",
+            AvroType::Fixed { .. } => "fixed",
+            AvroType::Enum(_) => "enum",
+            AvroType::Array(_) => "array",
+            AvroType::Map(_) => "map",
+            AvroType::Union(_) => "union",
+            AvroType::Record(_) => "record",
+        }
+    }
+}
+
+fn invalid() -> Error {
+    Error::InvalidValue("invalid Avro schema")
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|_| invalid())
+}
+
+type Members<'a> = [(Cow<'a, [u8]>, Json<'a>)];
+
+fn member<'m, 'a>(members: &'m Members<'a>, key: &str) -> Option<&'m Json<'a>> {
+    members
+        .iter()
+        .find(|(name, _)| name.as_ref() == key.as_bytes())
+        .map(|(_, value)| value)
+}
+
+fn text<'m>(members: &'m Members<'_>, key: &str) -> Result<Option<&'m str>> {
+    match member(members, key) {
+        Some(Json::String(text)) => utf8(text).map(Some),
+        None => Ok(None),
+        Some(_) => Err(invalid()),
+    }
+}
+
+/// Parses the JSON text of an Avro schema.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidValue`] when the schema is malformed and
+/// [`Error::UnsupportedType`] for unknown or recursive named types.
+pub(crate) fn parse_schema(text: &str) -> Result<AvroType> {
+    SchemaParser::default().parse(&parse_json(text.as_bytes())?, "")
+}
+
+/// Named types seen so far; `None` while a type's definition is parsed.
+#[derive(Default)]
+struct SchemaParser {
+    named: HashMap<String, Option<AvroType>>,
+}
+
+impl SchemaParser {
+    fn parse(&mut self, json: &Json<'_>, namespace: &str) -> Result<AvroType> {
+        match json {
+            Json::String(name) => self.reference(utf8(name)?, namespace),
+            Json::Array(branches) => {
+                let branches = branches
+                    .iter()
+                    .map(|branch| self.parse(branch, namespace))
+                    .collect::<Result<Vec<_>>>()?;
+                if branches
+                    .iter()
+                    .any(|branch| matches!(branch, AvroType::Union(_)))
+                {
+                    return Err(invalid());
+                }
+                Ok(AvroType::Union(branches))
+            }
+            Json::Object(members) => self.complex(members, namespace),
+            _ => Err(invalid()),
+        }
+    }
+
+    fn reference(&self, name: &str, namespace: &str) -> Result<AvroType> {
+        let primitive = match name {
+            "null" => AvroType::Null,
+            "boolean" => AvroType::Boolean,
+            "int" => AvroType::Int(None),
+            "long" => AvroType::Long(None),
+            "float" => AvroType::Float,
+            "double" => AvroType::Double,
+            "bytes" => AvroType::Bytes(None),
+            "string" => AvroType::String(None),
+            _ => {
+                let qualified = format!("{namespace}.{name}");
+                let full = if name.contains('.') || namespace.is_empty() {
+                    name
+                } else if self.named.contains_key(&qualified) {
+                    &qualified
+                } else {
+                    name
+                };
+                return match self.named.get(full) {
+                    Some(Some(ty)) => Ok(ty.clone()),
+                    Some(None) => Err(Error::UnsupportedType(format!(
+                        "recursive Avro type {name}"
+                    ))),
+                    None => Err(Error::UnsupportedType(format!("unknown Avro type {name}"))),
+                };
+            }
+        };
+        Ok(primitive)
+    }
+
+    fn complex(&mut self, members: &Members<'_>, namespace: &str) -> Result<AvroType> {
+        let kind = match member(members, "type").ok_or_else(invalid)? {
+            Json::String(kind) => utf8(kind)?,
+            nested => return self.parse(nested, namespace),
+        };
+        match kind {
+            "record" | "error" | "enum" | "fixed" => {
+                let name = text(members, "name")?.ok_or_else(invalid)?;
+                let (full, namespace) = if let Some((space, _)) = name.rsplit_once('.') {
+                    (name.to_string(), space.to_string())
+                } else {
+                    let space = text(members, "namespace")?.unwrap_or(namespace);
+                    let full = if space.is_empty() {
+                        name.to_string()
+                    } else {
+                        format!("{space}.{name}")
+                    };
+                    (full, space.to_string())
+                };
+                if self.named.insert(full.clone(), None).is_some() {
+                    return Err(invalid());
+                }
+                let ty = match kind {
+                    "enum" => {
+                        let Some(Json::Array(symbols)) = member(members, "symbols") else {
+                            return Err(invalid());
+                        };
+                        AvroType::Enum(
+                            symbols
+                                .iter()
+                                .map(|symbol| match symbol {
+                                    Json::String(symbol) => utf8(symbol).map(str::to_string),
+                                    _ => Err(invalid()),
+                                })
+                                .collect::<Result<_>>()?,
+                        )
+                    }
+                    "fixed" => AvroType::Fixed {
+                        size: number(member(members, "size").ok_or_else(invalid)?)?,
+                        logical: logical(kind, members)?,
+                    },
+                    _ => {
+                        let Some(Json::Array(fields)) = member(members, "fields") else {
+                            return Err(invalid());
+                        };
+                        let mut record = Vec::with_capacity(fields.len());
+                        for field in fields {
+                            let Json::Object(field) = field else {
+                                return Err(invalid());
+                            };
+                            let name = text(field, "name")?.ok_or_else(invalid)?;
+                            let ty = member(field, "type").ok_or_else(invalid)?;
+                            record.push((name.to_string(), self.parse(ty, &namespace)?));
+                        }
+                        AvroType::Record(record)
+                    }
+                };
+                self.named.insert(full, Some(ty.clone()));
+                Ok(ty)
+            }
+            "array" => Ok(AvroType::Array(Box::new(
+                self.parse(member(members, "items").ok_or_else(invalid)?, namespace)?,
+            ))),
+            "map" => Ok(AvroType::Map(Box::new(self.parse(
+                member(members, "values").ok_or_else(invalid)?,
+                namespace,
+            )?))),
+            primitive => {
+                let logical = logical(primitive, members)?;
+                Ok(match self.reference(primitive, namespace)? {
+                    AvroType::Int(_) => AvroType::Int(logical),
+                    AvroType::Long(_) => AvroType::Long(logical),
+                    AvroType::Bytes(_) => AvroType::Bytes(logical),
+                    AvroType::String(_) => AvroType::String(logical),
+                    ty => ty,
+                })
+            }
+        }
+    }
+}
+
+fn number(json: &Json<'_>) -> Result<usize> {
+    match json {
+        Json::Number(text) => text.parse().map_err(|_| invalid()),
+        _ => Err(invalid()),
+    }
+}
+
+/// Interprets a `logicalType` annotation; unknown annotations are ignored
+/// as the Avro specification requires.
+fn logical(kind: &str, members: &Members<'_>) -> Result<Option<Logical>> {
+    Ok(match (kind, text(members, "logicalType")?) {
+        ("int", Some("date")) => Some(Logical::Date),
+        ("long", Some("timestamp-millis" | "local-timestamp-millis")) => {
+            Some(Logical::TimestampMillis)
+        }
+        ("long", Some("timestamp-micros" | "local-timestamp-micros")) => {
+            Some(Logical::TimestampMicros)
+        }
+        ("long", Some("timestamp-nanos" | "local-timestamp-nanos")) => {
+            Some(Logical::TimestampNanos)
+        }
+        ("bytes" | "fixed", Some("decimal")) => {
+            let scale = member(members, "scale").map_or(Ok(0), number)?;
+            Some(Logical::Decimal {
+                scale: u8::try_from(scale).map_err(|_| invalid())?,
+            })
+        }
+        ("string" | "fixed", Some("uuid")) => Some(Logical::Uuid),
+        _ => None,
+    })
+}
+
+/// Derives the Avro schema of rows of `schema`: a record named `row`
+/// with one field per column, following the server's type mapping.
+///
+/// # Errors
+///
+/// Returns [`Error::UnsupportedType`] for columns without an Avro
+/// counterpart, such as `Dynamic` or `JSON`.
+pub(crate) fn derive_schema(schema: &Schema) -> Result<String> {
+    let mut writer = SchemaWriter::default();
+    let fields = schema
+        .fields()
+        .iter()
+        .map(|field| (field.name.clone(), &field.ty));
+    writer.record("row", "", fields)?;
+    String::from_utf8(writer.out).map_err(|_| Error::Internal("Avro schema is not UTF-8"))
+}
+
+#[derive(Default)]
+struct SchemaWriter {
+    names: HashSet<String>,
+    out: Vec<u8>,
+}
+
+impl SchemaWriter {
+    /// Picks an unused type name derived from the column path.
+    fn name(&mut self, path: &str) -> String {
+        let mut base: String = path
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+            base.insert(0, '_');
+        }
+        let mut name = base.clone();
+        let mut suffix = 1;
+        while !self.names.insert(name.clone()) {
+            suffix += 1;
+            name = format!("{base}_{suffix}");
+        }
+        name
+    }
+
+    fn push(&mut self, text: &str) {
+        self.out.extend_from_slice(text.as_bytes());
+    }
+
+    fn named_header(&mut self, kind: &str, path: &str) {
+        let name = self.name(path);
+        self.push(&format!("{{\"type\":\"{kind}\",\"name\":"));
+        write_string(name.as_bytes(), &mut self.out);
+    }
+
+    /// Writes a record named after `path` whose field types are named
+    /// after `prefix` and the field name.
+    fn record<'t>(
+        &mut self,
+        path: &str,
+        prefix: &str,
+        fields: impl Iterator<Item = (String, &'t TypeDesc)>,
+    ) -> Result<()> {
+        self.named_header("record", path);
+        self.push(",\"fields\":[");
+        for (index, (name, ty)) in fields.enumerate() {
+            if index > 0 {
+                self.push(",");
+            }
+            self.push("{\"name\":");
+            write_string(name.as_bytes(), &mut self.out);
+            self.push(",\"type\":");
+            let path = if prefix.is_empty() {
+                name
+            } else {
+                format!("{prefix}_{name}")
+            };
+            self.ty(ty, &path)?;
+            self.push("}");
+        }
+        self.push("]}");
+        Ok(())
+    }
+
+    fn tuple(&mut self, items: &[TupleItem], path: &str) -> Result<()> {
+        let fields = items.iter().enumerate().map(|(index, item)| {
+            let name = item
+                .name
+                .clone()
+                .unwrap_or_else(|| format!("field{}", index + 1));
+            (name, &item.ty)
+        });
+        self.record(path, path, fields)
+    }
+
+    fn enumeration<'n>(&mut self, path: &str, names: impl Iterator<Item = &'n str>) {
+        self.named_header("enum", path);
+        self.push(",\"symbols\":[");
+        for (index, name) in names.enumerate() {
+            if index > 0 {
+                self.push(",");
+            }
+            write_string(name.as_bytes(), &mut self.out);
+        }
+        self.push("]}");
+    }
+
+    fn fixed(&mut self, path: &str, size: usize) {
+        self.named_header("fixed", path);
+        self.push(&format!(",\"size\":{size}}}"));
+    }
+
+    /// Writes the Avro type of `ty`, returning the name of its kind when
+    /// it is unnamed; a union holds at most one branch of each such kind.
+    #[allow(clippy::too_many_lines)]
+    fn ty(&mut self, ty: &TypeDesc, path: &str) -> Result<Option<&'static str>> {
+        let ty = text_type(ty);
+        let kind = match ty.as_ref() {
+            TypeDesc::Nothing => {
+                self.push("\"null\"");
+                "null"
+            }
+            TypeDesc::Bool => {
+                self.push("\"boolean\"");
+                "boolean"
+            }
+            TypeDesc::Int8
+            | TypeDesc::Int16
+            | TypeDesc::Int32
+            | TypeDesc::UInt8
+            | TypeDesc::UInt16
+            | TypeDesc::UInt32
+            | TypeDesc::DateTime { .. }
+            | TypeDesc::Ipv4 => {
+                self.push("\"int\"");
+                "int"
+            }
+            TypeDesc::Date | TypeDesc::Date32 => {
+                self.push("{\"type\":\"int\",\"logicalType\":\"date\"}");
+                "int"
+            }
+            TypeDesc::Int64 | TypeDesc::UInt64 | TypeDesc::Interval(_) => {
+                self.push("\"long\"");
+                "long"
+            }
+            TypeDesc::DateTime64 { precision, .. } => {
+                match precision {
+                    3 => self.push("{\"type\":\"long\",\"logicalType\":\"timestamp-millis\"}"),
+                    6 => self.push("{\"type\":\"long\",\"logicalType\":\"timestamp-micros\"}"),
+                    9 => self.push("{\"type\":\"long\",\"logicalType\":\"timestamp-nanos\"}"),
+                    _ => self.push("\"long\""),
+                }
+                "long"
+            }
+            TypeDesc::Float32 | TypeDesc::Float16 | TypeDesc::BFloat16 => {
+                self.push("\"float\"");
+                "float"
+            }
+            TypeDesc::Float64 => {
+                self.push("\"double\"");
+                "double"
+            }
+            TypeDesc::String => {
+                self.push("\"bytes\"");
+                "bytes"
+            }
+            TypeDesc::Uuid => {
+                self.push("{\"type\":\"string\",\"logicalType\":\"uuid\"}");
+                "string"
+            }
+            TypeDesc::FixedString { length } => {
+                self.fixed(path, *length);
+                return Ok(None);
+            }
+            TypeDesc::Int128 | TypeDesc::UInt128 | TypeDesc::Ipv6 => {
+                self.fixed(path, 16);
+                return Ok(None);
+            }
+            TypeDesc::Int256 | TypeDesc::UInt256 => {
+                self.fixed(path, 32);
+                return Ok(None);
+            }
+            TypeDesc::Enum8(items) => {
+                self.enumeration(path, items.iter().map(|(name, _)| name.as_str()));
+                return Ok(None);
+            }
+            TypeDesc::Enum16(items) => {
+                self.enumeration(path, items.iter().map(|(name, _)| name.as_str()));
+                return Ok(None);
+            }
+            TypeDesc::Nullable(inner) => {
+                self.push("[\"null\",");
+                self.ty(inner, path)?;
+                self.push("]");
+                "union"
+            }
+            TypeDesc::Array(inner) => {
+                self.push("{\"type\":\"array\",\"items\":");
+                self.ty(inner, &format!("{path}_item"))?;
+                self.push("}");
+                "array"
+            }
+            TypeDesc::Nested(items) => {
+                self.push("{\"type\":\"array\",\"items\":");
+                self.tuple(items, &format!("{path}_item"))?;
+                self.push("}");
+                "array"
+            }
+            TypeDesc::Map { key, value } => {
+                let key = text_type(key);
+                if is_composite(&key)
+                    || matches!(
+                        key.as_ref(),
+                        TypeDesc::Nullable(_)
+                            | TypeDesc::Variant(_)
+                            | TypeDesc::Dynamic { .. }
+                            | TypeDesc::Nothing
+                            | TypeDesc::AggregateFunction { .. }
+                    )
+                {
+                    return Err(Error::UnsupportedType(ty.type_name()));
+                }
+                self.push("{\"type\":\"map\",\"values\":");
+                self.ty(value, &format!("{path}_value"))?;
+                self.push("}");
+                "map"
+            }
+            TypeDesc::Tuple(items) => {
+                self.tuple(items, path)?;
+                return Ok(None);
+            }
+            TypeDesc::Variant(types) => {
+                self.push("[\"null\"");
+                let mut kinds = HashSet::from(["null"]);
+                for (index, alternative) in types.iter().enumerate() {
+                    self.push(",");
+                    if let Some(kind) = self.ty(alternative, &format!("{path}_{index}"))?
+                        && !kinds.insert(kind)
+                    {
+                        return Err(Error::UnsupportedType(format!(
+                            "{}: Avro unions hold one {kind} branch at most",
+                            ty.type_name()
+                        )));
+                    }
+                }
+                self.push("]");
+                "union"
+            }
+            other => {
+                let Some((scale, size)) = decimal_parts(other) else {
+                    return Err(Error::UnsupportedType(other.type_name()));
+                };
+                let precision = match other {
+                    TypeDesc::Decimal { precision, .. } => *precision,
+                    _ => match size {
+                        DecimalSize::Bits32 => 9,
+                        DecimalSize::Bits64 => 18,
+                        DecimalSize::Bits128 => 38,
+                        DecimalSize::Bits256 => 76,
+                    },
+                };
+                self.push(&format!(
+                    "{{\"type\":\"bytes\",\"logicalType\":\"decimal\",\"precision\":{precision},\"scale\":{scale}}}"
+                ));
+                "bytes"
+            }
+        };
+        Ok(Some(kind))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn named_types_resolve_within_namespaces() {
+        let schema = parse_schema(
+            r#"{"type":"record","name":"row","namespace":"app","fields":[
+                {"name":"a","type":{"type":"fixed","name":"Hash","size":4}},
+                {"name":"b","type":["null","Hash","app.Hash"]},
+                {"name":"c","type":{"type":"long","logicalType":"timestamp-micros"}},
+                {"name":"d","type":{"type":"bytes","logicalType":"decimal","precision":9,"scale":2}},
+                {"name":"e","type":{"type":"int","logicalType":"time-millis"}}
+            ]}"#,
+        )
+        .unwrap();
+        let hash = AvroType::Fixed {
+            size: 4,
+            logical: None,
+        };
+        assert_eq!(
+            schema,
+            AvroType::Record(vec![
+                ("a".into(), hash.clone()),
+                (
+                    "b".into(),
+                    AvroType::Union(vec![AvroType::Null, hash.clone(), hash])
+                ),
+                ("c".into(), AvroType::Long(Some(Logical::TimestampMicros))),
+                (
+                    "d".into(),
+                    AvroType::Bytes(Some(Logical::Decimal { scale: 2 }))
+                ),
+                ("e".into(), AvroType::Int(None)),
+            ])
+        );
+
+        for text in [
+            r#"{"type":"record","name":"node","fields":[{"name":"next","type":["null","node"]}]}"#,
+            r#"{"type":"record","name":"row","fields":[{"name":"x","type":"Missing"}]}"#,
+        ] {
+            assert!(matches!(parse_schema(text), Err(Error::UnsupportedType(_))));
+        }
+        assert!(matches!(
+            parse_schema(r#"[["int"],"long"]"#),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn derived_names_are_valid_and_unique() {
+        let schema = Schema::from_type_strings(&[
+            ("x-1", "FixedString(2)"),
+            ("x_1", "Tuple(Int8, b Enum8('a' = 1))"),
+        ])
+        .unwrap();
+        let text = derive_schema(&schema).unwrap();
+        assert_eq!(
+            text,
+            concat!(
+                r#"{"type":"record","name":"row","fields":["#,
+                r#"{"name":"x-1","type":{"type":"fixed","name":"x_1","size":2}},"#,
+                r#"{"name":"x_1","type":{"type":"record","name":"x_1_2","fields":["#,
+                r#"{"name":"field1","type":"int"},"#,
+                r#"{"name":"b","type":{"type":"enum","name":"x_1_b","symbols":["a"]}}]}}]}"#,
+            )
+        );
+        assert!(parse_schema(&text).is_ok());
+
+        let schema = Schema::from_type_strings(&[("v", "Variant(Int8, UInt16)")]).unwrap();
+        assert!(matches!(
+            derive_schema(&schema),
+            Err(Error::UnsupportedType(_))
+        ));
+    }
+}
@@ -0,0 +1,228 @@
+//! Avro datum writer.
+
+use std::io::Write;
+
+use num_bigint::BigInt;
+
+use crate::{
+    error::{Error, Result},
+    io::write_uvarint,
+    jsoneachrow::json::map_key_text,
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::Schema,
+    text::{decimal_parts, text_type},
+    types::{DecimalSize, TupleItem, TypeDesc},
+    value::Value,
+};
+
+use super::schema::derive_schema;
+
+/// Writer that encodes rows as Avro binary datums of a derived schema.
+pub struct AvroWriter<W: Write> {
+    output: RowOutput<W>,
+    schema: Schema,
+    avro_schema: String,
+}
+
+impl<W: Write> AvroWriter<W> {
+    /// Creates a writer for rows of `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsupportedType`] when a column has no Avro
+    /// counterpart.
+    pub fn new(inner: W, schema: Schema) -> Result<Self> {
+        Ok(Self {
+            output: RowOutput::new(inner),
+            avro_schema: derive_schema(&schema)?,
+            schema,
+        })
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the JSON text of the Avro schema datums are written with,
+    /// as registered with a schema registry or passed to
+    /// [`AvroReader::new`](super::AvroReader::new).
+    #[must_use]
+    pub fn avro_schema(&self) -> &str {
+        &self.avro_schema
+    }
+
+    /// Encodes and writes one row as an Avro datum.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            for (field, value) in self.schema.fields().iter().zip(row) {
+                write_value(&field.ty, value, out)?;
+            }
+            Ok(())
+        })
+    }
+}
+
+impl_row_writer!(AvroWriter);
+
+fn mismatch(ty: &TypeDesc, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+/// Appends `value` as a zigzag-encoded variable-length integer.
+fn write_long(value: i64, out: &mut Vec<u8>) -> Result<()> {
+    write_uvarint(((value << 1) ^ (value >> 63)).cast_unsigned(), out)
+}
+
+fn write_len(len: usize, out: &mut Vec<u8>) -> Result<()> {
+    let len =
+        i64::try_from(len).map_err(|_| Error::Overflow("length does not fit in an Avro long"))?;
+    write_long(len, out)
+}
+
+fn write_bytes(bytes: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    write_len(bytes.len(), out)?;
+    out.extend_from_slice(bytes);
+    Ok(())
+}
+
+fn write_enum<T: PartialEq>(items: &[(String, T)], value: &T, out: &mut Vec<u8>) -> Result<()> {
+    let index = items
+        .iter()
+        .position(|(_, item)| item == value)
+        .ok_or(Error::InvalidValue("enum value has no name"))?;
+    write_len(index, out)
+}
+
+fn write_tuple(items: &[TupleItem], values: &[Value], out: &mut Vec<u8>) -> Result<()> {
+    if items.len() != values.len() {
+        return Err(Error::InvalidValue("tuple length does not match its type"));
+    }
+    for (item, value) in items.iter().zip(values) {
+        write_value(&item.ty, value, out)?;
+    }
+    Ok(())
+}
+
+/// Writes an Avro array or map block holding every item, followed by the
+/// terminating empty block.
+fn write_block<T>(
+    items: &[T],
+    out: &mut Vec<u8>,
+    mut write_item: impl FnMut(&T, &mut Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    if !items.is_empty() {
+        write_len(items.len(), out)?;
+        for item in items {
+            write_item(item, out)?;
+        }
+    }
+    write_long(0, out)
+}
+
+fn write_value(ty: &TypeDesc, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), value) {
+        (TypeDesc::Nothing, Value::Nothing) => {}
+        (TypeDesc::Bool, Value::Bool(value)) => out.push(u8::from(*value)),
+        (TypeDesc::UInt8, Value::UInt8(value)) => write_long(i64::from(*value), out)?,
+        (TypeDesc::UInt16, Value::UInt16(value)) => write_long(i64::from(*value), out)?,
+        // Unsigned 32- and 64-bit values keep their bits in the signed
+        // Avro type, as the server writes them.
+        (TypeDesc::UInt32, Value::UInt32(value))
+        | (TypeDesc::DateTime { .. }, Value::DateTime(value)) => {
+            write_long(i64::from(value.cast_signed()), out)?;
+        }
+        (TypeDesc::Ipv4, Value::Ipv4(address)) => {
+            write_long(i64::from(u32::from(*address).cast_signed()), out)?;
+        }
+        (TypeDesc::UInt64, Value::UInt64(value)) => write_long(value.cast_signed(), out)?,
+        (TypeDesc::Int8, Value::Int8(value)) => write_long(i64::from(*value), out)?,
+        (TypeDesc::Int16, Value::Int16(value)) => write_long(i64::from(*value), out)?,
+        (TypeDesc::Int32, Value::Int32(value)) => write_long(i64::from(*value), out)?,
+        (TypeDesc::Int64 | TypeDesc::Interval(_), Value::Int64(value))
+        | (TypeDesc::DateTime64 { .. }, Value::DateTime64(value)) => write_long(*value, out)?,
+        (TypeDesc::Date, Value::Date(days)) => write_long(i64::from(*days), out)?,
+        (TypeDesc::Date32, Value::Date32(days)) => write_long(i64::from(*days), out)?,
+        (TypeDesc::Int128, Value::Int128(value)) => out.extend_from_slice(&value.to_le_bytes()),
+        (TypeDesc::UInt128, Value::UInt128(value)) => out.extend_from_slice(&value.to_le_bytes()),
+        (TypeDesc::Int256, Value::Int256(bytes)) | (TypeDesc::UInt256, Value::UInt256(bytes)) => {
+            out.extend_from_slice(bytes);
+        }
+        (TypeDesc::Float32, Value::Float32(value))
+        | (TypeDesc::Float16, Value::Float16(value))
+        | (TypeDesc::BFloat16, Value::BFloat16(value)) => {
+            out.extend_from_slice(&value.to_le_bytes());
+        }
+        (TypeDesc::Float64, Value::Float64(value)) => out.extend_from_slice(&value.to_le_bytes()),
+        (TypeDesc::String, Value::String(bytes)) => write_bytes(bytes, out)?,
+        (TypeDesc::FixedString { length }, Value::FixedString(bytes)) => {
+            if bytes.len() != *length {
+                return Err(Error::InvalidValue(
+                    "FixedString value length does not match its type",
+                ));
+            }
+            out.extend_from_slice(bytes);
+        }
+        (TypeDesc::Uuid, Value::Uuid(uuid)) => write_bytes(uuid.to_string().as_bytes(), out)?,
+        (TypeDesc::Ipv6, Value::Ipv6(address)) => out.extend_from_slice(&address.octets()),
+        (TypeDesc::Enum8(items), Value::Enum8(value)) => write_enum(items, value, out)?,
+        (TypeDesc::Enum16(items), Value::Enum16(value)) => write_enum(items, value, out)?,
+        (TypeDesc::Nullable(_), Value::Nullable(None))
+        | (TypeDesc::Variant(_), Value::VariantNull) => {
+            write_long(0, out)?;
+        }
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            write_long(1, out)?;
+            write_value(inner, value, out)?;
+        }
+        (TypeDesc::Variant(types), Value::Variant { index, value }) => {
+            let ty = types
+                .get(usize::from(*index))
+                .ok_or(Error::InvalidValue("variant index out of range"))?;
+            write_long(i64::from(*index) + 1, out)?;
+            write_value(ty, value, out)?;
+        }
+        (TypeDesc::Array(inner), Value::Array(items)) => {
+            write_block(items, out, |item, out| write_value(inner, item, out))?;
+        }
+        (TypeDesc::Nested(items), Value::Array(rows)) => {
+            write_block(rows, out, |row, out| match row {
+                Value::Tuple(values) => write_tuple(items, values, out),
+                row => Err(mismatch(&TypeDesc::Tuple(items.clone()), row)),
+            })?;
+        }
+        (TypeDesc::Map { key, value: inner }, Value::Map(entries)) => {
+            write_block(entries, out, |(key_value, value), out| {
+                write_bytes(&map_key_text(key, key_value)?, out)?;
+                write_value(inner, value, out)
+            })?;
+        }
+        (TypeDesc::Tuple(items), Value::Tuple(values)) => write_tuple(items, values, out)?,
+        (ty, value) => {
+            let mantissa = match (decimal_parts(ty), value) {
+                (Some((_, DecimalSize::Bits32)), Value::Decimal32(value)) => BigInt::from(*value),
+                (Some((_, DecimalSize::Bits64)), Value::Decimal64(value)) => BigInt::from(*value),
+                (Some((_, DecimalSize::Bits128)), Value::Decimal128(value)) => BigInt::from(*value),
+                (Some((_, DecimalSize::Bits256)), Value::Decimal256(bytes)) => {
+                    BigInt::from_signed_bytes_le(bytes)
+                }
+                _ => return Err(mismatch(ty, value)),
+            };
+            write_bytes(&mantissa.to_signed_bytes_be(), out)?;
+        }
+    }
+    Ok(())
+}
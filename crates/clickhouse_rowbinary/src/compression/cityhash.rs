@@ -0,0 +1,365 @@
+//! `CityHash128` as of `CityHash` v1.0.2, which the server pins for
+//! compressed block checksums.
+//!
+//! Later `CityHash` releases changed the algorithm, so general-purpose
+//! implementations produce different, incompatible checksums. Variable
+//! names follow the reference implementation.
+
+#![allow(clippy::many_single_char_names)]
+
+const K0: u64 = 0xc3a5_c85c_97cb_3127;
+const K1: u64 = 0xb492_b66f_be98_f273;
+const K2: u64 = 0x9ae1_6a3b_2f90_404f;
+const K3: u64 = 0xc949_d7c7_509e_6557;
+
+/// A 128-bit hash as its `(low, high)` halves.
+type U128 = (u64, u64);
+
+fn fetch64(data: &[u8], at: usize) -> u64 {
+    let mut bytes = [0_u8; 8];
+    bytes.copy_from_slice(&data[at..at + 8]);
+    u64::from_le_bytes(bytes)
+}
+
+fn fetch32(data: &[u8], at: usize) -> u64 {
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(&data[at..at + 4]);
+    u64::from(u32::from_le_bytes(bytes))
+}
+
+fn shift_mix(value: u64) -> u64 {
+    value ^ (value >> 47)
+}
+
+fn hash_len16(low: u64, high: u64) -> u64 {
+    const MUL: u64 = 0x9ddf_ea08_eb38_2d69;
+    let mut a = (low ^ high).wrapping_mul(MUL);
+    a ^= a >> 47;
+    let mut b = (high ^ a).wrapping_mul(MUL);
+    b ^= b >> 47;
+    b.wrapping_mul(MUL)
+}
+
+/// Rotation by the length of a 9 to 16 byte input.
+#[allow(clippy::cast_possible_truncation)]
+fn rotate_by_len(value: u64, len: usize) -> u64 {
+    value.rotate_right(len as u32)
+}
+
+fn hash_len_0_to_16(data: &[u8]) -> u64 {
+    let len = data.len();
+    if len > 8 {
+        let a = fetch64(data, 0);
+        let b = fetch64(data, len - 8);
+        return hash_len16(a, rotate_by_len(b.wrapping_add(len as u64), len)) ^ b;
+    }
+    if len >= 4 {
+        let a = fetch32(data, 0);
+        return hash_len16((len as u64).wrapping_add(a << 3), fetch32(data, len - 4));
+    }
+    if len > 0 {
+        let a = u32::from(data[0]);
+        let b = u32::from(data[len >> 1]);
+        let c = u32::from(data[len - 1]);
+        let y = a + (b << 8);
+        #[allow(clippy::cast_possible_truncation)]
+        let z = len as u32 + (c << 2);
+        return shift_mix(u64::from(y).wrapping_mul(K2) ^ u64::from(z).wrapping_mul(K3))
+            .wrapping_mul(K2);
+    }
+    K2
+}
+
+/// Hash of inputs shorter than 128 bytes.
+fn city_murmur(data: &[u8], seed: U128) -> U128 {
+    let len = data.len();
+    let (mut a, mut b) = seed;
+    let mut c;
+    let mut d;
+    if len <= 16 {
+        a = shift_mix(a.wrapping_mul(K1)).wrapping_mul(K1);
+        c = b.wrapping_mul(K1).wrapping_add(hash_len_0_to_16(data));
+        d = shift_mix(a.wrapping_add(if len >= 8 { fetch64(data, 0) } else { c }));
+    } else {
+        c = hash_len16(fetch64(data, len - 8).wrapping_add(K1), a);
+        d = hash_len16(
+            b.wrapping_add(len as u64),
+            c.wrapping_add(fetch64(data, len - 16)),
+        );
+        a = a.wrapping_add(d);
+        let mut at = 0;
+        while at + 16 < len {
+            a ^= shift_mix(fetch64(data, at).wrapping_mul(K1)).wrapping_mul(K1);
+            a = a.wrapping_mul(K1);
+            b ^= a;
+            c ^= shift_mix(fetch64(data, at + 8).wrapping_mul(K1)).wrapping_mul(K1);
+            c = c.wrapping_mul(K1);
+            d ^= c;
+            at += 16;
+        }
+    }
+    let a = hash_len16(a, c);
+    let b = hash_len16(d, b);
+    (a ^ b, hash_len16(b, a))
+}
+
+fn weak_hash_len32_with_seeds(data: &[u8], at: usize, mut a: u64, mut b: u64) -> U128 {
+    let w = fetch64(data, at);
+    let x = fetch64(data, at + 8);
+    let y = fetch64(data, at + 16);
+    let z = fetch64(data, at + 24);
+    a = a.wrapping_add(w);
+    b = b.wrapping_add(a).wrapping_add(z).rotate_right(21);
+    let c = a;
+    a = a.wrapping_add(x).wrapping_add(y);
+    b = b.wrapping_add(a.rotate_right(44));
+    (a.wrapping_add(z), b.wrapping_add(c))
+}
+
+fn city_hash128_with_seed(data: &[u8], seed: U128) -> U128 {
+    if data.len() < 128 {
+        return city_murmur(data, seed);
+    }
+    let (mut x, mut y) = seed;
+    let mut len = data.len();
+    let mut z = (len as u64).wrapping_mul(K1);
+    let mut v = (0, 0);
+    v.0 = (y ^ K1)
+        .rotate_right(49)
+        .wrapping_mul(K1)
+        .wrapping_add(fetch64(data, 0));
+    v.1 =
+        v.0.rotate_right(42)
+            .wrapping_mul(K1)
+            .wrapping_add(fetch64(data, 8));
+    let mut w = (
+        y.wrapping_add(z)
+            .rotate_right(35)
+            .wrapping_mul(K1)
+            .wrapping_add(x),
+        x.wrapping_add(fetch64(data, 88))
+            .rotate_right(53)
+            .wrapping_mul(K1),
+    );
+    let mut at = 0;
+    while len >= 128 {
+        for _ in 0..2 {
+            x = x
+                .wrapping_add(y)
+                .wrapping_add(v.0)
+                .wrapping_add(fetch64(data, at + 16))
+                .rotate_right(37)
+                .wrapping_mul(K1);
+            y = y
+                .wrapping_add(v.1)
+                .wrapping_add(fetch64(data, at + 48))
+                .rotate_right(42)
+                .wrapping_mul(K1);
+            x ^= w.1;
+            y ^= v.0;
+            z = (z ^ w.0).rotate_right(33);
+            v = weak_hash_len32_with_seeds(data, at, v.1.wrapping_mul(K1), x.wrapping_add(w.0));
+            w = weak_hash_len32_with_seeds(data, at + 32, z.wrapping_add(w.1), y);
+            std::mem::swap(&mut z, &mut x);
+            at += 64;
+        }
+        len -= 128;
+    }
+    y = y.wrapping_add(w.0.rotate_right(37).wrapping_mul(K0).wrapping_add(z));
+    x = x.wrapping_add(v.0.wrapping_add(z).rotate_right(49).wrapping_mul(K0));
+    // Hash up to four 32-byte chunks from the end of the input.
+    let mut tail_done = 0;
+    while tail_done < len {
+        tail_done += 32;
+        let chunk = at + len - tail_done;
+        y = y
+            .wrapping_sub(x)
+            .rotate_right(42)
+            .wrapping_mul(K0)
+            .wrapping_add(v.1);
+        w.0 = w.0.wrapping_add(fetch64(data, chunk + 16));
+        x = x.rotate_right(49).wrapping_mul(K0).wrapping_add(w.0);
+        w.0 = w.0.wrapping_add(v.0);
+        v = weak_hash_len32_with_seeds(data, chunk, v.0, v.1);
+    }
+    x = hash_len16(x, v.0);
+    y = hash_len16(y, w.0);
+    (
+        hash_len16(x.wrapping_add(v.1), w.1).wrapping_add(y),
+        hash_len16(x.wrapping_add(w.1), y.wrapping_add(v.1)),
+    )
+}
+
+/// Returns the `(low, high)` halves of the `CityHash128` of `data`.
+pub(crate) fn city_hash128(data: &[u8]) -> U128 {
+    let len = data.len();
+    if len >= 16 {
+        city_hash128_with_seed(&data[16..], (fetch64(data, 0) ^ K3, fetch64(data, 8)))
+    } else if len >= 8 {
+        city_hash128_with_seed(
+            &[],
+            (
+                fetch64(data, 0) ^ (len as u64).wrapping_mul(K0),
+                fetch64(data, len - 8) ^ K1,
+            ),
+        )
+    } else {
+        city_hash128_with_seed(data, (K0, K1))
+    }
+}
+
+/// Bytes hashed at a time by [`ChunkedHasher`].
+#[cfg(any(feature = "http", feature = "async-http"))]
+const HASH_CHUNK: usize = 64 * 1024;
+
+/// Hashes a stream in fixed-size chunks, each seeded with the hash of the
+/// chunks before it, so the result depends on the bytes alone and not on
+/// how the writes split them.
+#[cfg(any(feature = "http", feature = "async-http"))]
+#[derive(Default)]
+pub(crate) struct ChunkedHasher {
+    chunk: Vec<u8>,
+    state: Option<U128>,
+}
+
+#[cfg(any(feature = "http", feature = "async-http"))]
+impl ChunkedHasher {
+    fn hash_chunk(&mut self) {
+        self.state = Some(match self.state {
+            None => city_hash128(&self.chunk),
+            Some(seed) => city_hash128_with_seed(&self.chunk, seed),
+        });
+        self.chunk.clear();
+    }
+
+    /// Returns the `(low, high)` halves of the hash of everything written;
+    /// a stream shorter than a chunk hashes as [`city_hash128`] does.
+    pub(crate) fn finish(mut self) -> U128 {
+        if !self.chunk.is_empty() || self.state.is_none() {
+            self.hash_chunk();
+        }
+        self.state.unwrap_or_default()
+    }
+}
+
+#[cfg(any(feature = "http", feature = "async-http"))]
+impl std::io::Write for ChunkedHasher {
+    fn write(&mut self, mut buf: &[u8]) -> std::io::Result<usize> {
+        let len = buf.len();
+        while !buf.is_empty() {
+            let take = (HASH_CHUNK - self.chunk.len()).min(buf.len());
+            self.chunk.extend_from_slice(&buf[..take]);
+            buf = &buf[take..];
+            if self.chunk.len() == HASH_CHUNK {
+                self.hash_chunk();
+            }
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use super::{K0, city_hash128};
+
+    /// Returns the input of the reference `city-test.cc` of `CityHash`
+    /// v1.0.2, which hashes `data[i * i..i * i + i]` for each length `i`.
+    fn reference_data(len: usize) -> Vec<u8> {
+        let (mut a, mut b) = (9_u64, 777_u64);
+        (0..len as u64)
+            .map(|i| {
+                a = (a ^ (a >> 41)).wrapping_mul(K0).wrapping_add(b);
+                b = (b ^ (b >> 41)).wrapping_mul(K0).wrapping_add(i);
+                (b >> 37).to_le_bytes()[0]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn matches_the_reference_test_vectors() {
+        // `(length, low, high)` of the `CityHash128` results expected by
+        // the reference `city-test.cc` of `CityHash` v1.0.2.
+        let expected: [(usize, u64, u64); 18] = [
+            (0, 0x3df0_9dfc_64c0_9a2b, 0x3cb5_40c3_92e5_1e29),
+            (1, 0x1290_f0e8_a5ca_a74d, 0xca4c_6bf7_583f_5cda),
+            (4, 0x0798_637e_677c_65a3, 0x83e3_b06a_dc4c_d3ff),
+            (8, 0x7635_29c8_d418_9ea8, 0x860d_77e7_fef7_4ca3),
+            (9, 0x313d_49cb_51b8_cd2c, 0x6e98_2d8b_4658_654a),
+            (15, 0x1c66_ceea_4734_13df, 0xdc3f_70a1_24b2_5a40),
+            (16, 0x8505_c996_b70e_e9fc, 0xb92b_ba6b_5d77_8eb7),
+            (17, 0x1660_a2c4_972d_0fa1, 0x01a1_538d_6b50_a57c),
+            (31, 0x4411_33d2_2148_6a3d, 0x0fb9_c5a4_0e19_515b),
+            (32, 0x15bb_4638_3dae_c2a5, 0x7162_9406_3b4b_a089),
+            (64, 0x4caf_4dee_da66_a6ee, 0x2647_20f6_f35f_7840),
+            (100, 0x2a55_6456_4091_1e27, 0x4fac_2eef_bd36_e26f),
+            (127, 0x664e_c3fa_d852_1859, 0x406f_082b_eb9c_a29a),
+            (128, 0x5414_e385_f567_7a6d, 0x41ef_105f_8a68_2a28),
+            (129, 0xd4bd_358f_ed3e_6aa5, 0x8a1b_a396_3561_97d9),
+            (200, 0xac61_387d_34b8_eba3, 0xf658_ecef_f68e_4f98),
+            (255, 0x3ad5_fe46_fea9_6c61, 0x5b78_b66f_ef8d_ea6f),
+            (298, 0x19fa_4878_1ce2_b326, 0xf34f_c200_e9ca_457c),
+        ];
+        let data = reference_data(300 * 300);
+        for (len, low, high) in expected {
+            let input = &data[len * len..len * len + len];
+            assert_eq!(city_hash128(input), (low, high), "length {len}");
+        }
+    }
+
+    #[test]
+    fn every_length_class_hashes_distinctly() {
+        let data: Vec<u8> = (0..600_u32)
+            .map(|i| u8::try_from(i * 31 % 251).unwrap())
+            .collect();
+        let mut seen = HashSet::new();
+        for len in 0..data.len() {
+            let hash = city_hash128(&data[..len]);
+            assert_eq!(hash, city_hash128(&data[..len]));
+            assert!(seen.insert(hash), "collision at length {len}");
+        }
+    }
+
+    #[test]
+    fn single_byte_changes_alter_the_hash() {
+        let data = vec![7_u8; 300];
+        let base = city_hash128(&data);
+        for at in [0, 15, 16, 100, 200, 299] {
+            let mut changed = data.clone();
+            changed[at] ^= 1;
+            assert_ne!(city_hash128(&changed), base, "byte {at}");
+        }
+    }
+
+    #[cfg(any(feature = "http", feature = "async-http"))]
+    #[test]
+    fn chunked_hashes_ignore_how_writes_split() {
+        use std::io::Write;
+
+        use super::{ChunkedHasher, HASH_CHUNK};
+
+        let data: Vec<u8> = (0..3 * HASH_CHUNK + 100)
+            .map(|i| u8::try_from(i % 253).unwrap())
+            .collect();
+        let mut whole = ChunkedHasher::default();
+        whole.write_all(&data).unwrap();
+        let whole = whole.finish();
+        let mut pieces = ChunkedHasher::default();
+        for piece in data.chunks(1000) {
+            pieces.write_all(piece).unwrap();
+        }
+        assert_eq!(pieces.finish(), whole);
+
+        let mut short = ChunkedHasher::default();
+        short.write_all(&data[..100]).unwrap();
+        assert_eq!(short.finish(), city_hash128(&data[..100]));
+        let mut changed = ChunkedHasher::default();
+        changed.write_all(&data[..data.len() - 1]).unwrap();
+        assert_ne!(changed.finish(), whole);
+    }
+}
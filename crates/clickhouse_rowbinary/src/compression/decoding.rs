@@ -0,0 +1,128 @@
+//! Content-encoding detection for downloaded payloads.
+
+use std::{
+    fmt,
+    io::{self, BufRead, BufReader, Chain, Cursor, Read},
+};
+
+use flate2::read::MultiGzDecoder;
+
+use crate::error::Result;
+
+use super::frame::Lz4FrameReader;
+
+/// Compression detected at the start of a stream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentEncoding {
+    /// No recognized compression; bytes pass through unchanged.
+    Identity,
+    /// Gzip members, as sent for `Accept-Encoding: gzip`.
+    Gzip,
+    /// ZSTD frames, as sent for `Accept-Encoding: zstd`.
+    Zstd,
+    /// LZ4 frames, as sent for `Accept-Encoding: lz4`.
+    Lz4,
+}
+
+impl ContentEncoding {
+    /// Detects the encoding from the first bytes of a stream.
+    fn detect(prefix: &[u8]) -> Self {
+        match prefix {
+            [0x1f, 0x8b, ..] => Self::Gzip,
+            [0x28, 0xb5, 0x2f, 0xfd, ..] => Self::Zstd,
+            [0x04, 0x22, 0x4d, 0x18, ..] => Self::Lz4,
+            _ => Self::Identity,
+        }
+    }
+}
+
+impl fmt::Display for ContentEncoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Identity => "identity",
+            Self::Gzip => "gzip",
+            Self::Zstd => "zstd",
+            Self::Lz4 => "lz4",
+        })
+    }
+}
+
+/// The stream with its already-inspected prefix put back in front.
+type Source<R> = Chain<Cursor<Vec<u8>>, R>;
+
+enum Decoder<R: Read> {
+    Identity(Source<R>),
+    Gzip(MultiGzDecoder<Source<R>>),
+    Zstd(zstd::stream::read::Decoder<'static, BufReader<Source<R>>>),
+    Lz4(Lz4FrameReader<Source<R>>),
+}
+
+impl<R: Read> Read for Decoder<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Identity(inner) => inner.read(buf),
+            Self::Gzip(inner) => inner.read(buf),
+            Self::Zstd(inner) => inner.read(buf),
+            Self::Lz4(inner) => inner.read(buf),
+        }
+    }
+}
+
+/// Reader that detects gzip, ZSTD or LZ4 framing from the magic bytes at
+/// the start of a stream and decompresses it transparently.
+///
+/// Streams in any other encoding pass through unchanged, so the reader can
+/// wrap every HTTP response body whether or not the server compressed it.
+/// The server's own compressed blocks (`compress=1`) carry no magic bytes
+/// and are read with [`CompressedReader`](super::CompressedReader) instead.
+pub struct DecompressingReader<R: Read> {
+    inner: BufReader<Decoder<R>>,
+    encoding: ContentEncoding,
+}
+
+impl<R: Read> DecompressingReader<R> {
+    /// Inspects the first bytes of `inner` and sets up the matching
+    /// decompressor.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] if reading the first bytes fails.
+    pub fn new(mut inner: R) -> Result<Self> {
+        let mut prefix = Vec::with_capacity(4);
+        (&mut inner).take(4).read_to_end(&mut prefix)?;
+        let encoding = ContentEncoding::detect(&prefix);
+        let source = Cursor::new(prefix).chain(inner);
+        let decoder = match encoding {
+            ContentEncoding::Identity => Decoder::Identity(source),
+            ContentEncoding::Gzip => Decoder::Gzip(MultiGzDecoder::new(source)),
+            ContentEncoding::Zstd => Decoder::Zstd(zstd::stream::read::Decoder::new(source)?),
+            ContentEncoding::Lz4 => Decoder::Lz4(Lz4FrameReader::new(source)),
+        };
+        Ok(Self {
+            inner: BufReader::new(decoder),
+            encoding,
+        })
+    }
+
+    /// Returns the encoding detected at the start of the stream.
+    #[must_use]
+    pub fn encoding(&self) -> ContentEncoding {
+        self.encoding
+    }
+}
+
+impl<R: Read> Read for DecompressingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl<R: Read> BufRead for DecompressingReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt);
+    }
+}
@@ -0,0 +1,253 @@
+//! LZ4 frame format reader, as sent for `Content-Encoding: lz4`.
+
+use std::io::{self, Read};
+
+use crate::error::{Error, Result, into_io_error};
+
+use super::{
+    lz4, read_exact_or_eof,
+    xxhash::{Xxh32, xxh32},
+};
+
+const MAGIC: u32 = 0x184d_2204;
+/// Skippable frames use the magic numbers `0x184d2a50..=0x184d2a5f`.
+const SKIPPABLE_MAGIC: u32 = 0x184d_2a50;
+/// Linked blocks may refer back this far into previous blocks.
+const WINDOW_SIZE: usize = 64 * 1024;
+
+/// Flags from the descriptor of the frame being read.
+struct FrameInfo {
+    linked: bool,
+    block_checksums: bool,
+    content_checksum: Option<Xxh32>,
+    max_block_size: usize,
+}
+
+/// Reader that decompresses a stream of concatenated LZ4 frames.
+pub(crate) struct Lz4FrameReader<R: Read> {
+    inner: R,
+    frame: Option<FrameInfo>,
+    compressed: Vec<u8>,
+    /// History kept for linked blocks, followed by the current block.
+    window: Vec<u8>,
+    pos: usize,
+}
+
+fn corrupt() -> Error {
+    Error::InvalidValue("corrupt LZ4 frame")
+}
+
+fn read_u32<R: Read>(inner: &mut R) -> Result<u32> {
+    let mut bytes = [0_u8; 4];
+    inner.read_exact(&mut bytes)?;
+    Ok(u32::from_le_bytes(bytes))
+}
+
+impl<R: Read> Lz4FrameReader<R> {
+    pub(crate) fn new(inner: R) -> Self {
+        Self {
+            inner,
+            frame: None,
+            compressed: Vec::new(),
+            window: Vec::new(),
+            pos: 0,
+        }
+    }
+
+    /// Reads the next frame descriptor, skipping skippable frames, and
+    /// returns `false` at the end of the stream.
+    fn read_frame_header(&mut self) -> Result<bool> {
+        loop {
+            let mut magic = [0_u8; 4];
+            if !read_exact_or_eof(&mut self.inner, &mut magic)? {
+                return Ok(false);
+            }
+            let magic = u32::from_le_bytes(magic);
+            if magic & 0xffff_fff0 == SKIPPABLE_MAGIC {
+                let len = u64::from(read_u32(&mut self.inner)?);
+                let skipped = io::copy(&mut (&mut self.inner).take(len), &mut io::sink())?;
+                if skipped != len {
+                    return Err(Error::Io(io::ErrorKind::UnexpectedEof.into()));
+                }
+                continue;
+            }
+            if magic != MAGIC {
+                return Err(Error::InvalidValue("not an LZ4 frame"));
+            }
+            break;
+        }
+        let mut descriptor = vec![0_u8; 2];
+        self.inner.read_exact(&mut descriptor)?;
+        let (flags, block_descriptor) = (descriptor[0], descriptor[1]);
+        if flags >> 6 != 1 || flags & 0b10 != 0 || block_descriptor & 0b1000_1111 != 0 {
+            return Err(Error::InvalidValue("unsupported LZ4 frame version"));
+        }
+        if flags & 1 != 0 {
+            return Err(Error::InvalidValue(
+                "LZ4 frames with dictionaries are not supported",
+            ));
+        }
+        let max_block_size = match block_descriptor >> 4 {
+            4 => 64 * 1024,
+            5 => 256 * 1024,
+            6 => 1024 * 1024,
+            7 => 4 * 1024 * 1024,
+            _ => return Err(corrupt()),
+        };
+        if flags & 0b1000 != 0 {
+            // The content size is informational; blocks carry the data.
+            let mut content_size = [0_u8; 8];
+            self.inner.read_exact(&mut content_size)?;
+            descriptor.extend_from_slice(&content_size);
+        }
+        let mut header_checksum = [0_u8; 1];
+        self.inner.read_exact(&mut header_checksum)?;
+        if header_checksum[0] != xxh32(&descriptor).to_le_bytes()[1] {
+            return Err(Error::InvalidValue("LZ4 frame header checksum mismatch"));
+        }
+        self.frame = Some(FrameInfo {
+            linked: flags & 0b10_0000 == 0,
+            block_checksums: flags & 0b1_0000 != 0,
+            content_checksum: (flags & 0b100 != 0).then(Xxh32::new),
+            max_block_size,
+        });
+        self.window.clear();
+        self.pos = 0;
+        Ok(true)
+    }
+
+    /// Decodes the next non-empty block, returning `false` at the end of
+    /// the stream.
+    fn read_block(&mut self) -> Result<bool> {
+        loop {
+            if self.frame.is_none() && !self.read_frame_header()? {
+                return Ok(false);
+            }
+            let block_size = read_u32(&mut self.inner)?;
+            let frame = self
+                .frame
+                .as_mut()
+                .ok_or(Error::Internal("missing LZ4 frame"))?;
+            if block_size == 0 {
+                if let Some(content) = &frame.content_checksum
+                    && read_u32(&mut self.inner)? != content.digest()
+                {
+                    return Err(Error::InvalidValue("LZ4 frame content checksum mismatch"));
+                }
+                self.frame = None;
+                continue;
+            }
+            let stored = block_size & 0x8000_0000 != 0;
+            let len = (block_size & 0x7fff_ffff) as usize;
+            if len > frame.max_block_size {
+                return Err(corrupt());
+            }
+            self.compressed.resize(len, 0);
+            self.inner.read_exact(&mut self.compressed)?;
+            if frame.block_checksums && read_u32(&mut self.inner)? != xxh32(&self.compressed) {
+                return Err(Error::InvalidValue("LZ4 frame block checksum mismatch"));
+            }
+            if frame.linked {
+                let keep = self.window.len().min(WINDOW_SIZE);
+                self.window.drain(..self.window.len() - keep);
+            } else {
+                self.window.clear();
+            }
+            let start = self.window.len();
+            if stored {
+                self.window.extend_from_slice(&self.compressed);
+            } else {
+                lz4::decompress_linked(
+                    &self.compressed,
+                    0,
+                    frame.max_block_size,
+                    &mut self.window,
+                )?;
+            }
+            if let Some(content) = &mut frame.content_checksum {
+                content.update(&self.window[start..]);
+            }
+            self.pos = start;
+            if self.pos < self.window.len() {
+                return Ok(true);
+            }
+        }
+    }
+}
+
+impl<R: Read> Read for Lz4FrameReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.pos == self.window.len() && !self.read_block().map_err(into_io_error)? {
+            return Ok(0);
+        }
+        let len = (self.window.len() - self.pos).min(buf.len());
+        buf[..len].copy_from_slice(&self.window[self.pos..self.pos + len]);
+        self.pos += len;
+        Ok(len)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::Lz4FrameReader;
+    use crate::compression::{lz4, xxhash::xxh32};
+
+    /// Builds a frame from `blocks`, each stored or compressed.
+    fn frame(blocks: &[(&[u8], bool)], flags: u8, content: &[u8]) -> Vec<u8> {
+        let mut out = 0x184d_2204_u32.to_le_bytes().to_vec();
+        let descriptor = [flags, 0x40];
+        out.extend_from_slice(&descriptor);
+        out.push(xxh32(&descriptor).to_le_bytes()[1]);
+        for (data, compress) in blocks {
+            let mut block = Vec::new();
+            if *compress {
+                lz4::compress(data, &mut block);
+                out.extend_from_slice(&u32::try_from(block.len()).unwrap().to_le_bytes());
+            } else {
+                block.extend_from_slice(data);
+                out.extend_from_slice(
+                    &(u32::try_from(block.len()).unwrap() | 1 << 31).to_le_bytes(),
+                );
+            }
+            out.extend_from_slice(&block);
+        }
+        out.extend_from_slice(&[0; 4]);
+        if flags & 0b100 != 0 {
+            out.extend_from_slice(&xxh32(content).to_le_bytes());
+        }
+        out
+    }
+
+    fn decode(payload: &[u8]) -> std::io::Result<Vec<u8>> {
+        let mut out = Vec::new();
+        Lz4FrameReader::new(payload).read_to_end(&mut out)?;
+        Ok(out)
+    }
+
+    #[test]
+    fn decodes_stored_and_compressed_blocks() {
+        let text = b"lz4 frame payload ".repeat(50);
+        let payload = frame(
+            &[(&text, true), (b"tail", false)],
+            0x64,
+            &[text.as_slice(), b"tail"].concat(),
+        );
+        assert_eq!(
+            decode(&payload).unwrap(),
+            [text.as_slice(), b"tail"].concat()
+        );
+    }
+
+    #[test]
+    fn rejects_bad_checksums() {
+        let mut payload = frame(&[(b"data", false)], 0x64, b"data");
+        let last = payload.len() - 1;
+        payload[last] ^= 1;
+        assert!(decode(&payload).is_err());
+        let mut payload = frame(&[(b"data", false)], 0x60, b"");
+        payload[6] ^= 1;
+        assert!(decode(&payload).is_err());
+    }
+}
@@ -0,0 +1,209 @@
+//! LZ4 block format codec.
+//!
+//! Blocks carry no frame header; the server stores the uncompressed size
+//! in the compressed block header instead.
+
+use crate::error::{Error, Result};
+
+const MIN_MATCH: usize = 4;
+/// The last match must start at least this many bytes before the end.
+const MF_LIMIT: usize = 12;
+/// The last bytes of a block are always literals.
+const LAST_LITERALS: usize = 5;
+const MAX_OFFSET: usize = 65_535;
+const HASH_LOG: u32 = 12;
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(&data[at..at + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn hash(sequence: u32) -> usize {
+    (sequence.wrapping_mul(2_654_435_761) >> (32 - HASH_LOG)) as usize
+}
+
+/// Appends the remainder of a length that did not fit in a token nibble.
+fn write_length(mut len: usize, out: &mut Vec<u8>) {
+    while len >= 255 {
+        out.push(255);
+        len -= 255;
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    out.push(len as u8);
+}
+
+#[allow(clippy::cast_possible_truncation)]
+fn write_sequence(literals: &[u8], offset_and_len: Option<(usize, usize)>, out: &mut Vec<u8>) {
+    let literal_len = literals.len();
+    let match_len = offset_and_len.map_or(0, |(_, len)| len - MIN_MATCH);
+    out.push(((literal_len.min(15) as u8) << 4) | match_len.min(15) as u8);
+    if literal_len >= 15 {
+        write_length(literal_len - 15, out);
+    }
+    out.extend_from_slice(literals);
+    if let Some((offset, _)) = offset_and_len {
+        out.extend_from_slice(&(offset as u16).to_le_bytes());
+        if match_len >= 15 {
+            write_length(match_len - 15, out);
+        }
+    }
+}
+
+/// Appends `input` compressed as one LZ4 block.
+pub(crate) fn compress(input: &[u8], out: &mut Vec<u8>) {
+    let len = input.len();
+    let mut anchor = 0;
+    if len > MF_LIMIT {
+        // Positions are stored plus one so that zero marks an empty slot.
+        let mut table = vec![0_usize; 1 << HASH_LOG];
+        let match_limit = len - MF_LIMIT;
+        let mut pos = 0;
+        while pos < match_limit {
+            let sequence = read_u32(input, pos);
+            let slot = &mut table[hash(sequence)];
+            let candidate = slot.checked_sub(1);
+            *slot = pos + 1;
+            match candidate {
+                Some(candidate)
+                    if pos - candidate <= MAX_OFFSET && read_u32(input, candidate) == sequence =>
+                {
+                    let mut match_len = MIN_MATCH;
+                    while pos + match_len < len - LAST_LITERALS
+                        && input[candidate + match_len] == input[pos + match_len]
+                    {
+                        match_len += 1;
+                    }
+                    write_sequence(&input[anchor..pos], Some((pos - candidate, match_len)), out);
+                    pos += match_len;
+                    anchor = pos;
+                }
+                _ => pos += 1,
+            }
+        }
+    }
+    write_sequence(&input[anchor..], None, out);
+}
+
+fn corrupt() -> Error {
+    Error::InvalidValue("corrupt LZ4 block")
+}
+
+fn read_length(input: &[u8], pos: &mut usize, mut len: usize) -> Result<usize> {
+    if len == 15 {
+        loop {
+            let byte = *input.get(*pos).ok_or_else(corrupt)?;
+            *pos += 1;
+            len += usize::from(byte);
+            if byte != 255 {
+                break;
+            }
+        }
+    }
+    Ok(len)
+}
+
+/// Decompresses one LZ4 block that expands to exactly `size` bytes,
+/// appending the output to `out`.
+pub(crate) fn decompress(input: &[u8], size: usize, out: &mut Vec<u8>) -> Result<()> {
+    let end = out.len() + size;
+    out.reserve(size);
+    decompress_linked(input, out.len(), size, out)?;
+    if out.len() == end {
+        Ok(())
+    } else {
+        Err(corrupt())
+    }
+}
+
+/// Decompresses one LZ4 block of at most `limit` bytes, appending the
+/// output to `out`. Matches may refer back to `out[history..]`, which
+/// holds the data of previous blocks for linked frames.
+pub(crate) fn decompress_linked(
+    input: &[u8],
+    history: usize,
+    limit: usize,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let end = out.len() + limit;
+    let mut pos = 0;
+    loop {
+        let token = *input.get(pos).ok_or_else(corrupt)?;
+        pos += 1;
+        let literal_len = read_length(input, &mut pos, usize::from(token >> 4))?;
+        let literals = input.get(pos..pos + literal_len).ok_or_else(corrupt)?;
+        if out.len() + literal_len > end {
+            return Err(corrupt());
+        }
+        out.extend_from_slice(literals);
+        pos += literal_len;
+        if pos == input.len() {
+            break;
+        }
+        let offset = input.get(pos..pos + 2).ok_or_else(corrupt)?;
+        let offset = usize::from(u16::from_le_bytes([offset[0], offset[1]]));
+        pos += 2;
+        let match_len = read_length(input, &mut pos, usize::from(token & 15))? + MIN_MATCH;
+        if offset == 0 || offset > out.len() - history || out.len() + match_len > end {
+            return Err(corrupt());
+        }
+        let from = out.len() - offset;
+        if offset >= match_len {
+            out.extend_from_within(from..from + match_len);
+        } else {
+            // Overlapping matches repeat the bytes they are still producing.
+            for index in from..from + match_len {
+                out.push(out[index]);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, decompress};
+
+    fn round_trip(input: &[u8]) -> usize {
+        let mut compressed = Vec::new();
+        compress(input, &mut compressed);
+        let mut output = Vec::new();
+        decompress(&compressed, input.len(), &mut output).unwrap();
+        assert_eq!(output, input);
+        compressed.len()
+    }
+
+    #[test]
+    fn round_trips_assorted_inputs() {
+        assert_eq!(round_trip(b""), 1);
+        round_trip(b"short");
+        round_trip(&[0_u8; 13]);
+        assert!(round_trip(&vec![b'a'; 100_000]) < 1_000);
+        let text = b"the quick brown fox jumps over the lazy dog ".repeat(500);
+        assert!(round_trip(&text) < text.len() / 10);
+        let noise: Vec<u8> = (0..70_000_u32)
+            .map(|i| i.wrapping_mul(2_654_435_761).to_le_bytes()[2])
+            .collect();
+        round_trip(&noise);
+    }
+
+    #[test]
+    fn decodes_overlapping_matches() {
+        // "ab" as literals, then a 10-byte match at offset 2, then "c".
+        let block = [0x26, b'a', b'b', 2, 0, 0x10, b'c'];
+        let mut output = Vec::new();
+        decompress(&block, 13, &mut output).unwrap();
+        assert_eq!(output, b"ababababababc");
+    }
+
+    #[test]
+    fn rejects_corrupt_blocks() {
+        let mut output = Vec::new();
+        // Offset pointing before the start of the output.
+        assert!(decompress(&[0x10, b'a', 5, 0, 0x10, b'c'], 6, &mut output).is_err());
+        // Literal run past the end of the input.
+        assert!(decompress(&[0x50, b'a'], 5, &mut output).is_err());
+        // Output shorter than the declared size.
+        assert!(decompress(&[0x10, b'a'], 2, &mut output).is_err());
+    }
+}
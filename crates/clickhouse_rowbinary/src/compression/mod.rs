@@ -0,0 +1,219 @@
+//! `ClickHouse` compressed block framing.
+//!
+//! The server's own compression wraps a byte stream in blocks, each made
+//! of a 16-byte `CityHash128` checksum, a method byte, the compressed and
+//! uncompressed sizes and the compressed data. It is what the server sends
+//! for `compress=1` over HTTP, what it accepts with `decompress=1`, and what
+//! the native TCP protocol uses for data packets. It is independent of
+//! `Content-Encoding`, and wraps any format:
+//!
+//! ```
+//! # use std::io::{Read, Write};
+//! # use clickhouse_rowbinary::{CompressedReader, CompressedWriter, CompressionMethod};
+//! let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::Lz4);
+//! writer.write_all(&b"row ".repeat(100))?;
+//! let payload = writer.finish()?;
+//! assert!(payload.len() < 100);
+//!
+//! let mut decoded = Vec::new();
+//! CompressedReader::new(payload.as_slice()).read_to_end(&mut decoded)?;
+//! assert_eq!(decoded, b"row ".repeat(100));
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! [`CompressedReader`] implements [`BufRead`](std::io::BufRead), so any
+//! reader in this crate can decode rows straight from it.
+//!
+//! HTTP compression negotiated with `Accept-Encoding` is a separate layer.
+//! [`DecompressingReader`] recognizes gzip, ZSTD and LZ4 frames by their
+//! magic bytes and undoes them, passing anything else through:
+//!
+//! ```
+//! # use std::io::Read;
+//! # use clickhouse_rowbinary::{ContentEncoding, DecompressingReader};
+//! let body = zstd::encode_all(&b"payload"[..], 3)?;
+//! let mut reader = DecompressingReader::new(body.as_slice())?;
+//! assert_eq!(reader.encoding(), ContentEncoding::Zstd);
+//! let mut decoded = Vec::new();
+//! reader.read_to_end(&mut decoded)?;
+//! assert_eq!(decoded, b"payload");
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+
+mod cityhash;
+mod decoding;
+mod frame;
+mod lz4;
+mod reader;
+mod writer;
+mod xxhash;
+
+#[cfg(any(feature = "http", feature = "async-http"))]
+pub(crate) use cityhash::ChunkedHasher;
+pub use decoding::{ContentEncoding, DecompressingReader};
+pub use reader::CompressedReader;
+pub use writer::CompressedWriter;
+
+use std::io::{self, Read};
+
+use crate::error::{Error, Result};
+
+/// Size of the checksum preceding each block.
+const CHECKSUM_SIZE: usize = 16;
+/// Size of the method byte and the two block sizes.
+const HEADER_SIZE: usize = 9;
+/// Largest block the server accepts, compressed or not.
+pub(crate) const MAX_BLOCK_SIZE: usize = 1 << 30;
+
+/// Codec used for the data of a compressed block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CompressionMethod {
+    /// Data stored as is, still framed and checksummed.
+    None,
+    /// LZ4 block compression, the server's default.
+    #[default]
+    Lz4,
+    /// ZSTD compression, as selected by `network_compression_method`.
+    Zstd,
+}
+
+impl CompressionMethod {
+    fn byte(self) -> u8 {
+        match self {
+            Self::None => 0x02,
+            Self::Lz4 => 0x82,
+            Self::Zstd => 0x90,
+        }
+    }
+
+    fn from_byte(byte: u8) -> Result<Self> {
+        match byte {
+            0x02 => Ok(Self::None),
+            0x82 => Ok(Self::Lz4),
+            0x90 => Ok(Self::Zstd),
+            _ => Err(Error::UnsupportedType(format!(
+                "compression method 0x{byte:02x}"
+            ))),
+        }
+    }
+}
+
+fn block_size(size: usize) -> Result<u32> {
+    if size > MAX_BLOCK_SIZE {
+        return Err(Error::Overflow("compressed block exceeds 1 GiB"));
+    }
+    #[allow(clippy::cast_possible_truncation)]
+    Ok(size as u32)
+}
+
+/// Appends `data` as one compressed block, using `zstd_level` for ZSTD.
+fn encode_block(
+    method: CompressionMethod,
+    zstd_level: i32,
+    data: &[u8],
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let uncompressed_size = block_size(data.len())?;
+    let start = out.len();
+    out.resize(start + CHECKSUM_SIZE + HEADER_SIZE, 0);
+    out[start + CHECKSUM_SIZE] = method.byte();
+    match method {
+        CompressionMethod::None => out.extend_from_slice(data),
+        CompressionMethod::Lz4 => lz4::compress(data, out),
+        CompressionMethod::Zstd => {
+            out.extend_from_slice(&zstd::bulk::compress(data, zstd_level)?);
+        }
+    }
+    let compressed_size = match block_size(out.len() - start - CHECKSUM_SIZE) {
+        Ok(size) => size,
+        Err(err) => {
+            out.truncate(start);
+            return Err(err);
+        }
+    };
+    let sizes = start + CHECKSUM_SIZE + 1;
+    out[sizes..sizes + 4].copy_from_slice(&compressed_size.to_le_bytes());
+    out[sizes + 4..sizes + 8].copy_from_slice(&uncompressed_size.to_le_bytes());
+    let (low, high) = cityhash::city_hash128(&out[start + CHECKSUM_SIZE..]);
+    out[start..start + 8].copy_from_slice(&low.to_le_bytes());
+    out[start + 8..start + CHECKSUM_SIZE].copy_from_slice(&high.to_le_bytes());
+    Ok(())
+}
+
+/// Method and sizes from the header of a compressed block.
+struct BlockHeader {
+    method: CompressionMethod,
+    /// Size of the header and the compressed data.
+    compressed_size: usize,
+    uncompressed_size: usize,
+}
+
+impl BlockHeader {
+    fn parse(header: &[u8]) -> Result<Self> {
+        let size = |at: usize| {
+            let mut bytes = [0_u8; 4];
+            bytes.copy_from_slice(&header[at..at + 4]);
+            u32::from_le_bytes(bytes) as usize
+        };
+        let method = CompressionMethod::from_byte(header[0])?;
+        let compressed_size = size(1);
+        let uncompressed_size = size(5);
+        if compressed_size < HEADER_SIZE {
+            return Err(Error::InvalidValue("compressed block size is too small"));
+        }
+        if compressed_size > MAX_BLOCK_SIZE || uncompressed_size > MAX_BLOCK_SIZE {
+            return Err(Error::Overflow("compressed block exceeds 1 GiB"));
+        }
+        Ok(Self {
+            method,
+            compressed_size,
+            uncompressed_size,
+        })
+    }
+}
+
+/// Decodes the data of a block described by `header`, appending it to
+/// `out`.
+fn decode_block(header: &BlockHeader, data: &[u8], out: &mut Vec<u8>) -> Result<()> {
+    match header.method {
+        CompressionMethod::None => {
+            if data.len() != header.uncompressed_size {
+                return Err(Error::InvalidValue(
+                    "uncompressed block size does not match its header",
+                ));
+            }
+            out.extend_from_slice(data);
+            Ok(())
+        }
+        CompressionMethod::Lz4 => lz4::decompress(data, header.uncompressed_size, out),
+        CompressionMethod::Zstd => {
+            let block = zstd::bulk::decompress(data, header.uncompressed_size)
+                .map_err(|_| Error::InvalidValue("corrupt ZSTD block"))?;
+            if block.len() != header.uncompressed_size {
+                return Err(Error::InvalidValue(
+                    "ZSTD block size does not match its header",
+                ));
+            }
+            out.extend_from_slice(&block);
+            Ok(())
+        }
+    }
+}
+
+/// Fills `buf` completely, or returns `false` if the stream is already at
+/// its end.
+fn read_exact_or_eof<R: Read>(inner: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match inner.read(&mut buf[filled..]) {
+            Ok(0) if filled == 0 => return Ok(false),
+            Ok(0) => {
+                return Err(Error::Io(io::Error::from(io::ErrorKind::UnexpectedEof)));
+            }
+            Ok(read) => filled += read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
+    }
+    Ok(true)
+}
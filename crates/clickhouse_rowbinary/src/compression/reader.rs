@@ -0,0 +1,134 @@
+//! Compressed block reader.
+
+use std::io::{self, BufRead, Read};
+
+use crate::error::{Error, Result, into_io_error};
+
+use super::{
+    BlockHeader, CHECKSUM_SIZE, HEADER_SIZE, cityhash::city_hash128, decode_block,
+    read_exact_or_eof,
+};
+
+/// Reader that decompresses a stream of compressed blocks.
+///
+/// Each block's checksum is verified before it is decompressed, and a
+/// mismatch fails with [`Error::ChecksumMismatch`] naming the block and its
+/// offset in the stream. Through the [`Read`] implementation it arrives as
+/// the inner error of an [`io::ErrorKind::InvalidData`] error. The stream
+/// may end only between blocks.
+pub struct CompressedReader<R: Read> {
+    inner: R,
+    verify_checksums: bool,
+    compressed: Vec<u8>,
+    block: Vec<u8>,
+    pos: usize,
+    /// Index of the next block.
+    block_index: u64,
+    /// Stream offset of the next block.
+    offset: u64,
+}
+
+impl<R: Read> CompressedReader<R> {
+    /// Creates a reader over a stream of compressed blocks.
+    #[must_use]
+    pub fn new(inner: R) -> Self {
+        Self {
+            inner,
+            verify_checksums: true,
+            compressed: Vec::new(),
+            block: Vec::new(),
+            pos: 0,
+            block_index: 0,
+            offset: 0,
+        }
+    }
+
+    /// Enables or disables checksum verification, which is on by default.
+    ///
+    /// Skipping it saves hashing every block when the transport already
+    /// guarantees integrity; corrupted data may then decode to wrong bytes
+    /// or fail later.
+    pub fn set_verify_checksums(&mut self, enabled: bool) {
+        self.verify_checksums = enabled;
+    }
+
+    /// Returns the number of blocks read so far.
+    #[must_use]
+    pub fn blocks_read(&self) -> u64 {
+        self.block_index
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    ///
+    /// Decompressed data that has not been read yet is discarded.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads and decompresses the next block, returning `false` at the end
+    /// of the stream.
+    fn read_block(&mut self) -> Result<bool> {
+        let mut checksum = [0_u8; CHECKSUM_SIZE];
+        if !read_exact_or_eof(&mut self.inner, &mut checksum)? {
+            return Ok(false);
+        }
+        self.compressed.clear();
+        self.compressed.resize(HEADER_SIZE, 0);
+        self.inner.read_exact(&mut self.compressed)?;
+        let header = BlockHeader::parse(&self.compressed)?;
+        self.compressed.resize(header.compressed_size, 0);
+        self.inner.read_exact(&mut self.compressed[HEADER_SIZE..])?;
+        if self.verify_checksums {
+            let (low, high) = city_hash128(&self.compressed);
+            if checksum[..8] != low.to_le_bytes() || checksum[8..] != high.to_le_bytes() {
+                return Err(Error::ChecksumMismatch {
+                    block: self.block_index,
+                    offset: self.offset,
+                });
+            }
+        }
+        self.block_index += 1;
+        self.offset += (CHECKSUM_SIZE + header.compressed_size) as u64;
+        self.block.clear();
+        self.pos = 0;
+        decode_block(&header, &self.compressed[HEADER_SIZE..], &mut self.block)?;
+        Ok(true)
+    }
+}
+
+impl<R: Read> Read for CompressedReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let available = self.fill_buf()?;
+        let len = available.len().min(buf.len());
+        buf[..len].copy_from_slice(&available[..len]);
+        self.consume(len);
+        Ok(len)
+    }
+}
+
+impl<R: Read> BufRead for CompressedReader<R> {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.pos == self.block.len() {
+            if !self.read_block().map_err(into_io_error)? {
+                break;
+            }
+        }
+        Ok(&self.block[self.pos..])
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.pos = (self.pos + amt).min(self.block.len());
+    }
+}
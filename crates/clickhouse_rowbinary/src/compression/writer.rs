@@ -0,0 +1,126 @@
+//! Compressed block writer.
+
+use std::io::{self, Write};
+
+use crate::error::{Error, Result, into_io_error};
+
+use super::{CompressionMethod, MAX_BLOCK_SIZE, encode_block};
+
+/// ZSTD level the server compresses network data with by default.
+const DEFAULT_ZSTD_LEVEL: i32 = 1;
+/// Uncompressed block size the server writes by default.
+const DEFAULT_MAX_BLOCK_SIZE: usize = 1024 * 1024;
+
+/// Writer that compresses its input into compressed blocks as it goes.
+///
+/// Written bytes are buffered until they reach the maximum block size
+/// (1 MiB by default, as on the server), then compressed and written as one
+/// block, so memory use stays bounded however large the payload is.
+/// [`flush`](Write::flush) emits the buffered bytes as a shorter block;
+/// call [`finish`](Self::finish) to write the last block.
+pub struct CompressedWriter<W: Write> {
+    inner: W,
+    method: CompressionMethod,
+    zstd_level: i32,
+    max_block_size: usize,
+    buffer: Vec<u8>,
+    block: Vec<u8>,
+}
+
+impl<W: Write> CompressedWriter<W> {
+    /// Creates a writer that compresses blocks with `method`.
+    #[must_use]
+    pub fn new(inner: W, method: CompressionMethod) -> Self {
+        Self {
+            inner,
+            method,
+            zstd_level: DEFAULT_ZSTD_LEVEL,
+            max_block_size: DEFAULT_MAX_BLOCK_SIZE,
+            buffer: Vec::new(),
+            block: Vec::new(),
+        }
+    }
+
+    /// Returns the method blocks are compressed with.
+    #[must_use]
+    pub fn method(&self) -> CompressionMethod {
+        self.method
+    }
+
+    /// Sets the ZSTD compression level, matching the server's
+    /// `network_zstd_compression_level` (1 by default). Other methods
+    /// ignore it.
+    pub fn set_zstd_level(&mut self, level: i32) {
+        self.zstd_level = level;
+    }
+
+    /// Sets the uncompressed size at which a block is emitted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `size` is zero or above the
+    /// server's 1 GiB block limit.
+    pub fn set_max_block_size(&mut self, size: usize) -> Result<()> {
+        if size == 0 || size > MAX_BLOCK_SIZE {
+            return Err(Error::InvalidValue(
+                "block size must be between 1 byte and 1 GiB",
+            ));
+        }
+        self.max_block_size = size;
+        if self.buffer.len() >= size {
+            self.write_block()?;
+        }
+        Ok(())
+    }
+
+    /// Returns a reference to the inner writer.
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Writes buffered bytes as a final block, flushes and returns the
+    /// inner writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the block is too large or IO
+    /// fails.
+    pub fn finish(mut self) -> Result<W> {
+        self.write_block()?;
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn write_block(&mut self) -> Result<()> {
+        if self.buffer.is_empty() {
+            return Ok(());
+        }
+        self.block.clear();
+        encode_block(self.method, self.zstd_level, &self.buffer, &mut self.block)?;
+        self.inner.write_all(&self.block)?;
+        self.buffer.clear();
+        Ok(())
+    }
+}
+
+impl<W: Write> Write for CompressedWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let len = buf.len().min(self.max_block_size - self.buffer.len());
+        self.buffer.extend_from_slice(&buf[..len]);
+        if self.buffer.len() == self.max_block_size {
+            self.write_block().map_err(into_io_error)?;
+        }
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.write_block().map_err(into_io_error)?;
+        self.inner.flush()
+    }
+}
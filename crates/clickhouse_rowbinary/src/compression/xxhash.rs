@@ -0,0 +1,136 @@
+//! `XXH32`, the checksum of the LZ4 frame format.
+
+const PRIME1: u32 = 2_654_435_761;
+const PRIME2: u32 = 2_246_822_519;
+const PRIME3: u32 = 3_266_489_917;
+const PRIME4: u32 = 668_265_263;
+const PRIME5: u32 = 374_761_393;
+
+fn read_u32(data: &[u8], at: usize) -> u32 {
+    let mut bytes = [0_u8; 4];
+    bytes.copy_from_slice(&data[at..at + 4]);
+    u32::from_le_bytes(bytes)
+}
+
+fn round(acc: u32, input: u32) -> u32 {
+    acc.wrapping_add(input.wrapping_mul(PRIME2))
+        .rotate_left(13)
+        .wrapping_mul(PRIME1)
+}
+
+/// Streaming `XXH32` state with a zero seed.
+pub(crate) struct Xxh32 {
+    lanes: [u32; 4],
+    total_len: u64,
+    pending: [u8; 16],
+    pending_len: usize,
+}
+
+impl Xxh32 {
+    pub(crate) fn new() -> Self {
+        Self {
+            lanes: [
+                PRIME1.wrapping_add(PRIME2),
+                PRIME2,
+                0,
+                0_u32.wrapping_sub(PRIME1),
+            ],
+            total_len: 0,
+            pending: [0; 16],
+            pending_len: 0,
+        }
+    }
+
+    fn consume_stripe(&mut self, stripe: &[u8]) {
+        for (lane, at) in self.lanes.iter_mut().zip([0, 4, 8, 12]) {
+            *lane = round(*lane, read_u32(stripe, at));
+        }
+    }
+
+    pub(crate) fn update(&mut self, mut data: &[u8]) {
+        self.total_len += data.len() as u64;
+        if self.pending_len > 0 {
+            let take = (16 - self.pending_len).min(data.len());
+            self.pending[self.pending_len..self.pending_len + take].copy_from_slice(&data[..take]);
+            self.pending_len += take;
+            data = &data[take..];
+            if self.pending_len < 16 {
+                return;
+            }
+            let stripe = self.pending;
+            self.consume_stripe(&stripe);
+            self.pending_len = 0;
+        }
+        let mut stripes = data.chunks_exact(16);
+        for stripe in &mut stripes {
+            self.consume_stripe(stripe);
+        }
+        let rest = stripes.remainder();
+        self.pending[..rest.len()].copy_from_slice(rest);
+        self.pending_len = rest.len();
+    }
+
+    pub(crate) fn digest(&self) -> u32 {
+        let [v1, v2, v3, v4] = self.lanes;
+        let mut hash = if self.total_len >= 16 {
+            v1.rotate_left(1)
+                .wrapping_add(v2.rotate_left(7))
+                .wrapping_add(v3.rotate_left(12))
+                .wrapping_add(v4.rotate_left(18))
+        } else {
+            PRIME5
+        };
+        // The length is mixed in modulo 2^32.
+        #[allow(clippy::cast_possible_truncation)]
+        let total_len = self.total_len as u32;
+        hash = hash.wrapping_add(total_len);
+        let rest = &self.pending[..self.pending_len];
+        let mut words = rest.chunks_exact(4);
+        for word in &mut words {
+            hash = hash
+                .wrapping_add(read_u32(word, 0).wrapping_mul(PRIME3))
+                .rotate_left(17)
+                .wrapping_mul(PRIME4);
+        }
+        for &byte in words.remainder() {
+            hash = hash
+                .wrapping_add(u32::from(byte).wrapping_mul(PRIME5))
+                .rotate_left(11)
+                .wrapping_mul(PRIME1);
+        }
+        hash ^= hash >> 15;
+        hash = hash.wrapping_mul(PRIME2);
+        hash ^= hash >> 13;
+        hash = hash.wrapping_mul(PRIME3);
+        hash ^ (hash >> 16)
+    }
+}
+
+/// Returns the `XXH32` of `data` with a zero seed.
+pub(crate) fn xxh32(data: &[u8]) -> u32 {
+    let mut state = Xxh32::new();
+    state.update(data);
+    state.digest()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Xxh32, xxh32};
+
+    #[test]
+    fn matches_reference_vectors() {
+        assert_eq!(xxh32(b""), 0x02cc_5d05);
+        assert_eq!(xxh32(b"a"), 0x550d_7456);
+        assert_eq!(xxh32(b"abc"), 0x32d1_53ff);
+    }
+
+    #[test]
+    fn streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..1_000_u32).map(|i| i.to_le_bytes()[0] ^ 0x5a).collect();
+        let mut state = Xxh32::new();
+        for chunk in data.chunks(7) {
+            state.update(chunk);
+        }
+        assert_eq!(state.digest(), xxh32(&data));
+    }
+}
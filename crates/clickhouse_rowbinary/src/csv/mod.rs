@@ -0,0 +1,74 @@
+//! `CSV` and `CSVWithNames` format support.
+//!
+//! Rows are written one record per line. Values are typed by a
+//! [`Schema`](crate::Schema) following the server's CSV conventions:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{CsvFormat, CsvReader, CsvWriter, Schema, Value};
+//! let schema = Schema::from_type_strings(&[
+//!     ("id", "UInt32"),
+//!     ("name", "Nullable(String)"),
+//!     ("tags", "Array(String)"),
+//! ])?;
+//! let row = vec![
+//!     Value::UInt32(1),
+//!     Value::Nullable(None),
+//!     Value::Array(vec![Value::String(b"a\"b".to_vec())]),
+//! ];
+//! let mut writer = CsvWriter::new(Vec::new(), CsvFormat::CsvWithNames, schema.clone());
+//! writer.write_header()?;
+//! writer.write_row(&row)?;
+//! let payload = writer.into_inner();
+//! assert_eq!(payload, b"\"id\",\"name\",\"tags\"\n1,\\N,\"['a\"\"b']\"\n");
+//!
+//! let mut reader = CsvReader::new(payload.as_slice(), CsvFormat::CsvWithNames, schema)?;
+//! assert_eq!(reader.read_row()?, Some(row));
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! Strings, dates and other non-numeric values are double-quoted with `""`
+//! escaping, numbers are bare and `NULL` is written as `\N`. Arrays, maps
+//! and `JSON` values are written as their quoted literal text, while
+//! tuples are spread over one field per element. On input, unquoted empty
+//! fields and `\N` in columns that cannot hold `NULL` take the column's
+//! default value. Date-times are written as Unix timestamps and read
+//! either as timestamps or as UTC text.
+
+mod reader;
+mod writer;
+
+use std::fmt;
+
+pub use reader::CsvReader;
+pub use writer::CsvWriter;
+
+/// CSV format variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CsvFormat {
+    /// Records only.
+    Csv,
+    /// A header record with the column names precedes the data.
+    CsvWithNames,
+}
+
+impl fmt::Display for CsvFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            CsvFormat::Csv => "CSV",
+            CsvFormat::CsvWithNames => "CSVWithNames",
+        })
+    }
+}
+
+/// Options matching the server's CSV format settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CsvOptions {
+    /// Field delimiter, as with `format_csv_delimiter`.
+    pub delimiter: u8,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self { delimiter: b',' }
+    }
+}
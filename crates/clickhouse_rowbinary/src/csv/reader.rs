@@ -0,0 +1,344 @@
+//! CSV reader.
+
+use std::{borrow::Cow, collections::HashMap, io::BufRead};
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::json::{parse as parse_json, read_value as read_json},
+    rowbinary::{Row, Schema, default_value},
+    text::{
+        fixed_string, is_composite, is_numeric,
+        literal::{infer_literal, parse_literal, read_literal},
+        parse_scalar, text_type,
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+use super::{CsvFormat, CsvOptions};
+
+/// One field of a CSV record, unquoted.
+struct CsvField<'a> {
+    bytes: Cow<'a, [u8]>,
+    quoted: bool,
+}
+
+impl CsvField<'_> {
+    fn is_null(&self) -> bool {
+        !self.quoted && self.bytes.as_ref() == b"\\N"
+    }
+
+    fn is_empty(&self) -> bool {
+        !self.quoted && self.bytes.is_empty()
+    }
+}
+
+/// Reader that decodes CSV records into rows of the given schema.
+///
+/// For [`CsvFormat::CsvWithNames`] the header may list the schema columns
+/// in any order or leave some out; missing columns take their default
+/// value.
+pub struct CsvReader<R: BufRead> {
+    inner: R,
+    schema: Schema,
+    options: CsvOptions,
+    /// Schema column index of each record column, in record order.
+    order: Vec<usize>,
+    record: Vec<u8>,
+}
+
+impl<R: BufRead> CsvReader<R> {
+    /// Creates a reader using the default [`CsvOptions`], reading the
+    /// header record for [`CsvFormat::CsvWithNames`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the header is malformed, names
+    /// an unknown or repeated column, or IO fails.
+    pub fn new(inner: R, format: CsvFormat, schema: Schema) -> Result<Self> {
+        Self::with_options(inner, format, schema, CsvOptions::default())
+    }
+
+    /// Creates a reader with explicit CSV options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the header is malformed, names
+    /// an unknown or repeated column, or IO fails.
+    pub fn with_options(
+        inner: R,
+        format: CsvFormat,
+        schema: Schema,
+        options: CsvOptions,
+    ) -> Result<Self> {
+        let mut reader = Self {
+            inner,
+            order: (0..schema.len()).collect(),
+            schema,
+            options,
+            record: Vec::new(),
+        };
+        if format == CsvFormat::CsvWithNames {
+            reader.read_header()?;
+        }
+        Ok(reader)
+    }
+
+    fn read_header(&mut self) -> Result<()> {
+        if !read_record(&mut self.inner, &mut self.record)? {
+            return Ok(());
+        }
+        let columns: HashMap<&str, usize> = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (field.name.as_str(), index))
+            .collect();
+        let mut order = Vec::new();
+        for field in split_record(&self.record, self.options.delimiter)? {
+            let name = utf8(&field.bytes)?;
+            let &index = columns
+                .get(name)
+                .ok_or_else(|| Error::UnknownColumn(name.to_string()))?;
+            if order.contains(&index) {
+                return Err(Error::InvalidValue("duplicate column in CSV header"));
+            }
+            order.push(index);
+        }
+        self.order = order;
+        Ok(())
+    }
+
+    /// Returns the schema rows are decoded with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next row, or returns `Ok(None)` at EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a record has the wrong number
+    /// of fields, a value does not fit its column type, or IO fails.
+    pub fn read_row(&mut self) -> Result<Option<Row>> {
+        if !read_record(&mut self.inner, &mut self.record)? {
+            return Ok(None);
+        }
+        let fields = split_record(&self.record, self.options.delimiter)?;
+        let mut fields = fields.iter();
+        let mut row = vec![None; self.schema.len()];
+        for &index in &self.order {
+            row[index] = Some(read_column(&self.schema.fields()[index].ty, &mut fields)?);
+        }
+        if fields.next().is_some() {
+            return Err(Error::InvalidValue(
+                "CSV record has more fields than the schema",
+            ));
+        }
+        self.schema
+            .fields()
+            .iter()
+            .zip(row)
+            .map(|(field, value)| value.map_or_else(|| default_value(&field.ty), Ok))
+            .collect::<Result<Row>>()
+            .map(Some)
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for CsvReader<R> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// Reads one record into `record` without its line ending, continuing over
+/// line breaks inside quoted fields. Returns `false` at EOF.
+fn read_record<R: BufRead>(inner: &mut R, record: &mut Vec<u8>) -> Result<bool> {
+    record.clear();
+    let mut quoted = false;
+    loop {
+        let start = record.len();
+        if inner.read_until(b'\n', record)? == 0 {
+            if quoted {
+                return Err(Error::InvalidValue("unterminated quoted CSV field"));
+            }
+            return Ok(!record.is_empty());
+        }
+        quoted = record[start..]
+            .iter()
+            .fold(quoted, |quoted, &byte| quoted ^ (byte == b'"'));
+        if !quoted {
+            break;
+        }
+    }
+    if record.last() == Some(&b'\n') {
+        record.pop();
+        if record.last() == Some(&b'\r') {
+            record.pop();
+        }
+    }
+    Ok(true)
+}
+
+fn split_record(record: &[u8], delimiter: u8) -> Result<Vec<CsvField<'_>>> {
+    let mut fields = Vec::new();
+    let mut pos = 0;
+    loop {
+        if record.get(pos) == Some(&b'"') {
+            let mut bytes = Vec::new();
+            pos += 1;
+            loop {
+                match record.get(pos) {
+                    None => return Err(Error::InvalidValue("unterminated quoted CSV field")),
+                    Some(b'"') if record.get(pos + 1) == Some(&b'"') => {
+                        bytes.push(b'"');
+                        pos += 2;
+                    }
+                    Some(b'"') => {
+                        pos += 1;
+                        break;
+                    }
+                    Some(&byte) => {
+                        bytes.push(byte);
+                        pos += 1;
+                    }
+                }
+            }
+            fields.push(CsvField {
+                bytes: Cow::Owned(bytes),
+                quoted: true,
+            });
+            match record.get(pos) {
+                None => return Ok(fields),
+                Some(&byte) if byte == delimiter => pos += 1,
+                Some(_) => {
+                    return Err(Error::InvalidValue(
+                        "unexpected character after quoted CSV field",
+                    ));
+                }
+            }
+        } else {
+            let end = record[pos..]
+                .iter()
+                .position(|&byte| byte == delimiter)
+                .map_or(record.len(), |offset| pos + offset);
+            fields.push(CsvField {
+                bytes: Cow::Borrowed(&record[pos..end]),
+                quoted: false,
+            });
+            if end == record.len() {
+                return Ok(fields);
+            }
+            pos = end + 1;
+        }
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|_| Error::InvalidValue("CSV text is not valid UTF-8"))
+}
+
+/// Reads a column value, gathering tuples from one field per element.
+fn read_column<'f, 'a: 'f>(
+    ty: &TypeDesc,
+    fields: &mut impl Iterator<Item = &'f CsvField<'a>>,
+) -> Result<Value> {
+    let ty = text_type(ty);
+    if let TypeDesc::Tuple(items) = ty.as_ref() {
+        return Ok(Value::Tuple(
+            items
+                .iter()
+                .map(|item| read_column(&item.ty, fields))
+                .collect::<Result<_>>()?,
+        ));
+    }
+    let field = fields.next().ok_or(Error::InvalidValue(
+        "CSV record has fewer fields than the schema",
+    ))?;
+    read_field(&ty, field)
+}
+
+fn read_field(ty: &TypeDesc, field: &CsvField<'_>) -> Result<Value> {
+    let ty = text_type(ty);
+    let ty = ty.as_ref();
+    let bytes = field.bytes.as_ref();
+    Ok(match ty {
+        TypeDesc::Nullable(_) if field.is_null() || field.is_empty() => Value::Nullable(None),
+        TypeDesc::Nullable(inner) => Value::Nullable(Some(Box::new(read_field(inner, field)?))),
+        TypeDesc::Variant(_) if field.is_null() => Value::VariantNull,
+        TypeDesc::Variant(types) => read_variant(types, field)?,
+        TypeDesc::Dynamic { .. } if field.is_null() => Value::DynamicNull,
+        TypeDesc::Dynamic { .. } => {
+            // Quoted fields are strings unless they hold a composite, as
+            // written for `Dynamic` arrays and tuples.
+            let literal = !field.quoted || matches!(bytes.first(), Some(b'[' | b'('));
+            let inferred = literal
+                .then(|| parse_literal(bytes).ok())
+                .flatten()
+                .and_then(|literal| infer_literal(&literal).ok().flatten());
+            let (ty, value) =
+                inferred.unwrap_or_else(|| (TypeDesc::String, Value::String(bytes.to_vec())));
+            Value::Dynamic {
+                ty: Box::new(ty),
+                value: Box::new(value),
+            }
+        }
+        _ if field.is_null() || field.is_empty() => default_value(ty)?,
+        TypeDesc::String => Value::String(bytes.to_vec()),
+        TypeDesc::FixedString { length } => fixed_string(bytes, *length)?,
+        TypeDesc::Json { .. } => read_json(ty, &parse_json(bytes)?)?,
+        ty if is_composite(ty) => read_literal(ty, &parse_literal(bytes)?)?,
+        TypeDesc::AggregateFunction { .. } => {
+            return Err(Error::UnsupportedCombination(
+                "text formats do not support AggregateFunction columns".into(),
+            ));
+        }
+        ty => parse_scalar(ty, utf8(bytes)?)?,
+    })
+}
+
+/// Picks the first variant matching the field's shape, falling back to
+/// the first alternative that accepts it at all.
+fn read_variant(types: &[TypeDesc], field: &CsvField<'_>) -> Result<Value> {
+    let natural = |ty: &&TypeDesc| {
+        let ty = text_type(ty);
+        if field.quoted {
+            !is_numeric(&ty)
+        } else {
+            is_numeric(&ty)
+        }
+    };
+    let matching = types.iter().enumerate().filter(|(_, ty)| natural(ty));
+    for (index, ty) in matching.chain(types.iter().enumerate()) {
+        if let Ok(value) = read_field(ty, field) {
+            return Ok(Value::Variant {
+                index: u8::try_from(index).map_err(|_| Error::Overflow("too many variants"))?,
+                value: Box::new(value),
+            });
+        }
+    }
+    Err(Error::InvalidValue(
+        "CSV field matches no variant alternative",
+    ))
+}
@@ -0,0 +1,175 @@
+//! CSV writer.
+
+use std::io::Write;
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::{JsonOptions, json::write_value as write_json},
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::Schema,
+    text::{format_scalar, is_numeric, literal::write_literal, text_type},
+    types::TypeDesc,
+    value::Value,
+};
+
+use super::{CsvFormat, CsvOptions};
+
+/// Writer that encodes rows as CSV records.
+pub struct CsvWriter<W: Write> {
+    output: RowOutput<W>,
+    format: CsvFormat,
+    schema: Schema,
+    options: CsvOptions,
+}
+
+impl<W: Write> CsvWriter<W> {
+    /// Creates a writer using the default [`CsvOptions`].
+    #[must_use]
+    pub fn new(inner: W, format: CsvFormat, schema: Schema) -> Self {
+        Self::with_options(inner, format, schema, CsvOptions::default())
+    }
+
+    /// Creates a writer with explicit CSV options.
+    #[must_use]
+    pub fn with_options(inner: W, format: CsvFormat, schema: Schema, options: CsvOptions) -> Self {
+        Self {
+            output: RowOutput::new(inner),
+            format,
+            schema,
+            options,
+        }
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Writes the header record for [`CsvFormat::CsvWithNames`]; does
+    /// nothing for [`CsvFormat::Csv`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] if IO fails.
+    pub fn write_header(&mut self) -> Result<()> {
+        if self.format == CsvFormat::Csv {
+            return Ok(());
+        }
+        self.output.write(|out| {
+            for (index, field) in self.schema.fields().iter().enumerate() {
+                if index > 0 {
+                    out.push(self.options.delimiter);
+                }
+                write_quoted(field.name.as_bytes(), out);
+            }
+            out.push(b'\n');
+            Ok(())
+        })
+    }
+
+    /// Encodes and writes one row as a CSV record.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, a column type is not supported, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+                if index > 0 {
+                    out.push(self.options.delimiter);
+                }
+                write_column(&field.ty, value, self.options.delimiter, out)?;
+            }
+            out.push(b'\n');
+            Ok(())
+        })
+    }
+}
+
+impl_row_writer!(CsvWriter);
+
+/// Appends `bytes` as a double-quoted CSV field.
+fn write_quoted(bytes: &[u8], out: &mut Vec<u8>) {
+    out.push(b'"');
+    for &byte in bytes {
+        if byte == b'"' {
+            out.push(b'"');
+        }
+        out.push(byte);
+    }
+    out.push(b'"');
+}
+
+/// Writes a column value, spreading tuples over one field per element.
+fn write_column(ty: &TypeDesc, value: &Value, delimiter: u8, out: &mut Vec<u8>) -> Result<()> {
+    let ty = text_type(ty);
+    if let (TypeDesc::Tuple(items), Value::Tuple(values)) = (ty.as_ref(), value) {
+        if items.len() != values.len() {
+            return Err(Error::InvalidValue("tuple length does not match its type"));
+        }
+        for (index, (item, value)) in items.iter().zip(values).enumerate() {
+            if index > 0 {
+                out.push(delimiter);
+            }
+            write_column(&item.ty, value, delimiter, out)?;
+        }
+        return Ok(());
+    }
+    write_field(&ty, value, out)
+}
+
+fn write_field(ty: &TypeDesc, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), value) {
+        (TypeDesc::Nothing, Value::Nothing)
+        | (TypeDesc::Nullable(_), Value::Nullable(None))
+        | (TypeDesc::Variant(_), Value::VariantNull)
+        | (TypeDesc::Dynamic { .. }, Value::DynamicNull | Value::Nullable(None)) => {
+            out.extend_from_slice(b"\\N");
+        }
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            write_field(inner, value, out)?;
+        }
+        (TypeDesc::Variant(types), Value::Variant { index, value }) => {
+            let ty = types
+                .get(usize::from(*index))
+                .ok_or(Error::InvalidValue("variant index out of range"))?;
+            write_field(ty, value, out)?;
+        }
+        (TypeDesc::Dynamic { .. }, Value::Dynamic { ty, value }) => write_field(ty, value, out)?,
+        (TypeDesc::String, Value::String(bytes))
+        | (TypeDesc::FixedString { .. }, Value::FixedString(bytes)) => write_quoted(bytes, out),
+        (TypeDesc::Json { .. }, _) => {
+            let mut json = Vec::new();
+            write_json(&ty, value, &JsonOptions::default(), &mut json)?;
+            write_quoted(&json, out);
+        }
+        (
+            TypeDesc::Array(_)
+            | TypeDesc::Nested(_)
+            | TypeDesc::Tuple(_)
+            | TypeDesc::Map { .. }
+            | TypeDesc::AggregateFunction { .. },
+            _,
+        ) => {
+            let mut literal = Vec::new();
+            write_literal(&ty, value, &mut literal)?;
+            write_quoted(&literal, out);
+        }
+        (ty, value) => {
+            let mut text = String::new();
+            format_scalar(ty, value, &mut text)?;
+            if is_numeric(ty) {
+                out.extend_from_slice(text.as_bytes());
+            } else {
+                write_quoted(text.as_bytes(), out);
+            }
+        }
+    }
+    Ok(())
+}
@@ -8,7 +8,7 @@ pub type Result<T, E = Error> = std::result::Result<T, E>;
 pub enum Error {
     /// IO error bubbling from [`std::io`].
     #[error("io error: {0}")]
-    Io(#[from] std::io::Error),
+    Io(#[source] std::io::Error),
     /// Zstd seekable error bubbling from `zeekstd`.
     #[error("zstd seekable error: {0}")]
     Zstd(#[from] zeekstd::Error),
@@ -37,6 +37,95 @@ pub enum Error {
     /// bug or upstream issue).
     #[error("internal error: {0}")]
     Internal(&'static str),
+    /// Returned when a row has no column with the requested name or index.
+    #[error("unknown column: {0}")]
+    UnknownColumn(String),
+    /// Returned when serde cannot map a row onto a user type.
+    #[error("serde error: {0}")]
+    Serde(String),
+    /// Returned when a typed row's fields do not match the schema columns.
+    #[error("row mapping error: {0}")]
+    Mapping(String),
+    /// Returned by strict readers when the stream ends inside a row.
+    #[error("truncated row {row}: stream ended in column {column} at byte {offset}")]
+    Truncated {
+        /// Index of the incomplete row.
+        row: u64,
+        /// Index of the column being decoded.
+        column: usize,
+        /// Stream offset where the column starts.
+        offset: u64,
+    },
+    /// Returned by strict readers when a row holds bytes that do not decode.
+    #[error("corrupt row {row}: column {column} at byte {offset}: {source}")]
+    Corrupt {
+        /// Index of the row that failed to decode.
+        row: u64,
+        /// Index of the column being decoded.
+        column: usize,
+        /// Stream offset where the column starts.
+        offset: u64,
+        /// Underlying decoding error.
+        source: Box<Error>,
+    },
+    /// Returned when a compressed block does not match its checksum.
+    #[error("checksum mismatch in compressed block {block} at byte {offset}")]
+    ChecksumMismatch {
+        /// Index of the block in the stream.
+        block: u64,
+        /// Stream offset where the block starts.
+        offset: u64,
+    },
+    /// Returned when the server rejects a request.
+    #[error("server error {code}: {message}")]
+    Server {
+        /// `ClickHouse` error code, such as 60 for an unknown table.
+        code: i32,
+        /// Exception message sent by the server.
+        message: String,
+    },
+    /// Returned when an HTTP request fails without a server error.
+    #[error("http error: {0}")]
+    Http(String),
+    /// Returned when a native TCP connection receives data the protocol
+    /// does not allow, or is unusable after an earlier failure.
+    #[error("protocol error: {0}")]
+    Protocol(String),
+    /// Returned when Arrow rejects data converted to or from `RowBinary`.
+    #[error("arrow error: {0}")]
+    Arrow(String),
+    /// Returned when a Parquet file cannot be read or written.
+    #[error("parquet error: {0}")]
+    Parquet(String),
+    /// Returned when Polars rejects data converted to or from `RowBinary`.
+    #[error("polars error: {0}")]
+    Polars(String),
+}
+
+/// Payload type of [`std::io::Error`].
+type IoPayload = dyn std::error::Error + Send + Sync;
+
+impl From<std::io::Error> for Error {
+    /// Wraps an IO error, unwrapping crate errors that were raised behind
+    /// an IO trait so they surface as themselves.
+    fn from(err: std::io::Error) -> Self {
+        if !err.get_ref().is_some_and(IoPayload::is::<Self>) {
+            return Self::Io(err);
+        }
+        match err.into_inner().map(IoPayload::downcast::<Self>) {
+            Some(Ok(inner)) => *inner,
+            _ => Self::Internal("wrapped error lost while unwrapping"),
+        }
+    }
+}
+
+/// Converts a crate error raised behind an IO trait into an IO error;
+/// [`Error`]'s `From<std::io::Error>` turns it back.
+pub(crate) fn into_io_error(err: Error) -> std::io::Error {
+    match err {
+        Error::Io(err) => err,
+        err => std::io::Error::new(std::io::ErrorKind::InvalidData, err),
+    }
 }
 
 #[cfg(test)]
@@ -71,7 +160,63 @@ mod tests {
         let overflow = Error::Overflow("too big");
         assert!(format!("{overflow}").contains("too big"));
 
+        let unknown = Error::UnknownColumn("user_id".into());
+        assert!(format!("{unknown}").contains("unknown column: user_id"));
+
+        let mapping = Error::Mapping("missing column for field `id`".into());
+        assert!(format!("{mapping}").contains("row mapping error"));
+
         let internal = Error::Internal("bug");
         assert!(format!("{internal}").contains("bug"));
+
+        let truncated = Error::Truncated {
+            row: 3,
+            column: 1,
+            offset: 42,
+        };
+        assert!(format!("{truncated}").contains("byte 42"));
+
+        let corrupt = Error::Corrupt {
+            row: 3,
+            column: 1,
+            offset: 42,
+            source: Box::new(Error::InvalidValue("invalid Bool value")),
+        };
+        assert!(format!("{corrupt}").contains("invalid Bool value"));
+
+        let checksum = Error::ChecksumMismatch {
+            block: 2,
+            offset: 4096,
+        };
+        assert!(format!("{checksum}").contains("block 2 at byte 4096"));
+
+        let server = Error::Server {
+            code: 60,
+            message: "Unknown table".into(),
+        };
+        assert!(format!("{server}").contains("server error 60: Unknown table"));
+
+        let http = Error::Http("connection refused".into());
+        assert!(format!("{http}").contains("http error"));
+
+        let protocol = Error::Protocol("unexpected packet 42".into());
+        assert!(format!("{protocol}").contains("protocol error"));
+    }
+
+    #[test]
+    fn errors_raised_behind_io_traits_surface_unchanged() {
+        let tunnelled = into_io_error(Error::ChecksumMismatch {
+            block: 1,
+            offset: 10,
+        });
+        assert!(matches!(
+            Error::from(tunnelled),
+            Error::ChecksumMismatch {
+                block: 1,
+                offset: 10
+            }
+        ));
+        let plain = std::io::Error::other("boom");
+        assert!(matches!(Error::from(plain), Error::Io(_)));
     }
 }
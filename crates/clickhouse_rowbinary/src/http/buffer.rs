@@ -0,0 +1,530 @@
+//! Buffer that gathers rows into batched inserts.
+
+use std::{
+    sync::{Arc, Mutex, MutexGuard, PoisonError, Weak},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use tokio::{runtime::Handle, task::JoinHandle, time};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema},
+    value::Value,
+};
+
+use super::{AsyncClient, Query, insert_statement};
+
+/// Shortest pause between two checks for batches past their delay.
+const MIN_TICK: Duration = Duration::from_millis(10);
+
+/// Limits at which an [`InsertBuffer`] flushes a batch.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InsertBufferOptions {
+    /// Rows a batch gathers before it is flushed.
+    pub max_rows: usize,
+    /// Encoded size, in bytes, at which a batch is flushed.
+    pub max_bytes: usize,
+    /// Time after its first row at which a batch is flushed, however few
+    /// rows it holds. Batches wait for one of the other limits when
+    /// `None`.
+    pub max_delay: Option<Duration>,
+}
+
+impl Default for InsertBufferOptions {
+    fn default() -> Self {
+        Self {
+            max_rows: 100_000,
+            max_bytes: 16 * 1024 * 1024,
+            max_delay: Some(Duration::from_secs(1)),
+        }
+    }
+}
+
+/// A batch an [`InsertBuffer`] failed to insert on its own, as handed to
+/// its error callback.
+#[derive(Debug)]
+pub struct FlushError {
+    /// Table the rows were meant for.
+    pub table: String,
+    /// Rows in the batch, which are dropped; like any failed insert, it may
+    /// have been partially applied.
+    pub rows: usize,
+    /// Why the insert failed.
+    pub error: Error,
+}
+
+/// Callback that receives the failures of automatic flushes.
+type ErrorCallback = Box<dyn Fn(FlushError) + Send + Sync>;
+
+/// Gathers rows pushed one at a time into batched inserts, the shape
+/// `MergeTree` tables want, with one batch per table and schema.
+///
+/// A batch is flushed as one `RowBinary` insert once it holds
+/// [`InsertBufferOptions::max_rows`] rows or
+/// [`InsertBufferOptions::max_bytes`] bytes, or once its first row is
+/// [`InsertBufferOptions::max_delay`] old, which a background task checks.
+/// The pushes that fill a batch wait for its insert, which holds producers
+/// back while the server is slow. Since a batch is held in memory, its
+/// insert fails over between endpoints and is retried as
+/// [`RetryPolicy`](super::RetryPolicy) allows; under
+/// [`Deduplication::PayloadHash`](super::Deduplication::PayloadHash) it
+/// carries a token hashed from the batch.
+///
+/// Automatic flushes have no caller to fail, so their errors go to the
+/// callback given to [`InsertBuffer::new`] and the batch is dropped.
+/// Dropping the buffer flushes what it still holds in the background, or
+/// drops it outside a Tokio runtime; await [`InsertBuffer::flush`] first to
+/// know the rows were written.
+///
+/// ```no_run
+/// # use clickhouse_rowbinary::{Schema, Value, http::{AsyncClient, InsertBuffer, InsertBufferOptions}};
+/// # async fn example() -> clickhouse_rowbinary::Result<()> {
+/// let client = AsyncClient::new("http://localhost:8123")?;
+/// let buffer = InsertBuffer::new(client, InsertBufferOptions::default(), |failed| {
+///     eprintln!("lost {} rows for {}: {}", failed.rows, failed.table, failed.error);
+/// });
+/// let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")])?;
+/// for id in 0..1_000 {
+///     let row = [Value::UInt64(id), Value::String(b"ada".to_vec())];
+///     buffer.push("people", &schema, &row).await?;
+/// }
+/// buffer.flush().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct InsertBuffer {
+    shared: Arc<Shared>,
+    /// Task flushing batches past their delay.
+    timer: Option<JoinHandle<()>>,
+}
+
+struct Shared {
+    client: AsyncClient,
+    options: InsertBufferOptions,
+    /// Batches gathering rows, in the order they were started.
+    batches: Mutex<Vec<Batch>>,
+    on_error: ErrorCallback,
+}
+
+/// Rows gathered for one table and schema.
+struct Batch {
+    table: String,
+    schema: Schema,
+    encoder: RowBinaryValueWriter<Vec<u8>>,
+    rows: usize,
+    since: Instant,
+}
+
+/// A batch taken out of the buffer to be inserted.
+struct Flush {
+    table: String,
+    schema: Schema,
+    rows: usize,
+    body: Bytes,
+}
+
+impl InsertBuffer {
+    /// Creates a buffer inserting through `client`, which hands the
+    /// failures of automatic flushes to `on_error`.
+    ///
+    /// # Panics
+    ///
+    /// Panics when called outside a Tokio runtime while
+    /// [`InsertBufferOptions::max_delay`] is set, since the delay is kept
+    /// by a task of that runtime.
+    pub fn new(
+        client: AsyncClient,
+        options: InsertBufferOptions,
+        on_error: impl Fn(FlushError) + Send + Sync + 'static,
+    ) -> Self {
+        let max_delay = options.max_delay;
+        let shared = Arc::new(Shared {
+            client,
+            options,
+            batches: Mutex::new(Vec::new()),
+            on_error: Box::new(on_error),
+        });
+        let timer = max_delay.map(|delay| tokio::spawn(tick(Arc::downgrade(&shared), delay)));
+        Self { shared, timer }
+    }
+
+    /// Returns the limits batches are flushed at.
+    #[must_use]
+    pub fn options(&self) -> &InsertBufferOptions {
+        &self.shared.options
+    }
+
+    /// Returns how many rows wait in the buffer.
+    #[must_use]
+    pub fn pending_rows(&self) -> usize {
+        self.shared.lock().iter().map(|batch| batch.rows).sum()
+    }
+
+    /// Adds `row` to the batch of `table`, named as in SQL, with the
+    /// columns of `schema`, and inserts the batch when it reaches its row
+    /// or byte limit.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when `row` does not match `schema`,
+    /// in which case it is left out of the batch. A failed insert goes to
+    /// the error callback instead.
+    pub async fn push(&self, table: &str, schema: &Schema, row: &[Value]) -> Result<()> {
+        let full = {
+            let mut batches = self.shared.lock();
+            let found = batches
+                .iter()
+                .position(|batch| batch.table == table && batch.schema == *schema);
+            let index = if let Some(index) = found {
+                index
+            } else {
+                batches.push(Batch::new(table, schema));
+                batches.len() - 1
+            };
+            let batch = &mut batches[index];
+            let start = batch.encoder.get_ref().len();
+            if let Err(err) = batch.encoder.write_row(row) {
+                batch.encoder.get_mut().truncate(start);
+                if batch.rows == 0 {
+                    batches.remove(index);
+                }
+                return Err(err);
+            }
+            batch.rows += 1;
+            let options = &self.shared.options;
+            (batch.rows >= options.max_rows || batch.encoder.get_ref().len() >= options.max_bytes)
+                .then(|| batches.remove(index).into_flush())
+        };
+        if let Some(flush) = full {
+            self.shared.send_reporting(flush).await;
+        }
+        Ok(())
+    }
+
+    /// Inserts every batch gathered so far, in the order they were started.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the first insert that fails, whose rows are
+    /// dropped; the batches after it stay in the buffer.
+    pub async fn flush(&self) -> Result<()> {
+        let flushes = self.shared.take(|_| true);
+        let mut flushes = flushes.into_iter();
+        while let Some(flush) = flushes.next() {
+            if let Err(err) = self.shared.send(flush).await {
+                self.shared.restore(flushes);
+                return Err(err.error);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for InsertBuffer {
+    fn drop(&mut self) {
+        if let Some(timer) = &self.timer {
+            timer.abort();
+        }
+        let flushes = self.shared.take(|_| true);
+        if flushes.is_empty() {
+            return;
+        }
+        if let Ok(handle) = Handle::try_current() {
+            let shared = Arc::clone(&self.shared);
+            handle.spawn(async move {
+                for flush in flushes {
+                    shared.send_reporting(flush).await;
+                }
+            });
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, Vec<Batch>> {
+        self.batches.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Takes the batches `due` selects out of the buffer.
+    fn take(&self, due: impl Fn(&Batch) -> bool) -> Vec<Flush> {
+        let mut batches = self.lock();
+        let (taken, kept) = batches.drain(..).partition(|batch| due(batch));
+        *batches = kept;
+        taken.into_iter().map(Batch::into_flush).collect()
+    }
+
+    /// Puts batches whose insert was not attempted back into the buffer.
+    fn restore(&self, flushes: impl Iterator<Item = Flush>) {
+        let mut batches = self.lock();
+        for flush in flushes.collect::<Vec<_>>().into_iter().rev() {
+            let mut batch = Batch::new(&flush.table, &flush.schema);
+            batch.encoder.get_mut().extend_from_slice(&flush.body);
+            batch.rows += flush.rows;
+            batches.insert(0, batch);
+        }
+    }
+
+    /// Inserts the rows of `flush`.
+    async fn send(&self, flush: Flush) -> std::result::Result<(), FlushError> {
+        let query = Query::new(insert_statement(&flush.table, &flush.schema));
+        match self.client.insert_bytes(query, flush.body).await {
+            Ok(_) => Ok(()),
+            Err(error) => Err(FlushError {
+                table: flush.table,
+                rows: flush.rows,
+                error,
+            }),
+        }
+    }
+
+    /// Inserts the rows of `flush`, handing a failure to the callback.
+    async fn send_reporting(&self, flush: Flush) {
+        if let Err(err) = self.send(flush).await {
+            (self.on_error)(err);
+        }
+    }
+}
+
+impl Batch {
+    fn new(table: &str, schema: &Schema) -> Self {
+        Self {
+            table: table.to_string(),
+            schema: schema.clone(),
+            encoder: RowBinaryValueWriter::new(
+                Vec::new(),
+                RowBinaryFormat::RowBinary,
+                schema.clone(),
+            ),
+            rows: 0,
+            since: Instant::now(),
+        }
+    }
+
+    fn into_flush(self) -> Flush {
+        Flush {
+            table: self.table,
+            schema: self.schema,
+            rows: self.rows,
+            body: Bytes::from(self.encoder.into_inner()),
+        }
+    }
+}
+
+/// Flushes the batches of `shared` whose first row is `delay` old, until
+/// the buffer is dropped.
+async fn tick(shared: Weak<Shared>, delay: Duration) {
+    let mut interval = time::interval((delay / 4).max(MIN_TICK));
+    interval.set_missed_tick_behavior(time::MissedTickBehavior::Delay);
+    loop {
+        interval.tick().await;
+        let Some(shared) = shared.upgrade() else {
+            return;
+        };
+        for flush in shared.take(|batch| batch.since.elapsed() >= delay) {
+            shared.send_reporting(flush).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::{Arc, Mutex},
+        time::Duration,
+    };
+
+    use super::{InsertBuffer, InsertBufferOptions};
+    use crate::{
+        error::Error,
+        http::{
+            AsyncClient, ClientOptions, Deduplication, RetryPolicy,
+            mock::{response, serve},
+        },
+        rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap()
+    }
+
+    fn row(id: u64) -> Vec<Value> {
+        vec![
+            Value::UInt64(id),
+            Value::String(format!("n{id}").into_bytes()),
+        ]
+    }
+
+    fn encode(ids: std::ops::Range<u64>) -> Vec<u8> {
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+        writer.write_rows(ids.map(row)).unwrap();
+        writer.into_inner()
+    }
+
+    fn no_failures() -> impl Fn(super::FlushError) + Send + Sync {
+        |err| panic!("unexpected flush failure: {}", err.error)
+    }
+
+    #[tokio::test]
+    async fn batches_flush_at_their_row_limit() {
+        let (url, server) = serve(vec![response("200 OK", &[], b""); 3]);
+        let options = InsertBufferOptions {
+            max_rows: 2,
+            max_delay: None,
+            ..InsertBufferOptions::default()
+        };
+        let buffer = InsertBuffer::new(AsyncClient::new(&url).unwrap(), options, no_failures());
+        for id in 0..3 {
+            buffer.push("people", &schema(), &row(id)).await.unwrap();
+        }
+        let other = Schema::from_type_strings(&[("id", "UInt64")]).unwrap();
+        buffer
+            .push("people", &other, &[Value::UInt64(7)])
+            .await
+            .unwrap();
+        assert_eq!(buffer.pending_rows(), 2);
+        buffer.flush().await.unwrap();
+        assert_eq!(buffer.pending_rows(), 0);
+
+        let requests = server.join().unwrap();
+        let line = requests[0].head.lines().next().unwrap();
+        assert!(line.starts_with("POST /?query=INSERT"), "{line}");
+        assert_eq!(
+            [&requests[0].body, &requests[1].body, &requests[2].body],
+            [&encode(0..2), &encode(2..3), &7_u64.to_le_bytes().to_vec()]
+        );
+    }
+
+    #[tokio::test]
+    async fn payload_tokens_let_flushes_retry() {
+        let (url, server) = serve(vec![
+            response("503 Service Unavailable", &[], b""),
+            response("200 OK", &[], b""),
+        ]);
+        let client = AsyncClient::with_options(
+            &url,
+            ClientOptions {
+                retry: RetryPolicy {
+                    max_retries: 1,
+                    initial_backoff: Duration::from_millis(1),
+                    ..RetryPolicy::default()
+                },
+                deduplication: Deduplication::PayloadHash,
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+        let options = InsertBufferOptions {
+            max_delay: None,
+            ..InsertBufferOptions::default()
+        };
+        let buffer = InsertBuffer::new(client, options, no_failures());
+        buffer.push("people", &schema(), &row(0)).await.unwrap();
+        buffer.flush().await.unwrap();
+
+        let requests = server.join().unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[0].body, requests[1].body);
+        let line = |at: usize| requests[at].head.lines().next().unwrap();
+        assert!(
+            line(0).contains("insert_deduplication_token="),
+            "{}",
+            line(0)
+        );
+        assert_eq!(line(0), line(1));
+    }
+
+    #[tokio::test]
+    async fn batches_flush_after_their_delay() {
+        let (url, server) = serve(vec![response("200 OK", &[], b"")]);
+        let options = InsertBufferOptions {
+            max_delay: Some(Duration::from_millis(20)),
+            ..InsertBufferOptions::default()
+        };
+        let buffer = InsertBuffer::new(AsyncClient::new(&url).unwrap(), options, no_failures());
+        buffer.push("people", &schema(), &row(0)).await.unwrap();
+        for _ in 0..100 {
+            if buffer.pending_rows() == 0 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+        assert_eq!(buffer.pending_rows(), 0);
+        // The insert may still be running on this thread.
+        let server = tokio::task::spawn_blocking(move || server.join().unwrap());
+        assert_eq!(server.await.unwrap()[0].body, encode(0..1));
+    }
+
+    #[tokio::test]
+    async fn failed_flushes_reach_the_callback() {
+        let rejected = || {
+            response(
+                "404 Not Found",
+                &[("X-ClickHouse-Exception-Code", "60")],
+                b"Code: 60. DB::Exception: Unknown table default.people. (UNKNOWN_TABLE)",
+            )
+        };
+        let (url, server) = serve(vec![rejected(), rejected(), response("200 OK", &[], b"")]);
+        let failures = Arc::new(Mutex::new(Vec::new()));
+        let options = InsertBufferOptions {
+            max_rows: 2,
+            max_delay: None,
+            ..InsertBufferOptions::default()
+        };
+        let buffer = InsertBuffer::new(AsyncClient::new(&url).unwrap(), options, {
+            let failures = Arc::clone(&failures);
+            move |err| failures.lock().unwrap().push(err)
+        });
+        for id in 0..2 {
+            buffer.push("people", &schema(), &row(id)).await.unwrap();
+        }
+        {
+            let failures = failures.lock().unwrap();
+            assert_eq!(failures.len(), 1);
+            assert_eq!(
+                (failures[0].table.as_str(), failures[0].rows),
+                ("people", 2)
+            );
+            assert!(matches!(failures[0].error, Error::Server { code: 60, .. }));
+        }
+
+        // An explicit flush reports its failure and keeps the later batches.
+        buffer.push("people", &schema(), &row(2)).await.unwrap();
+        buffer.push("pets", &schema(), &row(3)).await.unwrap();
+        let err = buffer.flush().await.err().unwrap();
+        assert!(matches!(err, Error::Server { code: 60, .. }), "{err}");
+        assert_eq!(buffer.pending_rows(), 1);
+        buffer.flush().await.unwrap();
+        assert_eq!(failures.lock().unwrap().len(), 1);
+
+        let requests = server.join().unwrap();
+        assert!(
+            requests[2].head.contains("INSERT+INTO+pets"),
+            "{}",
+            requests[2].head
+        );
+        assert_eq!(requests[2].body, encode(3..4));
+    }
+
+    #[tokio::test]
+    async fn rows_that_do_not_match_the_schema_are_left_out() {
+        let (url, server) = serve(vec![response("200 OK", &[], b"")]);
+        let buffer = InsertBuffer::new(
+            AsyncClient::new(&url).unwrap(),
+            InsertBufferOptions::default(),
+            no_failures(),
+        );
+        buffer.push("people", &schema(), &row(0)).await.unwrap();
+        let err = buffer
+            .push("people", &schema(), &[Value::UInt64(1), Value::UInt8(2)])
+            .await;
+        assert!(err.is_err());
+        assert_eq!(buffer.pending_rows(), 1);
+        drop(buffer);
+        let server = tokio::task::spawn_blocking(move || server.join().unwrap());
+        assert_eq!(server.await.unwrap()[0].body, encode(0..1));
+    }
+}
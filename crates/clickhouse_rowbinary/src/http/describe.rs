@@ -0,0 +1,245 @@
+//! Schemas described by the server.
+
+#[cfg(feature = "async-http")]
+use super::AsyncClient;
+#[cfg(feature = "http")]
+use super::Client;
+use super::{Query, trim_statement};
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Field, Schema},
+    types::parse_type_desc,
+    value::Value,
+};
+
+/// Column kinds an insert without a column list does not take.
+const NON_INSERTABLE: &[&str] = &["ALIAS", "MATERIALIZED"];
+
+impl Schema {
+    /// Fetches the schema of `table`, written as in SQL such as
+    /// `db.people`, with the columns an insert without a column list takes,
+    /// in table order.
+    ///
+    /// `ALIAS` and `MATERIALIZED` columns are left out, since the server
+    /// computes them.
+    ///
+    /// ```no_run
+    /// # use clickhouse_rowbinary::{Schema, http::Client};
+    /// let client = Client::new("http://localhost:8123")?;
+    /// let schema = Schema::from_server(&client, "people")?;
+    /// println!("{}", schema.column_definitions());
+    /// # Ok::<(), clickhouse_rowbinary::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the request fails or a column
+    /// type is not supported.
+    #[cfg(feature = "http")]
+    pub fn from_server(client: &Client, table: &str) -> Result<Self> {
+        describe(client, describe_table(table), true)
+    }
+
+    /// Fetches the schema of the rows `sql` returns, using
+    /// `DESCRIBE (sql)`, without running the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the request fails or a column
+    /// type is not supported.
+    #[cfg(feature = "http")]
+    pub fn from_query(client: &Client, sql: &str) -> Result<Self> {
+        describe(client, describe_query(sql), false)
+    }
+
+    /// Fetches the schema of `table` over an [`AsyncClient`], with the
+    /// columns an insert without a column list takes, in table order.
+    ///
+    /// `ALIAS` and `MATERIALIZED` columns are left out, since the server
+    /// computes them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the request fails or a column
+    /// type is not supported.
+    #[cfg(feature = "async-http")]
+    pub async fn from_server_async(client: &AsyncClient, table: &str) -> Result<Self> {
+        describe_async(client, describe_table(table), true).await
+    }
+
+    /// Fetches the schema of the rows `sql` returns over an
+    /// [`AsyncClient`], using `DESCRIBE (sql)`, without running the query.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the request fails or a column
+    /// type is not supported.
+    #[cfg(feature = "async-http")]
+    pub async fn from_query_async(client: &AsyncClient, sql: &str) -> Result<Self> {
+        describe_async(client, describe_query(sql), false).await
+    }
+}
+
+fn describe_table(table: &str) -> Query {
+    // Subcolumns would be listed as extra columns.
+    Query::new(format!("DESCRIBE TABLE {table}")).setting("describe_include_subcolumns", 0)
+}
+
+fn describe_query(sql: &str) -> Query {
+    Query::new(format!("DESCRIBE ({})", trim_statement(sql)))
+        .setting("describe_include_subcolumns", 0)
+}
+
+#[cfg(feature = "http")]
+fn describe(client: &Client, query: Query, insertable: bool) -> Result<Schema> {
+    let reader = client.query(query)?;
+    let mut columns = Columns::new(reader.schema(), insertable)?;
+    for row in reader.rows() {
+        columns.push(&row?)?;
+    }
+    Ok(Schema::new(columns.fields))
+}
+
+#[cfg(feature = "async-http")]
+async fn describe_async(client: &AsyncClient, query: Query, insertable: bool) -> Result<Schema> {
+    let mut reader = client.query(query).await?;
+    let mut columns = Columns::new(reader.schema(), insertable)?;
+    while let Some(row) = reader.next_row().await? {
+        columns.push(&row)?;
+    }
+    Ok(Schema::new(columns.fields))
+}
+
+/// Fields collected from the rows of a `DESCRIBE` result.
+struct Columns {
+    name: usize,
+    ty: usize,
+    /// Position of `default_type`, when only insertable columns are kept.
+    default_type: Option<usize>,
+    fields: Vec<Field>,
+}
+
+impl Columns {
+    fn new(result: &Schema, insertable: bool) -> Result<Self> {
+        let column = |name| {
+            result
+                .index_of(name)
+                .ok_or(Error::InvalidValue("DESCRIBE result lacks a column"))
+        };
+        Ok(Self {
+            name: column("name")?,
+            ty: column("type")?,
+            default_type: insertable.then(|| column("default_type")).transpose()?,
+            fields: Vec::new(),
+        })
+    }
+
+    fn push(&mut self, row: &[Value]) -> Result<()> {
+        if let Some(default_type) = self.default_type
+            && NON_INSERTABLE.contains(&text(&row[default_type])?)
+        {
+            return Ok(());
+        }
+        self.fields.push(Field {
+            name: text(&row[self.name])?.to_string(),
+            ty: parse_type_desc(text(&row[self.ty])?)?,
+        });
+        Ok(())
+    }
+}
+
+fn text(value: &Value) -> Result<&str> {
+    match value {
+        Value::String(bytes) => std::str::from_utf8(bytes)
+            .map_err(|_| Error::InvalidValue("DESCRIBE result is not valid UTF-8")),
+        other => Err(Error::TypeMismatch {
+            expected: "String".to_string(),
+            actual: other.type_name().to_string(),
+        }),
+    }
+}
+
+#[cfg(all(test, feature = "http"))]
+mod tests {
+    use crate::{
+        http::{
+            Client,
+            mock::{response, serve},
+        },
+        rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    fn describe_result(columns: &[(&str, &str, &str)]) -> Vec<u8> {
+        let schema = Schema::from_type_strings(&[
+            ("name", "String"),
+            ("type", "String"),
+            ("default_type", "String"),
+            ("default_expression", "String"),
+        ])
+        .unwrap();
+        let mut writer = RowBinaryValueWriter::new(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema,
+        );
+        writer.write_header().unwrap();
+        for (name, ty, default_type) in columns {
+            writer
+                .write_row(&[
+                    Value::String(name.as_bytes().to_vec()),
+                    Value::String(ty.as_bytes().to_vec()),
+                    Value::String(default_type.as_bytes().to_vec()),
+                    Value::String(Vec::new()),
+                ])
+                .unwrap();
+        }
+        writer.into_inner()
+    }
+
+    #[test]
+    fn table_schemas_keep_the_insertable_columns() {
+        let body = describe_result(&[
+            ("id", "UInt64", ""),
+            ("point", "Tuple(\n    x Float64,\n    y Float64)", ""),
+            ("note", "String", "DEFAULT"),
+            ("id_text", "String", "MATERIALIZED"),
+            ("id_alias", "UInt64", "ALIAS"),
+        ]);
+        let (url, server) = serve(vec![response("200 OK", &[], &body)]);
+        let client = Client::new(&url).unwrap();
+        let schema = Schema::from_server(&client, "db.people").unwrap();
+        assert_eq!(
+            schema,
+            Schema::from_type_strings(&[
+                ("id", "UInt64"),
+                ("point", "Tuple(x Float64, y Float64)"),
+                ("note", "String"),
+            ])
+            .unwrap()
+        );
+
+        let request = &server.join().unwrap()[0];
+        assert!(request.head.contains("describe_include_subcolumns=0"));
+        assert_eq!(
+            request.body,
+            b"DESCRIBE TABLE db.people FORMAT RowBinaryWithNamesAndTypes"
+        );
+    }
+
+    #[test]
+    fn query_schemas_describe_the_result() {
+        let body = describe_result(&[("total", "Nullable(Int64)", "")]);
+        let (url, server) = serve(vec![response("200 OK", &[], &body)]);
+        let client = Client::new(&url).unwrap();
+        let schema = Schema::from_query(&client, "SELECT sum(x) AS total FROM t;").unwrap();
+        assert_eq!(
+            schema,
+            Schema::from_type_strings(&[("total", "Nullable(Int64)")]).unwrap()
+        );
+        assert_eq!(
+            server.join().unwrap()[0].body,
+            b"DESCRIBE (SELECT sum(x) AS total FROM t) FORMAT RowBinaryWithNamesAndTypes"
+        );
+    }
+}
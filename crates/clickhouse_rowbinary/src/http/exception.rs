@@ -0,0 +1,222 @@
+//! Detection of exceptions the server writes into a streamed response.
+//!
+//! Once a response has started with status 200, the server can no longer
+//! report a failure through the status line, so it appends the exception to
+//! the body instead. Servers that send an `X-ClickHouse-Exception-Tag`
+//! header frame it as
+//!
+//! ```text
+//! __exception__\r\n<tag>\r\n<message>\n<message length> <tag>\r\n__exception__\r\n
+//! ```
+//!
+//! while older servers append the bare `Code: N. DB::Exception: ...` text.
+
+use crate::error::Result;
+
+use super::{MAX_ERROR_BODY, server_error};
+
+/// Header carrying the tag that frames a mid-stream exception.
+pub(crate) const EXCEPTION_TAG_HEADER: &str = "X-ClickHouse-Exception-Tag";
+
+const MARKER: &[u8] = b"__exception__\r\n";
+/// Start of the text older servers append.
+const LEGACY_PREFIX: &[u8] = b"Code: ";
+const LEGACY_EXCEPTION: &[u8] = b". DB::Exception:";
+
+/// Buffer between a response body and its reader that holds back the bytes
+/// that may turn out to be an exception until the body ends.
+///
+/// With a tag only a possible partial marker is held back; without one the
+/// last [`MAX_ERROR_BODY`] bytes are, since the bare exception text is only
+/// recognizable once nothing follows it.
+pub(crate) struct ExceptionGuard {
+    /// `__exception__\r\n<tag>\r\n`, when the server sent a tag.
+    start_marker: Option<Vec<u8>>,
+    buffer: Vec<u8>,
+    /// Start of the bytes not yet consumed.
+    pos: usize,
+    /// Start of the tagged exception, once its marker has arrived.
+    exception_start: Option<usize>,
+    /// Set once the body has ended without an exception.
+    finished: bool,
+}
+
+impl ExceptionGuard {
+    pub(crate) fn new(tag: Option<&str>) -> Self {
+        Self {
+            start_marker: tag.map(|tag| [MARKER, tag.as_bytes(), b"\r\n"].concat()),
+            buffer: Vec::new(),
+            pos: 0,
+            exception_start: None,
+            finished: false,
+        }
+    }
+
+    /// Appends a chunk of the body.
+    pub(crate) fn feed(&mut self, chunk: &[u8]) {
+        if self.pos > 0 && self.pos * 2 >= self.buffer.len() {
+            self.buffer.drain(..self.pos);
+            self.exception_start = self.exception_start.map(|start| start - self.pos);
+            self.pos = 0;
+        }
+        let searched = self.buffer.len();
+        self.buffer.extend_from_slice(chunk);
+        if let Some(marker) = &self.start_marker
+            && self.exception_start.is_none()
+        {
+            // Look again at the tail that may hold a partial marker.
+            let from = searched.saturating_sub(marker.len() - 1).max(self.pos);
+            self.exception_start = find(&self.buffer[from..], marker).map(|at| from + at);
+        }
+    }
+
+    /// Returns the bytes that can be handed to the reader.
+    pub(crate) fn available(&self) -> &[u8] {
+        let end = if self.finished {
+            self.buffer.len()
+        } else if let Some(start) = self.exception_start {
+            start
+        } else {
+            let hold = self
+                .start_marker
+                .as_ref()
+                .map_or(MAX_ERROR_BODY, |marker| marker.len() - 1);
+            self.buffer.len().saturating_sub(hold)
+        };
+        &self.buffer[self.pos..end.max(self.pos)]
+    }
+
+    /// Marks `amt` bytes of [`Self::available`] as consumed.
+    pub(crate) fn consume(&mut self, amt: usize) {
+        self.pos += amt;
+    }
+
+    /// Signals the end of the body, releasing the held-back bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the exception found at the end of the body, as
+    /// [`crate::Error::Server`] when it carries an error code.
+    pub(crate) fn finish(&mut self) -> Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        let held = &self.buffer[self.pos..];
+        let exception = match (&self.start_marker, self.exception_start) {
+            (Some(marker), Some(start)) => {
+                let message = &self.buffer[start + marker.len()..];
+                Some(strip_trailer(message, &marker[MARKER.len()..]))
+            }
+            (Some(_), None) => None,
+            (None, _) => find_legacy(held).map(|start| &held[start..]),
+        };
+        if let Some(message) = exception {
+            return Err(server_error(200, None, message));
+        }
+        self.finished = true;
+        Ok(())
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Removes the `\n<length> <tag>\r\n__exception__\r\n` trailer after a
+/// tagged message; `tag_line` is `<tag>\r\n`.
+fn strip_trailer<'a>(message: &'a [u8], tag_line: &[u8]) -> &'a [u8] {
+    let Some(rest) = message.strip_suffix(MARKER) else {
+        return message;
+    };
+    let Some(rest) = rest.strip_suffix(tag_line) else {
+        return message;
+    };
+    let Some(rest) = rest.strip_suffix(b" ") else {
+        return message;
+    };
+    let digits = rest
+        .iter()
+        .rev()
+        .take_while(|byte| byte.is_ascii_digit())
+        .count();
+    rest[..rest.len() - digits]
+        .strip_suffix(b"\n")
+        .unwrap_or(message)
+}
+
+/// Finds the last `Code: N. DB::Exception:` text that runs to the end of
+/// `held`.
+fn find_legacy(held: &[u8]) -> Option<usize> {
+    (0..held.len()).rev().find(|&start| {
+        let Some(rest) = held[start..].strip_prefix(LEGACY_PREFIX) else {
+            return false;
+        };
+        let digits = rest.iter().take_while(|byte| byte.is_ascii_digit()).count();
+        digits > 0 && rest[digits..].starts_with(LEGACY_EXCEPTION)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ExceptionGuard;
+    use crate::error::Error;
+
+    /// Feeds `chunks` and drains the guard, returning the released bytes
+    /// and the outcome at the end of the body.
+    fn drain(guard: &mut ExceptionGuard, chunks: &[&[u8]]) -> (Vec<u8>, Result<(), Error>) {
+        let mut out = Vec::new();
+        for chunk in chunks {
+            guard.feed(chunk);
+            let available = guard.available().to_vec();
+            guard.consume(available.len());
+            out.extend_from_slice(&available);
+        }
+        let result = guard.finish();
+        out.extend_from_slice(guard.available());
+        (out, result)
+    }
+
+    #[test]
+    fn tagged_exceptions_are_cut_from_the_body() {
+        let message = "Code: 395. DB::Exception: Value passed to 'throwIf' function is non-zero. (FUNCTION_THROW_IF_VALUE_IS_NON_ZERO)";
+        let body = format!(
+            "rows__exception__\r\nabc123\r\n{message}\n{} abc123\r\n__exception__\r\n",
+            message.len()
+        );
+        // Split the marker across chunks.
+        let (head, tail) = body.as_bytes().split_at(10);
+        let (released, result) = drain(&mut ExceptionGuard::new(Some("abc123")), &[head, tail]);
+        assert_eq!(released, b"rows");
+        assert!(
+            matches!(&result, Err(Error::Server { code: 395, message: text }) if text == message),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn legacy_exception_text_is_detected_at_the_end() {
+        let mut body = vec![0_u8; 100_000];
+        body.extend_from_slice(
+            b"Code: 241. DB::Exception: Memory limit exceeded. (MEMORY_LIMIT_EXCEEDED)\n",
+        );
+        let (released, result) = drain(&mut ExceptionGuard::new(None), &[&body]);
+        assert!(released.len() < 100_000);
+        assert!(
+            matches!(result, Err(Error::Server { code: 241, .. })),
+            "{result:?}"
+        );
+    }
+
+    #[test]
+    fn clean_bodies_pass_through() {
+        let body = b"Code: 1 mentioned in data, __exception__\r\n with another tag";
+        for tag in [None, Some("abc123")] {
+            let mut guard = ExceptionGuard::new(tag);
+            let (released, result) = drain(&mut guard, &[&body[..7], &body[7..]]);
+            assert!(result.is_ok());
+            assert_eq!(released, body);
+        }
+    }
+}
@@ -0,0 +1,205 @@
+//! External data: in-memory tables sent along with a query.
+//!
+//! The HTTP interface reads them from a `multipart/form-data` body, one
+//! part per table, with each table's structure and format in the
+//! `<name>_structure` and `<name>_format` URL parameters. The statement
+//! then travels in the `query` URL parameter.
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema},
+    value::Value,
+};
+
+use super::unique_id;
+
+/// In-memory table sent along with a query, which reads it like a
+/// temporary table, as in `WHERE id IN wanted`.
+///
+/// Large `IN` lists shipped this way are parsed as `RowBinary` rather than
+/// as SQL.
+///
+/// ```
+/// # use clickhouse_rowbinary::{Schema, Value, http::{ExternalTable, Query}};
+/// let schema = Schema::from_type_strings(&[("id", "UInt64")])?;
+/// let wanted = ExternalTable::new("wanted", &schema, (0..10_000).map(|id| [Value::UInt64(id)]))?;
+/// let query = Query::new("SELECT name FROM people WHERE id IN wanted").external(wanted);
+/// # Ok::<(), clickhouse_rowbinary::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExternalTable {
+    name: String,
+    schema: Schema,
+    data: Vec<u8>,
+}
+
+impl ExternalTable {
+    /// Encodes `rows` as a table named `name` with the columns of
+    /// `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `name` is not a plain
+    /// identifier, or [`crate::error::Error`] when a row does not match the
+    /// schema.
+    pub fn new<I, R>(name: impl Into<String>, schema: &Schema, rows: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+        for row in rows {
+            writer.write_row(row.as_ref())?;
+        }
+        Self::from_row_binary(name, schema, writer.into_inner())
+    }
+
+    /// Wraps rows already encoded as `RowBinary` with the columns of
+    /// `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `name` is not a plain
+    /// identifier.
+    pub fn from_row_binary(
+        name: impl Into<String>,
+        schema: &Schema,
+        data: Vec<u8>,
+    ) -> Result<Self> {
+        let name = name.into();
+        let valid = name
+            .bytes()
+            .next()
+            .is_some_and(|first| !first.is_ascii_digit())
+            && name
+                .bytes()
+                .all(|byte| byte.is_ascii_alphanumeric() || byte == b'_');
+        if !valid {
+            return Err(Error::InvalidValue(
+                "external table name must be a plain identifier",
+            ));
+        }
+        Ok(Self {
+            name,
+            schema: schema.clone(),
+            data,
+        })
+    }
+
+    /// Returns the name the query refers to the table by.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns the columns of the table.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+}
+
+/// URL parameters describing `tables`.
+pub(crate) fn external_params(tables: &[ExternalTable]) -> Vec<(String, String)> {
+    tables
+        .iter()
+        .flat_map(|table| {
+            [
+                (
+                    format!("{}_structure", table.name),
+                    table.schema.column_definitions(),
+                ),
+                (
+                    format!("{}_format", table.name),
+                    RowBinaryFormat::RowBinary.to_string(),
+                ),
+            ]
+        })
+        .collect()
+}
+
+/// Builds the `multipart/form-data` body carrying `tables`, returning its
+/// content type and bytes.
+pub(crate) fn multipart(tables: &[ExternalTable]) -> (String, Vec<u8>) {
+    let boundary = loop {
+        let boundary = format!("----{}", unique_id());
+        // Binary rows could contain the boundary by chance.
+        let clashes = tables.iter().any(|table| {
+            table
+                .data
+                .windows(boundary.len())
+                .any(|window| window == boundary.as_bytes())
+        });
+        if !clashes {
+            break boundary;
+        }
+    };
+    let mut body = Vec::new();
+    for table in tables {
+        let head = format!(
+            "--{boundary}\r\nContent-Disposition: form-data; name=\"{0}\"; filename=\"{0}\"\r\n\
+             Content-Type: application/octet-stream\r\n\r\n",
+            table.name
+        );
+        body.extend_from_slice(head.as_bytes());
+        body.extend_from_slice(&table.data);
+        body.extend_from_slice(b"\r\n");
+    }
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+    (format!("multipart/form-data; boundary={boundary}"), body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ExternalTable, external_params, multipart};
+    use crate::{rowbinary::Schema, value::Value};
+
+    #[test]
+    fn tables_become_form_parts_and_structure_params() {
+        let schema = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+        let table = ExternalTable::new(
+            "wanted",
+            &schema,
+            [[Value::UInt8(7), Value::String(b"ada".to_vec())]],
+        )
+        .unwrap();
+        assert_eq!(
+            external_params(std::slice::from_ref(&table)),
+            [
+                (
+                    "wanted_structure".to_string(),
+                    "`id` UInt8, `name` String".to_string()
+                ),
+                ("wanted_format".to_string(), "RowBinary".to_string()),
+            ]
+        );
+
+        let (content_type, body) = multipart(&[table]);
+        let boundary = content_type
+            .strip_prefix("multipart/form-data; boundary=")
+            .unwrap();
+        let expected = [
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"wanted\"; \
+                 filename=\"wanted\"\r\nContent-Type: application/octet-stream\r\n\r\n"
+            )
+            .as_bytes(),
+            b"\x07\x03ada\r\n",
+            format!("--{boundary}--\r\n").as_bytes(),
+        ]
+        .concat();
+        assert_eq!(body, expected);
+    }
+
+    #[test]
+    fn names_must_be_identifiers() {
+        let schema = Schema::from_type_strings(&[("id", "UInt8")]).unwrap();
+        for name in ["", "1st", "a b", "a\"b"] {
+            assert!(
+                ExternalTable::from_row_binary(name, &schema, Vec::new()).is_err(),
+                "{name}"
+            );
+        }
+    }
+}
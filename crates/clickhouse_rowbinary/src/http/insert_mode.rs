@@ -0,0 +1,154 @@
+//! Synchronous and asynchronous inserts, and what their answers confirm.
+
+use super::{ClientOptions, Progress, Query};
+
+/// Setting that queues inserts in the server's async insert buffer.
+const ASYNC_INSERT: &str = "async_insert";
+/// Setting that holds the answer to an async insert until its buffer is
+/// flushed.
+const WAIT_FOR_ASYNC_INSERT: &str = "wait_for_async_insert";
+
+/// How the server takes in the rows of inserts.
+///
+/// Async inserts collect the rows of many small inserts, from any number
+/// of clients, in a server-side buffer that is written into the table as
+/// one part, which spares `MergeTree` tables the parts of every small
+/// insert. The modes set `async_insert` and `wait_for_async_insert`,
+/// unless a [`Query`] sets `async_insert` itself.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InsertMode {
+    /// Inserts are written as they arrive, under the server's own
+    /// `async_insert` default.
+    #[default]
+    Sync,
+    /// Inserts are buffered, and answered once the buffer holding them
+    /// is written into the table.
+    AsyncWait,
+    /// Inserts are buffered and answered at once, before they are written.
+    /// Rows are lost if the server stops before flushing its buffer, and a
+    /// failure to write them shows only in
+    /// `system.asynchronous_insert_log`.
+    AsyncNoWait,
+}
+
+impl InsertMode {
+    /// Returns `query` with the settings of the mode.
+    pub(crate) fn apply(self, query: Query) -> Query {
+        if query
+            .settings()
+            .iter()
+            .any(|(name, _)| name == ASYNC_INSERT)
+        {
+            return query;
+        }
+        match self {
+            Self::Sync => query,
+            Self::AsyncWait => query
+                .setting(ASYNC_INSERT, 1)
+                .setting(WAIT_FOR_ASYNC_INSERT, 1),
+            Self::AsyncNoWait => query
+                .setting(ASYNC_INSERT, 1)
+                .setting(WAIT_FOR_ASYNC_INSERT, 0),
+        }
+    }
+}
+
+/// Reports whether the server answers an insert sent as `query` by a
+/// client with `options` before writing its rows.
+pub(crate) fn queued(options: &ClientOptions, query: &Query) -> bool {
+    let setting = |name: &str| {
+        query
+            .settings()
+            .iter()
+            .chain(&options.settings)
+            .find(|(set, _)| set == name)
+            .map(|(_, value)| value.trim())
+    };
+    let enabled = |value: &str| value == "1" || value.eq_ignore_ascii_case("true");
+    setting(ASYNC_INSERT).is_some_and(enabled)
+        && setting(WAIT_FOR_ASYNC_INSERT).is_some_and(|value| !enabled(value))
+}
+
+/// What the server confirmed when it answered an insert.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Inserted {
+    /// The rows are in the table: the server wrote them, or the async
+    /// insert buffer holding them, before answering. Carries the summary
+    /// the server reported.
+    Written(Option<Progress>),
+    /// The server queued the rows in its async insert buffer and answered
+    /// before writing them; see [`InsertMode::AsyncNoWait`].
+    Queued,
+}
+
+impl Inserted {
+    /// Returns the outcome of an insert the server answered with `summary`,
+    /// given whether it was [`queued`].
+    pub(crate) fn answered(queued: bool, summary: Option<Progress>) -> Self {
+        if queued {
+            Self::Queued
+        } else {
+            Self::Written(summary)
+        }
+    }
+
+    /// Reports whether the rows are in the table.
+    #[must_use]
+    pub fn is_written(&self) -> bool {
+        matches!(self, Self::Written(_))
+    }
+
+    /// Returns the summary of a written insert.
+    #[must_use]
+    pub fn summary(&self) -> Option<Progress> {
+        match self {
+            Self::Written(summary) => *summary,
+            Self::Queued => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{InsertMode, Inserted, queued};
+    use crate::http::{ClientOptions, Progress, Query};
+
+    fn answered(options: &ClientOptions, query: &Query) -> Inserted {
+        Inserted::answered(queued(options, query), Some(Progress::default()))
+    }
+
+    #[test]
+    fn only_unawaited_async_inserts_are_queued() {
+        let options = ClientOptions::default();
+        let insert = || Query::new("INSERT INTO t FORMAT RowBinary");
+        assert_eq!(
+            answered(&options, &InsertMode::Sync.apply(insert())),
+            Inserted::Written(Some(Progress::default()))
+        );
+        assert!(answered(&options, &InsertMode::AsyncWait.apply(insert())).is_written());
+        assert_eq!(
+            answered(&options, &InsertMode::AsyncNoWait.apply(insert())),
+            Inserted::Queued
+        );
+        assert_eq!(Inserted::Queued.summary(), None);
+
+        let options = ClientOptions {
+            settings: vec![
+                ("async_insert".into(), "true".into()),
+                ("wait_for_async_insert".into(), "0".into()),
+            ],
+            ..ClientOptions::default()
+        };
+        assert_eq!(answered(&options, &insert()), Inserted::Queued);
+        let waiting = insert().setting("wait_for_async_insert", 1);
+        assert!(answered(&options, &waiting).is_written());
+    }
+
+    #[test]
+    fn queries_keep_their_own_async_insert_setting() {
+        let sync = InsertMode::AsyncNoWait
+            .apply(Query::new("INSERT INTO t FORMAT RowBinary").setting("async_insert", 0));
+        assert_eq!(sync.settings(), [("async_insert".into(), "0".into())]);
+        assert!(answered(&ClientOptions::default(), &sync).is_written());
+    }
+}
@@ -0,0 +1,92 @@
+//! Minimal HTTP server for client unit tests.
+
+use std::{
+    fmt::Write as _,
+    io::{BufRead, BufReader, Write},
+    net::TcpListener,
+    thread::{self, JoinHandle},
+};
+
+/// A request received by [`serve`].
+pub(crate) struct Request {
+    /// Request line and headers, with header names lowercased.
+    pub(crate) head: String,
+    pub(crate) body: Vec<u8>,
+}
+
+/// Answers one connection per canned response and returns the requests
+/// it received.
+pub(crate) fn serve(responses: Vec<Vec<u8>>) -> (String, JoinHandle<Vec<Request>>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}", listener.local_addr().unwrap());
+    let handle = thread::spawn(move || {
+        responses
+            .into_iter()
+            .map(|response| {
+                let (stream, _) = listener.accept().unwrap();
+                let mut reader = BufReader::new(stream);
+                let request = read_request(&mut reader);
+                reader.get_mut().write_all(&response).unwrap();
+                request
+            })
+            .collect()
+    });
+    (url, handle)
+}
+
+/// Returns the URL of a local port nothing listens on, so connections to
+/// it are refused.
+pub(crate) fn refused() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    format!("http://{}", listener.local_addr().unwrap())
+}
+
+fn read_request(reader: &mut impl BufRead) -> Request {
+    let mut head = String::new();
+    loop {
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        if line == "\r\n" {
+            break;
+        }
+        match line.split_once(':') {
+            Some((name, value)) => write!(head, "{}:{value}", name.to_lowercase()).unwrap(),
+            None => head.push_str(&line),
+        }
+    }
+    let header = |name: &str| {
+        head.lines()
+            .find_map(|line| line.strip_prefix(name))
+            .map(str::trim)
+    };
+    let mut body = Vec::new();
+    if let Some(len) = header("content-length:") {
+        body.resize(len.parse().unwrap(), 0);
+        reader.read_exact(&mut body).unwrap();
+    } else if header("transfer-encoding:") == Some("chunked") {
+        loop {
+            let mut size = String::new();
+            reader.read_line(&mut size).unwrap();
+            let size = usize::from_str_radix(size.trim(), 16).unwrap();
+            let mut chunk = vec![0; size + 2];
+            reader.read_exact(&mut chunk).unwrap();
+            if size == 0 {
+                break;
+            }
+            body.extend_from_slice(&chunk[..size]);
+        }
+    }
+    Request { head, body }
+}
+
+/// Builds a response that closes its connection.
+pub(crate) fn response(status: &str, headers: &[(&str, &str)], body: &[u8]) -> Vec<u8> {
+    let mut out = format!("HTTP/1.1 {status}\r\nConnection: close\r\n");
+    for (name, value) in headers {
+        write!(out, "{name}: {value}\r\n").unwrap();
+    }
+    write!(out, "Content-Length: {}\r\n\r\n", body.len()).unwrap();
+    let mut out = out.into_bytes();
+    out.extend_from_slice(body);
+    out
+}
@@ -0,0 +1,119 @@
+//! Query statistics reported in response headers.
+
+use std::time::Duration;
+
+use crate::jsoneachrow::json::{self, Json};
+
+/// Header carrying the statistics of the query once its response starts.
+pub(crate) const SUMMARY_HEADER: &str = "X-ClickHouse-Summary";
+/// Header the server repeats while a query runs, when
+/// `send_progress_in_http_headers` is set.
+pub(crate) const PROGRESS_HEADER: &str = "X-ClickHouse-Progress";
+
+/// Rows and bytes a query has processed, as reported by the server.
+///
+/// The HTTP interface sends these in headers, which precede the result
+/// body, so they describe the query up to the moment its response started.
+/// That covers all the work of an insert or a statement without result
+/// rows, while a `SELECT` that streams its result reports only what ran
+/// before the first rows unless it is sent with `wait_end_of_query = 1`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Rows read from tables.
+    pub read_rows: u64,
+    /// Uncompressed bytes read from tables.
+    pub read_bytes: u64,
+    /// Rows written into tables.
+    pub written_rows: u64,
+    /// Uncompressed bytes written into tables.
+    pub written_bytes: u64,
+    /// Estimate of the rows the query reads in total.
+    pub total_rows_to_read: u64,
+    /// Rows in the result.
+    pub result_rows: u64,
+    /// Bytes of the result.
+    pub result_bytes: u64,
+    /// Time the query has run; zero from servers that do not report it.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Parses the last of the header `values`, such as
+    /// `{"read_rows":"10","read_bytes":"80",...}`.
+    ///
+    /// Counters are sent as quoted or bare numbers and unknown ones are
+    /// ignored; a malformed header yields `None` rather than failing the
+    /// query.
+    pub(crate) fn from_headers<'a>(values: impl Iterator<Item = &'a [u8]>) -> Option<Self> {
+        let Json::Object(members) = json::parse(values.last()?).ok()? else {
+            return None;
+        };
+        let mut progress = Self::default();
+        for (name, value) in &members {
+            let value = match value {
+                Json::Number(text) => text.parse().ok()?,
+                Json::String(text) => std::str::from_utf8(text).ok()?.parse().ok()?,
+                _ => return None,
+            };
+            match &name[..] {
+                b"read_rows" => progress.read_rows = value,
+                b"read_bytes" => progress.read_bytes = value,
+                b"written_rows" => progress.written_rows = value,
+                b"written_bytes" => progress.written_bytes = value,
+                b"total_rows_to_read" => progress.total_rows_to_read = value,
+                b"result_rows" => progress.result_rows = value,
+                b"result_bytes" => progress.result_bytes = value,
+                b"elapsed_ns" => progress.elapsed = Duration::from_nanos(value),
+                _ => {}
+            }
+        }
+        Some(progress)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Progress;
+
+    #[test]
+    fn the_last_header_is_parsed() {
+        let first = br#"{"read_rows":"1","read_bytes":"8"}"#;
+        let last = br#"{"read_rows":"10","read_bytes":"80","written_rows":"3","written_bytes":"24","total_rows_to_read":"10","result_rows":"3","result_bytes":"120","elapsed_ns":"1500","memory_usage":"4096"}"#;
+        let progress = Progress::from_headers([&first[..], &last[..]].into_iter()).unwrap();
+        assert_eq!(
+            progress,
+            Progress {
+                read_rows: 10,
+                read_bytes: 80,
+                written_rows: 3,
+                written_bytes: 24,
+                total_rows_to_read: 10,
+                result_rows: 3,
+                result_bytes: 120,
+                elapsed: Duration::from_nanos(1500),
+            }
+        );
+    }
+
+    #[test]
+    fn missing_and_malformed_headers_yield_none() {
+        assert_eq!(Progress::from_headers(std::iter::empty()), None);
+        assert_eq!(
+            Progress::from_headers([&b"read_rows=1"[..]].into_iter()),
+            None
+        );
+        assert_eq!(
+            Progress::from_headers([&br#"{"read_rows":"-1"}"#[..]].into_iter()),
+            None
+        );
+        assert_eq!(
+            Progress::from_headers([&br#"{"read_rows":7}"#[..]].into_iter()),
+            Some(Progress {
+                read_rows: 7,
+                ..Progress::default()
+            })
+        );
+    }
+}
@@ -0,0 +1,394 @@
+//! Statements with bound parameters.
+
+use std::{fmt::Display, ops::Range};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::RowBinaryFormat,
+    text::literal::write_literal,
+    tsv::write_field,
+    types::parse_type_desc,
+    value::Value,
+};
+
+use super::{
+    ExternalTable, quote_identifier, retry::DEDUPLICATION_TOKEN, set_setting, trim_statement,
+};
+
+/// Placeholder type that substitutes a table or column name.
+const IDENTIFIER_TYPE: &str = "Identifier";
+
+/// SQL statement with parameters bound to its placeholders and the
+/// settings it runs with.
+///
+/// Placeholders use the server's `{name:Type}` syntax, and each bound
+/// [`Value`] is rendered in the text form the server parses for the
+/// placeholder's type, so untrusted input never becomes SQL. Settings are
+/// sent as URL parameters and override those of
+/// [`ClientOptions::settings`](super::ClientOptions::settings). Attached
+/// [`ExternalTable`]s are sent in the request body. `&str` and `String`
+/// convert into a query without parameters or settings.
+///
+/// ```
+/// # use clickhouse_rowbinary::{Value, http::Query};
+/// let query = Query::new("SELECT name FROM {table:Identifier} WHERE id = {id:UInt64}")
+///     .param("table", Value::String(b"people".to_vec()))
+///     .param("id", Value::UInt64(5));
+/// assert_eq!(
+///     query.inlined_sql()?,
+///     "SELECT name FROM `people` WHERE id = CAST(5 AS UInt64)"
+/// );
+/// # Ok::<(), clickhouse_rowbinary::Error>(())
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Query {
+    pub(super) sql: String,
+    params: Vec<(String, Value)>,
+    settings: Vec<(String, String)>,
+    externals: Vec<ExternalTable>,
+    id: Option<String>,
+}
+
+impl Query {
+    /// Creates a query without parameters.
+    #[must_use]
+    pub fn new(sql: impl Into<String>) -> Self {
+        Self {
+            sql: sql.into(),
+            params: Vec::new(),
+            settings: Vec::new(),
+            externals: Vec::new(),
+            id: None,
+        }
+    }
+
+    /// Binds `value` to the `{name:Type}` placeholders, replacing any value
+    /// bound to `name` before.
+    #[must_use]
+    pub fn param(mut self, name: impl Into<String>, value: Value) -> Self {
+        let name = name.into();
+        self.params.retain(|(bound, _)| *bound != name);
+        self.params.push((name, value));
+        self
+    }
+
+    /// Runs the query with setting `name`, such as `max_execution_time`,
+    /// set to `value`, replacing any value set before.
+    #[must_use]
+    pub fn setting(mut self, name: impl Into<String>, value: impl Display) -> Self {
+        set_setting(&mut self.settings, name, value);
+        self
+    }
+
+    /// Sends `table` along with the query, replacing any table attached
+    /// before under the same name.
+    #[must_use]
+    pub fn external(mut self, table: ExternalTable) -> Self {
+        self.externals
+            .retain(|attached| attached.name() != table.name());
+        self.externals.push(table);
+        self
+    }
+
+    /// Runs the query under `id`, by which the clients' `kill_query` can
+    /// stop it and `system.query_log` records it; the server picks a
+    /// random id otherwise.
+    #[must_use]
+    pub fn with_query_id(mut self, id: impl Into<String>) -> Self {
+        self.id = Some(id.into());
+        self
+    }
+
+    /// Runs the insert under `token`, so the server drops it when it
+    /// already stored a batch under the same token; see
+    /// [`Deduplication`](super::Deduplication).
+    #[must_use]
+    pub fn with_deduplication_token(self, token: impl Into<String>) -> Self {
+        self.setting(DEDUPLICATION_TOKEN, token.into())
+    }
+
+    /// Returns the statement text, placeholders included.
+    #[must_use]
+    pub fn sql(&self) -> &str {
+        &self.sql
+    }
+
+    /// Returns the settings as name and value pairs.
+    #[must_use]
+    pub fn settings(&self) -> &[(String, String)] {
+        &self.settings
+    }
+
+    /// Returns the id set with [`Self::with_query_id`].
+    #[must_use]
+    pub fn query_id(&self) -> Option<&str> {
+        self.id.as_deref()
+    }
+
+    /// Returns the attached external tables.
+    #[must_use]
+    pub fn externals(&self) -> &[ExternalTable] {
+        &self.externals
+    }
+
+    /// Renders the bound parameters as the `param_<name>` URL parameters
+    /// the HTTP interface substitutes into the placeholders.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a parameter has no placeholder
+    /// or its value does not match the placeholder's type.
+    pub fn url_params(&self) -> Result<Vec<(String, String)>> {
+        let placeholders = placeholders(&self.sql);
+        self.params
+            .iter()
+            .map(|(name, value)| {
+                let ty = placeholder_type(&placeholders, name)?;
+                let mut text = Vec::new();
+                if ty == IDENTIFIER_TYPE {
+                    text.extend_from_slice(identifier(value)?.as_bytes());
+                } else {
+                    write_field(&parse_type_desc(ty)?, value, &mut text)?;
+                }
+                let text = String::from_utf8(text)
+                    .map_err(|_| Error::InvalidValue("query parameter is not valid UTF-8"))?;
+                Ok((format!("param_{name}"), text))
+            })
+            .collect()
+    }
+
+    /// Returns the statement with each placeholder replaced by its value as
+    /// an escaped literal cast to the placeholder's type, for transports
+    /// without server-side parameters and for logging.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a placeholder or parameter is
+    /// unbound or a value does not match its placeholder's type.
+    pub fn inlined_sql(&self) -> Result<String> {
+        let placeholders = placeholders(&self.sql);
+        for (name, _) in &self.params {
+            placeholder_type(&placeholders, name)?;
+        }
+        let mut out = Vec::with_capacity(self.sql.len());
+        let mut copied = 0;
+        for placeholder in &placeholders {
+            let value = self
+                .params
+                .iter()
+                .find_map(|(name, value)| (*name == placeholder.name).then_some(value))
+                .ok_or(Error::InvalidValue(
+                    "query placeholder has no bound parameter",
+                ))?;
+            out.extend_from_slice(&self.sql.as_bytes()[copied..placeholder.range.start]);
+            if placeholder.ty == IDENTIFIER_TYPE {
+                out.extend_from_slice(quote_identifier(identifier(value)?).as_bytes());
+            } else {
+                out.extend_from_slice(b"CAST(");
+                write_literal(&parse_type_desc(&placeholder.ty)?, value, &mut out)?;
+                out.extend_from_slice(b" AS ");
+                out.extend_from_slice(placeholder.ty.as_bytes());
+                out.push(b')');
+            }
+            copied = placeholder.range.end;
+        }
+        out.extend_from_slice(&self.sql.as_bytes()[copied..]);
+        String::from_utf8(out)
+            .map_err(|_| Error::InvalidValue("query parameter is not valid UTF-8"))
+    }
+
+    /// Appends a `FORMAT` clause to the statement.
+    pub(super) fn with_format(mut self, format: RowBinaryFormat) -> Self {
+        self.sql = format!("{} FORMAT {format}", trim_statement(&self.sql));
+        self
+    }
+}
+
+impl From<&str> for Query {
+    fn from(sql: &str) -> Self {
+        Self::new(sql)
+    }
+}
+
+impl From<String> for Query {
+    fn from(sql: String) -> Self {
+        Self::new(sql)
+    }
+}
+
+/// A `{name:Type}` placeholder in a statement.
+struct Placeholder {
+    name: String,
+    ty: String,
+    /// Byte range of the placeholder, braces included.
+    range: Range<usize>,
+}
+
+fn placeholder_type<'a>(placeholders: &'a [Placeholder], name: &str) -> Result<&'a str> {
+    placeholders
+        .iter()
+        .find(|placeholder| placeholder.name == name)
+        .map(|placeholder| placeholder.ty.as_str())
+        .ok_or(Error::InvalidValue(
+            "query parameter has no {name:Type} placeholder",
+        ))
+}
+
+fn identifier(value: &Value) -> Result<&str> {
+    match value {
+        Value::String(bytes) => std::str::from_utf8(bytes)
+            .map_err(|_| Error::InvalidValue("identifier is not valid UTF-8")),
+        other => Err(Error::TypeMismatch {
+            expected: IDENTIFIER_TYPE.to_string(),
+            actual: other.type_name().to_string(),
+        }),
+    }
+}
+
+/// Finds the placeholders outside string literals, quoted identifiers and
+/// comments.
+fn placeholders(sql: &str) -> Vec<Placeholder> {
+    let bytes = sql.as_bytes();
+    let mut found = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            quote @ (b'\'' | b'"' | b'`') => pos = skip_quoted(bytes, pos + 1, quote),
+            b'-' if bytes.get(pos + 1) == Some(&b'-') => {
+                pos = bytes[pos..]
+                    .iter()
+                    .position(|&byte| byte == b'\n')
+                    .map_or(bytes.len(), |end| pos + end + 1);
+            }
+            b'/' if bytes.get(pos + 1) == Some(&b'*') => {
+                pos = bytes[pos + 2..]
+                    .windows(2)
+                    .position(|window| window == b"*/")
+                    .map_or(bytes.len(), |end| pos + 2 + end + 2);
+            }
+            b'{' => match parse_placeholder(sql, pos) {
+                Some(placeholder) => {
+                    pos = placeholder.range.end;
+                    found.push(placeholder);
+                }
+                None => pos += 1,
+            },
+            _ => pos += 1,
+        }
+    }
+    found
+}
+
+/// Returns the position after the quoted text that starts at `pos`.
+fn skip_quoted(bytes: &[u8], mut pos: usize, quote: u8) -> usize {
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'\\' => pos += 2,
+            byte if byte == quote => {
+                // A doubled quote stands for itself.
+                if bytes.get(pos + 1) == Some(&quote) {
+                    pos += 2;
+                } else {
+                    return pos + 1;
+                }
+            }
+            _ => pos += 1,
+        }
+    }
+    bytes.len()
+}
+
+/// Parses `{name:Type}` at `start`; other braces, such as map literals,
+/// yield `None`.
+fn parse_placeholder(sql: &str, start: usize) -> Option<Placeholder> {
+    let bytes = sql.as_bytes();
+    let name_start = start + 1;
+    let name_len = bytes[name_start..]
+        .iter()
+        .take_while(|byte| byte.is_ascii_alphanumeric() || **byte == b'_')
+        .count();
+    let colon = name_start + name_len;
+    if name_len == 0 || bytes[name_start].is_ascii_digit() || bytes.get(colon) != Some(&b':') {
+        return None;
+    }
+    let mut pos = colon + 1;
+    while pos < bytes.len() {
+        match bytes[pos] {
+            b'}' => {
+                let ty = sql[colon + 1..pos].trim();
+                return (!ty.is_empty()).then(|| Placeholder {
+                    name: sql[name_start..colon].to_string(),
+                    ty: ty.to_string(),
+                    range: start..pos + 1,
+                });
+            }
+            quote @ b'\'' => pos = skip_quoted(bytes, pos + 1, quote),
+            b'{' => return None,
+            _ => pos += 1,
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Query, placeholders};
+    use crate::{error::Error, value::Value};
+
+    #[test]
+    fn placeholders_skip_literals_and_comments() {
+        let sql = "SELECT '{a:UInt8}', `{b:UInt8}`, map('k', 1) -- {c:UInt8}\n\
+                   /* {d:UInt8} */ FROM t WHERE x = {e:DateTime('UTC')} AND y IN {f: Array(String)}";
+        let found: Vec<_> = placeholders(sql)
+            .into_iter()
+            .map(|placeholder| (placeholder.name, placeholder.ty))
+            .collect();
+        assert_eq!(
+            found,
+            [
+                ("e".to_string(), "DateTime('UTC')".to_string()),
+                ("f".to_string(), "Array(String)".to_string())
+            ]
+        );
+    }
+
+    #[test]
+    fn url_params_use_the_escaped_text_form() {
+        let query = Query::new("SELECT {s:String}, {a:Array(String)}, {n:Nullable(UInt8)}")
+            .param("s", Value::String(b"it's\ta".to_vec()))
+            .param("a", Value::Array(vec![Value::String(b"x'y".to_vec())]))
+            .param("n", Value::Nullable(None));
+        assert_eq!(
+            query.url_params().unwrap(),
+            [
+                ("param_s".to_string(), "it\\'s\\ta".to_string()),
+                ("param_a".to_string(), "['x\\'y']".to_string()),
+                ("param_n".to_string(), "\\N".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn inlined_sql_casts_escaped_literals() {
+        let query = Query::new("SELECT * FROM t WHERE name = {name:String} LIMIT {n:UInt8}")
+            .param("name", Value::String(b"x' OR 1 = 1 --".to_vec()))
+            .param("n", Value::UInt8(1))
+            .param("n", Value::UInt8(3));
+        assert_eq!(
+            query.inlined_sql().unwrap(),
+            "SELECT * FROM t WHERE name = CAST('x\\' OR 1 = 1 --' AS String) LIMIT CAST(3 AS UInt8)"
+        );
+    }
+
+    #[test]
+    fn mismatched_and_unbound_parameters_are_rejected() {
+        let query = Query::new("SELECT {id:UInt64}").param("id", Value::String(b"1".to_vec()));
+        assert!(matches!(
+            query.url_params(),
+            Err(Error::TypeMismatch { .. })
+        ));
+        let query = Query::new("SELECT {id:UInt64}").param("other", Value::UInt64(1));
+        assert!(query.url_params().is_err());
+        assert!(Query::new("SELECT {id:UInt64}").inlined_sql().is_err());
+    }
+}
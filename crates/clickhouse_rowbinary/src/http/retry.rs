@@ -0,0 +1,298 @@
+//! Retries of requests that failed before the server could answer them.
+
+use std::time::Duration;
+
+use crate::{
+    compression::ChunkedHasher,
+    error::{Error, Result},
+};
+
+/// Statuses proxies and load balancers answer with while no server can
+/// take the request.
+pub(crate) const UNAVAILABLE_STATUSES: &[u16] = &[502, 503, 504];
+/// Leading keywords of statements that only read, so running them twice
+/// is harmless.
+const READ_STATEMENTS: &[&str] = &[
+    "DESC", "DESCRIBE", "EXISTS", "EXPLAIN", "SELECT", "SHOW", "WITH",
+];
+/// Setting that makes the server drop a repeated insert.
+pub(crate) const DEDUPLICATION_TOKEN: &str = "insert_deduplication_token";
+
+/// When and how often the clients send a failed request again.
+///
+/// A request whose connection could not be established is always retried,
+/// since the server never saw it. One answered with status 502, 503 or 504,
+/// as proxies do while no server is available, is retried only when
+/// running it twice is harmless: for reads, and for inserts made with
+/// `insert()` under a [`Deduplication`] token or an
+/// `insert_deduplication_token` setting. Inserts that
+/// stream their body from a reader or a sink cannot be sent again and are
+/// not retried at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first; zero disables retries.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled before each later one.
+    pub initial_backoff: Duration,
+    /// Upper bound on the delay between attempts.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_retries: 3,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(5),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Returns a policy that never retries.
+    #[must_use]
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            ..Self::default()
+        }
+    }
+
+    /// Delay before retry number `retry`, counted from zero.
+    fn backoff(&self, retry: u32) -> Duration {
+        self.initial_backoff
+            .saturating_mul(2_u32.saturating_pow(retry))
+            .min(self.max_backoff)
+    }
+
+    /// Runs `attempt` until it succeeds, fails in a way that rules out
+    /// another attempt, or the retries run out. `replayable` tells whether
+    /// the request may run twice.
+    #[cfg(feature = "http")]
+    pub(crate) fn run<T>(
+        &self,
+        replayable: bool,
+        mut attempt: impl FnMut() -> std::result::Result<T, Failure>,
+    ) -> Result<T> {
+        let mut retry = 0;
+        loop {
+            match attempt() {
+                Ok(value) => return Ok(value),
+                Err(failure) => {
+                    self.allow_retry(failure, replayable, retry)?;
+                    std::thread::sleep(self.backoff(retry));
+                    retry += 1;
+                }
+            }
+        }
+    }
+
+    /// Async counterpart of [`Self::run`].
+    #[cfg(feature = "async-http")]
+    pub(crate) async fn run_async<T, F>(
+        &self,
+        replayable: bool,
+        mut attempt: impl FnMut() -> F,
+    ) -> Result<T>
+    where
+        F: Future<Output = std::result::Result<T, Failure>>,
+    {
+        let mut retry = 0;
+        loop {
+            match attempt().await {
+                Ok(value) => return Ok(value),
+                Err(failure) => {
+                    self.allow_retry(failure, replayable, retry)?;
+                    tokio::time::sleep(self.backoff(retry)).await;
+                    retry += 1;
+                }
+            }
+        }
+    }
+
+    /// Returns the error of `failure` unless the request may be retried.
+    fn allow_retry(&self, failure: Failure, replayable: bool, retry: u32) -> Result<()> {
+        let (err, retryable) = match failure {
+            Failure::Unsent(err) => (err, true),
+            Failure::Unavailable(err) => (err, replayable),
+            Failure::Fatal(err) => (err, false),
+        };
+        if retryable && retry < self.max_retries {
+            Ok(())
+        } else {
+            Err(err)
+        }
+    }
+}
+
+/// A failed attempt at a request, classified by whether it can be retried.
+pub(crate) enum Failure {
+    /// The connection could not be established.
+    Unsent(Error),
+    /// A proxy or the server answered that it is unavailable.
+    Unavailable(Error),
+    /// Any other failure.
+    Fatal(Error),
+}
+
+impl Failure {
+    /// Returns the error, for requests that are not retried.
+    #[cfg(feature = "async-http")]
+    pub(crate) fn into_error(self) -> Error {
+        match self {
+            Self::Unsent(err) | Self::Unavailable(err) | Self::Fatal(err) => err,
+        }
+    }
+}
+
+impl From<Error> for Failure {
+    fn from(err: Error) -> Self {
+        Self::Fatal(err)
+    }
+}
+
+/// How the clients tag inserts with an `insert_deduplication_token`, under
+/// which the server stores a batch sent twice only once.
+///
+/// The server drops repeated batches of `Replicated*MergeTree` tables, and
+/// of other `MergeTree` tables with `non_replicated_deduplication_window`
+/// set. With a token, a replayed `insert()` stores its rows once, so it is
+/// retried even after the server answered with 502, 503 or 504.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum Deduplication {
+    /// Inserts carry no token of their own.
+    #[default]
+    Off,
+    /// Every insert carries this token. The server drops any later batch
+    /// under the same token, so set it on a clone of the client per batch.
+    Token(String),
+    /// Each insert carries a token hashed from its encoded rows, so the
+    /// same rows sent twice to a table are stored once. Rows are encoded
+    /// twice: once to hash them and once to send them. Inserts that stream
+    /// their rows, such as `insert_raw()` and `AsyncClient::insert()`, are
+    /// not known before they are sent and carry no token.
+    PayloadHash,
+}
+
+impl Deduplication {
+    /// Returns the token of an insert whose body `encode` writes, or `None`
+    /// when inserts carry none.
+    pub(crate) fn token(
+        &self,
+        encode: impl FnOnce(&mut ChunkedHasher) -> Result<()>,
+    ) -> Result<Option<String>> {
+        match self {
+            Self::Off => Ok(None),
+            Self::Token(token) => Ok(Some(token.clone())),
+            Self::PayloadHash => {
+                let mut hasher = ChunkedHasher::default();
+                encode(&mut hasher)?;
+                let (low, high) = hasher.finish();
+                Ok(Some(format!("{high:016x}{low:016x}")))
+            }
+        }
+    }
+
+    /// Returns the token of inserts whose body is unknown before they are
+    /// sent.
+    pub(crate) fn streamed_token(&self) -> Option<&str> {
+        match self {
+            Self::Token(token) => Some(token),
+            Self::Off | Self::PayloadHash => None,
+        }
+    }
+}
+
+/// Reports whether `sql` only reads, judging by its first keyword.
+pub(crate) fn is_read(sql: &str) -> bool {
+    let sql = sql.trim_start_matches(|ch: char| ch.is_whitespace() || ch == '(');
+    let keyword = sql
+        .split(|ch: char| !ch.is_ascii_alphabetic())
+        .next()
+        .unwrap_or_default();
+    READ_STATEMENTS
+        .iter()
+        .any(|read| read.eq_ignore_ascii_case(keyword))
+}
+
+/// Reports whether inserts sent with `settings` are deduplicated, so
+/// sending them twice stores the rows once.
+pub(crate) fn deduplicated(settings: &[(String, String)]) -> bool {
+    settings
+        .iter()
+        .any(|(name, value)| name == DEDUPLICATION_TOKEN && !value.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::{Deduplication, RetryPolicy, deduplicated, is_read};
+
+    #[test]
+    fn backoff_doubles_up_to_the_limit() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(1),
+        };
+        let delays: Vec<_> = (0..6).map(|retry| policy.backoff(retry)).collect();
+        assert_eq!(
+            delays,
+            [100, 200, 400, 800, 1000, 1000].map(Duration::from_millis)
+        );
+        assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(1));
+    }
+
+    #[test]
+    fn reads_are_told_from_writes() {
+        for sql in [
+            "SELECT 1",
+            "  with x AS (SELECT 1) SELECT * FROM x",
+            "(SELECT 1) UNION ALL (SELECT 2)",
+            "DESCRIBE TABLE t",
+            "show tables",
+        ] {
+            assert!(is_read(sql), "{sql}");
+        }
+        for sql in [
+            "INSERT INTO t SELECT 1",
+            "CREATE TABLE t (x UInt8) ENGINE = Memory",
+            "SELECTED",
+            "-- comment\nSELECT 1",
+            "",
+        ] {
+            assert!(!is_read(sql), "{sql}");
+        }
+    }
+
+    #[test]
+    fn only_token_bearing_inserts_are_deduplicated() {
+        assert!(deduplicated(&[(
+            "insert_deduplication_token".into(),
+            "batch-7".into()
+        )]));
+        assert!(!deduplicated(&[(
+            "insert_deduplication_token".into(),
+            String::new()
+        )]));
+    }
+
+    #[test]
+    fn payload_tokens_follow_the_payload() {
+        let token = |body: &'static [u8]| {
+            Deduplication::PayloadHash
+                .token(|hasher| Ok(std::io::Write::write_all(hasher, body)?))
+                .unwrap()
+                .unwrap()
+        };
+        assert_eq!(token(b"batch"), token(b"batch"));
+        assert_ne!(token(b"batch"), token(b"other"));
+        assert_eq!(token(b"batch").len(), 32);
+        let fixed = Deduplication::Token("batch-7".into());
+        assert_eq!(fixed.token(|_| unreachable!()).unwrap().unwrap(), "batch-7");
+        assert_eq!(fixed.streamed_token(), Some("batch-7"));
+        assert_eq!(Deduplication::Off.token(|_| unreachable!()).unwrap(), None);
+        assert_eq!(Deduplication::PayloadHash.streamed_token(), None);
+    }
+}
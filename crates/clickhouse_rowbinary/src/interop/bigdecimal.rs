@@ -0,0 +1,164 @@
+//! Conversions between decimal values and `bigdecimal::BigDecimal`.
+
+use ::bigdecimal::{
+    BigDecimal,
+    num_bigint::{BigInt, BigUint},
+};
+
+use super::decimal_parts;
+use crate::{
+    error::{Error, Result},
+    typed::{FromValue, IntoValue, from_value::non_null, into_value::encode_leaf},
+    types::{DecimalSize, TypeDesc},
+    value::Value,
+};
+
+impl Value {
+    /// Converts a decimal value of any width into a [`BigDecimal`] using the
+    /// scale of the column type `ty`, which may be wrapped in `Nullable` or
+    /// `LowCardinality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when the value does not match a
+    /// decimal type.
+    pub fn to_big_decimal(&self, ty: &TypeDesc) -> Result<BigDecimal> {
+        let mismatch = || Error::TypeMismatch {
+            expected: ty.type_name(),
+            actual: self.type_name().to_string(),
+        };
+        let (_, scale, size) = decimal_parts(ty).ok_or_else(mismatch)?;
+        let mantissa = match (size, self) {
+            (DecimalSize::Bits32, Value::Decimal32(value)) => BigInt::from(*value),
+            (DecimalSize::Bits64, Value::Decimal64(value)) => BigInt::from(*value),
+            (DecimalSize::Bits128, Value::Decimal128(value)) => BigInt::from(*value),
+            (DecimalSize::Bits256, Value::Decimal256(bytes)) => BigInt::from_signed_bytes_le(bytes),
+            _ => return Err(mismatch()),
+        };
+        Ok(BigDecimal::new(mantissa, i64::from(scale)))
+    }
+
+    /// Builds a decimal value for the column type `ty` from a
+    /// [`BigDecimal`].
+    ///
+    /// The value is rescaled to the column scale; values with more
+    /// fractional digits than the column keeps are rejected rather than
+    /// rounded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when `ty` is not a decimal type,
+    /// [`Error::InvalidValue`] when rescaling would lose digits, or
+    /// [`Error::Overflow`] when the value has more digits than the column
+    /// precision.
+    pub fn from_big_decimal(value: &BigDecimal, ty: &TypeDesc) -> Result<Self> {
+        let (precision, scale, size) = decimal_parts(ty).ok_or_else(|| Error::TypeMismatch {
+            expected: "Decimal".to_string(),
+            actual: ty.type_name(),
+        })?;
+        let rescaled = value.with_scale(i64::from(scale));
+        if &rescaled != value {
+            return Err(Error::InvalidValue(
+                "decimal has more fractional digits than the column scale",
+            ));
+        }
+        let (mantissa, _) = rescaled.into_bigint_and_exponent();
+        if *mantissa.magnitude() >= BigUint::from(10_u8).pow(u32::from(precision)) {
+            return Err(Error::Overflow("decimal outside column precision"));
+        }
+        let overflow = |_| Error::Overflow("decimal outside column range");
+        Ok(match size {
+            DecimalSize::Bits32 => Value::Decimal32(i32::try_from(&mantissa).map_err(overflow)?),
+            DecimalSize::Bits64 => Value::Decimal64(i64::try_from(&mantissa).map_err(overflow)?),
+            DecimalSize::Bits128 => Value::Decimal128(i128::try_from(&mantissa).map_err(overflow)?),
+            DecimalSize::Bits256 => {
+                let bytes = mantissa.to_signed_bytes_le();
+                if bytes.len() > 32 {
+                    return Err(Error::Overflow("decimal outside column range"));
+                }
+                let fill = if mantissa.sign() == ::bigdecimal::num_bigint::Sign::Minus {
+                    0xFF
+                } else {
+                    0x00
+                };
+                let mut storage = [fill; 32];
+                storage[..bytes.len()].copy_from_slice(&bytes);
+                Value::Decimal256(storage)
+            }
+        })
+    }
+}
+
+impl IntoValue for BigDecimal {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_leaf(ty, "decimal", &|ty| Value::from_big_decimal(self, ty))
+    }
+}
+
+impl FromValue<'_> for BigDecimal {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "BigDecimal")?;
+        value.to_big_decimal(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::str::FromStr;
+
+    use ::bigdecimal::BigDecimal;
+
+    use crate::{error::Error, types::parse_type_desc, value::Value};
+
+    #[test]
+    fn converts_decimals_with_column_scale() {
+        let ty = parse_type_desc("Decimal(18, 4)").unwrap();
+        let value = Value::Decimal64(-1_234_567);
+        let decimal = value.to_big_decimal(&ty).unwrap();
+        assert_eq!(decimal, BigDecimal::from_str("-123.4567").unwrap());
+        assert_eq!(Value::from_big_decimal(&decimal, &ty).unwrap(), value);
+
+        let coarse = BigDecimal::from_str("2.5").unwrap();
+        assert_eq!(
+            Value::from_big_decimal(&coarse, &ty).unwrap(),
+            Value::Decimal64(25_000)
+        );
+        let fine = BigDecimal::from_str("0.00001").unwrap();
+        assert!(matches!(
+            Value::from_big_decimal(&fine, &ty),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_digits_beyond_the_column_precision() {
+        let ty = parse_type_desc("Decimal(9, 2)").unwrap();
+        let too_long = BigDecimal::from_str("12345678.90").unwrap();
+        assert!(matches!(
+            Value::from_big_decimal(&too_long, &ty),
+            Err(Error::Overflow(_))
+        ));
+        let longest = BigDecimal::from_str("-9999999.99").unwrap();
+        assert_eq!(
+            Value::from_big_decimal(&longest, &ty).unwrap(),
+            Value::Decimal32(-999_999_999)
+        );
+    }
+
+    #[test]
+    fn converts_decimal256_losslessly() {
+        let ty = parse_type_desc("Decimal(76, 10)").unwrap();
+        let decimal =
+            BigDecimal::from_str("-123456789012345678901234567890123456789012345.0123456789")
+                .unwrap();
+        let value = Value::from_big_decimal(&decimal, &ty).unwrap();
+        assert!(matches!(value, Value::Decimal256(_)));
+        assert_eq!(value.to_big_decimal(&ty).unwrap(), decimal);
+
+        let narrow = parse_type_desc("Decimal(9, 0)").unwrap();
+        assert!(matches!(
+            Value::from_big_decimal(&BigDecimal::from(i64::MAX), &narrow),
+            Err(Error::Overflow(_))
+        ));
+    }
+}
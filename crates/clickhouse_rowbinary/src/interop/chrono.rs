@@ -0,0 +1,313 @@
+//! Conversions between temporal values and `chrono` types.
+
+use ::chrono::{DateTime, Datelike, NaiveDate, Utc};
+
+use super::{date_value, datetime_type};
+use crate::{
+    error::{Error, Result},
+    typed::{
+        ColumnType, FromValue, IntoValue,
+        from_value::{mismatch as value_mismatch, non_null},
+        into_value::{encode_leaf, mismatch},
+    },
+    types::{TypeDesc, datetime64_ticks_per_second},
+    value::Value,
+};
+
+/// Days from 0001-01-01 (day 1 of the common era) to 1970-01-01.
+const UNIX_EPOCH_DAYS_FROM_CE: i32 = 719_163;
+
+impl From<NaiveDate> for Value {
+    /// Converts a date into a [`Value::Date32`].
+    ///
+    /// The writer also accepts the result for `Date` columns when the day
+    /// fits the narrower range.
+    fn from(date: NaiveDate) -> Self {
+        Value::Date32(date.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)
+    }
+}
+
+impl TryFrom<Value> for NaiveDate {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let days = match value {
+            Value::Date(days) => i32::from(days),
+            Value::Date32(days) => days,
+            other => {
+                return Err(Error::TypeMismatch {
+                    expected: "Date".to_string(),
+                    actual: other.type_name().to_string(),
+                });
+            }
+        };
+        days.checked_add(UNIX_EPOCH_DAYS_FROM_CE)
+            .and_then(NaiveDate::from_num_days_from_ce_opt)
+            .ok_or(Error::Overflow("date outside chrono range"))
+    }
+}
+
+impl TryFrom<DateTime<Utc>> for Value {
+    type Error = Error;
+
+    /// Converts a timestamp into a [`Value::DateTime`], truncating
+    /// fractional seconds.
+    fn try_from(value: DateTime<Utc>) -> Result<Self, Self::Error> {
+        u32::try_from(value.timestamp())
+            .map(Value::DateTime)
+            .map_err(|_| Error::Overflow("timestamp outside DateTime range"))
+    }
+}
+
+impl TryFrom<Value> for DateTime<Utc> {
+    type Error = Error;
+
+    /// Converts a [`Value::DateTime`]; use [`Value::to_chrono_datetime`] for
+    /// `DateTime64`, whose precision comes from the column type.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::DateTime(seconds) => DateTime::from_timestamp(i64::from(seconds), 0)
+                .ok_or(Error::Overflow("timestamp outside chrono range")),
+            other => Err(Error::TypeMismatch {
+                expected: "DateTime".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl Value {
+    /// Builds a [`Value::DateTime64`] with `precision` decimal places from a
+    /// `chrono` timestamp, truncating digits beyond the precision.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `precision` exceeds 9, or
+    /// [`Error::Overflow`] when the scaled value does not fit in 64 bits.
+    pub fn from_chrono_datetime64(value: DateTime<Utc>, precision: u8) -> Result<Self> {
+        let scale = datetime64_ticks_per_second(precision)?;
+        let fraction = i64::from(value.timestamp_subsec_nanos()) / (1_000_000_000 / scale);
+        value
+            .timestamp()
+            .checked_mul(scale)
+            .and_then(|ticks| ticks.checked_add(fraction))
+            .map(Value::DateTime64)
+            .ok_or(Error::Overflow("timestamp outside DateTime64 range"))
+    }
+
+    /// Converts a `DateTime` or `DateTime64` value into a UTC timestamp.
+    ///
+    /// `ty` is the column type; it supplies the `DateTime64` precision and
+    /// may be wrapped in `Nullable` or `LowCardinality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when the value or type is not a
+    /// timestamp, or [`Error::Overflow`] when chrono cannot represent it.
+    pub fn to_chrono_datetime(&self, ty: &TypeDesc) -> Result<DateTime<Utc>> {
+        match (datetime_type(ty), self) {
+            (Some(TypeDesc::DateTime { .. }), Value::DateTime(seconds)) => {
+                DateTime::from_timestamp(i64::from(*seconds), 0)
+                    .ok_or(Error::Overflow("timestamp outside chrono range"))
+            }
+            (Some(TypeDesc::DateTime64 { precision, .. }), Value::DateTime64(ticks)) => {
+                let scale = datetime64_ticks_per_second(*precision)?;
+                let seconds = ticks.div_euclid(scale);
+                let nanos = ticks.rem_euclid(scale) * (1_000_000_000 / scale);
+                u32::try_from(nanos)
+                    .ok()
+                    .and_then(|nanos| DateTime::from_timestamp(seconds, nanos))
+                    .ok_or(Error::Overflow("timestamp outside chrono range"))
+            }
+            _ => Err(Error::TypeMismatch {
+                expected: ty.type_name(),
+                actual: self.type_name().to_string(),
+            }),
+        }
+    }
+
+    /// Converts a `DateTime` or `DateTime64` value into a timestamp in the
+    /// column's timezone, falling back to UTC when the type has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the timezone is unknown to
+    /// `chrono-tz`, otherwise the errors of [`Value::to_chrono_datetime`].
+    #[cfg(feature = "chrono-tz")]
+    pub fn to_chrono_zoned(&self, ty: &TypeDesc) -> Result<DateTime<chrono_tz::Tz>> {
+        let timezone = ty
+            .timezone()
+            .unwrap_or("UTC")
+            .parse::<chrono_tz::Tz>()
+            .map_err(|_| Error::InvalidValue("unknown timezone"))?;
+        Ok(self.to_chrono_datetime(ty)?.with_timezone(&timezone))
+    }
+}
+
+impl ColumnType for NaiveDate {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Date32
+    }
+}
+
+impl ColumnType for DateTime<Utc> {
+    /// Microsecond `DateTime64` in UTC.
+    fn column_type() -> TypeDesc {
+        TypeDesc::DateTime64 {
+            precision: 6,
+            timezone: Some("UTC".to_string()),
+        }
+    }
+}
+
+impl IntoValue for NaiveDate {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        date_value(ty, self.num_days_from_ce() - UNIX_EPOCH_DAYS_FROM_CE)
+    }
+}
+
+impl FromValue<'_> for NaiveDate {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "NaiveDate")?.1 {
+            value @ (Value::Date(_) | Value::Date32(_)) => NaiveDate::try_from(value.clone()),
+            other => Err(value_mismatch("NaiveDate", other)),
+        }
+    }
+}
+
+impl IntoValue for DateTime<Utc> {
+    /// Encodes the timestamp into a `DateTime` or `DateTime64` column at
+    /// the column's precision.
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_leaf(ty, "timestamp", &|ty| match ty {
+            TypeDesc::DateTime { .. } => Value::try_from(*self),
+            TypeDesc::DateTime64 { precision, .. } => {
+                Value::from_chrono_datetime64(*self, *precision)
+            }
+            ty => Err(mismatch(ty, "timestamp")),
+        })
+    }
+}
+
+impl FromValue<'_> for DateTime<Utc> {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "DateTime")?;
+        value.to_chrono_datetime(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::chrono::{DateTime, NaiveDate, Utc};
+
+    use crate::{
+        typed::{ColumnType, FromValue, IntoValue},
+        types::parse_type_desc,
+        value::Value,
+    };
+
+    #[test]
+    fn converts_dates() {
+        let date = NaiveDate::from_ymd_opt(2024, 2, 29).unwrap();
+        let value = Value::from(date);
+        assert_eq!(value, Value::Date32(19_782));
+        assert_eq!(NaiveDate::try_from(value).unwrap(), date);
+        assert_eq!(NaiveDate::try_from(Value::Date(19_782)).unwrap(), date);
+
+        let before_epoch = NaiveDate::from_ymd_opt(1900, 1, 1).unwrap();
+        assert_eq!(Value::from(before_epoch), Value::Date32(-25_567));
+        assert!(NaiveDate::try_from(Value::UInt16(1)).is_err());
+    }
+
+    #[test]
+    fn converts_datetimes() {
+        let timestamp = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+        assert_eq!(
+            Value::try_from(timestamp).unwrap(),
+            Value::DateTime(1_700_000_000)
+        );
+        assert!(Value::try_from(DateTime::<Utc>::from_timestamp(-1, 0).unwrap()).is_err());
+
+        let ty = parse_type_desc("Nullable(DateTime('UTC'))").unwrap();
+        let value = Value::DateTime(1_700_000_000);
+        assert_eq!(
+            value.to_chrono_datetime(&ty).unwrap(),
+            DateTime::from_timestamp(1_700_000_000, 0).unwrap()
+        );
+        assert_eq!(
+            DateTime::<Utc>::try_from(value).unwrap().timestamp(),
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn converts_datetime64_with_precision() {
+        let timestamp = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+        let value = Value::from_chrono_datetime64(timestamp, 3).unwrap();
+        assert_eq!(value, Value::DateTime64(1_700_000_000_123));
+
+        let ty = parse_type_desc("DateTime64(3)").unwrap();
+        assert_eq!(
+            value.to_chrono_datetime(&ty).unwrap(),
+            DateTime::from_timestamp(1_700_000_000, 123_000_000).unwrap()
+        );
+
+        let before_epoch = Value::DateTime64(-1_500);
+        assert_eq!(
+            before_epoch.to_chrono_datetime(&ty).unwrap(),
+            DateTime::from_timestamp(-2, 500_000_000).unwrap()
+        );
+        assert!(Value::from_chrono_datetime64(timestamp, 10).is_err());
+        assert!(
+            Value::DateTime(0)
+                .to_chrono_datetime(&parse_type_desc("Date").unwrap())
+                .is_err()
+        );
+    }
+
+    #[cfg(feature = "chrono-tz")]
+    #[test]
+    fn converts_into_column_timezone() {
+        use ::chrono::Timelike;
+
+        let ty = parse_type_desc("DateTime('Asia/Tokyo')").unwrap();
+        let zoned = Value::DateTime(0).to_chrono_zoned(&ty).unwrap();
+        assert_eq!(zoned.hour(), 9);
+        assert_eq!(zoned.timezone(), chrono_tz::Asia::Tokyo);
+
+        let utc = parse_type_desc("DateTime").unwrap();
+        assert_eq!(Value::DateTime(0).to_chrono_zoned(&utc).unwrap().hour(), 0);
+
+        let unknown = parse_type_desc("DateTime('Mars/Olympus')").unwrap();
+        assert!(Value::DateTime(0).to_chrono_zoned(&unknown).is_err());
+    }
+
+    #[test]
+    fn typed_conversions_use_the_column_precision() {
+        let ty = parse_type_desc("Nullable(DateTime64(3, 'UTC'))").unwrap();
+        let at = DateTime::from_timestamp(1_700_000_000, 123_456_789).unwrap();
+        let value = at.to_value(&ty).unwrap();
+        assert_eq!(
+            value,
+            Value::Nullable(Some(Box::new(Value::DateTime64(1_700_000_000_123))))
+        );
+        let back = DateTime::<Utc>::from_value(&ty, &value).unwrap();
+        assert_eq!(back.timestamp_millis(), 1_700_000_000_123);
+
+        let ty = parse_type_desc("Date").unwrap();
+        let date = NaiveDate::from_ymd_opt(2024, 1, 2).unwrap();
+        let value = date.to_value(&ty).unwrap();
+        assert!(matches!(value, Value::Date(_)));
+        assert_eq!(NaiveDate::from_value(&ty, &value).unwrap(), date);
+        assert!(at.to_value(&ty).is_err());
+
+        assert_eq!(
+            <DateTime<Utc> as ColumnType>::column_type(),
+            parse_type_desc("DateTime64(6, 'UTC')").unwrap()
+        );
+        assert_eq!(
+            <NaiveDate as ColumnType>::column_type(),
+            parse_type_desc("Date32").unwrap()
+        );
+    }
+}
@@ -0,0 +1,124 @@
+//! Conversions between 256-bit integer values and `ethnum` types.
+
+use ::ethnum::{I256, U256};
+
+use crate::{
+    error::{Error, Result},
+    typed::{
+        ColumnType, FromValue, IntoValue,
+        from_value::{mismatch as value_mismatch, non_null},
+        into_value::encode_exact,
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+impl From<I256> for Value {
+    fn from(value: I256) -> Self {
+        Value::Int256(value.to_le_bytes())
+    }
+}
+
+impl From<U256> for Value {
+    fn from(value: U256) -> Self {
+        Value::UInt256(value.to_le_bytes())
+    }
+}
+
+impl TryFrom<Value> for I256 {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int256(bytes) => Ok(I256::from_le_bytes(bytes)),
+            other => Err(Error::TypeMismatch {
+                expected: "Int256".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for U256 {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::UInt256(bytes) => Ok(U256::from_le_bytes(bytes)),
+            other => Err(Error::TypeMismatch {
+                expected: "UInt256".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl ColumnType for I256 {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Int256
+    }
+}
+
+impl ColumnType for U256 {
+    fn column_type() -> TypeDesc {
+        TypeDesc::UInt256
+    }
+}
+
+impl IntoValue for I256 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_exact(ty, &TypeDesc::Int256, &Value::from(*self))
+    }
+}
+
+impl IntoValue for U256 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_exact(ty, &TypeDesc::UInt256, &Value::from(*self))
+    }
+}
+
+impl FromValue<'_> for I256 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "I256")?.1 {
+            Value::Int256(bytes) => Ok(I256::from_le_bytes(*bytes)),
+            other => Err(value_mismatch("I256", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for U256 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "U256")?.1 {
+            Value::UInt256(bytes) => Ok(U256::from_le_bytes(*bytes)),
+            other => Err(value_mismatch("U256", other)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::ethnum::{I256, U256};
+
+    use crate::value::Value;
+
+    #[test]
+    fn converts_256_bit_integers() {
+        let signed = I256::MIN;
+        let value = Value::from(signed);
+        let Value::Int256(bytes) = value else {
+            panic!("expected Int256");
+        };
+        assert_eq!(bytes[31], 0x80);
+        assert_eq!(I256::try_from(value).unwrap(), signed);
+
+        let unsigned = U256::from_words(1, 2);
+        let value = Value::from(unsigned);
+        assert_eq!(U256::try_from(value.clone()).unwrap(), unsigned);
+        assert_eq!(
+            U256::try_from(value).unwrap().to_string(),
+            "340282366920938463463374607431768211458"
+        );
+        assert!(U256::try_from(Value::Int256([0; 32])).is_err());
+        assert!(I256::try_from(Value::UInt128(1)).is_err());
+    }
+}
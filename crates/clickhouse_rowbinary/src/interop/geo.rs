@@ -0,0 +1,227 @@
+//! Conversions between geo values and `geo_types` geometries.
+//!
+//! `ClickHouse` geo values are nested tuples and arrays of `Float64`:
+//! a `Point` is `Tuple(Float64, Float64)`, a `Ring` or `LineString` is an
+//! array of points, a `Polygon` is an array of rings (exterior first) and a
+//! `MultiPolygon` an array of polygons. `geo_types` closes polygon rings,
+//! so an open ring read from `ClickHouse` repeats its first point when
+//! converted back.
+
+use ::geo_types::{Coord, LineString, MultiLineString, MultiPolygon, Point, Polygon};
+
+use crate::{
+    error::{Error, Result},
+    typed::{
+        ColumnType, FromValue, IntoValue,
+        from_value::non_null,
+        into_value::{encode_leaf, mismatch},
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+impl From<Point<f64>> for Value {
+    fn from(point: Point<f64>) -> Self {
+        coord_to_value(point.0)
+    }
+}
+
+impl From<LineString<f64>> for Value {
+    /// Converts a line string into a `LineString` or `Ring` value.
+    fn from(line: LineString<f64>) -> Self {
+        Value::Array(line.0.into_iter().map(coord_to_value).collect())
+    }
+}
+
+impl From<MultiLineString<f64>> for Value {
+    fn from(lines: MultiLineString<f64>) -> Self {
+        Value::Array(lines.0.into_iter().map(Value::from).collect())
+    }
+}
+
+impl From<Polygon<f64>> for Value {
+    fn from(polygon: Polygon<f64>) -> Self {
+        let (exterior, interiors) = polygon.into_inner();
+        let mut rings = Vec::with_capacity(interiors.len() + 1);
+        rings.push(Value::from(exterior));
+        rings.extend(interiors.into_iter().map(Value::from));
+        Value::Array(rings)
+    }
+}
+
+impl From<MultiPolygon<f64>> for Value {
+    fn from(polygons: MultiPolygon<f64>) -> Self {
+        Value::Array(polygons.0.into_iter().map(Value::from).collect())
+    }
+}
+
+impl TryFrom<Value> for Point<f64> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        value_to_coord(value).map(Point)
+    }
+}
+
+impl TryFrom<Value> for LineString<f64> {
+    type Error = Error;
+
+    /// Converts a `LineString` or `Ring` value.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        array_items(value, "LineString")?
+            .into_iter()
+            .map(value_to_coord)
+            .collect::<Result<Vec<_>, _>>()
+            .map(LineString)
+    }
+}
+
+impl TryFrom<Value> for MultiLineString<f64> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        array_items(value, "MultiLineString")?
+            .into_iter()
+            .map(LineString::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map(MultiLineString)
+    }
+}
+
+impl TryFrom<Value> for Polygon<f64> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let mut rings = array_items(value, "Polygon")?
+            .into_iter()
+            .map(LineString::try_from)
+            .collect::<Result<Vec<_>, _>>()?
+            .into_iter();
+        let exterior = rings.next().unwrap_or_else(|| LineString(Vec::new()));
+        Ok(Polygon::new(exterior, rings.collect()))
+    }
+}
+
+impl TryFrom<Value> for MultiPolygon<f64> {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        array_items(value, "MultiPolygon")?
+            .into_iter()
+            .map(Polygon::try_from)
+            .collect::<Result<Vec<_>, _>>()
+            .map(MultiPolygon)
+    }
+}
+
+fn coord_to_value(coord: Coord<f64>) -> Value {
+    Value::Tuple(vec![Value::Float64(coord.x), Value::Float64(coord.y)])
+}
+
+fn value_to_coord(value: Value) -> Result<Coord<f64>, Error> {
+    match value {
+        Value::Tuple(items) => match items.as_slice() {
+            [Value::Float64(x), Value::Float64(y)] => Ok(Coord { x: *x, y: *y }),
+            _ => Err(Error::InvalidValue("Point must hold two Float64 values")),
+        },
+        other => Err(Error::TypeMismatch {
+            expected: "Point".to_string(),
+            actual: other.type_name().to_string(),
+        }),
+    }
+}
+
+fn array_items(value: Value, expected: &str) -> Result<Vec<Value>, Error> {
+    match value {
+        Value::Array(items) => Ok(items),
+        other => Err(Error::TypeMismatch {
+            expected: expected.to_string(),
+            actual: other.type_name().to_string(),
+        }),
+    }
+}
+
+/// Implements [`IntoValue`] for a geometry that fits the geo column types
+/// `$column` and `$accepted`, [`FromValue`] through its `TryFrom<Value>`
+/// conversion, and [`ColumnType`] as `$column`.
+macro_rules! impl_geometry_value {
+    ($($geometry:ty => $column:ident $(| $accepted:ident)*),* $(,)?) => {
+        $(
+            impl ColumnType for $geometry {
+                fn column_type() -> TypeDesc {
+                    TypeDesc::$column
+                }
+            }
+
+            impl IntoValue for $geometry {
+                fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+                    let actual = stringify!($geometry);
+                    encode_leaf(ty, actual, &|ty| match ty {
+                        TypeDesc::$column $(| TypeDesc::$accepted)* => {
+                            Ok(Value::from(self.clone()))
+                        }
+                        ty => Err(mismatch(ty, actual)),
+                    })
+                }
+            }
+
+            impl FromValue<'_> for $geometry {
+                fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+                    Self::try_from(non_null(ty, value, stringify!($geometry))?.1.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_geometry_value!(
+    Point<f64> => Point,
+    LineString<f64> => LineString | Ring,
+    MultiLineString<f64> => MultiLineString,
+    Polygon<f64> => Polygon,
+    MultiPolygon<f64> => MultiPolygon,
+);
+
+#[cfg(test)]
+mod tests {
+    use ::geo_types::{LineString, MultiPolygon, Point, Polygon, line_string, point, polygon};
+
+    use crate::value::Value;
+
+    #[test]
+    fn converts_points_and_line_strings() {
+        let value = Value::from(point!(x: 1.5, y: -2.0));
+        assert_eq!(
+            value,
+            Value::Tuple(vec![Value::Float64(1.5), Value::Float64(-2.0)])
+        );
+        assert_eq!(Point::try_from(value).unwrap(), point!(x: 1.5, y: -2.0));
+
+        let line = line_string![(x: 0.0, y: 0.0), (x: 1.0, y: 1.0)];
+        let value = Value::from(line.clone());
+        assert_eq!(LineString::try_from(value).unwrap(), line);
+        assert!(Point::try_from(Value::Tuple(vec![Value::Float64(1.0)])).is_err());
+        assert!(LineString::try_from(Value::Float64(1.0)).is_err());
+    }
+
+    #[test]
+    fn converts_polygons() {
+        let polygon = polygon!(
+            exterior: [(x: 0.0, y: 0.0), (x: 4.0, y: 0.0), (x: 4.0, y: 4.0), (x: 0.0, y: 0.0)],
+            interiors: [[(x: 1.0, y: 1.0), (x: 2.0, y: 1.0), (x: 2.0, y: 2.0), (x: 1.0, y: 1.0)]],
+        );
+        let value = Value::from(polygon.clone());
+        let Value::Array(rings) = &value else {
+            panic!("expected array");
+        };
+        assert_eq!(rings.len(), 2);
+        assert_eq!(Polygon::try_from(value).unwrap(), polygon);
+
+        let multi = MultiPolygon(vec![polygon.clone(), polygon]);
+        let value = Value::from(multi.clone());
+        assert_eq!(MultiPolygon::try_from(value).unwrap(), multi);
+
+        let empty = Polygon::try_from(Value::Array(Vec::new())).unwrap();
+        assert!(empty.exterior().0.is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! Conversions between temporal values and `jiff` types.
+
+use ::jiff::{Timestamp, Zoned, tz::TimeZone};
+
+use super::{datetime_nanos, datetime64_from_nanos};
+use crate::{
+    error::{Error, Result},
+    typed::{
+        ColumnType, FromValue, IntoValue,
+        from_value::non_null,
+        into_value::{encode_leaf, mismatch},
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+impl TryFrom<Timestamp> for Value {
+    type Error = Error;
+
+    /// Converts a timestamp into a [`Value::DateTime`], truncating
+    /// fractional seconds.
+    fn try_from(value: Timestamp) -> Result<Self, Self::Error> {
+        u32::try_from(value.as_second())
+            .map(Value::DateTime)
+            .map_err(|_| Error::Overflow("timestamp outside DateTime range"))
+    }
+}
+
+impl TryFrom<Value> for Timestamp {
+    type Error = Error;
+
+    /// Converts a [`Value::DateTime`]; use [`Value::to_jiff_timestamp`] for
+    /// `DateTime64`, whose precision comes from the column type.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::DateTime(seconds) => Timestamp::from_second(i64::from(seconds))
+                .map_err(|_| Error::Overflow("timestamp outside jiff range")),
+            other => Err(Error::TypeMismatch {
+                expected: "DateTime".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl Value {
+    /// Builds a [`Value::DateTime64`] with `precision` decimal places from a
+    /// `jiff` timestamp, truncating digits beyond the precision.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `precision` exceeds 9, or
+    /// [`Error::Overflow`] when the scaled value does not fit in 64 bits.
+    pub fn from_jiff_datetime64(value: Timestamp, precision: u8) -> Result<Self> {
+        datetime64_from_nanos(value.as_nanosecond(), precision)
+    }
+
+    /// Converts a `DateTime` or `DateTime64` value into a timestamp.
+    ///
+    /// `ty` is the column type; it supplies the `DateTime64` precision and
+    /// may be wrapped in `Nullable` or `LowCardinality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when the value or type is not a
+    /// timestamp, or [`Error::Overflow`] when `jiff` cannot represent it.
+    pub fn to_jiff_timestamp(&self, ty: &TypeDesc) -> Result<Timestamp> {
+        Timestamp::from_nanosecond(datetime_nanos(self, ty)?)
+            .map_err(|_| Error::Overflow("timestamp outside jiff range"))
+    }
+
+    /// Converts a `DateTime` or `DateTime64` value into a zoned datetime in
+    /// the column's timezone, falling back to UTC when the type has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the timezone is not in the
+    /// bundled database, otherwise the errors of
+    /// [`Value::to_jiff_timestamp`].
+    pub fn to_jiff_zoned(&self, ty: &TypeDesc) -> Result<Zoned> {
+        let timezone = match ty.timezone() {
+            Some(name) => {
+                TimeZone::get(name).map_err(|_| Error::InvalidValue("unknown timezone"))?
+            }
+            None => TimeZone::UTC,
+        };
+        Ok(self.to_jiff_timestamp(ty)?.to_zoned(timezone))
+    }
+}
+
+impl ColumnType for Timestamp {
+    /// Microsecond `DateTime64` in UTC.
+    fn column_type() -> TypeDesc {
+        TypeDesc::DateTime64 {
+            precision: 6,
+            timezone: Some("UTC".to_string()),
+        }
+    }
+}
+
+impl IntoValue for Timestamp {
+    /// Encodes the timestamp into a `DateTime` or `DateTime64` column at
+    /// the column's precision.
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_leaf(ty, "timestamp", &|ty| match ty {
+            TypeDesc::DateTime { .. } => Value::try_from(*self),
+            TypeDesc::DateTime64 { precision, .. } => {
+                Value::from_jiff_datetime64(*self, *precision)
+            }
+            ty => Err(mismatch(ty, "timestamp")),
+        })
+    }
+}
+
+impl FromValue<'_> for Timestamp {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "Timestamp")?;
+        value.to_jiff_timestamp(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::jiff::Timestamp;
+
+    use crate::{types::parse_type_desc, value::Value};
+
+    #[test]
+    fn converts_datetimes() {
+        let timestamp = Timestamp::new(1_700_000_000, 123_456_789).unwrap();
+        assert_eq!(
+            Value::try_from(timestamp).unwrap(),
+            Value::DateTime(1_700_000_000)
+        );
+        assert!(Value::try_from(Timestamp::new(-1, 0).unwrap()).is_err());
+
+        let value = Value::DateTime(1_700_000_000);
+        assert_eq!(
+            Timestamp::try_from(value.clone()).unwrap(),
+            Timestamp::from_second(1_700_000_000).unwrap()
+        );
+        let ty = parse_type_desc("Nullable(DateTime)").unwrap();
+        assert_eq!(
+            value.to_jiff_timestamp(&ty).unwrap().as_second(),
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn converts_datetime64_with_precision() {
+        let timestamp = Timestamp::new(1_700_000_000, 123_456_789).unwrap();
+        let value = Value::from_jiff_datetime64(timestamp, 6).unwrap();
+        assert_eq!(value, Value::DateTime64(1_700_000_000_123_456));
+
+        let ty = parse_type_desc("DateTime64(6)").unwrap();
+        assert_eq!(
+            value.to_jiff_timestamp(&ty).unwrap(),
+            Timestamp::new(1_700_000_000, 123_456_000).unwrap()
+        );
+        assert!(Value::from_jiff_datetime64(timestamp, 10).is_err());
+    }
+
+    #[test]
+    fn converts_into_column_timezone() {
+        let ty = parse_type_desc("DateTime64(3, 'Asia/Tokyo')").unwrap();
+        let zoned = Value::DateTime64(0).to_jiff_zoned(&ty).unwrap();
+        assert_eq!(zoned.hour(), 9);
+        assert_eq!(zoned.time_zone().iana_name(), Some("Asia/Tokyo"));
+
+        let utc = parse_type_desc("DateTime").unwrap();
+        assert_eq!(Value::DateTime(0).to_jiff_zoned(&utc).unwrap().hour(), 0);
+
+        let unknown = parse_type_desc("DateTime('Mars/Olympus')").unwrap();
+        assert!(Value::DateTime(0).to_jiff_zoned(&unknown).is_err());
+    }
+}
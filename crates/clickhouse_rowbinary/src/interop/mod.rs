@@ -0,0 +1,109 @@
+//! Optional conversions between [`crate::Value`] and third-party crates.
+
+#[cfg(feature = "bigdecimal")]
+mod bigdecimal;
+#[cfg(feature = "chrono")]
+mod chrono;
+#[cfg(feature = "ethnum")]
+mod ethnum;
+#[cfg(feature = "geo")]
+mod geo;
+#[cfg(feature = "jiff")]
+mod jiff;
+#[cfg(feature = "rust_decimal")]
+mod rust_decimal;
+#[cfg(feature = "time")]
+mod time;
+
+#[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+use crate::error::{Error, Result};
+#[cfg(any(feature = "bigdecimal", feature = "rust_decimal"))]
+use crate::types::DecimalSize;
+#[cfg(any(
+    feature = "bigdecimal",
+    feature = "chrono",
+    feature = "jiff",
+    feature = "rust_decimal",
+    feature = "time"
+))]
+use crate::types::TypeDesc;
+#[cfg(any(feature = "jiff", feature = "time"))]
+use crate::types::datetime64_ticks_per_second;
+#[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+use crate::value::Value;
+
+/// Returns the `DateTime`/`DateTime64` type behind `Nullable` and
+/// `LowCardinality` wrappers.
+#[cfg(any(feature = "chrono", feature = "jiff", feature = "time"))]
+fn datetime_type(ty: &TypeDesc) -> Option<&TypeDesc> {
+    match ty {
+        TypeDesc::DateTime { .. } | TypeDesc::DateTime64 { .. } => Some(ty),
+        TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => datetime_type(inner),
+        _ => None,
+    }
+}
+
+/// Encodes `days` since the Unix epoch into a `Date` or `Date32` column of
+/// type `ty`; `Dynamic` columns store it as `Date32`.
+#[cfg(any(feature = "chrono", feature = "time"))]
+fn date_value(ty: &TypeDesc, days: i32) -> Result<Value> {
+    crate::typed::into_value::encode_leaf(ty, "date", &|ty| match ty {
+        TypeDesc::Date => u16::try_from(days)
+            .map(Value::Date)
+            .map_err(|_| Error::Overflow("date outside Date range")),
+        TypeDesc::Date32 => Ok(Value::Date32(days)),
+        TypeDesc::Dynamic { .. } => Ok(Value::Dynamic {
+            ty: Box::new(TypeDesc::Date32),
+            value: Box::new(Value::Date32(days)),
+        }),
+        ty => Err(crate::typed::into_value::mismatch(ty, "date")),
+    })
+}
+
+/// Returns the nanoseconds since the Unix epoch of a `DateTime` or
+/// `DateTime64` value of column type `ty`.
+#[cfg(any(feature = "jiff", feature = "time"))]
+fn datetime_nanos(value: &Value, ty: &TypeDesc) -> Result<i128> {
+    match (datetime_type(ty), value) {
+        (Some(TypeDesc::DateTime { .. }), Value::DateTime(seconds)) => {
+            Ok(i128::from(*seconds) * 1_000_000_000)
+        }
+        (Some(TypeDesc::DateTime64 { precision, .. }), Value::DateTime64(ticks)) => {
+            Ok(i128::from(*ticks)
+                * i128::from(1_000_000_000 / datetime64_ticks_per_second(*precision)?))
+        }
+        _ => Err(Error::TypeMismatch {
+            expected: ty.type_name(),
+            actual: value.type_name().to_string(),
+        }),
+    }
+}
+
+/// Builds a `DateTime64` value from nanoseconds since the Unix epoch,
+/// truncating digits beyond `precision`.
+#[cfg(any(feature = "jiff", feature = "time"))]
+fn datetime64_from_nanos(nanos: i128, precision: u8) -> Result<Value> {
+    let divisor = i128::from(1_000_000_000 / datetime64_ticks_per_second(precision)?);
+    i64::try_from(nanos.div_euclid(divisor))
+        .map(Value::DateTime64)
+        .map_err(|_| Error::Overflow("timestamp outside DateTime64 range"))
+}
+
+/// Returns the precision, scale and storage size of a decimal column type,
+/// looking through `Nullable` and `LowCardinality` wrappers.
+#[cfg(any(feature = "bigdecimal", feature = "rust_decimal"))]
+fn decimal_parts(ty: &TypeDesc) -> Option<(u8, u8, DecimalSize)> {
+    match ty {
+        TypeDesc::Decimal {
+            precision,
+            scale,
+            size,
+        } => Some((*precision, *scale, *size)),
+        TypeDesc::Decimal32 { scale } => Some((9, *scale, DecimalSize::Bits32)),
+        TypeDesc::Decimal64 { scale } => Some((18, *scale, DecimalSize::Bits64)),
+        TypeDesc::Decimal128 { scale } => Some((38, *scale, DecimalSize::Bits128)),
+        TypeDesc::Decimal256 { scale } => Some((76, *scale, DecimalSize::Bits256)),
+        TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => decimal_parts(inner),
+        _ => None,
+    }
+}
@@ -0,0 +1,199 @@
+//! Conversions between decimal values and `rust_decimal::Decimal`.
+
+use ::rust_decimal::Decimal;
+
+use super::decimal_parts;
+use crate::{
+    error::{Error, Result},
+    typed::{FromValue, IntoValue, from_value::non_null, into_value::encode_leaf},
+    types::{DecimalSize, TypeDesc},
+    value::Value,
+};
+
+impl Value {
+    /// Converts a decimal value into a [`Decimal`] using the scale of the
+    /// column type `ty`, which may be wrapped in `Nullable` or
+    /// `LowCardinality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when the value does not match a
+    /// decimal type, or [`Error::Overflow`] when the scale exceeds 28 or
+    /// the mantissa does not fit in 96 bits.
+    pub fn to_rust_decimal(&self, ty: &TypeDesc) -> Result<Decimal> {
+        let mismatch = || Error::TypeMismatch {
+            expected: ty.type_name(),
+            actual: self.type_name().to_string(),
+        };
+        let (_, scale, size) = decimal_parts(ty).ok_or_else(mismatch)?;
+        let mantissa = match (size, self) {
+            (DecimalSize::Bits32, Value::Decimal32(value)) => i128::from(*value),
+            (DecimalSize::Bits64, Value::Decimal64(value)) => i128::from(*value),
+            (DecimalSize::Bits128, Value::Decimal128(value)) => *value,
+            (DecimalSize::Bits256, Value::Decimal256(bytes)) => {
+                i256_to_i128(bytes).ok_or(Error::Overflow("decimal outside rust_decimal range"))?
+            }
+            _ => return Err(mismatch()),
+        };
+        Decimal::try_from_i128_with_scale(mantissa, u32::from(scale))
+            .map_err(|_| Error::Overflow("decimal outside rust_decimal range"))
+    }
+
+    /// Builds a decimal value for the column type `ty` from a [`Decimal`].
+    ///
+    /// The mantissa is rescaled to the column scale; values with more
+    /// fractional digits than the column keeps are rejected rather than
+    /// rounded.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when `ty` is not a decimal type,
+    /// [`Error::InvalidValue`] when rescaling would lose digits, or
+    /// [`Error::Overflow`] when the value has more digits than the column
+    /// precision.
+    pub fn from_rust_decimal(value: Decimal, ty: &TypeDesc) -> Result<Self> {
+        let (precision, scale, size) = decimal_parts(ty).ok_or_else(|| Error::TypeMismatch {
+            expected: "Decimal".to_string(),
+            actual: ty.type_name(),
+        })?;
+        let mantissa = rescale(value.mantissa(), value.scale(), u32::from(scale))?;
+        // A 96-bit mantissa has at most 29 digits, within any wider precision.
+        if let Some(limit) = 10_u128.checked_pow(u32::from(precision))
+            && mantissa.unsigned_abs() >= limit
+        {
+            return Err(Error::Overflow("decimal outside column precision"));
+        }
+        let overflow = |_| Error::Overflow("decimal outside column range");
+        Ok(match size {
+            DecimalSize::Bits32 => Value::Decimal32(i32::try_from(mantissa).map_err(overflow)?),
+            DecimalSize::Bits64 => Value::Decimal64(i64::try_from(mantissa).map_err(overflow)?),
+            DecimalSize::Bits128 => Value::Decimal128(mantissa),
+            DecimalSize::Bits256 => Value::Decimal256(i128_to_i256(mantissa)),
+        })
+    }
+}
+
+fn rescale(mantissa: i128, from: u32, to: u32) -> Result<i128> {
+    if to >= from {
+        return 10_i128
+            .checked_pow(to - from)
+            .and_then(|factor| mantissa.checked_mul(factor))
+            .ok_or(Error::Overflow("decimal outside column range"));
+    }
+    let divisor = 10_i128.pow(from - to);
+    if mantissa % divisor != 0 {
+        return Err(Error::InvalidValue(
+            "decimal has more fractional digits than the column scale",
+        ));
+    }
+    Ok(mantissa / divisor)
+}
+
+fn i256_to_i128(bytes: &[u8; 32]) -> Option<i128> {
+    let (low, high) = bytes.split_at(16);
+    let value = i128::from_le_bytes(low.try_into().ok()?);
+    let extension = if value < 0 { 0xFF } else { 0x00 };
+    high.iter().all(|byte| *byte == extension).then_some(value)
+}
+
+fn i128_to_i256(value: i128) -> [u8; 32] {
+    let mut bytes = if value < 0 { [0xFF; 32] } else { [0x00; 32] };
+    bytes[..16].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+impl IntoValue for Decimal {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_leaf(ty, "decimal", &|ty| Value::from_rust_decimal(*self, ty))
+    }
+}
+
+impl FromValue<'_> for Decimal {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "Decimal")?;
+        value.to_rust_decimal(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::rust_decimal::Decimal;
+
+    use crate::{
+        error::Error,
+        typed::{FromValue, IntoValue},
+        types::parse_type_desc,
+        value::Value,
+    };
+
+    #[test]
+    fn converts_decimals_with_column_scale() {
+        let ty = parse_type_desc("Decimal(9, 2)").unwrap();
+        let value = Value::Decimal32(-12_345);
+        let decimal = value.to_rust_decimal(&ty).unwrap();
+        assert_eq!(decimal.to_string(), "-123.45");
+        assert_eq!(Value::from_rust_decimal(decimal, &ty).unwrap(), value);
+
+        let ty = parse_type_desc("Nullable(Decimal64(4))").unwrap();
+        let decimal = Decimal::new(15, 1);
+        assert_eq!(
+            Value::from_rust_decimal(decimal, &ty).unwrap(),
+            Value::Decimal64(15_000)
+        );
+        assert!(matches!(
+            Value::from_rust_decimal(Decimal::new(12_345, 5), &ty),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_digits_beyond_the_column_precision() {
+        let ty = parse_type_desc("Decimal(9, 2)").unwrap();
+        assert!(matches!(
+            Value::from_rust_decimal(Decimal::new(1_234_567_890, 2), &ty),
+            Err(Error::Overflow(_))
+        ));
+        assert_eq!(
+            Value::from_rust_decimal(Decimal::new(-999_999_999, 2), &ty).unwrap(),
+            Value::Decimal32(-999_999_999)
+        );
+        let ty = parse_type_desc("Decimal(5, 1)").unwrap();
+        assert!(Value::from_rust_decimal(Decimal::new(12_345, 0), &ty).is_err());
+        assert!(Value::from_rust_decimal(Decimal::new(1_234, 0), &ty).is_ok());
+    }
+
+    #[test]
+    fn converts_wide_decimals_when_they_fit() {
+        let ty = parse_type_desc("Decimal(76, 3)").unwrap();
+        let decimal = Decimal::new(-1_000_001, 3);
+        let value = Value::from_rust_decimal(decimal, &ty).unwrap();
+        assert_eq!(value.to_rust_decimal(&ty).unwrap(), decimal);
+
+        let mut huge = [0_u8; 32];
+        huge[20] = 1;
+        assert!(matches!(
+            Value::Decimal256(huge).to_rust_decimal(&ty),
+            Err(Error::Overflow(_))
+        ));
+
+        let too_precise = parse_type_desc("Decimal(38, 30)").unwrap();
+        assert!(Value::Decimal128(1).to_rust_decimal(&too_precise).is_err());
+        assert!(
+            Value::Decimal32(1)
+                .to_rust_decimal(&parse_type_desc("Decimal(18, 2)").unwrap())
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn typed_conversions_use_the_column_scale() {
+        let ty = parse_type_desc("Nullable(Decimal(9, 2))").unwrap();
+        let price = Decimal::new(125, 1);
+        let value = price.to_value(&ty).unwrap();
+        assert_eq!(
+            value,
+            Value::Nullable(Some(Box::new(Value::Decimal32(1250))))
+        );
+        assert_eq!(Decimal::from_value(&ty, &value).unwrap(), price);
+    }
+}
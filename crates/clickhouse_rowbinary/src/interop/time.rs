@@ -0,0 +1,219 @@
+//! Conversions between temporal values and `time` types.
+
+use ::time::{Date, OffsetDateTime};
+
+use super::{date_value, datetime_nanos, datetime64_from_nanos};
+use crate::{
+    error::{Error, Result},
+    typed::{
+        ColumnType, FromValue, IntoValue,
+        from_value::{mismatch as value_mismatch, non_null},
+        into_value::{encode_leaf, mismatch},
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+/// Julian day number of 1970-01-01.
+const UNIX_EPOCH_JULIAN_DAY: i32 = 2_440_588;
+
+impl From<Date> for Value {
+    /// Converts a date into a [`Value::Date32`].
+    ///
+    /// The writer also accepts the result for `Date` columns when the day
+    /// fits the narrower range.
+    fn from(date: Date) -> Self {
+        Value::Date32(date.to_julian_day() - UNIX_EPOCH_JULIAN_DAY)
+    }
+}
+
+impl TryFrom<Value> for Date {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        let days = match value {
+            Value::Date(days) => i32::from(days),
+            Value::Date32(days) => days,
+            other => {
+                return Err(Error::TypeMismatch {
+                    expected: "Date".to_string(),
+                    actual: other.type_name().to_string(),
+                });
+            }
+        };
+        days.checked_add(UNIX_EPOCH_JULIAN_DAY)
+            .and_then(|day| Date::from_julian_day(day).ok())
+            .ok_or(Error::Overflow("date outside time range"))
+    }
+}
+
+impl TryFrom<OffsetDateTime> for Value {
+    type Error = Error;
+
+    /// Converts a timestamp into a [`Value::DateTime`], truncating
+    /// fractional seconds. The offset is ignored; the instant is kept.
+    fn try_from(value: OffsetDateTime) -> Result<Self, Self::Error> {
+        u32::try_from(value.unix_timestamp())
+            .map(Value::DateTime)
+            .map_err(|_| Error::Overflow("timestamp outside DateTime range"))
+    }
+}
+
+impl TryFrom<Value> for OffsetDateTime {
+    type Error = Error;
+
+    /// Converts a [`Value::DateTime`] into a UTC timestamp; use
+    /// [`Value::to_time_datetime`] for `DateTime64`, whose precision comes
+    /// from the column type.
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::DateTime(seconds) => OffsetDateTime::from_unix_timestamp(i64::from(seconds))
+                .map_err(|_| Error::Overflow("timestamp outside time range")),
+            other => Err(Error::TypeMismatch {
+                expected: "DateTime".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl Value {
+    /// Builds a [`Value::DateTime64`] with `precision` decimal places from a
+    /// `time` timestamp, truncating digits beyond the precision.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `precision` exceeds 9, or
+    /// [`Error::Overflow`] when the scaled value does not fit in 64 bits.
+    pub fn from_time_datetime64(value: OffsetDateTime, precision: u8) -> Result<Self> {
+        datetime64_from_nanos(value.unix_timestamp_nanos(), precision)
+    }
+
+    /// Converts a `DateTime` or `DateTime64` value into a UTC timestamp.
+    ///
+    /// `ty` is the column type; it supplies the `DateTime64` precision and
+    /// may be wrapped in `Nullable` or `LowCardinality`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when the value or type is not a
+    /// timestamp, or [`Error::Overflow`] when `time` cannot represent it.
+    pub fn to_time_datetime(&self, ty: &TypeDesc) -> Result<OffsetDateTime> {
+        OffsetDateTime::from_unix_timestamp_nanos(datetime_nanos(self, ty)?)
+            .map_err(|_| Error::Overflow("timestamp outside time range"))
+    }
+}
+
+impl ColumnType for Date {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Date32
+    }
+}
+
+impl ColumnType for OffsetDateTime {
+    /// Microsecond `DateTime64` in UTC.
+    fn column_type() -> TypeDesc {
+        TypeDesc::DateTime64 {
+            precision: 6,
+            timezone: Some("UTC".to_string()),
+        }
+    }
+}
+
+impl IntoValue for Date {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        date_value(ty, self.to_julian_day() - UNIX_EPOCH_JULIAN_DAY)
+    }
+}
+
+impl FromValue<'_> for Date {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "Date")?.1 {
+            value @ (Value::Date(_) | Value::Date32(_)) => Date::try_from(value.clone()),
+            other => Err(value_mismatch("Date", other)),
+        }
+    }
+}
+
+impl IntoValue for OffsetDateTime {
+    /// Encodes the instant into a `DateTime` or `DateTime64` column at the
+    /// column's precision.
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_leaf(ty, "timestamp", &|ty| match ty {
+            TypeDesc::DateTime { .. } => Value::try_from(*self),
+            TypeDesc::DateTime64 { precision, .. } => {
+                Value::from_time_datetime64(*self, *precision)
+            }
+            ty => Err(mismatch(ty, "timestamp")),
+        })
+    }
+}
+
+impl FromValue<'_> for OffsetDateTime {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "OffsetDateTime")?;
+        value.to_time_datetime(ty)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::time::{Date, Month, OffsetDateTime};
+
+    use crate::{types::parse_type_desc, value::Value};
+
+    #[test]
+    fn converts_dates() {
+        let date = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+        let value = Value::from(date);
+        assert_eq!(value, Value::Date32(19_782));
+        assert_eq!(Date::try_from(value).unwrap(), date);
+        assert_eq!(Date::try_from(Value::Date(19_782)).unwrap(), date);
+
+        let before_epoch = Date::from_calendar_date(1900, Month::January, 1).unwrap();
+        assert_eq!(Value::from(before_epoch), Value::Date32(-25_567));
+        assert!(Date::try_from(Value::Date32(i32::MAX)).is_err());
+    }
+
+    #[test]
+    fn converts_datetimes() {
+        let timestamp =
+            OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789).unwrap();
+        assert_eq!(
+            Value::try_from(timestamp).unwrap(),
+            Value::DateTime(1_700_000_000)
+        );
+        assert!(Value::try_from(OffsetDateTime::UNIX_EPOCH - ::time::Duration::SECOND).is_err());
+
+        let value = Value::DateTime(1_700_000_000);
+        let ty = parse_type_desc("Nullable(DateTime('UTC'))").unwrap();
+        assert_eq!(
+            value.to_time_datetime(&ty).unwrap().unix_timestamp(),
+            1_700_000_000
+        );
+        assert_eq!(
+            OffsetDateTime::try_from(value).unwrap().unix_timestamp(),
+            1_700_000_000
+        );
+    }
+
+    #[test]
+    fn converts_datetime64_with_precision() {
+        let timestamp =
+            OffsetDateTime::from_unix_timestamp_nanos(1_700_000_000_123_456_789).unwrap();
+        let value = Value::from_time_datetime64(timestamp, 3).unwrap();
+        assert_eq!(value, Value::DateTime64(1_700_000_000_123));
+
+        let ty = parse_type_desc("DateTime64(3, 'UTC')").unwrap();
+        assert_eq!(
+            value.to_time_datetime(&ty).unwrap().unix_timestamp_nanos(),
+            1_700_000_000_123_000_000
+        );
+
+        let before_epoch = OffsetDateTime::from_unix_timestamp_nanos(-1_500_000_000).unwrap();
+        let value = Value::from_time_datetime64(before_epoch, 3).unwrap();
+        assert_eq!(value, Value::DateTime64(-1_500));
+        assert_eq!(value.to_time_datetime(&ty).unwrap(), before_epoch);
+        assert!(Value::from_time_datetime64(timestamp, 10).is_err());
+    }
+}
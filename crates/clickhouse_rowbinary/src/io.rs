@@ -4,6 +4,49 @@ use std::io::{self, Read, Write};
 
 use crate::error::{Error, Result};
 
+/// Largest buffer reserved up front for a length-prefixed value.
+const PREALLOCATE_LIMIT: usize = 1024 * 1024;
+
+/// Cause of the unexpected-EOF [`Error::Io`] returned when the input ends
+/// inside a value whose length is known.
+#[derive(Debug)]
+pub(crate) struct Shortfall {
+    /// Bytes still missing from the value.
+    pub(crate) missing: usize,
+}
+
+impl std::fmt::Display for Shortfall {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "input ended {} bytes short of a value", self.missing)
+    }
+}
+
+impl std::error::Error for Shortfall {}
+
+/// Returns the error for input that ended `missing` bytes short of a value.
+pub(crate) fn truncated(missing: usize) -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        Shortfall { missing },
+    ))
+}
+
+/// Returns how many more bytes are needed at least when `err` reports input
+/// that ended too early, or `None` for any other error.
+pub(crate) fn missing_bytes(err: &Error) -> Option<usize> {
+    let Error::Io(io_err) = err else {
+        return None;
+    };
+    if io_err.kind() != io::ErrorKind::UnexpectedEof {
+        return None;
+    }
+    let missing = io_err
+        .get_ref()
+        .and_then(|cause| cause.downcast_ref::<Shortfall>())
+        .map_or(1, |shortfall| shortfall.missing);
+    Some(missing)
+}
+
 /// Writes an unsigned varint using `ClickHouse`'s encoding.
 ///
 /// # Errors
@@ -103,8 +146,13 @@ pub fn read_bytes<R: Read + ?Sized>(reader: &mut R) -> Result<Option<Vec<u8>>> {
         return Ok(None);
     };
     let len = usize::try_from(len).map_err(|_| Error::Overflow("byte length too large"))?;
-    let mut buf = vec![0_u8; len];
-    reader.read_exact(&mut buf)?;
+    // The buffer grows with the bytes that arrive, so a corrupt length
+    // cannot allocate more than the input holds.
+    let mut buf = Vec::with_capacity(len.min(PREALLOCATE_LIMIT));
+    let read = reader.take(len as u64).read_to_end(&mut buf)?;
+    if read < len {
+        return Err(truncated(len - read));
+    }
     Ok(Some(buf))
 }
 
@@ -0,0 +1,155 @@
+//! `JSONCompactEachRow` reader and writer.
+
+use std::io::{BufRead, Write};
+
+use crate::{
+    error::{Error, Result},
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::{Row, Schema},
+    value::Value,
+};
+
+use super::{
+    JsonOptions,
+    json::{Json, parse, read_value, write_value},
+    reader::read_line,
+};
+
+/// Reader that decodes `JSONCompactEachRow` lines, one JSON array of
+/// values in schema order per row.
+///
+/// Blank lines are skipped. Skipping key lookup makes this cheaper to
+/// parse than `JSONEachRow`, but every row must carry every column.
+pub struct JsonCompactEachRowReader<R: BufRead> {
+    inner: R,
+    schema: Schema,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> JsonCompactEachRowReader<R> {
+    /// Creates a reader for rows of `schema`.
+    #[must_use]
+    pub fn new(inner: R, schema: Schema) -> Self {
+        Self {
+            inner,
+            schema,
+            line: Vec::new(),
+        }
+    }
+
+    /// Returns the schema rows are decoded with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next row, or returns `Ok(None)` at EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a line is not a JSON array with
+    /// one element per column, a value does not fit its column type, or IO
+    /// fails.
+    pub fn read_row(&mut self) -> Result<Option<Row>> {
+        if !read_line(&mut self.inner, &mut self.line)? {
+            return Ok(None);
+        }
+        let Json::Array(values) = parse(&self.line)? else {
+            return Err(Error::InvalidValue(
+                "JSONCompactEachRow rows must be JSON arrays",
+            ));
+        };
+        if values.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.schema
+            .fields()
+            .iter()
+            .zip(&values)
+            .map(|(field, json)| read_value(&field.ty, json))
+            .collect::<Result<Row>>()
+            .map(Some)
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for JsonCompactEachRowReader<R> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// Writer that encodes rows as `JSONCompactEachRow` lines.
+pub struct JsonCompactEachRowWriter<W: Write> {
+    output: RowOutput<W>,
+    schema: Schema,
+    options: JsonOptions,
+}
+
+impl<W: Write> JsonCompactEachRowWriter<W> {
+    /// Creates a writer using the default [`JsonOptions`].
+    #[must_use]
+    pub fn new(inner: W, schema: Schema) -> Self {
+        Self::with_options(inner, schema, JsonOptions::default())
+    }
+
+    /// Creates a writer with explicit JSON options.
+    /// [`JsonOptions::skip_unknown_fields`] has no effect on output.
+    #[must_use]
+    pub fn with_options(inner: W, schema: Schema, options: JsonOptions) -> Self {
+        Self {
+            output: RowOutput::new(inner),
+            schema,
+            options,
+        }
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Encodes and writes one row as a JSON array line.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, a column type is not supported, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            out.push(b'[');
+            for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_value(&field.ty, value, &self.options, out)?;
+            }
+            out.extend_from_slice(b"]\n");
+            Ok(())
+        })
+    }
+}
+
+impl_row_writer!(JsonCompactEachRowWriter);
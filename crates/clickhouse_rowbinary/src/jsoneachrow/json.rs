@@ -0,0 +1,744 @@
+//! JSON parsing and the schema-driven mapping between JSON and values.
+
+use std::borrow::Cow;
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::default_value,
+    text::{
+        fixed_string, format_scalar, infer_array, infer_number, is_composite, is_numeric,
+        parse_scalar, text_type,
+    },
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+use super::JsonOptions;
+
+/// Deepest array/object nesting accepted by the parser.
+const MAX_DEPTH: usize = 256;
+
+/// Parsed JSON document borrowing from its input.
+#[derive(Debug)]
+pub(crate) enum Json<'a> {
+    Null,
+    Bool(bool),
+    /// Number kept as its source text so wide integers and decimals keep
+    /// their precision.
+    Number(&'a str),
+    String(Cow<'a, [u8]>),
+    Array(Vec<Json<'a>>),
+    Object(Vec<(Cow<'a, [u8]>, Json<'a>)>),
+}
+
+/// Parses the single JSON document making up `input`, which may be
+/// surrounded by whitespace.
+pub(crate) fn parse(input: &[u8]) -> Result<Json<'_>> {
+    let mut parser = Parser { input, pos: 0 };
+    let json = parser.value(0)?;
+    parser.skip_whitespace();
+    if parser.pos != input.len() {
+        return Err(Error::InvalidValue("trailing characters after JSON value"));
+    }
+    Ok(json)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.input.get(self.pos) {
+            self.pos += 1;
+        }
+    }
+
+    fn peek(&mut self) -> Result<u8> {
+        self.skip_whitespace();
+        self.input
+            .get(self.pos)
+            .copied()
+            .ok_or(Error::InvalidValue("unexpected end of JSON"))
+    }
+
+    fn expect(&mut self, byte: u8, message: &'static str) -> Result<()> {
+        if self.peek()? != byte {
+            return Err(Error::InvalidValue(message));
+        }
+        self.pos += 1;
+        Ok(())
+    }
+
+    fn literal(&mut self, word: &[u8], json: Json<'a>) -> Result<Json<'a>> {
+        if !self.input[self.pos..].starts_with(word) {
+            return Err(Error::InvalidValue("invalid JSON literal"));
+        }
+        self.pos += word.len();
+        Ok(json)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<Json<'a>> {
+        match self.peek()? {
+            b'n' => self.literal(b"null", Json::Null),
+            b't' => self.literal(b"true", Json::Bool(true)),
+            b'f' => self.literal(b"false", Json::Bool(false)),
+            b'"' => self.string().map(Json::String),
+            b'[' | b'{' if depth >= MAX_DEPTH => {
+                Err(Error::InvalidValue("JSON nesting is too deep"))
+            }
+            b'[' => {
+                self.pos += 1;
+                let mut items = Vec::new();
+                if self.peek()? == b']' {
+                    self.pos += 1;
+                    return Ok(Json::Array(items));
+                }
+                loop {
+                    items.push(self.value(depth + 1)?);
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b']' => {
+                            self.pos += 1;
+                            return Ok(Json::Array(items));
+                        }
+                        _ => return Err(Error::InvalidValue("expected ',' or ']' in JSON array")),
+                    }
+                }
+            }
+            b'{' => {
+                self.pos += 1;
+                let mut fields = Vec::new();
+                if self.peek()? == b'}' {
+                    self.pos += 1;
+                    return Ok(Json::Object(fields));
+                }
+                loop {
+                    if self.peek()? != b'"' {
+                        return Err(Error::InvalidValue("expected a JSON object key"));
+                    }
+                    let key = self.string()?;
+                    self.expect(b':', "expected ':' after JSON object key")?;
+                    fields.push((key, self.value(depth + 1)?));
+                    match self.peek()? {
+                        b',' => self.pos += 1,
+                        b'}' => {
+                            self.pos += 1;
+                            return Ok(Json::Object(fields));
+                        }
+                        _ => return Err(Error::InvalidValue("expected ',' or '}' in JSON object")),
+                    }
+                }
+            }
+            _ => self.number(),
+        }
+    }
+
+    fn number(&mut self) -> Result<Json<'a>> {
+        let invalid = Error::InvalidValue("invalid JSON value");
+        let start = self.pos;
+        let digits = |parser: &mut Self| {
+            let begin = parser.pos;
+            while parser.input.get(parser.pos).is_some_and(u8::is_ascii_digit) {
+                parser.pos += 1;
+            }
+            parser.pos > begin
+        };
+        if self.input.get(self.pos) == Some(&b'-') {
+            self.pos += 1;
+        }
+        if self.input.get(self.pos) == Some(&b'0') {
+            self.pos += 1;
+            if self.input.get(self.pos).is_some_and(u8::is_ascii_digit) {
+                return Err(invalid);
+            }
+        } else if !digits(self) {
+            return Err(invalid);
+        }
+        if self.input.get(self.pos) == Some(&b'.') {
+            self.pos += 1;
+            if !digits(self) {
+                return Err(invalid);
+            }
+        }
+        if let Some(b'e' | b'E') = self.input.get(self.pos) {
+            self.pos += 1;
+            if let Some(b'+' | b'-') = self.input.get(self.pos) {
+                self.pos += 1;
+            }
+            if !digits(self) {
+                return Err(invalid);
+            }
+        }
+        // The scanned bytes are ASCII by construction.
+        std::str::from_utf8(&self.input[start..self.pos])
+            .map(Json::Number)
+            .map_err(|_| invalid)
+    }
+
+    fn string(&mut self) -> Result<Cow<'a, [u8]>> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut owned: Option<Vec<u8>> = None;
+        loop {
+            let Some(&byte) = self.input.get(self.pos) else {
+                return Err(Error::InvalidValue("unterminated JSON string"));
+            };
+            match byte {
+                b'"' => {
+                    let borrowed = &self.input[start..self.pos];
+                    self.pos += 1;
+                    return Ok(owned.map_or(Cow::Borrowed(borrowed), Cow::Owned));
+                }
+                b'\\' => {
+                    let out = owned.get_or_insert_with(|| self.input[start..self.pos].to_vec());
+                    let escape = *self
+                        .input
+                        .get(self.pos + 1)
+                        .ok_or(Error::InvalidValue("unterminated JSON string"))?;
+                    self.pos += 2;
+                    let decoded = match escape {
+                        b'"' => b'"',
+                        b'\\' => b'\\',
+                        b'/' => b'/',
+                        b'b' => 0x08,
+                        b'f' => 0x0c,
+                        b'n' => b'\n',
+                        b'r' => b'\r',
+                        b't' => b'\t',
+                        b'u' => {
+                            let ch = unicode_escape(self.input, &mut self.pos)?;
+                            out.extend_from_slice(ch.encode_utf8(&mut [0; 4]).as_bytes());
+                            continue;
+                        }
+                        _ => return Err(Error::InvalidValue("invalid JSON string escape")),
+                    };
+                    out.push(decoded);
+                }
+                0..0x20 => return Err(Error::InvalidValue("control character in JSON string")),
+                _ => {
+                    if let Some(out) = owned.as_mut() {
+                        out.push(byte);
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the hex digits of a `\u` escape at `pos`, combining surrogate
+/// pairs.
+fn unicode_escape(input: &[u8], pos: &mut usize) -> Result<char> {
+    let hex = |pos: &mut usize| -> Result<u32> {
+        let digits = input.get(*pos..*pos + 4).ok_or_else(invalid_escape)?;
+        let digits = std::str::from_utf8(digits).map_err(|_| invalid_escape())?;
+        *pos += 4;
+        u32::from_str_radix(digits, 16).map_err(|_| invalid_escape())
+    };
+    let high = hex(pos)?;
+    let code = if (0xD800..0xDC00).contains(&high) {
+        if input.get(*pos..*pos + 2) != Some(b"\\u") {
+            return Err(invalid_escape());
+        }
+        *pos += 2;
+        let low = hex(pos)?;
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(invalid_escape());
+        }
+        0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00)
+    } else {
+        high
+    };
+    char::from_u32(code).ok_or_else(invalid_escape)
+}
+
+fn invalid_escape() -> Error {
+    Error::InvalidValue("invalid JSON unicode escape")
+}
+
+/// Appends `bytes` as a JSON string. Bytes that are not valid UTF-8 are
+/// copied through unchanged, as `ClickHouse` does.
+pub(crate) fn write_string(bytes: &[u8], out: &mut Vec<u8>) {
+    const HEX: &[u8; 16] = b"0123456789abcdef";
+    out.push(b'"');
+    for &byte in bytes {
+        match byte {
+            b'"' => out.extend_from_slice(b"\\\""),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0c => out.extend_from_slice(b"\\f"),
+            0..0x20 => {
+                out.extend_from_slice(b"\\u00");
+                out.push(HEX[usize::from(byte >> 4)]);
+                out.push(HEX[usize::from(byte & 0xf)]);
+            }
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'"');
+}
+
+fn mismatch(ty: &TypeDesc, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+fn unsupported_aggregate() -> Error {
+    Error::UnsupportedCombination("JSON formats do not support AggregateFunction columns".into())
+}
+
+/// Appends `value` of type `ty` as JSON.
+pub(crate) fn write_value(
+    ty: &TypeDesc,
+    value: &Value,
+    options: &JsonOptions,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), value) {
+        (TypeDesc::Nothing, Value::Nothing)
+        | (TypeDesc::Nullable(_), Value::Nullable(None))
+        | (TypeDesc::Variant(_), Value::VariantNull)
+        | (TypeDesc::Dynamic { .. }, Value::DynamicNull | Value::Nullable(None)) => {
+            out.extend_from_slice(b"null");
+        }
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            write_value(inner, value, options, out)?;
+        }
+        (TypeDesc::String, Value::String(bytes))
+        | (TypeDesc::FixedString { .. }, Value::FixedString(bytes)) => write_string(bytes, out),
+        (TypeDesc::Array(inner), Value::Array(items)) => {
+            out.push(b'[');
+            for (index, item) in items.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_value(inner, item, options, out)?;
+            }
+            out.push(b']');
+        }
+        (TypeDesc::Nested(items), Value::Array(rows)) => {
+            out.push(b'[');
+            for (index, row) in rows.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                let Value::Tuple(values) = row else {
+                    return Err(mismatch(&TypeDesc::Tuple(items.clone()), row));
+                };
+                write_tuple(items, values, options, out)?;
+            }
+            out.push(b']');
+        }
+        (TypeDesc::Tuple(items), Value::Tuple(values)) => {
+            write_tuple(items, values, options, out)?;
+        }
+        (TypeDesc::Map { key, value: inner }, Value::Map(entries)) => {
+            out.push(b'{');
+            for (index, (entry_key, entry_value)) in entries.iter().enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_string(&map_key_text(key, entry_key)?, out);
+                out.push(b':');
+                write_value(inner, entry_value, options, out)?;
+            }
+            out.push(b'}');
+        }
+        (TypeDesc::Variant(types), Value::Variant { index, value }) => {
+            let ty = types
+                .get(usize::from(*index))
+                .ok_or(Error::InvalidValue("variant index out of range"))?;
+            write_value(ty, value, options, out)?;
+        }
+        (TypeDesc::Dynamic { .. }, Value::Dynamic { ty, value }) => {
+            write_value(ty, value, options, out)?;
+        }
+        (TypeDesc::Json { typed_paths, .. }, Value::JsonObject(entries)) => {
+            let dynamic = TypeDesc::Dynamic { max_types: None };
+            let paths: Vec<(&str, &TypeDesc, &Value)> = entries
+                .iter()
+                .map(|(path, value)| {
+                    let ty = typed_paths
+                        .iter()
+                        .find(|(name, _)| name == path)
+                        .map_or(&dynamic, |(_, ty)| ty);
+                    (path.as_str(), ty, value)
+                })
+                .collect();
+            write_json_paths(&paths, options, out)?;
+        }
+        (TypeDesc::AggregateFunction { .. }, _) => return Err(unsupported_aggregate()),
+        (
+            TypeDesc::Float32 | TypeDesc::Float16 | TypeDesc::BFloat16,
+            Value::Float32(value) | Value::Float16(value) | Value::BFloat16(value),
+        ) if !value.is_finite() => out.extend_from_slice(b"null"),
+        (TypeDesc::Float64, Value::Float64(value)) if !value.is_finite() => {
+            out.extend_from_slice(b"null");
+        }
+        (ty, value) => {
+            let mut text = String::new();
+            format_scalar(ty, value, &mut text)?;
+            if is_quoted(ty, options) {
+                write_string(text.as_bytes(), out);
+            } else {
+                out.extend_from_slice(text.as_bytes());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports whether scalars of `ty` are written as JSON strings.
+fn is_quoted(ty: &TypeDesc, options: &JsonOptions) -> bool {
+    match ty {
+        TypeDesc::UInt64
+        | TypeDesc::Int64
+        | TypeDesc::UInt128
+        | TypeDesc::Int128
+        | TypeDesc::UInt256
+        | TypeDesc::Int256
+        | TypeDesc::Interval(_) => options.quote_64bit_integers,
+        ty => !is_numeric(ty),
+    }
+}
+
+fn write_tuple(
+    items: &[TupleItem],
+    values: &[Value],
+    options: &JsonOptions,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    if items.len() != values.len() {
+        return Err(Error::InvalidValue("tuple length does not match its type"));
+    }
+    let named = options.named_tuples_as_objects && items.iter().all(|item| item.name.is_some());
+    out.push(if named { b'{' } else { b'[' });
+    for (index, (item, value)) in items.iter().zip(values).enumerate() {
+        if index > 0 {
+            out.push(b',');
+        }
+        if let (true, Some(name)) = (named, &item.name) {
+            write_string(name.as_bytes(), out);
+            out.push(b':');
+        }
+        write_value(&item.ty, value, options, out)?;
+    }
+    out.push(if named { b'}' } else { b']' });
+    Ok(())
+}
+
+/// Writes dotted JSON paths as nested objects.
+fn write_json_paths(
+    paths: &[(&str, &TypeDesc, &Value)],
+    options: &JsonOptions,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    out.push(b'{');
+    let mut written: Vec<&str> = Vec::new();
+    for (path, ty, value) in paths {
+        let head = path.split_once('.').map_or(*path, |(head, _)| head);
+        if written.contains(&head) {
+            continue;
+        }
+        if !written.is_empty() {
+            out.push(b',');
+        }
+        written.push(head);
+        write_string(head.as_bytes(), out);
+        out.push(b':');
+        if head.len() == path.len() {
+            write_value(ty, value, options, out)?;
+            continue;
+        }
+        let children: Vec<(&str, &TypeDesc, &Value)> = paths
+            .iter()
+            .filter_map(|(path, ty, value)| {
+                let rest = path.strip_prefix(head)?.strip_prefix('.')?;
+                Some((rest, *ty, *value))
+            })
+            .collect();
+        write_json_paths(&children, options, out)?;
+    }
+    out.push(b'}');
+    Ok(())
+}
+
+/// Renders a map key as the text of an object member name.
+pub(crate) fn map_key_text(ty: &TypeDesc, key: &Value) -> Result<Vec<u8>> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), key) {
+        (TypeDesc::String, Value::String(bytes))
+        | (TypeDesc::FixedString { .. }, Value::FixedString(bytes)) => Ok(bytes.clone()),
+        (ty, key) => {
+            let mut text = String::new();
+            format_scalar(ty, key, &mut text)?;
+            Ok(text.into_bytes())
+        }
+    }
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|_| Error::InvalidValue("JSON text is not valid UTF-8"))
+}
+
+fn unexpected(ty: &TypeDesc) -> Error {
+    Error::InvalidValue(match ty {
+        TypeDesc::Array(_) | TypeDesc::Nested(_) => "expected a JSON array",
+        TypeDesc::Map { .. } | TypeDesc::Json { .. } => "expected a JSON object",
+        TypeDesc::Tuple(_) => "expected a JSON array or object",
+        TypeDesc::String | TypeDesc::FixedString { .. } => "expected a JSON string",
+        _ => "JSON value does not match the column type",
+    })
+}
+
+/// Decodes `json` as a value of type `ty`.
+///
+/// A `null` for a type that cannot hold one decodes as the type's default
+/// value, and numbers are accepted both bare and quoted.
+pub(crate) fn read_value(ty: &TypeDesc, json: &Json<'_>) -> Result<Value> {
+    let ty = text_type(ty);
+    let ty = ty.as_ref();
+    Ok(match (ty, json) {
+        (TypeDesc::AggregateFunction { .. }, _) => return Err(unsupported_aggregate()),
+        (TypeDesc::Nothing | TypeDesc::Nullable(_), Json::Null) => {
+            if let TypeDesc::Nullable(_) = ty {
+                Value::Nullable(None)
+            } else {
+                Value::Nothing
+            }
+        }
+        (TypeDesc::Variant(_), Json::Null) => Value::VariantNull,
+        (TypeDesc::Dynamic { .. }, Json::Null) => Value::DynamicNull,
+        (_, Json::Null) => default_value(ty)?,
+        (TypeDesc::Nullable(inner), json) => {
+            Value::Nullable(Some(Box::new(read_value(inner, json)?)))
+        }
+        (TypeDesc::String, json) => Value::String(match json {
+            Json::String(bytes) => bytes.to_vec(),
+            Json::Number(text) => text.as_bytes().to_vec(),
+            Json::Bool(value) => value.to_string().into_bytes(),
+            _ => return Err(unexpected(ty)),
+        }),
+        (TypeDesc::FixedString { length }, Json::String(bytes)) => fixed_string(bytes, *length)?,
+        (TypeDesc::Array(inner), Json::Array(items)) => Value::Array(
+            items
+                .iter()
+                .map(|item| read_value(inner, item))
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Nested(items), Json::Array(rows)) => Value::Array(
+            rows.iter()
+                .map(|row| read_tuple(items, row))
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Tuple(items), json) => read_tuple(items, json)?,
+        (TypeDesc::Map { key, value }, Json::Object(fields)) => Value::Map(
+            fields
+                .iter()
+                .map(|(name, json)| Ok((map_key(key, name)?, read_value(value, json)?)))
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Variant(types), json) => read_variant(types, json)?,
+        (TypeDesc::Dynamic { .. }, json) => match infer_dynamic(json)? {
+            Some((ty, value)) => Value::Dynamic {
+                ty: Box::new(ty),
+                value: Box::new(value),
+            },
+            None => Value::DynamicNull,
+        },
+        (
+            TypeDesc::Json {
+                typed_paths,
+                skip_paths,
+                ..
+            },
+            Json::Object(fields),
+        ) => {
+            let mut entries = Vec::new();
+            flatten_json(fields, "", typed_paths, skip_paths, &mut entries)?;
+            Value::JsonObject(entries)
+        }
+        (TypeDesc::Bool, Json::Bool(value)) => Value::Bool(*value),
+        (ty, Json::Number(text)) => parse_scalar(ty, text)?,
+        (ty, Json::String(bytes)) if !matches!(ty, TypeDesc::FixedString { .. }) => {
+            let text = utf8(bytes)?;
+            // Quoted scalars must not fall through to composite types.
+            match ty {
+                TypeDesc::Array(_)
+                | TypeDesc::Nested(_)
+                | TypeDesc::Map { .. }
+                | TypeDesc::Json { .. } => {
+                    return Err(unexpected(ty));
+                }
+                _ => parse_scalar(ty, text)?,
+            }
+        }
+        (ty, _) => return Err(unexpected(ty)),
+    })
+}
+
+fn read_tuple(items: &[TupleItem], json: &Json<'_>) -> Result<Value> {
+    match json {
+        Json::Array(values) => {
+            if values.len() != items.len() {
+                return Err(Error::InvalidValue(
+                    "JSON array length does not match the tuple",
+                ));
+            }
+            Ok(Value::Tuple(
+                items
+                    .iter()
+                    .zip(values)
+                    .map(|(item, json)| read_value(&item.ty, json))
+                    .collect::<Result<_>>()?,
+            ))
+        }
+        Json::Object(fields) => {
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                let Some(name) = &item.name else {
+                    return Err(Error::InvalidValue("unnamed tuples must be JSON arrays"));
+                };
+                let json = fields
+                    .iter()
+                    .find(|(key, _)| key.as_ref() == name.as_bytes())
+                    .map(|(_, json)| json);
+                values.push(match json {
+                    Some(json) => read_value(&item.ty, json)?,
+                    None => default_value(&item.ty)?,
+                });
+            }
+            Ok(Value::Tuple(values))
+        }
+        _ => Err(unexpected(&TypeDesc::Tuple(Vec::new()))),
+    }
+}
+
+/// Decodes an object member name as a map key of type `ty`.
+pub(crate) fn map_key(ty: &TypeDesc, name: &[u8]) -> Result<Value> {
+    let ty = text_type(ty);
+    match ty.as_ref() {
+        TypeDesc::String => Ok(Value::String(name.to_vec())),
+        ty @ TypeDesc::FixedString { .. } => read_value(ty, &Json::String(Cow::Borrowed(name))),
+        ty => parse_scalar(ty, utf8(name)?),
+    }
+}
+
+/// Picks the first variant whose JSON shape matches `json`, falling back
+/// to the first alternative that accepts it at all.
+fn read_variant(types: &[TypeDesc], json: &Json<'_>) -> Result<Value> {
+    let matching = types
+        .iter()
+        .enumerate()
+        .filter(|(_, ty)| natural_match(ty, json));
+    for (index, ty) in matching.chain(types.iter().enumerate()) {
+        if let Ok(value) = read_value(ty, json) {
+            return Ok(Value::Variant {
+                index: u8::try_from(index).map_err(|_| Error::Overflow("too many variants"))?,
+                value: Box::new(value),
+            });
+        }
+    }
+    Err(Error::InvalidValue(
+        "JSON value matches no variant alternative",
+    ))
+}
+
+fn natural_match(ty: &TypeDesc, json: &Json<'_>) -> bool {
+    let ty = text_type(ty);
+    match json {
+        Json::Null => false,
+        Json::Bool(_) => matches!(ty.as_ref(), TypeDesc::Bool),
+        Json::Number(_) => is_numeric(&ty) && !matches!(ty.as_ref(), TypeDesc::Bool),
+        Json::String(_) => !is_numeric(&ty) && !is_composite(&ty),
+        Json::Array(_) => matches!(
+            ty.as_ref(),
+            TypeDesc::Array(_) | TypeDesc::Nested(_) | TypeDesc::Tuple(_)
+        ),
+        Json::Object(_) => matches!(
+            ty.as_ref(),
+            TypeDesc::Map { .. } | TypeDesc::Tuple(_) | TypeDesc::Json { .. }
+        ),
+    }
+}
+
+/// Infers the type of a JSON value stored in a `Dynamic` column, or
+/// `None` for `null`.
+fn infer_dynamic(json: &Json<'_>) -> Result<Option<(TypeDesc, Value)>> {
+    Ok(Some(match json {
+        Json::Null => return Ok(None),
+        Json::Bool(value) => (TypeDesc::Bool, Value::Bool(*value)),
+        Json::Number(text) => infer_number(text)?,
+        Json::String(bytes) => (TypeDesc::String, Value::String(bytes.to_vec())),
+        Json::Array(items) => infer_array(
+            items
+                .iter()
+                .map(infer_dynamic)
+                .collect::<Result<Vec<_>>>()?,
+        )?,
+        Json::Object(_) => {
+            return Err(Error::UnsupportedCombination(
+                "Dynamic values cannot hold JSON objects".into(),
+            ));
+        }
+    }))
+}
+
+/// Flattens nested JSON objects into dotted paths for a `JSON` column.
+/// Typed paths keep their declared type, `null` leaves are dropped and
+/// other leaves become `Dynamic` values.
+fn flatten_json(
+    fields: &[(Cow<'_, [u8]>, Json<'_>)],
+    prefix: &str,
+    typed_paths: &[(String, TypeDesc)],
+    skip_paths: &[String],
+    entries: &mut Vec<(String, Value)>,
+) -> Result<()> {
+    for (key, json) in fields {
+        let path = format!("{prefix}{}", utf8(key)?);
+        if skip_paths.iter().any(|skip| {
+            path == *skip
+                || path
+                    .strip_prefix(skip.as_str())
+                    .is_some_and(|rest| rest.starts_with('.'))
+        }) {
+            continue;
+        }
+        if let Some((_, ty)) = typed_paths.iter().find(|(name, _)| *name == path) {
+            entries.push((path, read_value(ty, json)?));
+            continue;
+        }
+        match json {
+            Json::Null => {}
+            Json::Object(fields) => {
+                flatten_json(
+                    fields,
+                    &format!("{path}."),
+                    typed_paths,
+                    skip_paths,
+                    entries,
+                )?;
+            }
+            json => {
+                if let Some((ty, value)) = infer_dynamic(json)? {
+                    entries.push((
+                        path,
+                        Value::Dynamic {
+                            ty: Box::new(ty),
+                            value: Box::new(value),
+                        },
+                    ));
+                }
+            }
+        }
+    }
+    Ok(())
+}
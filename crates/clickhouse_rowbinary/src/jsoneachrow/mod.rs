@@ -0,0 +1,62 @@
+//! `JSONEachRow` and `JSONCompactEachRow` format support.
+//!
+//! `JSONEachRow` sends one JSON object per line, keyed by column name;
+//! `JSONCompactEachRow` sends one JSON array of values in column order.
+//! Values are typed by a [`Schema`](crate::Schema), so numbers, dates and
+//! decimals decode to the same [`Value`](crate::Value)s as `RowBinary`:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{JsonEachRowReader, JsonEachRowWriter, Schema, Value};
+//! let schema = Schema::from_type_strings(&[("id", "UInt64"), ("day", "Date")])?;
+//! let mut writer = JsonEachRowWriter::new(Vec::new(), schema.clone());
+//! writer.write_row(&[Value::UInt64(1), Value::Date(19_723)])?;
+//! let payload = writer.into_inner();
+//! assert_eq!(payload, b"{\"id\":\"1\",\"day\":\"2024-01-01\"}\n");
+//!
+//! let mut reader = JsonEachRowReader::new(payload.as_slice(), schema);
+//! assert_eq!(
+//!     reader.read_row()?,
+//!     Some(vec![Value::UInt64(1), Value::Date(19_723)])
+//! );
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! Output follows the server's default JSON settings, except that
+//! date-times are written as Unix timestamps and read either as timestamps
+//! or as UTC text. On input, missing keys and `null`s in columns
+//! that cannot hold one take the column's default value, numbers may be
+//! quoted, and `Dynamic` values infer their type from the JSON value.
+//! `AggregateFunction` columns are not supported.
+
+mod compact;
+pub(crate) mod json;
+mod reader;
+mod writer;
+
+pub use compact::{JsonCompactEachRowReader, JsonCompactEachRowWriter};
+pub use reader::JsonEachRowReader;
+pub use writer::JsonEachRowWriter;
+
+/// Options matching the server's JSON format settings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonOptions {
+    /// 64-bit and wider integers are written as JSON strings, as with
+    /// `output_format_json_quote_64bit_integers`.
+    pub quote_64bit_integers: bool,
+    /// Tuples with named elements are written as JSON objects, as with
+    /// `output_format_json_named_tuples_as_objects`.
+    pub named_tuples_as_objects: bool,
+    /// Keys that name no schema column are ignored instead of rejected, as
+    /// with `input_format_skip_unknown_fields`.
+    pub skip_unknown_fields: bool,
+}
+
+impl Default for JsonOptions {
+    fn default() -> Self {
+        Self {
+            quote_64bit_integers: true,
+            named_tuples_as_objects: true,
+            skip_unknown_fields: true,
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! `JSONEachRow` reader.
+
+use std::{collections::HashMap, io::BufRead};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Row, Schema, default_value},
+};
+
+use super::{
+    JsonOptions,
+    json::{Json, parse, read_value},
+};
+
+/// Reader that decodes `JSONEachRow` lines into rows of the given schema.
+///
+/// Blank lines are skipped. Keys may appear in any order; columns without
+/// a key take their default value.
+pub struct JsonEachRowReader<R: BufRead> {
+    inner: R,
+    schema: Schema,
+    options: JsonOptions,
+    columns: HashMap<String, usize>,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> JsonEachRowReader<R> {
+    /// Creates a reader using the default [`JsonOptions`].
+    #[must_use]
+    pub fn new(inner: R, schema: Schema) -> Self {
+        Self::with_options(inner, schema, JsonOptions::default())
+    }
+
+    /// Creates a reader with explicit JSON options.
+    #[must_use]
+    pub fn with_options(inner: R, schema: Schema, options: JsonOptions) -> Self {
+        let columns = schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (field.name.clone(), index))
+            .collect();
+        Self {
+            inner,
+            schema,
+            options,
+            columns,
+            line: Vec::new(),
+        }
+    }
+
+    /// Returns the schema rows are decoded with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next row, or returns `Ok(None)` at EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] for keys outside the schema unless
+    /// [`JsonOptions::skip_unknown_fields`] is set, and
+    /// [`crate::error::Error`] when a line is not a JSON object, a value
+    /// does not fit its column type, or IO fails.
+    pub fn read_row(&mut self) -> Result<Option<Row>> {
+        if !read_line(&mut self.inner, &mut self.line)? {
+            return Ok(None);
+        }
+        let Json::Object(fields) = parse(&self.line)? else {
+            return Err(Error::InvalidValue("JSONEachRow rows must be JSON objects"));
+        };
+        let mut row = vec![None; self.schema.len()];
+        for (key, json) in &fields {
+            let name = std::str::from_utf8(key)
+                .map_err(|_| Error::InvalidValue("JSON text is not valid UTF-8"))?;
+            let Some(&index) = self.columns.get(name) else {
+                if self.options.skip_unknown_fields {
+                    continue;
+                }
+                return Err(Error::UnknownColumn(name.to_string()));
+            };
+            if row[index].is_some() {
+                return Err(Error::InvalidValue("duplicate key in JSON row"));
+            }
+            row[index] = Some(read_value(&self.schema.fields()[index].ty, json)?);
+        }
+        self.schema
+            .fields()
+            .iter()
+            .zip(row)
+            .map(|(field, value)| value.map_or_else(|| default_value(&field.ty), Ok))
+            .collect::<Result<Row>>()
+            .map(Some)
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for JsonEachRowReader<R> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// Reads the next non-blank line into `line`, returning `false` at EOF.
+pub(super) fn read_line<R: BufRead>(inner: &mut R, line: &mut Vec<u8>) -> Result<bool> {
+    loop {
+        line.clear();
+        if inner.read_until(b'\n', line)? == 0 {
+            return Ok(false);
+        }
+        if !line.iter().all(u8::is_ascii_whitespace) {
+            return Ok(true);
+        }
+    }
+}
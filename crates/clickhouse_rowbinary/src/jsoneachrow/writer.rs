@@ -0,0 +1,73 @@
+//! `JSONEachRow` writer.
+
+use std::io::Write;
+
+use crate::{
+    error::{Error, Result},
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::Schema,
+    value::Value,
+};
+
+use super::{
+    JsonOptions,
+    json::{write_string, write_value},
+};
+
+/// Writer that encodes rows as `JSONEachRow` lines.
+pub struct JsonEachRowWriter<W: Write> {
+    output: RowOutput<W>,
+    schema: Schema,
+    options: JsonOptions,
+}
+
+impl<W: Write> JsonEachRowWriter<W> {
+    /// Creates a writer using the default [`JsonOptions`].
+    #[must_use]
+    pub fn new(inner: W, schema: Schema) -> Self {
+        Self::with_options(inner, schema, JsonOptions::default())
+    }
+
+    /// Creates a writer with explicit JSON options.
+    #[must_use]
+    pub fn with_options(inner: W, schema: Schema, options: JsonOptions) -> Self {
+        Self {
+            output: RowOutput::new(inner),
+            schema,
+            options,
+        }
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Encodes and writes one row as a JSON object line.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, a column type is not supported, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            out.push(b'{');
+            for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_string(field.name.as_bytes(), out);
+                out.push(b':');
+                write_value(&field.ty, value, &self.options, out)?;
+            }
+            out.extend_from_slice(b"}\n");
+            Ok(())
+        })
+    }
+}
+
+impl_row_writer!(JsonEachRowWriter);
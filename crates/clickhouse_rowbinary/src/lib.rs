@@ -1,16 +1,79 @@
 #![deny(missing_docs)]
 //! `RowBinary` read/write support for `ClickHouse` formats.
 
+// Lets `#[derive(ClickhouseRow)]` output refer to this crate in unit tests.
+#[cfg(all(test, feature = "derive"))]
+extern crate self as clickhouse_rowbinary;
+
+#[cfg(feature = "arrow")]
+pub mod arrow;
+pub mod avro;
+pub mod compression;
+pub mod csv;
+#[cfg(any(feature = "http", feature = "async-http", feature = "tcp"))]
+pub mod endpoints;
 pub mod error;
+#[cfg(any(feature = "http", feature = "async-http"))]
+pub mod http;
+mod interop;
 pub mod io;
+pub mod jsoneachrow;
+pub mod native;
+#[cfg(feature = "parquet")]
+pub mod parquet;
+#[cfg(feature = "polars")]
+pub mod polars;
+mod row_writer;
 pub mod rowbinary;
+#[cfg(feature = "serde")]
+mod serde;
+#[cfg(feature = "tcp")]
+pub mod tcp;
+mod text;
+#[cfg(all(
+    feature = "tls",
+    any(feature = "http", feature = "async-http", feature = "tcp")
+))]
+mod tls;
+pub mod tsv;
+mod typed;
 pub mod types;
 pub mod value;
+pub mod values;
 
+#[cfg(feature = "serde")]
+pub use crate::serde::{from_row, from_value, to_row, to_value};
+pub use avro::{AvroReader, AvroWriter};
+#[cfg(feature = "derive")]
+pub use clickhouse_rowbinary_derive::ClickhouseRow;
+pub use compression::{
+    CompressedReader, CompressedWriter, CompressionMethod, ContentEncoding, DecompressingReader,
+};
+pub use csv::{CsvFormat, CsvOptions, CsvReader, CsvWriter};
+#[cfg(any(feature = "http", feature = "async-http", feature = "tcp"))]
+pub use endpoints::{Endpoints, LoadBalancing};
 pub use error::{Error, Result};
+pub use jsoneachrow::{
+    JsonCompactEachRowReader, JsonCompactEachRowWriter, JsonEachRowReader, JsonEachRowWriter,
+    JsonOptions,
+};
+pub use native::{Block, BlockInfo, NativeOptions, NativeReader, NativeWriter};
+#[cfg(feature = "async")]
+pub use rowbinary::{AsyncRowBinaryReader, AsyncRowBinaryWriter};
 pub use rowbinary::{
-    Field, Row, RowBinaryFileReader, RowBinaryFileWriter, RowBinaryFormat, RowBinaryHeader,
-    RowBinaryReader, RowBinaryValueReader, RowBinaryValueWriter, RowBinaryWriter, Schema,
+    ColumnCodec, ColumnValue, DecodeColumn, EncodeColumn, Field, FlushPolicy, FromColumnValue,
+    HeaderPolicy, PushDecoded, ReaderOptions, Row, RowBinaryFileReader, RowBinaryFileWriter,
+    RowBinaryFormat, RowBinaryHeader, RowBinaryPushDecoder, RowBinaryReader, RowBinaryRefReader,
+    RowBinaryValueReader, RowBinaryValueWriter, RowBinaryWriter, Schema, WriteStats,
+};
+pub use tsv::{TsvFormat, TsvReader, TsvWriter};
+#[cfg(feature = "derive")]
+#[doc(hidden)]
+pub use typed::derive as __private;
+pub use typed::{ClickhouseRow, ColumnType, FromRow, FromValue, IntoValue, RowView, ToRow};
+pub use types::{
+    DEFAULT_MAX_TYPE_DEPTH, DecimalSize, IntervalKind, TypeDesc, parse_type_desc,
+    parse_type_desc_with_max_depth,
 };
-pub use types::{DecimalSize, TypeDesc, parse_type_desc};
-pub use value::Value;
+pub use value::{Value, ValueRef};
+pub use values::ValuesWriter;
@@ -0,0 +1,125 @@
+//! Column-oriented blocks of decoded values.
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Row, Schema},
+    value::Value,
+};
+
+/// Block metadata sent ahead of each block in the TCP protocol.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct BlockInfo {
+    /// Whether the block holds the rows that exceeded `max_rows_to_group_by`.
+    pub is_overflows: bool,
+    /// Bucket of two-level aggregation, or `-1`.
+    pub bucket_num: i32,
+}
+
+impl Default for BlockInfo {
+    fn default() -> Self {
+        Self {
+            is_overflows: false,
+            bucket_num: -1,
+        }
+    }
+}
+
+/// Block of rows stored column by column, the unit of the `Native` format.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Block {
+    /// Block metadata, encoded only with [`crate::NativeOptions::block_info`].
+    pub info: BlockInfo,
+    schema: Schema,
+    columns: Vec<Vec<Value>>,
+    rows: usize,
+}
+
+impl Block {
+    /// Creates a block from one vector of values per schema field.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the column count does not match
+    /// the schema or the columns differ in length.
+    pub fn new(schema: Schema, columns: Vec<Vec<Value>>) -> Result<Self> {
+        if columns.len() != schema.len() {
+            return Err(Error::InvalidValue("column count does not match schema"));
+        }
+        let rows = columns.first().map_or(0, Vec::len);
+        if columns.iter().any(|column| column.len() != rows) {
+            return Err(Error::InvalidValue("column lengths differ"));
+        }
+        Ok(Self {
+            info: BlockInfo::default(),
+            schema,
+            columns,
+            rows,
+        })
+    }
+
+    /// Creates a block from rows in schema order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when a row length does not match the
+    /// schema.
+    pub fn from_rows<I: IntoIterator<Item = Row>>(schema: Schema, rows: I) -> Result<Self> {
+        let mut columns: Vec<Vec<Value>> = vec![Vec::new(); schema.len()];
+        for row in rows {
+            if row.len() != schema.len() {
+                return Err(Error::InvalidValue("row length does not match schema"));
+            }
+            for (column, value) in columns.iter_mut().zip(row) {
+                column.push(value);
+            }
+        }
+        Self::new(schema, columns)
+    }
+
+    /// Returns the block schema.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the columns, one per schema field.
+    #[must_use]
+    pub fn columns(&self) -> &[Vec<Value>] {
+        &self.columns
+    }
+
+    /// Returns the values of the column `name`.
+    #[must_use]
+    pub fn column(&self, name: &str) -> Option<&[Value]> {
+        self.schema
+            .index_of(name)
+            .map(|index| self.columns[index].as_slice())
+    }
+
+    /// Returns the number of rows.
+    #[must_use]
+    pub fn num_rows(&self) -> usize {
+        self.rows
+    }
+
+    /// Returns `true` when the block has no rows.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.rows == 0
+    }
+
+    /// Converts the block into rows in schema order.
+    #[must_use]
+    pub fn into_rows(self) -> Vec<Row> {
+        let mut columns: Vec<_> = self.columns.into_iter().map(Vec::into_iter).collect();
+        (0..self.rows)
+            .map(|_| columns.iter_mut().filter_map(Iterator::next).collect())
+            .collect()
+    }
+
+    /// Splits the block into its schema and columns.
+    #[must_use]
+    pub fn into_parts(self) -> (Schema, Vec<Vec<Value>>) {
+        (self.schema, self.columns)
+    }
+}
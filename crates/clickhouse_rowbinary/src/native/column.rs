@@ -0,0 +1,577 @@
+//! Column-wise encoding of `Native` block data.
+//!
+//! Each column starts with the state prefixes of all its streams (only
+//! `LowCardinality` and `Variant` have one), followed by the column data.
+//! Fixed-size and length-prefixed types store their values back to back,
+//! exactly as `RowBinary` encodes them one at a time, so those reuse the
+//! `RowBinary` value codec.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    io::{self, Read, Write},
+};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{WriteOptions, default_value, read_value_required, write_value},
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+/// `LowCardinality` prefix version with per-block dictionaries.
+const SHARED_DICTIONARIES_WITH_ADDITIONAL_KEYS: u64 = 1;
+/// Bits of the `LowCardinality` index flags holding the index width.
+const INDEX_TYPE_MASK: u64 = 0xff;
+/// `LowCardinality` index flag: keys refer to a global dictionary.
+const NEED_GLOBAL_DICTIONARY: u64 = 1 << 8;
+/// `LowCardinality` index flag: the block carries its own keys.
+const HAS_ADDITIONAL_KEYS: u64 = 1 << 9;
+/// `Variant` prefix mode with one discriminator per row.
+const VARIANT_BASIC_MODE: u64 = 0;
+/// `Variant` discriminator of a `NULL` row.
+const VARIANT_NULL: u8 = u8::MAX;
+
+/// Resolves types whose columns are encoded as another type.
+fn column_type(ty: &TypeDesc) -> Cow<'_, TypeDesc> {
+    match ty {
+        TypeDesc::SimpleAggregateFunction { ty, .. } => column_type(ty),
+        _ => Cow::Borrowed(ty.geo_storage().unwrap_or(ty)),
+    }
+}
+
+fn unsupported(ty: &TypeDesc) -> Error {
+    Error::UnsupportedCombination(format!(
+        "Native format does not support {} columns",
+        ty.type_name()
+    ))
+}
+
+fn mismatch(ty: &TypeDesc, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+fn eof(what: &str) -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        format!("unexpected EOF while reading {what}"),
+    ))
+}
+
+fn to_usize(value: u64, what: &'static str) -> Result<usize> {
+    usize::try_from(value).map_err(|_| Error::Overflow(what))
+}
+
+fn write_u64<W: Write + ?Sized>(value: u64, writer: &mut W) -> Result<()> {
+    writer.write_all(&value.to_le_bytes())?;
+    Ok(())
+}
+
+pub(super) fn read_u64<R: Read + ?Sized>(reader: &mut R) -> Result<u64> {
+    let mut buf = [0_u8; 8];
+    reader.read_exact(&mut buf)?;
+    Ok(u64::from_le_bytes(buf))
+}
+
+/// Reads exactly `len` bytes without trusting `len` for the allocation.
+fn read_byte_column<R: Read + ?Sized>(reader: &mut R, len: usize) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    reader.take(len as u64).read_to_end(&mut bytes)?;
+    if bytes.len() == len {
+        Ok(bytes)
+    } else {
+        Err(eof("column data"))
+    }
+}
+
+/// Writes the state prefixes of a column of type `ty`.
+pub(super) fn write_prefix<W: Write + ?Sized>(ty: &TypeDesc, writer: &mut W) -> Result<()> {
+    match &*column_type(ty) {
+        TypeDesc::LowCardinality(_) => {
+            write_u64(SHARED_DICTIONARIES_WITH_ADDITIONAL_KEYS, writer)?;
+        }
+        TypeDesc::Nullable(inner) | TypeDesc::Array(inner) => write_prefix(inner, writer)?,
+        TypeDesc::Map { key, value } => {
+            write_prefix(key, writer)?;
+            write_prefix(value, writer)?;
+        }
+        TypeDesc::Tuple(items) | TypeDesc::Nested(items) => {
+            for item in items {
+                write_prefix(&item.ty, writer)?;
+            }
+        }
+        TypeDesc::Variant(variants) => {
+            write_u64(VARIANT_BASIC_MODE, writer)?;
+            for variant in variants {
+                write_prefix(variant, writer)?;
+            }
+        }
+        ty @ (TypeDesc::Dynamic { .. } | TypeDesc::Json { .. }) => return Err(unsupported(ty)),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Reads and checks the state prefixes of a column of type `ty`.
+pub(super) fn read_prefix<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<()> {
+    match &*column_type(ty) {
+        TypeDesc::LowCardinality(_) => {
+            if read_u64(reader)? != SHARED_DICTIONARIES_WITH_ADDITIONAL_KEYS {
+                return Err(Error::UnsupportedCombination(
+                    "unsupported LowCardinality serialization version".into(),
+                ));
+            }
+        }
+        TypeDesc::Nullable(inner) | TypeDesc::Array(inner) => read_prefix(inner, reader)?,
+        TypeDesc::Map { key, value } => {
+            read_prefix(key, reader)?;
+            read_prefix(value, reader)?;
+        }
+        TypeDesc::Tuple(items) | TypeDesc::Nested(items) => {
+            for item in items {
+                read_prefix(&item.ty, reader)?;
+            }
+        }
+        TypeDesc::Variant(variants) => {
+            if read_u64(reader)? != VARIANT_BASIC_MODE {
+                return Err(Error::UnsupportedCombination(
+                    "only the basic Variant discriminators mode is supported".into(),
+                ));
+            }
+            for variant in variants {
+                read_prefix(variant, reader)?;
+            }
+        }
+        ty @ (TypeDesc::Dynamic { .. } | TypeDesc::Json { .. }) => return Err(unsupported(ty)),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Writes the data of a column of type `ty` holding `values`.
+pub(super) fn write_column<W: Write + ?Sized>(
+    ty: &TypeDesc,
+    values: &[&Value],
+    options: WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    let ty = column_type(ty);
+    match &*ty {
+        TypeDesc::Nothing => writer.write_all(&vec![b'0'; values.len()])?,
+        TypeDesc::Nullable(inner) => {
+            let default = default_value(inner)?;
+            let mut nulls = Vec::with_capacity(values.len());
+            let mut inner_values = Vec::with_capacity(values.len());
+            for value in values {
+                match value {
+                    Value::Nullable(Some(value)) => {
+                        nulls.push(0);
+                        inner_values.push(&**value);
+                    }
+                    Value::Nullable(None) => {
+                        nulls.push(1);
+                        inner_values.push(&default);
+                    }
+                    other => return Err(mismatch(&ty, other)),
+                }
+            }
+            writer.write_all(&nulls)?;
+            write_column(inner, &inner_values, options, writer)?;
+        }
+        TypeDesc::Array(inner) => {
+            let elements = write_offsets(&ty, values, writer)?;
+            write_column(inner, &elements, options, writer)?;
+        }
+        TypeDesc::Nested(items) => {
+            let elements = write_offsets(&ty, values, writer)?;
+            write_column(&TypeDesc::Tuple(items.clone()), &elements, options, writer)?;
+        }
+        TypeDesc::Map { key, value } => {
+            let mut offset = 0_u64;
+            let mut keys = Vec::new();
+            let mut entry_values = Vec::new();
+            for map in values {
+                let Value::Map(entries) = map else {
+                    return Err(mismatch(&ty, map));
+                };
+                offset += entries.len() as u64;
+                write_u64(offset, writer)?;
+                for (entry_key, entry_value) in entries {
+                    keys.push(entry_key);
+                    entry_values.push(entry_value);
+                }
+            }
+            write_column(key, &keys, options, writer)?;
+            write_column(value, &entry_values, options, writer)?;
+        }
+        TypeDesc::Tuple(items) => {
+            let mut elements: Vec<Vec<&Value>> =
+                vec![Vec::with_capacity(values.len()); items.len()];
+            for tuple in values {
+                let Value::Tuple(tuple_values) = tuple else {
+                    return Err(mismatch(&ty, tuple));
+                };
+                if tuple_values.len() != items.len() {
+                    return Err(Error::InvalidValue("tuple length mismatch"));
+                }
+                for (column, value) in elements.iter_mut().zip(tuple_values) {
+                    column.push(value);
+                }
+            }
+            for (item, column) in items.iter().zip(&elements) {
+                write_column(&item.ty, column, options, writer)?;
+            }
+        }
+        TypeDesc::LowCardinality(inner) => write_low_cardinality(inner, values, options, writer)?,
+        TypeDesc::Variant(variants) => {
+            let mut discriminators = Vec::with_capacity(values.len());
+            let mut columns: Vec<Vec<&Value>> = vec![Vec::new(); variants.len()];
+            for value in values {
+                match variant_of(variants, value, options)? {
+                    Some((index, value)) => {
+                        discriminators.push(index);
+                        columns[usize::from(index)].push(value);
+                    }
+                    None => discriminators.push(VARIANT_NULL),
+                }
+            }
+            writer.write_all(&discriminators)?;
+            for (variant, column) in variants.iter().zip(&columns) {
+                write_column(variant, column, options, writer)?;
+            }
+        }
+        ty @ (TypeDesc::Dynamic { .. } | TypeDesc::Json { .. }) => return Err(unsupported(ty)),
+        leaf => {
+            for value in values {
+                write_value(leaf, value, options, writer)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Writes the offsets of an array column and returns its elements.
+fn write_offsets<'a, W: Write + ?Sized>(
+    ty: &TypeDesc,
+    values: &[&'a Value],
+    writer: &mut W,
+) -> Result<Vec<&'a Value>> {
+    let mut offset = 0_u64;
+    let mut elements = Vec::new();
+    for value in values {
+        let Value::Array(items) = value else {
+            return Err(mismatch(ty, value));
+        };
+        offset += items.len() as u64;
+        write_u64(offset, writer)?;
+        elements.extend(items);
+    }
+    Ok(elements)
+}
+
+/// Picks the discriminator of a `Variant` value, or `None` for `NULL`.
+///
+/// Plain values take the first alternative that can encode them, as in
+/// `RowBinary`.
+fn variant_of<'a>(
+    variants: &[TypeDesc],
+    value: &'a Value,
+    options: WriteOptions,
+) -> Result<Option<(u8, &'a Value)>> {
+    match value {
+        Value::VariantNull | Value::Nullable(None) => Ok(None),
+        Value::Variant { index, value } => {
+            if usize::from(*index) >= variants.len() {
+                return Err(Error::InvalidValue("Variant discriminator out of range"));
+            }
+            Ok(Some((*index, value)))
+        }
+        value => {
+            let mut scratch = Vec::new();
+            for (index, variant) in variants.iter().enumerate() {
+                scratch.clear();
+                if write_value(variant, value, options, &mut scratch).is_ok() {
+                    let index = u8::try_from(index)
+                        .map_err(|_| Error::Overflow("Variant discriminator too large"))?;
+                    return Ok(Some((index, value)));
+                }
+            }
+            Err(mismatch(&TypeDesc::Variant(variants.to_vec()), value))
+        }
+    }
+}
+
+/// Writes a `LowCardinality(inner)` column with a dictionary of its own.
+///
+/// For `LowCardinality(Nullable(T))` the dictionary holds `T` keys and key
+/// `0` stands for `NULL`.
+fn write_low_cardinality<W: Write + ?Sized>(
+    inner: &TypeDesc,
+    values: &[&Value],
+    options: WriteOptions,
+    writer: &mut W,
+) -> Result<()> {
+    if values.is_empty() {
+        return Ok(());
+    }
+    let (key_type, nullable) = match inner {
+        TypeDesc::Nullable(key_type) => (&**key_type, true),
+        key_type => (key_type, false),
+    };
+    // Keys are leaf types, so their column data is just their encodings.
+    let mut dictionary = Vec::new();
+    let mut keys = 0_u64;
+    if nullable {
+        write_value(
+            key_type,
+            &default_value(key_type)?,
+            options,
+            &mut dictionary,
+        )?;
+        keys += 1;
+    }
+    let mut lookup: HashMap<Vec<u8>, u64> = HashMap::new();
+    let mut encoded = Vec::new();
+    let mut indexes = Vec::with_capacity(values.len());
+    for value in values {
+        let key = match (nullable, value) {
+            (true, Value::Nullable(None)) => {
+                indexes.push(0);
+                continue;
+            }
+            (true, Value::Nullable(Some(key))) => &**key,
+            (true, other) => return Err(mismatch(inner, other)),
+            (false, key) => *key,
+        };
+        encoded.clear();
+        write_value(key_type, key, options, &mut encoded)?;
+        let index = if let Some(index) = lookup.get(&encoded) {
+            *index
+        } else {
+            dictionary.extend_from_slice(&encoded);
+            lookup.insert(encoded.clone(), keys);
+            keys += 1;
+            keys - 1
+        };
+        indexes.push(index);
+    }
+
+    let index_type = match keys {
+        0..=0x100 => 0,
+        0x101..=0x1_0000 => 1,
+        0x1_0001..=0x1_0000_0000 => 2,
+        _ => 3,
+    };
+    write_u64(index_type | HAS_ADDITIONAL_KEYS, writer)?;
+    write_u64(keys, writer)?;
+    writer.write_all(&dictionary)?;
+    write_u64(indexes.len() as u64, writer)?;
+    for index in indexes {
+        #[allow(clippy::cast_possible_truncation)]
+        match index_type {
+            0 => writer.write_all(&[index as u8])?,
+            1 => writer.write_all(&(index as u16).to_le_bytes())?,
+            2 => writer.write_all(&(index as u32).to_le_bytes())?,
+            _ => writer.write_all(&index.to_le_bytes())?,
+        }
+    }
+    Ok(())
+}
+
+/// Reads `rows` values of a column of type `ty`.
+pub(super) fn read_column<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    rows: usize,
+    reader: &mut R,
+) -> Result<Vec<Value>> {
+    let ty = column_type(ty);
+    Ok(match &*ty {
+        TypeDesc::Nothing => {
+            read_byte_column(reader, rows)?;
+            vec![Value::Nothing; rows]
+        }
+        TypeDesc::Nullable(inner) => {
+            let nulls = read_byte_column(reader, rows)?;
+            let values = read_column(inner, rows, reader)?;
+            nulls
+                .into_iter()
+                .zip(values)
+                .map(|(null, value)| match null {
+                    0 => Ok(Value::Nullable(Some(Box::new(value)))),
+                    1 => Ok(Value::Nullable(None)),
+                    _ => Err(Error::InvalidValue("invalid nullable flag")),
+                })
+                .collect::<Result<_>>()?
+        }
+        TypeDesc::Array(inner) => {
+            let offsets = read_offsets(rows, reader)?;
+            let elements = read_column(inner, offsets.last().copied().unwrap_or(0), reader)?;
+            split_offsets(&offsets, elements)
+        }
+        TypeDesc::Nested(items) => {
+            let offsets = read_offsets(rows, reader)?;
+            let elements = read_column(
+                &TypeDesc::Tuple(items.clone()),
+                offsets.last().copied().unwrap_or(0),
+                reader,
+            )?;
+            split_offsets(&offsets, elements)
+        }
+        TypeDesc::Map { key, value } => {
+            let offsets = read_offsets(rows, reader)?;
+            let len = offsets.last().copied().unwrap_or(0);
+            let keys = read_column(key, len, reader)?;
+            let values = read_column(value, len, reader)?;
+            let mut entries = keys.into_iter().zip(values);
+            let mut start = 0;
+            offsets
+                .iter()
+                .map(|end| {
+                    let map = entries.by_ref().take(end - start).collect();
+                    start = *end;
+                    Value::Map(map)
+                })
+                .collect()
+        }
+        TypeDesc::Tuple(items) => read_tuples(items, rows, reader)?,
+        TypeDesc::LowCardinality(inner) => read_low_cardinality(inner, rows, reader)?,
+        TypeDesc::Variant(variants) => {
+            let discriminators = read_byte_column(reader, rows)?;
+            let mut counts = vec![0; variants.len()];
+            for discriminator in &discriminators {
+                if *discriminator != VARIANT_NULL {
+                    *counts
+                        .get_mut(usize::from(*discriminator))
+                        .ok_or(Error::InvalidValue("Variant discriminator out of range"))? += 1;
+                }
+            }
+            let mut columns = Vec::with_capacity(variants.len());
+            for (variant, count) in variants.iter().zip(counts) {
+                columns.push(read_column(variant, count, reader)?.into_iter());
+            }
+            discriminators
+                .into_iter()
+                .map(|index| {
+                    if index == VARIANT_NULL {
+                        return Ok(Value::VariantNull);
+                    }
+                    let value = columns[usize::from(index)]
+                        .next()
+                        .ok_or(Error::Internal("Variant column shorter than its count"))?;
+                    Ok(Value::Variant {
+                        index,
+                        value: Box::new(value),
+                    })
+                })
+                .collect::<Result<_>>()?
+        }
+        ty @ (TypeDesc::Dynamic { .. } | TypeDesc::Json { .. }) => return Err(unsupported(ty)),
+        leaf => {
+            let mut values = Vec::with_capacity(rows.min(1024));
+            for _ in 0..rows {
+                values.push(read_value_required(leaf, reader)?);
+            }
+            values
+        }
+    })
+}
+
+/// Reads the cumulative end offsets of `rows` arrays.
+fn read_offsets<R: Read + ?Sized>(rows: usize, reader: &mut R) -> Result<Vec<usize>> {
+    let mut offsets = Vec::with_capacity(rows.min(1024));
+    let mut previous = 0;
+    for _ in 0..rows {
+        let offset = to_usize(read_u64(reader)?, "array offset too large")?;
+        if offset < previous {
+            return Err(Error::InvalidValue("array offsets are not increasing"));
+        }
+        offsets.push(offset);
+        previous = offset;
+    }
+    Ok(offsets)
+}
+
+fn split_offsets(offsets: &[usize], elements: Vec<Value>) -> Vec<Value> {
+    let mut elements = elements.into_iter();
+    let mut start = 0;
+    offsets
+        .iter()
+        .map(|end| {
+            let array = elements.by_ref().take(end - start).collect();
+            start = *end;
+            Value::Array(array)
+        })
+        .collect()
+}
+
+fn read_tuples<R: Read + ?Sized>(
+    items: &[TupleItem],
+    rows: usize,
+    reader: &mut R,
+) -> Result<Vec<Value>> {
+    let mut columns = Vec::with_capacity(items.len());
+    for item in items {
+        columns.push(read_column(&item.ty, rows, reader)?.into_iter());
+    }
+    Ok((0..rows)
+        .map(|_| Value::Tuple(columns.iter_mut().filter_map(Iterator::next).collect()))
+        .collect())
+}
+
+/// Reads a `LowCardinality(inner)` column, which may be split into several
+/// dictionary-encoded chunks.
+fn read_low_cardinality<R: Read + ?Sized>(
+    inner: &TypeDesc,
+    rows: usize,
+    reader: &mut R,
+) -> Result<Vec<Value>> {
+    let (key_type, nullable) = match inner {
+        TypeDesc::Nullable(key_type) => (&**key_type, true),
+        key_type => (key_type, false),
+    };
+    let mut values = Vec::with_capacity(rows.min(1024));
+    while values.len() < rows {
+        let flags = read_u64(reader)?;
+        if flags & NEED_GLOBAL_DICTIONARY != 0 || flags & HAS_ADDITIONAL_KEYS == 0 {
+            return Err(Error::UnsupportedCombination(
+                "LowCardinality global dictionaries are not supported".into(),
+            ));
+        }
+        let key_count = to_usize(read_u64(reader)?, "dictionary too large")?;
+        let keys = read_column(key_type, key_count, reader)?;
+        let count = to_usize(read_u64(reader)?, "index count too large")?;
+        if count == 0 || count > rows - values.len() {
+            return Err(Error::InvalidValue(
+                "LowCardinality index count does not match rows",
+            ));
+        }
+        for _ in 0..count {
+            let index = match flags & INDEX_TYPE_MASK {
+                0 => u64::from(read_fixed::<_, 1>(reader)?[0]),
+                1 => u64::from(u16::from_le_bytes(read_fixed(reader)?)),
+                2 => u64::from(u32::from_le_bytes(read_fixed(reader)?)),
+                3 => read_u64(reader)?,
+                _ => return Err(Error::InvalidValue("unknown LowCardinality index type")),
+            };
+            let index = to_usize(index, "dictionary index too large")?;
+            let key = keys
+                .get(index)
+                .ok_or(Error::InvalidValue("dictionary index out of range"))?;
+            values.push(match (nullable, index) {
+                (true, 0) => Value::Nullable(None),
+                (true, _) => Value::Nullable(Some(Box::new(key.clone()))),
+                (false, _) => key.clone(),
+            });
+        }
+    }
+    Ok(values)
+}
+
+fn read_fixed<R: Read + ?Sized, const N: usize>(reader: &mut R) -> Result<[u8; N]> {
+    let mut buf = [0_u8; N];
+    reader.read_exact(&mut buf)?;
+    Ok(buf)
+}
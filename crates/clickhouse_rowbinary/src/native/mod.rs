@@ -0,0 +1,65 @@
+//! `Native` format support.
+//!
+//! `Native` sends data as blocks of rows stored column by column. Each
+//! block starts with its column and row counts, followed by each column's
+//! name, type name and data:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{Block, NativeReader, NativeWriter, Schema, Value};
+//! let schema =
+//!     Schema::from_type_strings(&[("id", "UInt32"), ("name", "LowCardinality(String)")])?;
+//! let block = Block::from_rows(
+//!     schema,
+//!     [
+//!         vec![Value::UInt32(1), Value::String(b"a".to_vec())],
+//!         vec![Value::UInt32(2), Value::String(b"a".to_vec())],
+//!     ],
+//! )?;
+//! let mut writer = NativeWriter::new(Vec::new());
+//! writer.write_block(&block)?;
+//! let payload = writer.into_inner();
+//!
+//! let mut reader = NativeReader::new(payload.as_slice());
+//! assert_eq!(reader.read_block()?, Some(block));
+//! assert_eq!(reader.read_block()?, None);
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! The default layout matches `FORMAT Native` over HTTP. The TCP protocol
+//! adds block metadata and a serialization flag per column, enabled through
+//! [`NativeOptions`]. `Dynamic` and `JSON` columns, sparse serialization
+//! and `LowCardinality` global dictionaries are not supported.
+
+mod block;
+mod column;
+mod reader;
+mod writer;
+
+pub use block::{Block, BlockInfo};
+pub use reader::NativeReader;
+pub use writer::NativeWriter;
+
+use crate::types::DEFAULT_MAX_TYPE_DEPTH;
+
+/// Options selecting the `Native` block layout.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NativeOptions {
+    /// Blocks start with their [`BlockInfo`], as in the TCP protocol.
+    pub block_info: bool,
+    /// Each column header ends with a custom serialization flag, as in the
+    /// TCP protocol since revision 54454. Only the default serialization is
+    /// supported.
+    pub custom_serialization: bool,
+    /// Maximum nesting depth accepted for column type names.
+    pub max_type_depth: usize,
+}
+
+impl Default for NativeOptions {
+    fn default() -> Self {
+        Self {
+            block_info: false,
+            custom_serialization: false,
+            max_type_depth: DEFAULT_MAX_TYPE_DEPTH,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+//! Streaming `Native` block reader.
+
+use std::io::{self, Read};
+
+use crate::{
+    error::{Error, Result},
+    io::{read_string, read_uvarint},
+    rowbinary::{Field, Schema},
+    types::parse_type_desc_with_max_depth,
+};
+
+use super::{
+    NativeOptions,
+    block::{Block, BlockInfo},
+    column::{read_column, read_prefix},
+};
+
+/// Reader that decodes `Native` blocks from the provided reader.
+///
+/// Each block carries its own column names and types, so no schema is
+/// needed up front. Wrap unbuffered sources in a [`std::io::BufReader`].
+pub struct NativeReader<R: Read> {
+    inner: R,
+    options: NativeOptions,
+}
+
+impl<R: Read> NativeReader<R> {
+    /// Creates a reader for the `Native` HTTP output format.
+    #[must_use]
+    pub fn new(inner: R) -> Self {
+        Self::with_options(inner, NativeOptions::default())
+    }
+
+    /// Creates a reader for the block layout described by `options`.
+    #[must_use]
+    pub fn with_options(inner: R, options: NativeOptions) -> Self {
+        Self { inner, options }
+    }
+
+    /// Reads the next block, or returns `Ok(None)` at a clean EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the block is malformed, uses an
+    /// unsupported column type or serialization, or the stream ends inside
+    /// it.
+    pub fn read_block(&mut self) -> Result<Option<Block>> {
+        let reader = &mut self.inner;
+        let mut info = BlockInfo::default();
+        let columns = if self.options.block_info {
+            let Some(field) = read_uvarint(reader)? else {
+                return Ok(None);
+            };
+            read_block_info(field, &mut info, reader)?;
+            required(read_uvarint(reader)?)?
+        } else {
+            let Some(columns) = read_uvarint(reader)? else {
+                return Ok(None);
+            };
+            columns
+        };
+        let rows = usize::try_from(required(read_uvarint(reader)?)?)
+            .map_err(|_| Error::Overflow("block row count too large"))?;
+
+        let mut fields = Vec::new();
+        let mut data = Vec::new();
+        for _ in 0..columns {
+            let name = required(read_string(reader)?)?;
+            let type_name = required(read_string(reader)?)?;
+            let ty = parse_type_desc_with_max_depth(&type_name, self.options.max_type_depth)?;
+            if self.options.custom_serialization {
+                let mut flag = [0_u8; 1];
+                reader.read_exact(&mut flag)?;
+                if flag[0] != 0 {
+                    return Err(Error::UnsupportedCombination(format!(
+                        "column `{name}` uses a custom serialization"
+                    )));
+                }
+            }
+            let values = if rows == 0 {
+                Vec::new()
+            } else {
+                read_prefix(&ty, reader)?;
+                read_column(&ty, rows, reader)?
+            };
+            fields.push(Field { name, ty });
+            data.push(values);
+        }
+        let mut block = Block::new(Schema::new(fields), data)?;
+        block.info = info;
+        Ok(Some(block))
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Returns the underlying reader, positioned after the last block read.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: Read> Iterator for NativeReader<R> {
+    type Item = Result<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_block().transpose()
+    }
+}
+
+/// Reads the numbered `BlockInfo` fields, starting with `field`.
+fn read_block_info<R: Read + ?Sized>(
+    mut field: u64,
+    info: &mut BlockInfo,
+    reader: &mut R,
+) -> Result<()> {
+    loop {
+        match field {
+            0 => return Ok(()),
+            1 => {
+                let mut flag = [0_u8; 1];
+                reader.read_exact(&mut flag)?;
+                info.is_overflows = flag[0] != 0;
+            }
+            2 => {
+                let mut bucket = [0_u8; 4];
+                reader.read_exact(&mut bucket)?;
+                info.bucket_num = i32::from_le_bytes(bucket);
+            }
+            _ => return Err(Error::InvalidValue("unknown BlockInfo field")),
+        }
+        field = required(read_uvarint(reader)?)?;
+    }
+}
+
+fn required<T>(value: Option<T>) -> Result<T> {
+    value.ok_or_else(|| {
+        Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected EOF while reading block",
+        ))
+    })
+}
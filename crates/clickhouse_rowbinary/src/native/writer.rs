@@ -0,0 +1,111 @@
+//! `Native` block writer.
+
+use std::io::Write;
+
+use crate::{
+    error::{Error, Result},
+    io::{write_string, write_uvarint},
+    rowbinary::WriteOptions,
+    value::Value,
+};
+
+use super::{
+    NativeOptions,
+    block::Block,
+    column::{write_column, write_prefix},
+};
+
+/// Writer that encodes `Native` blocks into the provided writer.
+///
+/// Each block is encoded in memory and written with a single `write_all`,
+/// so a block that fails to encode leaves no partial output behind.
+pub struct NativeWriter<W: Write> {
+    inner: W,
+    options: NativeOptions,
+    write_options: WriteOptions,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> NativeWriter<W> {
+    /// Creates a writer for the `Native` HTTP input format.
+    #[must_use]
+    pub fn new(inner: W) -> Self {
+        Self::with_options(inner, NativeOptions::default())
+    }
+
+    /// Creates a writer for the block layout described by `options`.
+    #[must_use]
+    pub fn with_options(inner: W, options: NativeOptions) -> Self {
+        Self {
+            inner,
+            options,
+            write_options: WriteOptions::default(),
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Controls how `FixedString` values shorter than the declared length
+    /// are written; see
+    /// [`crate::RowBinaryValueWriter::set_fixed_string_padding`].
+    pub fn set_fixed_string_padding(&mut self, enabled: bool) {
+        self.write_options.pad_fixed_strings = enabled;
+    }
+
+    /// Encodes and writes one block.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a value does not match its
+    /// column type, a column type is not supported, or IO fails.
+    pub fn write_block(&mut self, block: &Block) -> Result<()> {
+        self.buffer.clear();
+        let out = &mut self.buffer;
+        if self.options.block_info {
+            write_uvarint(1, out)?;
+            out.push(u8::from(block.info.is_overflows));
+            write_uvarint(2, out)?;
+            out.extend_from_slice(&block.info.bucket_num.to_le_bytes());
+            write_uvarint(0, out)?;
+        }
+        write_uvarint(block.schema().len() as u64, out)?;
+        write_uvarint(block.num_rows() as u64, out)?;
+        for (field, column) in block.schema().fields().iter().zip(block.columns()) {
+            write_string(&field.name, out)?;
+            write_string(&field.ty.type_name(), out)?;
+            if self.options.custom_serialization {
+                out.push(0);
+            }
+            if !column.is_empty() {
+                let values: Vec<&Value> = column.iter().collect();
+                write_prefix(&field.ty, out)?;
+                write_column(&field.ty, &values, self.write_options, out)?;
+            }
+        }
+        self.inner.write_all(&self.buffer).map_err(Error::Io)
+    }
+
+    /// Flushes the underlying writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the flush fails.
+    pub fn flush(&mut self) -> Result<()> {
+        self.inner.flush().map_err(Error::Io)
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
@@ -0,0 +1,446 @@
+//! Parquet export of decoded rows and import into `RowBinary` payloads.
+//!
+//! Both directions go through Arrow record batches, so columns map to
+//! Parquet as they map to Arrow in [`crate::arrow`]. [`ParquetWriter`]
+//! buffers rows as `RowBinary` and writes them as a record batch every
+//! `batch_rows` rows; [`ParquetReader`] reads the columns of a target
+//! [`Schema`] and encodes each row group as one insert payload.
+
+use std::{
+    collections::VecDeque,
+    io::{Read, Write},
+    sync::Arc,
+};
+
+use ::parquet::{
+    arrow::{
+        ArrowWriter, ProjectionMask,
+        arrow_reader::{ParquetRecordBatchReader, ParquetRecordBatchReaderBuilder},
+    },
+    basic::Compression,
+    errors::ParquetError,
+    file::{properties::WriterProperties, reader::ChunkReader},
+};
+use arrow_array::RecordBatch;
+
+use crate::{
+    arrow::{arrow_schema, read_record_batch, record_batch_to_rowbinary},
+    error::{Error, Result},
+    rowbinary::{RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+    value::Value,
+};
+
+/// Rows buffered by a [`ParquetWriter`] before they are written.
+pub const DEFAULT_BATCH_ROWS: usize = 8192;
+
+impl From<ParquetError> for Error {
+    fn from(err: ParquetError) -> Self {
+        Error::Parquet(err.to_string())
+    }
+}
+
+/// Writes decoded rows of a [`Schema`] into a Parquet file.
+///
+/// Call [`Self::finish`] to write the file footer; a writer dropped without
+/// it leaves an unreadable file.
+pub struct ParquetWriter<W: Write + Send> {
+    schema: Schema,
+    rows: RowBinaryValueWriter<Vec<u8>>,
+    pending: usize,
+    batch_rows: usize,
+    writer: ArrowWriter<W>,
+}
+
+impl<W: Write + Send> ParquetWriter<W> {
+    /// Creates a writer of Snappy-compressed row groups.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a column type has no Arrow
+    /// mapping or the file header cannot be written.
+    pub fn new(inner: W, schema: Schema) -> Result<Self> {
+        let properties = WriterProperties::builder()
+            .set_compression(Compression::SNAPPY)
+            .build();
+        Self::with_properties(inner, schema, properties)
+    }
+
+    /// Creates a writer with Parquet writer properties, such as the
+    /// compression and row group size.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a column type has no Arrow
+    /// mapping or the file header cannot be written.
+    pub fn with_properties(inner: W, schema: Schema, properties: WriterProperties) -> Result<Self> {
+        let arrow = Arc::new(arrow_schema(&schema)?);
+        let writer = ArrowWriter::try_new(inner, arrow, Some(properties))?;
+        Ok(Self {
+            rows: RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone()),
+            schema,
+            pending: 0,
+            batch_rows: DEFAULT_BATCH_ROWS,
+            writer,
+        })
+    }
+
+    /// Sets how many rows are buffered before they are written, at least
+    /// one; [`DEFAULT_BATCH_ROWS`] by default.
+    pub fn set_batch_rows(&mut self, rows: usize) {
+        self.batch_rows = rows.max(1);
+    }
+
+    /// Buffers a row, writing the buffered rows once there are
+    /// `batch_rows` of them.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row does not match the
+    /// schema or writing fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        self.rows.write_row(row)?;
+        self.pending += 1;
+        if self.pending >= self.batch_rows {
+            self.write_pending()?;
+        }
+        Ok(())
+    }
+
+    /// Buffers multiple rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a row does not match the schema
+    /// or writing fails; rows before it have already been buffered.
+    pub fn write_rows<I, R>(&mut self, rows: I) -> Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        for row in rows {
+            self.write_row(row.as_ref())?;
+        }
+        Ok(())
+    }
+
+    /// Writes every row decoded by `reader`, whose schema must be the
+    /// writer's, and returns the number of rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the schemas differ, and
+    /// [`crate::error::Error`] when decoding or writing fails.
+    pub fn write_reader<R: Read>(&mut self, reader: &mut RowBinaryValueReader<R>) -> Result<u64> {
+        if *reader.schema() != self.schema {
+            return Err(Error::InvalidValue(
+                "reader schema does not match the Parquet writer schema",
+            ));
+        }
+        self.write_pending()?;
+        let mut rows = 0;
+        while let Some(batch) = read_record_batch(reader, self.batch_rows)? {
+            self.writer.write(&batch)?;
+            rows += batch.num_rows() as u64;
+        }
+        Ok(rows)
+    }
+
+    /// Writes a record batch holding columns of the writer's schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the batch schema differs from
+    /// the file schema or writing fails.
+    pub fn write_record_batch(&mut self, batch: &RecordBatch) -> Result<()> {
+        self.write_pending()?;
+        self.writer.write(batch)?;
+        Ok(())
+    }
+
+    /// Writes the buffered rows and closes the current row group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when writing fails.
+    pub fn flush(&mut self) -> Result<()> {
+        self.write_pending()?;
+        self.writer.flush()?;
+        Ok(())
+    }
+
+    /// Writes the buffered rows and the file footer, and returns the inner
+    /// writer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when writing fails.
+    pub fn finish(mut self) -> Result<W> {
+        self.write_pending()?;
+        Ok(self.writer.into_inner()?)
+    }
+
+    fn write_pending(&mut self) -> Result<()> {
+        if self.pending == 0 {
+            return Ok(());
+        }
+        let payload = self.rows.take_inner();
+        let mut reader = RowBinaryValueReader::with_schema(
+            payload.as_slice(),
+            RowBinaryFormat::RowBinary,
+            self.schema.clone(),
+        )?;
+        if let Some(batch) = read_record_batch(&mut reader, self.pending)? {
+            self.writer.write(&batch)?;
+        }
+        self.pending = 0;
+        Ok(())
+    }
+}
+
+/// Reads the row groups of a Parquet file as `RowBinary` insert payloads.
+///
+/// Columns are picked from the file by the names of the target schema
+/// fields and encoded with its types; other file columns are not read.
+pub struct ParquetReader {
+    schema: Schema,
+    batches: ParquetRecordBatchReader,
+    row_groups: VecDeque<usize>,
+    pending: Option<RecordBatch>,
+}
+
+impl ParquetReader {
+    /// Opens a Parquet file, such as a [`std::fs::File`], for rows of
+    /// `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when the file has no column named
+    /// after a schema field, and [`crate::error::Error`] when the file
+    /// metadata cannot be read.
+    pub fn new<R: ChunkReader + 'static>(input: R, schema: Schema) -> Result<Self> {
+        let builder = ParquetRecordBatchReaderBuilder::try_new(input)?;
+        let roots = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                builder
+                    .schema()
+                    .index_of(&field.name)
+                    .map_err(|_| Error::UnknownColumn(field.name.clone()))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let row_groups = builder
+            .metadata()
+            .row_groups()
+            .iter()
+            .map(|group| usize::try_from(group.num_rows()))
+            .filter(|rows| *rows != Ok(0))
+            .collect::<Result<_, _>>()
+            .map_err(|_| Error::InvalidValue("negative Parquet row group size"))?;
+        let mask = ProjectionMask::roots(builder.parquet_schema(), roots);
+        Ok(Self {
+            schema,
+            batches: builder
+                .with_batch_size(DEFAULT_BATCH_ROWS)
+                .with_projection(mask)
+                .build()?,
+            row_groups,
+            pending: None,
+        })
+    }
+
+    /// Returns the target schema.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the rows of the next row group as a `RowBinary` payload, or
+    /// `None` after the last row group.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the file cannot be read or a
+    /// column does not hold the Arrow type of its schema field.
+    pub fn next_payload(&mut self) -> Result<Option<Vec<u8>>> {
+        let Some(mut remaining) = self.row_groups.pop_front() else {
+            return Ok(None);
+        };
+        let mut payload = Vec::new();
+        while remaining > 0 {
+            let batch = match self.pending.take() {
+                Some(batch) => batch,
+                None => self
+                    .batches
+                    .next()
+                    .ok_or(Error::InvalidValue("Parquet row group ended early"))??,
+            };
+            let rows = batch.num_rows().min(remaining);
+            if rows < batch.num_rows() {
+                self.pending = Some(batch.slice(rows, batch.num_rows() - rows));
+            }
+            payload.extend(record_batch_to_rowbinary(
+                &batch.slice(0, rows),
+                &self.schema,
+            )?);
+            remaining -= rows;
+        }
+        Ok(Some(payload))
+    }
+}
+
+impl Iterator for ParquetReader {
+    type Item = Result<Vec<u8>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_payload().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::parquet::file::properties::WriterProperties;
+    use bytes::Bytes;
+
+    use super::{ParquetReader, ParquetWriter};
+    use crate::{
+        error::Error,
+        rowbinary::{Row, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[
+            ("id", "UInt64"),
+            ("name", "Nullable(String)"),
+            ("at", "DateTime64(3, 'UTC')"),
+        ])
+        .unwrap()
+    }
+
+    fn rows(count: u64) -> Vec<Row> {
+        (0..count)
+            .map(|id| {
+                vec![
+                    Value::UInt64(id),
+                    Value::Nullable((id % 2 == 1).then(|| Box::new(Value::String(b"x".to_vec())))),
+                    Value::DateTime64(1_700_000_000_000 + i64::try_from(id).unwrap()),
+                ]
+            })
+            .collect()
+    }
+
+    fn decode(payload: &[u8], schema: Schema) -> Vec<Row> {
+        RowBinaryValueReader::with_schema(payload, RowBinaryFormat::RowBinary, schema)
+            .unwrap()
+            .rows()
+            .collect::<Result<_, _>>()
+            .unwrap()
+    }
+
+    #[test]
+    fn row_groups_become_insert_payloads() {
+        let properties = WriterProperties::builder()
+            .set_max_row_group_row_count(Some(3))
+            .build();
+        let mut writer = ParquetWriter::with_properties(Vec::new(), schema(), properties).unwrap();
+        writer.set_batch_rows(2);
+        writer.write_rows(rows(5)).unwrap();
+        let file = Bytes::from(writer.finish().unwrap());
+
+        let payloads = ParquetReader::new(file.clone(), schema())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(decode(&payloads[0], schema()), rows(3));
+        assert_eq!(decode(&payloads[1], schema()), rows(5)[3..]);
+
+        let target =
+            Schema::from_type_strings(&[("name", "Nullable(String)"), ("id", "UInt64")]).unwrap();
+        let mut reader = ParquetReader::new(file.clone(), target.clone()).unwrap();
+        let first = decode(&reader.next_payload().unwrap().unwrap(), target);
+        assert_eq!(first[1], [rows(2)[1][1].clone(), Value::UInt64(1)]);
+
+        let missing = Schema::from_type_strings(&[("score", "Float64")]).unwrap();
+        assert!(matches!(
+            ParquetReader::new(file, missing),
+            Err(Error::UnknownColumn(name)) if name == "score"
+        ));
+    }
+
+    #[test]
+    fn writes_rows_decoded_by_a_reader() {
+        let mut encoder =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+        encoder.write_rows(rows(4)).unwrap();
+        let payload = encoder.into_inner();
+        let mut reader = RowBinaryValueReader::with_schema(
+            payload.as_slice(),
+            RowBinaryFormat::RowBinary,
+            schema(),
+        )
+        .unwrap();
+
+        let mut writer = ParquetWriter::new(Vec::new(), schema()).unwrap();
+        writer.write_row(&rows(1)[0]).unwrap();
+        assert_eq!(writer.write_reader(&mut reader).unwrap(), 4);
+        let file = Bytes::from(writer.finish().unwrap());
+
+        let payloads = ParquetReader::new(file, schema())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        let mut expected = rows(1);
+        expected.extend(rows(4));
+        assert_eq!(decode(&payloads.concat(), schema()), expected);
+
+        let other = Schema::from_type_strings(&[("id", "UInt64")]).unwrap();
+        let mut reader =
+            RowBinaryValueReader::with_schema([].as_slice(), RowBinaryFormat::RowBinary, other)
+                .unwrap();
+        let mut writer = ParquetWriter::new(Vec::new(), schema()).unwrap();
+        assert!(matches!(
+            writer.write_reader(&mut reader),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+
+    #[test]
+    fn round_trips_nested_columns() {
+        let schema = Schema::from_type_strings(&[
+            ("tags", "Array(Nullable(String))"),
+            ("point", "Tuple(x Float64, y Float64)"),
+            ("counts", "Map(String, UInt32)"),
+        ])
+        .unwrap();
+        let rows = (0..3u32)
+            .map(|id| {
+                vec![
+                    Value::Array(
+                        (0..id)
+                            .map(|_| Value::Nullable(Some(Box::new(Value::String(b"t".to_vec())))))
+                            .chain([Value::Nullable(None)])
+                            .collect(),
+                    ),
+                    Value::Tuple(vec![Value::Float64(f64::from(id)), Value::Float64(0.5)]),
+                    Value::Map(vec![(Value::String(b"n".to_vec()), Value::UInt32(id))]),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let properties = WriterProperties::builder()
+            .set_max_row_group_row_count(Some(2))
+            .build();
+        let mut writer =
+            ParquetWriter::with_properties(Vec::new(), schema.clone(), properties).unwrap();
+        writer.write_rows(rows.clone()).unwrap();
+        let file = Bytes::from(writer.finish().unwrap());
+
+        let payloads = ParquetReader::new(file, schema.clone())
+            .unwrap()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(payloads.len(), 2);
+        assert_eq!(decode(&payloads.concat(), schema), rows);
+    }
+}
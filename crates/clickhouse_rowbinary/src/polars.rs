@@ -0,0 +1,684 @@
+//! Conversions between `RowBinary` data and Polars data frames.
+//!
+//! Frames are decoded with [`RowBinaryValueReader::read_columns`] and
+//! encoded with [`RowBinaryValueWriter::write_columns`], so columns move
+//! between Polars series and `RowBinary` without building
+//! [`Value`](crate::Value)s.
+//!
+//! | `ClickHouse` type | Polars type |
+//! | --- | --- |
+//! | `Bool` | `Boolean` |
+//! | `UInt8`..`UInt64`, `Int8`..`Int64` | `UInt8`..`UInt64`, `Int8`..`Int64` |
+//! | `Float32`, `Float64` | `Float32`, `Float64` |
+//! | `String` | `String` |
+//! | `FixedString(N)` | `Binary` |
+//! | `Date`, `Date32` | `Date` |
+//! | `DateTime` | `Datetime(Milliseconds)` |
+//! | `DateTime64(P)` | `Datetime` of the coarsest unit holding `P` digits |
+//! | `Decimal(P, S)` up to 128 bits | `Decimal(P, S)` |
+//! | `Enum8`, `Enum16` | `Int8`, `Int16` holding the enum values |
+//!
+//! Polars series are always nullable, `LowCardinality` columns are stored
+//! as their inner type, and datetimes keep the column timezone. Other types
+//! are rejected with [`Error::UnsupportedType`]. `String` values read into
+//! Polars must be valid UTF-8; `Binary` series can be written as `String`
+//! columns too, datetimes of any unit can be written as `DateTime` and
+//! `DateTime64` columns, and decimals of any precision can be written as
+//! decimal columns of the same scale.
+
+use std::io::{Read, Write};
+
+use ::polars::prelude::{
+    BinaryChunked, BooleanChunked, ChunkedArray, Column, DataFrame, DataType, Field as PolarsField,
+    Float32Type, Float64Type, Int8Type, Int16Type, Int32Type, Int64Type, Int128Type, IntoSeries,
+    PolarsError, PolarsNumericType, Schema as PolarsSchema, Series, StringChunked, TimeUnit,
+    TimeZone, UInt8Type, UInt16Type, UInt32Type, UInt64Type,
+};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{
+        Collect, ColumnValue, EncodeColumn, FromColumnValue, Row, RowBinaryFormat,
+        RowBinaryValueReader, RowBinaryValueWriter, Schema, Values, WriteStats, collected,
+        column_type, read_collected, storage_type,
+    },
+    types::{DATETIME64_MAX_PRECISION, DecimalSize, TypeDesc},
+};
+
+impl From<PolarsError> for Error {
+    fn from(err: PolarsError) -> Self {
+        Error::Polars(err.to_string())
+    }
+}
+
+/// Returns the Polars schema of data frames holding rows of `schema`.
+///
+/// # Errors
+///
+/// Returns [`Error::UnsupportedType`] when a column type has no Polars
+/// mapping.
+pub fn polars_schema(schema: &Schema) -> Result<PolarsSchema> {
+    schema
+        .fields()
+        .iter()
+        .map(|field| {
+            let (_, ty) = column_type(&field.ty);
+            Ok(PolarsField::new(
+                field.name.as_str().into(),
+                polars_type(ty)?,
+            ))
+        })
+        .collect()
+}
+
+/// Decodes up to `max_rows` rows from `reader` into a data frame.
+///
+/// Returns `None` once the stream has no more rows.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a column type has no Polars
+/// mapping, a `String` is not valid UTF-8, or
+/// [`RowBinaryValueReader::read_columns`] fails.
+pub fn read_dataframe<R: Read>(
+    reader: &mut RowBinaryValueReader<R>,
+    max_rows: usize,
+) -> Result<Option<DataFrame>> {
+    let columns = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| collector(&field.ty))
+        .collect::<Result<Vec<_>>>()?;
+    let names = reader
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| field.name.clone())
+        .collect::<Vec<_>>();
+    let Some(series) = read_collected(reader, columns, max_rows)? else {
+        return Ok(None);
+    };
+    let height = series.first().map_or(0, |series| series.len());
+    let columns = series
+        .into_iter()
+        .zip(names)
+        .map(|(series, name)| Column::from(series.with_name(name.into())))
+        .collect();
+    Ok(Some(DataFrame::new(height, columns)?))
+}
+
+/// Encodes the rows of `df` with `writer`.
+///
+/// Frame columns are matched to the writer's schema fields by name;
+/// columns the schema does not name are ignored. Call
+/// [`RowBinaryValueWriter::write_header`] before writing the first frame.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a schema field has no frame
+/// column, a column does not hold the Polars type of its field, a
+/// non-`Nullable` column holds nulls, a value does not fit its column, or
+/// [`RowBinaryValueWriter::write_columns`] fails.
+pub fn write_dataframe<W: Write>(
+    writer: &mut RowBinaryValueWriter<W>,
+    df: &DataFrame,
+) -> Result<WriteStats> {
+    let columns = writer
+        .schema()
+        .fields()
+        .iter()
+        .map(|field| {
+            df.column(&field.name)
+                .map(|column| SeriesColumn(column.as_materialized_series().rechunk()))
+                .map_err(|_| Error::UnknownColumn(field.name.clone()))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let columns = columns
+        .iter()
+        .map(|column| column as &dyn EncodeColumn)
+        .collect::<Vec<_>>();
+    writer.write_columns(&columns)
+}
+
+/// Converts decoded `rows` of `schema` into a data frame.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a column type has no Polars
+/// mapping or a row does not match `schema`.
+pub fn to_dataframe(rows: &[Row], schema: &Schema) -> Result<DataFrame> {
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_rows(rows)?;
+    let payload = writer.into_inner();
+    let mut reader = RowBinaryValueReader::with_schema(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinary,
+        schema.clone(),
+    )?;
+    match read_dataframe(&mut reader, rows.len())? {
+        Some(df) => Ok(df),
+        None => Ok(DataFrame::empty_with_schema(&polars_schema(schema)?)),
+    }
+}
+
+/// Converts the rows of `df` into decoded rows of `schema`.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] under the conditions of
+/// [`write_dataframe`].
+pub fn dataframe_to_rows(df: &DataFrame, schema: &Schema) -> Result<Vec<Row>> {
+    let payload = dataframe_to_rowbinary(df, schema)?;
+    RowBinaryValueReader::with_schema(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinary,
+        schema.clone(),
+    )?
+    .rows()
+    .collect()
+}
+
+/// Encodes the rows of `df` as a `RowBinary` insert payload for `schema`.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] under the conditions of
+/// [`write_dataframe`].
+pub fn dataframe_to_rowbinary(df: &DataFrame, schema: &Schema) -> Result<Vec<u8>> {
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    write_dataframe(&mut writer, df)?;
+    Ok(writer.into_inner())
+}
+
+/// Returns the Polars type of values of `ty`.
+fn polars_type(ty: &TypeDesc) -> Result<DataType> {
+    Ok(match ty {
+        TypeDesc::Bool => DataType::Boolean,
+        TypeDesc::UInt8 => DataType::UInt8,
+        TypeDesc::UInt16 => DataType::UInt16,
+        TypeDesc::UInt32 => DataType::UInt32,
+        TypeDesc::UInt64 => DataType::UInt64,
+        TypeDesc::Int8 | TypeDesc::Enum8(_) => DataType::Int8,
+        TypeDesc::Int16 | TypeDesc::Enum16(_) => DataType::Int16,
+        TypeDesc::Int32 => DataType::Int32,
+        TypeDesc::Int64 => DataType::Int64,
+        TypeDesc::Float32 => DataType::Float32,
+        TypeDesc::Float64 => DataType::Float64,
+        TypeDesc::String => DataType::String,
+        TypeDesc::FixedString { .. } => DataType::Binary,
+        TypeDesc::Date | TypeDesc::Date32 => DataType::Date,
+        TypeDesc::DateTime { timezone } => {
+            DataType::Datetime(TimeUnit::Milliseconds, time_zone(timezone.as_deref())?)
+        }
+        TypeDesc::DateTime64 {
+            precision,
+            timezone,
+        } => DataType::Datetime(
+            datetime_unit(*precision)?.0,
+            time_zone(timezone.as_deref())?,
+        ),
+        ty => match decimal_parts(ty) {
+            Some((precision, scale)) => DataType::Decimal(precision, scale),
+            None => return Err(Error::UnsupportedType(ty.type_name())),
+        },
+    })
+}
+
+/// Returns the Polars timezone of a column timezone.
+fn time_zone(timezone: Option<&str>) -> Result<Option<TimeZone>> {
+    Ok(TimeZone::opt_try_new(timezone)?)
+}
+
+/// Returns the precision and scale of decimals stored in at most 128 bits.
+fn decimal_parts(ty: &TypeDesc) -> Option<(usize, usize)> {
+    let (precision, scale) = match ty {
+        TypeDesc::Decimal32 { scale } => (9, *scale),
+        TypeDesc::Decimal64 { scale } => (18, *scale),
+        TypeDesc::Decimal128 { scale } => (38, *scale),
+        TypeDesc::Decimal {
+            precision,
+            scale,
+            size: DecimalSize::Bits32 | DecimalSize::Bits64 | DecimalSize::Bits128,
+        } => (*precision, *scale),
+        _ => return None,
+    };
+    Some((usize::from(precision), usize::from(scale)))
+}
+
+/// Returns the storage size of a decimal type with a Polars mapping.
+fn decimal_storage(ty: &TypeDesc) -> Option<DecimalSize> {
+    match ty {
+        TypeDesc::Decimal32 { .. } => Some(DecimalSize::Bits32),
+        TypeDesc::Decimal64 { .. } => Some(DecimalSize::Bits64),
+        TypeDesc::Decimal128 { .. } => Some(DecimalSize::Bits128),
+        TypeDesc::Decimal { size, .. } => Some(*size),
+        _ => None,
+    }
+}
+
+/// Returns the Polars unit of `DateTime64(precision)` values and the
+/// number of units per tick.
+fn datetime_unit(precision: u8) -> Result<(TimeUnit, i64)> {
+    if precision > DATETIME64_MAX_PRECISION {
+        return Err(Error::InvalidValue("DateTime64 precision must be <= 9"));
+    }
+    let (unit, digits) = match precision {
+        0..=3 => (TimeUnit::Milliseconds, 3),
+        4..=6 => (TimeUnit::Microseconds, 6),
+        _ => (TimeUnit::Nanoseconds, DATETIME64_MAX_PRECISION),
+    };
+    Ok((unit, 10_i64.pow(u32::from(digits - precision))))
+}
+
+/// Returns the number of `unit`s in a second.
+fn units_per_second(unit: TimeUnit) -> i64 {
+    match unit {
+        TimeUnit::Milliseconds => 1_000,
+        TimeUnit::Microseconds => 1_000_000,
+        TimeUnit::Nanoseconds => 1_000_000_000,
+    }
+}
+
+/// Collects values decoded as `T` into a series of `P`, converted with
+/// `logical`.
+fn primitive<P, T>(
+    nullable: bool,
+    convert: impl Fn(T) -> Result<P::Native> + 'static,
+    logical: impl FnOnce(Series) -> Result<Series> + 'static,
+) -> Box<dyn Collect<Series>>
+where
+    P: PolarsNumericType,
+    T: FromColumnValue + 'static,
+{
+    collected(nullable, move |values: Values<T>| {
+        let array = values
+            .into_options()
+            .map(|value| value.map(&convert).transpose())
+            .collect::<Result<ChunkedArray<P>>>()?;
+        logical(array.into_series())
+    })
+}
+
+/// Returns the column that values of `ty` are decoded into.
+fn collector(ty: &TypeDesc) -> Result<Box<dyn Collect<Series>>> {
+    let (nullable, inner) = column_type(ty);
+    Ok(match polars_type(inner)? {
+        DataType::Boolean => collected(nullable, |values: Values<bool>| {
+            Ok(values
+                .into_options()
+                .collect::<BooleanChunked>()
+                .into_series())
+        }),
+        DataType::UInt8 => primitive::<UInt8Type, u8>(nullable, Ok, Ok),
+        DataType::UInt16 => primitive::<UInt16Type, u16>(nullable, Ok, Ok),
+        DataType::UInt32 => primitive::<UInt32Type, u32>(nullable, Ok, Ok),
+        DataType::UInt64 => primitive::<UInt64Type, u64>(nullable, Ok, Ok),
+        DataType::Int8 => primitive::<Int8Type, i8>(nullable, Ok, Ok),
+        DataType::Int16 => primitive::<Int16Type, i16>(nullable, Ok, Ok),
+        DataType::Int32 => primitive::<Int32Type, i32>(nullable, Ok, Ok),
+        DataType::Int64 => primitive::<Int64Type, i64>(nullable, Ok, Ok),
+        DataType::Float32 => primitive::<Float32Type, f32>(nullable, Ok, Ok),
+        DataType::Float64 => primitive::<Float64Type, f64>(nullable, Ok, Ok),
+        DataType::String => collected(nullable, |values: Values<String>| {
+            Ok(values
+                .into_options()
+                .collect::<StringChunked>()
+                .into_series())
+        }),
+        DataType::Binary => collected(nullable, |values: Values<Vec<u8>>| {
+            Ok(values
+                .into_options()
+                .collect::<BinaryChunked>()
+                .into_series())
+        }),
+        DataType::Date => {
+            let logical = |series: Series| Ok(series.into_date());
+            match inner {
+                TypeDesc::Date => {
+                    primitive::<Int32Type, u16>(nullable, |days| Ok(i32::from(days)), logical)
+                }
+                _ => primitive::<Int32Type, i32>(nullable, Ok, logical),
+            }
+        }
+        DataType::Datetime(unit, tz) => {
+            let logical = move |series: Series| Ok(series.into_datetime(unit, tz));
+            match inner {
+                TypeDesc::DateTime64 { precision, .. } => {
+                    let (_, factor) = datetime_unit(*precision)?;
+                    primitive::<Int64Type, i64>(
+                        nullable,
+                        move |ticks| {
+                            ticks
+                                .checked_mul(factor)
+                                .ok_or(Error::Overflow("DateTime64 outside Polars datetime range"))
+                        },
+                        logical,
+                    )
+                }
+                _ => primitive::<Int64Type, u32>(
+                    nullable,
+                    |seconds| Ok(i64::from(seconds) * 1_000),
+                    logical,
+                ),
+            }
+        }
+        DataType::Decimal(precision, scale) => {
+            let logical = move |series: Series| Ok(series.into_decimal(precision, scale)?);
+            match decimal_storage(inner) {
+                Some(DecimalSize::Bits32) => {
+                    primitive::<Int128Type, i32>(nullable, |value| Ok(i128::from(value)), logical)
+                }
+                Some(DecimalSize::Bits64) => {
+                    primitive::<Int128Type, i64>(nullable, |value| Ok(i128::from(value)), logical)
+                }
+                _ => primitive::<Int128Type, i128>(nullable, Ok, logical),
+            }
+        }
+        _ => return Err(Error::UnsupportedType(inner.type_name())),
+    })
+}
+
+/// Polars series encoded as a `RowBinary` column.
+struct SeriesColumn(Series);
+
+impl EncodeColumn for SeriesColumn {
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        let (nullable, inner) = column_type(ty);
+        let expected = polars_type(inner)?;
+        let actual = self.0.dtype();
+        let matches = match (&expected, actual) {
+            (DataType::String, actual) => matches!(actual, DataType::String | DataType::Binary),
+            (DataType::Datetime(..), actual) => matches!(actual, DataType::Datetime(..)),
+            (DataType::Decimal(_, scale), DataType::Decimal(_, actual)) => scale == actual,
+            (expected, actual) => expected == actual,
+        };
+        if !matches {
+            return Err(Error::TypeMismatch {
+                expected: ty.type_name(),
+                actual: actual.to_string(),
+            });
+        }
+        if !nullable && self.0.null_count() > 0 {
+            return Err(Error::InvalidValue("null value in a non-Nullable column"));
+        }
+        Ok(())
+    }
+
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()> {
+        match storage_type(ty) {
+            TypeDesc::Nullable(inner) => {
+                if self.0.get(index)?.is_null() {
+                    writer.write_all(&[1])?;
+                    return Ok(());
+                }
+                writer.write_all(&[0])?;
+                write_scalar(&self.0, index, storage_type(inner), writer)
+            }
+            ty => write_scalar(&self.0, index, ty, writer),
+        }
+    }
+}
+
+/// Returns a value that [`SeriesColumn::check_type`] found non-null.
+fn non_null<T>(value: Option<T>) -> Result<T> {
+    value.ok_or(Error::InvalidValue("null value in a non-Nullable column"))
+}
+
+/// Encodes the value at `index` of a series that
+/// [`SeriesColumn::check_type`] accepted for `ty`.
+fn write_scalar(
+    series: &Series,
+    index: usize,
+    ty: &TypeDesc,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    match ty {
+        TypeDesc::Bool => non_null(series.bool()?.get(index))?.write_to(ty, writer),
+        TypeDesc::UInt8 => non_null(series.u8()?.get(index))?.write_to(ty, writer),
+        TypeDesc::UInt16 => non_null(series.u16()?.get(index))?.write_to(ty, writer),
+        TypeDesc::UInt32 => non_null(series.u32()?.get(index))?.write_to(ty, writer),
+        TypeDesc::UInt64 => non_null(series.u64()?.get(index))?.write_to(ty, writer),
+        TypeDesc::Int8 | TypeDesc::Enum8(_) => {
+            non_null(series.i8()?.get(index))?.write_to(ty, writer)
+        }
+        TypeDesc::Int16 | TypeDesc::Enum16(_) => {
+            non_null(series.i16()?.get(index))?.write_to(ty, writer)
+        }
+        TypeDesc::Int32 => non_null(series.i32()?.get(index))?.write_to(ty, writer),
+        TypeDesc::Int64 => non_null(series.i64()?.get(index))?.write_to(ty, writer),
+        TypeDesc::Float32 => non_null(series.f32()?.get(index))?.write_to(ty, writer),
+        TypeDesc::Float64 => non_null(series.f64()?.get(index))?.write_to(ty, writer),
+        TypeDesc::String => {
+            let bytes = match series.dtype() {
+                DataType::Binary => non_null(series.binary()?.get(index))?,
+                _ => non_null(series.str()?.get(index))?.as_bytes(),
+            };
+            bytes.write_to(ty, writer)
+        }
+        TypeDesc::FixedString { .. } => non_null(series.binary()?.get(index))?.write_to(ty, writer),
+        TypeDesc::Date => u16::try_from(physical_i32(series, index)?)
+            .map_err(|_| Error::Overflow("date outside Date range"))?
+            .write_to(ty, writer),
+        TypeDesc::Date32 => physical_i32(series, index)?.write_to(ty, writer),
+        TypeDesc::DateTime { .. } => u32::try_from(datetime_ticks(series, index, 0)?)
+            .map_err(|_| Error::Overflow("datetime outside DateTime range"))?
+            .write_to(ty, writer),
+        TypeDesc::DateTime64 { precision, .. } => {
+            datetime_ticks(series, index, *precision)?.write_to(ty, writer)
+        }
+        ty => {
+            let physical = series.to_physical_repr();
+            let value = non_null(physical.i128()?.get(index))?;
+            match decimal_storage(ty) {
+                Some(DecimalSize::Bits32) => i32::try_from(value)
+                    .map_err(|_| Error::Overflow("decimal outside Decimal32 range"))?
+                    .write_to(ty, writer),
+                Some(DecimalSize::Bits64) => i64::try_from(value)
+                    .map_err(|_| Error::Overflow("decimal outside Decimal64 range"))?
+                    .write_to(ty, writer),
+                _ => value.write_to(ty, writer),
+            }
+        }
+    }
+}
+
+/// Returns the days since the epoch at `index` of a `Date` series.
+fn physical_i32(series: &Series, index: usize) -> Result<i32> {
+    non_null(series.to_physical_repr().i32()?.get(index))
+}
+
+/// Returns the value at `index` of a `Datetime` series in ticks of
+/// `10^-precision` seconds.
+fn datetime_ticks(series: &Series, index: usize, precision: u8) -> Result<i64> {
+    let DataType::Datetime(unit, _) = series.dtype() else {
+        return Err(Error::TypeMismatch {
+            expected: "Datetime".to_owned(),
+            actual: series.dtype().to_string(),
+        });
+    };
+    let value = non_null(series.to_physical_repr().i64()?.get(index))?;
+    let units = units_per_second(*unit);
+    let ticks = 10_i64.pow(u32::from(precision));
+    if units >= ticks {
+        Ok(value.div_euclid(units / ticks))
+    } else {
+        value
+            .checked_mul(ticks / units)
+            .ok_or(Error::Overflow("datetime outside DateTime64 range"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ::polars::prelude::{Column, DataFrame, DataType, NamedFrom, Series, TimeUnit};
+
+    use super::{
+        dataframe_to_rowbinary, dataframe_to_rows, polars_schema, read_dataframe, to_dataframe,
+        write_dataframe,
+    };
+    use crate::{
+        error::Error,
+        rowbinary::{Row, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[
+            ("id", "UInt32"),
+            ("name", "Nullable(String)"),
+            ("tag", "LowCardinality(String)"),
+            ("code", "FixedString(2)"),
+            ("day", "Date"),
+            ("at", "DateTime('UTC')"),
+            ("tick", "DateTime64(2)"),
+            ("price", "Decimal(10, 2)"),
+            ("ok", "Bool"),
+            ("kind", "Enum8('a' = 1, 'b' = 2)"),
+        ])
+        .unwrap()
+    }
+
+    fn row(id: u32, name: Option<&str>) -> Row {
+        vec![
+            Value::UInt32(id),
+            Value::Nullable(name.map(|name| Box::new(Value::String(name.into())))),
+            Value::String(b"t".to_vec()),
+            Value::FixedString(b"ab".to_vec()),
+            Value::Date(19_000),
+            Value::DateTime(1_700_000_000),
+            Value::DateTime64(123),
+            Value::Decimal64(1_050),
+            Value::Bool(id.is_multiple_of(2)),
+            Value::Enum8(2),
+        ]
+    }
+
+    fn frame(columns: Vec<Series>) -> DataFrame {
+        DataFrame::new_infer_height(columns.into_iter().map(Column::from).collect()).unwrap()
+    }
+
+    #[test]
+    fn maps_column_types() {
+        let polars = polars_schema(&schema()).unwrap();
+        let types = polars.iter_values().cloned().collect::<Vec<_>>();
+        assert_eq!(types[0], DataType::UInt32);
+        assert_eq!(types[1], DataType::String);
+        assert_eq!(types[3], DataType::Binary);
+        assert_eq!(types[4], DataType::Date);
+        assert!(matches!(
+            &types[5],
+            DataType::Datetime(TimeUnit::Milliseconds, Some(tz)) if tz.as_str() == "UTC"
+        ));
+        assert_eq!(types[6], DataType::Datetime(TimeUnit::Milliseconds, None));
+        assert_eq!(types[7], DataType::Decimal(10, 2));
+        assert_eq!(types[9], DataType::Int8);
+        let nested = Schema::from_type_strings(&[("ids", "Array(UInt8)")]).unwrap();
+        assert!(matches!(
+            polars_schema(&nested),
+            Err(Error::UnsupportedType(name)) if name == "Array(UInt8)"
+        ));
+    }
+
+    #[test]
+    fn converts_rows_to_dataframes_and_back() {
+        let rows = vec![row(1, Some("a")), row(2, None)];
+        let df = to_dataframe(&rows, &schema()).unwrap();
+        assert_eq!(df.height(), 2);
+        let names = df.column("name").unwrap().str().unwrap();
+        assert_eq!(names.get(0), Some("a"));
+        assert_eq!(names.get(1), None);
+        let physical = |name: &str| {
+            df.column(name)
+                .unwrap()
+                .as_materialized_series()
+                .to_physical_repr()
+                .into_owned()
+        };
+        assert_eq!(physical("day").i32().unwrap().get(0), Some(19_000));
+        assert_eq!(
+            physical("at").i64().unwrap().get(0),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(physical("tick").i64().unwrap().get(0), Some(1_230));
+        assert_eq!(physical("price").i128().unwrap().get(0), Some(1_050));
+        assert_eq!(dataframe_to_rows(&df, &schema()).unwrap(), rows);
+
+        let empty = to_dataframe(&[], &schema()).unwrap();
+        assert_eq!(empty.height(), 0);
+        assert_eq!(empty.width(), 10);
+    }
+
+    #[test]
+    fn reads_dataframes_of_at_most_max_rows() {
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+        writer
+            .write_rows([row(1, None), row(2, None), row(3, Some("c"))])
+            .unwrap();
+        let payload = writer.into_inner();
+        let mut reader = RowBinaryValueReader::with_schema(
+            payload.as_slice(),
+            RowBinaryFormat::RowBinary,
+            schema(),
+        )
+        .unwrap();
+        let sizes = std::iter::from_fn(|| read_dataframe(&mut reader, 2).unwrap())
+            .map(|df| df.height())
+            .collect::<Vec<_>>();
+        assert_eq!(sizes, [2, 1]);
+    }
+
+    #[test]
+    fn writes_dataframe_columns_by_name() {
+        let schema = Schema::from_type_strings(&[
+            ("id", "UInt32"),
+            ("name", "String"),
+            ("at", "DateTime64(3)"),
+        ])
+        .unwrap();
+        let at = Series::new("at".into(), [1_500_i64, 2_000_999])
+            .into_datetime(TimeUnit::Microseconds, None);
+        let df = frame(vec![
+            Series::new("name".into(), ["a", "b"]),
+            Series::new("extra".into(), [0_i32, 0]),
+            at,
+            Series::new("id".into(), [1_u32, 2]),
+        ]);
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+        let stats = write_dataframe(&mut writer, &df).unwrap();
+        assert_eq!(stats.rows, 2);
+        let mut expected = vec![1, 0, 0, 0, 1, b'a'];
+        expected.extend(1_i64.to_le_bytes());
+        expected.extend([2, 0, 0, 0, 1, b'b']);
+        expected.extend(2_000_i64.to_le_bytes());
+        assert_eq!(writer.into_inner(), expected);
+
+        let missing = frame(vec![Series::new("id".into(), [1_u32])]);
+        assert!(matches!(
+            dataframe_to_rowbinary(&missing, &schema),
+            Err(Error::UnknownColumn(name)) if name == "name"
+        ));
+        let columns = |id: Series| {
+            let at = Series::new("at".into(), [0_i64]).into_datetime(TimeUnit::Milliseconds, None);
+            frame(vec![id, Series::new("name".into(), ["a"]), at])
+        };
+        let wrong_type = columns(Series::new("id".into(), [1_i32]));
+        assert!(matches!(
+            dataframe_to_rowbinary(&wrong_type, &schema),
+            Err(Error::TypeMismatch { .. })
+        ));
+        let null = columns(Series::new("id".into(), [None::<u32>]));
+        assert!(matches!(
+            dataframe_to_rowbinary(&null, &schema),
+            Err(Error::InvalidValue(_))
+        ));
+    }
+}
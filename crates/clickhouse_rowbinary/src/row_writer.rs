@@ -0,0 +1,110 @@
+//! Buffered row output shared by the writers of row-oriented formats.
+//!
+//! A [`RowOutput`] encodes each row into a buffer in memory and hands it to
+//! the inner writer with a single `write_all`, so a row that fails to
+//! encode leaves no partial output behind and the output always ends with
+//! a complete row. [`impl_row_writer!`] gives a writer that holds its
+//! output in an `output` field the `write_rows`, `flush` and inner writer
+//! accessors every writer offers.
+
+use std::io::Write;
+
+use crate::error::Result;
+
+/// Inner writer of a row writer, with the buffer rows are encoded into.
+pub(crate) struct RowOutput<W> {
+    inner: W,
+    buffer: Vec<u8>,
+}
+
+impl<W: Write> RowOutput<W> {
+    pub(crate) fn new(inner: W) -> Self {
+        Self {
+            inner,
+            buffer: Vec::new(),
+        }
+    }
+
+    /// Encodes a row, or a header, with `encode` and writes it once it is
+    /// complete.
+    pub(crate) fn write(&mut self, encode: impl FnOnce(&mut Vec<u8>) -> Result<()>) -> Result<()> {
+        self.buffer.clear();
+        encode(&mut self.buffer)?;
+        self.inner.write_all(&self.buffer)?;
+        Ok(())
+    }
+
+    pub(crate) fn flush(&mut self) -> Result<()> {
+        self.inner.flush()?;
+        Ok(())
+    }
+
+    pub(crate) fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub(crate) fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    pub(crate) fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+/// Implements `write_rows`, `flush`, `get_ref`, `get_mut` and `into_inner`
+/// for a writer with a `write_row` method and an `output` field.
+macro_rules! impl_row_writer {
+    ($writer:ident) => {
+        impl<W: std::io::Write> $writer<W> {
+            /// Encodes and writes multiple rows.
+            ///
+            /// Each row is encoded in memory before any of it is written, so
+            /// the output always ends with a complete row.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`crate::error::Error`] when any row fails to encode;
+            /// rows before it have already been written.
+            pub fn write_rows<I, R>(&mut self, rows: I) -> $crate::error::Result<()>
+            where
+                I: IntoIterator<Item = R>,
+                R: AsRef<[$crate::value::Value]>,
+            {
+                for row in rows {
+                    self.write_row(row.as_ref())?;
+                }
+                Ok(())
+            }
+
+            /// Flushes the inner writer.
+            ///
+            /// # Errors
+            ///
+            /// Returns [`crate::error::Error`] if the inner writer fails to
+            /// flush.
+            pub fn flush(&mut self) -> $crate::error::Result<()> {
+                self.output.flush()
+            }
+
+            /// Returns a reference to the inner writer.
+            #[must_use]
+            pub fn get_ref(&self) -> &W {
+                self.output.get_ref()
+            }
+
+            /// Returns a mutable reference to the inner writer.
+            pub fn get_mut(&mut self) -> &mut W {
+                self.output.get_mut()
+            }
+
+            /// Consumes the writer and returns the inner writer.
+            #[must_use]
+            pub fn into_inner(self) -> W {
+                self.output.into_inner()
+            }
+        }
+    };
+}
+
+pub(crate) use impl_row_writer;
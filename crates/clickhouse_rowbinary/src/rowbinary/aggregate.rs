@@ -0,0 +1,235 @@
+//! Aggregate function state layouts.
+//!
+//! `RowBinary` writes aggregate states without a length prefix, so the
+//! state boundaries can only be found for functions whose serialization
+//! layout is known. States are otherwise treated as opaque bytes.
+
+use std::io::Read;
+
+use crate::{
+    error::{Error, Result},
+    io::read_uvarint,
+    types::TypeDesc,
+};
+
+use super::scan::{discard_exact, fixed_len_for_type, read_fixed};
+
+/// Serialized state layout of a supported aggregate function.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum StateLayout {
+    /// A single `VarUInt` (`count`).
+    VarUInt,
+    /// A fixed-width little-endian number (`sum`).
+    Fixed(usize),
+    /// A presence flag followed by a fixed-width value (`min`, `max`, `any`).
+    OptionalFixed(usize),
+    /// An `Int32` size (`-1` when empty) followed by the bytes (`min`, `max`,
+    /// `any` over strings).
+    OptionalString,
+    /// A fixed-width numerator followed by a `VarUInt` denominator (`avg`).
+    Average(usize),
+    /// A skip degree byte, a `VarUInt` element count and `UInt32` hashes
+    /// (`uniq`).
+    UniquesHashSet,
+}
+
+fn state_layout(function: &str, arguments: &[TypeDesc]) -> Result<StateLayout> {
+    let unsupported = || {
+        Error::UnsupportedType(format!(
+            "state layout of aggregate function {function} is unknown"
+        ))
+    };
+    // The -If combinator adds a trailing UInt8 condition but keeps the state.
+    let (name, arguments) = match function.strip_suffix("If") {
+        Some(base) if !base.is_empty() && !arguments.is_empty() => {
+            (base, &arguments[..arguments.len() - 1])
+        }
+        _ => (function, arguments),
+    };
+    let single_fixed = || match arguments {
+        [argument] => fixed_len_for_type(argument).ok_or_else(unsupported),
+        _ => Err(unsupported()),
+    };
+    let layout = match name {
+        "count" => StateLayout::VarUInt,
+        "sum" => StateLayout::Fixed(single_fixed()?.max(8)),
+        "sumWithOverflow" => StateLayout::Fixed(single_fixed()?),
+        "min" | "max" | "any" | "anyLast" => match arguments {
+            [TypeDesc::String] => StateLayout::OptionalString,
+            _ => StateLayout::OptionalFixed(single_fixed()?),
+        },
+        "avg" => match single_fixed()? {
+            len if len <= 8 => StateLayout::Average(8),
+            _ => return Err(unsupported()),
+        },
+        "uniq" => StateLayout::UniquesHashSet,
+        _ => return Err(unsupported()),
+    };
+    if matches!(
+        layout,
+        StateLayout::Fixed(_) | StateLayout::OptionalFixed(_) | StateLayout::Average(_)
+    ) && !arguments.iter().all(is_numeric_or_temporal)
+    {
+        return Err(unsupported());
+    }
+    Ok(layout)
+}
+
+fn is_numeric_or_temporal(ty: &TypeDesc) -> bool {
+    matches!(
+        ty,
+        TypeDesc::UInt8
+            | TypeDesc::UInt16
+            | TypeDesc::UInt32
+            | TypeDesc::UInt64
+            | TypeDesc::UInt128
+            | TypeDesc::UInt256
+            | TypeDesc::Int8
+            | TypeDesc::Int16
+            | TypeDesc::Int32
+            | TypeDesc::Int64
+            | TypeDesc::Int128
+            | TypeDesc::Int256
+            | TypeDesc::Float32
+            | TypeDesc::Float64
+            | TypeDesc::Date
+            | TypeDesc::Date32
+            | TypeDesc::DateTime { .. }
+            | TypeDesc::DateTime64 { .. }
+    )
+}
+
+/// Skips one aggregate state, returning `None` on EOF before the state.
+pub(crate) fn skip_aggregate_state<R: Read + ?Sized>(
+    function: &str,
+    arguments: &[TypeDesc],
+    reader: &mut R,
+) -> Result<Option<()>> {
+    match state_layout(function, arguments)? {
+        StateLayout::VarUInt => Ok(read_uvarint(reader)?.map(|_| ())),
+        StateLayout::Fixed(len) => read_fixed(reader, len),
+        StateLayout::OptionalFixed(len) => {
+            let Some(flag) = read_flag(reader)? else {
+                return Ok(None);
+            };
+            if flag {
+                discard_exact(reader, len)?;
+            }
+            Ok(Some(()))
+        }
+        StateLayout::OptionalString => {
+            let mut size = [0_u8; 4];
+            if read_fixed_into(reader, &mut size)? {
+                return Ok(None);
+            }
+            let size = i32::from_le_bytes(size);
+            if size > 0 {
+                let len = usize::try_from(size)
+                    .map_err(|_| Error::Overflow("aggregate state size too large"))?;
+                discard_exact(reader, len)?;
+            } else if size < -1 {
+                return Err(Error::InvalidValue("invalid aggregate state string size"));
+            }
+            Ok(Some(()))
+        }
+        StateLayout::Average(len) => {
+            if read_fixed(reader, len)?.is_none() {
+                return Ok(None);
+            }
+            required_uvarint(reader)?;
+            Ok(Some(()))
+        }
+        StateLayout::UniquesHashSet => {
+            if read_fixed(reader, 1)?.is_none() {
+                return Ok(None);
+            }
+            let count = required_uvarint(reader)?;
+            let len = usize::try_from(count)
+                .ok()
+                .and_then(|count| count.checked_mul(4))
+                .ok_or(Error::Overflow("uniq state too large"))?;
+            discard_exact(reader, len)?;
+            Ok(Some(()))
+        }
+    }
+}
+
+fn read_flag<R: Read + ?Sized>(reader: &mut R) -> Result<Option<bool>> {
+    let mut flag = [0_u8; 1];
+    if read_fixed_into(reader, &mut flag)? {
+        return Ok(None);
+    }
+    match flag[0] {
+        0 => Ok(Some(false)),
+        1 => Ok(Some(true)),
+        _ => Err(Error::InvalidValue("invalid aggregate state flag")),
+    }
+}
+
+fn read_fixed_into<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> Result<bool> {
+    let read = reader.read(&mut buf[..1])?;
+    if read == 0 {
+        return Ok(true);
+    }
+    reader.read_exact(&mut buf[1..])?;
+    Ok(false)
+}
+
+fn required_uvarint<R: Read + ?Sized>(reader: &mut R) -> Result<u64> {
+    read_uvarint(reader)?.ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "unexpected EOF in aggregate state",
+        ))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn skip(ty: &str, payload: &[u8]) -> Result<Option<()>> {
+        let ty = crate::types::parse_type_desc(ty).unwrap();
+        let TypeDesc::AggregateFunction {
+            function,
+            arguments,
+        } = ty
+        else {
+            panic!("expected AggregateFunction");
+        };
+        let mut cursor = payload;
+        let result = skip_aggregate_state(&function, &arguments, &mut cursor)?;
+        assert!(cursor.is_empty(), "state not fully consumed");
+        Ok(result)
+    }
+
+    #[test]
+    fn skips_known_state_layouts() {
+        skip("AggregateFunction(count)", &[0x96, 0x01]).unwrap();
+        skip("AggregateFunction(sum, UInt32)", &[7, 0, 0, 0, 0, 0, 0, 0]).unwrap();
+        skip("AggregateFunction(sumIf, Int64, UInt8)", &[1; 8]).unwrap();
+        skip("AggregateFunction(max, Int32)", &[1, 5, 0, 0, 0]).unwrap();
+        skip("AggregateFunction(min, Int32)", &[0]).unwrap();
+        skip("AggregateFunction(any, String)", &[2, 0, 0, 0, b'h', b'i']).unwrap();
+        skip("AggregateFunction(any, String)", &[0xFF, 0xFF, 0xFF, 0xFF]).unwrap();
+        skip(
+            "AggregateFunction(avg, Float64)",
+            &[0, 0, 0, 0, 0, 0, 0, 0, 3],
+        )
+        .unwrap();
+        skip(
+            "AggregateFunction(uniq, UInt64)",
+            &[0, 2, 1, 0, 0, 0, 2, 0, 0, 0],
+        )
+        .unwrap();
+        assert_eq!(skip("AggregateFunction(count)", &[]).unwrap(), None);
+    }
+
+    #[test]
+    fn rejects_unknown_state_layouts() {
+        let err = skip("AggregateFunction(quantiles(0.5), Float64)", &[]).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedType(_)));
+        let err = skip("AggregateFunction(sum, String)", &[]).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedType(_)));
+    }
+}
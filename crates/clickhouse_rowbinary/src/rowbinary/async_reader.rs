@@ -0,0 +1,271 @@
+//! Async `RowBinary` reader over [`tokio::io::AsyncRead`].
+
+use futures::{Stream, stream};
+use tokio::io::{AsyncRead, AsyncReadExt};
+
+use crate::error::Result;
+
+use super::{
+    format::RowBinaryFormat,
+    push::{PushDecoded, RowBinaryPushDecoder},
+    reader::{ReaderOptions, RowBinaryHeader},
+    schema::{Row, Schema},
+};
+
+/// Size of each read from the underlying source.
+const READ_CHUNK_SIZE: usize = 8 * 1024;
+
+/// `RowBinary` reader that decodes rows from an async source.
+///
+/// Input is read in chunks and decoded incrementally, so streamed responses
+/// are never buffered whole. The reader reads ahead of the rows it returns,
+/// which is why the underlying source is not handed back.
+pub struct AsyncRowBinaryReader<R: AsyncRead + Unpin> {
+    inner: R,
+    decoder: RowBinaryPushDecoder,
+    schema: Schema,
+    chunk: Vec<u8>,
+    eof: bool,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRowBinaryReader<R> {
+    /// Creates a reader without a pre-defined schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub async fn new(inner: R, format: RowBinaryFormat) -> Result<Self> {
+        Self::with_options(inner, format, None, &ReaderOptions::default()).await
+    }
+
+    /// Creates a reader with an expected schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub async fn with_schema(inner: R, format: RowBinaryFormat, schema: Schema) -> Result<Self> {
+        Self::with_options(inner, format, Some(schema), &ReaderOptions::default()).await
+    }
+
+    /// Creates a reader with explicit [`ReaderOptions`].
+    ///
+    /// When `schema` is `None`, the header must carry the column types.
+    /// [`ReaderOptions::strict`] is not supported and is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub async fn with_options(
+        inner: R,
+        format: RowBinaryFormat,
+        schema: Option<Schema>,
+        options: &ReaderOptions,
+    ) -> Result<Self> {
+        let mut reader = Self {
+            inner,
+            decoder: RowBinaryPushDecoder::new(format, schema, options.clone()),
+            schema: Schema::new(Vec::new()),
+            chunk: vec![0; READ_CHUNK_SIZE],
+            eof: false,
+        };
+        while !reader.decoder.ensure_header()? {
+            if !reader.fill().await? {
+                reader.decoder.finish()?;
+                break;
+            }
+        }
+        if let Some(schema) = reader.decoder.schema() {
+            reader.schema = schema.clone();
+        }
+        Ok(reader)
+    }
+
+    /// Reads the next row, returning `None` at the end of the stream.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when decoding fails or the stream ends
+    /// inside a row.
+    pub async fn next_row(&mut self) -> Result<Option<Row>> {
+        loop {
+            match self.decoder.next_row()? {
+                PushDecoded::Row(row) => return Ok(Some(row)),
+                PushDecoded::NeedMoreData => {
+                    if !self.fill().await? {
+                        self.decoder.finish()?;
+                        return Ok(None);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Converts the reader into a stream of rows.
+    ///
+    /// The stream ends after the last row or after the first error.
+    pub fn into_stream(self) -> impl Stream<Item = Result<Row>> {
+        stream::unfold(Some(self), |reader| async move {
+            let mut reader = reader?;
+            match reader.next_row().await {
+                Ok(Some(row)) => Some((Ok(row), Some(reader))),
+                Ok(None) => None,
+                Err(err) => Some((Err(err), None)),
+            }
+        })
+    }
+
+    /// Returns the parsed header, if present.
+    #[must_use]
+    pub fn header(&self) -> Option<&RowBinaryHeader> {
+        self.decoder.header()
+    }
+
+    /// Returns the schema used for decoding, either the one supplied or the
+    /// one built from the header.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns a reference to the underlying source.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Feeds the next chunk to the decoder, returning `Ok(false)` at EOF.
+    async fn fill(&mut self) -> Result<bool> {
+        if self.eof {
+            return Ok(false);
+        }
+        let read = self.inner.read(&mut self.chunk).await?;
+        if read == 0 {
+            self.eof = true;
+            return Ok(false);
+        }
+        self.decoder.feed(&self.chunk[..read]);
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        pin::Pin,
+        task::{Context, Poll},
+    };
+
+    use futures::{StreamExt, TryStreamExt};
+    use tokio::io::{AsyncRead, ReadBuf};
+
+    use super::AsyncRowBinaryReader;
+    use crate::{
+        rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    /// Source that yields a single byte per read.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl AsyncRead for Trickle<'_> {
+        fn poll_read(
+            mut self: Pin<&mut Self>,
+            _cx: &mut Context<'_>,
+            buf: &mut ReadBuf<'_>,
+        ) -> Poll<io::Result<()>> {
+            if let Some((first, rest)) = self.0.split_first() {
+                buf.put_slice(&[*first]);
+                self.0 = rest;
+            }
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn payload() -> (Schema, Vec<Vec<Value>>, Vec<u8>) {
+        let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+        let rows: Vec<Vec<Value>> = (0..20_u32)
+            .map(|id| {
+                vec![
+                    Value::UInt32(id),
+                    Value::String(format!("row{id}").into_bytes()),
+                ]
+            })
+            .collect();
+        let mut writer = RowBinaryValueWriter::new(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema.clone(),
+        );
+        writer.write_header().unwrap();
+        writer.write_rows(&rows).unwrap();
+        (schema, rows, writer.into_inner())
+    }
+
+    #[tokio::test]
+    async fn reads_rows_from_async_source() {
+        let (schema, rows, payload) = payload();
+        let mut reader = AsyncRowBinaryReader::new(
+            Trickle(&payload),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        )
+        .await
+        .unwrap();
+        assert_eq!(reader.schema(), &schema);
+        let mut decoded = Vec::new();
+        while let Some(row) = reader.next_row().await.unwrap() {
+            decoded.push(row);
+        }
+        assert_eq!(decoded, rows);
+        assert!(reader.next_row().await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn reports_truncated_input() {
+        let (_, rows, payload) = payload();
+        let truncated = &payload[..payload.len() - 2];
+        let mut reader =
+            AsyncRowBinaryReader::new(truncated, RowBinaryFormat::RowBinaryWithNamesAndTypes)
+                .await
+                .unwrap();
+        for row in &rows[..rows.len() - 1] {
+            assert_eq!(reader.next_row().await.unwrap().as_ref(), Some(row));
+        }
+        assert!(reader.next_row().await.is_err());
+
+        let header_only = &payload[..3];
+        assert!(
+            AsyncRowBinaryReader::new(header_only, RowBinaryFormat::RowBinaryWithNamesAndTypes)
+                .await
+                .is_err()
+        );
+    }
+
+    #[tokio::test]
+    async fn streams_rows_until_error() {
+        let (_, rows, payload) = payload();
+        let reader = AsyncRowBinaryReader::new(
+            Trickle(&payload),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        )
+        .await
+        .unwrap();
+        let batches: Vec<Vec<_>> = reader
+            .into_stream()
+            .try_chunks(8)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(batches.len(), 3);
+        assert_eq!(batches.concat(), rows);
+
+        let truncated = &payload[..payload.len() - 2];
+        let reader =
+            AsyncRowBinaryReader::new(truncated, RowBinaryFormat::RowBinaryWithNamesAndTypes)
+                .await
+                .unwrap();
+        let results: Vec<_> = reader.into_stream().collect().await;
+        assert_eq!(results.len(), rows.len());
+        assert!(results.last().unwrap().is_err());
+    }
+}
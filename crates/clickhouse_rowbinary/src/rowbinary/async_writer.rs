@@ -0,0 +1,332 @@
+//! Async `RowBinary` writer over [`tokio::io::AsyncWrite`].
+
+use std::{
+    future::poll_fn,
+    io,
+    pin::Pin,
+    task::{Context, Poll, ready},
+};
+
+use futures::Sink;
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+
+use crate::{
+    error::{Error, Result},
+    value::Value,
+};
+
+use super::{
+    format::RowBinaryFormat,
+    schema::{Row, Schema},
+    writer::RowBinaryValueWriter,
+};
+
+/// Encoded bytes buffered before they are written to the sink.
+const DEFAULT_BUFFER_SIZE: usize = 64 * 1024;
+
+/// `RowBinary` writer that encodes rows into an async sink.
+///
+/// Rows are encoded into an internal buffer that is written out once it
+/// reaches the configured size, so each write awaits the sink and a slow
+/// consumer slows the producer down. Call [`Self::flush`] or
+/// [`Self::shutdown`] when done; buffered bytes are otherwise lost.
+///
+/// The writer is also a [`Sink`] of rows, so row streams can be forwarded
+/// into it directly. The sink writes the header before the first row and
+/// shuts the underlying writer down when closed.
+pub struct AsyncRowBinaryWriter<W: AsyncWrite + Unpin> {
+    inner: W,
+    encoder: RowBinaryValueWriter<Vec<u8>>,
+    buffer_size: usize,
+    /// Bytes at the start of the encoder buffer already written to `inner`.
+    written: usize,
+}
+
+impl<W: AsyncWrite + Unpin> AsyncRowBinaryWriter<W> {
+    /// Creates a writer for the specified format and schema.
+    #[must_use]
+    pub fn new(inner: W, format: RowBinaryFormat, schema: Schema) -> Self {
+        Self::with_buffer_size(inner, format, schema, DEFAULT_BUFFER_SIZE)
+    }
+
+    /// Creates a writer that buffers up to `buffer_size` encoded bytes
+    /// before writing to the sink.
+    #[must_use]
+    pub fn with_buffer_size(
+        inner: W,
+        format: RowBinaryFormat,
+        schema: Schema,
+        buffer_size: usize,
+    ) -> Self {
+        Self {
+            inner,
+            encoder: RowBinaryValueWriter::new(Vec::new(), format, schema),
+            buffer_size,
+            written: 0,
+        }
+    }
+
+    /// Controls how `FixedString` values shorter than the declared length
+    /// are written; see
+    /// [`RowBinaryValueWriter::set_fixed_string_padding`].
+    pub fn set_fixed_string_padding(&mut self, enabled: bool) {
+        self.encoder.set_fixed_string_padding(enabled);
+    }
+
+    /// Controls how header types are written; see
+    /// [`RowBinaryValueWriter::set_binary_types`].
+    pub fn set_binary_types(&mut self, enabled: bool) {
+        self.encoder.set_binary_types(enabled);
+    }
+
+    /// Writes the `RowBinary` header (names/types) when required.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the schema is invalid or IO fails.
+    pub async fn write_header(&mut self) -> Result<()> {
+        self.encoder.write_header()?;
+        self.write_if_full().await
+    }
+
+    /// Writes a single row.
+    ///
+    /// Call [`Self::write_header`] before writing the first row. A row that
+    /// fails to encode is discarded without affecting buffered rows.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row is invalid or IO fails.
+    pub async fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        let start = self.encoder.get_ref().len();
+        if let Err(err) = self.encoder.write_row(row) {
+            self.encoder.get_mut().truncate(start);
+            return Err(err);
+        }
+        self.write_if_full().await
+    }
+
+    /// Writes multiple rows.
+    ///
+    /// Call [`Self::write_header`] before writing the first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when any row is invalid or IO fails.
+    pub async fn write_rows<I, R>(&mut self, rows: I) -> Result<()>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        for row in rows {
+            self.write_row(row.as_ref()).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes buffered bytes to the sink and flushes it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when IO fails.
+    pub async fn flush(&mut self) -> Result<()> {
+        poll_fn(|cx| self.poll_write_buffered(cx)).await?;
+        self.inner.flush().await?;
+        Ok(())
+    }
+
+    /// Flushes buffered bytes and shuts the sink down, signalling the end
+    /// of the payload.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when IO fails.
+    pub async fn shutdown(&mut self) -> Result<()> {
+        poll_fn(|cx| self.poll_write_buffered(cx)).await?;
+        self.inner.shutdown().await?;
+        Ok(())
+    }
+
+    /// Returns the number of encoded bytes not yet written to the sink.
+    #[must_use]
+    pub fn buffered_len(&self) -> usize {
+        self.encoder.get_ref().len() - self.written
+    }
+
+    /// Returns a reference to the sink.
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns the sink, discarding any bytes not yet written.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+
+    async fn write_if_full(&mut self) -> Result<()> {
+        if self.buffered_len() >= self.buffer_size {
+            poll_fn(|cx| self.poll_write_buffered(cx)).await?;
+        }
+        Ok(())
+    }
+
+    /// Writes buffered bytes to `inner`, resuming after partial writes.
+    fn poll_write_buffered(&mut self, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        loop {
+            let buffer = self.encoder.get_ref();
+            if self.written >= buffer.len() {
+                self.encoder.get_mut().clear();
+                self.written = 0;
+                return Poll::Ready(Ok(()));
+            }
+            let count = ready!(Pin::new(&mut self.inner).poll_write(cx, &buffer[self.written..]))?;
+            if count == 0 {
+                return Poll::Ready(Err(Error::Io(io::Error::from(io::ErrorKind::WriteZero))));
+            }
+            self.written += count;
+        }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> Sink<Row> for AsyncRowBinaryWriter<W> {
+    type Error = Error;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = Pin::into_inner(self);
+        if this.buffered_len() >= this.buffer_size {
+            return this.poll_write_buffered(cx);
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, row: Row) -> Result<()> {
+        let this = Pin::into_inner(self);
+        this.encoder.write_header()?;
+        let start = this.encoder.get_ref().len();
+        this.encoder.write_row(&row).inspect_err(|_| {
+            this.encoder.get_mut().truncate(start);
+        })
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = Pin::into_inner(self);
+        ready!(this.poll_write_buffered(cx))?;
+        Pin::new(&mut this.inner).poll_flush(cx).map_err(Error::Io)
+    }
+
+    fn poll_close(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<()>> {
+        let this = Pin::into_inner(self);
+        this.encoder.write_header()?;
+        ready!(this.poll_write_buffered(cx))?;
+        Pin::new(&mut this.inner)
+            .poll_shutdown(cx)
+            .map_err(Error::Io)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::{StreamExt, stream};
+
+    use super::AsyncRowBinaryWriter;
+    use crate::{
+        rowbinary::{AsyncRowBinaryReader, RowBinaryFormat, RowBinaryValueReader, Schema},
+        value::Value,
+    };
+
+    fn sample() -> (Schema, Vec<Vec<Value>>) {
+        let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+        let rows = (0..500_u32)
+            .map(|id| {
+                vec![
+                    Value::UInt32(id),
+                    Value::String(format!("row{id}").into_bytes()),
+                ]
+            })
+            .collect();
+        (schema, rows)
+    }
+
+    #[tokio::test]
+    async fn writes_rows_to_async_sink() {
+        let (schema, rows) = sample();
+        let mut writer = AsyncRowBinaryWriter::with_buffer_size(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema.clone(),
+            100,
+        );
+        writer.write_header().await.unwrap();
+        writer.write_rows(&rows).await.unwrap();
+        assert!(writer.buffered_len() < 100);
+        assert!(!writer.get_ref().is_empty());
+
+        let bad_row = [Value::String(b"x".to_vec()), Value::UInt32(1)];
+        let buffered = writer.buffered_len();
+        assert!(writer.write_row(&bad_row).await.is_err());
+        assert_eq!(writer.buffered_len(), buffered);
+
+        writer.flush().await.unwrap();
+        assert_eq!(writer.buffered_len(), 0);
+        let payload = writer.into_inner();
+        let reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+        assert_eq!(reader.schema(), &schema);
+        let decoded: Vec<_> = reader.rows().collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded, rows);
+    }
+
+    #[tokio::test]
+    async fn applies_backpressure_through_small_pipe() {
+        let (schema, rows) = sample();
+        let (client, server) = tokio::io::duplex(64);
+        let write = async {
+            let mut writer = AsyncRowBinaryWriter::with_buffer_size(
+                client,
+                RowBinaryFormat::RowBinary,
+                schema.clone(),
+                32,
+            );
+            writer.write_rows(&rows).await.unwrap();
+            writer.shutdown().await.unwrap();
+        };
+        let read = async {
+            let mut reader = AsyncRowBinaryReader::with_schema(
+                server,
+                RowBinaryFormat::RowBinary,
+                schema.clone(),
+            )
+            .await
+            .unwrap();
+            let mut decoded = Vec::new();
+            while let Some(row) = reader.next_row().await.unwrap() {
+                decoded.push(row);
+            }
+            decoded
+        };
+        let ((), decoded) = tokio::join!(write, read);
+        assert_eq!(decoded, rows);
+    }
+
+    #[tokio::test]
+    async fn forwards_row_stream_into_sink() {
+        let (schema, rows) = sample();
+        let mut writer = AsyncRowBinaryWriter::with_buffer_size(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema.clone(),
+            100,
+        );
+        stream::iter(rows.clone().into_iter().map(Ok))
+            .forward(&mut writer)
+            .await
+            .unwrap();
+        assert_eq!(writer.buffered_len(), 0);
+
+        let payload = writer.into_inner();
+        let reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+        assert_eq!(reader.schema(), &schema);
+        let decoded: Vec<_> = reader.rows().collect::<Result<_, _>>().unwrap();
+        assert_eq!(decoded, rows);
+    }
+}
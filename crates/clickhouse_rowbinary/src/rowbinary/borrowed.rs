@@ -0,0 +1,171 @@
+//! Borrowed decoding of in-memory `RowBinary` payloads.
+
+use std::io;
+
+use crate::{
+    error::{Error, Result},
+    io::read_uvarint,
+    types::TypeDesc,
+    value::ValueRef,
+};
+
+use super::{
+    format::RowBinaryFormat,
+    reader::{ReaderOptions, RowBinaryHeader, parse_header_from_reader},
+    schema::Schema,
+    value_rw::{USE_DEFAULT, default_value, read_value_required},
+};
+
+/// `RowBinary` reader over a byte slice that borrows string data.
+///
+/// Rows are decoded into [`ValueRef`]s whose strings point into the
+/// payload, so string-heavy result sets are scanned without copying.
+pub struct RowBinaryRefReader<'a> {
+    input: &'a [u8],
+    schema: Schema,
+    header: Option<RowBinaryHeader>,
+    /// Values carry a "use default" flag byte (`RowBinaryWithDefaults`).
+    defaults: bool,
+}
+
+impl<'a> RowBinaryRefReader<'a> {
+    /// Creates a reader over `payload`.
+    ///
+    /// When `schema` is `None`, the header must carry the column types.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub fn new(payload: &'a [u8], format: RowBinaryFormat, schema: Option<Schema>) -> Result<Self> {
+        let mut input = payload;
+        let (schema, header, _) =
+            parse_header_from_reader(&mut input, format, schema, &ReaderOptions::default())?;
+        Ok(Self {
+            input,
+            schema,
+            header,
+            defaults: format.has_default_flags(),
+        })
+    }
+
+    /// Returns the schema used for decoding.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Returns the parsed header, if present.
+    #[must_use]
+    pub fn header(&self) -> Option<&RowBinaryHeader> {
+        self.header.as_ref()
+    }
+
+    /// Reads the next row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when decoding fails or the payload
+    /// ends in the middle of a row.
+    pub fn read_row(&mut self) -> Result<Option<Vec<ValueRef<'a>>>> {
+        if self.input.is_empty() || self.schema.is_empty() {
+            return Ok(None);
+        }
+        if matches!(self.schema.fields()[0].ty, TypeDesc::Nothing) {
+            return Err(Error::UnsupportedCombination(
+                "RowBinary cannot stream Nothing as the leading column".into(),
+            ));
+        }
+        let mut row = Vec::with_capacity(self.schema.len());
+        for field in self.schema.fields() {
+            if self.defaults && take_default_flag(&field.ty, &mut self.input)? {
+                row.push(ValueRef::Owned(default_value(&field.ty)?));
+            } else {
+                row.push(read_value_ref(&field.ty, &mut self.input)?);
+            }
+        }
+        Ok(Some(row))
+    }
+}
+
+fn read_value_ref<'a>(ty: &TypeDesc, input: &mut &'a [u8]) -> Result<ValueRef<'a>> {
+    match ty {
+        TypeDesc::String => {
+            let len = read_length(input, "string length too large")?;
+            take(input, len).map(ValueRef::String)
+        }
+        TypeDesc::FixedString { length } => take(input, *length).map(ValueRef::FixedString),
+        TypeDesc::Nullable(inner) => match take(input, 1)?[0] {
+            0 => Ok(ValueRef::Nullable(Some(Box::new(read_value_ref(
+                inner, input,
+            )?)))),
+            1 => Ok(ValueRef::Nullable(None)),
+            _ => Err(Error::InvalidValue("invalid nullable flag")),
+        },
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
+            read_value_ref(inner, input)
+        }
+        TypeDesc::Array(inner) => {
+            let len = read_length(input, "array length too large")?;
+            let mut items = Vec::with_capacity(len.min(input.len()));
+            for _ in 0..len {
+                items.push(read_value_ref(inner, input)?);
+            }
+            Ok(ValueRef::Array(items))
+        }
+        TypeDesc::Map { key, value } => {
+            let len = read_length(input, "map length too large")?;
+            let mut entries = Vec::with_capacity(len.min(input.len()));
+            for _ in 0..len {
+                let key = read_value_ref(key, input)?;
+                let value = read_value_ref(value, input)?;
+                entries.push((key, value));
+            }
+            Ok(ValueRef::Map(entries))
+        }
+        TypeDesc::Tuple(items) => items
+            .iter()
+            .map(|item| read_value_ref(&item.ty, input))
+            .collect::<Result<_>>()
+            .map(ValueRef::Tuple),
+        _ => match ty.geo_storage() {
+            Some(storage) => read_value_ref(storage, input),
+            None => read_value_required(ty, input).map(ValueRef::Owned),
+        },
+    }
+}
+
+/// Reads a `RowBinaryWithDefaults` flag byte, returning `true` when the
+/// value is omitted in favor of the column default.
+fn take_default_flag(ty: &TypeDesc, input: &mut &[u8]) -> Result<bool> {
+    if matches!(ty, TypeDesc::Nested(_)) {
+        return Err(Error::UnsupportedCombination(
+            "RowBinaryWithDefaults does not support Nested columns".into(),
+        ));
+    }
+    match take(input, 1)?[0] {
+        0 => Ok(false),
+        USE_DEFAULT => Ok(true),
+        _ => Err(Error::InvalidValue("invalid default flag")),
+    }
+}
+
+fn read_length(input: &mut &[u8], overflow: &'static str) -> Result<usize> {
+    let len = read_uvarint(input)?.ok_or_else(unexpected_eof)?;
+    usize::try_from(len).map_err(|_| Error::Overflow(overflow))
+}
+
+fn take<'a>(input: &mut &'a [u8], len: usize) -> Result<&'a [u8]> {
+    if input.len() < len {
+        return Err(unexpected_eof());
+    }
+    let (head, tail) = input.split_at(len);
+    *input = tail;
+    Ok(head)
+}
+
+fn unexpected_eof() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "unexpected EOF while reading row",
+    ))
+}
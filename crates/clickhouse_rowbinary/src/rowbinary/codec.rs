@@ -0,0 +1,171 @@
+//! Per-column value conversions applied by readers and writers.
+
+use std::{borrow::Cow, fmt, sync::Arc};
+
+use crate::{
+    error::{Error, Result},
+    types::TypeDesc,
+    value::Value,
+};
+
+use super::schema::{Field, Row, Schema};
+
+type Encode = dyn Fn(&Value) -> Result<Value> + Send + Sync;
+type Decode = dyn Fn(Value) -> Result<Value> + Send + Sync;
+
+/// Custom conversion between the values stored in a column and the values
+/// the application works with, such as a `String` column holding an
+/// encoded document.
+///
+/// Once registered with [`crate::RowBinaryValueReader::set_column_codec`]
+/// or [`crate::RowBinaryValueWriter::set_column_codec`], `decode` runs on
+/// every value read from the column and `encode` on every value written to
+/// it. Rows, typed conversions and the reader's schema then see the column
+/// as [`Self::ty`]:
+///
+/// ```
+/// # use clickhouse_rowbinary::{ColumnCodec, Error, Value, parse_type_desc};
+/// // `String` column holding "x:y" pairs, exposed as `Tuple(UInt32, UInt32)`.
+/// let codec = ColumnCodec::new(
+///     parse_type_desc("Tuple(UInt32, UInt32)")?,
+///     |value| match value {
+///         Value::Tuple(items) => match items.as_slice() {
+///             [Value::UInt32(x), Value::UInt32(y)] => {
+///                 Ok(Value::String(format!("{x}:{y}").into()))
+///             }
+///             _ => Err(Error::InvalidValue("expected a pair")),
+///         },
+///         _ => Err(Error::InvalidValue("expected a pair")),
+///     },
+///     |value| {
+///         let Value::String(bytes) = value else {
+///             return Err(Error::InvalidValue("expected a string"));
+///         };
+///         let text = String::from_utf8(bytes).map_err(|err| Error::Mapping(err.to_string()))?;
+///         let (x, y) = text
+///             .split_once(':')
+///             .ok_or(Error::InvalidValue("expected x:y"))?;
+///         let parse = |part: &str| {
+///             part.parse()
+///                 .map_err(|_| Error::InvalidValue("expected a number"))
+///         };
+///         Ok(Value::Tuple(vec![
+///             Value::UInt32(parse(x)?),
+///             Value::UInt32(parse(y)?),
+///         ]))
+///     },
+/// );
+/// assert_eq!(codec.ty().type_name(), "Tuple(UInt32, UInt32)");
+/// # Ok::<(), clickhouse_rowbinary::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct ColumnCodec {
+    ty: TypeDesc,
+    encode: Arc<Encode>,
+    decode: Arc<Decode>,
+}
+
+impl ColumnCodec {
+    /// Creates a codec whose decoded values have type `ty`.
+    ///
+    /// `encode` turns a decoded value back into a value of the stored
+    /// column type; `decode` does the reverse.
+    pub fn new<E, D>(ty: TypeDesc, encode: E, decode: D) -> Self
+    where
+        E: Fn(&Value) -> Result<Value> + Send + Sync + 'static,
+        D: Fn(Value) -> Result<Value> + Send + Sync + 'static,
+    {
+        Self {
+            ty,
+            encode: Arc::new(encode),
+            decode: Arc::new(decode),
+        }
+    }
+
+    /// Returns the type of the decoded values.
+    #[must_use]
+    pub fn ty(&self) -> &TypeDesc {
+        &self.ty
+    }
+
+    /// Converts a decoded value into a value of the stored column type.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error produced by the encoder.
+    pub fn encode(&self, value: &Value) -> Result<Value> {
+        (self.encode)(value)
+    }
+
+    /// Converts a stored value into its decoded form.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error produced by the decoder.
+    pub fn decode(&self, value: Value) -> Result<Value> {
+        (self.decode)(value)
+    }
+}
+
+impl fmt::Debug for ColumnCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ColumnCodec")
+            .field("ty", &self.ty)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Codecs registered on a reader or writer, by schema position.
+#[derive(Clone, Debug, Default)]
+pub(super) struct ColumnCodecs {
+    /// Codec of each schema field; empty when none is registered.
+    columns: Vec<Option<ColumnCodec>>,
+    /// Schema with each coded column retyped to its codec type.
+    schema: Option<Schema>,
+}
+
+impl ColumnCodecs {
+    /// Registers `codec` for the column `name` of `stored`, replacing any
+    /// previous codec of that column.
+    pub(super) fn insert(&mut self, stored: &Schema, name: &str, codec: ColumnCodec) -> Result<()> {
+        let index = stored
+            .index_of(name)
+            .ok_or_else(|| Error::UnknownColumn(name.to_string()))?;
+        self.columns.resize(stored.len(), None);
+        let mut fields = self.schema.take().map_or_else(
+            || stored.fields().to_vec(),
+            |schema| schema.fields().to_vec(),
+        );
+        fields[index] = Field {
+            name: name.to_string(),
+            ty: codec.ty.clone(),
+        };
+        self.columns[index] = Some(codec);
+        self.schema = Some(Schema::new(fields));
+        Ok(())
+    }
+
+    /// Returns the schema rows are exposed with.
+    pub(super) fn schema<'a>(&'a self, stored: &'a Schema) -> &'a Schema {
+        self.schema.as_ref().unwrap_or(stored)
+    }
+
+    /// Decodes the coded columns of a row read with the stored schema.
+    pub(super) fn decode(&self, row: &mut Row) -> Result<()> {
+        for (codec, value) in self.columns.iter().zip(row.iter_mut()) {
+            if let Some(codec) = codec {
+                let stored = std::mem::replace(value, Value::Nothing);
+                *value = codec.decode(stored)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Encodes the value of column `index` for writing.
+    pub(super) fn encode<'a>(&self, index: usize, value: &'a Value) -> Result<Cow<'a, Value>> {
+        match self.columns.get(index) {
+            Some(Some(codec)) => codec.encode(value).map(Cow::Owned),
+            _ => Ok(Cow::Borrowed(value)),
+        }
+    }
+}
@@ -0,0 +1,117 @@
+//! Columns decoded into `Vec`s by
+//! [`crate::RowBinaryValueReader::read_columns`] and then converted into
+//! the columns of a dataframe library.
+
+use std::io::Read;
+
+use super::{DecodeColumn, FromColumnValue, RowBinaryValueReader, columnar::storage_type};
+use crate::{error::Result, types::TypeDesc};
+
+/// Values decoded for one column.
+pub(crate) enum Values<T> {
+    Plain(Vec<T>),
+    Nullable(Vec<Option<T>>),
+}
+
+impl<T> Values<T> {
+    pub(crate) fn into_options(self) -> impl Iterator<Item = Option<T>> {
+        let (plain, nullable) = match self {
+            Values::Plain(values) => (values, Vec::new()),
+            Values::Nullable(values) => (Vec::new(), values),
+        };
+        plain.into_iter().map(Some).chain(nullable)
+    }
+}
+
+/// [`DecodeColumn`] that becomes an `O` once decoding is done.
+pub(crate) trait Collect<O>: DecodeColumn {
+    fn finish(self: Box<Self>) -> Result<O>;
+}
+
+/// Column decoded into a `Vec` and then built into an `O`.
+struct Collected<T, O> {
+    values: Values<T>,
+    build: Box<dyn FnOnce(Values<T>) -> Result<O>>,
+}
+
+impl<T: FromColumnValue, O> DecodeColumn for Collected<T, O> {
+    fn len(&self) -> usize {
+        match &self.values {
+            Values::Plain(values) => values.len(),
+            Values::Nullable(values) => values.len(),
+        }
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        match &self.values {
+            Values::Plain(values) => DecodeColumn::check_type(values, ty),
+            Values::Nullable(values) => DecodeColumn::check_type(values, ty),
+        }
+    }
+
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()> {
+        match &mut self.values {
+            Values::Plain(values) => values.read_value(ty, reader),
+            Values::Nullable(values) => values.read_value(ty, reader),
+        }
+    }
+
+    fn truncate(&mut self, len: usize) {
+        match &mut self.values {
+            Values::Plain(values) => values.truncate(len),
+            Values::Nullable(values) => values.truncate(len),
+        }
+    }
+}
+
+impl<T: FromColumnValue, O> Collect<O> for Collected<T, O> {
+    fn finish(self: Box<Self>) -> Result<O> {
+        (self.build)(self.values)
+    }
+}
+
+/// Returns a column that decodes values as `T`, as `Option<T>` when
+/// `nullable`, and converts them with `build`.
+pub(crate) fn collected<T: FromColumnValue + 'static, O: 'static>(
+    nullable: bool,
+    build: impl FnOnce(Values<T>) -> Result<O> + 'static,
+) -> Box<dyn Collect<O>> {
+    let values = if nullable {
+        Values::Nullable(Vec::new())
+    } else {
+        Values::Plain(Vec::new())
+    };
+    Box::new(Collected {
+        values,
+        build: Box::new(build),
+    })
+}
+
+/// Decodes up to `max_rows` rows from `reader` into `columns` and converts
+/// them, or returns `None` once the stream has no more rows.
+pub(crate) fn read_collected<R: Read, O>(
+    reader: &mut RowBinaryValueReader<R>,
+    mut columns: Vec<Box<dyn Collect<O>>>,
+    max_rows: usize,
+) -> Result<Option<Vec<O>>> {
+    let mut targets = columns
+        .iter_mut()
+        .map(|column| column.as_mut() as &mut dyn DecodeColumn)
+        .collect::<Vec<_>>();
+    if reader.read_columns(&mut targets, max_rows)? == 0 {
+        return Ok(None);
+    }
+    columns
+        .into_iter()
+        .map(Collect::finish)
+        .collect::<Result<Vec<_>>>()
+        .map(Some)
+}
+
+/// Splits a column type into its nullability and the type of its values.
+pub(crate) fn column_type(ty: &TypeDesc) -> (bool, &TypeDesc) {
+    match storage_type(ty) {
+        TypeDesc::Nullable(inner) => (true, storage_type(inner)),
+        ty => (false, ty),
+    }
+}
@@ -0,0 +1,395 @@
+//! Column-oriented encoding from typed Rust slices and decoding into typed
+//! vectors.
+//!
+//! [`ColumnValue`] maps plain Rust types onto `RowBinary` column types and
+//! [`EncodeColumn`] exposes a whole column to
+//! [`crate::RowBinaryValueWriter::write_columns`], which interleaves the
+//! columns into rows without building [`crate::Value`]s. In the other
+//! direction, [`FromColumnValue`] and [`DecodeColumn`] let
+//! [`crate::RowBinaryValueReader::read_columns`] fill one `Vec` per column.
+
+use std::io::{Read, Write};
+
+use crate::{
+    error::{Error, Result},
+    io::{read_bytes, write_bytes},
+    types::{DecimalSize, TypeDesc},
+};
+
+/// Rust type that encodes directly as a `RowBinary` column value.
+pub trait ColumnValue {
+    /// Rust type name used in type mismatch errors.
+    const NAME: &'static str;
+
+    /// Returns whether values of this type can be encoded as `ty`.
+    ///
+    /// `ty` has `LowCardinality` and `SimpleAggregateFunction` wrappers
+    /// removed.
+    fn supports(ty: &TypeDesc) -> bool;
+
+    /// Encodes the value as `ty`, which [`Self::supports`] accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the value does not fit `ty` or
+    /// IO fails.
+    fn write_to<W: Write + ?Sized>(&self, ty: &TypeDesc, writer: &mut W) -> Result<()>;
+}
+
+/// Column of values that can be interleaved into `RowBinary` rows.
+///
+/// Implemented for `Vec<T>`, `&[T]` and `[T; N]` of any [`ColumnValue`].
+pub trait EncodeColumn {
+    /// Number of values in the column.
+    fn len(&self) -> usize;
+
+    /// Returns whether the column is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks that the column can be encoded as `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when it cannot.
+    fn check_type(&self, ty: &TypeDesc) -> Result<()>;
+
+    /// Encodes the value at `index` as `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the value does not fit `ty` or
+    /// IO fails.
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()>;
+}
+
+/// [`ColumnValue`] that can also be decoded from a `RowBinary` column.
+pub trait FromColumnValue: ColumnValue + Sized {
+    /// Decodes a value of `ty`, which [`ColumnValue::supports`] accepted.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the data is malformed or IO
+    /// fails.
+    fn read_from<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<Self>;
+}
+
+/// Column that decoded values are appended to.
+///
+/// Implemented for `Vec<T>` of any [`FromColumnValue`].
+pub trait DecodeColumn {
+    /// Number of values in the column.
+    fn len(&self) -> usize;
+
+    /// Returns whether the column is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Checks that the column can be decoded from `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when it cannot.
+    fn check_type(&self, ty: &TypeDesc) -> Result<()>;
+
+    /// Decodes one value of `ty` and appends it.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the data is malformed or IO
+    /// fails.
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()>;
+
+    /// Shortens the column to `len` values.
+    fn truncate(&mut self, len: usize);
+}
+
+impl<T: FromColumnValue> DecodeColumn for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        check_slice::<T>(ty)
+    }
+
+    fn read_value(&mut self, ty: &TypeDesc, reader: &mut dyn Read) -> Result<()> {
+        self.push(T::read_from(storage_type(ty), reader)?);
+        Ok(())
+    }
+
+    fn truncate(&mut self, len: usize) {
+        Vec::truncate(self, len);
+    }
+}
+
+/// Strips wrappers that do not change the `RowBinary` encoding.
+pub(crate) fn storage_type(mut ty: &TypeDesc) -> &TypeDesc {
+    while let TypeDesc::LowCardinality(inner)
+    | TypeDesc::SimpleAggregateFunction { ty: inner, .. } = ty
+    {
+        ty = inner;
+    }
+    ty
+}
+
+fn encode_slice<T: ColumnValue>(
+    values: &[T],
+    index: usize,
+    ty: &TypeDesc,
+    writer: &mut dyn Write,
+) -> Result<()> {
+    let value = values
+        .get(index)
+        .ok_or(Error::InvalidValue("column index out of range"))?;
+    value.write_to(storage_type(ty), writer)
+}
+
+fn check_slice<T: ColumnValue>(ty: &TypeDesc) -> Result<()> {
+    if T::supports(storage_type(ty)) {
+        Ok(())
+    } else {
+        Err(Error::TypeMismatch {
+            expected: ty.type_name(),
+            actual: T::NAME.to_string(),
+        })
+    }
+}
+
+impl<T: ColumnValue> EncodeColumn for &[T] {
+    fn len(&self) -> usize {
+        <[T]>::len(self)
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        check_slice::<T>(ty)
+    }
+
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()> {
+        encode_slice(self, index, ty, writer)
+    }
+}
+
+impl<T: ColumnValue> EncodeColumn for Vec<T> {
+    fn len(&self) -> usize {
+        Vec::len(self)
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        check_slice::<T>(ty)
+    }
+
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()> {
+        encode_slice(self, index, ty, writer)
+    }
+}
+
+impl<T: ColumnValue, const N: usize> EncodeColumn for [T; N] {
+    fn len(&self) -> usize {
+        N
+    }
+
+    fn check_type(&self, ty: &TypeDesc) -> Result<()> {
+        check_slice::<T>(ty)
+    }
+
+    fn write_value(&self, index: usize, ty: &TypeDesc, writer: &mut dyn Write) -> Result<()> {
+        encode_slice(self, index, ty, writer)
+    }
+}
+
+macro_rules! impl_column_value {
+    ($ty:ty, $name:literal, $pattern:pat) => {
+        impl ColumnValue for $ty {
+            const NAME: &'static str = $name;
+
+            fn supports(ty: &TypeDesc) -> bool {
+                matches!(ty, $pattern)
+            }
+
+            fn write_to<W: Write + ?Sized>(&self, _ty: &TypeDesc, writer: &mut W) -> Result<()> {
+                writer.write_all(&self.to_le_bytes())?;
+                Ok(())
+            }
+        }
+
+        impl FromColumnValue for $ty {
+            fn read_from<R: Read + ?Sized>(_ty: &TypeDesc, reader: &mut R) -> Result<Self> {
+                let mut bytes = [0_u8; size_of::<$ty>()];
+                reader.read_exact(&mut bytes)?;
+                Ok(Self::from_le_bytes(bytes))
+            }
+        }
+    };
+}
+
+impl_column_value!(u8, "u8", TypeDesc::UInt8);
+impl_column_value!(u16, "u16", TypeDesc::UInt16 | TypeDesc::Date);
+impl_column_value!(u32, "u32", TypeDesc::UInt32 | TypeDesc::DateTime { .. });
+impl_column_value!(u64, "u64", TypeDesc::UInt64);
+impl_column_value!(u128, "u128", TypeDesc::UInt128);
+impl_column_value!(i8, "i8", TypeDesc::Int8 | TypeDesc::Enum8(_));
+impl_column_value!(i16, "i16", TypeDesc::Int16 | TypeDesc::Enum16(_));
+impl_column_value!(
+    i32,
+    "i32",
+    TypeDesc::Int32
+        | TypeDesc::Date32
+        | TypeDesc::Decimal32 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits32,
+            ..
+        }
+);
+impl_column_value!(
+    i64,
+    "i64",
+    TypeDesc::Int64
+        | TypeDesc::Interval(_)
+        | TypeDesc::DateTime64 { .. }
+        | TypeDesc::Decimal64 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits64,
+            ..
+        }
+);
+impl_column_value!(
+    i128,
+    "i128",
+    TypeDesc::Int128
+        | TypeDesc::Decimal128 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits128,
+            ..
+        }
+);
+impl_column_value!(f32, "f32", TypeDesc::Float32);
+impl_column_value!(f64, "f64", TypeDesc::Float64);
+
+impl ColumnValue for bool {
+    const NAME: &'static str = "bool";
+
+    fn supports(ty: &TypeDesc) -> bool {
+        matches!(ty, TypeDesc::Bool)
+    }
+
+    fn write_to<W: Write + ?Sized>(&self, _ty: &TypeDesc, writer: &mut W) -> Result<()> {
+        writer.write_all(&[u8::from(*self)])?;
+        Ok(())
+    }
+}
+
+impl FromColumnValue for bool {
+    fn read_from<R: Read + ?Sized>(_ty: &TypeDesc, reader: &mut R) -> Result<Self> {
+        match read_byte(reader)? {
+            0 => Ok(false),
+            1 => Ok(true),
+            _ => Err(Error::InvalidValue("invalid Bool value")),
+        }
+    }
+}
+
+fn read_byte<R: Read + ?Sized>(reader: &mut R) -> Result<u8> {
+    let mut byte = [0_u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+/// Encodes bytes as `String` or as a `FixedString` of exactly that length.
+fn write_byte_string<W: Write + ?Sized>(bytes: &[u8], ty: &TypeDesc, writer: &mut W) -> Result<()> {
+    match ty {
+        TypeDesc::FixedString { length } => {
+            if bytes.len() != *length {
+                return Err(Error::InvalidValue("FixedString length mismatch"));
+            }
+            writer.write_all(bytes)?;
+            Ok(())
+        }
+        _ => write_bytes(bytes, writer),
+    }
+}
+
+macro_rules! impl_byte_string_column_value {
+    ($ty:ty, $name:literal) => {
+        impl ColumnValue for $ty {
+            const NAME: &'static str = $name;
+
+            fn supports(ty: &TypeDesc) -> bool {
+                matches!(ty, TypeDesc::String | TypeDesc::FixedString { .. })
+            }
+
+            fn write_to<W: Write + ?Sized>(&self, ty: &TypeDesc, writer: &mut W) -> Result<()> {
+                write_byte_string(self.as_ref(), ty, writer)
+            }
+        }
+    };
+}
+
+/// Decodes a `String` or `FixedString` value as bytes.
+fn read_byte_string<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<Vec<u8>> {
+    if let TypeDesc::FixedString { length } = ty {
+        let mut bytes = vec![0_u8; *length];
+        reader.read_exact(&mut bytes)?;
+        return Ok(bytes);
+    }
+    read_bytes(reader)?.ok_or_else(|| {
+        Error::Io(std::io::Error::new(
+            std::io::ErrorKind::UnexpectedEof,
+            "unexpected EOF while reading string",
+        ))
+    })
+}
+
+impl FromColumnValue for Vec<u8> {
+    fn read_from<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<Self> {
+        read_byte_string(ty, reader)
+    }
+}
+
+impl FromColumnValue for String {
+    fn read_from<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<Self> {
+        String::from_utf8(read_byte_string(ty, reader)?)
+            .map_err(|_| Error::InvalidValue("invalid UTF-8 string"))
+    }
+}
+
+impl_byte_string_column_value!(&str, "&str");
+impl_byte_string_column_value!(String, "String");
+impl_byte_string_column_value!(&[u8], "&[u8]");
+impl_byte_string_column_value!(Vec<u8>, "Vec<u8>");
+
+impl<T: ColumnValue> ColumnValue for Option<T> {
+    const NAME: &'static str = "Option";
+
+    fn supports(ty: &TypeDesc) -> bool {
+        matches!(ty, TypeDesc::Nullable(inner) if T::supports(storage_type(inner)))
+    }
+
+    fn write_to<W: Write + ?Sized>(&self, ty: &TypeDesc, writer: &mut W) -> Result<()> {
+        let TypeDesc::Nullable(inner) = ty else {
+            return Err(Error::Internal("Option column without Nullable type"));
+        };
+        if let Some(value) = self {
+            writer.write_all(&[0])?;
+            value.write_to(storage_type(inner), writer)
+        } else {
+            writer.write_all(&[1])?;
+            Ok(())
+        }
+    }
+}
+
+impl<T: FromColumnValue> FromColumnValue for Option<T> {
+    fn read_from<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut R) -> Result<Self> {
+        let TypeDesc::Nullable(inner) = ty else {
+            return Err(Error::Internal("Option column without Nullable type"));
+        };
+        match read_byte(reader)? {
+            0 => T::read_from(storage_type(inner), reader).map(Some),
+            1 => Ok(None),
+            _ => Err(Error::InvalidValue("invalid nullable flag")),
+        }
+    }
+}
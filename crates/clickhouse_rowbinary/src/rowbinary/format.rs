@@ -9,6 +9,24 @@ pub enum RowBinaryFormat {
     RowBinaryWithNames,
     /// `RowBinary` with column names and types.
     RowBinaryWithNamesAndTypes,
+    /// Plain `RowBinary` where every value is preceded by a flag byte; `1`
+    /// omits the value and lets the server apply the column default.
+    RowBinaryWithDefaults,
+}
+
+impl RowBinaryFormat {
+    /// Returns `true` when payloads start with a names (and types) header.
+    pub(crate) fn has_header(self) -> bool {
+        matches!(
+            self,
+            RowBinaryFormat::RowBinaryWithNames | RowBinaryFormat::RowBinaryWithNamesAndTypes
+        )
+    }
+
+    /// Returns `true` when every value is preceded by a "use default" flag.
+    pub(crate) fn has_default_flags(self) -> bool {
+        self == RowBinaryFormat::RowBinaryWithDefaults
+    }
 }
 
 impl std::fmt::Display for RowBinaryFormat {
@@ -19,6 +37,7 @@ impl std::fmt::Display for RowBinaryFormat {
             RowBinaryFormat::RowBinaryWithNamesAndTypes => {
                 f.write_str("RowBinaryWithNamesAndTypes")
             }
+            RowBinaryFormat::RowBinaryWithDefaults => f.write_str("RowBinaryWithDefaults"),
         }
     }
 }
@@ -1,6 +1,17 @@
 //! `RowBinary` read/write support.
 
+mod aggregate;
+#[cfg(feature = "async")]
+mod async_reader;
+#[cfg(feature = "async")]
+mod async_writer;
+mod borrowed;
+mod codec;
+#[cfg(any(feature = "arrow", feature = "polars"))]
+mod collect;
+mod columnar;
 mod format;
+mod push;
 mod reader;
 mod scan;
 mod schema;
@@ -8,10 +19,24 @@ mod type_binary;
 mod value_rw;
 mod writer;
 
+#[cfg(feature = "async")]
+pub use async_reader::AsyncRowBinaryReader;
+#[cfg(feature = "async")]
+pub use async_writer::AsyncRowBinaryWriter;
+pub use borrowed::RowBinaryRefReader;
+pub use codec::ColumnCodec;
+#[cfg(any(feature = "arrow", feature = "polars"))]
+pub(crate) use collect::{Collect, Values, collected, column_type, read_collected};
+pub(crate) use columnar::storage_type;
+pub use columnar::{ColumnValue, DecodeColumn, EncodeColumn, FromColumnValue};
 pub use format::RowBinaryFormat;
-pub use reader::{RowBinaryHeader, RowBinaryReader, RowBinaryValueReader};
+pub use push::{PushDecoded, RowBinaryPushDecoder};
+pub use reader::{
+    HeaderPolicy, ReaderOptions, RowBinaryHeader, RowBinaryReader, RowBinaryValueReader,
+};
 pub use schema::{Field, Row, Schema};
-pub use writer::{RowBinaryValueWriter, RowBinaryWriter};
+pub(crate) use value_rw::{WriteOptions, default_value, read_value_required, write_value};
+pub use writer::{FlushPolicy, RowBinaryValueWriter, RowBinaryWriter, WriteStats};
 
 /// File-backed seekable Zstd reader.
 pub type RowBinaryFileReader = RowBinaryReader<std::io::BufReader<std::fs::File>>;
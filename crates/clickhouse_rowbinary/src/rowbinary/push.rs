@@ -0,0 +1,222 @@
+//! Push-based decoding for payloads that arrive in chunks.
+
+use std::io;
+
+use crate::{
+    compression::MAX_BLOCK_SIZE,
+    error::{Error, Result},
+    io::missing_bytes,
+    types::TypeDesc,
+    value::Value,
+};
+
+use super::{
+    format::RowBinaryFormat,
+    reader::{ReaderOptions, RowBinaryHeader, header_ordered_schema, read_header_from_reader},
+    schema::{Row, Schema},
+    value_rw::{read_value_or_default_required, read_value_required},
+};
+
+/// Outcome of [`RowBinaryPushDecoder::next_row`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum PushDecoded {
+    /// A complete row was decoded.
+    Row(Row),
+    /// The buffered data ends before the next row does; feed more input.
+    NeedMoreData,
+}
+
+/// `RowBinary` decoder fed with chunks of input as they arrive.
+///
+/// Rows split across chunk boundaries are kept buffered until they are
+/// complete, so network responses can be decoded without buffering the
+/// whole body. Call [`RowBinaryPushDecoder::finish`] once the input ends to
+/// detect a truncated final row.
+pub struct RowBinaryPushDecoder {
+    format: RowBinaryFormat,
+    options: ReaderOptions,
+    /// Supplied schema until the header is parsed.
+    pending_schema: Option<Schema>,
+    state: Option<DecodeState>,
+    buffer: Vec<u8>,
+    /// Start of the unconsumed input in `buffer`.
+    position: usize,
+    /// Bytes past `position` known to be needed before decoding can make
+    /// progress, so that a large value is not decoded again on every feed.
+    needed: usize,
+}
+
+struct DecodeState {
+    schema: Schema,
+    /// Schema in header order when columns are reordered.
+    decode_schema: Option<Schema>,
+    positions: Option<Vec<usize>>,
+    header: Option<RowBinaryHeader>,
+}
+
+impl RowBinaryPushDecoder {
+    /// Creates a decoder; see [`crate::RowBinaryValueReader::with_options`]
+    /// for how `schema` and the header interact.
+    #[must_use]
+    pub fn new(format: RowBinaryFormat, schema: Option<Schema>, options: ReaderOptions) -> Self {
+        Self {
+            format,
+            options,
+            pending_schema: schema,
+            state: None,
+            buffer: Vec::new(),
+            position: 0,
+            needed: 0,
+        }
+    }
+
+    /// Appends a chunk of input.
+    pub fn feed(&mut self, chunk: &[u8]) {
+        if self.position > 0 && self.position * 2 >= self.buffer.len() {
+            self.buffer.drain(..self.position);
+            self.position = 0;
+        }
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Decodes the next row from the buffered input.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the header or a row is malformed.
+    /// Running out of input is reported as [`PushDecoded::NeedMoreData`].
+    pub fn next_row(&mut self) -> Result<PushDecoded> {
+        if self.state.is_none() && !self.parse_header()? {
+            return Ok(PushDecoded::NeedMoreData);
+        }
+        let Some(state) = &self.state else {
+            return Err(Error::Internal("push decoder header state missing"));
+        };
+        let input = &self.buffer[self.position..];
+        if input.is_empty() || input.len() < self.needed {
+            return Ok(PushDecoded::NeedMoreData);
+        }
+        let schema = state.decode_schema.as_ref().unwrap_or(&state.schema);
+        let mut cursor = input;
+        let mut row = Vec::with_capacity(schema.len());
+        for field in schema.fields() {
+            let value = if self.format.has_default_flags() {
+                read_value_or_default_required(&field.ty, &mut cursor)
+            } else {
+                read_value_required(&field.ty, &mut cursor)
+            };
+            match value {
+                Ok(value) => row.push(value),
+                Err(err) => {
+                    let Some(missing) = missing_bytes(&err) else {
+                        return Err(err);
+                    };
+                    self.needed = needed_input(input.len(), missing)?;
+                    return Ok(PushDecoded::NeedMoreData);
+                }
+            }
+        }
+        self.position += input.len() - cursor.len();
+        self.needed = 0;
+        if let Some(positions) = &state.positions {
+            row = positions
+                .iter()
+                .map(|index| std::mem::replace(&mut row[*index], Value::Nothing))
+                .collect();
+        }
+        Ok(PushDecoded::Row(row))
+    }
+
+    /// Signals the end of input.
+    ///
+    /// # Errors
+    ///
+    /// Returns an unexpected-EOF [`Error::Io`] when buffered bytes do not
+    /// form a complete header or row.
+    pub fn finish(&self) -> Result<()> {
+        let header_pending = self.state.is_none() && self.format.has_header();
+        if header_pending || self.position < self.buffer.len() {
+            return Err(Error::Io(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "input ended inside a header or row",
+            )));
+        }
+        Ok(())
+    }
+
+    /// Returns the decoding schema once the header has been parsed.
+    #[must_use]
+    pub fn schema(&self) -> Option<&Schema> {
+        self.state.as_ref().map(|state| &state.schema)
+    }
+
+    /// Returns the parsed header, if present.
+    #[must_use]
+    pub fn header(&self) -> Option<&RowBinaryHeader> {
+        self.state.as_ref().and_then(|state| state.header.as_ref())
+    }
+
+    /// Parses the header if it has not been parsed yet, returning
+    /// `Ok(false)` when more input is needed.
+    #[cfg(feature = "async")]
+    pub(super) fn ensure_header(&mut self) -> Result<bool> {
+        if self.state.is_some() {
+            return Ok(true);
+        }
+        self.parse_header()
+    }
+
+    /// Parses the header, returning `Ok(false)` when more input is needed.
+    fn parse_header(&mut self) -> Result<bool> {
+        let input = &self.buffer[self.position..];
+        if input.len() < self.needed {
+            return Ok(false);
+        }
+        let mut cursor = input;
+        let parsed = read_header_from_reader(
+            &mut cursor,
+            self.format,
+            self.pending_schema.clone(),
+            &self.options,
+            std::convert::identity,
+        );
+        let (schema, header, positions) = match parsed {
+            Ok(parsed) => parsed,
+            Err(err) => {
+                let Some(missing) = missing_bytes(&err) else {
+                    return Err(err);
+                };
+                self.needed = needed_input(input.len(), missing)?;
+                return Ok(false);
+            }
+        };
+        if matches!(schema.fields()[0].ty, TypeDesc::Nothing) {
+            return Err(Error::UnsupportedCombination(
+                "RowBinary cannot stream Nothing as the leading column".into(),
+            ));
+        }
+        self.position = self.buffer.len() - cursor.len();
+        self.needed = 0;
+        self.pending_schema = None;
+        self.state = Some(DecodeState {
+            decode_schema: positions
+                .as_ref()
+                .map(|positions| header_ordered_schema(&schema, positions)),
+            schema,
+            positions,
+            header,
+        });
+        Ok(true)
+    }
+}
+
+/// Returns how much buffered input the next decode attempt needs.
+///
+/// Corrupt length prefixes can claim far more data than any real payload
+/// holds, so requests beyond the largest server block are rejected.
+fn needed_input(buffered: usize, missing: usize) -> Result<usize> {
+    buffered
+        .checked_add(missing)
+        .filter(|needed| *needed <= MAX_BLOCK_SIZE)
+        .ok_or(Error::Overflow("push decoder input exceeds 1 GiB"))
+}
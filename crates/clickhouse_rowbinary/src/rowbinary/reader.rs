@@ -2,28 +2,122 @@
 //! - `RowBinaryValueReader` decodes rows into `Value`s.
 //! - `RowBinaryReader` scans seekable streams and exposes raw row bytes.
 
-use std::io::{self, Read, Seek, SeekFrom};
+use std::{
+    io::{self, Read, Seek, SeekFrom},
+    iter::{FusedIterator, Take},
+};
 
 use zeekstd::{Decoder, Seekable};
 
 use crate::{
     error::{Error, Result},
-    io::{read_string, read_uvarint},
-    types::{TypeDesc, parse_type_desc},
+    io::{missing_bytes, read_string, read_uvarint},
+    typed::FromRow,
+    types::{DEFAULT_MAX_TYPE_DEPTH, TypeDesc, parse_type_desc_with_max_depth},
+    value::Value,
 };
 
 use super::{
+    codec::{ColumnCodec, ColumnCodecs},
+    columnar::DecodeColumn,
     format::RowBinaryFormat,
-    scan::{CaptureReader, skip_value_optional, skip_value_required},
+    scan::{
+        CaptureReader, CountingReader, skip_value_optional, skip_value_or_default_optional,
+        skip_value_or_default_required, skip_value_required,
+    },
     schema::{Field, Row, Schema},
-    value_rw::{read_value_optional, read_value_required},
+    type_binary::decode_type_binary_with_max_depth,
+    value_rw::{
+        read_value_optional, read_value_or_default_optional, read_value_or_default_required,
+        read_value_required,
+    },
 };
 
+/// Options controlling how [`RowBinaryValueReader`] parses its input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReaderOptions {
+    /// Maximum nesting depth accepted for header type names.
+    pub max_type_depth: usize,
+    /// How a supplied schema is reconciled with the payload header.
+    pub header_policy: HeaderPolicy,
+    /// Reports where a row failed instead of returning the bare error.
+    ///
+    /// A stream ending inside a row yields [`Error::Truncated`] and other
+    /// decoding failures [`Error::Corrupt`], both carrying the row, column
+    /// and byte offset. Trailing garbage after the last complete row
+    /// surfaces as one of these on the following row, so a complete
+    /// transfer is exactly one that ends with `Ok(None)`.
+    pub strict: bool,
+    /// Header types are binary type descriptors instead of type names, as
+    /// sent with the `output_format_binary_encode_types_in_binary_format`
+    /// server setting.
+    pub binary_types: bool,
+}
+
+impl Default for ReaderOptions {
+    fn default() -> Self {
+        Self {
+            max_type_depth: DEFAULT_MAX_TYPE_DEPTH,
+            header_policy: HeaderPolicy::default(),
+            strict: false,
+            binary_types: false,
+        }
+    }
+}
+
+/// Reconciliation of a supplied schema with a `RowBinaryWithNames` or
+/// `RowBinaryWithNamesAndTypes` header.
+///
+/// The policy only applies when a schema is supplied; without one the
+/// schema is built from the header.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum HeaderPolicy {
+    /// Column counts must match; `RowBinaryWithNames` headers must also
+    /// match the schema names.
+    #[default]
+    Lenient,
+    /// Header names must match the schema names in order.
+    MatchNames,
+    /// Header names must match, and so must header types when present.
+    MatchNamesAndTypes,
+    /// Header columns are matched to schema fields by name. Rows are
+    /// decoded in header order and returned in schema order; header types
+    /// are not checked.
+    Reorder,
+    /// The header is skipped without any checks.
+    Ignore,
+}
+
 /// `RowBinary` reader that streams rows from the provided reader.
+///
+/// Any [`Read`] source works, including files, sockets and decompressors,
+/// and rows are decoded without loading the whole payload into memory.
+/// Values are read in small pieces, so unbuffered sources should be
+/// wrapped in a [`std::io::BufReader`]. The reader never consumes bytes
+/// past the last row it returns.
 pub struct RowBinaryValueReader<R: Read> {
     inner: R,
     schema: Schema,
     header: Option<RowBinaryHeader>,
+    column_order: Option<ColumnOrder>,
+    codecs: ColumnCodecs,
+    strict: bool,
+    /// Values carry a "use default" flag byte (`RowBinaryWithDefaults`).
+    defaults: bool,
+    /// Bytes consumed from `inner`, including the header.
+    offset: u64,
+    /// Rows read or skipped so far.
+    rows_read: u64,
+}
+
+/// Decoding order for [`HeaderPolicy::Reorder`].
+struct ColumnOrder {
+    /// Schema fields in header order.
+    decode_schema: Schema,
+    /// Header index of each schema field.
+    positions: Vec<usize>,
+    /// Row decoded in header order.
+    scratch: Row,
 }
 
 impl<R: Read> RowBinaryValueReader<R> {
@@ -45,6 +139,66 @@ impl<R: Read> RowBinaryValueReader<R> {
         Self::with_schema_optional(inner, format, Some(schema))
     }
 
+    /// Creates a `RowBinaryWithNamesAndTypes` reader whose schema is taken
+    /// from the payload header.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub fn from_header(inner: R) -> Result<Self> {
+        Self::new(inner, RowBinaryFormat::RowBinaryWithNamesAndTypes)
+    }
+
+    /// Creates a reader with explicit [`ReaderOptions`].
+    ///
+    /// When `schema` is `None`, the header must carry the column types.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when header parsing fails.
+    pub fn with_options(
+        mut inner: R,
+        format: RowBinaryFormat,
+        schema: Option<Schema>,
+        options: &ReaderOptions,
+    ) -> Result<Self> {
+        let mut offset = 0;
+        let (schema, header, positions) = parse_header_from_reader(
+            &mut CountingReader::new(&mut inner, &mut offset),
+            format,
+            schema,
+            options,
+        )?;
+        let column_order = positions.map(|positions| ColumnOrder {
+            decode_schema: header_ordered_schema(&schema, &positions),
+            positions,
+            scratch: Vec::new(),
+        });
+        Ok(Self {
+            inner,
+            schema,
+            header,
+            column_order,
+            codecs: ColumnCodecs::default(),
+            strict: options.strict,
+            defaults: format.has_default_flags(),
+            offset,
+            rows_read: 0,
+        })
+    }
+
+    /// Registers `codec` to decode every value read from column `name`.
+    ///
+    /// [`Self::schema`] then reports the column with the codec type.
+    /// [`Self::read_columns`] and [`Self::skip_rows`] bypass codecs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when the schema has no such column.
+    pub fn set_column_codec(&mut self, name: &str, codec: ColumnCodec) -> Result<()> {
+        self.codecs.insert(&self.schema, name, codec)
+    }
+
     /// Reads the next row.
     ///
     /// # Errors
@@ -52,68 +206,193 @@ impl<R: Read> RowBinaryValueReader<R> {
     /// Returns [`crate::error::Error`] when decoding fails or the stream ends
     /// unexpectedly.
     pub fn read_row(&mut self) -> Result<Option<Row>> {
-        if self.schema.is_empty() {
-            return Ok(None);
-        }
-
-        if matches!(self.schema.fields()[0].ty, crate::types::TypeDesc::Nothing) {
-            return Err(Error::UnsupportedCombination(
-                "RowBinary cannot stream Nothing as the leading column".into(),
-            ));
-        }
         let mut row = Vec::with_capacity(self.schema.len());
-        for (index, field) in self.schema.fields().iter().enumerate() {
-            let value = if index == 0 {
-                match read_value_optional(&field.ty, &mut self.inner)? {
-                    Some(value) => value,
-                    None => return Ok(None),
-                }
-            } else {
-                read_value_required(&field.ty, &mut self.inner)?
-            };
-            row.push(value);
-        }
-        Ok(Some(row))
+        Ok(self.read_row_into(&mut row)?.then_some(row))
     }
 
     /// Reads the next row into the provided buffer.
     ///
-    /// Returns `Ok(true)` when a row was read, or `Ok(false)` on EOF.
+    /// The buffer is cleared and refilled, so its allocation is reused
+    /// across rows. Returns `Ok(true)` when a row was read, or `Ok(false)`
+    /// on EOF.
     ///
     /// # Errors
     ///
     /// Returns [`crate::error::Error`] when decoding fails or the stream ends
     /// unexpectedly.
     pub fn read_row_into(&mut self, row: &mut Row) -> Result<bool> {
-        if self.schema.is_empty() {
-            row.clear();
-            return Ok(false);
+        let position = self.strict.then_some(self.rows_read);
+        let mut reader = CountingReader::new(&mut self.inner, &mut self.offset);
+        let read = match &mut self.column_order {
+            None => read_row_values(&self.schema, &mut reader, row, position, self.defaults)?,
+            Some(order) => {
+                row.clear();
+                let read = read_row_values(
+                    &order.decode_schema,
+                    &mut reader,
+                    &mut order.scratch,
+                    position,
+                    self.defaults,
+                )?;
+                if read {
+                    row.extend(order.positions.iter().map(|index| {
+                        std::mem::replace(&mut order.scratch[*index], Value::Nothing)
+                    }));
+                }
+                read
+            }
+        };
+        if read {
+            self.rows_read += 1;
+            self.codecs.decode(row)?;
         }
+        Ok(read)
+    }
+
+    /// Reads the next row and decodes it with [`FromRow`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when decoding fails or the row cannot
+    /// be converted into `T`.
+    pub fn read_typed<T: FromRow>(&mut self) -> Result<Option<T>> {
+        let Some(row) = self.read_row()? else {
+            return Ok(None);
+        };
+        T::from_row(self.schema(), &row).map(Some)
+    }
 
-        if matches!(self.schema.fields()[0].ty, crate::types::TypeDesc::Nothing) {
+    /// Reads the next row and deserializes it into `T`; see
+    /// [`crate::from_row`] for how columns map onto `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when decoding fails or the row cannot
+    /// be deserialized into `T`.
+    #[cfg(feature = "serde")]
+    pub fn deserialize<T: serde::de::DeserializeOwned>(&mut self) -> Result<Option<T>> {
+        let Some(row) = self.read_row()? else {
+            return Ok(None);
+        };
+        crate::serde::from_row(self.schema(), &row).map(Some)
+    }
+
+    /// Decodes up to `max_rows` rows into per-column vectors, one entry per
+    /// schema field, and returns the number of rows read (`0` at EOF).
+    ///
+    /// Rows are appended to the existing contents of the columns. When a
+    /// row fails to decode, the columns are truncated back to the last
+    /// complete row before the error is returned.
+    ///
+    /// ```
+    /// # use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueReader, Schema};
+    /// let schema = Schema::from_type_strings(&[("id", "UInt32"), ("score", "Nullable(Float64)")])?;
+    /// let payload = [1, 0, 0, 0, 1, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xf8, 0x3f];
+    /// let mut reader =
+    ///     RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema)?;
+    /// let mut ids: Vec<u32> = Vec::new();
+    /// let mut scores: Vec<Option<f64>> = Vec::new();
+    /// assert_eq!(reader.read_columns(&mut [&mut ids, &mut scores], 1024)?, 2);
+    /// assert_eq!(ids, [1, 2]);
+    /// assert_eq!(scores, [None, Some(1.5)]);
+    /// # Ok::<(), clickhouse_rowbinary::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the column count or types do not
+    /// match the schema, decoding fails, or the stream ends inside a row.
+    /// `RowBinaryWithDefaults` payloads are not supported.
+    pub fn read_columns(
+        &mut self,
+        columns: &mut [&mut dyn DecodeColumn],
+        max_rows: usize,
+    ) -> Result<usize> {
+        if self.defaults {
             return Err(Error::UnsupportedCombination(
-                "RowBinary cannot stream Nothing as the leading column".into(),
+                "columnar reads do not support RowBinaryWithDefaults".into(),
             ));
         }
-        row.clear();
-        row.reserve(self.schema.len());
-        for (index, field) in self.schema.fields().iter().enumerate() {
-            let value = if index == 0 {
-                match read_value_optional(&field.ty, &mut self.inner)? {
-                    Some(value) => value,
-                    None => return Ok(false),
+        if columns.len() != self.schema.len() {
+            return Err(Error::InvalidValue("column count does not match schema"));
+        }
+        for (column, field) in columns.iter().zip(self.schema.fields()) {
+            column.check_type(&field.ty)?;
+        }
+        if self.schema.is_empty() {
+            return Ok(0);
+        }
+        let (decode_schema, targets) = match &self.column_order {
+            None => (&self.schema, (0..self.schema.len()).collect::<Vec<_>>()),
+            Some(order) => {
+                let mut targets = vec![0; order.positions.len()];
+                for (field, position) in order.positions.iter().enumerate() {
+                    targets[*position] = field;
+                }
+                (&order.decode_schema, targets)
+            }
+        };
+        let lengths: Vec<usize> = columns.iter().map(|column| column.len()).collect();
+        let mut reader = CountingReader::new(&mut self.inner, &mut self.offset);
+        let mut rows = 0;
+        while rows < max_rows {
+            let position = self.strict.then_some(self.rows_read);
+            match read_column_row(decode_schema, &targets, columns, &mut reader, position) {
+                Ok(true) => {
+                    rows += 1;
+                    self.rows_read += 1;
+                }
+                Ok(false) => break,
+                Err(err) => {
+                    for (column, len) in columns.iter_mut().zip(&lengths) {
+                        column.truncate(len + rows);
+                    }
+                    return Err(err);
                 }
-            } else {
-                read_value_required(&field.ty, &mut self.inner)?
-            };
-            row.push(value);
+            }
+        }
+        Ok(rows)
+    }
+
+    /// Skips up to `count` rows without decoding them into values.
+    ///
+    /// Returns the number of rows skipped, which is less than `count` only
+    /// when the stream ends first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the stream is malformed or ends
+    /// in the middle of a row.
+    pub fn skip_rows(&mut self, count: usize) -> Result<usize> {
+        let schema = match &self.column_order {
+            Some(order) => &order.decode_schema,
+            None => &self.schema,
+        };
+        let mut reader = CountingReader::new(&mut self.inner, &mut self.offset);
+        for skipped in 0..count {
+            if !skip_row_or_eof(schema, &mut reader, self.defaults)? {
+                return Ok(skipped);
+            }
+            self.rows_read += 1;
         }
-        Ok(true)
+        Ok(count)
+    }
+
+    /// Returns an iterator over at most `count` decoded rows; the rest of
+    /// the stream is left unread.
+    pub fn take_rows(self, count: usize) -> Take<RowBinaryRows<R>> {
+        self.rows().take(count)
     }
 
     /// Returns an iterator over decoded rows.
+    ///
+    /// The iterator ends at EOF. A decoding error is yielded once, after
+    /// which the iterator is exhausted.
     pub fn rows(self) -> RowBinaryRows<R> {
-        RowBinaryRows { reader: self }
+        RowBinaryRows {
+            reader: self,
+            done: false,
+        }
     }
 
     /// Returns the parsed header, if present.
@@ -122,37 +401,160 @@ impl<R: Read> RowBinaryValueReader<R> {
         self.header.as_ref()
     }
 
+    /// Returns a reference to the underlying reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the underlying reader.
+    ///
+    /// Reading from it directly desynchronizes the row stream.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the underlying reader, positioned
+    /// right after the last row that was read.
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Returns the schema used for decoding, either the one supplied or the
+    /// one built from the header, with columns that have a codec retyped to
+    /// the codec type.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        self.codecs.schema(&self.schema)
+    }
+
     fn with_schema_optional(
-        mut inner: R,
+        inner: R,
         format: RowBinaryFormat,
         schema: Option<Schema>,
     ) -> Result<Self> {
-        let (schema, header) = parse_header_from_reader(&mut inner, format, schema)?;
-        Ok(Self {
-            inner,
-            schema,
-            header,
-        })
+        Self::with_options(inner, format, schema, &ReaderOptions::default())
     }
 }
 
+/// Decodes one row into `row`, returning `Ok(false)` on a clean EOF.
+///
+/// `position` is the row index in strict mode; errors are then located with
+/// [`Error::Truncated`] or [`Error::Corrupt`]. `defaults` selects the
+/// `RowBinaryWithDefaults` value layout.
+fn read_row_values<R: Read>(
+    schema: &Schema,
+    reader: &mut CountingReader<'_, R>,
+    row: &mut Row,
+    position: Option<u64>,
+    defaults: bool,
+) -> Result<bool> {
+    row.clear();
+    if schema.is_empty() {
+        return Ok(false);
+    }
+
+    if matches!(schema.fields()[0].ty, TypeDesc::Nothing) {
+        return Err(Error::UnsupportedCombination(
+            "RowBinary cannot stream Nothing as the leading column".into(),
+        ));
+    }
+    row.reserve(schema.len());
+    for (index, field) in schema.fields().iter().enumerate() {
+        let offset = reader.count();
+        let result = match (index == 0, defaults) {
+            (true, false) => read_value_optional(&field.ty, reader),
+            (false, false) => read_value_required(&field.ty, reader).map(Some),
+            (true, true) => read_value_or_default_optional(&field.ty, reader),
+            (false, true) => read_value_or_default_required(&field.ty, reader).map(Some),
+        };
+        match result {
+            Ok(Some(value)) => row.push(value),
+            Ok(None) => return Ok(false),
+            Err(err) => return Err(locate_error(err, position, index, offset)),
+        }
+    }
+    Ok(true)
+}
+
+/// Wraps a decoding error with its location when `row` is known.
+fn locate_error(err: Error, row: Option<u64>, column: usize, offset: u64) -> Error {
+    let Some(row) = row else {
+        return err;
+    };
+    match err {
+        Error::Io(io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof => Error::Truncated {
+            row,
+            column,
+            offset,
+        },
+        source => Error::Corrupt {
+            row,
+            column,
+            offset,
+            source: Box::new(source),
+        },
+    }
+}
+
+/// Decodes one row into `columns`, returning `Ok(false)` at EOF.
+///
+/// `targets[i]` is the column receiving the `i`-th field of `schema`.
+fn read_column_row<R: Read>(
+    schema: &Schema,
+    targets: &[usize],
+    columns: &mut [&mut dyn DecodeColumn],
+    reader: &mut CountingReader<'_, R>,
+    position: Option<u64>,
+) -> Result<bool> {
+    let start = reader.count();
+    let mut first = [0_u8; 1];
+    if reader.read(&mut first)? == 0 {
+        return Ok(false);
+    }
+    let mut input = first.as_slice().chain(reader);
+    for (index, field) in schema.fields().iter().enumerate() {
+        let offset = if index == 0 {
+            start
+        } else {
+            input.get_ref().1.count()
+        };
+        columns[targets[index]]
+            .read_value(&field.ty, &mut input)
+            .map_err(|err| locate_error(err, position, index, offset))?;
+    }
+    Ok(true)
+}
+
 /// Iterator over `RowBinary` rows.
 pub struct RowBinaryRows<R: Read> {
     reader: RowBinaryValueReader<R>,
+    done: bool,
 }
 
 impl<R: Read> Iterator for RowBinaryRows<R> {
     type Item = Result<Row>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
         match self.reader.read_row() {
             Ok(Some(row)) => Some(Ok(row)),
-            Ok(None) => None,
-            Err(err) => Some(Err(err)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(err) => {
+                self.done = true;
+                Some(Err(err))
+            }
         }
     }
 }
 
+impl<R: Read> FusedIterator for RowBinaryRows<R> {}
+
 /// Header metadata for `RowBinary` formats with names and/or types.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RowBinaryHeader {
@@ -168,21 +570,46 @@ fn parse_header<S: Seekable>(
     schema: Option<Schema>,
 ) -> Result<(Schema, Option<RowBinaryHeader>, u64)> {
     decoder.seek(SeekFrom::Start(0))?;
-    let (schema, header) = parse_header_from_reader(decoder, format, schema)?;
+    let (schema, header, _) =
+        parse_header_from_reader(decoder, format, schema, &ReaderOptions::default())?;
     let offset = decoder.offset();
     Ok((schema, header, offset))
 }
 
-fn parse_header_from_reader<R: Read + ?Sized>(
+pub(super) fn parse_header_from_reader<R: Read + ?Sized>(
+    reader: &mut R,
+    format: RowBinaryFormat,
+    schema: Option<Schema>,
+    options: &ReaderOptions,
+) -> Result<(Schema, Option<RowBinaryHeader>, Option<Vec<usize>>)> {
+    read_header_from_reader(reader, format, schema, options, |_| {
+        Error::InvalidValue("missing header")
+    })
+}
+
+/// Parses the header like [`parse_header_from_reader`], passing the
+/// unexpected-EOF [`Error::Io`] of input that ends inside the column names
+/// or type names through `truncated`, so that a push decoder can keep it
+/// and wait for more input.
+pub(super) fn read_header_from_reader<R: Read + ?Sized>(
     reader: &mut R,
     format: RowBinaryFormat,
     schema: Option<Schema>,
-) -> Result<(Schema, Option<RowBinaryHeader>)> {
+    options: &ReaderOptions,
+    truncated: fn(Error) -> Error,
+) -> Result<(Schema, Option<RowBinaryHeader>, Option<Vec<usize>>)> {
+    let cut = |err: Error| {
+        if missing_bytes(&err).is_some() {
+            truncated(err)
+        } else {
+            err
+        }
+    };
     let has_schema = schema.is_some();
     let mut schema = schema.unwrap_or_else(|| Schema::new(Vec::new()));
 
     match format {
-        RowBinaryFormat::RowBinary => {
+        RowBinaryFormat::RowBinary | RowBinaryFormat::RowBinaryWithDefaults => {
             if !has_schema {
                 return Err(Error::InvalidValue("schema required for RowBinary reader"));
             }
@@ -191,17 +618,12 @@ fn parse_header_from_reader<R: Read + ?Sized>(
                     "schema must contain at least one column",
                 ));
             }
-            return Ok((schema, None));
+            return Ok((schema, None, None));
         }
         RowBinaryFormat::RowBinaryWithNames | RowBinaryFormat::RowBinaryWithNamesAndTypes => {}
     }
 
-    let column_count = read_uvarint(reader)?.ok_or_else(|| {
-        Error::Io(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "missing header",
-        ))
-    })?;
+    let column_count = read_uvarint(reader)?.ok_or_else(missing_header)?;
     let column_count = usize::try_from(column_count)
         .map_err(|_| Error::Overflow("header column count too large"))?;
     if column_count == 0 {
@@ -210,48 +632,44 @@ fn parse_header_from_reader<R: Read + ?Sized>(
 
     let mut names = Vec::with_capacity(column_count);
     for _ in 0..column_count {
-        let name = match read_string(reader) {
-            Ok(Some(value)) => value,
-            Ok(None) => return Err(Error::InvalidValue("missing header")),
-            Err(Error::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
-                return Err(Error::InvalidValue("missing header"));
-            }
-            Err(err) => return Err(err),
-        };
+        let name = read_string(reader)
+            .and_then(|name| name.ok_or_else(missing_header))
+            .map_err(cut)?;
         names.push(name);
     }
 
     let types = if format == RowBinaryFormat::RowBinaryWithNamesAndTypes {
         let mut types = Vec::with_capacity(column_count);
         for _ in 0..column_count {
-            let type_name = match read_string(reader) {
-                Ok(Some(value)) => value,
-                Ok(None) => return Err(Error::InvalidValue("missing header")),
-                Err(Error::Io(err)) if err.kind() == io::ErrorKind::UnexpectedEof => {
-                    return Err(Error::InvalidValue("missing header"));
-                }
-                Err(err) => return Err(err),
-            };
-            types.push(parse_type_desc(&type_name)?);
+            if options.binary_types {
+                types.push(
+                    decode_type_binary_with_max_depth(reader, options.max_type_depth)
+                        .map_err(cut)?,
+                );
+                continue;
+            }
+            let type_name = read_string(reader)
+                .and_then(|name| name.ok_or_else(missing_header))
+                .map_err(cut)?;
+            types.push(parse_type_desc_with_max_depth(
+                &type_name,
+                options.max_type_depth,
+            )?);
         }
         Some(types)
     } else {
         None
     };
 
+    let mut positions = None;
     if has_schema {
-        if schema.len() != names.len() {
-            return Err(Error::InvalidValue("header column count mismatch"));
-        }
-        if format == RowBinaryFormat::RowBinaryWithNames
-            && schema
-                .fields()
-                .iter()
-                .map(|field| field.name.as_str())
-                .ne(names.iter().map(String::as_str))
-        {
-            return Err(Error::InvalidValue("header column names mismatch"));
-        }
+        positions = check_header(
+            &schema,
+            format,
+            &names,
+            types.as_deref(),
+            options.header_policy,
+        )?;
     } else if let Some(types) = types.clone() {
         schema = Schema::new(
             names
@@ -274,7 +692,79 @@ fn parse_header_from_reader<R: Read + ?Sized>(
     }
 
     let header = Some(RowBinaryHeader { names, types });
-    Ok((schema, header))
+    Ok((schema, header, positions))
+}
+
+/// Returns the unexpected-EOF error for input that ends inside the header.
+fn missing_header() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "missing header",
+    ))
+}
+
+/// Returns the fields of `schema` in header order, given the header index
+/// of each schema field.
+pub(super) fn header_ordered_schema(schema: &Schema, positions: &[usize]) -> Schema {
+    let mut fields = schema.fields().to_vec();
+    for (field_index, header_index) in positions.iter().enumerate() {
+        fields[*header_index] = schema.fields()[field_index].clone();
+    }
+    Schema::new(fields)
+}
+
+/// Applies `policy` to a supplied schema, returning the header index of each
+/// schema field when columns are reordered.
+fn check_header(
+    schema: &Schema,
+    format: RowBinaryFormat,
+    names: &[String],
+    types: Option<&[TypeDesc]>,
+    policy: HeaderPolicy,
+) -> Result<Option<Vec<usize>>> {
+    if policy == HeaderPolicy::Ignore {
+        return Ok(None);
+    }
+    if schema.len() != names.len() {
+        return Err(Error::InvalidValue("header column count mismatch"));
+    }
+    if policy == HeaderPolicy::Reorder {
+        let mut used = vec![false; names.len()];
+        let mut positions = Vec::with_capacity(names.len());
+        for field in schema.fields() {
+            let index = names
+                .iter()
+                .enumerate()
+                .position(|(index, name)| !used[index] && *name == field.name)
+                .ok_or(Error::InvalidValue("header column names mismatch"))?;
+            used[index] = true;
+            positions.push(index);
+        }
+        return Ok(Some(positions));
+    }
+
+    let check_names =
+        policy != HeaderPolicy::Lenient || format == RowBinaryFormat::RowBinaryWithNames;
+    if check_names
+        && schema
+            .fields()
+            .iter()
+            .map(|field| field.name.as_str())
+            .ne(names.iter().map(String::as_str))
+    {
+        return Err(Error::InvalidValue("header column names mismatch"));
+    }
+    if let (HeaderPolicy::MatchNamesAndTypes, Some(types)) = (policy, types) {
+        for (field, ty) in schema.fields().iter().zip(types) {
+            if field.ty != *ty {
+                return Err(Error::TypeMismatch {
+                    expected: field.ty.type_name(),
+                    actual: ty.type_name(),
+                });
+            }
+        }
+    }
+    Ok(None)
 }
 
 /// Seekable Zstd reader for `RowBinary` payloads.
@@ -293,6 +783,8 @@ pub struct RowBinaryReader<S: Seekable> {
     current_row: usize,
     /// Buffer holding the current row bytes (empty when unloaded).
     row_buf: Vec<u8>,
+    /// Values carry a "use default" flag byte (`RowBinaryWithDefaults`).
+    defaults: bool,
 }
 
 const DEFAULT_ROW_OFFSET_STRIDE: usize = 1024;
@@ -334,9 +826,15 @@ impl<S: Seekable> RowBinaryReader<S> {
             row_offsets: Vec::new(),
             current_row: 0,
             row_buf: Vec::new(),
+            defaults: format.has_default_flags(),
         };
         reader.row_offsets.push(data_start_offset);
-        let maybe_len = read_row_bytes(&reader.schema, &mut reader.decoder, &mut reader.row_buf)?;
+        let maybe_len = read_row_bytes(
+            &reader.schema,
+            &mut reader.decoder,
+            &mut reader.row_buf,
+            reader.defaults,
+        )?;
         if maybe_len.is_none() {
             reader.row_buf.clear();
         } else {
@@ -351,6 +849,13 @@ impl<S: Seekable> RowBinaryReader<S> {
         self.header.as_ref()
     }
 
+    /// Returns the schema used for decoding, either the one supplied or the
+    /// one built from the header.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
     /// Seeks to a specific row index.
     ///
     /// # Errors
@@ -404,7 +909,7 @@ impl<S: Seekable> RowBinaryReader<S> {
         self.decoder.seek(SeekFrom::Start(offset))?;
         while self.row_offsets.len() <= block {
             for _ in 0..self.row_stride {
-                if let Err(err) = skip_row(&self.schema, &mut self.decoder) {
+                if let Err(err) = skip_row(&self.schema, &mut self.decoder, self.defaults) {
                     if matches!(
                         err,
                         Error::Io(ref io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof
@@ -429,7 +934,7 @@ impl<S: Seekable> RowBinaryReader<S> {
         if index > start {
             let mut row = start;
             while row < index {
-                if let Err(err) = skip_row(&self.schema, &mut self.decoder) {
+                if let Err(err) = skip_row(&self.schema, &mut self.decoder, self.defaults) {
                     if matches!(
                         err,
                         Error::Io(ref io_err) if io_err.kind() == io::ErrorKind::UnexpectedEof
@@ -445,7 +950,12 @@ impl<S: Seekable> RowBinaryReader<S> {
     }
 
     fn load_current_row(&mut self) -> Result<()> {
-        let maybe_len = read_row_bytes(&self.schema, &mut self.decoder, &mut self.row_buf)?;
+        let maybe_len = read_row_bytes(
+            &self.schema,
+            &mut self.decoder,
+            &mut self.row_buf,
+            self.defaults,
+        )?;
         if maybe_len.is_none() {
             self.row_buf.clear();
             return Err(Error::InvalidValue("row index out of range"));
@@ -469,6 +979,7 @@ fn read_row_bytes<R: Read + ?Sized>(
     schema: &Schema,
     reader: &mut R,
     buf: &mut Vec<u8>,
+    defaults: bool,
 ) -> Result<Option<usize>> {
     if matches!(schema.fields()[0].ty, TypeDesc::Nothing) {
         return Err(Error::UnsupportedCombination(
@@ -481,33 +992,63 @@ fn read_row_bytes<R: Read + ?Sized>(
     let Some(first) = iter.next() else {
         return Ok(None);
     };
-    if let Some(()) = skip_value_optional(&first.ty, &mut capture)? {
+    if let Some(()) = skip_field(&first.ty, &mut capture, true, defaults)? {
     } else {
         buf.clear();
         return Ok(None);
     }
     for field in iter {
-        skip_value_required(&field.ty, &mut capture)?;
+        skip_field(&field.ty, &mut capture, false, defaults)?;
     }
     Ok(Some(buf.len()))
 }
 
-fn skip_row<R: Read + ?Sized>(schema: &Schema, reader: &mut R) -> Result<()> {
-    if schema.is_empty() {
+fn skip_row<R: Read + ?Sized>(schema: &Schema, reader: &mut R, defaults: bool) -> Result<()> {
+    if schema.is_empty() || skip_row_or_eof(schema, reader, defaults)? {
         return Ok(());
     }
+    Err(Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "unexpected EOF while reading row",
+    )))
+}
+
+/// Skips one row, returning `Ok(false)` on a clean EOF before the row.
+fn skip_row_or_eof<R: Read + ?Sized>(
+    schema: &Schema,
+    reader: &mut R,
+    defaults: bool,
+) -> Result<bool> {
     let mut iter = schema.fields().iter();
     let Some(first) = iter.next() else {
-        return Ok(());
+        return Ok(false);
     };
-    let Some(()) = skip_value_optional(&first.ty, reader)? else {
-        return Err(Error::Io(io::Error::new(
-            io::ErrorKind::UnexpectedEof,
-            "unexpected EOF while reading row",
-        )));
+    if matches!(first.ty, TypeDesc::Nothing) {
+        return Err(Error::UnsupportedCombination(
+            "RowBinary cannot stream Nothing as the leading column".into(),
+        ));
+    }
+    let Some(()) = skip_field(&first.ty, reader, true, defaults)? else {
+        return Ok(false);
     };
     for field in iter {
-        skip_value_required(&field.ty, reader)?;
+        skip_field(&field.ty, reader, false, defaults)?;
+    }
+    Ok(true)
+}
+
+/// Skips one field of a row; only the first field of a row (`first`) may
+/// hit a clean EOF, reported as `Ok(None)`.
+fn skip_field<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    reader: &mut R,
+    first: bool,
+    defaults: bool,
+) -> Result<Option<()>> {
+    match (first, defaults) {
+        (true, false) => skip_value_optional(ty, reader),
+        (false, false) => skip_value_required(ty, reader).map(Some),
+        (true, true) => skip_value_or_default_optional(ty, reader),
+        (false, true) => skip_value_or_default_required(ty, reader).map(Some),
     }
-    Ok(())
 }
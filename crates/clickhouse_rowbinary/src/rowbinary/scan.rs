@@ -4,11 +4,14 @@ use std::io::{self, Read};
 
 use crate::{
     error::{Error, Result},
-    io::{read_string, read_uvarint},
+    io::{read_string, read_uvarint, truncated},
     types::{DecimalSize, TupleItem, TypeDesc},
 };
 
-use super::type_binary::decode_type_binary_from_tag;
+use super::{
+    aggregate::skip_aggregate_state, type_binary::decode_type_binary_from_tag,
+    value_rw::USE_DEFAULT,
+};
 
 const DISCARD_CHUNK: usize = 8 * 1024;
 
@@ -34,6 +37,30 @@ impl<R: Read + ?Sized> Read for CaptureReader<'_, R> {
     }
 }
 
+/// Reader wrapper that counts the bytes read.
+pub(crate) struct CountingReader<'a, R: ?Sized> {
+    reader: &'a mut R,
+    count: &'a mut u64,
+}
+
+impl<'a, R: ?Sized> CountingReader<'a, R> {
+    pub(crate) fn new(reader: &'a mut R, count: &'a mut u64) -> Self {
+        Self { reader, count }
+    }
+
+    pub(crate) fn count(&self) -> u64 {
+        *self.count
+    }
+}
+
+impl<R: Read + ?Sized> Read for CountingReader<'_, R> {
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let n = self.reader.read(out)?;
+        *self.count += n as u64;
+        Ok(n)
+    }
+}
+
 #[allow(clippy::too_many_lines)]
 pub(crate) fn skip_value_optional<R: Read + ?Sized>(
     ty: &TypeDesc,
@@ -70,7 +97,9 @@ pub(crate) fn skip_value_optional<R: Read + ?Sized>(
                 _ => Err(Error::InvalidValue("invalid nullable flag")),
             }
         }
-        TypeDesc::LowCardinality(inner) => skip_value_optional(inner, reader),
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
+            skip_value_optional(inner, reader)
+        }
         TypeDesc::Array(inner) => {
             let Some(len) = read_uvarint(reader)? else {
                 return Ok(None);
@@ -150,6 +179,11 @@ pub(crate) fn skip_value_optional<R: Read + ?Sized>(
             let Some(ty) = ty else {
                 return Ok(Some(()));
             };
+            if matches!(ty, TypeDesc::Dynamic { .. }) {
+                return Err(Error::UnsupportedType(
+                    "Dynamic value of type Dynamic".into(),
+                ));
+            }
             skip_value_required(&ty, reader)?;
             Ok(Some(()))
         }
@@ -162,6 +196,21 @@ pub(crate) fn skip_value_optional<R: Read + ?Sized>(
             };
             read_fixed(reader, len)
         }
+        TypeDesc::Point
+        | TypeDesc::Ring
+        | TypeDesc::LineString
+        | TypeDesc::MultiLineString
+        | TypeDesc::Polygon
+        | TypeDesc::MultiPolygon => {
+            let storage = ty
+                .geo_storage()
+                .ok_or(Error::Internal("geo type without storage type"))?;
+            skip_value_optional(storage, reader)
+        }
+        TypeDesc::AggregateFunction {
+            function,
+            arguments,
+        } => skip_aggregate_state(function, arguments, reader),
         TypeDesc::Nothing => Ok(Some(())),
         _ => Err(Error::Internal("unsupported skip type")),
     }
@@ -177,6 +226,41 @@ pub(crate) fn skip_value_required<R: Read + ?Sized>(ty: &TypeDesc, reader: &mut
     }
 }
 
+/// Skips a `RowBinaryWithDefaults` value and its leading flag byte,
+/// returning `Ok(None)` on a clean EOF before the flag.
+pub(crate) fn skip_value_or_default_optional<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    reader: &mut R,
+) -> Result<Option<()>> {
+    if matches!(ty, TypeDesc::Nested(_)) {
+        return Err(Error::UnsupportedCombination(
+            "RowBinaryWithDefaults does not support Nested columns".into(),
+        ));
+    }
+    let mut flag = [0_u8; 1];
+    if read_exact_or_eof(reader, &mut flag)? {
+        return Ok(None);
+    }
+    match flag[0] {
+        0 => skip_value_required(ty, reader).map(Some),
+        USE_DEFAULT => Ok(Some(())),
+        _ => Err(Error::InvalidValue("invalid default flag")),
+    }
+}
+
+pub(crate) fn skip_value_or_default_required<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    reader: &mut R,
+) -> Result<()> {
+    match skip_value_or_default_optional(ty, reader)? {
+        Some(()) => Ok(()),
+        None => Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected EOF while reading row",
+        ))),
+    }
+}
+
 fn skip_tuple<R: Read + ?Sized>(items: &[TupleItem], reader: &mut R) -> Result<Option<()>> {
     let mut iter = items.iter();
     let Some(first) = iter.next() else {
@@ -201,7 +285,7 @@ fn skip_tuple_required<R: Read + ?Sized>(items: &[TupleItem], reader: &mut R) ->
     }
 }
 
-fn skip_bytes<R: Read + ?Sized>(reader: &mut R) -> Result<Option<()>> {
+pub(crate) fn skip_bytes<R: Read + ?Sized>(reader: &mut R) -> Result<Option<()>> {
     let Some(len) = read_uvarint(reader)? else {
         return Ok(None);
     };
@@ -210,7 +294,7 @@ fn skip_bytes<R: Read + ?Sized>(reader: &mut R) -> Result<Option<()>> {
     Ok(Some(()))
 }
 
-fn read_fixed<R: Read + ?Sized>(reader: &mut R, len: usize) -> Result<Option<()>> {
+pub(crate) fn read_fixed<R: Read + ?Sized>(reader: &mut R, len: usize) -> Result<Option<()>> {
     let mut buf = vec![0_u8; len];
     if read_exact_or_eof(reader, &mut buf)? {
         return Ok(None);
@@ -238,17 +322,21 @@ fn read_exact_or_eof<R: Read + ?Sized>(reader: &mut R, buf: &mut [u8]) -> Result
     }
 }
 
-fn discard_exact<R: Read + ?Sized>(reader: &mut R, mut len: usize) -> Result<()> {
+pub(crate) fn discard_exact<R: Read + ?Sized>(reader: &mut R, mut len: usize) -> Result<()> {
     let mut buf = [0_u8; DISCARD_CHUNK];
     while len > 0 {
         let take = len.min(buf.len());
-        reader.read_exact(&mut buf[..take])?;
-        len -= take;
+        match reader.read(&mut buf[..take]) {
+            Ok(0) => return Err(truncated(len)),
+            Ok(read) => len -= read,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err(err.into()),
+        }
     }
     Ok(())
 }
 
-fn fixed_len_for_type(ty: &TypeDesc) -> Option<usize> {
+pub(crate) fn fixed_len_for_type(ty: &TypeDesc) -> Option<usize> {
     match ty {
         TypeDesc::UInt8 | TypeDesc::Bool | TypeDesc::Int8 | TypeDesc::Enum8(_) => Some(1),
         TypeDesc::UInt16
@@ -268,7 +356,8 @@ fn fixed_len_for_type(ty: &TypeDesc) -> Option<usize> {
         | TypeDesc::Int64
         | TypeDesc::Float64
         | TypeDesc::DateTime64 { .. }
-        | TypeDesc::Decimal64 { .. } => Some(8),
+        | TypeDesc::Decimal64 { .. }
+        | TypeDesc::Interval(_) => Some(8),
         TypeDesc::UInt128
         | TypeDesc::Int128
         | TypeDesc::Decimal128 { .. }
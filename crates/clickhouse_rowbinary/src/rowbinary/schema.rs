@@ -1,11 +1,16 @@
 //! Schema definitions for `RowBinary` encoding.
 
+use std::collections::HashSet;
+
 use crate::{
     error::{Error, Result},
+    typed::ClickhouseRow,
     types::{TypeDesc, parse_type_desc},
     value::Value,
 };
 
+use super::value_rw::{encoded_nested_len, encoded_value_len};
+
 /// Column descriptor used by `RowBinary` readers and writers.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Field {
@@ -46,6 +51,41 @@ impl Schema {
         self.fields.is_empty()
     }
 
+    /// Returns the index of the column named `name`.
+    #[must_use]
+    pub fn index_of(&self, name: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.name == name)
+    }
+
+    /// Returns the schema the typed row `T` maps onto, one column per field.
+    ///
+    /// With `#[derive(ClickhouseRow)]` the column names and types come from
+    /// the struct definition; see [`crate::ClickhouseRow::schema`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a declared column type does not
+    /// parse.
+    pub fn of<T: ClickhouseRow>() -> Result<Self> {
+        T::schema()
+    }
+
+    /// Returns the column list of a `CREATE TABLE` statement for this
+    /// schema, such as ``"`id` UInt64, `name` String"``.
+    ///
+    /// Column names are quoted with backticks.
+    #[must_use]
+    pub fn column_definitions(&self) -> String {
+        self.fields
+            .iter()
+            .map(|field| {
+                let name = field.name.replace('\\', "\\\\").replace('`', "\\`");
+                format!("`{name}` {}", field.ty.type_name())
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
     /// Creates a schema from name/type pairs.
     pub fn from_names_and_types<I, S>(pairs: I) -> Self
     where
@@ -64,6 +104,11 @@ impl Schema {
 
     /// Parses a schema from name/type strings.
     ///
+    /// Column clauses copied from `DESCRIBE` or `SHOW CREATE TABLE` output
+    /// (`DEFAULT`, `MATERIALIZED`, `EPHEMERAL`, `ALIAS`, `CODEC`, `TTL`,
+    /// `COMMENT`) are ignored, so `"String DEFAULT 'x' CODEC(ZSTD(1))"`
+    /// parses as `String`.
+    ///
     /// # Errors
     ///
     /// Returns [`crate::error::Error`] when parsing type names fails.
@@ -72,16 +117,130 @@ impl Schema {
         for (name, ty) in pairs {
             fields.push(Field {
                 name: (*name).to_string(),
-                ty: parse_type_desc(ty)?,
+                ty: parse_type_desc(strip_column_clauses(ty))?,
             });
         }
         Ok(Self { fields })
     }
+
+    /// Expands `Nested(...)` columns into their parallel `Array` columns.
+    ///
+    /// A column `n Nested(a UInt32, b String)` becomes `n.a Array(UInt32)`
+    /// and `n.b Array(String)`, matching how `ClickHouse` exposes nested
+    /// columns with `flatten_nested = 1`. Other columns are kept as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when a nested element is unnamed.
+    pub fn flatten_nested(&self) -> Result<Self> {
+        ensure_nested_names(self)?;
+        Ok(expand_schema_for_writing(self))
+    }
+
+    /// Pairs the values of `row` with their column names, in column order.
+    ///
+    /// See [`crate::RowView::as_map`] for a borrowed view.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the row length does not match
+    /// the schema or two columns share a name, which results may have
+    /// but a by-name view cannot hold.
+    pub fn named_row(&self, row: Row) -> Result<Vec<(String, Value)>> {
+        if row.len() != self.fields.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.ensure_unique_names()?;
+        Ok(self
+            .fields
+            .iter()
+            .map(|field| field.name.clone())
+            .zip(row)
+            .collect())
+    }
+
+    /// Checks that no two columns share a name.
+    pub(crate) fn ensure_unique_names(&self) -> Result<()> {
+        let mut names = HashSet::with_capacity(self.fields.len());
+        if self.fields.iter().all(|field| names.insert(&field.name)) {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue("schema has duplicate column names"))
+        }
+    }
+
+    /// Returns the number of bytes `row` occupies when written with this
+    /// schema, excluding any header.
+    ///
+    /// See [`Value::encoded_size`] for how values are sized.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length does not match
+    /// the schema or a value cannot be encoded as its column type.
+    pub fn encoded_size(&self, row: &[Value]) -> Result<usize> {
+        if row.len() != self.fields.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        let mut len = 0;
+        for (field, value) in self.fields.iter().zip(row) {
+            len += match &field.ty {
+                TypeDesc::Nested(items) => encoded_nested_len(items, value)?,
+                ty => encoded_value_len(ty, value)?,
+            };
+        }
+        Ok(len)
+    }
 }
 
 /// A single `RowBinary` row.
 pub type Row = Vec<Value>;
 
+const COLUMN_CLAUSE_KEYWORDS: &[&str] = &[
+    "DEFAULT",
+    "MATERIALIZED",
+    "EPHEMERAL",
+    "ALIAS",
+    "CODEC",
+    "TTL",
+    "COMMENT",
+];
+
+/// Cuts a column definition at the first top-level DDL clause keyword.
+fn strip_column_clauses(input: &str) -> &str {
+    let bytes = input.as_bytes();
+    let mut depth = 0_usize;
+    let mut in_quote = false;
+    let mut escape = false;
+    for (idx, &byte) in bytes.iter().enumerate() {
+        if escape {
+            escape = false;
+            continue;
+        }
+        match byte {
+            b'\\' if in_quote => escape = true,
+            b'\'' => in_quote = !in_quote,
+            b'(' if !in_quote => depth += 1,
+            b')' if !in_quote => depth = depth.saturating_sub(1),
+            byte if byte.is_ascii_whitespace() && !in_quote && depth == 0 => {
+                let rest = &input[idx + 1..];
+                let word_len = rest
+                    .find(|ch: char| !ch.is_ascii_alphabetic())
+                    .unwrap_or(rest.len());
+                let word = &rest[..word_len];
+                if COLUMN_CLAUSE_KEYWORDS
+                    .iter()
+                    .any(|keyword| keyword.eq_ignore_ascii_case(word))
+                {
+                    return input[..idx].trim_end();
+                }
+            }
+            _ => {}
+        }
+    }
+    input
+}
+
 pub(crate) fn expand_schema_for_writing(schema: &Schema) -> Schema {
     let mut fields = Vec::new();
     for field in &schema.fields {
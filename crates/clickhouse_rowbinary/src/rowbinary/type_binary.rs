@@ -1,11 +1,15 @@
-//! Binary encoding/decoding of `ClickHouse` types (used by Dynamic values).
+//! Binary encoding/decoding of `ClickHouse` types (used by Dynamic values and
+//! binary-encoded `RowBinaryWithNamesAndTypes` headers).
 
 use std::io::{self, Read, Write};
 
 use crate::{
     error::{Error, Result},
     io::{read_string, read_uvarint, write_string, write_uvarint},
-    types::{DecimalSize, TupleItem, TypeDesc, can_be_inside_low_cardinality, is_valid_map_key},
+    types::{
+        DATETIME64_MAX_PRECISION, DEFAULT_MAX_TYPE_DEPTH, DecimalSize, IntervalKind, TupleItem,
+        TypeDesc, can_be_inside_low_cardinality, can_be_inside_nullable, is_valid_map_key,
+    },
 };
 
 const MAX_TYPE_ITEMS: usize = 1_000_000;
@@ -17,6 +21,8 @@ const DECIMAL256_MAX_PRECISION: u8 = 76;
 const JSON_SERIALIZATION_VERSION: u8 = 0;
 const JSON_MAX_TYPED_PATHS: usize = 1000;
 const JSON_MAX_DYNAMIC_PATHS_LIMIT: usize = 10000;
+/// `max_types` of a `Dynamic` type declared without parameters.
+const DYNAMIC_DEFAULT_MAX_TYPES: u8 = 32;
 
 #[repr(u8)]
 enum BinaryTypeIndex {
@@ -53,14 +59,18 @@ enum BinaryTypeIndex {
     Array = 0x1E,
     UnnamedTuple = 0x1F,
     NamedTuple = 0x20,
+    Interval = 0x22,
     Nullable = 0x23,
+    AggregateFunction = 0x25,
     LowCardinality = 0x26,
     Map = 0x27,
     Ipv4 = 0x28,
     Ipv6 = 0x29,
     Variant = 0x2A,
     Dynamic = 0x2B,
+    Custom = 0x2C,
     Bool = 0x2D,
+    SimpleAggregateFunction = 0x2E,
     Nested = 0x2F,
     Json = 0x30,
     BFloat16 = 0x31,
@@ -69,7 +79,7 @@ enum BinaryTypeIndex {
 #[allow(clippy::too_many_lines)]
 pub(crate) fn encode_type_binary<W: Write + ?Sized>(ty: &TypeDesc, writer: &mut W) -> Result<()> {
     match ty {
-        TypeDesc::Nothing => Err(Error::UnsupportedType("Nothing".into())),
+        TypeDesc::Nothing => write_tag(BinaryTypeIndex::Nothing, writer),
         TypeDesc::UInt8 => write_tag(BinaryTypeIndex::UInt8, writer),
         TypeDesc::Bool => write_tag(BinaryTypeIndex::Bool, writer),
         TypeDesc::UInt16 => write_tag(BinaryTypeIndex::UInt16, writer),
@@ -87,6 +97,65 @@ pub(crate) fn encode_type_binary<W: Write + ?Sized>(ty: &TypeDesc, writer: &mut
         TypeDesc::Float64 => write_tag(BinaryTypeIndex::Float64, writer),
         TypeDesc::Float16 => Err(Error::UnsupportedType("Float16".into())),
         TypeDesc::BFloat16 => write_tag(BinaryTypeIndex::BFloat16, writer),
+        TypeDesc::AggregateFunction {
+            function,
+            arguments,
+        } => {
+            if function.contains('(') {
+                return Err(Error::UnsupportedType(format!(
+                    "binary encoding of parametric aggregate function {function}"
+                )));
+            }
+            write_tag(BinaryTypeIndex::AggregateFunction, writer)?;
+            write_uvarint(0, writer)?;
+            write_string(function, writer)?;
+            write_uvarint(0, writer)?;
+            write_uvarint(arguments.len() as u64, writer)?;
+            for argument in arguments {
+                encode_type_binary(argument, writer)?;
+            }
+            Ok(())
+        }
+        TypeDesc::SimpleAggregateFunction { function, ty } => {
+            if function.contains('(') {
+                return Err(Error::UnsupportedType(format!(
+                    "binary encoding of parametric aggregate function {function}"
+                )));
+            }
+            write_tag(BinaryTypeIndex::SimpleAggregateFunction, writer)?;
+            write_string(function, writer)?;
+            write_uvarint(0, writer)?;
+            write_uvarint(1, writer)?;
+            encode_type_binary(ty, writer)
+        }
+        TypeDesc::Interval(kind) => {
+            write_tag(BinaryTypeIndex::Interval, writer)?;
+            let kind = match kind {
+                IntervalKind::Nanosecond => 0x00,
+                IntervalKind::Microsecond => 0x01,
+                IntervalKind::Millisecond => 0x02,
+                IntervalKind::Second => 0x03,
+                IntervalKind::Minute => 0x04,
+                IntervalKind::Hour => 0x05,
+                IntervalKind::Day => 0x06,
+                IntervalKind::Week => 0x07,
+                IntervalKind::Month => 0x08,
+                IntervalKind::Quarter => 0x09,
+                IntervalKind::Year => 0x0A,
+            };
+            writer.write_all(&[kind])?;
+            Ok(())
+        }
+        TypeDesc::Point
+        | TypeDesc::Ring
+        | TypeDesc::LineString
+        | TypeDesc::MultiLineString
+        | TypeDesc::Polygon
+        | TypeDesc::MultiPolygon => {
+            write_tag(BinaryTypeIndex::Custom, writer)?;
+            write_string(&ty.type_name(), writer)?;
+            Ok(())
+        }
         TypeDesc::Date => write_tag(BinaryTypeIndex::Date, writer),
         TypeDesc::Date32 => write_tag(BinaryTypeIndex::Date32, writer),
         TypeDesc::DateTime { timezone } => match timezone {
@@ -196,6 +265,12 @@ pub(crate) fn encode_type_binary<W: Write + ?Sized>(ty: &TypeDesc, writer: &mut
         TypeDesc::Nested(items) => encode_nested(items, writer),
         TypeDesc::Variant(items) => encode_variant(items, writer),
         TypeDesc::Nullable(inner) => {
+            if !can_be_inside_nullable(inner) {
+                return Err(Error::UnsupportedCombination(format!(
+                    "Nullable({}) is unsupported",
+                    inner.type_name()
+                )));
+            }
             write_tag(BinaryTypeIndex::Nullable, writer)?;
             encode_type_binary(inner, writer)
         }
@@ -241,7 +316,11 @@ pub(crate) fn encode_type_binary<W: Write + ?Sized>(ty: &TypeDesc, writer: &mut
             }
             Ok(())
         }
-        TypeDesc::Dynamic { .. } => Err(Error::UnsupportedType("Dynamic".into())),
+        TypeDesc::Dynamic { max_types } => {
+            write_tag(BinaryTypeIndex::Dynamic, writer)?;
+            writer.write_all(&[max_types.unwrap_or(DYNAMIC_DEFAULT_MAX_TYPES)])?;
+            Ok(())
+        }
     }
 }
 
@@ -255,31 +334,63 @@ pub(crate) fn encode_type_binary_option<W: Write + ?Sized>(
     }
 }
 
-#[allow(clippy::too_many_lines)]
+/// Bounds on the work a single binary type descriptor may cause.
+struct DecodeBudget {
+    complexity: usize,
+    depth: usize,
+    max_depth: usize,
+}
+
+impl DecodeBudget {
+    fn new(max_depth: usize) -> Self {
+        Self {
+            complexity: 0,
+            depth: 0,
+            max_depth,
+        }
+    }
+}
+
+/// Decodes a standalone type descriptor, such as a header column type,
+/// rejecting types nested deeper than `max_depth`.
+pub(crate) fn decode_type_binary_with_max_depth<R: Read + ?Sized>(
+    reader: &mut R,
+    max_depth: usize,
+) -> Result<TypeDesc> {
+    let mut budget = DecodeBudget::new(max_depth);
+    Ok(decode_type_binary_inner(reader, &mut budget)?.unwrap_or(TypeDesc::Nothing))
+}
+
 fn decode_type_binary_inner<R: Read + ?Sized>(
     reader: &mut R,
-    complexity: &mut usize,
+    budget: &mut DecodeBudget,
 ) -> Result<Option<TypeDesc>> {
     let tag = read_u8(reader)?;
-    decode_type_binary_inner_with_tag(tag, reader, complexity)
+    if budget.depth > budget.max_depth {
+        return Err(Error::InvalidValue("type nesting depth limit exceeded"));
+    }
+    budget.depth += 1;
+    let ty = decode_type_binary_inner_with_tag(tag, reader, budget)?;
+    budget.depth -= 1;
+    Ok(ty)
 }
 
 pub(crate) fn decode_type_binary_from_tag<R: Read + ?Sized>(
     tag: u8,
     reader: &mut R,
 ) -> Result<Option<TypeDesc>> {
-    let mut complexity = 0usize;
-    decode_type_binary_inner_with_tag(tag, reader, &mut complexity)
+    let mut budget = DecodeBudget::new(DEFAULT_MAX_TYPE_DEPTH);
+    decode_type_binary_inner_with_tag(tag, reader, &mut budget)
 }
 
 #[allow(clippy::too_many_lines)]
 fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
     tag: u8,
     reader: &mut R,
-    complexity: &mut usize,
+    budget: &mut DecodeBudget,
 ) -> Result<Option<TypeDesc>> {
-    *complexity = complexity.saturating_add(1);
-    if *complexity > MAX_TYPE_COMPLEXITY {
+    budget.complexity = budget.complexity.saturating_add(1);
+    if budget.complexity > MAX_TYPE_COMPLEXITY {
         return Err(Error::InvalidValue(
             "binary type decoding complexity limit exceeded",
         ));
@@ -312,14 +423,14 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             Ok(Some(TypeDesc::DateTime { timezone: Some(tz) }))
         }
         x if x == BinaryTypeIndex::DateTime64UTC as u8 => {
-            let precision = read_u8(reader)?;
+            let precision = read_datetime64_precision(reader)?;
             Ok(Some(TypeDesc::DateTime64 {
                 precision,
                 timezone: None,
             }))
         }
         x if x == BinaryTypeIndex::DateTime64WithTimezone as u8 => {
-            let precision = read_u8(reader)?;
+            let precision = read_datetime64_precision(reader)?;
             let tz = read_required_string(reader, "missing DateTime64 timezone")?;
             Ok(Some(TypeDesc::DateTime64 {
                 precision,
@@ -419,7 +530,7 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             }
             let mut items = Vec::with_capacity(count);
             for _ in 0..count {
-                let ty = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+                let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                     Error::UnsupportedCombination("Variant cannot contain Nothing".into())
                 })?;
                 items.push(ty);
@@ -427,16 +538,14 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             Ok(Some(TypeDesc::Variant(canonicalize_variant_types(items)?)))
         }
         x if x == BinaryTypeIndex::Array as u8 => {
-            let inner = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
-                Error::UnsupportedCombination("Array(Nothing) is unsupported".into())
-            })?;
+            let inner = decode_type_binary_inner(reader, budget)?.unwrap_or(TypeDesc::Nothing);
             Ok(Some(TypeDesc::Array(Box::new(inner))))
         }
         x if x == BinaryTypeIndex::Map as u8 => {
-            let key = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+            let key = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                 Error::UnsupportedCombination("Map(Nothing, T) is unsupported".into())
             })?;
-            let value = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+            let value = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                 Error::UnsupportedCombination("Map(T, Nothing) is unsupported".into())
             })?;
             if !is_valid_map_key(&key) {
@@ -462,7 +571,7 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             }
             let mut items = Vec::with_capacity(count);
             for _ in 0..count {
-                let ty = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+                let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                     Error::UnsupportedCombination("Tuple(Nothing) is unsupported".into())
                 })?;
                 items.push(TupleItem { name: None, ty });
@@ -482,7 +591,7 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             let mut items = Vec::with_capacity(count);
             for _ in 0..count {
                 let name = read_required_string(reader, "missing Tuple element name")?;
-                let ty = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+                let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                     Error::UnsupportedCombination("Tuple(Nothing) is unsupported".into())
                 })?;
                 items.push(TupleItem {
@@ -493,13 +602,17 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             Ok(Some(TypeDesc::Tuple(items)))
         }
         x if x == BinaryTypeIndex::Nullable as u8 => {
-            let inner = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
-                Error::UnsupportedCombination("Nullable(Nothing) is unsupported".into())
-            })?;
+            let inner = decode_type_binary_inner(reader, budget)?.unwrap_or(TypeDesc::Nothing);
+            if !can_be_inside_nullable(&inner) {
+                return Err(Error::UnsupportedCombination(format!(
+                    "Nullable({}) is unsupported",
+                    inner.type_name()
+                )));
+            }
             Ok(Some(TypeDesc::Nullable(Box::new(inner))))
         }
         x if x == BinaryTypeIndex::LowCardinality as u8 => {
-            let inner = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+            let inner = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                 Error::UnsupportedCombination("LowCardinality(Nothing) is unsupported".into())
             })?;
             if !can_be_inside_low_cardinality(&inner) {
@@ -523,7 +636,7 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             let mut items = Vec::with_capacity(count);
             for _ in 0..count {
                 let name = read_required_string(reader, "missing Nested element name")?;
-                let ty = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+                let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                     Error::UnsupportedCombination("Nested(Nothing) is unsupported".into())
                 })?;
                 items.push(TupleItem {
@@ -555,7 +668,7 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
             let mut typed_paths = Vec::with_capacity(typed_paths_count);
             for _ in 0..typed_paths_count {
                 let path = read_required_string(reader, "missing JSON typed path")?;
-                let ty = decode_type_binary_inner(reader, complexity)?.ok_or_else(|| {
+                let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
                     Error::UnsupportedCombination("JSON typed path cannot be Nothing".into())
                 })?;
                 typed_paths.push((path, ty));
@@ -585,7 +698,98 @@ fn decode_type_binary_inner_with_tag<R: Read + ?Sized>(
                 skip_regexps,
             }))
         }
-        x if x == BinaryTypeIndex::Dynamic as u8 => Err(Error::UnsupportedType("Dynamic".into())),
+        x if x == BinaryTypeIndex::Dynamic as u8 => {
+            let max_types = read_u8(reader)?;
+            Ok(Some(TypeDesc::Dynamic {
+                max_types: (max_types != DYNAMIC_DEFAULT_MAX_TYPES).then_some(max_types),
+            }))
+        }
+        x if x == BinaryTypeIndex::AggregateFunction as u8 => {
+            let _version = read_required_uvarint(reader, "missing AggregateFunction version")?;
+            let function = read_required_string(reader, "missing AggregateFunction name")?;
+            let parameters =
+                read_required_uvarint(reader, "missing AggregateFunction parameter count")?;
+            if parameters > 0 {
+                return Err(Error::UnsupportedType(format!(
+                    "binary encoding of parametric aggregate function {function}"
+                )));
+            }
+            let count = read_required_uvarint(reader, "missing AggregateFunction argument count")?;
+            let count = usize::try_from(count)
+                .map_err(|_| Error::Overflow("AggregateFunction argument count too large"))?;
+            if count > MAX_TYPE_COMPLEXITY {
+                return Err(Error::InvalidValue("too many AggregateFunction arguments"));
+            }
+            let mut arguments = Vec::with_capacity(count);
+            for _ in 0..count {
+                let argument = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
+                    Error::UnsupportedCombination(
+                        "AggregateFunction argument cannot be Nothing".into(),
+                    )
+                })?;
+                arguments.push(argument);
+            }
+            Ok(Some(TypeDesc::AggregateFunction {
+                function,
+                arguments,
+            }))
+        }
+        x if x == BinaryTypeIndex::SimpleAggregateFunction as u8 => {
+            let function = read_required_string(reader, "missing SimpleAggregateFunction name")?;
+            let parameters =
+                read_required_uvarint(reader, "missing SimpleAggregateFunction parameter count")?;
+            if parameters > 0 {
+                return Err(Error::UnsupportedType(format!(
+                    "binary encoding of parametric aggregate function {function}"
+                )));
+            }
+            let count =
+                read_required_uvarint(reader, "missing SimpleAggregateFunction argument count")?;
+            if count != 1 {
+                return Err(Error::InvalidValue(
+                    "SimpleAggregateFunction expects exactly one type",
+                ));
+            }
+            let ty = decode_type_binary_inner(reader, budget)?.ok_or_else(|| {
+                Error::UnsupportedCombination(
+                    "SimpleAggregateFunction(f, Nothing) is unsupported".into(),
+                )
+            })?;
+            Ok(Some(TypeDesc::SimpleAggregateFunction {
+                function,
+                ty: Box::new(ty),
+            }))
+        }
+        x if x == BinaryTypeIndex::Interval as u8 => {
+            let kind = match read_u8(reader)? {
+                0x00 => IntervalKind::Nanosecond,
+                0x01 => IntervalKind::Microsecond,
+                0x02 => IntervalKind::Millisecond,
+                0x03 => IntervalKind::Second,
+                0x04 => IntervalKind::Minute,
+                0x05 => IntervalKind::Hour,
+                0x06 => IntervalKind::Day,
+                0x07 => IntervalKind::Week,
+                0x08 => IntervalKind::Month,
+                0x09 => IntervalKind::Quarter,
+                0x0A => IntervalKind::Year,
+                _ => return Err(Error::InvalidValue("invalid Interval kind")),
+            };
+            Ok(Some(TypeDesc::Interval(kind)))
+        }
+        x if x == BinaryTypeIndex::Custom as u8 => {
+            let name = read_required_string(reader, "missing custom type name")?;
+            let ty = match name.as_str() {
+                "Point" => TypeDesc::Point,
+                "Ring" => TypeDesc::Ring,
+                "LineString" => TypeDesc::LineString,
+                "MultiLineString" => TypeDesc::MultiLineString,
+                "Polygon" => TypeDesc::Polygon,
+                "MultiPolygon" => TypeDesc::MultiPolygon,
+                _ => return Err(Error::UnsupportedType(name)),
+            };
+            Ok(Some(ty))
+        }
         other => Err(Error::UnsupportedType(format!("binary type 0x{other:02x}"))),
     }
 }
@@ -601,6 +805,14 @@ fn read_u8<R: Read + ?Sized>(reader: &mut R) -> Result<u8> {
     Ok(buf[0])
 }
 
+fn read_datetime64_precision<R: Read + ?Sized>(reader: &mut R) -> Result<u8> {
+    let precision = read_u8(reader)?;
+    if precision > DATETIME64_MAX_PRECISION {
+        return Err(Error::InvalidValue("DateTime64 precision must be <= 9"));
+    }
+    Ok(precision)
+}
+
 fn read_required_string<R: Read + ?Sized>(reader: &mut R, context: &'static str) -> Result<String> {
     read_string(reader)?
         .ok_or_else(|| Error::Io(io::Error::new(io::ErrorKind::UnexpectedEof, context)))
@@ -886,9 +1098,97 @@ mod tests {
     }
 
     #[test]
-    fn rejects_dynamic_type_encoding() {
-        let err = encode_type_binary(&TypeDesc::Dynamic { max_types: None }, &mut Vec::new())
+    fn rejects_out_of_range_datetime64_precision() {
+        let mut cursor = Cursor::new(vec![10_u8]);
+        let err = decode_type_binary_from_tag(BinaryTypeIndex::DateTime64UTC as u8, &mut cursor)
             .unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+    }
+
+    #[test]
+    fn roundtrip_aggregate_function_types() {
+        let ty = TypeDesc::AggregateFunction {
+            function: "uniq".to_string(),
+            arguments: vec![TypeDesc::UInt64, TypeDesc::String],
+        };
+        assert_eq!(roundtrip(&ty), ty);
+
+        let simple = TypeDesc::SimpleAggregateFunction {
+            function: "sum".to_string(),
+            ty: Box::new(TypeDesc::UInt64),
+        };
+        assert_eq!(roundtrip(&simple), simple);
+
+        let parametric = TypeDesc::AggregateFunction {
+            function: "quantiles(0.5)".to_string(),
+            arguments: vec![TypeDesc::Float64],
+        };
+        let err = encode_type_binary(&parametric, &mut Vec::new()).unwrap_err();
         assert!(matches!(err, Error::UnsupportedType(_)));
     }
+
+    #[test]
+    fn roundtrip_interval_types() {
+        for kind in [
+            IntervalKind::Nanosecond,
+            IntervalKind::Second,
+            IntervalKind::Day,
+            IntervalKind::Quarter,
+            IntervalKind::Year,
+        ] {
+            let ty = TypeDesc::Interval(kind);
+            assert_eq!(roundtrip(&ty), ty);
+        }
+        let mut buf = Vec::new();
+        encode_type_binary(&TypeDesc::Interval(IntervalKind::Month), &mut buf).unwrap();
+        assert_eq!(buf, vec![0x22, 0x08]);
+    }
+
+    #[test]
+    fn roundtrip_nested_nothing_types() {
+        let types = vec![
+            TypeDesc::Array(Box::new(TypeDesc::Nothing)),
+            TypeDesc::Nullable(Box::new(TypeDesc::Nothing)),
+            TypeDesc::Array(Box::new(TypeDesc::Nullable(Box::new(TypeDesc::Nothing)))),
+        ];
+
+        for ty in types {
+            assert_eq!(roundtrip(&ty), ty);
+        }
+    }
+
+    #[test]
+    fn roundtrip_geo_types() {
+        let types = vec![
+            TypeDesc::Point,
+            TypeDesc::Ring,
+            TypeDesc::LineString,
+            TypeDesc::MultiLineString,
+            TypeDesc::Polygon,
+            TypeDesc::MultiPolygon,
+        ];
+
+        for ty in types {
+            assert_eq!(roundtrip(&ty), ty);
+        }
+    }
+
+    #[test]
+    fn roundtrip_dynamic_types() {
+        for ty in [
+            TypeDesc::Dynamic { max_types: None },
+            TypeDesc::Dynamic { max_types: Some(8) },
+            TypeDesc::Array(Box::new(TypeDesc::Dynamic { max_types: None })),
+        ] {
+            assert_eq!(roundtrip(&ty), ty);
+        }
+        let mut buf = Vec::new();
+        encode_type_binary(&TypeDesc::Dynamic { max_types: None }, &mut buf).unwrap();
+        assert_eq!(buf, [0x2B, 32]);
+        assert_eq!(
+            decode_type_binary_with_max_depth(&mut [0_u8].as_slice(), DEFAULT_MAX_TYPE_DEPTH)
+                .unwrap(),
+            TypeDesc::Nothing
+        );
+    }
 }
@@ -15,7 +15,11 @@ use crate::{
     value::Value,
 };
 
-use super::type_binary::{decode_type_binary_from_tag, encode_type_binary_option};
+use super::{
+    aggregate::skip_aggregate_state,
+    scan::{CaptureReader, fixed_len_for_type},
+    type_binary::{decode_type_binary_from_tag, encode_type_binary_option},
+};
 
 pub(crate) fn read_value_required<R: Read + ?Sized>(
     ty: &TypeDesc,
@@ -89,7 +93,7 @@ pub(crate) fn read_value_optional<R: Read + ?Sized>(
         TypeDesc::Int32 => {
             read_fixed::<_, _, 4>(reader, |bytes| Value::Int32(i32::from_le_bytes(bytes)))
         }
-        TypeDesc::Int64 => {
+        TypeDesc::Int64 | TypeDesc::Interval(_) => {
             read_fixed::<_, _, 8>(reader, |bytes| Value::Int64(i64::from_le_bytes(bytes)))
         }
         TypeDesc::Int128 => {
@@ -143,6 +147,28 @@ pub(crate) fn read_value_optional<R: Read + ?Sized>(
         TypeDesc::Ipv6 => {
             read_fixed::<_, _, 16>(reader, |bytes| Value::Ipv6(Ipv6Addr::from(bytes)))
         }
+        TypeDesc::Point
+        | TypeDesc::Ring
+        | TypeDesc::LineString
+        | TypeDesc::MultiLineString
+        | TypeDesc::Polygon
+        | TypeDesc::MultiPolygon => {
+            let storage = ty
+                .geo_storage()
+                .ok_or(Error::Internal("geo type without storage type"))?;
+            read_value_optional(storage, reader)
+        }
+        TypeDesc::AggregateFunction {
+            function,
+            arguments,
+        } => {
+            let mut state = Vec::new();
+            let mut capture = CaptureReader::new(reader, &mut state);
+            if skip_aggregate_state(function, arguments, &mut capture)?.is_none() {
+                return Ok(None);
+            }
+            Ok(Some(Value::AggregateState(state)))
+        }
         TypeDesc::Decimal32 { .. } => {
             read_fixed::<_, _, 4>(reader, |bytes| Value::Decimal32(i32::from_le_bytes(bytes)))
         }
@@ -189,7 +215,9 @@ pub(crate) fn read_value_optional<R: Read + ?Sized>(
                 Ok(Some(Value::Nullable(Some(Box::new(inner_value)))))
             }
         }
-        TypeDesc::LowCardinality(inner) => read_value_optional(inner, reader),
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
+            read_value_optional(inner, reader)
+        }
         TypeDesc::Array(inner) => {
             let Some(len) = read_uvarint(reader)? else {
                 return Ok(None);
@@ -287,6 +315,11 @@ pub(crate) fn read_value_optional<R: Read + ?Sized>(
             let Some(ty) = ty else {
                 return Ok(Some(Value::DynamicNull));
             };
+            if matches!(ty, TypeDesc::Dynamic { .. }) {
+                return Err(Error::UnsupportedType(
+                    "Dynamic value of type Dynamic".into(),
+                ));
+            }
             let value = read_value_required(&ty, reader)?;
             Ok(Some(Value::Dynamic {
                 ty: Box::new(ty),
@@ -308,10 +341,148 @@ where
     Ok(Some(map(buf)))
 }
 
+/// Returns the zero value of `ty`, which the server uses for columns without
+/// an explicit `DEFAULT` expression.
+pub(crate) fn default_value(ty: &TypeDesc) -> Result<Value> {
+    if let Some(storage) = ty.geo_storage() {
+        return default_value(storage);
+    }
+    Ok(match ty {
+        TypeDesc::Nothing => Value::Nothing,
+        TypeDesc::UInt8 => Value::UInt8(0),
+        TypeDesc::Bool => Value::Bool(false),
+        TypeDesc::UInt16 => Value::UInt16(0),
+        TypeDesc::UInt32 => Value::UInt32(0),
+        TypeDesc::UInt64 => Value::UInt64(0),
+        TypeDesc::UInt128 => Value::UInt128(0),
+        TypeDesc::UInt256 => Value::UInt256([0; 32]),
+        TypeDesc::Int8 => Value::Int8(0),
+        TypeDesc::Int16 => Value::Int16(0),
+        TypeDesc::Int32 => Value::Int32(0),
+        TypeDesc::Int64 | TypeDesc::Interval(_) => Value::Int64(0),
+        TypeDesc::Int128 => Value::Int128(0),
+        TypeDesc::Int256 => Value::Int256([0; 32]),
+        TypeDesc::Float32 => Value::Float32(0.0),
+        TypeDesc::Float64 => Value::Float64(0.0),
+        TypeDesc::Float16 => Value::Float16(0.0),
+        TypeDesc::BFloat16 => Value::BFloat16(0.0),
+        TypeDesc::String => Value::String(Vec::new()),
+        TypeDesc::FixedString { length } => Value::FixedString(vec![0; *length]),
+        TypeDesc::Date => Value::Date(0),
+        TypeDesc::Date32 => Value::Date32(0),
+        TypeDesc::DateTime { .. } => Value::DateTime(0),
+        TypeDesc::DateTime64 { .. } => Value::DateTime64(0),
+        TypeDesc::Uuid => Value::Uuid(Uuid::nil()),
+        TypeDesc::Ipv4 => Value::Ipv4(Ipv4Addr::UNSPECIFIED),
+        TypeDesc::Ipv6 => Value::Ipv6(Ipv6Addr::UNSPECIFIED),
+        TypeDesc::Decimal32 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits32,
+            ..
+        } => Value::Decimal32(0),
+        TypeDesc::Decimal64 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits64,
+            ..
+        } => Value::Decimal64(0),
+        TypeDesc::Decimal128 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits128,
+            ..
+        } => Value::Decimal128(0),
+        TypeDesc::Decimal256 { .. }
+        | TypeDesc::Decimal {
+            size: DecimalSize::Bits256,
+            ..
+        } => Value::Decimal256([0; 32]),
+        TypeDesc::Enum8(values) => Value::Enum8(values.first().map_or(0, |(_, value)| *value)),
+        TypeDesc::Enum16(values) => Value::Enum16(values.first().map_or(0, |(_, value)| *value)),
+        TypeDesc::Nullable(_) => Value::Nullable(None),
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
+            default_value(inner)?
+        }
+        TypeDesc::Array(_) | TypeDesc::Nested(_) => Value::Array(Vec::new()),
+        TypeDesc::Map { .. } => Value::Map(Vec::new()),
+        TypeDesc::Tuple(items) => Value::Tuple(
+            items
+                .iter()
+                .map(|item| default_value(&item.ty))
+                .collect::<Result<_>>()?,
+        ),
+        TypeDesc::Variant(_) => Value::VariantNull,
+        TypeDesc::Dynamic { .. } => Value::DynamicNull,
+        TypeDesc::Json { .. } => Value::JsonObject(Vec::new()),
+        TypeDesc::AggregateFunction { .. } => {
+            return Err(Error::UnsupportedCombination(
+                "AggregateFunction columns have no default value".into(),
+            ));
+        }
+        TypeDesc::Point
+        | TypeDesc::Ring
+        | TypeDesc::LineString
+        | TypeDesc::MultiLineString
+        | TypeDesc::Polygon
+        | TypeDesc::MultiPolygon => return Err(Error::Internal("geo type without storage")),
+    })
+}
+
+/// `RowBinaryWithDefaults` flag byte asking for the column default instead
+/// of a value.
+pub(crate) const USE_DEFAULT: u8 = 1;
+
+/// Reads a `RowBinaryWithDefaults` value and its leading flag byte; see
+/// [`read_value_or_default_optional`].
+pub(crate) fn read_value_or_default_required<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    reader: &mut R,
+) -> Result<Value> {
+    match read_value_or_default_optional(ty, reader)? {
+        Some(value) => Ok(value),
+        None => Err(Error::Io(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "unexpected EOF while reading row",
+        ))),
+    }
+}
+
+/// Reads a `RowBinaryWithDefaults` value and its leading flag byte,
+/// returning `Ok(None)` on a clean EOF before the flag.
+///
+/// Server-side `DEFAULT` expressions are unknown here, so a value marked
+/// "use default" decodes as the [`default_value`] of its type.
+pub(crate) fn read_value_or_default_optional<R: Read + ?Sized>(
+    ty: &TypeDesc,
+    reader: &mut R,
+) -> Result<Option<Value>> {
+    if matches!(ty, TypeDesc::Nested(_)) {
+        return Err(Error::UnsupportedCombination(
+            "RowBinaryWithDefaults does not support Nested columns".into(),
+        ));
+    }
+    let mut flag = [0_u8; 1];
+    if read_exact_or_eof(reader, &mut flag)? {
+        return Ok(None);
+    }
+    match flag[0] {
+        0 => read_value_required(ty, reader).map(Some),
+        USE_DEFAULT => default_value(ty).map(Some),
+        _ => Err(Error::InvalidValue("invalid default flag")),
+    }
+}
+
+/// Encoding knobs applied while writing values.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct WriteOptions {
+    /// Zero-pads `FixedString` values shorter than the declared length
+    /// instead of rejecting them.
+    pub(crate) pad_fixed_strings: bool,
+}
+
 #[allow(clippy::too_many_lines)]
 pub(crate) fn write_value<W: Write + ?Sized>(
     ty: &TypeDesc,
     value: &Value,
+    options: WriteOptions,
     writer: &mut W,
 ) -> Result<()> {
     match (ty, value) {
@@ -319,9 +490,20 @@ pub(crate) fn write_value<W: Write + ?Sized>(
         (TypeDesc::Bool, Value::Bool(value)) => {
             writer.write_all(&[u8::from(*value)])?;
         }
+        (TypeDesc::Bool, Value::UInt8(value)) => {
+            if *value > 1 {
+                return Err(Error::InvalidValue("invalid Bool value"));
+            }
+            writer.write_all(&[*value])?;
+        }
         (TypeDesc::UInt16, Value::UInt16(value)) | (TypeDesc::Date, Value::Date(value)) => {
             writer.write_all(&value.to_le_bytes())?;
         }
+        (TypeDesc::Date, Value::Date32(value)) => {
+            let days =
+                u16::try_from(*value).map_err(|_| Error::Overflow("date outside Date range"))?;
+            writer.write_all(&days.to_le_bytes())?;
+        }
         (TypeDesc::UInt32, Value::UInt32(value)) => writer.write_all(&value.to_le_bytes())?,
         (TypeDesc::UInt64, Value::UInt64(value)) => writer.write_all(&value.to_le_bytes())?,
         (TypeDesc::UInt128, Value::UInt128(value)) => {
@@ -338,7 +520,6 @@ pub(crate) fn write_value<W: Write + ?Sized>(
         | (TypeDesc::Decimal32 { .. }, Value::Decimal32(value)) => {
             writer.write_all(&value.to_le_bytes())?;
         }
-        (TypeDesc::Int64, Value::Int64(value)) => writer.write_all(&value.to_le_bytes())?,
         (TypeDesc::Int128, Value::Int128(value)) => writer.write_all(&value.to_le_bytes())?,
         (TypeDesc::Int256, Value::Int256(value)) => writer.write_all(value)?,
         (TypeDesc::Float32, Value::Float32(value)) => writer.write_all(&value.to_le_bytes())?,
@@ -353,15 +534,19 @@ pub(crate) fn write_value<W: Write + ?Sized>(
         }
         (TypeDesc::String, Value::String(value)) => write_bytes(value, writer)?,
         (TypeDesc::FixedString { length }, Value::FixedString(value)) => {
-            if value.len() != *length {
+            if value.len() > *length || (value.len() < *length && !options.pad_fixed_strings) {
                 return Err(Error::InvalidValue("FixedString length mismatch"));
             }
             writer.write_all(value)?;
+            if value.len() < *length {
+                writer.write_all(&vec![0_u8; *length - value.len()])?;
+            }
         }
         (TypeDesc::DateTime { .. }, Value::DateTime(value)) => {
             writer.write_all(&value.to_le_bytes())?;
         }
-        (TypeDesc::DateTime64 { .. }, Value::DateTime64(value))
+        (TypeDesc::Int64 | TypeDesc::Interval(_), Value::Int64(value))
+        | (TypeDesc::DateTime64 { .. }, Value::DateTime64(value))
         | (TypeDesc::Decimal64 { .. }, Value::Decimal64(value)) => {
             writer.write_all(&value.to_le_bytes())?;
         }
@@ -406,32 +591,63 @@ pub(crate) fn write_value<W: Write + ?Sized>(
         (TypeDesc::Enum16(_), Value::Enum16(value)) => {
             writer.write_all(&value.to_le_bytes())?;
         }
+        (TypeDesc::Enum8(values), Value::String(name)) => {
+            let (_, value) = values
+                .iter()
+                .find(|(candidate, _)| candidate.as_bytes() == name.as_slice())
+                .ok_or(Error::InvalidValue("unknown Enum8 name"))?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        (TypeDesc::Enum16(values), Value::String(name)) => {
+            let (_, value) = values
+                .iter()
+                .find(|(candidate, _)| candidate.as_bytes() == name.as_slice())
+                .ok_or(Error::InvalidValue("unknown Enum16 name"))?;
+            writer.write_all(&value.to_le_bytes())?;
+        }
         (TypeDesc::Nullable(inner), Value::Nullable(value)) => {
             if let Some(inner_value) = value {
                 writer.write_all(&[0])?;
-                write_value(inner, inner_value, writer)?;
+                write_value(inner, inner_value, options, writer)?;
             } else {
                 writer.write_all(&[1])?;
             }
         }
-        (TypeDesc::LowCardinality(inner), value) => {
-            write_value(inner, value, writer)?;
+        (
+            TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. },
+            value,
+        ) => {
+            write_value(inner, value, options, writer)?;
+        }
+        (
+            TypeDesc::Point
+            | TypeDesc::Ring
+            | TypeDesc::LineString
+            | TypeDesc::MultiLineString
+            | TypeDesc::Polygon
+            | TypeDesc::MultiPolygon,
+            Value::Tuple(_) | Value::Array(_),
+        ) => {
+            let storage = ty
+                .geo_storage()
+                .ok_or(Error::Internal("geo type without storage type"))?;
+            write_value(storage, value, options, writer)?;
         }
         (TypeDesc::Array(inner), Value::Array(values)) => {
             write_uvarint(values.len() as u64, writer)?;
             for item in values {
-                write_value(inner, item, writer)?;
+                write_value(inner, item, options, writer)?;
             }
         }
         (TypeDesc::Map { key, value }, Value::Map(entries)) => {
             write_uvarint(entries.len() as u64, writer)?;
             for (entry_key, entry_value) in entries {
-                write_value(key, entry_key, writer)?;
-                write_value(value, entry_value, writer)?;
+                write_value(key, entry_key, options, writer)?;
+                write_value(value, entry_value, options, writer)?;
             }
         }
         (TypeDesc::Tuple(items), Value::Tuple(values)) => {
-            write_tuple_values(items, values, writer)?;
+            write_tuple_values(items, values, options, writer)?;
         }
         (TypeDesc::Variant(variants), Value::Variant { index, value }) => {
             let discr = *index;
@@ -439,12 +655,48 @@ pub(crate) fn write_value<W: Write + ?Sized>(
                 .get(usize::from(discr))
                 .ok_or(Error::InvalidValue("Variant discriminator out of range"))?;
             writer.write_all(&[discr])?;
-            write_value(variant, value, writer)?;
+            write_value(variant, value, options, writer)?;
         }
-        (TypeDesc::Variant(_), Value::VariantNull) => {
+        (TypeDesc::Variant(_), Value::VariantNull | Value::Nullable(None)) => {
             writer.write_all(&[u8::MAX])?;
         }
+        (TypeDesc::Variant(variants), value) => {
+            // Plain values pick the first alternative (in the sorted declaration
+            // order used for discriminators) that can encode them, preferring
+            // alternatives of the value's own type over ones that coerce it.
+            let native = |variant: &TypeDesc| {
+                variant.type_name().split('(').next() == Some(value.type_name())
+            };
+            let native_first = variants
+                .iter()
+                .enumerate()
+                .filter(|(_, variant)| native(variant))
+                .chain(
+                    variants
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, variant)| !native(variant)),
+                );
+            let mut scratch = Vec::new();
+            for (discr, variant) in native_first {
+                scratch.clear();
+                if write_value(variant, value, options, &mut scratch).is_ok() {
+                    let discr = u8::try_from(discr)
+                        .map_err(|_| Error::Overflow("Variant discriminator too large"))?;
+                    writer.write_all(&[discr])?;
+                    writer.write_all(&scratch)?;
+                    return Ok(());
+                }
+            }
+            return Err(Error::TypeMismatch {
+                expected: ty.type_name(),
+                actual: value.type_name().to_string(),
+            });
+        }
         (TypeDesc::Nothing, Value::Nothing) => {}
+        (TypeDesc::AggregateFunction { .. }, Value::AggregateState(state)) => {
+            writer.write_all(state)?;
+        }
         (TypeDesc::Nested(items), Value::Array(values)) => {
             write_uvarint(values.len() as u64, writer)?;
             for value in values {
@@ -454,7 +706,7 @@ pub(crate) fn write_value<W: Write + ?Sized>(
                         actual: value.type_name().to_string(),
                     });
                 };
-                write_tuple_values(items, values, writer)?;
+                write_tuple_values(items, values, options, writer)?;
             }
         }
         (TypeDesc::Json { typed_paths, .. }, Value::JsonObject(entries)) => {
@@ -462,9 +714,14 @@ pub(crate) fn write_value<W: Write + ?Sized>(
             for (path, value) in entries {
                 write_string(path, writer)?;
                 if let Some((_, ty)) = typed_paths.iter().find(|(name, _)| name == path) {
-                    write_value(ty, value, writer)?;
+                    write_value(ty, value, options, writer)?;
                 } else {
-                    write_value(&TypeDesc::Dynamic { max_types: None }, value, writer)?;
+                    write_value(
+                        &TypeDesc::Dynamic { max_types: None },
+                        value,
+                        options,
+                        writer,
+                    )?;
                 }
             }
         }
@@ -479,7 +736,7 @@ pub(crate) fn write_value<W: Write + ?Sized>(
                 ));
             }
             encode_type_binary_option(Some(ty.as_ref()), writer)?;
-            write_value(ty.as_ref(), value, writer)?;
+            write_value(ty.as_ref(), value, options, writer)?;
         }
         (ty, value) => {
             return Err(Error::TypeMismatch {
@@ -513,20 +770,156 @@ fn read_tuple_values<R: Read + ?Sized>(
 fn write_tuple_values<W: Write + ?Sized>(
     items: &[crate::types::TupleItem],
     values: &[Value],
+    options: WriteOptions,
     writer: &mut W,
 ) -> Result<()> {
     if items.len() != values.len() {
         return Err(Error::InvalidValue("Tuple length mismatch"));
     }
     for (item, value) in items.iter().zip(values.iter()) {
-        write_value(&item.ty, value, writer)?;
+        write_value(&item.ty, value, options, writer)?;
     }
     Ok(())
 }
 
+impl Value {
+    /// Returns the number of bytes this value occupies when encoded as `ty`.
+    ///
+    /// Fixed-width types are sized from the type alone, so a value that
+    /// does not match such a type is only rejected when it is written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the value cannot be encoded as
+    /// `ty`.
+    pub fn encoded_size(&self, ty: &TypeDesc) -> Result<usize> {
+        encoded_value_len(ty, self)
+    }
+}
+
+/// Returns the number of bytes [`write_value`] produces without writing.
+///
+/// Types whose size depends on more than their own contents (`Variant`,
+/// `Dynamic`, `JSON`) are encoded into a byte counter instead.
+pub(crate) fn encoded_value_len(ty: &TypeDesc, value: &Value) -> Result<usize> {
+    if let Some(len) = fixed_len_for_type(ty) {
+        return Ok(len);
+    }
+    let len = match (ty, value) {
+        (TypeDesc::String, Value::String(bytes)) => uvarint_len(bytes.len()) + bytes.len(),
+        (TypeDesc::FixedString { length }, Value::FixedString(bytes)) => {
+            if bytes.len() > *length {
+                return Err(Error::InvalidValue("FixedString length mismatch"));
+            }
+            *length
+        }
+        (TypeDesc::Decimal { size, .. }, _) => match size {
+            DecimalSize::Bits32 => 4,
+            DecimalSize::Bits64 => 8,
+            DecimalSize::Bits128 => 16,
+            DecimalSize::Bits256 => 32,
+        },
+        (TypeDesc::Nullable(_), Value::Nullable(None)) => 1,
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            1 + encoded_value_len(inner, value)?
+        }
+        (
+            TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. },
+            value,
+        ) => encoded_value_len(inner, value)?,
+        (TypeDesc::Array(inner), Value::Array(values)) => {
+            let mut len = uvarint_len(values.len());
+            for value in values {
+                len += encoded_value_len(inner, value)?;
+            }
+            len
+        }
+        (TypeDesc::Map { key, value }, Value::Map(entries)) => {
+            let mut len = uvarint_len(entries.len());
+            for (entry_key, entry_value) in entries {
+                len += encoded_value_len(key, entry_key)? + encoded_value_len(value, entry_value)?;
+            }
+            len
+        }
+        (TypeDesc::Tuple(items), Value::Tuple(values)) => {
+            if items.len() != values.len() {
+                return Err(Error::InvalidValue("Tuple length mismatch"));
+            }
+            let mut len = 0;
+            for (item, value) in items.iter().zip(values) {
+                len += encoded_value_len(&item.ty, value)?;
+            }
+            len
+        }
+        (TypeDesc::AggregateFunction { .. }, Value::AggregateState(state)) => state.len(),
+        (TypeDesc::Nothing, Value::Nothing) => 0,
+        (ty, value) => {
+            let mut counter = ByteCounter(0);
+            let options = WriteOptions {
+                pad_fixed_strings: true,
+            };
+            write_value(ty, value, options, &mut counter)?;
+            counter.0
+        }
+    };
+    Ok(len)
+}
+
+/// Returns the number of bytes [`write_nested_value`] produces.
+pub(crate) fn encoded_nested_len(
+    items: &[crate::types::TupleItem],
+    value: &Value,
+) -> Result<usize> {
+    let Value::Array(rows) = value else {
+        return Err(Error::TypeMismatch {
+            expected: "Array(Tuple(...))".to_string(),
+            actual: value.type_name().to_string(),
+        });
+    };
+    let mut len = uvarint_len(rows.len()) * items.len();
+    for row in rows {
+        let Value::Tuple(values) = row else {
+            return Err(Error::TypeMismatch {
+                expected: "Tuple".to_string(),
+                actual: row.type_name().to_string(),
+            });
+        };
+        if values.len() != items.len() {
+            return Err(Error::InvalidValue("Nested tuple length mismatch"));
+        }
+        for (item, value) in items.iter().zip(values) {
+            len += encoded_value_len(&item.ty, value)?;
+        }
+    }
+    Ok(len)
+}
+
+/// Returns the encoded length of `value` as an unsigned varint.
+fn uvarint_len(value: usize) -> usize {
+    let bits = usize::BITS - (value | 1).leading_zeros();
+    bits.div_ceil(7) as usize
+}
+
+/// Writer that only counts the bytes written to it.
+struct ByteCounter(usize);
+
+impl Write for ByteCounter {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.0 += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes a `Nested` value as one array per field; the caller checked that
+/// every field is named.
 pub(crate) fn write_nested_value<W: Write + ?Sized>(
     items: &[crate::types::TupleItem],
     value: &Value,
+    options: WriteOptions,
     writer: &mut W,
 ) -> Result<()> {
     if items.is_empty() {
@@ -554,14 +947,9 @@ pub(crate) fn write_nested_value<W: Write + ?Sized>(
         }
     }
     for (item, column) in items.iter().zip(columns.into_iter()) {
-        if item.name.as_deref().unwrap_or("").is_empty() {
-            return Err(Error::InvalidValue(
-                "Nested fields must have names when writing",
-            ));
-        }
         let array_type = TypeDesc::Array(Box::new(item.ty.clone()));
         let array_value = Value::Array(column);
-        write_value(&array_type, &array_value, writer)?;
+        write_value(&array_type, &array_value, options, writer)?;
     }
     Ok(())
 }
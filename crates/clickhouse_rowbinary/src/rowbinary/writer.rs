@@ -2,30 +2,87 @@
 //! - `RowBinaryValueWriter` encodes rows from `Value`s.
 //! - `RowBinaryWriter` writes raw row bytes into a seekable Zstd stream.
 
-use std::io::{BufWriter, Write};
+use std::io::{self, BufWriter, Write};
 
 use zeekstd::{Encoder, seek_table::Format};
 
 use crate::{
     error::{Error, Result},
     io::{write_string, write_uvarint},
+    typed::ToRow,
     types::TypeDesc,
     value::Value,
 };
 
 use super::{
+    codec::{ColumnCodec, ColumnCodecs},
+    columnar::EncodeColumn,
     format::RowBinaryFormat,
     schema::{Row, Schema, ensure_nested_names, expand_schema_for_writing},
-    value_rw::{write_nested_value, write_value},
+    type_binary::encode_type_binary,
+    value_rw::{USE_DEFAULT, WriteOptions, write_nested_value, write_value},
 };
 
+/// When [`RowBinaryValueWriter`] flushes its inner writer on its own.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Only [`RowBinaryValueWriter::flush`] flushes.
+    #[default]
+    Manual,
+    /// Flush after every `n` rows.
+    EveryRows(u64),
+    /// Flush once at least `n` bytes were written since the last flush.
+    EveryBytes(u64),
+}
+
+/// Rows and bytes written by a batch call such as
+/// [`RowBinaryValueWriter::write_batch`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WriteStats {
+    /// Rows written.
+    pub rows: u64,
+    /// Encoded bytes written, excluding the header.
+    pub bytes: u64,
+}
+
 /// `RowBinary` writer that streams rows into the provided writer.
+///
+/// Any [`Write`] sink works; wrap unbuffered sinks such as files and
+/// sockets with [`Self::new_buffered`] and choose a [`FlushPolicy`] to
+/// control when buffered data reaches them.
 pub struct RowBinaryValueWriter<W: Write> {
     inner: W,
     format: RowBinaryFormat,
     schema: Schema,
     wire_schema: Schema,
+    codecs: ColumnCodecs,
     header_written: bool,
+    /// Header types are written as binary type descriptors.
+    binary_types: bool,
+    options: WriteOptions,
+    flush_policy: FlushPolicy,
+    /// Rows written since the last flush.
+    pending_rows: u64,
+    /// Bytes written since the last flush.
+    pending_bytes: u64,
+}
+
+/// Writer adapter that counts the bytes passing through it.
+struct CountingWriter<'a, W: ?Sized> {
+    writer: &'a mut W,
+    count: &'a mut u64,
+}
+
+impl<W: Write + ?Sized> Write for CountingWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let n = self.writer.write(buf)?;
+        *self.count += n as u64;
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
 }
 
 impl<W: Write> RowBinaryValueWriter<W> {
@@ -38,7 +95,13 @@ impl<W: Write> RowBinaryValueWriter<W> {
             format,
             schema,
             wire_schema,
+            codecs: ColumnCodecs::default(),
             header_written: false,
+            binary_types: false,
+            options: WriteOptions::default(),
+            flush_policy: FlushPolicy::default(),
+            pending_rows: 0,
+            pending_bytes: 0,
         }
     }
 
@@ -52,6 +115,46 @@ impl<W: Write> RowBinaryValueWriter<W> {
         RowBinaryValueWriter::new(BufWriter::new(inner), format, schema)
     }
 
+    /// Controls how `FixedString` values shorter than the declared length
+    /// are written.
+    ///
+    /// When enabled, short values are zero-padded to the declared length
+    /// (matching `ClickHouse` text input semantics). When disabled (the
+    /// default), any length mismatch is rejected. Longer values are always
+    /// rejected.
+    pub fn set_fixed_string_padding(&mut self, enabled: bool) {
+        self.options.pad_fixed_strings = enabled;
+    }
+
+    /// Controls how `RowBinaryWithNamesAndTypes` header types are written.
+    ///
+    /// When enabled, each type is written as a binary type descriptor
+    /// instead of its name, as expected by the server with
+    /// `input_format_binary_decode_types_in_binary_format` set.
+    pub fn set_binary_types(&mut self, enabled: bool) {
+        self.binary_types = enabled;
+    }
+
+    /// Registers `codec` to encode every value written to column `name`.
+    ///
+    /// Rows passed to the writer then hold decoded values for that column,
+    /// and [`Self::write_typed`] converts into the codec type.
+    /// [`Self::write_columns`] and [`Self::write_row_bytes`] bypass codecs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when the schema has no such column.
+    pub fn set_column_codec(&mut self, name: &str, codec: ColumnCodec) -> Result<()> {
+        self.codecs.insert(&self.schema, name, codec)
+    }
+
+    /// Sets when the inner writer is flushed automatically.
+    ///
+    /// Policies are checked after each row, so a flush never splits a row.
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        self.flush_policy = policy;
+    }
+
     /// Writes the `RowBinary` header (names/types) when required.
     ///
     /// # Errors
@@ -62,20 +165,30 @@ impl<W: Write> RowBinaryValueWriter<W> {
             return Ok(());
         }
         ensure_nested_names(&self.schema)?;
+        let mut written = 0;
+        let mut out = CountingWriter {
+            writer: &mut self.inner,
+            count: &mut written,
+        };
         match self.format {
-            RowBinaryFormat::RowBinary => {}
+            RowBinaryFormat::RowBinary | RowBinaryFormat::RowBinaryWithDefaults => {}
             RowBinaryFormat::RowBinaryWithNames | RowBinaryFormat::RowBinaryWithNamesAndTypes => {
-                write_uvarint(self.wire_schema.len() as u64, &mut self.inner)?;
+                write_uvarint(self.wire_schema.len() as u64, &mut out)?;
                 for field in self.wire_schema.fields() {
-                    write_string(&field.name, &mut self.inner)?;
+                    write_string(&field.name, &mut out)?;
                 }
                 if self.format == RowBinaryFormat::RowBinaryWithNamesAndTypes {
                     for field in self.wire_schema.fields() {
-                        write_string(&field.ty.type_name(), &mut self.inner)?;
+                        if self.binary_types {
+                            encode_type_binary(&field.ty, &mut out)?;
+                        } else {
+                            write_string(&field.ty.type_name(), &mut out)?;
+                        }
                     }
                 }
             }
         }
+        self.pending_bytes += written;
         self.header_written = true;
         Ok(())
     }
@@ -91,15 +204,7 @@ impl<W: Write> RowBinaryValueWriter<W> {
         if row.len() != self.schema.len() {
             return Err(Error::InvalidValue("row length does not match schema"));
         }
-        for (field, value) in self.schema.fields().iter().zip(row.iter()) {
-            match &field.ty {
-                TypeDesc::Nested(items) => {
-                    write_nested_value(items, value, &mut self.inner)?;
-                }
-                _ => write_value(&field.ty, value, &mut self.inner)?,
-            }
-        }
-        Ok(())
+        self.encode_row(row).map(drop)
     }
 
     /// Writes a single owned row.
@@ -114,22 +219,169 @@ impl<W: Write> RowBinaryValueWriter<W> {
         self.write_row(&row)
     }
 
-    /// Writes multiple rows.
+    /// Writes a single `RowBinaryWithDefaults` row, where `None` omits the
+    /// value so the server applies the column default:
+    ///
+    /// ```
+    /// # use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
+    /// let schema = Schema::from_type_strings(&[("id", "UInt8"), ("note", "String")])?;
+    /// let mut writer =
+    ///     RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinaryWithDefaults, schema);
+    /// writer.write_row_with_defaults(&[Some(Value::UInt8(7)), None])?;
+    /// assert_eq!(writer.into_inner(), [0, 7, 1]);
+    /// # Ok::<(), clickhouse_rowbinary::Error>(())
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnsupportedCombination`] for other formats, and
+    /// [`crate::error::Error`] when the row is invalid or IO fails.
+    pub fn write_row_with_defaults(&mut self, row: &[Option<Value>]) -> Result<()> {
+        if !self.format.has_default_flags() {
+            return Err(Error::UnsupportedCombination(format!(
+                "{} has no default values",
+                self.format
+            )));
+        }
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.encode_fields(row.iter().map(Option::as_ref)).map(drop)
+    }
+
+    /// Encodes `value` with [`ToRow`] and writes it.
+    ///
+    /// Call [`Self::write_header`] before writing the first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when `value` does not match the schema
+    /// or IO fails.
+    pub fn write_typed<T: ToRow + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let row = value.to_row(self.codecs.schema(&self.schema))?;
+        self.encode_row(&row).map(drop)
+    }
+
+    /// Serializes `value` into a row and writes it; see [`crate::to_row`]
+    /// for how `value` maps onto the schema columns.
     ///
     /// Call [`Self::write_header`] before writing the first row.
     ///
     /// # Errors
     ///
+    /// Returns [`crate::error::Error`] when `value` does not match the schema
+    /// or IO fails.
+    #[cfg(feature = "serde")]
+    pub fn serialize<T: serde::Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let row = crate::serde::to_row(self.codecs.schema(&self.schema), value)?;
+        self.encode_row(&row).map(drop)
+    }
+
+    /// Writes multiple rows.
+    ///
+    /// Call [`Self::write_header`] before writing the first row. Rows
+    /// written before a failing row stay written.
+    ///
+    /// # Errors
+    ///
     /// Returns [`crate::error::Error`] when any row is invalid or IO fails.
     pub fn write_rows<I, R>(&mut self, rows: I) -> Result<()>
     where
         I: IntoIterator<Item = R>,
         R: AsRef<[Value]>,
     {
+        self.write_batch(rows).map(drop)
+    }
+
+    /// Writes multiple rows and returns how many rows and bytes were
+    /// written.
+    ///
+    /// The schema is checked once for the batch, before any row is
+    /// written, so each row only has its length and values checked.
+    /// Call [`Self::write_header`] before writing the first row. Rows
+    /// written before a failing row stay written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the schema cannot be written in
+    /// this format, any row is invalid or IO fails.
+    pub fn write_batch<I, R>(&mut self, rows: I) -> Result<WriteStats>
+    where
+        I: IntoIterator<Item = R>,
+        R: AsRef<[Value]>,
+    {
+        self.check_schema()?;
+        let columns = self.schema.len();
+        let mut stats = WriteStats::default();
         for row in rows {
-            self.write_row(row.as_ref())?;
+            let row = row.as_ref();
+            if row.len() != columns {
+                return Err(Error::InvalidValue("row length does not match schema"));
+            }
+            stats.bytes += self.encode_checked_fields(row.iter().map(Some))?;
+            stats.rows += 1;
         }
-        Ok(())
+        Ok(stats)
+    }
+
+    /// Writes rows from per-column data, one entry per schema field.
+    ///
+    /// Column types are checked against the schema once, then the columns
+    /// are interleaved into rows without building [`Value`]s:
+    ///
+    /// ```
+    /// # use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema};
+    /// let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")])?;
+    /// let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema);
+    /// let ids = vec![1_u32, 2];
+    /// let names = vec!["a", "b"];
+    /// let stats = writer.write_columns(&[&ids, &names])?;
+    /// assert_eq!(stats.rows, 2);
+    /// # Ok::<(), clickhouse_rowbinary::Error>(())
+    /// ```
+    ///
+    /// Call [`Self::write_header`] before writing the first row.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the column count, lengths or
+    /// types do not match the schema, a value does not fit its column, or
+    /// IO fails.
+    pub fn write_columns(&mut self, columns: &[&dyn EncodeColumn]) -> Result<WriteStats> {
+        if columns.len() != self.schema.len() {
+            return Err(Error::InvalidValue("column count does not match schema"));
+        }
+        let rows = columns.first().map_or(0, |column| column.len());
+        for (column, field) in columns.iter().zip(self.schema.fields()) {
+            if column.len() != rows {
+                return Err(Error::InvalidValue("column lengths differ"));
+            }
+            if matches!(field.ty, TypeDesc::Nested(_)) {
+                return Err(Error::UnsupportedCombination(
+                    "columnar writes do not support Nested columns".into(),
+                ));
+            }
+            column.check_type(&field.ty)?;
+        }
+        let defaults = self.format.has_default_flags();
+        let mut stats = WriteStats::default();
+        for index in 0..rows {
+            let mut written = 0;
+            let mut out = CountingWriter {
+                writer: &mut self.inner,
+                count: &mut written,
+            };
+            for (column, field) in columns.iter().zip(self.schema.fields()) {
+                if defaults {
+                    out.write_all(&[0])?;
+                }
+                column.write_value(index, &field.ty, &mut out)?;
+            }
+            self.row_written(written)?;
+            stats.rows += 1;
+            stats.bytes += written;
+        }
+        Ok(stats)
     }
 
     /// Writes a raw `RowBinary` row payload (without header).
@@ -141,7 +393,7 @@ impl<W: Write> RowBinaryValueWriter<W> {
     /// Returns [`crate::error::Error`] when the underlying writer fails.
     pub fn write_row_bytes(&mut self, row: &[u8]) -> Result<()> {
         self.inner.write_all(row)?;
-        Ok(())
+        self.row_written(row.len() as u64)
     }
 
     /// Flushes the underlying writer.
@@ -150,7 +402,23 @@ impl<W: Write> RowBinaryValueWriter<W> {
     ///
     /// Returns [`crate::error::Error`] when the flush fails.
     pub fn flush(&mut self) -> Result<()> {
-        self.inner.flush().map_err(Error::Io)
+        self.inner.flush().map_err(Error::Io)?;
+        self.pending_rows = 0;
+        self.pending_bytes = 0;
+        Ok(())
+    }
+
+    /// Returns a reference to the inner writer.
+    #[must_use]
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner writer.
+    ///
+    /// Writing to it directly corrupts the row stream.
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
     }
 
     /// Returns the inner writer.
@@ -158,10 +426,16 @@ impl<W: Write> RowBinaryValueWriter<W> {
         self.inner
     }
 
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
     /// Replaces the inner writer and resets header state.
     pub fn reset(&mut self, inner: W) {
         self.inner = inner;
-        self.header_written = false;
+        self.reset_state();
     }
 
     /// Takes the inner writer, replacing it with `Default::default()`.
@@ -169,9 +443,125 @@ impl<W: Write> RowBinaryValueWriter<W> {
     where
         W: Default,
     {
-        self.header_written = false;
+        self.reset_state();
         std::mem::take(&mut self.inner)
     }
+
+    fn reset_state(&mut self) {
+        self.header_written = false;
+        self.pending_rows = 0;
+        self.pending_bytes = 0;
+    }
+
+    /// Encodes a row whose length was checked, returning its size.
+    fn encode_row(&mut self, row: &[Value]) -> Result<u64> {
+        self.encode_fields(row.iter().map(Some))
+    }
+
+    /// Encodes the fields of a row whose length was checked, returning its
+    /// size. `None` fields are written as "use default" flags, which only
+    /// `RowBinaryWithDefaults` rows carry.
+    fn encode_fields<'v, I>(&mut self, row: I) -> Result<u64>
+    where
+        I: Iterator<Item = Option<&'v Value>>,
+    {
+        self.check_schema()?;
+        self.encode_checked_fields(row)
+    }
+
+    /// Checks what rows cannot be written under the schema whatever their
+    /// values, which batch writes do once rather than per row.
+    fn check_schema(&self) -> Result<()> {
+        let nested = self
+            .schema
+            .fields()
+            .iter()
+            .any(|field| matches!(field.ty, TypeDesc::Nested(_)));
+        if nested && self.format.has_default_flags() {
+            return Err(Error::UnsupportedCombination(
+                "RowBinaryWithDefaults does not support Nested columns".into(),
+            ));
+        }
+        ensure_nested_names(&self.schema)
+    }
+
+    /// Encodes a row as [`Self::encode_fields`] does, once
+    /// [`Self::check_schema`] passed.
+    fn encode_checked_fields<'v, I>(&mut self, row: I) -> Result<u64>
+    where
+        I: Iterator<Item = Option<&'v Value>>,
+    {
+        let defaults = self.format.has_default_flags();
+        let mut written = 0;
+        let mut out = CountingWriter {
+            writer: &mut self.inner,
+            count: &mut written,
+        };
+        for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+            let Some(value) = value else {
+                out.write_all(&[USE_DEFAULT])?;
+                continue;
+            };
+            if defaults {
+                out.write_all(&[0])?;
+            }
+            let value = self.codecs.encode(index, value)?;
+            match &field.ty {
+                TypeDesc::Nested(items) => {
+                    write_nested_value(items, &value, self.options, &mut out)?;
+                }
+                _ => write_value(&field.ty, &value, self.options, &mut out)?,
+            }
+        }
+        self.row_written(written)?;
+        Ok(written)
+    }
+
+    /// Records a completed row of `bytes` bytes and applies the flush
+    /// policy.
+    fn row_written(&mut self, bytes: u64) -> Result<()> {
+        self.pending_rows += 1;
+        self.pending_bytes += bytes;
+        let due = match self.flush_policy {
+            FlushPolicy::Manual => false,
+            FlushPolicy::EveryRows(rows) => self.pending_rows >= rows,
+            FlushPolicy::EveryBytes(bytes) => self.pending_bytes >= bytes,
+        };
+        if due { self.flush() } else { Ok(()) }
+    }
+}
+
+impl RowBinaryValueWriter<Vec<u8>> {
+    /// Clears the buffer, keeping its allocation, and resets header state
+    /// so the next batch starts a new payload.
+    pub fn clear(&mut self) {
+        self.inner.clear();
+        self.reset_state();
+    }
+
+    /// Moves the encoded payload into `out` and keeps encoding into the
+    /// previous allocation of `out`, cleared. Header state is reset.
+    ///
+    /// Swapping the same buffer back and forth lets batches be encoded and
+    /// sent without allocating a new payload each time:
+    ///
+    /// ```
+    /// # use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
+    /// let schema = Schema::from_type_strings(&[("id", "UInt8")])?;
+    /// let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema);
+    /// let mut payload = Vec::new();
+    /// for batch in [[1_u8, 2], [3, 4]] {
+    ///     writer.write_header()?;
+    ///     writer.write_rows(batch.map(|id| [Value::UInt8(id)]))?;
+    ///     writer.swap_buffer(&mut payload);
+    ///     assert_eq!(payload, batch);
+    /// }
+    /// # Ok::<(), clickhouse_rowbinary::Error>(())
+    /// ```
+    pub fn swap_buffer(&mut self, out: &mut Vec<u8>) {
+        std::mem::swap(&mut self.inner, out);
+        self.clear();
+    }
 }
 
 /// Seekable Zstd writer that produces `RowBinary` payloads.
@@ -213,7 +603,7 @@ impl<W: Write> RowBinaryWriter<W> {
         ensure_nested_names(schema)?;
         let wire_schema = expand_schema_for_writing(schema);
         match self.format {
-            RowBinaryFormat::RowBinary => {}
+            RowBinaryFormat::RowBinary | RowBinaryFormat::RowBinaryWithDefaults => {}
             RowBinaryFormat::RowBinaryWithNames | RowBinaryFormat::RowBinaryWithNamesAndTypes => {
                 crate::io::write_uvarint(wire_schema.len() as u64, &mut self.encoder)?;
                 for field in wire_schema.fields() {
@@ -0,0 +1,703 @@
+//! Deserializing decoded rows into user types.
+
+use std::slice;
+
+use serde::{
+    Deserialize,
+    de::{
+        self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+        value::BorrowedStrDeserializer,
+    },
+    forward_to_deserialize_any,
+};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Field, Schema},
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+/// Deserializes a decoded row into `T`.
+///
+/// Structs are matched to columns by name, so field order does not matter
+/// and columns without a matching field are ignored. Fields marked
+/// `#[serde(default)]` may have no column and then take their default
+/// value. Tuples and tuple structs are matched by position.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when the row does not match the schema
+/// or a value cannot be deserialized into its field.
+pub fn from_row<'de, T: Deserialize<'de>>(schema: &'de Schema, row: &'de [Value]) -> Result<T> {
+    if row.len() != schema.len() {
+        return Err(Error::InvalidValue("row length does not match schema"));
+    }
+    T::deserialize(RowDeserializer {
+        fields: schema.fields(),
+        values: row,
+    })
+}
+
+/// Deserializes a single decoded value of type `ty` into `T`.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when the value cannot be deserialized
+/// into `T`.
+pub fn from_value<'de, T: Deserialize<'de>>(ty: &'de TypeDesc, value: &'de Value) -> Result<T> {
+    T::deserialize(ValueDeserializer::new(Context::new(ty), value))
+}
+
+/// Type information available for a value being deserialized.
+#[derive(Clone, Copy)]
+enum Context<'de> {
+    /// No type information; the value alone drives deserialization.
+    Unknown,
+    /// Declared type of the value.
+    Type(&'de TypeDesc),
+    /// Element types of a `Nested` row, which is stored as a tuple.
+    Items(&'de [TupleItem]),
+}
+
+impl<'de> Context<'de> {
+    fn new(ty: &'de TypeDesc) -> Self {
+        let mut ty = ty;
+        while let TypeDesc::LowCardinality(inner)
+        | TypeDesc::SimpleAggregateFunction { ty: inner, .. } = ty
+        {
+            ty = inner;
+        }
+        Context::Type(ty)
+    }
+
+    fn ty(self) -> Option<&'de TypeDesc> {
+        match self {
+            Context::Type(ty) => Some(ty),
+            Context::Unknown | Context::Items(_) => None,
+        }
+    }
+
+    /// Context of the elements of an `Array` or `Nested` value.
+    fn element(self) -> Self {
+        match self.ty() {
+            Some(TypeDesc::Array(inner)) => Context::new(inner),
+            Some(TypeDesc::Nested(items)) => Context::Items(items),
+            _ => Context::Unknown,
+        }
+    }
+
+    /// Element types of a `Tuple` value.
+    fn items(self) -> Option<&'de [TupleItem]> {
+        match self {
+            Context::Type(TypeDesc::Tuple(items)) => Some(items),
+            Context::Items(items) => Some(items),
+            _ => None,
+        }
+    }
+}
+
+struct RowDeserializer<'de> {
+    fields: &'de [Field],
+    values: &'de [Value],
+}
+
+impl<'de> Deserializer<'de> for RowDeserializer<'de> {
+    type Error = Error;
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct map struct enum identifier
+        ignored_any
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_map(FieldMap {
+            names: self.fields.iter().map(|field| field.name.as_str()),
+            values: self
+                .fields
+                .iter()
+                .zip(self.values)
+                .map(|(field, value)| ValueDeserializer::new(Context::new(&field.ty), value)),
+            pending: None,
+        })
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        visitor.visit_seq(ValueSeq {
+            values: self
+                .fields
+                .iter()
+                .zip(self.values)
+                .map(|(field, value)| ValueDeserializer::new(Context::new(&field.ty), value)),
+        })
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _len: usize, visitor: V) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value> {
+        self.deserialize_seq(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+}
+
+/// Deserializer for a single decoded value.
+struct ValueDeserializer<'de> {
+    context: Context<'de>,
+    value: &'de Value,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    fn new(context: Context<'de>, value: &'de Value) -> Self {
+        Self { context, value }
+    }
+
+    /// Returns the inner value of `Nullable`, `Variant` and `Dynamic`
+    /// wrappers, or `None` for their null forms.
+    fn resolve(self) -> Option<Self> {
+        let ty = self.context.ty();
+        match self.value {
+            Value::Nullable(None) | Value::VariantNull | Value::DynamicNull => None,
+            Value::Nullable(Some(inner)) => {
+                let context = match ty {
+                    Some(TypeDesc::Nullable(inner_ty)) => Context::new(inner_ty),
+                    _ => Context::Unknown,
+                };
+                Self::new(context, inner).resolve()
+            }
+            Value::Variant { index, value } => {
+                let context = match ty {
+                    Some(TypeDesc::Variant(variants)) => variants
+                        .get(usize::from(*index))
+                        .map_or(Context::Unknown, Context::new),
+                    _ => Context::Unknown,
+                };
+                Self::new(context, value).resolve()
+            }
+            Value::Dynamic { ty, value } => Self::new(Context::new(ty), value).resolve(),
+            _ => Some(self),
+        }
+    }
+
+    fn enum_name(&self) -> Option<&'de str> {
+        let discriminant = match self.value {
+            Value::Enum8(value) => i16::from(*value),
+            Value::Enum16(value) => *value,
+            _ => return None,
+        };
+        self.context.ty()?.enum_name(discriminant)
+    }
+
+    fn mismatch(&self, expected: &str) -> Error {
+        Error::TypeMismatch {
+            expected: expected.to_string(),
+            actual: self.value.type_name().to_string(),
+        }
+    }
+}
+
+impl<'de> Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char bytes
+        byte_buf unit_struct seq tuple tuple_struct map identifier ignored_any
+    }
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let Some(this) = self.resolve() else {
+            return visitor.visit_none();
+        };
+        if let Some(name) = this.enum_name() {
+            return visitor.visit_borrowed_str(name);
+        }
+        let context = this.context;
+        match this.value {
+            Value::Nothing => visitor.visit_unit(),
+            Value::UInt8(value) => visitor.visit_u8(*value),
+            Value::Bool(value) => visitor.visit_bool(*value),
+            Value::UInt16(value) | Value::Date(value) => visitor.visit_u16(*value),
+            Value::UInt32(value) | Value::DateTime(value) => visitor.visit_u32(*value),
+            Value::UInt64(value) => visitor.visit_u64(*value),
+            Value::UInt128(value) => visitor.visit_u128(*value),
+            Value::Int8(value) | Value::Enum8(value) => visitor.visit_i8(*value),
+            Value::Int16(value) | Value::Enum16(value) => visitor.visit_i16(*value),
+            Value::Int32(value) | Value::Date32(value) | Value::Decimal32(value) => {
+                visitor.visit_i32(*value)
+            }
+            Value::Int64(value) | Value::DateTime64(value) | Value::Decimal64(value) => {
+                visitor.visit_i64(*value)
+            }
+            Value::Int128(value) | Value::Decimal128(value) => visitor.visit_i128(*value),
+            Value::Float32(value) | Value::Float16(value) | Value::BFloat16(value) => {
+                visitor.visit_f32(*value)
+            }
+            Value::Float64(value) => visitor.visit_f64(*value),
+            Value::String(bytes) | Value::FixedString(bytes) => match std::str::from_utf8(bytes) {
+                Ok(text) => visitor.visit_borrowed_str(text),
+                Err(_) => visitor.visit_borrowed_bytes(bytes),
+            },
+            Value::UInt256(bytes) | Value::Int256(bytes) | Value::Decimal256(bytes) => {
+                visitor.visit_borrowed_bytes(bytes)
+            }
+            Value::AggregateState(bytes) => visitor.visit_borrowed_bytes(bytes),
+            Value::Uuid(value) => visitor.visit_string(value.to_string()),
+            Value::Ipv4(value) => visitor.visit_string(value.to_string()),
+            Value::Ipv6(value) => visitor.visit_string(value.to_string()),
+            Value::Array(values) => {
+                let element = context.element();
+                visitor.visit_seq(ValueSeq {
+                    values: values
+                        .iter()
+                        .map(|value| ValueDeserializer::new(element, value)),
+                })
+            }
+            Value::Tuple(values) => visitor.visit_seq(ValueSeq {
+                values: TupleValues::new(context.items(), values),
+            }),
+            Value::Map(entries) => {
+                let (key, value) = match context.ty() {
+                    Some(TypeDesc::Map { key, value }) => (Context::new(key), Context::new(value)),
+                    _ => (Context::Unknown, Context::Unknown),
+                };
+                visitor.visit_map(EntryMap {
+                    entries: entries.iter(),
+                    key,
+                    value,
+                    pending: None,
+                })
+            }
+            Value::JsonObject(entries) => {
+                let typed_paths = match context.ty() {
+                    Some(TypeDesc::Json { typed_paths, .. }) => typed_paths.as_slice(),
+                    _ => &[],
+                };
+                visitor.visit_map(FieldMap {
+                    names: entries.iter().map(|(path, _)| path.as_str()),
+                    values: entries.iter().map(move |(path, value)| {
+                        let context = typed_paths
+                            .iter()
+                            .find(|(name, _)| name == path)
+                            .map_or(Context::Unknown, |(_, ty)| Context::new(ty));
+                        ValueDeserializer::new(context, value)
+                    }),
+                    pending: None,
+                })
+            }
+            Value::Nullable(_)
+            | Value::Variant { .. }
+            | Value::VariantNull
+            | Value::Dynamic { .. }
+            | Value::DynamicNull => Err(Error::Internal("unwrapped value still wrapped")),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.resolve() {
+            Some(this) => visitor.visit_some(this),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        match self.resolve() {
+            None => visitor.visit_unit(),
+            Some(this) if matches!(this.value, Value::Nothing) => visitor.visit_unit(),
+            Some(this) => Err(this.mismatch("Nothing")),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        let Some(this) = self.resolve() else {
+            return Err(Error::TypeMismatch {
+                expected: "String".to_string(),
+                actual: "NULL".to_string(),
+            });
+        };
+        match this.value {
+            Value::String(bytes) | Value::FixedString(bytes) => {
+                let text = std::str::from_utf8(bytes)
+                    .map_err(|_| Error::InvalidValue("invalid UTF-8 string"))?;
+                visitor.visit_borrowed_str(text)
+            }
+            _ => this.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let Some(this) = self.resolve() else {
+            return Err(Error::TypeMismatch {
+                expected: "Enum".to_string(),
+                actual: "NULL".to_string(),
+            });
+        };
+        let name = match this.value {
+            Value::String(bytes) | Value::FixedString(bytes) => std::str::from_utf8(bytes)
+                .map_err(|_| Error::InvalidValue("invalid UTF-8 string"))?,
+            _ => this.enum_name().ok_or_else(|| this.mismatch("Enum"))?,
+        };
+        visitor.visit_enum(IntoDeserializer::<Error>::into_deserializer(name))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value> {
+        let Some(this) = self.resolve() else {
+            return Err(Error::TypeMismatch {
+                expected: "Tuple".to_string(),
+                actual: "NULL".to_string(),
+            });
+        };
+        if let (Value::Tuple(values), Some(items)) = (this.value, this.context.items())
+            && items.len() == values.len()
+            && items.iter().all(|item| item.name.is_some())
+        {
+            return visitor.visit_map(FieldMap {
+                names: items
+                    .iter()
+                    .map(|item| item.name.as_deref().unwrap_or_default()),
+                values: items
+                    .iter()
+                    .zip(values)
+                    .map(|(item, value)| ValueDeserializer::new(Context::new(&item.ty), value)),
+                pending: None,
+            });
+        }
+        this.deserialize_any(visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value> {
+        visitor.visit_newtype_struct(self)
+    }
+}
+
+/// Tuple elements paired with their declared types, when known.
+struct TupleValues<'de> {
+    items: Option<slice::Iter<'de, TupleItem>>,
+    values: slice::Iter<'de, Value>,
+}
+
+impl<'de> TupleValues<'de> {
+    fn new(items: Option<&'de [TupleItem]>, values: &'de [Value]) -> Self {
+        Self {
+            items: items
+                .filter(|items| items.len() == values.len())
+                .map(<[TupleItem]>::iter),
+            values: values.iter(),
+        }
+    }
+}
+
+impl<'de> Iterator for TupleValues<'de> {
+    type Item = ValueDeserializer<'de>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let value = self.values.next()?;
+        let context = self
+            .items
+            .as_mut()
+            .and_then(Iterator::next)
+            .map_or(Context::Unknown, |item| Context::new(&item.ty));
+        Some(ValueDeserializer::new(context, value))
+    }
+}
+
+struct ValueSeq<I> {
+    values: I,
+}
+
+impl<'de, I: Iterator<Item = ValueDeserializer<'de>>> SeqAccess<'de> for ValueSeq<I> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<Option<T::Value>> {
+        self.values
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+}
+
+/// Map with string keys, used for rows, named tuples and JSON objects.
+struct FieldMap<'de, N, I> {
+    names: N,
+    values: I,
+    pending: Option<ValueDeserializer<'de>>,
+}
+
+impl<'de, N, I> MapAccess<'de> for FieldMap<'de, N, I>
+where
+    N: Iterator<Item = &'de str>,
+    I: Iterator<Item = ValueDeserializer<'de>>,
+{
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let (Some(name), Some(value)) = (self.names.next(), self.values.next()) else {
+            return Ok(None);
+        };
+        self.pending = Some(value);
+        seed.deserialize(BorrowedStrDeserializer::new(name))
+            .map(Some)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value> {
+        let value = self
+            .pending
+            .take()
+            .ok_or(Error::Internal("map value requested before its key"))?;
+        seed.deserialize(value)
+    }
+}
+
+/// Map over the entries of a `Map` value.
+struct EntryMap<'de> {
+    entries: slice::Iter<'de, (Value, Value)>,
+    key: Context<'de>,
+    value: Context<'de>,
+    pending: Option<&'de Value>,
+}
+
+impl<'de> MapAccess<'de> for EntryMap<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(&mut self, seed: K) -> Result<Option<K::Value>> {
+        let Some((key, value)) = self.entries.next() else {
+            return Ok(None);
+        };
+        self.pending = Some(value);
+        seed.deserialize(ValueDeserializer::new(self.key, key))
+            .map(Some)
+    }
+
+    fn next_value_seed<T: DeserializeSeed<'de>>(&mut self, seed: T) -> Result<T::Value> {
+        let value = self
+            .pending
+            .take()
+            .ok_or(Error::Internal("map value requested before its key"))?;
+        seed.deserialize(ValueDeserializer::new(self.value, value))
+    }
+}
+
+impl de::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::Deserialize;
+
+    use super::from_row;
+    use crate::{
+        error::Error,
+        rowbinary::{RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Active,
+        Disabled,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq)]
+    struct Event<'a> {
+        name: &'a str,
+        id: u64,
+        status: Status,
+        score: Option<f32>,
+        origin: Point,
+        tags: Vec<String>,
+        attrs: BTreeMap<String, i64>,
+    }
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[
+            ("id", "UInt32"),
+            ("name", "LowCardinality(String)"),
+            ("status", "Enum8('active' = 1, 'disabled' = 2)"),
+            ("score", "Nullable(Float32)"),
+            ("origin", "Tuple(x Float64, y Float64)"),
+            ("tags", "Array(String)"),
+            ("attrs", "Map(String, Int64)"),
+            ("ignored", "UUID"),
+        ])
+        .unwrap()
+    }
+
+    fn row(id: u32, score: Option<f32>) -> Vec<Value> {
+        vec![
+            Value::UInt32(id),
+            Value::String(format!("event{id}").into_bytes()),
+            Value::Enum8(2),
+            Value::Nullable(score.map(|score| Box::new(Value::Float32(score)))),
+            Value::Tuple(vec![Value::Float64(1.5), Value::Float64(-2.0)]),
+            Value::Array(vec![Value::String(b"a".to_vec())]),
+            Value::Map(vec![(Value::String(b"k".to_vec()), Value::Int64(-7))]),
+            Value::Uuid(uuid::Uuid::nil()),
+        ]
+    }
+
+    #[test]
+    fn deserializes_structs_by_column_name() {
+        let schema = schema();
+        let row = row(3, Some(0.5));
+        let event: Event<'_> = from_row(&schema, &row).unwrap();
+        assert_eq!(
+            event,
+            Event {
+                name: "event3",
+                id: 3,
+                status: Status::Disabled,
+                score: Some(0.5),
+                origin: Point { x: 1.5, y: -2.0 },
+                tags: vec!["a".to_string()],
+                attrs: BTreeMap::from([("k".to_string(), -7)]),
+            }
+        );
+
+        let (id, name): (u32, String) = from_row(
+            &Schema::from_type_strings(&[("a", "UInt32"), ("b", "String")]).unwrap(),
+            &[Value::UInt32(1), Value::String(b"x".to_vec())],
+        )
+        .unwrap();
+        assert_eq!((id, name.as_str()), (1, "x"));
+    }
+
+    #[test]
+    fn reports_missing_and_mismatched_fields() {
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Missing {
+            id: u32,
+            absent: u32,
+        }
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Mismatched {
+            name: u32,
+        }
+        #[derive(Debug, Deserialize)]
+        #[allow(dead_code)]
+        struct Narrow {
+            id: u8,
+        }
+
+        let schema = schema();
+        let row = row(300, None);
+        let err = from_row::<Missing>(&schema, &row).unwrap_err();
+        assert!(matches!(&err, Error::Serde(message) if message.contains("absent")));
+        assert!(matches!(
+            from_row::<Mismatched>(&schema, &row),
+            Err(Error::Serde(_))
+        ));
+        assert!(matches!(
+            from_row::<Narrow>(&schema, &row),
+            Err(Error::Serde(_))
+        ));
+        assert!(from_row::<Narrow>(&schema, &row[..2]).is_err());
+    }
+
+    #[test]
+    fn serde_defaults_fill_missing_columns() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Versioned {
+            id: u32,
+            #[serde(default)]
+            added_later: Vec<String>,
+        }
+
+        let schema = schema();
+        let row = row(4, None);
+        assert_eq!(
+            from_row::<Versioned>(&schema, &row).unwrap(),
+            Versioned {
+                id: 4,
+                added_later: Vec::new(),
+            }
+        );
+    }
+
+    #[test]
+    fn reader_deserializes_rows() {
+        #[derive(Debug, Deserialize, PartialEq)]
+        struct Owned {
+            id: u32,
+            name: String,
+            score: Option<f32>,
+        }
+
+        let schema = schema();
+        let mut writer = RowBinaryValueWriter::new(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema,
+        );
+        writer.write_header().unwrap();
+        writer
+            .write_rows([row(1, None), row(2, Some(1.0))])
+            .unwrap();
+        let payload = writer.into_inner();
+
+        let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+        let mut decoded = Vec::new();
+        while let Some(owned) = reader.deserialize::<Owned>().unwrap() {
+            decoded.push(owned);
+        }
+        assert_eq!(
+            decoded,
+            [
+                Owned {
+                    id: 1,
+                    name: "event1".to_string(),
+                    score: None,
+                },
+                Owned {
+                    id: 2,
+                    name: "event2".to_string(),
+                    score: Some(1.0),
+                },
+            ]
+        );
+    }
+}
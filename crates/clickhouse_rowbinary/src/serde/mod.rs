@@ -0,0 +1,7 @@
+//! serde integration: rows to and from user types.
+
+mod de;
+mod ser;
+
+pub use de::{from_row, from_value};
+pub use ser::{to_row, to_value};
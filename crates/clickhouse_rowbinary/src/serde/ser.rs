@@ -0,0 +1,929 @@
+//! Serializing user types into rows.
+
+use serde::{
+    Serialize,
+    ser::{
+        self, Impossible, SerializeMap, SerializeSeq, SerializeStruct, SerializeTuple,
+        SerializeTupleStruct, Serializer,
+    },
+};
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Field, Row, Schema},
+    typed::into_value::{
+        MapTarget, Scalar, SeqTarget, Wrap, encode_null, encode_scalar, json_path_type, map_target,
+        resolve,
+    },
+    types::TypeDesc,
+    value::Value,
+};
+
+/// Serializes `value` into a row of `schema`.
+///
+/// Structs and maps are matched to columns by name: every column needs a
+/// field and every field needs a column. Tuples, tuple structs and
+/// sequences are matched by position. Each value is checked against its
+/// column type and converted to the matching [`Value`] variant, so a `u8`
+/// field fills a `UInt32` column and a string fills an `Enum8` column by
+/// name.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when a field has no column, a column has
+/// no field, or a value does not fit its column type.
+pub fn to_row<T: Serialize + ?Sized>(schema: &Schema, value: &T) -> Result<Row> {
+    value.serialize(RowSerializer {
+        fields: schema.fields(),
+    })
+}
+
+/// Serializes `value` into a [`Value`] of type `ty`, converting it the same
+/// way [`to_row`] converts a field.
+///
+/// # Errors
+///
+/// Returns [`crate::error::Error`] when the value does not fit `ty`.
+pub fn to_value<T: Serialize + ?Sized>(ty: &TypeDesc, value: &T) -> Result<Value> {
+    value.serialize(ValueSerializer::new(ty))
+}
+
+/// Prefixes an error with the column or tuple element it occurred in.
+fn in_slot(kind: &str, name: &str, err: &Error) -> Error {
+    Error::Serde(format!("{kind} `{name}`: {err}"))
+}
+
+struct RowSerializer<'a> {
+    fields: &'a [Field],
+}
+
+impl RowSerializer<'_> {
+    fn reject(kind: &str) -> Error {
+        Error::Serde(format!(
+            "cannot serialize a row from {kind}; expected a struct, map, tuple or sequence"
+        ))
+    }
+}
+
+impl<'a> Serializer for RowSerializer<'a> {
+    type Error = Error;
+    type Ok = Row;
+    type SerializeMap = NamedSlots<'a>;
+    type SerializeSeq = RowSeq<'a>;
+    type SerializeStruct = NamedSlots<'a>;
+    type SerializeStructVariant = Impossible<Row, Error>;
+    type SerializeTuple = RowSeq<'a>;
+    type SerializeTupleStruct = RowSeq<'a>;
+    type SerializeTupleVariant = Impossible<Row, Error>;
+
+    fn serialize_bool(self, _value: bool) -> Result<Row> {
+        Err(Self::reject("a bool"))
+    }
+
+    fn serialize_i8(self, _value: i8) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_i16(self, _value: i16) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_i32(self, _value: i32) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_i64(self, _value: i64) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_u8(self, _value: u8) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_u16(self, _value: u16) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_u32(self, _value: u32) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_u64(self, _value: u64) -> Result<Row> {
+        Err(Self::reject("an integer"))
+    }
+
+    fn serialize_f32(self, _value: f32) -> Result<Row> {
+        Err(Self::reject("a float"))
+    }
+
+    fn serialize_f64(self, _value: f64) -> Result<Row> {
+        Err(Self::reject("a float"))
+    }
+
+    fn serialize_char(self, _value: char) -> Result<Row> {
+        Err(Self::reject("a char"))
+    }
+
+    fn serialize_str(self, _value: &str) -> Result<Row> {
+        Err(Self::reject("a string"))
+    }
+
+    fn serialize_bytes(self, _value: &[u8]) -> Result<Row> {
+        Err(Self::reject("bytes"))
+    }
+
+    fn serialize_none(self) -> Result<Row> {
+        Err(Self::reject("None"))
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Row> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Row> {
+        Err(Self::reject("a unit"))
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Row> {
+        Err(Self::reject("a unit struct"))
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+    ) -> Result<Row> {
+        Err(Self::reject("an enum variant"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Row> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<Row> {
+        Err(Self::reject("an enum variant"))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<RowSeq<'a>> {
+        Ok(RowSeq {
+            fields: self.fields,
+            values: Vec::with_capacity(len.unwrap_or(self.fields.len())),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<RowSeq<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<RowSeq<'a>> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Self::reject("an enum variant"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<NamedSlots<'a>> {
+        Ok(NamedSlots::new(
+            "column",
+            self.fields
+                .iter()
+                .map(|field| (field.name.as_str(), &field.ty))
+                .collect(),
+        ))
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<NamedSlots<'a>> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Self::reject("an enum variant"))
+    }
+}
+
+/// Positional row built from a tuple or sequence.
+struct RowSeq<'a> {
+    fields: &'a [Field],
+    values: Vec<Value>,
+}
+
+impl SerializeSeq for RowSeq<'_> {
+    type Error = Error;
+    type Ok = Row;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let Some(field) = self.fields.get(self.values.len()) else {
+            return Err(Error::Serde(format!(
+                "row has more values than the schema has columns ({})",
+                self.fields.len()
+            )));
+        };
+        let value = value
+            .serialize(ValueSerializer::new(&field.ty))
+            .map_err(|err| in_slot("column", &field.name, &err))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Row> {
+        if self.values.len() != self.fields.len() {
+            return Err(Error::Serde(format!(
+                "row has {} values but the schema has {} columns",
+                self.values.len(),
+                self.fields.len()
+            )));
+        }
+        Ok(self.values)
+    }
+}
+
+impl SerializeTuple for RowSeq<'_> {
+    type Error = Error;
+    type Ok = Row;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Row> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for RowSeq<'_> {
+    type Error = Error;
+    type Ok = Row;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Row> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// Named slots filled from struct fields or map entries, used for rows and
+/// named tuples.
+struct NamedSlots<'a> {
+    kind: &'static str,
+    slots: Vec<(&'a str, &'a TypeDesc)>,
+    values: Vec<Option<Value>>,
+    pending: Option<usize>,
+}
+
+impl<'a> NamedSlots<'a> {
+    fn new(kind: &'static str, slots: Vec<(&'a str, &'a TypeDesc)>) -> Self {
+        Self {
+            kind,
+            values: vec![None; slots.len()],
+            slots,
+            pending: None,
+        }
+    }
+
+    fn slot(&self, name: &str) -> Result<usize> {
+        let index = self
+            .slots
+            .iter()
+            .position(|(slot, _)| *slot == name)
+            .ok_or_else(|| Error::Serde(format!("field `{name}` has no matching {}", self.kind)))?;
+        if self.values[index].is_some() {
+            return Err(Error::Serde(format!("duplicate field `{name}`")));
+        }
+        Ok(index)
+    }
+
+    fn fill<T: Serialize + ?Sized>(&mut self, index: usize, value: &T) -> Result<()> {
+        let (name, ty) = self.slots[index];
+        let value = value
+            .serialize(ValueSerializer::new(ty))
+            .map_err(|err| in_slot(self.kind, name, &err))?;
+        self.values[index] = Some(value);
+        Ok(())
+    }
+
+    fn finish(self) -> Result<Vec<Value>> {
+        self.values
+            .into_iter()
+            .zip(&self.slots)
+            .map(|(value, (name, _))| {
+                value.ok_or_else(|| {
+                    Error::Serde(format!("missing field for {} `{name}`", self.kind))
+                })
+            })
+            .collect()
+    }
+}
+
+impl SerializeMap for NamedSlots<'_> {
+    type Error = Error;
+    type Ok = Row;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        let name = key_string(key)?;
+        self.pending = Some(self.slot(&name)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let index = self
+            .pending
+            .take()
+            .ok_or(Error::Internal("map value serialized before its key"))?;
+        self.fill(index, value)
+    }
+
+    fn end(self) -> Result<Row> {
+        self.finish()
+    }
+}
+
+impl SerializeStruct for NamedSlots<'_> {
+    type Error = Error;
+    type Ok = Row;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        let index = self.slot(key)?;
+        self.fill(index, value)
+    }
+
+    fn end(self) -> Result<Row> {
+        self.finish()
+    }
+}
+
+/// Serializes a map key that must be a string.
+fn key_string<T: Serialize + ?Sized>(key: &T) -> Result<String> {
+    match key.serialize(ValueSerializer::new(&TypeDesc::String))? {
+        Value::String(bytes) => {
+            String::from_utf8(bytes).map_err(|_| Error::InvalidValue("invalid UTF-8 string"))
+        }
+        _ => Err(Error::Internal("String serializer produced a non-string")),
+    }
+}
+
+/// Serializer for a single value of a known column type.
+struct ValueSerializer<'a> {
+    ty: &'a TypeDesc,
+}
+
+impl<'a> ValueSerializer<'a> {
+    fn new(ty: &'a TypeDesc) -> Self {
+        Self { ty }
+    }
+
+    fn scalar(&self, scalar: Scalar<'_>) -> Result<Value> {
+        encode_scalar(self.ty, scalar)
+    }
+
+    fn seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>> {
+        Ok(SeqSerializer {
+            target: SeqTarget::new(self.ty, "sequence")?,
+            values: Vec::with_capacity(len.unwrap_or_default()),
+        })
+    }
+
+    fn map(self, actual: &str) -> Result<MapSerializer<'a>> {
+        let (target, wrap) = resolve(self.ty, actual, map_target)?;
+        let entries = match target {
+            MapTarget::Map { key, value } => Entries::Map {
+                key,
+                value,
+                entries: Vec::new(),
+                pending: None,
+            },
+            MapTarget::Json(typed_paths) => Entries::Json {
+                typed_paths,
+                entries: Vec::new(),
+                pending: None,
+            },
+            MapTarget::Named(items) => Entries::Named(NamedSlots::new(
+                "tuple element",
+                items
+                    .iter()
+                    .map(|item| (item.name.as_deref().unwrap_or_default(), &item.ty))
+                    .collect(),
+            )),
+        };
+        Ok(MapSerializer { entries, wrap })
+    }
+}
+
+impl<'a> Serializer for ValueSerializer<'a> {
+    type Error = Error;
+    type Ok = Value;
+    type SerializeMap = MapSerializer<'a>;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeStruct = MapSerializer<'a>;
+    type SerializeStructVariant = Impossible<Value, Error>;
+    type SerializeTuple = SeqSerializer<'a>;
+    type SerializeTupleStruct = SeqSerializer<'a>;
+    type SerializeTupleVariant = Impossible<Value, Error>;
+
+    fn serialize_bool(self, value: bool) -> Result<Value> {
+        self.scalar(Scalar::Bool(value))
+    }
+
+    fn serialize_i8(self, value: i8) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_i16(self, value: i16) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_i32(self, value: i32) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_i64(self, value: i64) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_i128(self, value: i128) -> Result<Value> {
+        self.scalar(Scalar::Int(value))
+    }
+
+    fn serialize_u8(self, value: u8) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_u16(self, value: u16) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_u32(self, value: u32) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_u64(self, value: u64) -> Result<Value> {
+        self.scalar(Scalar::Int(value.into()))
+    }
+
+    fn serialize_u128(self, value: u128) -> Result<Value> {
+        self.scalar(Scalar::unsigned(value))
+    }
+
+    fn serialize_f32(self, value: f32) -> Result<Value> {
+        self.scalar(Scalar::Float32(value))
+    }
+
+    fn serialize_f64(self, value: f64) -> Result<Value> {
+        self.scalar(Scalar::Float64(value))
+    }
+
+    fn serialize_char(self, value: char) -> Result<Value> {
+        self.scalar(Scalar::Str(value.encode_utf8(&mut [0; 4])))
+    }
+
+    fn serialize_str(self, value: &str) -> Result<Value> {
+        self.scalar(Scalar::Str(value))
+    }
+
+    fn serialize_bytes(self, value: &[u8]) -> Result<Value> {
+        self.scalar(Scalar::Bytes(value))
+    }
+
+    fn serialize_none(self) -> Result<Value> {
+        encode_null(self.ty)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value> {
+        encode_null(self.ty)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value> {
+        encode_null(self.ty)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value> {
+        self.scalar(Scalar::Str(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SeqSerializer<'a>> {
+        self.seq(len)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SeqSerializer<'a>> {
+        self.seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, len: usize) -> Result<SeqSerializer<'a>> {
+        self.seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant> {
+        Err(Error::Serde(format!(
+            "enum variant `{name}::{variant}` with several fields cannot be serialized"
+        )))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<MapSerializer<'a>> {
+        self.map("map")
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<MapSerializer<'a>> {
+        self.map("struct")
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant> {
+        Err(Error::Serde(format!(
+            "enum variant `{name}::{variant}` with several fields cannot be serialized"
+        )))
+    }
+}
+
+struct SeqSerializer<'a> {
+    target: SeqTarget<'a>,
+    values: Vec<Value>,
+}
+
+impl SerializeSeq for SeqSerializer<'_> {
+    type Error = Error;
+    type Ok = Value;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let ty = self.target.element(self.values.len())?;
+        let value = value.serialize(ValueSerializer::new(ty))?;
+        self.values.push(value);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        self.target.finish(self.values)
+    }
+}
+
+impl SerializeTuple for SeqSerializer<'_> {
+    type Error = Error;
+    type Ok = Value;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        SerializeSeq::end(self)
+    }
+}
+
+impl SerializeTupleStruct for SeqSerializer<'_> {
+    type Error = Error;
+    type Ok = Value;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value> {
+        SerializeSeq::end(self)
+    }
+}
+
+/// Entries collected for a map-like target.
+enum Entries<'a> {
+    Map {
+        key: &'a TypeDesc,
+        value: &'a TypeDesc,
+        entries: Vec<(Value, Value)>,
+        pending: Option<Value>,
+    },
+    Json {
+        typed_paths: &'a [(String, TypeDesc)],
+        entries: Vec<(String, Value)>,
+        pending: Option<String>,
+    },
+    Named(NamedSlots<'a>),
+}
+
+struct MapSerializer<'a> {
+    entries: Entries<'a>,
+    wrap: Wrap,
+}
+
+impl MapSerializer<'_> {
+    fn json_value<T: Serialize + ?Sized>(
+        typed_paths: &[(String, TypeDesc)],
+        path: &str,
+        value: &T,
+    ) -> Result<Value> {
+        value.serialize(ValueSerializer::new(json_path_type(typed_paths, path)))
+    }
+}
+
+impl SerializeMap for MapSerializer<'_> {
+    type Error = Error;
+    type Ok = Value;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<()> {
+        match &mut self.entries {
+            Entries::Map {
+                key: key_ty,
+                pending,
+                ..
+            } => *pending = Some(key.serialize(ValueSerializer::new(key_ty))?),
+            Entries::Json { pending, .. } => *pending = Some(key_string(key)?),
+            Entries::Named(slots) => SerializeMap::serialize_key(slots, key)?,
+        }
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<()> {
+        let missing_key = Error::Internal("map value serialized before its key");
+        match &mut self.entries {
+            Entries::Map {
+                value: value_ty,
+                entries,
+                pending,
+                ..
+            } => {
+                let key = pending.take().ok_or(missing_key)?;
+                entries.push((key, value.serialize(ValueSerializer::new(value_ty))?));
+            }
+            Entries::Json {
+                typed_paths,
+                entries,
+                pending,
+            } => {
+                let path = pending.take().ok_or(missing_key)?;
+                let value = Self::json_value(typed_paths, &path, value)?;
+                entries.push((path, value));
+            }
+            Entries::Named(slots) => SerializeMap::serialize_value(slots, value)?,
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        let value = match self.entries {
+            Entries::Map { entries, .. } => Value::Map(entries),
+            Entries::Json { entries, .. } => Value::JsonObject(entries),
+            Entries::Named(slots) => Value::Tuple(slots.finish()?),
+        };
+        Ok(self.wrap.apply(value))
+    }
+}
+
+impl SerializeStruct for MapSerializer<'_> {
+    type Error = Error;
+    type Ok = Value;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<()> {
+        match &mut self.entries {
+            Entries::Map {
+                key: key_ty,
+                value: value_ty,
+                entries,
+                ..
+            } => {
+                let key = encode_scalar(key_ty, Scalar::Str(key))?;
+                entries.push((key, value.serialize(ValueSerializer::new(value_ty))?));
+            }
+            Entries::Json {
+                typed_paths,
+                entries,
+                ..
+            } => {
+                let value = Self::json_value(typed_paths, key, value)?;
+                entries.push((key.to_string(), value));
+            }
+            Entries::Named(slots) => SerializeStruct::serialize_field(slots, key, value)?,
+        }
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value> {
+        SerializeMap::end(self)
+    }
+}
+
+impl ser::Error for Error {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        Error::Serde(msg.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::to_row;
+    use crate::{
+        error::Error,
+        rowbinary::{RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        value::Value,
+    };
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    #[serde(rename_all = "lowercase")]
+    enum Status {
+        Active,
+        Disabled,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Point {
+        x: f64,
+        y: f64,
+    }
+
+    #[derive(Debug, Deserialize, PartialEq, Serialize)]
+    struct Event {
+        name: String,
+        id: u32,
+        status: Status,
+        score: Option<f32>,
+        origin: Point,
+        tags: Vec<String>,
+        attrs: BTreeMap<String, i64>,
+    }
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[
+            ("id", "UInt64"),
+            ("name", "LowCardinality(String)"),
+            ("status", "Enum8('active' = 1, 'disabled' = 2)"),
+            ("score", "Nullable(Float32)"),
+            ("origin", "Tuple(x Float64, y Float64)"),
+            ("tags", "Array(String)"),
+            ("attrs", "Map(String, Int64)"),
+        ])
+        .unwrap()
+    }
+
+    fn event(id: u32, score: Option<f32>) -> Event {
+        Event {
+            name: format!("event{id}"),
+            id,
+            status: Status::Disabled,
+            score,
+            origin: Point { x: 1.5, y: -2.0 },
+            tags: vec!["a".to_string()],
+            attrs: BTreeMap::from([("k".to_string(), -7)]),
+        }
+    }
+
+    #[test]
+    fn serializes_structs_by_column_name() {
+        let row = to_row(&schema(), &event(3, None)).unwrap();
+        assert_eq!(
+            row,
+            [
+                Value::UInt64(3),
+                Value::String(b"event3".to_vec()),
+                Value::Enum8(2),
+                Value::Nullable(None),
+                Value::Tuple(vec![Value::Float64(1.5), Value::Float64(-2.0)]),
+                Value::Array(vec![Value::String(b"a".to_vec())]),
+                Value::Map(vec![(Value::String(b"k".to_vec()), Value::Int64(-7))]),
+            ]
+        );
+
+        let positional = Schema::from_type_strings(&[
+            ("a", "UInt8"),
+            ("b", "String"),
+            ("c", "Variant(String, UInt64)"),
+        ])
+        .unwrap();
+        assert_eq!(
+            to_row(&positional, &(7_u64, b"raw".to_vec(), 9_u8)).unwrap(),
+            [
+                Value::UInt8(7),
+                Value::String(b"raw".to_vec()),
+                Value::Variant {
+                    index: 1,
+                    value: Box::new(Value::UInt64(9)),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn reports_missing_extra_and_mismatched_fields() {
+        #[derive(Serialize)]
+        struct Extra {
+            id: u32,
+            unknown: u32,
+        }
+        #[derive(Serialize)]
+        struct Missing {
+            id: u32,
+        }
+        #[derive(Serialize)]
+        struct Mismatch<'a> {
+            id: &'a str,
+        }
+
+        let schema = Schema::from_type_strings(&[("id", "UInt8")]).unwrap();
+        let message = |err: Error| err.to_string();
+        assert!(
+            message(to_row(&schema, &Extra { id: 1, unknown: 2 }).unwrap_err())
+                .contains("field `unknown` has no matching column")
+        );
+        let two = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+        assert!(
+            message(to_row(&two, &Missing { id: 1 }).unwrap_err())
+                .contains("missing field for column `name`")
+        );
+        let err = message(to_row(&schema, &Mismatch { id: "x" }).unwrap_err());
+        assert!(err.contains("column `id`") && err.contains("expected UInt8, got string"));
+        let err = message(to_row(&schema, &Missing { id: 300 }).unwrap_err());
+        assert!(err.contains("column `id`") && err.contains("out of range"));
+        assert!(to_row(&two, &(1_u8,)).is_err());
+        assert!(to_row(&schema, &5_u8).is_err());
+    }
+
+    #[test]
+    fn writer_serializes_rows_that_round_trip() {
+        let mut writer = RowBinaryValueWriter::new(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            schema(),
+        );
+        writer.write_header().unwrap();
+        writer.serialize(&event(1, None)).unwrap();
+        writer.serialize(&event(2, Some(0.5))).unwrap();
+        let payload = writer.into_inner();
+
+        let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+        let mut decoded = Vec::new();
+        while let Some(event) = reader.deserialize::<Event>().unwrap() {
+            decoded.push(event);
+        }
+        assert_eq!(decoded, [event(1, None), event(2, Some(0.5))]);
+    }
+}
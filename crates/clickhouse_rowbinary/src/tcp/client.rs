@@ -0,0 +1,843 @@
+//! Blocking client over `std::net`.
+
+use std::{
+    io::{BufReader, Write},
+    net::{Shutdown, TcpStream, ToSocketAddrs},
+    time::Duration,
+};
+
+#[cfg(feature = "tls")]
+use crate::endpoints::host;
+use crate::{
+    endpoints::Endpoints,
+    error::{Error, Result},
+    io::write_uvarint,
+    native::Block,
+    rowbinary::{Row, Schema},
+};
+
+use super::{
+    ClientOptions, INSERT_BLOCK_ROWS, ServerInfo,
+    progress::{ProfileInfo, Progress},
+    protocol::{
+        CLIENT_CANCEL, CLIENT_PING, Packet, REVISION, read_hello, read_packet, write_addendum,
+        write_data, write_end_of_data, write_hello, write_query,
+    },
+    stream::Stream,
+};
+
+/// Size of the buffer server packets are read through.
+const READ_BUFFER_SIZE: usize = 64 * 1024;
+
+/// Blocking client holding one connection over the native TCP protocol.
+///
+/// Queries run one at a time, so methods take `&mut self`. Results stream
+/// as [`Block`]s through a [`QueryReader`]; a reader dropped before the end
+/// of its result leaves its query to be cancelled by the next call, which
+/// discards what the server sent meanwhile.
+///
+/// ```no_run
+/// # use clickhouse_rowbinary::{Schema, Value, tcp::{Client, ClientOptions}};
+/// let mut client = Client::connect_with_options(
+///     "localhost:9000",
+///     ClientOptions {
+///         user: "reader".into(),
+///         password: "secret".into(),
+///         ..ClientOptions::default()
+///     },
+/// )?;
+/// let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")])?;
+/// client.insert(
+///     "people",
+///     &schema,
+///     [[Value::UInt64(1), Value::String(b"ada".to_vec())]],
+/// )?;
+///
+/// let mut blocks = client.query("SELECT id, name FROM people ORDER BY id")?;
+/// while let Some(block) = blocks.read_block()? {
+///     println!("{} rows", block.num_rows());
+/// }
+/// # Ok::<(), clickhouse_rowbinary::Error>(())
+/// ```
+pub struct Client {
+    stream: BufReader<Stream>,
+    addr: String,
+    options: ClientOptions,
+    server: ServerInfo,
+    /// Revision both sides speak, the lower of the two.
+    revision: u64,
+    state: State,
+    /// Packets being written, sent with a single write.
+    buffer: Vec<u8>,
+}
+
+/// Where the connection stands between calls.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    /// Ready for a query.
+    Idle,
+    /// Packets of the last query are still unread.
+    Pending,
+    /// The stream is at an unknown position after a failure.
+    Broken,
+}
+
+impl Client {
+    /// Connects to the server at `addr`, such as `localhost:9000`, with
+    /// default options.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the connection or the
+    /// handshake fails.
+    pub fn connect(addr: &str) -> Result<Self> {
+        Self::connect_with_options(addr, ClientOptions::default())
+    }
+
+    /// Connects with explicit [`ClientOptions`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Server`] when the server rejects the credentials,
+    /// [`Error::Protocol`] when it speaks a revision older than 54454
+    /// (`ClickHouse` 22.1), and [`crate::error::Error`] when the
+    /// connection or its TLS handshake fails.
+    pub fn connect_with_options(addr: &str, options: ClientOptions) -> Result<Self> {
+        let tcp = open(addr, options.connect_timeout)?;
+        tcp.set_nodelay(true)?;
+        tcp.set_read_timeout(options.timeout)?;
+        tcp.set_write_timeout(options.timeout)?;
+        #[cfg(feature = "tls")]
+        let stream = match &options.tls {
+            Some(tls) => Stream::tls(tcp, host(addr), tls)?,
+            None => Stream::Plain(tcp),
+        };
+        #[cfg(not(feature = "tls"))]
+        let stream = Stream::Plain(tcp);
+        let mut client = Self {
+            stream: BufReader::with_capacity(READ_BUFFER_SIZE, stream),
+            addr: addr.to_string(),
+            options,
+            server: ServerInfo::default(),
+            revision: REVISION,
+            state: State::Idle,
+            buffer: Vec::new(),
+        };
+        write_hello(&client.options, &mut client.buffer)?;
+        client.send()?;
+        client.server = read_hello(&mut client.stream)?;
+        client.revision = client.server.revision.min(REVISION);
+        write_addendum(client.revision, &mut client.buffer)?;
+        client.send()?;
+        Ok(client)
+    }
+
+    /// Connects to one of `endpoints`, trying them in the order of their
+    /// [`LoadBalancing`](crate::LoadBalancing) policy and moving on to the
+    /// next while the connection fails, as when a server is down or does
+    /// not resolve.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last endpoint tried when none could be
+    /// connected to, or the error of an endpoint that was reached but
+    /// rejected the handshake, as [`Self::connect_with_options`] does.
+    pub fn connect_to(endpoints: &Endpoints, options: ClientOptions) -> Result<Self> {
+        endpoints.failover(
+            false,
+            move |addr| Self::connect_with_options(addr, options.clone()),
+            |err| matches!(err, Error::Io(_)),
+        )
+    }
+
+    /// Returns the address the connection was opened to.
+    #[must_use]
+    pub fn addr(&self) -> &str {
+        &self.addr
+    }
+
+    /// Returns the options the connection was opened with.
+    #[must_use]
+    pub fn options(&self) -> &ClientOptions {
+        &self.options
+    }
+
+    /// Returns what the server told about itself in the handshake.
+    #[must_use]
+    pub fn server(&self) -> &ServerInfo {
+        &self.server
+    }
+
+    /// Checks that the server still answers on the connection.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the connection is broken.
+    pub fn ping(&mut self) -> Result<()> {
+        self.finish_pending()?;
+        self.buffer.clear();
+        write_uvarint(CLIENT_PING, &mut self.buffer)?;
+        self.send()?;
+        loop {
+            match self.next_packet()? {
+                Packet::Pong => return Ok(()),
+                Packet::Skipped => {}
+                _ => {
+                    self.state = State::Broken;
+                    return Err(Error::Protocol("unexpected answer to a ping".to_string()));
+                }
+            }
+        }
+    }
+
+    /// Runs a statement, discarding any result rows, and returns the
+    /// progress the server reported.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Server`] when the server rejects the statement, or
+    /// [`crate::error::Error`] when the connection fails.
+    pub fn execute(&mut self, sql: &str) -> Result<Progress> {
+        let mut reader = self.query(sql)?;
+        while reader.read_block()?.is_some() {}
+        Ok(reader.progress())
+    }
+
+    /// Runs a query and returns a reader over its result.
+    ///
+    /// The first block, which carries the result's columns, is read before
+    /// this returns, so rejected queries fail here.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Server`] when the server rejects the query, or
+    /// [`crate::error::Error`] when the connection fails.
+    pub fn query(&mut self, sql: &str) -> Result<QueryReader<'_>> {
+        self.start(sql)?;
+        QueryReader::new(self)
+    }
+
+    /// Inserts `rows` into `table`, named as in SQL, with the columns of
+    /// `schema`, and returns the progress the server reported.
+    ///
+    /// Rows are sent in blocks of 65 536 as they are encoded. A row that
+    /// does not match `schema` closes the connection, so the server
+    /// discards the rows already sent.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a row does not match the
+    /// schema, [`Error::Server`] when the server rejects the insert, or
+    /// when the connection fails.
+    pub fn insert<I, R>(&mut self, table: &str, schema: &Schema, rows: I) -> Result<Progress>
+    where
+        I: IntoIterator<Item = R>,
+        R: Into<Row>,
+    {
+        let columns: Vec<_> = schema
+            .fields()
+            .iter()
+            .map(|field| {
+                let name = field.name.replace('\\', "\\\\").replace('`', "\\`");
+                format!("`{name}`")
+            })
+            .collect();
+        self.start(&format!(
+            "INSERT INTO {table} ({}) VALUES",
+            columns.join(", ")
+        ))?;
+        let mut progress = Progress::default();
+        // The server answers with the structure of the table once it
+        // accepts the insert.
+        loop {
+            match self.next_packet()? {
+                Packet::Data(_) => break,
+                Packet::Progress(increment) => progress.add(&increment),
+                Packet::EndOfStream => {
+                    return Err(Error::Protocol(
+                        "server ended the insert before its data".to_string(),
+                    ));
+                }
+                _ => {}
+            }
+        }
+
+        let mut rows = rows.into_iter().map(Into::into);
+        loop {
+            let chunk: Vec<Row> = rows.by_ref().take(INSERT_BLOCK_ROWS).collect();
+            if chunk.is_empty() {
+                break;
+            }
+            self.buffer.clear();
+            let encoded = Block::from_rows(schema.clone(), chunk)
+                .and_then(|block| write_data(&block, self.options.compression, &mut self.buffer));
+            if let Err(err) = encoded {
+                self.abort();
+                return Err(err);
+            }
+            self.send()?;
+        }
+        self.buffer.clear();
+        write_end_of_data(self.options.compression, &mut self.buffer)?;
+        self.send()?;
+        loop {
+            match self.next_packet()? {
+                Packet::Progress(increment) => progress.add(&increment),
+                Packet::EndOfStream => return Ok(progress),
+                _ => {}
+            }
+        }
+    }
+
+    /// Sends a query running `sql`, followed by the empty block that ends
+    /// its external tables.
+    fn start(&mut self, sql: &str) -> Result<()> {
+        self.finish_pending()?;
+        self.buffer.clear();
+        let compression = self.options.compression;
+        write_query(
+            self.revision,
+            sql,
+            &self.options.settings,
+            compression.is_some(),
+            &mut self.buffer,
+        )?;
+        write_end_of_data(compression, &mut self.buffer)?;
+        self.send()?;
+        self.state = State::Pending;
+        Ok(())
+    }
+
+    /// Cancels an abandoned query and discards what is left of it.
+    fn finish_pending(&mut self) -> Result<()> {
+        if self.state == State::Pending {
+            self.buffer.clear();
+            write_uvarint(CLIENT_CANCEL, &mut self.buffer)?;
+            self.send()?;
+        }
+        while self.state == State::Pending {
+            match self.next_packet() {
+                Ok(_) | Err(Error::Server { .. }) => {}
+                Err(err) => return Err(err),
+            }
+        }
+        self.usable()
+    }
+
+    /// Reads the next packet, turning exceptions into errors and tracking
+    /// the end of the query.
+    fn next_packet(&mut self) -> Result<Packet> {
+        self.usable()?;
+        let compressed = self.options.compression.is_some();
+        match read_packet(&mut self.stream, self.revision, compressed) {
+            Ok(Packet::Exception(err)) => {
+                self.state = State::Idle;
+                Err(err)
+            }
+            Ok(Packet::EndOfStream) => {
+                self.state = State::Idle;
+                Ok(Packet::EndOfStream)
+            }
+            Ok(packet) => Ok(packet),
+            Err(err) => {
+                self.state = State::Broken;
+                Err(err)
+            }
+        }
+    }
+
+    /// Writes the buffered packets to the connection.
+    fn send(&mut self) -> Result<()> {
+        let stream = self.stream.get_mut();
+        let written = stream.write_all(&self.buffer).and_then(|()| stream.flush());
+        self.buffer.clear();
+        written.map_err(|err| {
+            self.state = State::Broken;
+            Error::Io(err)
+        })
+    }
+
+    /// Closes the connection in the middle of an insert, so the server
+    /// discards the rows sent so far.
+    fn abort(&mut self) {
+        self.state = State::Broken;
+        let _ = self.stream.get_ref().tcp().shutdown(Shutdown::Both);
+    }
+
+    /// Returns whether a failure left the connection unusable.
+    pub(super) fn is_broken(&self) -> bool {
+        self.state == State::Broken
+    }
+
+    fn usable(&self) -> Result<()> {
+        if self.state == State::Broken {
+            Err(Error::Protocol(
+                "connection is broken by an earlier failure".to_string(),
+            ))
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Opens a connection to the first address `addr` resolves to that
+/// accepts one.
+fn open(addr: &str, timeout: Option<Duration>) -> Result<TcpStream> {
+    let Some(timeout) = timeout else {
+        return Ok(TcpStream::connect(addr)?);
+    };
+    let mut last_err = None;
+    for addr in addr.to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(stream) => return Ok(stream),
+            Err(err) => last_err = Some(err),
+        }
+    }
+    Err(last_err.map_or(
+        Error::InvalidValue("server address resolves to nothing"),
+        Error::Io,
+    ))
+}
+
+/// Reader over the blocks of a query's result.
+///
+/// Empty blocks are skipped. Progress, profile information, totals and
+/// extremes are collected as they arrive; the last three come at the end
+/// of the result.
+pub struct QueryReader<'a> {
+    client: &'a mut Client,
+    schema: Schema,
+    /// First block of rows, read along with the result's columns.
+    next: Option<Block>,
+    progress: Progress,
+    profile_info: Option<ProfileInfo>,
+    totals: Option<Block>,
+    extremes: Option<Block>,
+    done: bool,
+}
+
+impl<'a> QueryReader<'a> {
+    fn new(client: &'a mut Client) -> Result<Self> {
+        let mut reader = Self {
+            client,
+            schema: Schema::new(Vec::new()),
+            next: None,
+            progress: Progress::default(),
+            profile_info: None,
+            totals: None,
+            extremes: None,
+            done: false,
+        };
+        if let Some(block) = reader.next_data()? {
+            reader.schema = block.schema().clone();
+            reader.next = Some(block).filter(|block| !block.is_empty());
+        }
+        Ok(reader)
+    }
+
+    /// Returns the columns of the result, empty for statements without
+    /// one.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next block of rows, or returns `Ok(None)` at the end of
+    /// the result.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Server`] when the query fails while it runs, or
+    /// [`crate::error::Error`] when a block does not decode or the
+    /// connection fails.
+    pub fn read_block(&mut self) -> Result<Option<Block>> {
+        if let Some(block) = self.next.take() {
+            return Ok(Some(block));
+        }
+        while let Some(block) = self.next_data()? {
+            if !block.is_empty() {
+                return Ok(Some(block));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns an iterator over the rows of the remaining blocks.
+    pub fn rows(mut self) -> impl Iterator<Item = Result<Row>> {
+        let mut rows = Vec::new().into_iter();
+        std::iter::from_fn(move || {
+            loop {
+                if let Some(row) = rows.next() {
+                    return Some(Ok(row));
+                }
+                match self.read_block() {
+                    Ok(Some(block)) => rows = block.into_rows().into_iter(),
+                    Ok(None) => return None,
+                    Err(err) => return Some(Err(err)),
+                }
+            }
+        })
+    }
+
+    /// Returns the progress reported so far.
+    #[must_use]
+    pub fn progress(&self) -> Progress {
+        self.progress
+    }
+
+    /// Returns the totals of the result, once the server sent them.
+    #[must_use]
+    pub fn profile_info(&self) -> Option<ProfileInfo> {
+        self.profile_info
+    }
+
+    /// Returns the row of totals a `WITH TOTALS` query computed, once the
+    /// server sent it.
+    #[must_use]
+    pub fn totals(&self) -> Option<&Block> {
+        self.totals.as_ref()
+    }
+
+    /// Cancels the query, so the server stops working on it, and reads
+    /// what the server sent until it acknowledges the cancellation.
+    ///
+    /// Does nothing when the result was read to its end.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the connection fails.
+    pub fn cancel(self) -> Result<()> {
+        self.client.finish_pending()
+    }
+
+    /// Returns the minimums and maximums computed with the `extremes`
+    /// setting, once the server sent them.
+    #[must_use]
+    pub fn extremes(&self) -> Option<&Block> {
+        self.extremes.as_ref()
+    }
+
+    /// Reads packets up to the next `Data` block, or returns `Ok(None)` at
+    /// the end of the result.
+    fn next_data(&mut self) -> Result<Option<Block>> {
+        while !self.done {
+            let packet = self
+                .client
+                .next_packet()
+                .inspect_err(|_| self.done = true)?;
+            match packet {
+                Packet::Data(block) => return Ok(Some(block)),
+                Packet::Totals(block) => self.totals = Some(block),
+                Packet::Extremes(block) => self.extremes = Some(block),
+                Packet::Progress(increment) => self.progress.add(&increment),
+                Packet::ProfileInfo(info) => self.profile_info = Some(info),
+                Packet::EndOfStream => self.done = true,
+                Packet::Exception(_) | Packet::Pong | Packet::Skipped => {}
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl Iterator for QueryReader<'_> {
+    type Item = Result<Block>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_block().transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Client, ClientOptions};
+    #[cfg(feature = "tls")]
+    use crate::tcp::{
+        TlsOptions,
+        mock::{CA_CERTIFICATE, serve_tls},
+    };
+    use crate::{
+        endpoints::{Endpoints, LoadBalancing},
+        error::Error,
+        native::Block,
+        rowbinary::Schema,
+        tcp::{
+            ProfileInfo,
+            mock::{Hello, SentQuery, refused, serve},
+        },
+        value::Value,
+    };
+
+    fn schema() -> Schema {
+        Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap()
+    }
+
+    fn block(ids: std::ops::Range<u64>) -> Block {
+        Block::from_rows(
+            schema(),
+            ids.map(|id| {
+                vec![
+                    Value::UInt64(id),
+                    Value::String(format!("n{id}").into_bytes()),
+                ]
+            }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn queries_stream_blocks_with_their_progress() {
+        let (addr, server) = serve(|peer| {
+            let hello = peer.handshake(54460);
+            assert_eq!(
+                hello,
+                Hello {
+                    database: "analytics".into(),
+                    user: "reader".into(),
+                    password: "secret".into(),
+                }
+            );
+            assert_eq!(
+                peer.read_query(),
+                SentQuery {
+                    sql: "SELECT id, name FROM people".into(),
+                    settings: vec![("max_threads".into(), "2".into())],
+                }
+            );
+            peer.send_data(&block(0..0));
+            peer.send_progress(3, 0);
+            peer.send_data(&block(0..2));
+            peer.send_log(&block(7..8));
+            peer.send_data(&block(2..3));
+            peer.send_profile_info(3);
+            peer.send_end_of_stream();
+        });
+        let mut client = Client::connect_with_options(
+            &addr,
+            ClientOptions {
+                user: "reader".into(),
+                password: "secret".into(),
+                database: Some("analytics".into()),
+                settings: vec![("max_threads".into(), "2".into())],
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(client.server().display_name, "mock");
+        assert_eq!(client.server().revision, 54460);
+
+        let mut reader = client.query("SELECT id, name FROM people").unwrap();
+        assert_eq!(reader.schema(), &schema());
+        assert_eq!(reader.read_block().unwrap(), Some(block(0..2)));
+        assert_eq!(reader.read_block().unwrap(), Some(block(2..3)));
+        assert_eq!(reader.read_block().unwrap(), None);
+        assert_eq!(reader.progress().read_rows, 3);
+        assert_eq!(
+            reader.profile_info(),
+            Some(ProfileInfo {
+                rows: 3,
+                blocks: 1,
+                bytes: 24,
+                ..ProfileInfo::default()
+            })
+        );
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn failed_and_abandoned_queries_leave_the_connection_usable() {
+        let (addr, server) = serve(|peer| {
+            peer.handshake(54454);
+            peer.read_query();
+            peer.send_exception(60, "Table default.missing does not exist. (UNKNOWN_TABLE)");
+
+            peer.read_query();
+            peer.send_data(&block(0..0));
+            peer.send_data(&block(0..2));
+            peer.send_data(&block(2..4));
+            peer.send_end_of_stream();
+            peer.read_cancel();
+
+            assert_eq!(peer.read_query().sql, "SELECT 1");
+            peer.send_progress(1, 0);
+            peer.send_end_of_stream();
+        });
+        let mut client = Client::connect_with_options(
+            &addr,
+            ClientOptions {
+                compression: None,
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+        let err = client.query("SELECT * FROM missing").err().unwrap();
+        assert!(
+            matches!(&err, Error::Server { code: 60, message } if message.ends_with("(UNKNOWN_TABLE)")),
+            "{err}"
+        );
+
+        let mut reader = client.query("SELECT id, name FROM people").unwrap();
+        assert_eq!(reader.next().unwrap().unwrap(), block(0..2));
+        drop(reader);
+        assert_eq!(client.execute("SELECT 1").unwrap().read_rows, 1);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn cancelled_queries_are_read_until_the_server_stops() {
+        let (addr, server) = serve(|peer| {
+            peer.handshake(54460);
+            peer.read_query();
+            peer.send_data(&block(0..0));
+            peer.send_data(&block(0..2));
+            peer.read_cancel();
+            peer.send_data(&block(2..4));
+            peer.send_progress(4, 0);
+            peer.send_end_of_stream();
+            peer.answer_pings();
+        });
+        let mut client = Client::connect(&addr).unwrap();
+        let mut reader = client.query("SELECT id, name FROM people").unwrap();
+        assert_eq!(reader.read_block().unwrap(), Some(block(0..2)));
+        reader.cancel().unwrap();
+        client.ping().unwrap();
+        drop(client);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn inserts_send_blocks_ending_with_an_empty_one() {
+        let (addr, server) = serve(|peer| {
+            peer.handshake(54460);
+            assert_eq!(
+                peer.read_query().sql,
+                "INSERT INTO people (`id`, `name`) VALUES"
+            );
+            peer.send_data(&block(0..0));
+            assert_eq!(peer.read_data(), block(0..3));
+            assert!(peer.read_data().is_empty());
+            peer.send_progress(0, 3);
+            peer.send_end_of_stream();
+        });
+        let mut client = Client::connect(&addr).unwrap();
+        let progress = client
+            .insert("people", &schema(), block(0..3).into_rows())
+            .unwrap();
+        assert_eq!(progress.written_rows, 3);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn rows_that_do_not_match_break_the_connection() {
+        let (addr, _server) = serve(|peer| {
+            peer.handshake(54460);
+            peer.read_query();
+            peer.send_data(&block(0..0));
+        });
+        let mut client = Client::connect(&addr).unwrap();
+        let err = client
+            .insert("people", &schema(), [vec![Value::UInt64(1)]])
+            .unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)), "{err}");
+        assert!(matches!(client.ping(), Err(Error::Protocol(_))));
+    }
+
+    #[test]
+    fn old_servers_are_refused() {
+        let (addr, _server) = serve(|peer| {
+            peer.handshake(54449);
+        });
+        let err = Client::connect(&addr).err().unwrap();
+        assert!(matches!(err, Error::Protocol(_)), "{err}");
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn tls_connections_trust_the_configured_authorities() {
+        let (addr, server) = serve_tls(|peer| {
+            peer.handshake(54460);
+            assert_eq!(peer.read_query().sql, "SELECT id, name FROM people");
+            peer.send_data(&block(0..2));
+            peer.send_end_of_stream();
+        });
+        let mut client = Client::connect_with_options(
+            &addr,
+            ClientOptions {
+                tls: Some(TlsOptions {
+                    ca_certificates: Some(CA_CERTIFICATE.into()),
+                    server_name: Some("localhost".into()),
+                }),
+                ..ClientOptions::default()
+            },
+        )
+        .unwrap();
+        let rows = client
+            .query("SELECT id, name FROM people")
+            .unwrap()
+            .rows()
+            .collect::<Result<Vec<_>, _>>()
+            .unwrap();
+        assert_eq!(rows, block(0..2).into_rows());
+        server.join().unwrap();
+    }
+
+    #[cfg(feature = "tls")]
+    #[test]
+    fn tls_connections_refuse_unverified_servers() {
+        let unknown_authority = TlsOptions {
+            server_name: Some("localhost".into()),
+            ..TlsOptions::default()
+        };
+        let other_name = TlsOptions {
+            ca_certificates: Some(CA_CERTIFICATE.into()),
+            server_name: None,
+        };
+        for tls in [unknown_authority, other_name] {
+            let (addr, server) = serve_tls(|_| unreachable!());
+            let err = Client::connect_with_options(
+                &addr,
+                ClientOptions {
+                    tls: Some(tls),
+                    ..ClientOptions::default()
+                },
+            )
+            .err()
+            .unwrap();
+            assert!(matches!(&err, Error::Io(_)), "{err}");
+            server.join().unwrap();
+        }
+    }
+
+    #[test]
+    fn connections_fail_over_to_the_next_endpoint() {
+        let (addr, server) = serve(|peer| {
+            peer.handshake(54454);
+        });
+        let down = refused();
+        let endpoints = Endpoints::new([down.as_str(), &addr], LoadBalancing::FirstAlive).unwrap();
+        let client = Client::connect_to(&endpoints, ClientOptions::default()).unwrap();
+        assert_eq!(client.addr(), addr);
+        drop(client);
+        server.join().unwrap();
+
+        let endpoints = Endpoints::new([refused(), refused()], LoadBalancing::RoundRobin).unwrap();
+        let err = Client::connect_to(&endpoints, ClientOptions::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Io(_)), "{err}");
+    }
+
+    #[test]
+    fn rejected_handshakes_do_not_fail_over() {
+        let (addr, server) = serve(|peer| {
+            peer.read_hello();
+            peer.send_exception(516, "default: Authentication failed");
+        });
+        let endpoints =
+            Endpoints::new([addr.clone(), refused()], LoadBalancing::FirstAlive).unwrap();
+        let err = Client::connect_to(&endpoints, ClientOptions::default())
+            .err()
+            .unwrap();
+        assert!(matches!(err, Error::Server { code: 516, .. }), "{err}");
+        server.join().unwrap();
+    }
+}
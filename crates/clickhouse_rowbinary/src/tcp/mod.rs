@@ -0,0 +1,124 @@
+//! Client for the `ClickHouse` native TCP protocol.
+//!
+//! The blocking [`Client`] (feature `tcp`) holds one connection to the
+//! server's native port, 9000 by default, and exchanges data as `Native`
+//! [`Block`](crate::Block)s, the protocol's own unit, compressed with the
+//! server's block framing. Unlike HTTP, results arrive with the progress
+//! and profile information the server reports while the query runs.
+//!
+//! A connection runs one query at a time. A rejected query fails with
+//! [`Error::Server`] carrying the server's error code and message, and
+//! leaves the connection usable; a broken connection fails every later call
+//! with [`Error::Protocol`] and has to be replaced.
+//!
+//! A [`Pool`] shares connections between threads, so concurrent callers
+//! do not pay the connection setup and handshake for every query. The
+//! HTTP clients need no such pool: their clones share the keep-alive
+//! connections of the HTTP stack.
+//!
+//! [`Client::connect_to`] and [`Pool::with_endpoints`] take the replicas of
+//! a cluster as [`Endpoints`](crate::Endpoints) and fail over between them
+//! when a server cannot be reached.
+//!
+//! With the `tls` feature, `ClientOptions::tls` runs connections over
+//! TLS, as servers expose on their secure native port, 9440 by default.
+//!
+//! [`Error::Server`]: crate::Error::Server
+//! [`Error::Protocol`]: crate::Error::Protocol
+
+mod client;
+#[cfg(test)]
+mod mock;
+mod pool;
+mod progress;
+mod protocol;
+mod stream;
+
+use std::time::Duration;
+
+pub use client::{Client, QueryReader};
+pub use pool::{Pool, PoolOptions, PoolStatus, PooledClient};
+pub use progress::{ProfileInfo, Progress};
+
+use crate::compression::CompressionMethod;
+
+/// Rows per block sent by inserts.
+const INSERT_BLOCK_ROWS: usize = 65_536;
+
+/// Connection settings of the native TCP client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientOptions {
+    /// User the connection authenticates as.
+    pub user: String,
+    /// Password of the user.
+    pub password: String,
+    /// Database that unqualified table names resolve in; the user's default
+    /// database when `None`.
+    pub database: Option<String>,
+    /// Method data blocks are compressed with in both directions; data
+    /// travels uncompressed when `None`.
+    pub compression: Option<CompressionMethod>,
+    /// Limit on establishing the connection. The system's when `None`.
+    pub connect_timeout: Option<Duration>,
+    /// Limit on each read from and write to the connection, so a query
+    /// that sends nothing for longer fails. No limit when `None`.
+    pub timeout: Option<Duration>,
+    /// Settings sent with every query, such as `max_execution_time`, as
+    /// name and value pairs. Unknown settings are rejected by the server.
+    pub settings: Vec<(String, String)>,
+    /// TLS settings of the connection, which runs over TLS when set.
+    #[cfg(feature = "tls")]
+    pub tls: Option<TlsOptions>,
+}
+
+impl Default for ClientOptions {
+    fn default() -> Self {
+        Self {
+            user: "default".to_string(),
+            password: String::new(),
+            database: None,
+            compression: Some(CompressionMethod::Lz4),
+            connect_timeout: None,
+            timeout: None,
+            settings: Vec::new(),
+            #[cfg(feature = "tls")]
+            tls: None,
+        }
+    }
+}
+
+/// How a connection verifies the server over TLS.
+///
+/// The default trusts the Mozilla roots bundled with `webpki-roots` and
+/// expects a certificate for the host connected to, which is also sent for
+/// SNI.
+#[cfg(feature = "tls")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TlsOptions {
+    /// PEM bundle of the certificate authorities to trust instead of the
+    /// bundled roots, for servers with certificates from a private CA.
+    pub ca_certificates: Option<Vec<u8>>,
+    /// Name sent for SNI and checked against the server's certificate in
+    /// place of the host connected to, for servers reached by address or
+    /// through a tunnel.
+    pub server_name: Option<String>,
+}
+
+/// Server identity sent in its handshake.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerInfo {
+    /// Server name, `ClickHouse` for the official server.
+    pub name: String,
+    /// Major version, such as `25` for 25.10.
+    pub version_major: u64,
+    /// Minor version.
+    pub version_minor: u64,
+    /// Patch version.
+    pub version_patch: u64,
+    /// Protocol revision the server speaks.
+    pub revision: u64,
+    /// Time zone of the server, such as `UTC`.
+    pub timezone: String,
+    /// Name the server is configured to display, usually its host name.
+    pub display_name: String,
+}
@@ -0,0 +1,546 @@
+//! Pool of connections shared between threads.
+
+use std::{
+    io,
+    ops::{Deref, DerefMut},
+    sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError, Weak},
+    thread,
+    time::{Duration, Instant},
+};
+
+use crate::{
+    endpoints::{Endpoints, LoadBalancing},
+    error::{Error, Result},
+};
+
+use super::{ClientOptions, client::Client};
+
+/// Shortest pause between two passes of the idle reaper.
+const MIN_REAP_PERIOD: Duration = Duration::from_millis(100);
+
+/// Size limits and checks of a [`Pool`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PoolOptions {
+    /// Connections opened with the pool, which are never closed for being
+    /// idle.
+    pub min_connections: usize,
+    /// Most connections open at once. Beyond it, [`Pool::get`] waits for
+    /// a connection to be returned.
+    pub max_connections: usize,
+    /// Limit on how long [`Pool::get`] waits for a connection when all of
+    /// them are in use. No limit when `None`.
+    pub wait_timeout: Option<Duration>,
+    /// Idle time after which a connection is pinged before it is handed
+    /// out, so connections the server or the network dropped meanwhile are
+    /// replaced instead of failing the next query. Never pinged when
+    /// `None`.
+    pub health_check_after: Option<Duration>,
+    /// Idle time after which connections beyond
+    /// [`Self::min_connections`] are closed. Kept open when `None`.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl Default for PoolOptions {
+    fn default() -> Self {
+        Self {
+            min_connections: 0,
+            max_connections: 10,
+            wait_timeout: None,
+            health_check_after: Some(Duration::from_secs(60)),
+            idle_timeout: Some(Duration::from_secs(600)),
+        }
+    }
+}
+
+/// Connections of a [`Pool`] at one moment.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// Connections open, in use or idle.
+    pub connections: usize,
+    /// Connections waiting in the pool to be handed out.
+    pub idle: usize,
+}
+
+/// Pool of native connections to one server, or to a cluster's
+/// [`Endpoints`], shared between threads.
+///
+/// [`Pool::get`] hands out a connection as a [`PooledClient`], opening one
+/// when none is idle and the pool is below its limit. Connections to
+/// endpoints are opened as [`Client::connect_to`] does, so the pool fails
+/// over when a server is down and, under
+/// [`LoadBalancing::RoundRobin`], spreads its connections over the
+/// cluster. The connection goes
+/// back to the pool when the `PooledClient` is dropped, unless a failure
+/// broke it. Pools are cheap to clone; clones share their connections.
+///
+/// ```no_run
+/// # use clickhouse_rowbinary::tcp::{ClientOptions, Pool, PoolOptions};
+/// let pool = Pool::new(
+///     "localhost:9000",
+///     ClientOptions::default(),
+///     PoolOptions {
+///         min_connections: 2,
+///         ..PoolOptions::default()
+///     },
+/// )?;
+/// let progress = pool.get()?.execute("OPTIMIZE TABLE people")?;
+/// # Ok::<(), clickhouse_rowbinary::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct Pool {
+    shared: Arc<Shared>,
+}
+
+struct Shared {
+    endpoints: Endpoints,
+    options: ClientOptions,
+    pool: PoolOptions,
+    state: Mutex<State>,
+    /// Signalled when a connection is returned or closed.
+    returned: Condvar,
+}
+
+struct State {
+    /// Idle connections, the most recently returned last.
+    idle: Vec<Idle>,
+    /// Connections open or being opened.
+    open: usize,
+}
+
+struct Idle {
+    client: Client,
+    since: Instant,
+}
+
+impl Pool {
+    /// Creates a pool of connections to `addr`, such as `localhost:9000`,
+    /// opened with `options`, and opens its
+    /// [`PoolOptions::min_connections`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `pool` allows no connection or
+    /// asks for more than it allows, or [`crate::error::Error`] when
+    /// opening a connection fails.
+    pub fn new(addr: &str, options: ClientOptions, pool: PoolOptions) -> Result<Self> {
+        let endpoints = Endpoints::new([addr], LoadBalancing::FirstAlive)?;
+        Self::with_endpoints(endpoints, options, pool)
+    }
+
+    /// Creates a pool of connections to `endpoints`, opened with `options`,
+    /// and opens its [`PoolOptions::min_connections`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when `pool` allows no connection or
+    /// asks for more than it allows, or [`crate::error::Error`] when no
+    /// endpoint accepts a connection.
+    pub fn with_endpoints(
+        endpoints: Endpoints,
+        options: ClientOptions,
+        pool: PoolOptions,
+    ) -> Result<Self> {
+        if pool.max_connections == 0 {
+            return Err(Error::InvalidValue(
+                "pool max_connections must be at least 1",
+            ));
+        }
+        if pool.min_connections > pool.max_connections {
+            return Err(Error::InvalidValue(
+                "pool min_connections must not exceed max_connections",
+            ));
+        }
+        let idle = (0..pool.min_connections)
+            .map(|_| {
+                Ok(Idle {
+                    client: Client::connect_to(&endpoints, options.clone())?,
+                    since: Instant::now(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+        let idle_timeout = pool.idle_timeout;
+        let shared = Arc::new(Shared {
+            endpoints,
+            options,
+            pool,
+            state: Mutex::new(State {
+                open: idle.len(),
+                idle,
+            }),
+            returned: Condvar::new(),
+        });
+        if let Some(idle_timeout) = idle_timeout {
+            spawn_reaper(Arc::downgrade(&shared), idle_timeout)?;
+        }
+        Ok(Self { shared })
+    }
+
+    /// Returns the options the pool opens connections with.
+    #[must_use]
+    pub fn options(&self) -> &ClientOptions {
+        &self.shared.options
+    }
+
+    /// Returns how many connections are open and idle.
+    #[must_use]
+    pub fn status(&self) -> PoolStatus {
+        let state = self.shared.lock();
+        PoolStatus {
+            connections: state.open,
+            idle: state.idle.len(),
+        }
+    }
+
+    /// Hands out an idle connection, or opens one when none is idle,
+    /// waiting for one to be returned when the pool is at its limit.
+    ///
+    /// A connection idle for longer than
+    /// [`PoolOptions::health_check_after`] is pinged first and replaced
+    /// when it does not answer.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Io`] with [`io::ErrorKind::TimedOut`] when no
+    /// connection became available within [`PoolOptions::wait_timeout`],
+    /// or [`crate::error::Error`] when opening a connection fails.
+    pub fn get(&self) -> Result<PooledClient> {
+        let shared = &self.shared;
+        let deadline = shared
+            .pool
+            .wait_timeout
+            .map(|timeout| Instant::now() + timeout);
+        let mut state = shared.lock();
+        loop {
+            if let Some(idle) = state.idle.pop() {
+                drop(state);
+                if let Some(client) = shared.check(idle) {
+                    return Ok(self.wrap(client));
+                }
+                shared.close_one();
+                state = shared.lock();
+                continue;
+            }
+            if state.open < shared.pool.max_connections {
+                state.open += 1;
+                drop(state);
+                return match Client::connect_to(&shared.endpoints, shared.options.clone()) {
+                    Ok(client) => Ok(self.wrap(client)),
+                    Err(err) => {
+                        shared.close_one();
+                        Err(err)
+                    }
+                };
+            }
+            state = match deadline {
+                None => shared
+                    .returned
+                    .wait(state)
+                    .unwrap_or_else(PoisonError::into_inner),
+                Some(deadline) => {
+                    let remaining = deadline
+                        .checked_duration_since(Instant::now())
+                        .filter(|remaining| !remaining.is_zero())
+                        .ok_or_else(|| {
+                            Error::Io(io::Error::new(
+                                io::ErrorKind::TimedOut,
+                                "no pooled connection became available",
+                            ))
+                        })?;
+                    shared
+                        .returned
+                        .wait_timeout(state, remaining)
+                        .unwrap_or_else(PoisonError::into_inner)
+                        .0
+                }
+            };
+        }
+    }
+
+    fn wrap(&self, client: Client) -> PooledClient {
+        PooledClient {
+            client: Some(client),
+            shared: Arc::clone(&self.shared),
+        }
+    }
+}
+
+impl Shared {
+    fn lock(&self) -> MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Pings a connection that sat idle past the health check age,
+    /// returning it when it is still usable.
+    fn check(&self, idle: Idle) -> Option<Client> {
+        let Idle { mut client, since } = idle;
+        let stale = self
+            .pool
+            .health_check_after
+            .is_some_and(|after| since.elapsed() >= after);
+        if stale {
+            client.ping().ok()?;
+        }
+        Some(client)
+    }
+
+    /// Takes back a connection handed out, closing it when it is broken.
+    fn put_back(&self, client: Client) {
+        if client.is_broken() {
+            drop(client);
+            self.close_one();
+            return;
+        }
+        self.lock().idle.push(Idle {
+            client,
+            since: Instant::now(),
+        });
+        self.returned.notify_one();
+    }
+
+    /// Frees the place of a connection that was closed or failed to open.
+    fn close_one(&self) {
+        self.lock().open -= 1;
+        self.returned.notify_one();
+    }
+
+    /// Closes the connections idle for `idle_timeout` or longer, oldest
+    /// first, down to the pool's minimum.
+    fn reap(&self, idle_timeout: Duration) {
+        let mut state = self.lock();
+        let spare = state.open.saturating_sub(self.pool.min_connections);
+        let expired = state
+            .idle
+            .iter()
+            .take_while(|idle| idle.since.elapsed() >= idle_timeout)
+            .count()
+            .min(spare);
+        let closed: Vec<_> = state.idle.drain(..expired).collect();
+        state.open -= closed.len();
+        drop(state);
+        drop(closed);
+    }
+}
+
+/// Runs [`Shared::reap`] in the background until the pool is dropped.
+fn spawn_reaper(shared: Weak<Shared>, idle_timeout: Duration) -> Result<()> {
+    let period = (idle_timeout / 2).max(MIN_REAP_PERIOD);
+    thread::Builder::new()
+        .name("clickhouse-pool-reaper".to_string())
+        .spawn(move || {
+            loop {
+                thread::sleep(period);
+                let Some(shared) = shared.upgrade() else {
+                    return;
+                };
+                shared.reap(idle_timeout);
+            }
+        })?;
+    Ok(())
+}
+
+/// Connection handed out by a [`Pool`], returned to it when dropped.
+///
+/// Dereferences to the [`Client`] it wraps.
+pub struct PooledClient {
+    client: Option<Client>,
+    shared: Arc<Shared>,
+}
+
+impl Deref for PooledClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Client {
+        self.client
+            .as_ref()
+            .expect("client is present until dropped")
+    }
+}
+
+impl DerefMut for PooledClient {
+    fn deref_mut(&mut self) -> &mut Client {
+        self.client
+            .as_mut()
+            .expect("client is present until dropped")
+    }
+}
+
+impl Drop for PooledClient {
+    fn drop(&mut self) {
+        if let Some(client) = self.client.take() {
+            self.shared.put_back(client);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+        time::Duration,
+    };
+
+    use super::{Pool, PoolOptions, PoolStatus};
+    use crate::{
+        endpoints::{Endpoints, LoadBalancing},
+        error::Error,
+        tcp::{ClientOptions, mock::serve_all},
+    };
+
+    fn status(connections: usize, idle: usize) -> PoolStatus {
+        PoolStatus { connections, idle }
+    }
+
+    #[test]
+    fn connections_are_reused_up_to_the_limit() {
+        let (addr, server) = serve_all(2, |peer| {
+            peer.handshake(54460);
+            peer.answer_pings();
+        });
+        let pool = Pool::new(
+            &addr,
+            ClientOptions::default(),
+            PoolOptions {
+                min_connections: 1,
+                max_connections: 2,
+                ..PoolOptions::default()
+            },
+        )
+        .unwrap();
+        assert_eq!(pool.status(), status(1, 1));
+
+        let mut first = pool.get().unwrap();
+        let second = pool.get().unwrap();
+        assert_eq!(pool.status(), status(2, 0));
+        first.ping().unwrap();
+        let waiting = thread::spawn({
+            let pool = pool.clone();
+            move || pool.get().unwrap().ping().unwrap()
+        });
+        thread::sleep(Duration::from_millis(20));
+        drop(first);
+        waiting.join().unwrap();
+        drop(second);
+        assert_eq!(pool.status(), status(2, 2));
+
+        drop(pool);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn waiting_for_a_connection_times_out() {
+        let (addr, _server) = serve_all(1, |peer| {
+            peer.handshake(54460);
+            peer.answer_pings();
+        });
+        let pool = Pool::new(
+            &addr,
+            ClientOptions::default(),
+            PoolOptions {
+                max_connections: 1,
+                wait_timeout: Some(Duration::from_millis(20)),
+                ..PoolOptions::default()
+            },
+        )
+        .unwrap();
+        let _held = pool.get().unwrap();
+        let err = pool.get().err().unwrap();
+        assert!(
+            matches!(&err, Error::Io(err) if err.kind() == io::ErrorKind::TimedOut),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn connections_that_fail_their_health_check_are_replaced() {
+        let accepted = AtomicUsize::new(0);
+        let (addr, server) = serve_all(2, move |peer| {
+            peer.handshake(54460);
+            // The first connection closes right after its handshake.
+            if accepted.fetch_add(1, Ordering::SeqCst) > 0 {
+                peer.answer_pings();
+            }
+        });
+        let pool = Pool::new(
+            &addr,
+            ClientOptions::default(),
+            PoolOptions {
+                min_connections: 1,
+                health_check_after: Some(Duration::ZERO),
+                ..PoolOptions::default()
+            },
+        )
+        .unwrap();
+        let mut client = pool.get().unwrap();
+        client.ping().unwrap();
+        assert_eq!(pool.status(), status(1, 0));
+
+        drop((client, pool));
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn idle_connections_beyond_the_minimum_are_closed() {
+        let (addr, server) = serve_all(3, |peer| {
+            peer.handshake(54460);
+            peer.answer_pings();
+        });
+        let pool = Pool::new(
+            &addr,
+            ClientOptions::default(),
+            PoolOptions {
+                min_connections: 1,
+                idle_timeout: Some(Duration::from_millis(50)),
+                ..PoolOptions::default()
+            },
+        )
+        .unwrap();
+        let clients: Vec<_> = (0..3).map(|_| pool.get().unwrap()).collect();
+        drop(clients);
+        assert_eq!(pool.status(), status(3, 3));
+        thread::sleep(Duration::from_millis(500));
+        assert_eq!(pool.status(), status(1, 1));
+
+        drop(pool);
+        server.join().unwrap();
+    }
+
+    #[test]
+    fn connections_spread_over_round_robin_endpoints() {
+        let (first, first_server) = serve_all(1, |peer| {
+            peer.handshake(54460);
+        });
+        let (second, second_server) = serve_all(1, |peer| {
+            peer.handshake(54460);
+        });
+        let endpoints = Endpoints::new([&first, &second], LoadBalancing::RoundRobin).unwrap();
+        let pool =
+            Pool::with_endpoints(endpoints, ClientOptions::default(), PoolOptions::default())
+                .unwrap();
+        let clients = [pool.get().unwrap(), pool.get().unwrap()];
+        assert_eq!(
+            clients.each_ref().map(|client| client.addr()),
+            [first.as_str(), &second]
+        );
+
+        drop((clients, pool));
+        first_server.join().unwrap();
+        second_server.join().unwrap();
+    }
+
+    #[test]
+    fn limits_are_validated() {
+        for (min_connections, max_connections) in [(0, 0), (3, 2)] {
+            let options = PoolOptions {
+                min_connections,
+                max_connections,
+                ..PoolOptions::default()
+            };
+            let err = Pool::new("localhost:9000", ClientOptions::default(), options)
+                .err()
+                .unwrap();
+            assert!(matches!(err, Error::InvalidValue(_)), "{err}");
+        }
+    }
+}
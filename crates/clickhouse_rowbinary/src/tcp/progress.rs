@@ -0,0 +1,89 @@
+//! Query statistics reported while a query runs.
+
+use std::time::Duration;
+
+/// Rows and bytes a query has processed, summed over the `Progress`
+/// packets the server sent so far.
+///
+/// Each packet reports the work done since the previous one; reads add up
+/// while a `SELECT` streams, writes while an insert's blocks are stored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// Rows read from tables.
+    pub read_rows: u64,
+    /// Uncompressed bytes read from tables.
+    pub read_bytes: u64,
+    /// Estimate of the rows the query reads in total.
+    pub total_rows_to_read: u64,
+    /// Rows written into tables.
+    pub written_rows: u64,
+    /// Uncompressed bytes written into tables.
+    pub written_bytes: u64,
+    /// Time the query has run; zero from servers that do not report it.
+    pub elapsed: Duration,
+}
+
+impl Progress {
+    /// Adds the work reported by a later packet.
+    pub(crate) fn add(&mut self, increment: &Self) {
+        self.read_rows = self.read_rows.saturating_add(increment.read_rows);
+        self.read_bytes = self.read_bytes.saturating_add(increment.read_bytes);
+        self.total_rows_to_read = self
+            .total_rows_to_read
+            .saturating_add(increment.total_rows_to_read);
+        self.written_rows = self.written_rows.saturating_add(increment.written_rows);
+        self.written_bytes = self.written_bytes.saturating_add(increment.written_bytes);
+        self.elapsed = self.elapsed.saturating_add(increment.elapsed);
+    }
+}
+
+/// Totals of a query's result, sent once before its end.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProfileInfo {
+    /// Rows in the result.
+    pub rows: u64,
+    /// Blocks the result was sent in.
+    pub blocks: u64,
+    /// Uncompressed bytes of the result.
+    pub bytes: u64,
+    /// Whether a `LIMIT` cut the result short.
+    pub applied_limit: bool,
+    /// Rows the result would have had without its `LIMIT`, when
+    /// [`Self::calculated_rows_before_limit`] is set.
+    pub rows_before_limit: u64,
+    /// Whether the server counted [`Self::rows_before_limit`].
+    pub calculated_rows_before_limit: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::Progress;
+
+    #[test]
+    fn increments_add_up() {
+        let mut progress = Progress {
+            read_rows: 10,
+            elapsed: Duration::from_millis(5),
+            ..Progress::default()
+        };
+        progress.add(&Progress {
+            read_rows: 5,
+            written_rows: 3,
+            total_rows_to_read: u64::MAX,
+            elapsed: Duration::from_millis(2),
+            ..Progress::default()
+        });
+        assert_eq!(
+            progress,
+            Progress {
+                read_rows: 15,
+                written_rows: 3,
+                total_rows_to_read: u64::MAX,
+                elapsed: Duration::from_millis(7),
+                ..Progress::default()
+            }
+        );
+    }
+}
@@ -0,0 +1,371 @@
+//! Packets of the native TCP protocol.
+//!
+//! Every packet starts with a varint code. Which fields follow depends on
+//! the protocol revision both sides speak, the lower of the two they
+//! announce in their handshakes.
+
+use std::{
+    io::{self, Read},
+    time::Duration,
+};
+
+use crate::{
+    compression::{CompressedReader, CompressedWriter, CompressionMethod},
+    error::{Error, Result},
+    io::{read_string, read_uvarint, write_string, write_uvarint},
+    native::{Block, NativeOptions, NativeReader, NativeWriter},
+    rowbinary::Schema,
+};
+
+use super::{
+    ClientOptions, ServerInfo,
+    progress::{ProfileInfo, Progress},
+};
+
+/// Protocol revision the client speaks.
+pub(super) const REVISION: u64 = 54460;
+/// Oldest revision the client talks to, the first to flag the
+/// serialization of each column.
+pub(super) const MIN_REVISION: u64 = 54454;
+/// Revision from which the client sends a quota key after the handshake.
+const ADDENDUM_REVISION: u64 = 54458;
+/// Revision from which queries carry parameters.
+const PARAMETERS_REVISION: u64 = 54459;
+/// Revision from which progress packets report the query's running time.
+const QUERY_TIME_REVISION: u64 = 54460;
+
+pub(super) const CLIENT_HELLO: u64 = 0;
+pub(super) const CLIENT_QUERY: u64 = 1;
+pub(super) const CLIENT_DATA: u64 = 2;
+pub(super) const CLIENT_CANCEL: u64 = 3;
+pub(super) const CLIENT_PING: u64 = 4;
+
+pub(super) const SERVER_HELLO: u64 = 0;
+pub(super) const SERVER_DATA: u64 = 1;
+pub(super) const SERVER_EXCEPTION: u64 = 2;
+pub(super) const SERVER_PROGRESS: u64 = 3;
+pub(super) const SERVER_PONG: u64 = 4;
+pub(super) const SERVER_END_OF_STREAM: u64 = 5;
+pub(super) const SERVER_PROFILE_INFO: u64 = 6;
+pub(super) const SERVER_TOTALS: u64 = 7;
+pub(super) const SERVER_EXTREMES: u64 = 8;
+pub(super) const SERVER_LOG: u64 = 10;
+pub(super) const SERVER_TABLE_COLUMNS: u64 = 11;
+pub(super) const SERVER_PROFILE_EVENTS: u64 = 14;
+
+/// Name the client introduces itself with.
+pub(super) const CLIENT_NAME: &str = "clickhouse-rowbinary";
+/// Query kind of a query sent by a user rather than another server.
+const INITIAL_QUERY: u8 = 1;
+/// Interface code of the TCP protocol.
+const TCP_INTERFACE: u8 = 1;
+/// Setting flag that makes the server reject a setting it does not know.
+const IMPORTANT_SETTING: u64 = 1;
+/// Processing stage that runs a query to its end.
+const COMPLETE_STAGE: u64 = 2;
+
+/// A packet sent by the server.
+pub(super) enum Packet {
+    /// Rows of the result, or the block structure of an insert.
+    Data(Block),
+    /// Totals computed by `WITH TOTALS`.
+    Totals(Block),
+    /// Minimums and maximums computed with `extremes = 1`.
+    Extremes(Block),
+    /// Work done since the previous progress packet.
+    Progress(Progress),
+    /// Totals of the result.
+    ProfileInfo(ProfileInfo),
+    /// The query failed.
+    Exception(Error),
+    /// The query ended.
+    EndOfStream,
+    /// Answer to a ping.
+    Pong,
+    /// Logs, profile events and column descriptions, which the client
+    /// does not use.
+    Skipped,
+}
+
+/// Returns the crate version as major, minor and patch numbers.
+fn version() -> [u64; 3] {
+    [
+        env!("CARGO_PKG_VERSION_MAJOR"),
+        env!("CARGO_PKG_VERSION_MINOR"),
+        env!("CARGO_PKG_VERSION_PATCH"),
+    ]
+    .map(|part| part.parse().unwrap_or(0))
+}
+
+/// Layout of the blocks in `Data` packets.
+pub(super) fn block_options() -> NativeOptions {
+    NativeOptions {
+        block_info: true,
+        custom_serialization: true,
+        ..NativeOptions::default()
+    }
+}
+
+/// Writes the handshake that opens a connection.
+pub(super) fn write_hello(options: &ClientOptions, out: &mut Vec<u8>) -> Result<()> {
+    let [major, minor, _] = version();
+    write_uvarint(CLIENT_HELLO, out)?;
+    write_string(CLIENT_NAME, out)?;
+    write_uvarint(major, out)?;
+    write_uvarint(minor, out)?;
+    write_uvarint(REVISION, out)?;
+    write_string(options.database.as_deref().unwrap_or_default(), out)?;
+    write_string(&options.user, out)?;
+    write_string(&options.password, out)
+}
+
+/// Writes what follows the server's handshake at `revision`: an empty
+/// quota key.
+pub(super) fn write_addendum(revision: u64, out: &mut Vec<u8>) -> Result<()> {
+    if revision >= ADDENDUM_REVISION {
+        write_string("", out)?;
+    }
+    Ok(())
+}
+
+/// Reads the server's handshake.
+pub(super) fn read_hello<R: Read>(reader: &mut R) -> Result<ServerInfo> {
+    match read_varint(reader)? {
+        SERVER_HELLO => {}
+        SERVER_EXCEPTION => return Err(read_exception(reader)?),
+        code => return Err(unexpected(code)),
+    }
+    let name = read_str(reader)?;
+    let version_major = read_varint(reader)?;
+    let version_minor = read_varint(reader)?;
+    let revision = read_varint(reader)?;
+    if revision < MIN_REVISION {
+        return Err(Error::Protocol(format!(
+            "server speaks revision {revision}, older than the oldest supported {MIN_REVISION}"
+        )));
+    }
+    let timezone = read_str(reader)?;
+    let display_name = read_str(reader)?;
+    let version_patch = read_varint(reader)?;
+    Ok(ServerInfo {
+        name,
+        version_major,
+        version_minor,
+        version_patch,
+        revision,
+        timezone,
+        display_name,
+    })
+}
+
+/// Writes a query that runs `sql` with `settings`, for the server to
+/// answer with compressed blocks when `compressed` is set.
+pub(super) fn write_query(
+    revision: u64,
+    sql: &str,
+    settings: &[(String, String)],
+    compressed: bool,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    let [major, minor, patch] = version();
+    write_uvarint(CLIENT_QUERY, out)?;
+    // The server picks the query id.
+    write_string("", out)?;
+
+    out.push(INITIAL_QUERY);
+    // Initial user and query id, which only servers forwarding a query set.
+    write_string("", out)?;
+    write_string("", out)?;
+    write_string("0.0.0.0:0", out)?;
+    // Start time of the initial query.
+    out.extend_from_slice(&0_i64.to_le_bytes());
+    out.push(TCP_INTERFACE);
+    // Operating system user and host name.
+    write_string("", out)?;
+    write_string("", out)?;
+    write_string(CLIENT_NAME, out)?;
+    write_uvarint(major, out)?;
+    write_uvarint(minor, out)?;
+    write_uvarint(REVISION, out)?;
+    // Quota key and distributed depth.
+    write_string("", out)?;
+    write_uvarint(0, out)?;
+    write_uvarint(patch, out)?;
+    // No OpenTelemetry trace, then the three parallel replica fields.
+    out.push(0);
+    for _ in 0..3 {
+        write_uvarint(0, out)?;
+    }
+
+    for (name, value) in settings {
+        write_string(name, out)?;
+        write_uvarint(IMPORTANT_SETTING, out)?;
+        write_string(value, out)?;
+    }
+    write_string("", out)?;
+    // Interserver secret.
+    write_string("", out)?;
+    write_uvarint(COMPLETE_STAGE, out)?;
+    write_uvarint(u64::from(compressed), out)?;
+    write_string(sql, out)?;
+    if revision >= PARAMETERS_REVISION {
+        write_string("", out)?;
+    }
+    Ok(())
+}
+
+/// Writes a `Data` packet carrying `block`.
+pub(super) fn write_data(
+    block: &Block,
+    compression: Option<CompressionMethod>,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    write_uvarint(CLIENT_DATA, out)?;
+    write_block(block, compression, out)
+}
+
+/// Writes the empty `Data` packet that ends a stream of blocks.
+pub(super) fn write_end_of_data(
+    compression: Option<CompressionMethod>,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    write_data(
+        &Block::new(Schema::new(Vec::new()), Vec::new())?,
+        compression,
+        out,
+    )
+}
+
+/// Writes `block` after the name of the temporary table it fills, empty
+/// for query data.
+pub(super) fn write_block(
+    block: &Block,
+    compression: Option<CompressionMethod>,
+    out: &mut Vec<u8>,
+) -> Result<()> {
+    write_string("", out)?;
+    match compression {
+        Some(method) => {
+            let compressed = CompressedWriter::new(&mut *out, method);
+            let mut writer = NativeWriter::with_options(compressed, block_options());
+            writer.write_block(block)?;
+            writer.into_inner().finish()?;
+        }
+        None => NativeWriter::with_options(&mut *out, block_options()).write_block(block)?,
+    }
+    Ok(())
+}
+
+/// Reads the next server packet.
+pub(super) fn read_packet<R: Read>(
+    reader: &mut R,
+    revision: u64,
+    compressed: bool,
+) -> Result<Packet> {
+    Ok(match read_varint(reader)? {
+        SERVER_DATA => Packet::Data(read_block(reader, compressed)?),
+        SERVER_EXCEPTION => Packet::Exception(read_exception(reader)?),
+        SERVER_PROGRESS => Packet::Progress(read_progress(reader, revision)?),
+        SERVER_PONG => Packet::Pong,
+        SERVER_END_OF_STREAM => Packet::EndOfStream,
+        SERVER_PROFILE_INFO => Packet::ProfileInfo(read_profile_info(reader)?),
+        SERVER_TOTALS => Packet::Totals(read_block(reader, compressed)?),
+        SERVER_EXTREMES => Packet::Extremes(read_block(reader, compressed)?),
+        // Logs and profile events are never compressed.
+        SERVER_LOG | SERVER_PROFILE_EVENTS => {
+            read_block(reader, false)?;
+            Packet::Skipped
+        }
+        SERVER_TABLE_COLUMNS => {
+            read_str(reader)?;
+            read_str(reader)?;
+            Packet::Skipped
+        }
+        code => return Err(unexpected(code)),
+    })
+}
+
+/// Reads a block after the name of the table it belongs to.
+pub(super) fn read_block<R: Read>(reader: &mut R, compressed: bool) -> Result<Block> {
+    read_str(reader)?;
+    let block = if compressed {
+        NativeReader::with_options(CompressedReader::new(&mut *reader), block_options())
+            .read_block()?
+    } else {
+        NativeReader::with_options(&mut *reader, block_options()).read_block()?
+    };
+    block.ok_or_else(closed)
+}
+
+/// Reads an exception, keeping the code and message of the outermost one.
+fn read_exception<R: Read>(reader: &mut R) -> Result<Error> {
+    let mut code = [0_u8; 4];
+    reader.read_exact(&mut code)?;
+    let _name = read_str(reader)?;
+    let message = read_str(reader)?;
+    let _stack_trace = read_str(reader)?;
+    if read_byte(reader)? != 0 {
+        read_exception(reader)?;
+    }
+    Ok(Error::Server {
+        code: i32::from_le_bytes(code),
+        message: message.trim().to_string(),
+    })
+}
+
+fn read_progress<R: Read>(reader: &mut R, revision: u64) -> Result<Progress> {
+    let read_rows = read_varint(reader)?;
+    let read_bytes = read_varint(reader)?;
+    let total_rows_to_read = read_varint(reader)?;
+    let written_rows = read_varint(reader)?;
+    let written_bytes = read_varint(reader)?;
+    let elapsed = if revision >= QUERY_TIME_REVISION {
+        Duration::from_nanos(read_varint(reader)?)
+    } else {
+        Duration::ZERO
+    };
+    Ok(Progress {
+        read_rows,
+        read_bytes,
+        total_rows_to_read,
+        written_rows,
+        written_bytes,
+        elapsed,
+    })
+}
+
+fn read_profile_info<R: Read>(reader: &mut R) -> Result<ProfileInfo> {
+    Ok(ProfileInfo {
+        rows: read_varint(reader)?,
+        blocks: read_varint(reader)?,
+        bytes: read_varint(reader)?,
+        applied_limit: read_byte(reader)? != 0,
+        rows_before_limit: read_varint(reader)?,
+        calculated_rows_before_limit: read_byte(reader)? != 0,
+    })
+}
+
+pub(super) fn read_varint<R: Read>(reader: &mut R) -> Result<u64> {
+    read_uvarint(reader)?.ok_or_else(closed)
+}
+
+pub(super) fn read_str<R: Read>(reader: &mut R) -> Result<String> {
+    read_string(reader)?.ok_or_else(closed)
+}
+
+pub(super) fn read_byte<R: Read>(reader: &mut R) -> Result<u8> {
+    let mut byte = [0_u8; 1];
+    reader.read_exact(&mut byte)?;
+    Ok(byte[0])
+}
+
+fn closed() -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::UnexpectedEof,
+        "connection closed by the server",
+    ))
+}
+
+pub(super) fn unexpected(code: u64) -> Error {
+    Error::Protocol(format!("unexpected packet {code} from the server"))
+}
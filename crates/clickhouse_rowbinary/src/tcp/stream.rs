@@ -0,0 +1,103 @@
+//! Connection streams, plain or over TLS.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+};
+
+#[cfg(feature = "tls")]
+use std::sync::Arc;
+
+#[cfg(feature = "tls")]
+use rustls::{
+    ClientConfig, ClientConnection, RootCertStore, StreamOwned, crypto::ring, pki_types::ServerName,
+};
+
+#[cfg(feature = "tls")]
+use super::TlsOptions;
+#[cfg(feature = "tls")]
+use crate::error::{Error, Result};
+
+/// Byte stream of one connection.
+pub(super) enum Stream {
+    Plain(TcpStream),
+    #[cfg(feature = "tls")]
+    Tls(Box<StreamOwned<ClientConnection, TcpStream>>),
+}
+
+impl Stream {
+    /// Runs the TLS handshake over `tcp`, verifying that the server's
+    /// certificate is valid for `host`, or for the name `options` sets.
+    #[cfg(feature = "tls")]
+    pub fn tls(mut tcp: TcpStream, host: &str, options: &TlsOptions) -> Result<Self> {
+        let name = options.server_name.as_deref().unwrap_or(host);
+        let name = ServerName::try_from(name.to_string())
+            .map_err(|_| Error::InvalidValue("TLS server name is not a host name or address"))?;
+        let mut connection = ClientConnection::new(client_config(options)?, name)
+            .map_err(|err| Error::Protocol(err.to_string()))?;
+        while connection.is_handshaking() {
+            connection.complete_io(&mut tcp)?;
+        }
+        Ok(Self::Tls(Box::new(StreamOwned::new(connection, tcp))))
+    }
+
+    /// Returns the TCP connection the stream runs over.
+    pub fn tcp(&self) -> &TcpStream {
+        match self {
+            Self::Plain(tcp) => tcp,
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.get_ref(),
+        }
+    }
+}
+
+impl Read for Stream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(tcp) => tcp.read(buf),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.read(buf),
+        }
+    }
+}
+
+impl Write for Stream {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self {
+            Self::Plain(tcp) => tcp.write(buf),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.write(buf),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self {
+            Self::Plain(tcp) => tcp.flush(),
+            #[cfg(feature = "tls")]
+            Self::Tls(stream) => stream.flush(),
+        }
+    }
+}
+
+/// Builds the rustls configuration trusting the CA bundle of `options`, or
+/// the Mozilla roots bundled with `webpki-roots` without one.
+#[cfg(feature = "tls")]
+fn client_config(options: &TlsOptions) -> Result<Arc<ClientConfig>> {
+    let mut roots = RootCertStore::empty();
+    match &options.ca_certificates {
+        Some(pem) => {
+            for certificate in crate::tls::certificates(pem)? {
+                roots
+                    .add(certificate)
+                    .map_err(|_| Error::InvalidValue("CA bundle holds an invalid certificate"))?;
+            }
+        }
+        None => roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned()),
+    }
+    let config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .map_err(|err| Error::Protocol(err.to_string()))?
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    Ok(Arc::new(config))
+}
@@ -0,0 +1,557 @@
+//! SQL literal text as used by the `Values` format and for values nested
+//! inside arrays, maps and tuples of the other text formats.
+//!
+//! Strings, dates and other non-numeric scalars are single-quoted with
+//! backslash escapes, numbers are bare, `NULL` is a keyword, and
+//! composites use `[..]`, `(..)` and `{key:value}`.
+
+use std::borrow::Cow;
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::{
+        JsonOptions,
+        json::{parse as parse_json, read_value as read_json, write_value as write_json},
+    },
+    rowbinary::default_value,
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+use super::{
+    fixed_string, format_scalar, infer_array, infer_number, is_composite, is_numeric, parse_scalar,
+    text_type,
+};
+
+const MAX_DEPTH: usize = 256;
+
+/// Parsed literal borrowing from its input.
+#[derive(Debug)]
+pub(crate) enum Literal<'a> {
+    Null,
+    /// Unquoted token such as a number, `true` or `inf`.
+    Bare(&'a str),
+    String(Cow<'a, [u8]>),
+    Array(Vec<Literal<'a>>),
+    Tuple(Vec<Literal<'a>>),
+    Map(Vec<(Literal<'a>, Literal<'a>)>),
+}
+
+/// Parses one literal at the start of `input`, returning it with the
+/// number of bytes consumed, leading whitespace included.
+pub(crate) fn parse_literal_prefix(input: &[u8]) -> Result<(Literal<'_>, usize)> {
+    let mut parser = Parser { input, pos: 0 };
+    let literal = parser.literal(0)?;
+    Ok((literal, parser.pos))
+}
+
+/// Parses the single literal making up `input`, which may be surrounded
+/// by whitespace.
+pub(crate) fn parse_literal(input: &[u8]) -> Result<Literal<'_>> {
+    let (literal, used) = parse_literal_prefix(input)?;
+    if !input[used..].iter().all(u8::is_ascii_whitespace) {
+        return Err(Error::InvalidValue("trailing characters after literal"));
+    }
+    Ok(literal)
+}
+
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&mut self) -> Result<u8> {
+        while self
+            .input
+            .get(self.pos)
+            .is_some_and(u8::is_ascii_whitespace)
+        {
+            self.pos += 1;
+        }
+        self.input
+            .get(self.pos)
+            .copied()
+            .ok_or(Error::InvalidValue("unexpected end of literal"))
+    }
+
+    /// Parses `close`-terminated, comma-separated items after an opening
+    /// bracket.
+    fn items<T>(
+        &mut self,
+        close: u8,
+        mut item: impl FnMut(&mut Self) -> Result<T>,
+    ) -> Result<Vec<T>> {
+        self.pos += 1;
+        let mut items = Vec::new();
+        if self.peek()? == close {
+            self.pos += 1;
+            return Ok(items);
+        }
+        loop {
+            items.push(item(self)?);
+            match self.peek()? {
+                b',' => self.pos += 1,
+                byte if byte == close => {
+                    self.pos += 1;
+                    return Ok(items);
+                }
+                _ => return Err(Error::InvalidValue("expected ',' between literal items")),
+            }
+        }
+    }
+
+    fn literal(&mut self, depth: usize) -> Result<Literal<'a>> {
+        let byte = self.peek()?;
+        if matches!(byte, b'[' | b'(' | b'{') && depth >= MAX_DEPTH {
+            return Err(Error::InvalidValue("literal nesting is too deep"));
+        }
+        match byte {
+            b'\'' => self.string().map(Literal::String),
+            b'[' => self
+                .items(b']', |parser| parser.literal(depth + 1))
+                .map(Literal::Array),
+            b'(' => self
+                .items(b')', |parser| parser.literal(depth + 1))
+                .map(Literal::Tuple),
+            b'{' => self
+                .items(b'}', |parser| {
+                    let key = parser.literal(depth + 1)?;
+                    if parser.peek()? != b':' {
+                        return Err(Error::InvalidValue("expected ':' after map key"));
+                    }
+                    parser.pos += 1;
+                    Ok((key, parser.literal(depth + 1)?))
+                })
+                .map(Literal::Map),
+            _ => {
+                let start = self.pos;
+                while self.input.get(self.pos).is_some_and(|b| {
+                    b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.' | b'_')
+                }) {
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    return Err(Error::InvalidValue("invalid literal"));
+                }
+                // The scanned bytes are ASCII by construction.
+                let token = std::str::from_utf8(&self.input[start..self.pos])
+                    .map_err(|_| Error::InvalidValue("invalid literal"))?;
+                Ok(if token.eq_ignore_ascii_case("null") {
+                    Literal::Null
+                } else {
+                    Literal::Bare(token)
+                })
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<Cow<'a, [u8]>> {
+        self.pos += 1;
+        let start = self.pos;
+        let mut owned: Option<Vec<u8>> = None;
+        loop {
+            let Some(&byte) = self.input.get(self.pos) else {
+                return Err(Error::InvalidValue("unterminated string literal"));
+            };
+            match byte {
+                // SQL-style doubled quotes stand for one quote.
+                b'\'' if self.input.get(self.pos + 1) == Some(&b'\'') => {
+                    owned
+                        .get_or_insert_with(|| self.input[start..self.pos].to_vec())
+                        .push(b'\'');
+                    self.pos += 2;
+                }
+                b'\'' => {
+                    let borrowed = &self.input[start..self.pos];
+                    self.pos += 1;
+                    return Ok(owned.map_or(Cow::Borrowed(borrowed), Cow::Owned));
+                }
+                b'\\' => {
+                    let out = owned.get_or_insert_with(|| self.input[start..self.pos].to_vec());
+                    self.pos += 1;
+                    self.pos += unescape(&self.input[self.pos..], out)?;
+                }
+                _ => {
+                    if let Some(out) = owned.as_mut() {
+                        out.push(byte);
+                    }
+                    self.pos += 1;
+                }
+            }
+        }
+    }
+}
+
+/// Decodes the escape sequence following a backslash at the start of
+/// `input` into `out`, returning the number of bytes consumed.
+pub(crate) fn unescape(input: &[u8], out: &mut Vec<u8>) -> Result<usize> {
+    let Some(&escape) = input.first() else {
+        return Err(Error::InvalidValue("unterminated escape sequence"));
+    };
+    let decoded = match escape {
+        b'b' => 0x08,
+        b'f' => 0x0c,
+        b'n' => b'\n',
+        b'r' => b'\r',
+        b't' => b'\t',
+        b'0' => 0,
+        b'a' => 0x07,
+        b'v' => 0x0b,
+        b'x' => {
+            let digits = input
+                .get(1..3)
+                .and_then(|digits| std::str::from_utf8(digits).ok())
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or(Error::InvalidValue("invalid \\x escape sequence"))?;
+            out.push(digits);
+            return Ok(3);
+        }
+        other => other,
+    };
+    out.push(decoded);
+    Ok(1)
+}
+
+/// Appends `bytes` as a single-quoted string literal.
+pub(crate) fn write_quoted_string(bytes: &[u8], out: &mut Vec<u8>) {
+    out.push(b'\'');
+    for &byte in bytes {
+        match byte {
+            b'\'' => out.extend_from_slice(b"\\'"),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\t' => out.extend_from_slice(b"\\t"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0c => out.extend_from_slice(b"\\f"),
+            0 => out.extend_from_slice(b"\\0"),
+            _ => out.push(byte),
+        }
+    }
+    out.push(b'\'');
+}
+
+fn mismatch(ty: &TypeDesc, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+/// Appends `value` of type `ty` as a literal.
+pub(crate) fn write_literal(ty: &TypeDesc, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), value) {
+        (TypeDesc::Nothing, Value::Nothing)
+        | (TypeDesc::Nullable(_), Value::Nullable(None))
+        | (TypeDesc::Variant(_), Value::VariantNull)
+        | (TypeDesc::Dynamic { .. }, Value::DynamicNull | Value::Nullable(None)) => {
+            out.extend_from_slice(b"NULL");
+        }
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            write_literal(inner, value, out)?;
+        }
+        (TypeDesc::String, Value::String(bytes))
+        | (TypeDesc::FixedString { .. }, Value::FixedString(bytes)) => {
+            write_quoted_string(bytes, out);
+        }
+        (TypeDesc::Array(inner), Value::Array(items)) => {
+            write_items(out, b'[', b']', items, |item, out| {
+                write_literal(inner, item, out)
+            })?;
+        }
+        (TypeDesc::Nested(items), Value::Array(rows)) => {
+            write_items(out, b'[', b']', rows, |row, out| {
+                let Value::Tuple(values) = row else {
+                    return Err(mismatch(&TypeDesc::Tuple(items.clone()), row));
+                };
+                write_tuple(items, values, out)
+            })?;
+        }
+        (TypeDesc::Tuple(items), Value::Tuple(values)) => write_tuple(items, values, out)?,
+        (TypeDesc::Map { key, value: inner }, Value::Map(entries)) => {
+            write_items(out, b'{', b'}', entries, |(entry_key, entry_value), out| {
+                write_literal(key, entry_key, out)?;
+                out.push(b':');
+                write_literal(inner, entry_value, out)
+            })?;
+        }
+        (TypeDesc::Variant(types), Value::Variant { index, value }) => {
+            let ty = types
+                .get(usize::from(*index))
+                .ok_or(Error::InvalidValue("variant index out of range"))?;
+            write_literal(ty, value, out)?;
+        }
+        (TypeDesc::Dynamic { .. }, Value::Dynamic { ty, value }) => write_literal(ty, value, out)?,
+        (TypeDesc::Json { .. }, Value::JsonObject(_)) => {
+            let mut json = Vec::new();
+            write_json(&ty, value, &JsonOptions::default(), &mut json)?;
+            write_quoted_string(&json, out);
+        }
+        (TypeDesc::AggregateFunction { .. }, _) => {
+            return Err(Error::UnsupportedCombination(
+                "text formats do not support AggregateFunction columns".into(),
+            ));
+        }
+        (ty, value) => {
+            let mut text = String::new();
+            format_scalar(ty, value, &mut text)?;
+            if is_numeric(ty)
+                || matches!(ty, TypeDesc::DateTime { .. } | TypeDesc::DateTime64 { .. })
+            {
+                out.extend_from_slice(text.as_bytes());
+            } else {
+                write_quoted_string(text.as_bytes(), out);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn write_items<T>(
+    out: &mut Vec<u8>,
+    open: u8,
+    close: u8,
+    items: &[T],
+    mut write: impl FnMut(&T, &mut Vec<u8>) -> Result<()>,
+) -> Result<()> {
+    out.push(open);
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            out.push(b',');
+        }
+        write(item, out)?;
+    }
+    out.push(close);
+    Ok(())
+}
+
+fn write_tuple(items: &[TupleItem], values: &[Value], out: &mut Vec<u8>) -> Result<()> {
+    if items.len() != values.len() {
+        return Err(Error::InvalidValue("tuple length does not match its type"));
+    }
+    let pairs: Vec<_> = items.iter().zip(values).collect();
+    write_items(out, b'(', b')', &pairs, |(item, value), out| {
+        write_literal(&item.ty, value, out)
+    })
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes).map_err(|_| Error::InvalidValue("literal text is not valid UTF-8"))
+}
+
+fn unexpected(ty: &TypeDesc) -> Error {
+    Error::InvalidValue(match ty {
+        TypeDesc::Array(_) | TypeDesc::Nested(_) => "expected an array literal",
+        TypeDesc::Map { .. } => "expected a map literal",
+        TypeDesc::Tuple(_) => "expected a tuple literal",
+        _ => "expected a string literal",
+    })
+}
+
+/// Decodes `literal` as a value of type `ty`. `NULL` for a type that
+/// cannot hold one decodes as the type's default value.
+pub(crate) fn read_literal(ty: &TypeDesc, literal: &Literal<'_>) -> Result<Value> {
+    let ty = text_type(ty);
+    let ty = ty.as_ref();
+    Ok(match (ty, literal) {
+        (TypeDesc::AggregateFunction { .. }, _) => {
+            return Err(Error::UnsupportedCombination(
+                "text formats do not support AggregateFunction columns".into(),
+            ));
+        }
+        (TypeDesc::Nullable(_), Literal::Null) => Value::Nullable(None),
+        (TypeDesc::Nothing, Literal::Null) => Value::Nothing,
+        (TypeDesc::Variant(_), Literal::Null) => Value::VariantNull,
+        (TypeDesc::Dynamic { .. }, Literal::Null) => Value::DynamicNull,
+        (_, Literal::Null) => default_value(ty)?,
+        (TypeDesc::Nullable(inner), literal) => {
+            Value::Nullable(Some(Box::new(read_literal(inner, literal)?)))
+        }
+        (TypeDesc::String, Literal::String(bytes)) => Value::String(bytes.to_vec()),
+        (TypeDesc::FixedString { length }, Literal::String(bytes)) => fixed_string(bytes, *length)?,
+        (TypeDesc::Array(inner), Literal::Array(items)) => Value::Array(
+            items
+                .iter()
+                .map(|item| read_literal(inner, item))
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Nested(items), Literal::Array(rows)) => Value::Array(
+            rows.iter()
+                .map(|row| match row {
+                    Literal::Tuple(values) => read_tuple(items, values),
+                    _ => Err(unexpected(&TypeDesc::Tuple(Vec::new()))),
+                })
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Tuple(items), Literal::Tuple(values)) => read_tuple(items, values)?,
+        (TypeDesc::Map { key, value }, Literal::Map(entries)) => Value::Map(
+            entries
+                .iter()
+                .map(|(entry_key, entry_value)| {
+                    Ok((
+                        read_literal(key, entry_key)?,
+                        read_literal(value, entry_value)?,
+                    ))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        (TypeDesc::Variant(types), literal) => read_variant(types, literal)?,
+        (TypeDesc::Dynamic { .. }, literal) => match infer_literal(literal)? {
+            Some((ty, value)) => Value::Dynamic {
+                ty: Box::new(ty),
+                value: Box::new(value),
+            },
+            None => Value::DynamicNull,
+        },
+        (TypeDesc::Json { .. }, Literal::String(bytes)) => read_json(ty, &parse_json(bytes)?)?,
+        (ty, Literal::Bare(text)) if !is_composite(ty) => parse_scalar(ty, text)?,
+        (ty, Literal::String(bytes)) if !is_composite(ty) => parse_scalar(ty, utf8(bytes)?)?,
+        (ty, _) => return Err(unexpected(ty)),
+    })
+}
+
+fn read_tuple(items: &[TupleItem], values: &[Literal<'_>]) -> Result<Value> {
+    if items.len() != values.len() {
+        return Err(Error::InvalidValue(
+            "tuple literal length does not match its type",
+        ));
+    }
+    Ok(Value::Tuple(
+        items
+            .iter()
+            .zip(values)
+            .map(|(item, literal)| read_literal(&item.ty, literal))
+            .collect::<Result<_>>()?,
+    ))
+}
+
+/// Picks the first variant whose literal shape matches, falling back to
+/// the first alternative that accepts the literal at all.
+fn read_variant(types: &[TypeDesc], literal: &Literal<'_>) -> Result<Value> {
+    let natural = |ty: &&TypeDesc| {
+        let ty = text_type(ty);
+        match literal {
+            Literal::Null => false,
+            Literal::Bare(_) => is_numeric(&ty),
+            Literal::String(_) => !is_numeric(&ty) && !is_composite(&ty),
+            Literal::Array(_) => matches!(ty.as_ref(), TypeDesc::Array(_) | TypeDesc::Nested(_)),
+            Literal::Tuple(_) => matches!(ty.as_ref(), TypeDesc::Tuple(_)),
+            Literal::Map(_) => matches!(ty.as_ref(), TypeDesc::Map { .. }),
+        }
+    };
+    let matching = types.iter().enumerate().filter(|(_, ty)| natural(ty));
+    for (index, ty) in matching.chain(types.iter().enumerate()) {
+        if let Ok(value) = read_literal(ty, literal) {
+            return Ok(Value::Variant {
+                index: u8::try_from(index).map_err(|_| Error::Overflow("too many variants"))?,
+                value: Box::new(value),
+            });
+        }
+    }
+    Err(Error::InvalidValue(
+        "literal matches no variant alternative",
+    ))
+}
+
+/// Infers the type of a literal stored in a `Dynamic` column, or `None`
+/// for `NULL`.
+pub(crate) fn infer_literal(literal: &Literal<'_>) -> Result<Option<(TypeDesc, Value)>> {
+    Ok(Some(match literal {
+        Literal::Null => return Ok(None),
+        Literal::Bare("true") => (TypeDesc::Bool, Value::Bool(true)),
+        Literal::Bare("false") => (TypeDesc::Bool, Value::Bool(false)),
+        Literal::Bare(text) => infer_number(text)?,
+        Literal::String(bytes) => (TypeDesc::String, Value::String(bytes.to_vec())),
+        Literal::Array(items) => infer_array(
+            items
+                .iter()
+                .map(infer_literal)
+                .collect::<Result<Vec<_>>>()?,
+        )?,
+        Literal::Tuple(items) => {
+            let mut types = Vec::with_capacity(items.len());
+            let mut values = Vec::with_capacity(items.len());
+            for item in items {
+                let (ty, value) = infer_literal(item)?.ok_or_else(|| {
+                    Error::UnsupportedCombination("Dynamic tuple elements cannot be NULL".into())
+                })?;
+                types.push(TupleItem { name: None, ty });
+                values.push(value);
+            }
+            (TypeDesc::Tuple(types), Value::Tuple(values))
+        }
+        Literal::Map(_) => {
+            return Err(Error::UnsupportedCombination(
+                "Dynamic values cannot hold map literals".into(),
+            ));
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::parse_type_desc;
+
+    fn round_trip(type_name: &str, value: &Value, text: &str) {
+        let ty = parse_type_desc(type_name).unwrap();
+        let mut out = Vec::new();
+        write_literal(&ty, value, &mut out).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), text, "{type_name}");
+        let literal = parse_literal(text.as_bytes()).unwrap();
+        assert_eq!(&read_literal(&ty, &literal).unwrap(), value, "{type_name}");
+    }
+
+    #[test]
+    fn literals_round_trip() {
+        let string = |text: &str| Value::String(text.as_bytes().to_vec());
+        round_trip("String", &string("it's\\\n"), r"'it\'s\\\n'");
+        round_trip(
+            "Array(Nullable(Date))",
+            &Value::Array(vec![
+                Value::Nullable(Some(Box::new(Value::Date(1)))),
+                Value::Nullable(None),
+            ]),
+            "['1970-01-02',NULL]",
+        );
+        round_trip(
+            "Map(String, Tuple(UInt8, Float64))",
+            &Value::Map(vec![(
+                string("k"),
+                Value::Tuple(vec![Value::UInt8(1), Value::Float64(f64::INFINITY)]),
+            )]),
+            "{'k':(1,inf)}",
+        );
+        round_trip(
+            "Variant(String, UInt8)",
+            &Value::Variant {
+                index: 1,
+                value: Box::new(Value::UInt8(4)),
+            },
+            "4",
+        );
+    }
+
+    #[test]
+    fn parsing_accepts_sql_spellings() {
+        let ty = parse_type_desc("Array(String)").unwrap();
+        let literal = parse_literal(b" [ 'a''b' , '\\x41' ] ").unwrap();
+        assert_eq!(
+            read_literal(&ty, &literal).unwrap(),
+            Value::Array(vec![
+                Value::String(b"a'b".to_vec()),
+                Value::String(b"A".to_vec())
+            ])
+        );
+        let dynamic = parse_literal(b"[1, 2.5, null]").unwrap();
+        assert_eq!(
+            infer_literal(&dynamic).unwrap().unwrap().0,
+            parse_type_desc("Array(Nullable(Float64))").unwrap()
+        );
+        assert!(parse_literal(b"[1, 2").is_err());
+        assert!(parse_literal(b"'abc").is_err());
+        assert!(parse_literal(b"1 2").is_err());
+    }
+}
@@ -0,0 +1,693 @@
+//! Text renderings of scalar values shared by the text formats.
+//!
+//! Scalars are written the way `ClickHouse` prints them with default
+//! settings: dates as `YYYY-MM-DD`, decimals without trailing zeros and
+//! enums by name. Date-times are written as Unix timestamps, such as
+//! `1700000000.123`, which the server reads as the same instant whatever
+//! the column timezone. On input, both timestamps and
+//! `YYYY-MM-DD hh:mm:ss` text are accepted, the latter read in UTC.
+
+pub(crate) mod literal;
+
+use std::{
+    borrow::Cow,
+    fmt::{Display, LowerExp, Write as _},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+use num_bigint::{BigInt, BigUint, Sign};
+use num_traits::ToPrimitive;
+use uuid::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    types::{DecimalSize, TypeDesc, can_be_inside_nullable, datetime64_ticks_per_second},
+    value::Value,
+};
+
+const SECONDS_PER_DAY: i64 = 86_400;
+/// Days from 0000-03-01 to 1970-01-01 in the proleptic Gregorian calendar.
+const UNIX_EPOCH_DAYS: i64 = 719_468;
+const DAYS_PER_ERA: i64 = 146_097;
+
+/// Resolves types whose values are stored as another type.
+pub(crate) fn text_type(ty: &TypeDesc) -> Cow<'_, TypeDesc> {
+    match ty {
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
+            text_type(inner)
+        }
+        _ => Cow::Borrowed(ty.geo_storage().unwrap_or(ty)),
+    }
+}
+
+/// Returns the scale and storage size of a decimal type.
+pub(crate) fn decimal_parts(ty: &TypeDesc) -> Option<(u8, DecimalSize)> {
+    match ty {
+        TypeDesc::Decimal { scale, size, .. } => Some((*scale, *size)),
+        TypeDesc::Decimal32 { scale } => Some((*scale, DecimalSize::Bits32)),
+        TypeDesc::Decimal64 { scale } => Some((*scale, DecimalSize::Bits64)),
+        TypeDesc::Decimal128 { scale } => Some((*scale, DecimalSize::Bits128)),
+        TypeDesc::Decimal256 { scale } => Some((*scale, DecimalSize::Bits256)),
+        _ => None,
+    }
+}
+
+/// Reports whether values of `ty` are written as bare numbers.
+pub(crate) fn is_numeric(ty: &TypeDesc) -> bool {
+    matches!(
+        ty,
+        TypeDesc::Bool
+            | TypeDesc::UInt8
+            | TypeDesc::UInt16
+            | TypeDesc::UInt32
+            | TypeDesc::UInt64
+            | TypeDesc::UInt128
+            | TypeDesc::UInt256
+            | TypeDesc::Int8
+            | TypeDesc::Int16
+            | TypeDesc::Int32
+            | TypeDesc::Int64
+            | TypeDesc::Int128
+            | TypeDesc::Int256
+            | TypeDesc::Float32
+            | TypeDesc::Float64
+            | TypeDesc::Float16
+            | TypeDesc::BFloat16
+            | TypeDesc::Interval(_)
+    ) || decimal_parts(ty).is_some()
+}
+
+/// Reports whether values of `ty` hold other values.
+pub(crate) fn is_composite(ty: &TypeDesc) -> bool {
+    matches!(
+        ty,
+        TypeDesc::Array(_)
+            | TypeDesc::Nested(_)
+            | TypeDesc::Tuple(_)
+            | TypeDesc::Map { .. }
+            | TypeDesc::Json { .. }
+    )
+}
+
+/// Infers the `Dynamic` type of a number: `Int64`, then `UInt64`, then
+/// `Float64`.
+pub(crate) fn infer_number(text: &str) -> Result<(TypeDesc, Value)> {
+    if !text.contains(['.', 'e', 'E']) {
+        if let Ok(value) = text.parse::<i64>() {
+            return Ok((TypeDesc::Int64, Value::Int64(value)));
+        }
+        if let Ok(value) = text.parse::<u64>() {
+            return Ok((TypeDesc::UInt64, Value::UInt64(value)));
+        }
+    }
+    Ok((TypeDesc::Float64, Value::Float64(parse_float(text)?)))
+}
+
+/// Infers the `Dynamic` type of an array from its inferred elements, with
+/// `None` for nulls. Mixed integer and float elements become `Float64`,
+/// and nulls make the element type `Nullable`.
+pub(crate) fn infer_array(items: Vec<Option<(TypeDesc, Value)>>) -> Result<(TypeDesc, Value)> {
+    let is_number =
+        |ty: &TypeDesc| matches!(ty, TypeDesc::Int64 | TypeDesc::UInt64 | TypeDesc::Float64);
+    let mut element: Option<TypeDesc> = None;
+    for (ty, _) in items.iter().flatten() {
+        match &element {
+            None => element = Some(ty.clone()),
+            Some(current) if current == ty => {}
+            Some(current) if is_number(current) && is_number(ty) => {
+                element = Some(TypeDesc::Float64);
+            }
+            Some(_) => {
+                return Err(Error::UnsupportedCombination(
+                    "array elements of a Dynamic value must share one type".into(),
+                ));
+            }
+        }
+    }
+    let element = element.unwrap_or(TypeDesc::Nothing);
+    let promote = |value: Value| match value {
+        #[allow(clippy::cast_precision_loss)]
+        Value::Int64(number) if element == TypeDesc::Float64 => Value::Float64(number as f64),
+        #[allow(clippy::cast_precision_loss)]
+        Value::UInt64(number) if element == TypeDesc::Float64 => Value::Float64(number as f64),
+        value => value,
+    };
+    if element != TypeDesc::Nothing && items.iter().all(Option::is_some) {
+        let values = items
+            .into_iter()
+            .flatten()
+            .map(|(_, value)| promote(value))
+            .collect();
+        return Ok((TypeDesc::Array(Box::new(element)), Value::Array(values)));
+    }
+    if element != TypeDesc::Nothing && !can_be_inside_nullable(&element) {
+        return Err(Error::UnsupportedCombination(format!(
+            "array of {} cannot hold null",
+            element.type_name()
+        )));
+    }
+    let values = items
+        .into_iter()
+        .map(|item| Value::Nullable(item.map(|(_, value)| Box::new(promote(value)))))
+        .collect();
+    Ok((
+        TypeDesc::Array(Box::new(TypeDesc::Nullable(Box::new(element)))),
+        Value::Array(values),
+    ))
+}
+
+/// Pads `bytes` with zeros to a `FixedString(length)` value.
+pub(crate) fn fixed_string(bytes: &[u8], length: usize) -> Result<Value> {
+    if bytes.len() > length {
+        return Err(Error::InvalidValue("FixedString value is too long"));
+    }
+    let mut padded = bytes.to_vec();
+    padded.resize(length, 0);
+    Ok(Value::FixedString(padded))
+}
+
+fn mismatch(ty: &TypeDesc, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+/// Appends the text of the scalar `value` of type `ty`.
+///
+/// Scalars are every type but strings, wrappers and composites, which
+/// each text format renders with its own quoting.
+///
+/// # Errors
+///
+/// Returns [`Error::TypeMismatch`] when `value` does not match `ty` or
+/// `ty` is not a scalar type.
+pub(crate) fn format_scalar(ty: &TypeDesc, value: &Value, out: &mut String) -> Result<()> {
+    match (ty, value) {
+        (TypeDesc::Bool, Value::Bool(value)) => out.push_str(if *value { "true" } else { "false" }),
+        (TypeDesc::UInt8, Value::UInt8(value)) => push(out, value),
+        (TypeDesc::UInt16, Value::UInt16(value)) => push(out, value),
+        (TypeDesc::UInt32, Value::UInt32(value)) => push(out, value),
+        (TypeDesc::UInt64, Value::UInt64(value)) => push(out, value),
+        (TypeDesc::UInt128, Value::UInt128(value)) => push(out, value),
+        (TypeDesc::UInt256, Value::UInt256(bytes)) => push(out, BigUint::from_bytes_le(bytes)),
+        (TypeDesc::Int8, Value::Int8(value)) => push(out, value),
+        (TypeDesc::Int16, Value::Int16(value)) => push(out, value),
+        (TypeDesc::Int32, Value::Int32(value)) => push(out, value),
+        (TypeDesc::Int64 | TypeDesc::Interval(_), Value::Int64(value)) => push(out, value),
+        (TypeDesc::Int128, Value::Int128(value)) => push(out, value),
+        (TypeDesc::Int256, Value::Int256(bytes)) => {
+            push(out, BigInt::from_signed_bytes_le(bytes));
+        }
+        (TypeDesc::Float32, Value::Float32(value))
+        | (TypeDesc::Float16, Value::Float16(value))
+        | (TypeDesc::BFloat16, Value::BFloat16(value)) => {
+            push_float(out, *value, f64::from(*value));
+        }
+        (TypeDesc::Float64, Value::Float64(value)) => push_float(out, *value, *value),
+        (TypeDesc::Date, Value::Date(days)) => push_date(out, i64::from(*days)),
+        (TypeDesc::Date32, Value::Date32(days)) => push_date(out, i64::from(*days)),
+        (TypeDesc::DateTime { .. }, Value::DateTime(seconds)) => push(out, seconds),
+        (TypeDesc::DateTime64 { precision, .. }, Value::DateTime64(ticks)) => {
+            let scale = datetime64_ticks_per_second(*precision)?.unsigned_abs();
+            if *ticks < 0 {
+                out.push('-');
+            }
+            push(out, ticks.unsigned_abs() / scale);
+            if *precision > 0 {
+                let fraction = ticks.unsigned_abs() % scale;
+                let _ = write!(out, ".{fraction:0width$}", width = usize::from(*precision));
+            }
+        }
+        (TypeDesc::Uuid, Value::Uuid(value)) => push(out, value),
+        (TypeDesc::Ipv4, Value::Ipv4(value)) => push(out, value),
+        (TypeDesc::Ipv6, Value::Ipv6(value)) => push(out, value),
+        (TypeDesc::Enum8(_), Value::Enum8(value)) => push_enum(out, ty, i16::from(*value))?,
+        (TypeDesc::Enum16(_), Value::Enum16(value)) => push_enum(out, ty, *value)?,
+        _ => {
+            let Some((scale, size)) = decimal_parts(ty) else {
+                return Err(mismatch(ty, value));
+            };
+            let mantissa = match (size, value) {
+                (DecimalSize::Bits32, Value::Decimal32(value)) => value.to_string(),
+                (DecimalSize::Bits64, Value::Decimal64(value)) => value.to_string(),
+                (DecimalSize::Bits128, Value::Decimal128(value)) => value.to_string(),
+                (DecimalSize::Bits256, Value::Decimal256(bytes)) => {
+                    BigInt::from_signed_bytes_le(bytes).to_string()
+                }
+                _ => return Err(mismatch(ty, value)),
+            };
+            push_decimal(out, &mantissa, scale);
+        }
+    }
+    Ok(())
+}
+
+fn push(out: &mut String, value: impl Display) {
+    let _ = write!(out, "{value}");
+}
+
+/// Writes a float in its shortest round-trip form, switching to
+/// exponent notation for very large and very small magnitudes.
+fn push_float<F: Display + LowerExp>(out: &mut String, value: F, wide: f64) {
+    if wide.is_nan() {
+        out.push_str("nan");
+    } else if wide.is_infinite() {
+        out.push_str(if wide > 0.0 { "inf" } else { "-inf" });
+    } else if wide != 0.0 && !(1e-5..1e16).contains(&wide.abs()) {
+        let _ = write!(out, "{value:e}");
+    } else {
+        let _ = write!(out, "{value}");
+    }
+}
+
+fn push_enum(out: &mut String, ty: &TypeDesc, discriminant: i16) -> Result<()> {
+    let name = ty
+        .enum_name(discriminant)
+        .ok_or(Error::InvalidValue("unknown enum discriminant"))?;
+    out.push_str(name);
+    Ok(())
+}
+
+/// Writes `mantissa`, a signed integer string, with `scale` fractional
+/// digits and trailing zeros removed.
+fn push_decimal(out: &mut String, mantissa: &str, scale: u8) {
+    let (sign, digits) = mantissa
+        .strip_prefix('-')
+        .map_or(("", mantissa), |digits| ("-", digits));
+    let scale = usize::from(scale);
+    let padded = if digits.len() <= scale {
+        format!("{}{digits}", "0".repeat(scale + 1 - digits.len()))
+    } else {
+        digits.to_string()
+    };
+    let (integer, fraction) = padded.split_at(padded.len() - scale);
+    let fraction = fraction.trim_end_matches('0');
+    out.push_str(sign);
+    out.push_str(integer);
+    if !fraction.is_empty() {
+        out.push('.');
+        out.push_str(fraction);
+    }
+}
+
+fn push_date(out: &mut String, days: i64) {
+    let (year, month, day) = civil_from_days(days);
+    let _ = write!(out, "{year:04}-{month:02}-{day:02}");
+}
+
+/// Converts days since the Unix epoch into a (year, month, day) date.
+fn civil_from_days(days: i64) -> (i64, i64, i64) {
+    let days = days + UNIX_EPOCH_DAYS;
+    let era = days.div_euclid(DAYS_PER_ERA);
+    let day_of_era = days.rem_euclid(DAYS_PER_ERA);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+/// Converts a (year, month, day) date into days since the Unix epoch.
+fn days_from_civil(year: i64, month: i64, day: i64) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = (month + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * DAYS_PER_ERA + day_of_era - UNIX_EPOCH_DAYS
+}
+
+fn days_in_month(year: i64, month: i64) -> i64 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Parses the text of a scalar of type `ty`; see [`format_scalar`].
+///
+/// Besides the rendered forms, integers accept a leading `+`, `Bool`
+/// accepts `0`/`1`, enums accept their numeric value, date-times accept
+/// Unix timestamps and `IPv6` accepts `IPv4` addresses.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidValue`] or [`Error::Overflow`] when `text` is
+/// not a valid value of `ty`, and [`Error::UnsupportedType`] when `ty` is
+/// not a scalar type.
+pub(crate) fn parse_scalar(ty: &TypeDesc, text: &str) -> Result<Value> {
+    Ok(match ty {
+        TypeDesc::Bool => match text {
+            "true" | "1" => Value::Bool(true),
+            "false" | "0" => Value::Bool(false),
+            _ => return Err(Error::InvalidValue("invalid Bool text")),
+        },
+        TypeDesc::UInt8 => Value::UInt8(parse_int(text)?),
+        TypeDesc::UInt16 => Value::UInt16(parse_int(text)?),
+        TypeDesc::UInt32 => Value::UInt32(parse_int(text)?),
+        TypeDesc::UInt64 => Value::UInt64(parse_int(text)?),
+        TypeDesc::UInt128 => Value::UInt128(parse_int(text)?),
+        TypeDesc::UInt256 => {
+            let value = parse_big(text)?;
+            if value.sign() == Sign::Minus {
+                return Err(Error::InvalidValue("invalid integer text"));
+            }
+            Value::UInt256(wide_bytes(&value)?)
+        }
+        TypeDesc::Int8 => Value::Int8(parse_int(text)?),
+        TypeDesc::Int16 => Value::Int16(parse_int(text)?),
+        TypeDesc::Int32 => Value::Int32(parse_int(text)?),
+        TypeDesc::Int64 | TypeDesc::Interval(_) => Value::Int64(parse_int(text)?),
+        TypeDesc::Int128 => Value::Int128(parse_int(text)?),
+        TypeDesc::Int256 => Value::Int256(wide_bytes(&parse_big(text)?)?),
+        TypeDesc::Float32 => Value::Float32(parse_float(text)?),
+        TypeDesc::Float64 => Value::Float64(parse_float(text)?),
+        TypeDesc::Float16 => Value::Float16(parse_float(text)?),
+        TypeDesc::BFloat16 => Value::BFloat16(parse_float(text)?),
+        TypeDesc::Date => Value::Date(
+            u16::try_from(parse_date(text)?)
+                .map_err(|_| Error::Overflow("date outside Date range"))?,
+        ),
+        TypeDesc::Date32 => Value::Date32(
+            i32::try_from(parse_date(text)?)
+                .map_err(|_| Error::Overflow("date outside Date32 range"))?,
+        ),
+        TypeDesc::DateTime { .. } => {
+            let seconds = if is_digits(text) {
+                text.parse::<i64>()
+                    .map_err(|_| Error::Overflow("timestamp outside DateTime range"))?
+            } else {
+                parse_datetime(text)?.0
+            };
+            Value::DateTime(
+                u32::try_from(seconds)
+                    .map_err(|_| Error::Overflow("timestamp outside DateTime range"))?,
+            )
+        }
+        TypeDesc::DateTime64 { precision, .. } => parse_datetime64(text, *precision)?,
+        TypeDesc::Uuid => Value::Uuid(
+            Uuid::parse_str(text).map_err(|_| Error::InvalidValue("invalid UUID text"))?,
+        ),
+        TypeDesc::Ipv4 => Value::Ipv4(
+            text.parse()
+                .map_err(|_| Error::InvalidValue("invalid IPv4 text"))?,
+        ),
+        TypeDesc::Ipv6 => Value::Ipv6(match text.parse::<Ipv6Addr>() {
+            Ok(address) => address,
+            Err(_) => text
+                .parse::<Ipv4Addr>()
+                .map_err(|_| Error::InvalidValue("invalid IPv6 text"))?
+                .to_ipv6_mapped(),
+        }),
+        TypeDesc::Enum8(_) => Value::Enum8(
+            i8::try_from(parse_enum(ty, text)?)
+                .map_err(|_| Error::Overflow("Enum8 value out of range"))?,
+        ),
+        TypeDesc::Enum16(_) => Value::Enum16(parse_enum(ty, text)?),
+        _ => {
+            let Some((scale, size)) = decimal_parts(ty) else {
+                return Err(Error::UnsupportedType(ty.type_name()));
+            };
+            decimal_value(size, &parse_decimal_mantissa(text, scale)?)?
+        }
+    })
+}
+
+/// Builds a decimal value of the given storage size from its mantissa.
+pub(crate) fn decimal_value(size: DecimalSize, mantissa: &BigInt) -> Result<Value> {
+    let overflow = || Error::Overflow("decimal does not fit its column type");
+    Ok(match size {
+        DecimalSize::Bits32 => Value::Decimal32(mantissa.to_i32().ok_or_else(overflow)?),
+        DecimalSize::Bits64 => Value::Decimal64(mantissa.to_i64().ok_or_else(overflow)?),
+        DecimalSize::Bits128 => Value::Decimal128(mantissa.to_i128().ok_or_else(overflow)?),
+        DecimalSize::Bits256 => Value::Decimal256(wide_bytes(mantissa)?),
+    })
+}
+
+fn parse_int<T: std::str::FromStr>(text: &str) -> Result<T> {
+    text.parse()
+        .map_err(|_| Error::InvalidValue("invalid integer text"))
+}
+
+fn parse_float<T: std::str::FromStr>(text: &str) -> Result<T> {
+    text.parse()
+        .map_err(|_| Error::InvalidValue("invalid float text"))
+}
+
+fn parse_big(text: &str) -> Result<BigInt> {
+    let digits = text.strip_prefix('+').unwrap_or(text);
+    BigInt::parse_bytes(digits.as_bytes(), 10).ok_or(Error::InvalidValue("invalid integer text"))
+}
+
+/// Encodes `value` as 32 little-endian two's complement bytes.
+fn wide_bytes(value: &BigInt) -> Result<[u8; 32]> {
+    let bytes = value.to_signed_bytes_le();
+    let unsigned = value.sign() != Sign::Minus;
+    // Unsigned 256-bit values may use all 32 bytes without a sign byte.
+    let bytes = if unsigned && bytes.len() == 33 && bytes[32] == 0 {
+        &bytes[..32]
+    } else {
+        bytes.as_slice()
+    };
+    if bytes.len() > 32 {
+        return Err(Error::Overflow("integer does not fit in 256 bits"));
+    }
+    let fill = if unsigned { 0 } else { 0xff };
+    let mut out = [fill; 32];
+    out[..bytes.len()].copy_from_slice(bytes);
+    Ok(out)
+}
+
+fn parse_decimal_mantissa(text: &str, scale: u8) -> Result<BigInt> {
+    let invalid = Error::InvalidValue("invalid decimal text");
+    let (negative, unsigned) = match text.as_bytes().first() {
+        Some(b'-') => (true, &text[1..]),
+        Some(b'+') => (false, &text[1..]),
+        _ => (false, text),
+    };
+    let (integer, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+    if (integer.is_empty() && fraction.is_empty())
+        || !integer
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid);
+    }
+    let scale = usize::from(scale);
+    if fraction.len() > scale {
+        return Err(Error::InvalidValue(
+            "decimal has more fractional digits than the column scale",
+        ));
+    }
+    let digits = format!("{integer}{fraction}{}", "0".repeat(scale - fraction.len()));
+    let mantissa = BigInt::parse_bytes(digits.as_bytes(), 10).ok_or(invalid)?;
+    Ok(if negative { -mantissa } else { mantissa })
+}
+
+fn parse_enum(ty: &TypeDesc, text: &str) -> Result<i16> {
+    if let Some(value) = ty.enum_value(text) {
+        return Ok(value);
+    }
+    text.parse::<i16>()
+        .ok()
+        .filter(|value| ty.enum_name(*value).is_some())
+        .ok_or(Error::InvalidValue("unknown enum name"))
+}
+
+fn is_digits(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn parse_digits(text: &str) -> Result<i64> {
+    if !is_digits(text) {
+        return Err(Error::InvalidValue("invalid date text"));
+    }
+    text.parse()
+        .map_err(|_| Error::InvalidValue("invalid date text"))
+}
+
+/// Parses `YYYY-MM-DD` into days since the Unix epoch.
+fn parse_date(text: &str) -> Result<i64> {
+    let bytes = text.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return Err(Error::InvalidValue("invalid date text"));
+    }
+    let year = parse_digits(&text[..4])?;
+    let month = parse_digits(&text[5..7])?;
+    let day = parse_digits(&text[8..])?;
+    if !(1..=12).contains(&month) || !(1..=days_in_month(year, month)).contains(&day) {
+        return Err(Error::InvalidValue("invalid date text"));
+    }
+    Ok(days_from_civil(year, month, day))
+}
+
+/// Parses `YYYY-MM-DD[( |T)hh:mm:ss[.fraction]][Z]` into seconds since the
+/// Unix epoch and the fractional digits.
+fn parse_datetime(text: &str) -> Result<(i64, &str)> {
+    let invalid = Error::InvalidValue("invalid date-time text");
+    let text = text.strip_suffix('Z').unwrap_or(text);
+    let (date, time) = match text.get(..10) {
+        Some(date) if text.len() == 10 => (date, None),
+        Some(date) if matches!(text.as_bytes()[10], b' ' | b'T') => (date, Some(&text[11..])),
+        _ => return Err(invalid),
+    };
+    let mut seconds = parse_date(date)? * SECONDS_PER_DAY;
+    let Some(time) = time else {
+        return Ok((seconds, ""));
+    };
+    let (clock, fraction) = time.split_once('.').unwrap_or((time, ""));
+    let bytes = clock.as_bytes();
+    if bytes.len() != 8
+        || bytes[2] != b':'
+        || bytes[5] != b':'
+        || !fraction.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid);
+    }
+    let hours = parse_digits(&clock[..2])?;
+    let minutes = parse_digits(&clock[3..5])?;
+    let secs = parse_digits(&clock[6..])?;
+    if hours > 23 || minutes > 59 || secs > 59 {
+        return Err(invalid);
+    }
+    seconds += hours * 3600 + minutes * 60 + secs;
+    Ok((seconds, fraction))
+}
+
+/// Parses a `DateTime64` from date-time text or a Unix timestamp with an
+/// optional fraction, truncating digits beyond `precision`.
+fn parse_datetime64(text: &str, precision: u8) -> Result<Value> {
+    let scale = datetime64_ticks_per_second(precision)?;
+    let (negative, seconds, fraction) = if text.as_bytes().get(4) == Some(&b'-') {
+        let (seconds, fraction) = parse_datetime(text)?;
+        (false, seconds, fraction)
+    } else {
+        let (negative, unsigned) = text
+            .strip_prefix('-')
+            .map_or((false, text), |rest| (true, rest));
+        let (seconds, fraction) = unsigned.split_once('.').unwrap_or((unsigned, ""));
+        if !is_digits(seconds) || !fraction.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(Error::InvalidValue("invalid date-time text"));
+        }
+        let seconds = seconds
+            .parse::<i64>()
+            .map_err(|_| Error::Overflow("timestamp outside DateTime64 range"))?;
+        (negative, seconds, fraction)
+    };
+    let digits = &fraction[..fraction.len().min(usize::from(precision))];
+    let mut ticks = 0_i64;
+    for (index, digit) in digits.bytes().enumerate() {
+        let place = 10_i64.pow(u32::from(precision) - 1 - u32::try_from(index).unwrap_or(0));
+        ticks += i64::from(digit - b'0') * place;
+    }
+    let ticks = seconds
+        .checked_mul(scale)
+        .and_then(|ticks_seconds| ticks_seconds.checked_add(ticks))
+        .ok_or(Error::Overflow("timestamp outside DateTime64 range"))?;
+    Ok(Value::DateTime64(if negative { -ticks } else { ticks }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::parse_type_desc;
+
+    fn round_trip(type_name: &str, value: &Value, text: &str) {
+        let ty = parse_type_desc(type_name).unwrap();
+        let mut out = String::new();
+        format_scalar(&ty, value, &mut out).unwrap();
+        assert_eq!(out, text, "{type_name}");
+        assert_eq!(&parse_scalar(&ty, text).unwrap(), value, "{type_name}");
+    }
+
+    #[test]
+    fn scalars_render_like_clickhouse() {
+        round_trip("UInt64", &Value::UInt64(u64::MAX), "18446744073709551615");
+        let max = (BigUint::from(1_u8) << 256_u32) - 1_u8;
+        round_trip("UInt256", &Value::UInt256([0xff; 32]), &max.to_string());
+        round_trip("Int256", &Value::Int256([0xff; 32]), "-1");
+        round_trip("Float64", &Value::Float64(1.5), "1.5");
+        round_trip("Float64", &Value::Float64(1e300), "1e300");
+        round_trip("Float32", &Value::Float32(0.1), "0.1");
+        round_trip("Float64", &Value::Float64(f64::NEG_INFINITY), "-inf");
+        round_trip("Decimal(9, 3)", &Value::Decimal32(-1_500), "-1.5");
+        round_trip("Decimal(9, 3)", &Value::Decimal32(5), "0.005");
+        round_trip("Decimal128(2)", &Value::Decimal128(1_200), "12");
+        round_trip("Date", &Value::Date(19_723), "2024-01-01");
+        round_trip("Date32", &Value::Date32(-1), "1969-12-31");
+        round_trip(
+            "DateTime('Asia/Tokyo')",
+            &Value::DateTime(1_709_251_199),
+            "1709251199",
+        );
+        round_trip("DateTime64(3, 'UTC')", &Value::DateTime64(-1), "-0.001");
+        round_trip(
+            "DateTime64(0)",
+            &Value::DateTime64(-2_208_988_800),
+            "-2208988800",
+        );
+        round_trip("Enum8('a' = -1, 'b' = 2)", &Value::Enum8(-1), "a");
+        round_trip(
+            "IPv6",
+            &Value::Ipv6(Ipv4Addr::new(1, 2, 3, 4).to_ipv6_mapped()),
+            "::ffff:1.2.3.4",
+        );
+    }
+
+    #[test]
+    fn parsing_accepts_alternate_forms() {
+        let parse =
+            |type_name: &str, text: &str| parse_scalar(&parse_type_desc(type_name).unwrap(), text);
+        assert_eq!(
+            parse("DateTime", "1700000000").unwrap(),
+            Value::DateTime(1_700_000_000)
+        );
+        assert_eq!(
+            parse("DateTime", "1970-01-02T00:00:01Z").unwrap(),
+            Value::DateTime(86_401)
+        );
+        assert_eq!(
+            parse("DateTime64(3, 'UTC')", "1969-12-31 23:59:59.999").unwrap(),
+            Value::DateTime64(-1)
+        );
+        assert_eq!(
+            parse("DateTime64(2)", "1.239").unwrap(),
+            Value::DateTime64(123)
+        );
+        assert_eq!(
+            parse("IPv6", "1.2.3.4").unwrap(),
+            Value::Ipv6(Ipv4Addr::new(1, 2, 3, 4).to_ipv6_mapped())
+        );
+        assert_eq!(parse("Enum8('a' = 1)", "1").unwrap(), Value::Enum8(1));
+        assert_eq!(parse("Bool", "1").unwrap(), Value::Bool(true));
+        assert!(matches!(
+            parse("Date", "2023-02-29"),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            parse("Decimal(9, 1)", "1.25"),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            parse("Decimal(9, 1)", "1e5"),
+            Err(Error::InvalidValue(_))
+        ));
+        assert!(matches!(
+            parse("Int256", &"9".repeat(80)),
+            Err(Error::Overflow(_))
+        ));
+        assert!(matches!(parse("UInt8", "256"), Err(Error::InvalidValue(_))));
+    }
+}
@@ -0,0 +1,78 @@
+//! `TabSeparated` format family support.
+//!
+//! Rows are written one line per row with tab-separated fields. Fields are
+//! never quoted; tabs, line breaks, backslashes and quotes inside them are
+//! backslash-escaped instead, and `NULL` is written as `\N`:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{Schema, TsvFormat, TsvReader, TsvWriter, Value};
+//! let schema = Schema::from_type_strings(&[("id", "UInt8"), ("text", "Nullable(String)")])?;
+//! let rows = [
+//!     vec![
+//!         Value::UInt8(1),
+//!         Value::Nullable(Some(Box::new(Value::String(b"a\tb".to_vec())))),
+//!     ],
+//!     vec![Value::UInt8(2), Value::Nullable(None)],
+//! ];
+//! let format = TsvFormat::TabSeparatedWithNamesAndTypes;
+//! let mut writer = TsvWriter::new(Vec::new(), format, schema.clone());
+//! writer.write_header()?;
+//! writer.write_rows(&rows)?;
+//! let payload = writer.into_inner();
+//! assert_eq!(
+//!     payload,
+//!     b"id\ttext\nUInt8\tNullable(String)\n1\ta\\tb\n2\t\\N\n"
+//! );
+//!
+//! let reader = TsvReader::new(payload.as_slice(), format, schema)?;
+//! assert_eq!(reader.collect::<Result<Vec<_>, _>>()?, rows);
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! Values are typed by a [`Schema`](crate::Schema). Arrays, tuples and
+//! maps are written as their literal text, such as `['a','b']`, and
+//! date-times are written as Unix timestamps and read either as timestamps
+//! or as UTC text. On input, `\N` in a column
+//! that cannot hold `NULL` takes the column's default value.
+
+mod reader;
+mod writer;
+
+use std::fmt;
+
+pub use reader::TsvReader;
+pub use writer::TsvWriter;
+#[cfg(any(feature = "http", feature = "async-http"))]
+pub(crate) use writer::write_field;
+
+/// `TabSeparated` format variants.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TsvFormat {
+    /// Rows only.
+    TabSeparated,
+    /// A header row with the column names precedes the data.
+    TabSeparatedWithNames,
+    /// Header rows with the column names and then their types precede the
+    /// data.
+    TabSeparatedWithNamesAndTypes,
+}
+
+impl TsvFormat {
+    pub(crate) fn has_names(self) -> bool {
+        self != TsvFormat::TabSeparated
+    }
+
+    pub(crate) fn has_types(self) -> bool {
+        self == TsvFormat::TabSeparatedWithNamesAndTypes
+    }
+}
+
+impl fmt::Display for TsvFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            TsvFormat::TabSeparated => "TabSeparated",
+            TsvFormat::TabSeparatedWithNames => "TabSeparatedWithNames",
+            TsvFormat::TabSeparatedWithNamesAndTypes => "TabSeparatedWithNamesAndTypes",
+        })
+    }
+}
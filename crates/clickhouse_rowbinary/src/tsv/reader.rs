@@ -0,0 +1,275 @@
+//! `TabSeparated` reader.
+
+use std::{collections::HashMap, io::BufRead};
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::json::{parse as parse_json, read_value as read_json},
+    rowbinary::{Row, Schema, default_value},
+    text::{
+        fixed_string, is_composite, is_numeric,
+        literal::{Literal, infer_literal, parse_literal, read_literal, unescape},
+        parse_scalar, text_type,
+    },
+    types::{TypeDesc, parse_type_desc},
+    value::Value,
+};
+
+use super::TsvFormat;
+
+/// Reader that decodes `TabSeparated` lines into rows of the given schema.
+///
+/// For formats with a header, the names may list the schema columns in any
+/// order or leave some out; missing columns take their default value. A
+/// types row must agree with the schema.
+pub struct TsvReader<R: BufRead> {
+    inner: R,
+    schema: Schema,
+    /// Schema column index of each field, in line order.
+    order: Vec<usize>,
+    line: Vec<u8>,
+}
+
+impl<R: BufRead> TsvReader<R> {
+    /// Creates a reader for rows of `schema`, reading the header rows the
+    /// format calls for.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] for header names outside the
+    /// schema, [`Error::TypeMismatch`] when a header type differs from the
+    /// schema, and [`crate::error::Error`] when the header is malformed or
+    /// IO fails.
+    pub fn new(inner: R, format: TsvFormat, schema: Schema) -> Result<Self> {
+        let mut reader = Self {
+            inner,
+            order: (0..schema.len()).collect(),
+            schema,
+            line: Vec::new(),
+        };
+        if format.has_names() {
+            reader.read_names()?;
+        }
+        if format.has_types() {
+            reader.read_types()?;
+        }
+        Ok(reader)
+    }
+
+    fn read_header_row(&mut self) -> Result<Option<Vec<String>>> {
+        if !read_line(&mut self.inner, &mut self.line)? {
+            return Ok(None);
+        }
+        self.line
+            .split(|&byte| byte == b'\t')
+            .map(|cell| {
+                String::from_utf8(unescape_field(cell)?)
+                    .map_err(|_| Error::InvalidValue("TabSeparated header is not valid UTF-8"))
+            })
+            .collect::<Result<_>>()
+            .map(Some)
+    }
+
+    fn read_names(&mut self) -> Result<()> {
+        let Some(names) = self.read_header_row()? else {
+            return Ok(());
+        };
+        let columns: HashMap<&str, usize> = self
+            .schema
+            .fields()
+            .iter()
+            .enumerate()
+            .map(|(index, field)| (field.name.as_str(), index))
+            .collect();
+        let mut order = Vec::with_capacity(names.len());
+        for name in names {
+            let &index = columns
+                .get(name.as_str())
+                .ok_or(Error::UnknownColumn(name))?;
+            if order.contains(&index) {
+                return Err(Error::InvalidValue(
+                    "duplicate column in TabSeparated header",
+                ));
+            }
+            order.push(index);
+        }
+        self.order = order;
+        Ok(())
+    }
+
+    fn read_types(&mut self) -> Result<()> {
+        let Some(types) = self.read_header_row()? else {
+            return Ok(());
+        };
+        if types.len() != self.order.len() {
+            return Err(Error::InvalidValue(
+                "TabSeparated header rows differ in length",
+            ));
+        }
+        for (type_name, &index) in types.iter().zip(&self.order) {
+            let expected = &self.schema.fields()[index].ty;
+            if parse_type_desc(type_name)? != *expected {
+                return Err(Error::TypeMismatch {
+                    expected: expected.type_name(),
+                    actual: type_name.clone(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the schema rows are decoded with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Reads the next row, or returns `Ok(None)` at EOF.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a line has the wrong number of
+    /// fields, a value does not fit its column type, or IO fails.
+    pub fn read_row(&mut self) -> Result<Option<Row>> {
+        if !read_line(&mut self.inner, &mut self.line)? {
+            return Ok(None);
+        }
+        let fields: Vec<&[u8]> = self.line.split(|&byte| byte == b'\t').collect();
+        if fields.len() != self.order.len() {
+            return Err(Error::InvalidValue(
+                "TabSeparated row length does not match schema",
+            ));
+        }
+        let mut row = vec![None; self.schema.len()];
+        for (field, &index) in fields.into_iter().zip(&self.order) {
+            row[index] = Some(read_field(&self.schema.fields()[index].ty, field)?);
+        }
+        self.schema
+            .fields()
+            .iter()
+            .zip(row)
+            .map(|(field, value)| value.map_or_else(|| default_value(&field.ty), Ok))
+            .collect::<Result<Row>>()
+            .map(Some)
+    }
+
+    /// Returns a reference to the inner reader.
+    #[must_use]
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    /// Returns a mutable reference to the inner reader.
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    /// Consumes the reader and returns the inner reader.
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+}
+
+impl<R: BufRead> Iterator for TsvReader<R> {
+    type Item = Result<Row>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_row().transpose()
+    }
+}
+
+/// Reads one line into `line` without its line break, returning `false`
+/// at EOF.
+fn read_line<R: BufRead>(inner: &mut R, line: &mut Vec<u8>) -> Result<bool> {
+    line.clear();
+    if inner.read_until(b'\n', line)? == 0 {
+        return Ok(false);
+    }
+    if line.last() == Some(&b'\n') {
+        line.pop();
+    }
+    Ok(true)
+}
+
+fn unescape_field(field: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::with_capacity(field.len());
+    let mut pos = 0;
+    while let Some(&byte) = field.get(pos) {
+        pos += 1;
+        if byte == b'\\' {
+            pos += unescape(&field[pos..], &mut out)?;
+        } else {
+            out.push(byte);
+        }
+    }
+    Ok(out)
+}
+
+fn utf8(bytes: &[u8]) -> Result<&str> {
+    std::str::from_utf8(bytes)
+        .map_err(|_| Error::InvalidValue("TabSeparated text is not valid UTF-8"))
+}
+
+fn read_field(ty: &TypeDesc, field: &[u8]) -> Result<Value> {
+    let ty = text_type(ty);
+    let ty = ty.as_ref();
+    let null = field == b"\\N";
+    Ok(match ty {
+        TypeDesc::Nullable(_) if null => Value::Nullable(None),
+        TypeDesc::Nullable(inner) => Value::Nullable(Some(Box::new(read_field(inner, field)?))),
+        TypeDesc::Variant(_) if null => Value::VariantNull,
+        TypeDesc::Variant(types) => read_variant(types, field)?,
+        TypeDesc::Dynamic { .. } if null => Value::DynamicNull,
+        TypeDesc::Dynamic { .. } => {
+            let inferred = parse_literal(field)
+                .ok()
+                .and_then(|literal| infer_literal(&literal).ok().flatten());
+            let (ty, value) = match inferred {
+                Some(inferred) => inferred,
+                None => (TypeDesc::String, Value::String(unescape_field(field)?)),
+            };
+            Value::Dynamic {
+                ty: Box::new(ty),
+                value: Box::new(value),
+            }
+        }
+        _ if null => default_value(ty)?,
+        TypeDesc::String => Value::String(unescape_field(field)?),
+        TypeDesc::FixedString { length } => fixed_string(&unescape_field(field)?, *length)?,
+        TypeDesc::Json { .. } => read_json(ty, &parse_json(&unescape_field(field)?)?)?,
+        ty if is_composite(ty) => read_literal(ty, &parse_literal(field)?)?,
+        TypeDesc::AggregateFunction { .. } => {
+            return Err(Error::UnsupportedCombination(
+                "text formats do not support AggregateFunction columns".into(),
+            ));
+        }
+        ty => parse_scalar(ty, utf8(&unescape_field(field)?)?)?,
+    })
+}
+
+/// Picks the first variant matching the field's shape, falling back to
+/// the first alternative that accepts it at all.
+fn read_variant(types: &[TypeDesc], field: &[u8]) -> Result<Value> {
+    let shape = parse_literal(field).ok();
+    let natural = |ty: &&TypeDesc| {
+        let ty = text_type(ty);
+        match &shape {
+            Some(Literal::Bare(_)) => is_numeric(&ty),
+            Some(Literal::Array(_) | Literal::Tuple(_) | Literal::Map(_)) => is_composite(&ty),
+            _ => !is_numeric(&ty) && !is_composite(&ty),
+        }
+    };
+    let matching = types.iter().enumerate().filter(|(_, ty)| natural(ty));
+    for (index, ty) in matching.chain(types.iter().enumerate()) {
+        if let Ok(value) = read_field(ty, field) {
+            return Ok(Value::Variant {
+                index: u8::try_from(index).map_err(|_| Error::Overflow("too many variants"))?,
+                value: Box::new(value),
+            });
+        }
+    }
+    Err(Error::InvalidValue(
+        "TabSeparated field matches no variant alternative",
+    ))
+}
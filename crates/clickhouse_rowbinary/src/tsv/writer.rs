@@ -0,0 +1,155 @@
+//! `TabSeparated` writer.
+
+use std::io::Write;
+
+use crate::{
+    error::{Error, Result},
+    jsoneachrow::{JsonOptions, json::write_value as write_json},
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::Schema,
+    text::{format_scalar, is_composite, literal::write_literal, text_type},
+    types::TypeDesc,
+    value::Value,
+};
+
+use super::TsvFormat;
+
+/// Writer that encodes rows as `TabSeparated` lines.
+pub struct TsvWriter<W: Write> {
+    output: RowOutput<W>,
+    format: TsvFormat,
+    schema: Schema,
+}
+
+impl<W: Write> TsvWriter<W> {
+    /// Creates a writer for rows of `schema`.
+    #[must_use]
+    pub fn new(inner: W, format: TsvFormat, schema: Schema) -> Self {
+        Self {
+            output: RowOutput::new(inner),
+            format,
+            schema,
+        }
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Writes the name and type header rows the format calls for; does
+    /// nothing for [`TsvFormat::TabSeparated`].
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] if IO fails.
+    pub fn write_header(&mut self) -> Result<()> {
+        self.output.write(|out| {
+            if self.format.has_names() {
+                let names = self.schema.fields().iter().map(|field| field.name.clone());
+                write_header_row(names, out);
+            }
+            if self.format.has_types() {
+                let types = self
+                    .schema
+                    .fields()
+                    .iter()
+                    .map(|field| field.ty.type_name());
+                write_header_row(types, out);
+            }
+            Ok(())
+        })
+    }
+
+    /// Encodes and writes one row as a tab-separated line.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, a column type is not supported, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+                if index > 0 {
+                    out.push(b'\t');
+                }
+                write_field(&field.ty, value, out)?;
+            }
+            out.push(b'\n');
+            Ok(())
+        })
+    }
+}
+
+impl_row_writer!(TsvWriter);
+
+fn write_header_row(cells: impl Iterator<Item = String>, out: &mut Vec<u8>) {
+    for (index, cell) in cells.enumerate() {
+        if index > 0 {
+            out.push(b'\t');
+        }
+        write_escaped(cell.as_bytes(), out);
+    }
+    out.push(b'\n');
+}
+
+/// Appends `bytes` with the `TabSeparated` backslash escapes.
+fn write_escaped(bytes: &[u8], out: &mut Vec<u8>) {
+    for &byte in bytes {
+        match byte {
+            b'\t' => out.extend_from_slice(b"\\t"),
+            b'\n' => out.extend_from_slice(b"\\n"),
+            b'\r' => out.extend_from_slice(b"\\r"),
+            b'\\' => out.extend_from_slice(b"\\\\"),
+            b'\'' => out.extend_from_slice(b"\\'"),
+            0x08 => out.extend_from_slice(b"\\b"),
+            0x0c => out.extend_from_slice(b"\\f"),
+            0 => out.extend_from_slice(b"\\0"),
+            _ => out.push(byte),
+        }
+    }
+}
+
+/// Appends `value` of type `ty` in the escaped text form of a cell.
+pub(crate) fn write_field(ty: &TypeDesc, value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    let ty = text_type(ty);
+    match (ty.as_ref(), value) {
+        (TypeDesc::Nothing, Value::Nothing)
+        | (TypeDesc::Nullable(_), Value::Nullable(None))
+        | (TypeDesc::Variant(_), Value::VariantNull)
+        | (TypeDesc::Dynamic { .. }, Value::DynamicNull | Value::Nullable(None)) => {
+            out.extend_from_slice(b"\\N");
+        }
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => {
+            write_field(inner, value, out)?;
+        }
+        (TypeDesc::Variant(types), Value::Variant { index, value }) => {
+            let ty = types
+                .get(usize::from(*index))
+                .ok_or(Error::InvalidValue("variant index out of range"))?;
+            write_field(ty, value, out)?;
+        }
+        (TypeDesc::Dynamic { .. }, Value::Dynamic { ty, value }) => write_field(ty, value, out)?,
+        (TypeDesc::String, Value::String(bytes))
+        | (TypeDesc::FixedString { .. }, Value::FixedString(bytes)) => write_escaped(bytes, out),
+        (TypeDesc::Json { .. }, _) => {
+            let mut json = Vec::new();
+            write_json(&ty, value, &JsonOptions::default(), &mut json)?;
+            write_escaped(&json, out);
+        }
+        // Literal text escapes its own tabs and line breaks.
+        (ty, value) if is_composite(ty) || matches!(ty, TypeDesc::AggregateFunction { .. }) => {
+            write_literal(ty, value, out)?;
+        }
+        (ty, value) => {
+            let mut text = String::new();
+            format_scalar(ty, value, &mut text)?;
+            write_escaped(text.as_bytes(), out);
+        }
+    }
+    Ok(())
+}
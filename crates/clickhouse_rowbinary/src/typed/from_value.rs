@@ -0,0 +1,378 @@
+//! Decoding [`Value`]s of a known column type into Rust values.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    hash::{BuildHasher, Hash},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use half::{bf16, f16};
+use uuid::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::storage_type,
+    types::TypeDesc,
+    value::Value,
+};
+
+static FLOAT64: TypeDesc = TypeDesc::Float64;
+static POINT: TypeDesc = TypeDesc::Point;
+static LINE_STRING: TypeDesc = TypeDesc::LineString;
+static RING: TypeDesc = TypeDesc::Ring;
+static POLYGON: TypeDesc = TypeDesc::Polygon;
+
+/// Rust type that can be read from a decoded [`Value`] of a given column
+/// type.
+///
+/// The column type supplies what the value alone does not carry: enum
+/// names, decimal scales and `DateTime64` precisions. `Nullable`, `Variant`
+/// and `Dynamic` wrappers are looked through, so a `u64` reads from a
+/// non-null `Nullable(UInt64)` value; read `Option<T>` to accept nulls.
+/// Integers convert between widths when the value fits.
+pub trait FromValue<'a>: Sized {
+    /// Converts `value`, a value of column type `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when `value` holds another type and
+    /// [`Error::Overflow`] when an integer does not fit `Self`.
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self>;
+}
+
+/// Returns the value inside `Nullable`, `Variant` and `Dynamic` wrappers
+/// with its type, or `None` for their null forms.
+pub(crate) fn unwrap_value<'a>(
+    ty: &'a TypeDesc,
+    value: &'a Value,
+) -> Option<(&'a TypeDesc, &'a Value)> {
+    let ty = storage_type(ty);
+    match (ty, value) {
+        (_, Value::Nullable(None) | Value::VariantNull | Value::DynamicNull) => None,
+        (TypeDesc::Nullable(inner), Value::Nullable(Some(value))) => unwrap_value(inner, value),
+        (TypeDesc::Variant(variants), Value::Variant { index, value }) => {
+            unwrap_value(variants.get(usize::from(*index)).unwrap_or(ty), value)
+        }
+        (_, Value::Nullable(Some(value)) | Value::Variant { value, .. }) => unwrap_value(ty, value),
+        (_, Value::Dynamic { ty, value }) => unwrap_value(ty, value),
+        _ => Some((ty, value)),
+    }
+}
+
+pub(crate) fn mismatch(expected: &str, value: &Value) -> Error {
+    Error::TypeMismatch {
+        expected: expected.to_string(),
+        actual: value.type_name().to_string(),
+    }
+}
+
+/// Unwraps `value`, rejecting nulls for the non-optional type `expected`.
+pub(crate) fn non_null<'a>(
+    ty: &'a TypeDesc,
+    value: &'a Value,
+    expected: &str,
+) -> Result<(&'a TypeDesc, &'a Value)> {
+    unwrap_value(ty, value).ok_or_else(|| Error::TypeMismatch {
+        expected: expected.to_string(),
+        actual: "NULL".to_string(),
+    })
+}
+
+/// Type of the element at `index` of a tuple-like value of type `ty`.
+/// `Nested` rows are stored as tuples and typed by the `Nested` type itself.
+fn tuple_element(ty: &TypeDesc, index: usize) -> Option<&TypeDesc> {
+    match ty {
+        TypeDesc::Tuple(items) | TypeDesc::Nested(items) => items.get(index).map(|item| &item.ty),
+        TypeDesc::Point if index < 2 => Some(&FLOAT64),
+        _ => None,
+    }
+}
+
+/// Type of the elements of an array-like value of type `ty`.
+fn array_element(ty: &TypeDesc) -> Option<&TypeDesc> {
+    match ty {
+        TypeDesc::Array(inner) => Some(inner),
+        TypeDesc::Nested(_) => Some(ty),
+        TypeDesc::Ring | TypeDesc::LineString => Some(&POINT),
+        TypeDesc::MultiLineString => Some(&LINE_STRING),
+        TypeDesc::Polygon => Some(&RING),
+        TypeDesc::MultiPolygon => Some(&POLYGON),
+        _ => None,
+    }
+}
+
+macro_rules! impl_from_value_int {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl FromValue<'_> for $ty {
+                /// Reads any integer column, and the stored integers of
+                /// date, time, enum and decimal columns.
+                #[allow(clippy::useless_conversion)]
+                fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+                    const OVERFLOW: &str = concat!("integer does not fit ", stringify!($ty));
+                    let (_, value) = non_null(ty, value, stringify!($ty))?;
+                    let wide = match value {
+                        Value::UInt8(value) => i128::from(*value),
+                        Value::UInt16(value) | Value::Date(value) => i128::from(*value),
+                        Value::UInt32(value) | Value::DateTime(value) => i128::from(*value),
+                        Value::UInt64(value) => i128::from(*value),
+                        Value::UInt128(value) => {
+                            return <$ty>::try_from(*value).map_err(|_| Error::Overflow(OVERFLOW));
+                        },
+                        Value::Int8(value) | Value::Enum8(value) => i128::from(*value),
+                        Value::Int16(value) | Value::Enum16(value) => i128::from(*value),
+                        Value::Int32(value) | Value::Date32(value) | Value::Decimal32(value) => {
+                            i128::from(*value)
+                        }
+                        Value::Int64(value) | Value::DateTime64(value) | Value::Decimal64(value) => {
+                            i128::from(*value)
+                        }
+                        Value::Int128(value) | Value::Decimal128(value) => *value,
+                        other => return Err(mismatch(stringify!($ty), other)),
+                    };
+                    <$ty>::try_from(wide).map_err(|_| Error::Overflow(OVERFLOW))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_value_int!(u8, u16, u32, u64, u128, i8, i16, i32, i64, i128);
+
+impl FromValue<'_> for bool {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "bool")?.1 {
+            Value::Bool(value) => Ok(*value),
+            other => Err(mismatch("bool", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for f32 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "f32")?.1 {
+            Value::Float32(value) | Value::Float16(value) | Value::BFloat16(value) => Ok(*value),
+            other => Err(mismatch("f32", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for f64 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "f64")?.1 {
+            Value::Float64(value) => Ok(*value),
+            Value::Float32(value) | Value::Float16(value) | Value::BFloat16(value) => {
+                Ok(f64::from(*value))
+            }
+            other => Err(mismatch("f64", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for f16 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "f16")?.1 {
+            Value::Float16(value) => Ok(f16::from_f32(*value)),
+            other => Err(mismatch("f16", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for bf16 {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "bf16")?.1 {
+            Value::BFloat16(value) => Ok(bf16::from_f32(*value)),
+            other => Err(mismatch("bf16", other)),
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for &'a [u8] {
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        match non_null(ty, value, "&[u8]")?.1 {
+            Value::String(bytes) | Value::FixedString(bytes) | Value::AggregateState(bytes) => {
+                Ok(bytes)
+            }
+            other => Err(mismatch("&[u8]", other)),
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for &'a str {
+    /// Reads `String` and `FixedString` columns, and enum columns by name.
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "&str")?;
+        let discriminant = match value {
+            Value::String(bytes) | Value::FixedString(bytes) => {
+                return std::str::from_utf8(bytes)
+                    .map_err(|_| Error::InvalidValue("invalid UTF-8 string"));
+            }
+            Value::Enum8(value) => i16::from(*value),
+            Value::Enum16(value) => *value,
+            other => return Err(mismatch("&str", other)),
+        };
+        ty.enum_name(discriminant)
+            .ok_or(Error::InvalidValue("enum value has no declared name"))
+    }
+}
+
+impl FromValue<'_> for String {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        <&str>::from_value(ty, value).map(str::to_string)
+    }
+}
+
+impl FromValue<'_> for Uuid {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "Uuid")?.1 {
+            Value::Uuid(value) => Ok(*value),
+            other => Err(mismatch("Uuid", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for Ipv4Addr {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "Ipv4Addr")?.1 {
+            Value::Ipv4(value) => Ok(*value),
+            other => Err(mismatch("Ipv4Addr", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for Ipv6Addr {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "Ipv6Addr")?.1 {
+            Value::Ipv6(value) => Ok(*value),
+            other => Err(mismatch("Ipv6Addr", other)),
+        }
+    }
+}
+
+impl FromValue<'_> for IpAddr {
+    fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+        match non_null(ty, value, "IpAddr")?.1 {
+            Value::Ipv4(value) => Ok(IpAddr::V4(*value)),
+            Value::Ipv6(value) => Ok(IpAddr::V6(*value)),
+            other => Err(mismatch("IpAddr", other)),
+        }
+    }
+}
+
+impl<'a> FromValue<'a> for &'a Value {
+    fn from_value(_ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        Ok(value)
+    }
+}
+
+impl FromValue<'_> for Value {
+    fn from_value(_ty: &TypeDesc, value: &Value) -> Result<Self> {
+        Ok(value.clone())
+    }
+}
+
+impl<'a, T: FromValue<'a>> FromValue<'a> for Option<T> {
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        unwrap_value(ty, value)
+            .map(|(ty, value)| T::from_value(ty, value))
+            .transpose()
+    }
+}
+
+impl<'a, T: FromValue<'a>> FromValue<'a> for Vec<T> {
+    /// Reads `Array`, `Nested` and geo array columns.
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        let (ty, value) = non_null(ty, value, "Vec")?;
+        match (array_element(ty), value) {
+            (Some(element), Value::Array(items)) => items
+                .iter()
+                .map(|item| T::from_value(element, item))
+                .collect(),
+            (_, other) => Err(mismatch("Vec", other)),
+        }
+    }
+}
+
+/// Decodes the entries of a `Map` value into `C`.
+fn map_entries<'a, K, V, C>(ty: &'a TypeDesc, value: &'a Value) -> Result<C>
+where
+    K: FromValue<'a>,
+    V: FromValue<'a>,
+    C: FromIterator<(K, V)>,
+{
+    match non_null(ty, value, "map")? {
+        (TypeDesc::Map { key, value }, Value::Map(entries)) => entries
+            .iter()
+            .map(|(k, v)| Ok((K::from_value(key, k)?, V::from_value(value, v)?)))
+            .collect(),
+        (_, other) => Err(mismatch("map", other)),
+    }
+}
+
+impl<'a, K, V, S> FromValue<'a> for HashMap<K, V, S>
+where
+    K: FromValue<'a> + Eq + Hash,
+    V: FromValue<'a>,
+    S: BuildHasher + Default,
+{
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        map_entries(ty, value)
+    }
+}
+
+impl<'a, K, V> FromValue<'a> for BTreeMap<K, V>
+where
+    K: FromValue<'a> + Ord,
+    V: FromValue<'a>,
+{
+    fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+        map_entries(ty, value)
+    }
+}
+
+macro_rules! impl_from_value_tuple {
+    ($len:literal => $($name:ident $index:tt),+) => {
+        impl<'a, $($name: FromValue<'a>),+> FromValue<'a> for ($($name,)+) {
+            /// Reads `Tuple` and `Point` columns and `Nested` rows by
+            /// position.
+            fn from_value(ty: &'a TypeDesc, value: &'a Value) -> Result<Self> {
+                let (ty, value) = non_null(ty, value, "tuple")?;
+                let Value::Tuple(items) = value else {
+                    return Err(mismatch("tuple", value));
+                };
+                if items.len() != $len {
+                    return Err(Error::InvalidValue("Tuple length mismatch"));
+                }
+                Ok(($(
+                    $name::from_value(
+                        tuple_element(ty, $index).ok_or_else(|| mismatch("tuple", value))?,
+                        &items[$index],
+                    )?,
+                )+))
+            }
+        }
+    };
+}
+
+impl_from_value_tuple!(1 => A 0);
+impl_from_value_tuple!(2 => A 0, B 1);
+impl_from_value_tuple!(3 => A 0, B 1, C 2);
+impl_from_value_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_from_value_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_from_value_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+impl_from_value_tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_from_value_tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_from_value_tuple!(9 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_from_value_tuple!(10 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_from_value_tuple!(11 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_from_value_tuple!(12 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+impl_from_value_tuple!(13 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+impl_from_value_tuple!(
+    14 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13
+);
+impl_from_value_tuple!(
+    15 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14
+);
+impl_from_value_tuple!(
+    16 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15
+);
@@ -0,0 +1,729 @@
+//! Encoding Rust values as [`Value`]s of a known column type.
+//!
+//! The conversions here are shared by [`IntoValue`], the serde serializer
+//! and `#[derive(ClickhouseRow)]`, so a value fits the same columns however
+//! it reaches the writer.
+
+use std::{
+    borrow::Cow,
+    collections::{BTreeMap, HashMap},
+    net::{IpAddr, Ipv4Addr, Ipv6Addr},
+};
+
+use half::{bf16, f16};
+use uuid::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::storage_type,
+    types::{DecimalSize, TupleItem, TypeDesc},
+    value::Value,
+};
+
+/// Type of the untyped paths of a `JSON` value.
+pub(crate) static DYNAMIC: TypeDesc = TypeDesc::Dynamic { max_types: None };
+/// Element type of a byte sequence encoded into a `String` column.
+static BYTE: TypeDesc = TypeDesc::UInt8;
+
+/// Rust type that can be encoded as a [`Value`] of a given column type.
+///
+/// The column type drives the conversion: a `u8` fills a `UInt32` column, a
+/// string fills an `Enum8` column by name and `Option<T>` fills `Nullable`
+/// columns. `Nullable`, `Variant`, `LowCardinality` and `Dynamic` columns
+/// accept whatever their inner types accept.
+pub trait IntoValue {
+    /// Encodes `self` as a value of column type `ty`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::TypeMismatch`] when `ty` cannot hold `self`, and
+    /// [`Error::Overflow`] or [`Error::InvalidValue`] when the value is out
+    /// of range for the column.
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value>;
+}
+
+pub(crate) fn mismatch(ty: &TypeDesc, actual: &str) -> Error {
+    Error::TypeMismatch {
+        expected: ty.type_name(),
+        actual: actual.to_string(),
+    }
+}
+
+/// Plain value handed to a column type for conversion.
+#[derive(Clone, Copy)]
+pub(crate) enum Scalar<'v> {
+    Bool(bool),
+    /// Any integer that fits `i128`.
+    Int(i128),
+    /// A `u128` above `i128::MAX`.
+    BigUInt(u128),
+    Float32(f32),
+    Float64(f64),
+    Str(&'v str),
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    Bytes(&'v [u8]),
+}
+
+impl Scalar<'_> {
+    pub(crate) fn unsigned(value: u128) -> Self {
+        i128::try_from(value).map_or(Scalar::BigUInt(value), Scalar::Int)
+    }
+
+    fn kind(self) -> &'static str {
+        match self {
+            Scalar::Bool(_) => "bool",
+            Scalar::Int(_) | Scalar::BigUInt(_) => "integer",
+            Scalar::Float32(_) | Scalar::Float64(_) => "float",
+            Scalar::Str(_) => "string",
+            Scalar::Bytes(_) => "bytes",
+        }
+    }
+
+    /// Type and value stored in a `Dynamic` column. Integers use the
+    /// narrowest of `Int64`, `UInt64`, `Int128` and `UInt128` that holds
+    /// them.
+    fn natural(self) -> (TypeDesc, Value) {
+        match self {
+            Scalar::Bool(value) => (TypeDesc::Bool, Value::Bool(value)),
+            Scalar::Int(value) => {
+                if let Ok(value) = i64::try_from(value) {
+                    (TypeDesc::Int64, Value::Int64(value))
+                } else if let Ok(value) = u64::try_from(value) {
+                    (TypeDesc::UInt64, Value::UInt64(value))
+                } else {
+                    (TypeDesc::Int128, Value::Int128(value))
+                }
+            }
+            Scalar::BigUInt(value) => (TypeDesc::UInt128, Value::UInt128(value)),
+            Scalar::Float32(value) => (TypeDesc::Float32, Value::Float32(value)),
+            Scalar::Float64(value) => (TypeDesc::Float64, Value::Float64(value)),
+            Scalar::Str(value) => (TypeDesc::String, Value::String(value.as_bytes().to_vec())),
+            Scalar::Bytes(value) => (TypeDesc::String, Value::String(value.to_vec())),
+        }
+    }
+}
+
+fn narrow<T: TryFrom<i128>>(value: i128) -> Result<T> {
+    T::try_from(value).map_err(|_| Error::Overflow("integer out of range for column type"))
+}
+
+/// Little-endian 256-bit two's complement of `value`.
+fn wide(value: i128) -> [u8; 32] {
+    let mut bytes = [if value < 0 { 0xff } else { 0 }; 32];
+    bytes[..16].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+fn wide_unsigned(value: u128) -> [u8; 32] {
+    let mut bytes = [0; 32];
+    bytes[..16].copy_from_slice(&value.to_le_bytes());
+    bytes
+}
+
+fn raw_256(bytes: &[u8]) -> Result<[u8; 32]> {
+    bytes
+        .try_into()
+        .map_err(|_| Error::InvalidValue("256-bit value must be 32 bytes"))
+}
+
+/// Encodes a single value, looking through `Nullable` and picking the first
+/// `Variant` alternative that `encode` accepts. Every other column type,
+/// `Dynamic` included, is handed to `encode` behind its storage wrappers.
+pub(crate) fn encode_leaf(
+    ty: &TypeDesc,
+    actual: &str,
+    encode: &dyn Fn(&TypeDesc) -> Result<Value>,
+) -> Result<Value> {
+    let ty = storage_type(ty);
+    match ty {
+        TypeDesc::Nullable(inner) => Ok(Value::Nullable(Some(Box::new(encode_leaf(
+            inner, actual, encode,
+        )?)))),
+        TypeDesc::Variant(variants) => variants
+            .iter()
+            .enumerate()
+            .find_map(|(index, variant)| {
+                let value = encode_leaf(variant, actual, encode).ok()?;
+                Some(Value::Variant {
+                    index: u8::try_from(index).ok()?,
+                    value: Box::new(value),
+                })
+            })
+            .ok_or_else(|| mismatch(ty, actual)),
+        _ => encode(ty),
+    }
+}
+
+/// Encodes a value that only fits columns of type `natural`, or `Dynamic`
+/// columns as that type.
+pub(crate) fn encode_exact(ty: &TypeDesc, natural: &TypeDesc, value: &Value) -> Result<Value> {
+    encode_leaf(ty, &natural.type_name(), &|ty| match ty {
+        TypeDesc::Dynamic { .. } => Ok(Value::Dynamic {
+            ty: Box::new(natural.clone()),
+            value: Box::new(value.clone()),
+        }),
+        ty if ty == natural => Ok(value.clone()),
+        ty => Err(mismatch(ty, &natural.type_name())),
+    })
+}
+
+pub(crate) fn encode_scalar(ty: &TypeDesc, scalar: Scalar<'_>) -> Result<Value> {
+    encode_leaf(ty, scalar.kind(), &|ty| scalar_value(ty, scalar))
+}
+
+#[allow(clippy::cast_possible_truncation, clippy::too_many_lines)]
+fn scalar_value(ty: &TypeDesc, scalar: Scalar<'_>) -> Result<Value> {
+    let value = match (ty, scalar) {
+        (TypeDesc::Dynamic { .. }, _) => {
+            let (ty, value) = scalar.natural();
+            Value::Dynamic {
+                ty: Box::new(ty),
+                value: Box::new(value),
+            }
+        }
+        (TypeDesc::Bool, Scalar::Bool(value)) => Value::Bool(value),
+        (TypeDesc::UInt8, Scalar::Int(value)) => Value::UInt8(narrow(value)?),
+        (TypeDesc::UInt16, Scalar::Int(value)) => Value::UInt16(narrow(value)?),
+        (TypeDesc::UInt32, Scalar::Int(value)) => Value::UInt32(narrow(value)?),
+        (TypeDesc::UInt64, Scalar::Int(value)) => Value::UInt64(narrow(value)?),
+        (TypeDesc::UInt128, Scalar::Int(value)) => Value::UInt128(narrow(value)?),
+        (TypeDesc::UInt128, Scalar::BigUInt(value)) => Value::UInt128(value),
+        (TypeDesc::Int8, Scalar::Int(value)) => Value::Int8(narrow(value)?),
+        (TypeDesc::Int16, Scalar::Int(value)) => Value::Int16(narrow(value)?),
+        (TypeDesc::Int32, Scalar::Int(value)) => Value::Int32(narrow(value)?),
+        (TypeDesc::Int64 | TypeDesc::Interval(_), Scalar::Int(value)) => {
+            Value::Int64(narrow(value)?)
+        }
+        (TypeDesc::Int128, Scalar::Int(value)) => Value::Int128(value),
+        (TypeDesc::UInt256, Scalar::Int(value)) => Value::UInt256(wide_unsigned(narrow(value)?)),
+        (TypeDesc::UInt256 | TypeDesc::Int256, Scalar::BigUInt(value)) => {
+            let bytes = wide_unsigned(value);
+            if matches!(ty, TypeDesc::UInt256) {
+                Value::UInt256(bytes)
+            } else {
+                Value::Int256(bytes)
+            }
+        }
+        (TypeDesc::Int256, Scalar::Int(value)) => Value::Int256(wide(value)),
+        (TypeDesc::UInt256, Scalar::Bytes(bytes)) => Value::UInt256(raw_256(bytes)?),
+        (TypeDesc::Int256, Scalar::Bytes(bytes)) => Value::Int256(raw_256(bytes)?),
+        (TypeDesc::Float32, Scalar::Float32(value)) => Value::Float32(value),
+        (TypeDesc::Float32, Scalar::Float64(value)) => Value::Float32(value as f32),
+        (TypeDesc::Float64, Scalar::Float32(value)) => Value::Float64(f64::from(value)),
+        (TypeDesc::Float64, Scalar::Float64(value)) => Value::Float64(value),
+        (TypeDesc::Float16, Scalar::Float32(value)) => Value::Float16(value),
+        (TypeDesc::Float16, Scalar::Float64(value)) => Value::Float16(value as f32),
+        (TypeDesc::BFloat16, Scalar::Float32(value)) => Value::BFloat16(value),
+        (TypeDesc::BFloat16, Scalar::Float64(value)) => Value::BFloat16(value as f32),
+        (TypeDesc::String, Scalar::Str(text)) => Value::String(text.as_bytes().to_vec()),
+        (TypeDesc::String, Scalar::Bytes(bytes)) => Value::String(bytes.to_vec()),
+        (TypeDesc::FixedString { .. }, Scalar::Str(text)) => {
+            Value::FixedString(text.as_bytes().to_vec())
+        }
+        (TypeDesc::FixedString { .. }, Scalar::Bytes(bytes)) => Value::FixedString(bytes.to_vec()),
+        (TypeDesc::Date, Scalar::Int(value)) => Value::Date(narrow(value)?),
+        (TypeDesc::Date32, Scalar::Int(value)) => Value::Date32(narrow(value)?),
+        (TypeDesc::DateTime { .. }, Scalar::Int(value)) => Value::DateTime(narrow(value)?),
+        (TypeDesc::DateTime64 { .. }, Scalar::Int(value)) => Value::DateTime64(narrow(value)?),
+        (TypeDesc::Uuid, Scalar::Str(text)) => Value::Uuid(
+            Uuid::parse_str(text).map_err(|_| Error::InvalidValue("invalid UUID string"))?,
+        ),
+        (TypeDesc::Uuid, Scalar::Bytes(bytes)) => Value::Uuid(
+            Uuid::from_slice(bytes).map_err(|_| Error::InvalidValue("UUID must be 16 bytes"))?,
+        ),
+        (TypeDesc::Ipv4, Scalar::Str(text)) => Value::Ipv4(
+            text.parse()
+                .map_err(|_| Error::InvalidValue("invalid IPv4 address"))?,
+        ),
+        (TypeDesc::Ipv4, Scalar::Int(value)) => Value::Ipv4(Ipv4Addr::from(narrow::<u32>(value)?)),
+        (TypeDesc::Ipv6, Scalar::Str(text)) => Value::Ipv6(
+            text.parse()
+                .map_err(|_| Error::InvalidValue("invalid IPv6 address"))?,
+        ),
+        (TypeDesc::Ipv6, Scalar::Int(value)) => Value::Ipv6(Ipv6Addr::from(narrow::<u128>(value)?)),
+        (TypeDesc::Ipv6, Scalar::BigUInt(value)) => Value::Ipv6(Ipv6Addr::from(value)),
+        (
+            TypeDesc::Decimal32 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits32,
+                ..
+            },
+            Scalar::Int(value),
+        ) => Value::Decimal32(narrow(value)?),
+        (
+            TypeDesc::Decimal64 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits64,
+                ..
+            },
+            Scalar::Int(value),
+        ) => Value::Decimal64(narrow(value)?),
+        (
+            TypeDesc::Decimal128 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits128,
+                ..
+            },
+            Scalar::Int(value),
+        ) => Value::Decimal128(value),
+        (
+            TypeDesc::Decimal256 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits256,
+                ..
+            },
+            Scalar::Int(value),
+        ) => Value::Decimal256(wide(value)),
+        (
+            TypeDesc::Decimal256 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits256,
+                ..
+            },
+            Scalar::BigUInt(value),
+        ) => Value::Decimal256(wide_unsigned(value)),
+        (
+            TypeDesc::Decimal256 { .. }
+            | TypeDesc::Decimal {
+                size: DecimalSize::Bits256,
+                ..
+            },
+            Scalar::Bytes(bytes),
+        ) => Value::Decimal256(raw_256(bytes)?),
+        (TypeDesc::Enum8(declared), Scalar::Str(name)) => {
+            let (_, value) = declared
+                .iter()
+                .find(|(candidate, _)| candidate == name)
+                .ok_or(Error::InvalidValue("unknown Enum8 name"))?;
+            Value::Enum8(*value)
+        }
+        (TypeDesc::Enum8(declared), Scalar::Int(value)) => {
+            let value = narrow::<i8>(value)?;
+            if !declared.iter().any(|(_, candidate)| *candidate == value) {
+                return Err(Error::InvalidValue("unknown Enum8 value"));
+            }
+            Value::Enum8(value)
+        }
+        (TypeDesc::Enum16(declared), Scalar::Str(name)) => {
+            let (_, value) = declared
+                .iter()
+                .find(|(candidate, _)| candidate == name)
+                .ok_or(Error::InvalidValue("unknown Enum16 name"))?;
+            Value::Enum16(*value)
+        }
+        (TypeDesc::Enum16(declared), Scalar::Int(value)) => {
+            let value = narrow::<i16>(value)?;
+            if !declared.iter().any(|(_, candidate)| *candidate == value) {
+                return Err(Error::InvalidValue("unknown Enum16 value"));
+            }
+            Value::Enum16(value)
+        }
+        (TypeDesc::AggregateFunction { .. }, Scalar::Bytes(bytes)) => {
+            Value::AggregateState(bytes.to_vec())
+        }
+        _ => return Err(mismatch(ty, scalar.kind())),
+    };
+    Ok(value)
+}
+
+pub(crate) fn encode_null(ty: &TypeDesc) -> Result<Value> {
+    match storage_type(ty) {
+        TypeDesc::Nullable(_) => Ok(Value::Nullable(None)),
+        TypeDesc::Variant(_) => Ok(Value::VariantNull),
+        TypeDesc::Dynamic { .. } => Ok(Value::DynamicNull),
+        TypeDesc::Nothing => Ok(Value::Nothing),
+        ty => Err(mismatch(ty, "null")),
+    }
+}
+
+/// Wrapper applied to a compound value once it is complete.
+#[derive(Clone, Copy)]
+pub(crate) enum Wrap {
+    Plain,
+    Nullable,
+    Variant(u8),
+}
+
+impl Wrap {
+    pub(crate) fn apply(self, value: Value) -> Value {
+        match self {
+            Wrap::Plain => value,
+            Wrap::Nullable => Value::Nullable(Some(Box::new(value))),
+            Wrap::Variant(index) => Value::Variant {
+                index,
+                value: Box::new(value),
+            },
+        }
+    }
+}
+
+/// Finds the type a compound value is encoded into, looking through
+/// `Nullable` and picking the first matching `Variant` alternative.
+pub(crate) fn resolve<'a, T>(
+    ty: &'a TypeDesc,
+    actual: &str,
+    target: impl Fn(&'a TypeDesc) -> Option<T>,
+) -> Result<(T, Wrap)> {
+    let ty = storage_type(ty);
+    let found = match ty {
+        TypeDesc::Nullable(inner) => {
+            target(storage_type(inner)).map(|found| (found, Wrap::Nullable))
+        }
+        TypeDesc::Variant(variants) => variants.iter().enumerate().find_map(|(index, variant)| {
+            let found = target(storage_type(variant))?;
+            Some((found, Wrap::Variant(u8::try_from(index).ok()?)))
+        }),
+        _ => target(ty).map(|found| (found, Wrap::Plain)),
+    };
+    found.ok_or_else(|| mismatch(ty, actual))
+}
+
+/// Element types of a sequence target.
+enum Elements<'a> {
+    /// Every element has the same type.
+    Same(Cow<'a, TypeDesc>),
+    /// Elements are typed by position.
+    Items(Cow<'a, [TupleItem]>),
+}
+
+/// Value a sequence is collected into.
+#[derive(Clone, Copy)]
+enum Collect {
+    Array,
+    Tuple,
+    String,
+    FixedString,
+}
+
+/// Column a sequence of values is encoded into: an `Array`, `Nested` or
+/// geo array, a `Tuple`, or a string built from bytes.
+pub(crate) struct SeqTarget<'a> {
+    elements: Elements<'a>,
+    collect: Collect,
+    wrap: Wrap,
+}
+
+impl<'a> SeqTarget<'a> {
+    pub(crate) fn new(ty: &'a TypeDesc, actual: &str) -> Result<Self> {
+        let ((elements, collect), wrap) = resolve(ty, actual, |ty| match ty {
+            TypeDesc::Array(inner) => Some((Elements::Same(Cow::Borrowed(inner)), Collect::Array)),
+            TypeDesc::Nested(items) => Some((
+                Elements::Same(Cow::Owned(TypeDesc::Tuple(items.clone()))),
+                Collect::Array,
+            )),
+            TypeDesc::Tuple(items) => Some((Elements::Items(Cow::Borrowed(items)), Collect::Tuple)),
+            TypeDesc::String => Some((Elements::Same(Cow::Borrowed(&BYTE)), Collect::String)),
+            TypeDesc::FixedString { .. } => {
+                Some((Elements::Same(Cow::Borrowed(&BYTE)), Collect::FixedString))
+            }
+            _ => match ty.geo_storage()? {
+                TypeDesc::Array(inner) => {
+                    Some((Elements::Same(Cow::Borrowed(inner)), Collect::Array))
+                }
+                TypeDesc::Tuple(items) => {
+                    Some((Elements::Items(Cow::Borrowed(items)), Collect::Tuple))
+                }
+                _ => None,
+            },
+        })?;
+        Ok(Self {
+            elements,
+            collect,
+            wrap,
+        })
+    }
+
+    /// Type of the element at `index`.
+    pub(crate) fn element(&self, index: usize) -> Result<&TypeDesc> {
+        match &self.elements {
+            Elements::Same(ty) => Ok(ty),
+            Elements::Items(items) => items
+                .get(index)
+                .map(|item| &item.ty)
+                .ok_or(Error::InvalidValue("Tuple length mismatch")),
+        }
+    }
+
+    /// Builds the column value from the encoded elements.
+    pub(crate) fn finish(self, values: Vec<Value>) -> Result<Value> {
+        if let Elements::Items(items) = &self.elements
+            && items.len() != values.len()
+        {
+            return Err(Error::InvalidValue("Tuple length mismatch"));
+        }
+        let bytes = |values: Vec<Value>| {
+            values
+                .into_iter()
+                .map(|value| match value {
+                    Value::UInt8(byte) => Ok(byte),
+                    _ => Err(Error::Internal("byte sequence produced a non-byte")),
+                })
+                .collect::<Result<Vec<u8>>>()
+        };
+        let value = match self.collect {
+            Collect::Array => Value::Array(values),
+            Collect::Tuple => Value::Tuple(values),
+            Collect::String => Value::String(bytes(values)?),
+            Collect::FixedString => Value::FixedString(bytes(values)?),
+        };
+        Ok(self.wrap.apply(value))
+    }
+}
+
+/// Map-like targets for maps and structs.
+pub(crate) enum MapTarget<'a> {
+    Map {
+        key: &'a TypeDesc,
+        value: &'a TypeDesc,
+    },
+    Json(&'a [(String, TypeDesc)]),
+    #[cfg_attr(not(feature = "serde"), allow(dead_code))]
+    Named(&'a [TupleItem]),
+}
+
+pub(crate) fn map_target(ty: &TypeDesc) -> Option<MapTarget<'_>> {
+    match ty {
+        TypeDesc::Map { key, value } => Some(MapTarget::Map { key, value }),
+        TypeDesc::Json { typed_paths, .. } => Some(MapTarget::Json(typed_paths)),
+        TypeDesc::Tuple(items) if items.iter().all(|item| item.name.is_some()) => {
+            Some(MapTarget::Named(items))
+        }
+        _ => None,
+    }
+}
+
+/// Type of the `JSON` path `path`: its declared type, or `Dynamic`.
+pub(crate) fn json_path_type<'a>(
+    typed_paths: &'a [(String, TypeDesc)],
+    path: &str,
+) -> &'a TypeDesc {
+    typed_paths
+        .iter()
+        .find(|(name, _)| name == path)
+        .map_or(&DYNAMIC, |(_, ty)| ty)
+}
+
+/// Encodes map entries into a `Map` or `JSON` column.
+fn encode_entries<'e, K, V>(
+    ty: &TypeDesc,
+    entries: impl Iterator<Item = (&'e K, &'e V)>,
+) -> Result<Value>
+where
+    K: IntoValue + 'e,
+    V: IntoValue + 'e,
+{
+    let (target, wrap) = resolve(ty, "map", |ty| {
+        map_target(ty).filter(|target| !matches!(target, MapTarget::Named(_)))
+    })?;
+    let value = match target {
+        MapTarget::Map { key, value } => Value::Map(
+            entries
+                .map(|(k, v)| Ok((k.to_value(key)?, v.to_value(value)?)))
+                .collect::<Result<_>>()?,
+        ),
+        MapTarget::Json(typed_paths) => Value::JsonObject(
+            entries
+                .map(|(k, v)| {
+                    let path = match k.to_value(&TypeDesc::String)? {
+                        Value::String(bytes) => String::from_utf8(bytes)
+                            .map_err(|_| Error::InvalidValue("invalid UTF-8 string"))?,
+                        _ => return Err(Error::Internal("String encoder produced a non-string")),
+                    };
+                    let value = v.to_value(json_path_type(typed_paths, &path))?;
+                    Ok((path, value))
+                })
+                .collect::<Result<_>>()?,
+        ),
+        MapTarget::Named(_) => return Err(Error::Internal("named tuple picked for a map")),
+    };
+    Ok(wrap.apply(value))
+}
+
+impl<T: IntoValue + ?Sized> IntoValue for &T {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        (**self).to_value(ty)
+    }
+}
+
+impl<T: IntoValue + ?Sized> IntoValue for Box<T> {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        (**self).to_value(ty)
+    }
+}
+
+macro_rules! impl_into_value_int {
+    ($($ty:ty),* $(,)?) => {
+        $(
+            impl IntoValue for $ty {
+                fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+                    encode_scalar(ty, Scalar::Int(i128::from(*self)))
+                }
+            }
+        )*
+    };
+}
+
+impl_into_value_int!(u8, u16, u32, u64, i8, i16, i32, i64, i128);
+
+impl IntoValue for u128 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::unsigned(*self))
+    }
+}
+
+impl IntoValue for bool {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Bool(*self))
+    }
+}
+
+impl IntoValue for f32 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Float32(*self))
+    }
+}
+
+impl IntoValue for f64 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Float64(*self))
+    }
+}
+
+impl IntoValue for f16 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Float32(self.to_f32()))
+    }
+}
+
+impl IntoValue for bf16 {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Float32(self.to_f32()))
+    }
+}
+
+impl IntoValue for str {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_scalar(ty, Scalar::Str(self))
+    }
+}
+
+impl IntoValue for String {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        self.as_str().to_value(ty)
+    }
+}
+
+impl IntoValue for Uuid {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_exact(ty, &TypeDesc::Uuid, &Value::Uuid(*self))
+    }
+}
+
+impl IntoValue for Ipv4Addr {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_exact(ty, &TypeDesc::Ipv4, &Value::Ipv4(*self))
+    }
+}
+
+impl IntoValue for Ipv6Addr {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_exact(ty, &TypeDesc::Ipv6, &Value::Ipv6(*self))
+    }
+}
+
+impl IntoValue for IpAddr {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        match self {
+            IpAddr::V4(addr) => addr.to_value(ty),
+            IpAddr::V6(addr) => addr.to_value(ty),
+        }
+    }
+}
+
+impl IntoValue for Value {
+    /// Returns a copy of the value as is; the writer checks it against the
+    /// column type.
+    fn to_value(&self, _ty: &TypeDesc) -> Result<Value> {
+        Ok(self.clone())
+    }
+}
+
+impl<T: IntoValue> IntoValue for Option<T> {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        match self {
+            Some(value) => value.to_value(ty),
+            None => encode_null(ty),
+        }
+    }
+}
+
+impl<T: IntoValue> IntoValue for [T] {
+    /// Encodes the elements into an `Array`, `Tuple` or geo column, or the
+    /// bytes of a `String` column.
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        let target = SeqTarget::new(ty, "sequence")?;
+        let values = self
+            .iter()
+            .enumerate()
+            .map(|(index, item)| item.to_value(target.element(index)?))
+            .collect::<Result<_>>()?;
+        target.finish(values)
+    }
+}
+
+impl<T: IntoValue, const N: usize> IntoValue for [T; N] {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        self.as_slice().to_value(ty)
+    }
+}
+
+impl<T: IntoValue> IntoValue for Vec<T> {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        self.as_slice().to_value(ty)
+    }
+}
+
+impl<K: IntoValue, V: IntoValue, S> IntoValue for HashMap<K, V, S> {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_entries(ty, self.iter())
+    }
+}
+
+impl<K: IntoValue, V: IntoValue> IntoValue for BTreeMap<K, V> {
+    fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+        encode_entries(ty, self.iter())
+    }
+}
+
+macro_rules! impl_into_value_tuple {
+    ($($name:ident $index:tt),+) => {
+        impl<$($name: IntoValue),+> IntoValue for ($($name,)+) {
+            fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+                let target = SeqTarget::new(ty, "tuple")?;
+                let values = vec![$(self.$index.to_value(target.element($index)?)?),+];
+                target.finish(values)
+            }
+        }
+    };
+}
+
+impl_into_value_tuple!(A 0);
+impl_into_value_tuple!(A 0, B 1);
+impl_into_value_tuple!(A 0, B 1, C 2);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+impl_into_value_tuple!(A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+impl_into_value_tuple!(
+    A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14
+);
+impl_into_value_tuple!(
+    A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15
+);
@@ -0,0 +1,637 @@
+//! Typed rows: conversions between user structs and [`Row`]s.
+//!
+//! [`ToRow`] and [`FromRow`] map a Rust type onto the columns of a
+//! [`Schema`], and [`ClickhouseRow`] describes the schema a type maps onto by
+//! default. With the `derive` feature, `#[derive(ClickhouseRow)]` implements
+//! all three for structs with named fields. For ad hoc access, [`RowView`]
+//! reads single columns by name or index. Field and column values convert
+//! through [`IntoValue`] and [`FromValue`], which users can implement for
+//! their own types.
+
+pub(crate) mod from_value;
+pub(crate) mod into_value;
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    net::{Ipv4Addr, Ipv6Addr},
+};
+
+pub use from_value::FromValue;
+use half::{bf16, f16};
+pub use into_value::IntoValue;
+use uuid::Uuid;
+
+use crate::{
+    error::{Error, Result},
+    rowbinary::{Row, Schema},
+    types::{TupleItem, TypeDesc},
+    value::Value,
+};
+
+/// Rust type with a default `ClickHouse` column type.
+///
+/// Used by `#[derive(ClickhouseRow)]` to build the schema of fields without
+/// an explicit `type` attribute.
+pub trait ColumnType {
+    /// Column type values of this Rust type are stored as.
+    fn column_type() -> TypeDesc;
+}
+
+/// Rust type that maps onto a default [`Schema`].
+pub trait ClickhouseRow: ToRow + FromRow {
+    /// Returns the schema of this type, one column per field.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a declared column type does not
+    /// parse.
+    fn schema() -> Result<Schema>;
+}
+
+/// Rust type that can be encoded as a row.
+pub trait ToRow {
+    /// Encodes `self` as a row of `schema`, in schema column order.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a column has no matching field,
+    /// a field has no matching column, or a value does not fit its column
+    /// type.
+    fn to_row(&self, schema: &Schema) -> Result<Row>;
+}
+
+/// Rust type that can be decoded from a row.
+pub trait FromRow: Sized {
+    /// Decodes a row of `schema`. Columns are matched by name and columns
+    /// without a matching field are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when a field has no matching column
+    /// or a value cannot be converted into its field.
+    fn from_row(schema: &Schema, row: &[Value]) -> Result<Self>;
+}
+
+/// Borrowed row paired with its schema, for reading columns by name.
+#[derive(Clone, Copy, Debug)]
+pub struct RowView<'a> {
+    schema: &'a Schema,
+    values: &'a [Value],
+}
+
+impl<'a> RowView<'a> {
+    /// Pairs `values` with the columns of `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the row length does not match
+    /// the schema.
+    pub fn new(schema: &'a Schema, values: &'a [Value]) -> Result<Self> {
+        if values.len() != schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        Ok(Self { schema, values })
+    }
+
+    /// Returns the schema of the row.
+    #[must_use]
+    pub fn schema(&self) -> &'a Schema {
+        self.schema
+    }
+
+    /// Returns the row values in column order.
+    #[must_use]
+    pub fn values(&self) -> &'a [Value] {
+        self.values
+    }
+
+    /// Iterates over `(column name, value)` pairs in column order.
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (&'a str, &'a Value)> + use<'a> {
+        self.schema
+            .fields()
+            .iter()
+            .map(|field| field.name.as_str())
+            .zip(self.values)
+    }
+
+    /// Returns the `(column name, value)` pairs in column order, as a map
+    /// that keeps the order of the schema.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when two columns share a name.
+    pub fn as_map(&self) -> Result<Vec<(&'a str, &'a Value)>> {
+        self.schema.ensure_unique_names()?;
+        Ok(self.iter().collect())
+    }
+
+    /// Returns the value of the column named `name`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when there is no such column.
+    pub fn value(&self, name: &str) -> Result<&'a Value> {
+        self.value_at(self.index_of(name)?)
+    }
+
+    /// Returns the value of the column at `index`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when `index` is out of range.
+    pub fn value_at(&self, index: usize) -> Result<&'a Value> {
+        self.values
+            .get(index)
+            .ok_or_else(|| Error::UnknownColumn(format!("index {index}")))
+    }
+
+    /// Returns the type and value of the column at `index`.
+    fn column(&self, index: usize) -> Result<(&'a TypeDesc, &'a Value)> {
+        let value = self.value_at(index)?;
+        Ok((&self.schema.fields()[index].ty, value))
+    }
+
+    fn index_of(&self, name: &str) -> Result<usize> {
+        self.schema
+            .index_of(name)
+            .ok_or_else(|| Error::UnknownColumn(name.to_string()))
+    }
+
+    /// Reads the column named `name` as `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when there is no such column, or the
+    /// [`FromValue`] error when the value does not convert; nulls only
+    /// convert into `Option`.
+    pub fn get<T: FromValue<'a>>(&self, name: &str) -> Result<T> {
+        self.get_at(self.index_of(name)?)
+    }
+
+    /// Reads the column named `name` as `T`, mapping nulls to `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when there is no such column, or the
+    /// [`FromValue`] error when the value does not convert.
+    pub fn get_opt<T: FromValue<'a>>(&self, name: &str) -> Result<Option<T>> {
+        self.get_opt_at(self.index_of(name)?)
+    }
+
+    /// Reads the column at `index` as `T`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when `index` is out of range, or the
+    /// [`FromValue`] error when the value does not convert.
+    pub fn get_at<T: FromValue<'a>>(&self, index: usize) -> Result<T> {
+        let (ty, value) = self.column(index)?;
+        T::from_value(ty, value)
+    }
+
+    /// Reads the column at `index` as `T`, mapping nulls to `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::UnknownColumn`] when `index` is out of range, or the
+    /// [`FromValue`] error when the value does not convert.
+    pub fn get_opt_at<T: FromValue<'a>>(&self, index: usize) -> Result<Option<T>> {
+        let (ty, value) = self.column(index)?;
+        Option::<T>::from_value(ty, value)
+    }
+}
+
+/// Implements positional tuple conversions: `TryFrom<RowView>` for borrowed
+/// reads and [`FromRow`] for owned ones, plus [`ColumnType`] as an unnamed
+/// `Tuple`.
+macro_rules! impl_row_tuple {
+    ($len:literal => $($name:ident $index:tt),+) => {
+        impl<'a, $($name: FromValue<'a>),+> TryFrom<RowView<'a>> for ($($name,)+) {
+            type Error = Error;
+
+            fn try_from(row: RowView<'a>) -> Result<Self> {
+                if row.values.len() != $len {
+                    return Err(Error::InvalidValue("tuple arity does not match row length"));
+                }
+                Ok(($(row.get_at::<$name>($index)?,)+))
+            }
+        }
+
+        impl<$($name: for<'v> FromValue<'v>),+> FromRow for ($($name,)+) {
+            fn from_row(schema: &Schema, row: &[Value]) -> Result<Self> {
+                RowView::new(schema, row)?.try_into()
+            }
+        }
+
+        impl<$($name: ColumnType),+> ColumnType for ($($name,)+) {
+            fn column_type() -> TypeDesc {
+                TypeDesc::Tuple(vec![$(TupleItem {
+                    name: None,
+                    ty: $name::column_type(),
+                }),+])
+            }
+        }
+    };
+}
+
+impl_row_tuple!(1 => A 0);
+impl_row_tuple!(2 => A 0, B 1);
+impl_row_tuple!(3 => A 0, B 1, C 2);
+impl_row_tuple!(4 => A 0, B 1, C 2, D 3);
+impl_row_tuple!(5 => A 0, B 1, C 2, D 3, E 4);
+impl_row_tuple!(6 => A 0, B 1, C 2, D 3, E 4, F 5);
+impl_row_tuple!(7 => A 0, B 1, C 2, D 3, E 4, F 5, G 6);
+impl_row_tuple!(8 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7);
+impl_row_tuple!(9 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8);
+impl_row_tuple!(10 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9);
+impl_row_tuple!(11 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10);
+impl_row_tuple!(12 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11);
+impl_row_tuple!(13 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12);
+impl_row_tuple!(14 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13);
+impl_row_tuple!(
+    15 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14
+);
+impl_row_tuple!(
+    16 => A 0, B 1, C 2, D 3, E 4, F 5, G 6, H 7, I 8, J 9, K 10, L 11, M 12, N 13, O 14, P 15
+);
+
+macro_rules! impl_column_type {
+    ($($ty:ty => $desc:expr),* $(,)?) => {
+        $(
+            impl ColumnType for $ty {
+                fn column_type() -> TypeDesc {
+                    $desc
+                }
+            }
+        )*
+    };
+}
+
+impl_column_type!(
+    bool => TypeDesc::Bool,
+    u8 => TypeDesc::UInt8,
+    u16 => TypeDesc::UInt16,
+    u32 => TypeDesc::UInt32,
+    u64 => TypeDesc::UInt64,
+    u128 => TypeDesc::UInt128,
+    i8 => TypeDesc::Int8,
+    i16 => TypeDesc::Int16,
+    i32 => TypeDesc::Int32,
+    i64 => TypeDesc::Int64,
+    i128 => TypeDesc::Int128,
+    f32 => TypeDesc::Float32,
+    f64 => TypeDesc::Float64,
+    f16 => TypeDesc::Float16,
+    bf16 => TypeDesc::BFloat16,
+    String => TypeDesc::String,
+    Uuid => TypeDesc::Uuid,
+    Ipv4Addr => TypeDesc::Ipv4,
+    Ipv6Addr => TypeDesc::Ipv6,
+);
+
+impl<T: ColumnType> ColumnType for Option<T> {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Nullable(Box::new(T::column_type()))
+    }
+}
+
+impl<T: ColumnType> ColumnType for Vec<T> {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Array(Box::new(T::column_type()))
+    }
+}
+
+impl<K: ColumnType, V: ColumnType, S> ColumnType for HashMap<K, V, S> {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Map {
+            key: Box::new(K::column_type()),
+            value: Box::new(V::column_type()),
+        }
+    }
+}
+
+impl<K: ColumnType, V: ColumnType> ColumnType for BTreeMap<K, V> {
+    fn column_type() -> TypeDesc {
+        TypeDesc::Map {
+            key: Box::new(K::column_type()),
+            value: Box::new(V::column_type()),
+        }
+    }
+}
+
+/// Support code for `#[derive(ClickhouseRow)]`; not a public API.
+#[cfg(feature = "derive")]
+pub mod derive {
+    use super::{FromValue, IntoValue};
+    use crate::{
+        error::{Error, Result},
+        rowbinary::{Field, Schema},
+        value::Value,
+    };
+
+    fn in_column(field: &Field, err: &Error) -> Error {
+        Error::Mapping(format!("column `{}`: {err}", field.name))
+    }
+
+    /// Checks that every field name has a column in `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mapping`] naming the first field without a column.
+    pub fn check_fields(schema: &Schema, names: &[&str]) -> Result<()> {
+        match names
+            .iter()
+            .find(|name| !schema.fields().iter().any(|field| field.name == **name))
+        {
+            Some(name) => Err(Error::Mapping(format!(
+                "field `{name}` has no matching column"
+            ))),
+            None => Ok(()),
+        }
+    }
+
+    /// Checks that `row` has one value per column of `schema`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::InvalidValue`] when the lengths differ.
+    pub fn check_row(schema: &Schema, row: &[Value]) -> Result<()> {
+        if row.len() == schema.len() {
+            Ok(())
+        } else {
+            Err(Error::InvalidValue("row length does not match schema"))
+        }
+    }
+
+    /// Error for a column without a matching field.
+    #[must_use]
+    pub fn unknown_column(field: &Field) -> Error {
+        Error::Mapping(format!("column `{}` has no matching field", field.name))
+    }
+
+    /// Error for a field without a matching column.
+    #[must_use]
+    pub fn missing_column(name: &str) -> Error {
+        Error::Mapping(format!("missing column for field `{name}`"))
+    }
+
+    /// Encodes a field value as the type of `field`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mapping`] naming the column when the value does not
+    /// fit its type.
+    pub fn encode_field<T: IntoValue + ?Sized>(field: &Field, value: &T) -> Result<Value> {
+        value
+            .to_value(&field.ty)
+            .map_err(|err| in_column(field, &err))
+    }
+
+    /// Decodes a field value from a value of `field`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`Error::Mapping`] naming the column when the value cannot
+    /// be converted.
+    pub fn decode_field<T: for<'v> FromValue<'v>>(field: &Field, value: &Value) -> Result<T> {
+        T::from_value(&field.ty, value).map_err(|err| in_column(field, &err))
+    }
+}
+
+#[cfg(all(test, feature = "derive"))]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{ClickhouseRow, ColumnType, FromRow, FromValue, IntoValue, ToRow};
+    use crate::{
+        error::Result,
+        rowbinary::{RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema},
+        types::TypeDesc,
+        value::Value,
+    };
+
+    #[derive(Debug, PartialEq, crate::ClickhouseRow)]
+    struct Event {
+        id: u64,
+        #[clickhouse(rename = "event_name")]
+        name: String,
+        #[clickhouse(type = "DateTime64(3, 'UTC')")]
+        at: i64,
+        score: Option<f32>,
+        tags: Vec<String>,
+        attrs: BTreeMap<String, i32>,
+    }
+
+    /// Amount in cents, stored as `Decimal64(2)`.
+    #[derive(Debug, PartialEq)]
+    struct Cents(i64);
+
+    impl ColumnType for Cents {
+        fn column_type() -> TypeDesc {
+            TypeDesc::Decimal64 { scale: 2 }
+        }
+    }
+
+    impl IntoValue for Cents {
+        fn to_value(&self, ty: &TypeDesc) -> Result<Value> {
+            self.0.to_value(ty)
+        }
+    }
+
+    impl FromValue<'_> for Cents {
+        fn from_value(ty: &TypeDesc, value: &Value) -> Result<Self> {
+            i64::from_value(ty, value).map(Cents)
+        }
+    }
+
+    #[derive(Debug, PartialEq, crate::ClickhouseRow)]
+    struct Payment {
+        amount: Cents,
+        refund: Option<Cents>,
+        parts: (u8, String),
+    }
+
+    /// Row read from tables with or without the `note` column.
+    #[derive(Debug, Default, PartialEq, crate::ClickhouseRow)]
+    struct Charge {
+        amount: i64,
+        #[clickhouse(default)]
+        note: String,
+    }
+
+    #[derive(Debug, Default, PartialEq, crate::ClickhouseRow)]
+    #[clickhouse(default)]
+    struct Sparse {
+        id: u64,
+        tags: Vec<String>,
+    }
+
+    fn event(id: u64) -> Event {
+        Event {
+            id,
+            name: format!("event{id}"),
+            at: 1_700_000_000_000,
+            score: id.is_multiple_of(2).then_some(0.5),
+            tags: vec!["a".to_string()],
+            attrs: BTreeMap::from([("k".to_string(), -1)]),
+        }
+    }
+
+    #[test]
+    fn derived_schema_uses_field_types_and_overrides() {
+        let schema = Event::schema().unwrap();
+        let expected = Schema::from_type_strings(&[
+            ("id", "UInt64"),
+            ("event_name", "String"),
+            ("at", "DateTime64(3, 'UTC')"),
+            ("score", "Nullable(Float32)"),
+            ("tags", "Array(String)"),
+            ("attrs", "Map(String, Int32)"),
+        ])
+        .unwrap();
+        assert_eq!(schema, expected);
+    }
+
+    #[test]
+    fn schema_of_builds_column_definitions() {
+        let schema = Schema::of::<Event>().unwrap();
+        assert_eq!(schema, Event::schema().unwrap());
+        assert_eq!(
+            schema.column_definitions(),
+            "`id` UInt64, `event_name` String, `at` DateTime64(3, 'UTC'), \
+             `score` Nullable(Float32), `tags` Array(String), `attrs` Map(String, Int32)"
+        );
+    }
+
+    #[test]
+    fn derived_rows_round_trip_through_writer_and_reader() {
+        let mut writer = RowBinaryValueWriter::new(
+            Vec::new(),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            Event::schema().unwrap(),
+        );
+        writer.write_header().unwrap();
+        for id in 1..=3 {
+            writer.write_typed(&event(id)).unwrap();
+        }
+        let payload = writer.into_inner();
+
+        let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+        let mut decoded = Vec::new();
+        while let Some(event) = reader.read_typed::<Event>().unwrap() {
+            decoded.push(event);
+        }
+        assert_eq!(decoded, [event(1), event(2), event(3)]);
+    }
+
+    #[test]
+    fn derived_rows_follow_schema_column_names() {
+        let reordered = Schema::from_type_strings(&[
+            ("extra", "UInt8"),
+            ("attrs", "Map(String, Int64)"),
+            ("tags", "Array(LowCardinality(String))"),
+            ("score", "Nullable(Float64)"),
+            ("at", "DateTime64(3)"),
+            ("event_name", "String"),
+            ("id", "UInt32"),
+        ])
+        .unwrap();
+        let row = vec![
+            Value::UInt8(9),
+            Value::Map(vec![(Value::String(b"k".to_vec()), Value::Int64(-1))]),
+            Value::Array(vec![Value::String(b"a".to_vec())]),
+            Value::Nullable(None),
+            Value::DateTime64(1_700_000_000_000),
+            Value::String(b"event1".to_vec()),
+            Value::UInt32(1),
+        ];
+        assert_eq!(Event::from_row(&reordered, &row).unwrap(), event(1));
+
+        let err = event(1).to_row(&reordered).unwrap_err().to_string();
+        assert!(
+            err.contains("column `extra` has no matching field"),
+            "{err}"
+        );
+
+        let narrow = Schema::from_type_strings(&[("id", "UInt64")]).unwrap();
+        let err = event(1).to_row(&narrow).unwrap_err().to_string();
+        assert!(
+            err.contains("field `event_name` has no matching column"),
+            "{err}"
+        );
+        let err = Event::from_row(&narrow, &[Value::UInt64(1)])
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains("missing column for field `event_name`"),
+            "{err}"
+        );
+
+        let small = Schema::from_type_strings(&[
+            ("id", "UInt8"),
+            ("event_name", "String"),
+            ("at", "DateTime64(3)"),
+            ("score", "Nullable(Float32)"),
+            ("tags", "Array(String)"),
+            ("attrs", "Map(String, Int32)"),
+        ])
+        .unwrap();
+        let err = event(300).to_row(&small).unwrap_err().to_string();
+        assert!(err.contains("column `id`"), "{err}");
+    }
+
+    #[test]
+    fn derived_rows_use_user_value_conversions() {
+        let schema = Payment::schema().unwrap();
+        let expected = Schema::from_type_strings(&[
+            ("amount", "Decimal64(2)"),
+            ("refund", "Nullable(Decimal64(2))"),
+            ("parts", "Tuple(UInt8, String)"),
+        ])
+        .unwrap();
+        assert_eq!(schema, expected);
+
+        let payment = Payment {
+            amount: Cents(1250),
+            refund: None,
+            parts: (1, "card".to_string()),
+        };
+        let row = payment.to_row(&schema).unwrap();
+        assert_eq!(
+            row,
+            [
+                Value::Decimal64(1250),
+                Value::Nullable(None),
+                Value::Tuple(vec![Value::UInt8(1), Value::String(b"card".to_vec())]),
+            ]
+        );
+        assert_eq!(Payment::from_row(&schema, &row).unwrap(), payment);
+    }
+
+    #[test]
+    fn default_fields_tolerate_missing_columns() {
+        let schema = Schema::from_type_strings(&[("extra", "UInt8"), ("amount", "Int64")]).unwrap();
+        let row = [Value::UInt8(1), Value::Int64(7)];
+        let charge = Charge::from_row(&schema, &row).unwrap();
+        assert_eq!(
+            charge,
+            Charge {
+                amount: 7,
+                note: String::new(),
+            }
+        );
+
+        let narrow = Schema::from_type_strings(&[("amount", "Int64")]).unwrap();
+        assert_eq!(charge.to_row(&narrow).unwrap(), [Value::Int64(7)]);
+
+        let empty = Schema::new(Vec::new());
+        let err = Charge::from_row(&empty, &[]).unwrap_err().to_string();
+        assert!(err.contains("missing column for field `amount`"), "{err}");
+        assert_eq!(Sparse::from_row(&empty, &[]).unwrap(), Sparse::default());
+        assert_eq!(
+            Sparse::from_row(&narrow, &[Value::Int64(7)]).unwrap(),
+            Sparse::default()
+        );
+    }
+}
@@ -1,12 +1,44 @@
 //! Type descriptors used by `RowBinary` read/write paths.
 
-use std::fmt;
+use std::{fmt, sync::LazyLock};
 
 use crate::error::{Error, Result};
 
 const JSON_DEFAULT_MAX_DYNAMIC_PATHS: usize = 1024;
 const JSON_DEFAULT_MAX_DYNAMIC_TYPES: u8 = 32;
 const JSON_MAX_TYPED_PATHS: usize = 1000;
+pub(crate) const DATETIME64_MAX_PRECISION: u8 = 9;
+
+/// Default maximum type nesting depth accepted by [`parse_type_desc`].
+pub const DEFAULT_MAX_TYPE_DEPTH: usize = 64;
+
+/// Storage types of the geo types, shared so decoding geo values does not
+/// build a type per value.
+static POINT_STORAGE: LazyLock<TypeDesc> = LazyLock::new(|| {
+    let coordinate = || TupleItem {
+        name: None,
+        ty: TypeDesc::Float64,
+    };
+    TypeDesc::Tuple(vec![coordinate(), coordinate()])
+});
+static POINTS_STORAGE: LazyLock<TypeDesc> =
+    LazyLock::new(|| TypeDesc::Array(Box::new(TypeDesc::Point)));
+static LINE_STRINGS_STORAGE: LazyLock<TypeDesc> =
+    LazyLock::new(|| TypeDesc::Array(Box::new(TypeDesc::LineString)));
+static RINGS_STORAGE: LazyLock<TypeDesc> =
+    LazyLock::new(|| TypeDesc::Array(Box::new(TypeDesc::Ring)));
+static POLYGONS_STORAGE: LazyLock<TypeDesc> =
+    LazyLock::new(|| TypeDesc::Array(Box::new(TypeDesc::Polygon)));
+
+/// Returns the number of `DateTime64` ticks per second for `precision`.
+pub(crate) fn datetime64_ticks_per_second(precision: u8) -> Result<i64> {
+    if precision > DATETIME64_MAX_PRECISION {
+        return Err(Error::InvalidValue(
+            "DateTime64 precision must be at most 9",
+        ));
+    }
+    Ok(10_i64.pow(u32::from(precision)))
+}
 
 /// Parsed `ClickHouse` type descriptor.
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -76,6 +108,34 @@ pub enum TypeDesc {
     Ipv4,
     /// IPv6 address column.
     Ipv6,
+    /// Aggregate function state column with opaque per-row state bytes.
+    AggregateFunction {
+        /// Function name including parameters, such as `quantiles(0.5, 0.9)`.
+        function: String,
+        /// Argument types.
+        arguments: Vec<TypeDesc>,
+    },
+    /// Simple aggregate function column stored as its value type.
+    SimpleAggregateFunction {
+        /// Function name including parameters, such as `sum` or `anyLast`.
+        function: String,
+        /// Stored value type.
+        ty: Box<TypeDesc>,
+    },
+    /// Interval stored as a signed 64-bit count of units.
+    Interval(IntervalKind),
+    /// Geo point stored as `Tuple(Float64, Float64)`.
+    Point,
+    /// Geo ring stored as `Array(Point)`.
+    Ring,
+    /// Geo line string stored as `Array(Point)`.
+    LineString,
+    /// Geo multi line string stored as `Array(LineString)`.
+    MultiLineString,
+    /// Geo polygon stored as `Array(Ring)`.
+    Polygon,
+    /// Geo multi polygon stored as `Array(Polygon)`.
+    MultiPolygon,
     /// Decimal type with explicit precision/scale.
     Decimal {
         /// Total precision (number of digits).
@@ -157,6 +217,53 @@ pub struct TupleItem {
     pub ty: TypeDesc,
 }
 
+/// Unit of an `Interval*` type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum IntervalKind {
+    /// `IntervalNanosecond`.
+    Nanosecond,
+    /// `IntervalMicrosecond`.
+    Microsecond,
+    /// `IntervalMillisecond`.
+    Millisecond,
+    /// `IntervalSecond`.
+    Second,
+    /// `IntervalMinute`.
+    Minute,
+    /// `IntervalHour`.
+    Hour,
+    /// `IntervalDay`.
+    Day,
+    /// `IntervalWeek`.
+    Week,
+    /// `IntervalMonth`.
+    Month,
+    /// `IntervalQuarter`.
+    Quarter,
+    /// `IntervalYear`.
+    Year,
+}
+
+impl IntervalKind {
+    /// Returns the unit name used in the `Interval<Unit>` type name.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            IntervalKind::Nanosecond => "Nanosecond",
+            IntervalKind::Microsecond => "Microsecond",
+            IntervalKind::Millisecond => "Millisecond",
+            IntervalKind::Second => "Second",
+            IntervalKind::Minute => "Minute",
+            IntervalKind::Hour => "Hour",
+            IntervalKind::Day => "Day",
+            IntervalKind::Week => "Week",
+            IntervalKind::Month => "Month",
+            IntervalKind::Quarter => "Quarter",
+            IntervalKind::Year => "Year",
+        }
+    }
+}
+
 /// Backing storage size for Decimal types.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum DecimalSize {
@@ -211,6 +318,22 @@ impl TypeDesc {
             TypeDesc::Uuid => "UUID".into(),
             TypeDesc::Ipv4 => "IPv4".into(),
             TypeDesc::Ipv6 => "IPv6".into(),
+            TypeDesc::AggregateFunction {
+                function,
+                arguments,
+            } => format_aggregate_function("AggregateFunction", function, arguments),
+            TypeDesc::SimpleAggregateFunction { function, ty } => format_aggregate_function(
+                "SimpleAggregateFunction",
+                function,
+                std::slice::from_ref(ty.as_ref()),
+            ),
+            TypeDesc::Interval(kind) => format!("Interval{}", kind.as_str()),
+            TypeDesc::Point => "Point".into(),
+            TypeDesc::Ring => "Ring".into(),
+            TypeDesc::LineString => "LineString".into(),
+            TypeDesc::MultiLineString => "MultiLineString".into(),
+            TypeDesc::Polygon => "Polygon".into(),
+            TypeDesc::MultiPolygon => "MultiPolygon".into(),
             TypeDesc::Decimal {
                 precision, scale, ..
             } => format!("Decimal({precision}, {scale})"),
@@ -248,6 +371,96 @@ impl TypeDesc {
             ),
         }
     }
+
+    /// Returns the timezone parameter of a `DateTime`/`DateTime64` type.
+    ///
+    /// `Nullable` and `LowCardinality` wrappers are looked through, so the
+    /// timezone of a column declared as `Nullable(DateTime('UTC'))` is
+    /// reported as well. Returns `None` for other types and for temporal
+    /// types without an explicit timezone (server default).
+    #[must_use]
+    pub fn timezone(&self) -> Option<&str> {
+        match self {
+            TypeDesc::DateTime { timezone } | TypeDesc::DateTime64 { timezone, .. } => {
+                timezone.as_deref()
+            }
+            TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => inner.timezone(),
+            _ => None,
+        }
+    }
+
+    /// Returns the storage type of a geo type, or `None` for other types.
+    ///
+    /// Geo types are aliases over tuples and arrays of `Float64` and are
+    /// encoded exactly like their storage type.
+    #[must_use]
+    pub fn geo_storage(&self) -> Option<&'static TypeDesc> {
+        match self {
+            TypeDesc::Point => Some(&POINT_STORAGE),
+            TypeDesc::Ring | TypeDesc::LineString => Some(&POINTS_STORAGE),
+            TypeDesc::MultiLineString => Some(&LINE_STRINGS_STORAGE),
+            TypeDesc::Polygon => Some(&RINGS_STORAGE),
+            TypeDesc::MultiPolygon => Some(&POLYGONS_STORAGE),
+            _ => None,
+        }
+    }
+
+    /// Returns the declared length of a `FixedString(N)` type.
+    ///
+    /// `Nullable` and `LowCardinality` wrappers are looked through. Returns
+    /// `None` for other types.
+    #[must_use]
+    pub fn fixed_string_length(&self) -> Option<usize> {
+        match self {
+            TypeDesc::FixedString { length } => Some(*length),
+            TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => {
+                inner.fixed_string_length()
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolves an `Enum8`/`Enum16` discriminant to its declared name.
+    ///
+    /// `Nullable` and `LowCardinality` wrappers are looked through. Returns
+    /// `None` for non-enum types or when the discriminant is not declared.
+    #[must_use]
+    pub fn enum_name(&self, discriminant: i16) -> Option<&str> {
+        match self {
+            TypeDesc::Enum8(values) => values
+                .iter()
+                .find(|(_, value)| i16::from(*value) == discriminant)
+                .map(|(name, _)| name.as_str()),
+            TypeDesc::Enum16(values) => values
+                .iter()
+                .find(|(_, value)| *value == discriminant)
+                .map(|(name, _)| name.as_str()),
+            TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => {
+                inner.enum_name(discriminant)
+            }
+            _ => None,
+        }
+    }
+
+    /// Resolves an `Enum8`/`Enum16` name to its declared discriminant.
+    ///
+    /// `Nullable` and `LowCardinality` wrappers are looked through. Returns
+    /// `None` for non-enum types or when the name is not declared.
+    #[must_use]
+    pub fn enum_value(&self, name: &str) -> Option<i16> {
+        match self {
+            TypeDesc::Enum8(values) => values
+                .iter()
+                .find(|(candidate, _)| candidate == name)
+                .map(|(_, value)| i16::from(*value)),
+            TypeDesc::Enum16(values) => values
+                .iter()
+                .find(|(candidate, _)| candidate == name)
+                .map(|(_, value)| *value),
+            TypeDesc::Nullable(inner) | TypeDesc::LowCardinality(inner) => inner.enum_value(name),
+            _ => None,
+        }
+    }
 }
 
 impl fmt::Display for TypeDesc {
@@ -258,12 +471,61 @@ impl fmt::Display for TypeDesc {
 
 /// Parses a textual `ClickHouse` type into a structured descriptor.
 ///
+/// Types nested deeper than [`DEFAULT_MAX_TYPE_DEPTH`] are rejected; use
+/// [`parse_type_desc_with_max_depth`] to choose another limit.
+///
 /// # Errors
 ///
 /// Returns [`Error::InvalidValue`] when the descriptor is malformed or
 /// [`Error::UnsupportedType`] for unsupported types.
-#[allow(clippy::too_many_lines)]
 pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
+    parse_type_desc_with_max_depth(input, DEFAULT_MAX_TYPE_DEPTH)
+}
+
+/// Parses a textual `ClickHouse` type, rejecting nesting deeper than
+/// `max_depth`.
+///
+/// The depth counts nested parentheses, so `Array(Array(UInt8))` has depth
+/// 2. The limit is checked before parsing so hostile inputs cannot exhaust
+/// the stack.
+///
+/// # Errors
+///
+/// Returns [`Error::InvalidValue`] when the descriptor is malformed or too
+/// deeply nested, or [`Error::UnsupportedType`] for unsupported types.
+pub fn parse_type_desc_with_max_depth(input: &str, max_depth: usize) -> Result<TypeDesc> {
+    if type_nesting_depth(input) > max_depth {
+        return Err(Error::InvalidValue("type nesting depth limit exceeded"));
+    }
+    parse_type_desc_unchecked(input)
+}
+
+fn type_nesting_depth(input: &str) -> usize {
+    let mut depth = 0_usize;
+    let mut max_depth = 0_usize;
+    let mut in_quote = false;
+    let mut escape = false;
+    for ch in input.chars() {
+        if escape {
+            escape = false;
+            continue;
+        }
+        match ch {
+            '\\' if in_quote => escape = true,
+            '\'' => in_quote = !in_quote,
+            '(' if !in_quote => {
+                depth += 1;
+                max_depth = max_depth.max(depth);
+            }
+            ')' if !in_quote => depth = depth.saturating_sub(1),
+            _ => {}
+        }
+    }
+    max_depth
+}
+
+#[allow(clippy::too_many_lines)]
+fn parse_type_desc_unchecked(input: &str) -> Result<TypeDesc> {
     let trimmed = input.trim();
     match trimmed {
         "Nothing" => Ok(TypeDesc::Nothing),
@@ -291,6 +553,23 @@ pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
         "UUID" => Ok(TypeDesc::Uuid),
         "IPv4" => Ok(TypeDesc::Ipv4),
         "IPv6" => Ok(TypeDesc::Ipv6),
+        "IntervalNanosecond" => Ok(TypeDesc::Interval(IntervalKind::Nanosecond)),
+        "IntervalMicrosecond" => Ok(TypeDesc::Interval(IntervalKind::Microsecond)),
+        "IntervalMillisecond" => Ok(TypeDesc::Interval(IntervalKind::Millisecond)),
+        "IntervalSecond" => Ok(TypeDesc::Interval(IntervalKind::Second)),
+        "IntervalMinute" => Ok(TypeDesc::Interval(IntervalKind::Minute)),
+        "IntervalHour" => Ok(TypeDesc::Interval(IntervalKind::Hour)),
+        "IntervalDay" => Ok(TypeDesc::Interval(IntervalKind::Day)),
+        "IntervalWeek" => Ok(TypeDesc::Interval(IntervalKind::Week)),
+        "IntervalMonth" => Ok(TypeDesc::Interval(IntervalKind::Month)),
+        "IntervalQuarter" => Ok(TypeDesc::Interval(IntervalKind::Quarter)),
+        "IntervalYear" => Ok(TypeDesc::Interval(IntervalKind::Year)),
+        "Point" => Ok(TypeDesc::Point),
+        "Ring" => Ok(TypeDesc::Ring),
+        "LineString" => Ok(TypeDesc::LineString),
+        "MultiLineString" => Ok(TypeDesc::MultiLineString),
+        "Polygon" => Ok(TypeDesc::Polygon),
+        "MultiPolygon" => Ok(TypeDesc::MultiPolygon),
         "Dynamic" => Ok(TypeDesc::Dynamic { max_types: None }),
         "JSON" => Ok(TypeDesc::Json {
             max_dynamic_paths: JSON_DEFAULT_MAX_DYNAMIC_PATHS,
@@ -306,6 +585,29 @@ pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
                     .ok_or(Error::InvalidValue("unterminated JSON type"))?;
                 return parse_json_descriptor(inner);
             }
+            if let Some(inner) = trimmed.strip_prefix("AggregateFunction(") {
+                let inner = inner
+                    .strip_suffix(')')
+                    .ok_or(Error::InvalidValue("unterminated AggregateFunction type"))?;
+                let (function, arguments) = parse_aggregate_function_descriptor(inner)?;
+                return Ok(TypeDesc::AggregateFunction {
+                    function,
+                    arguments,
+                });
+            }
+            if let Some(inner) = trimmed.strip_prefix("SimpleAggregateFunction(") {
+                let inner = inner.strip_suffix(')').ok_or(Error::InvalidValue(
+                    "unterminated SimpleAggregateFunction type",
+                ))?;
+                let (function, arguments) = parse_aggregate_function_descriptor(inner)?;
+                let [ty]: [TypeDesc; 1] = arguments.try_into().map_err(|_| {
+                    Error::InvalidValue("SimpleAggregateFunction expects exactly one type")
+                })?;
+                return Ok(TypeDesc::SimpleAggregateFunction {
+                    function,
+                    ty: Box::new(ty),
+                });
+            }
             if let Some(inner) = trimmed.strip_prefix("Decimal(") {
                 let inner = inner
                     .strip_suffix(')')
@@ -400,7 +702,7 @@ pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
                 let inner = inner
                     .strip_suffix(')')
                     .ok_or(Error::InvalidValue("unterminated LowCardinality type"))?;
-                let desc = parse_type_desc(inner)?;
+                let desc = parse_type_desc_unchecked(inner)?;
                 if matches!(desc, TypeDesc::LowCardinality(_)) {
                     return Err(Error::UnsupportedCombination(
                         "LowCardinality(LowCardinality(T)) is unsupported".into(),
@@ -418,7 +720,7 @@ pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
                 let inner = inner
                     .strip_suffix(')')
                     .ok_or(Error::InvalidValue("unterminated Nullable type"))?;
-                let desc = parse_type_desc(inner)?;
+                let desc = parse_type_desc_unchecked(inner)?;
                 if matches!(desc, TypeDesc::Nullable(_)) {
                     return Err(Error::UnsupportedCombination(
                         "Nullable(Nullable(T)) is unsupported".into(),
@@ -429,13 +731,19 @@ pub fn parse_type_desc(input: &str) -> Result<TypeDesc> {
                         "Nullable(Tuple(...)) is unsupported".into(),
                     ));
                 }
+                if !can_be_inside_nullable(&desc) {
+                    return Err(Error::UnsupportedCombination(format!(
+                        "Nullable({}) is unsupported",
+                        desc.type_name()
+                    )));
+                }
                 return Ok(TypeDesc::Nullable(Box::new(desc)));
             }
             if let Some(inner) = trimmed.strip_prefix("Array(") {
                 let inner = inner
                     .strip_suffix(')')
                     .ok_or(Error::InvalidValue("unterminated Array type"))?;
-                let desc = parse_type_desc(inner)?;
+                let desc = parse_type_desc_unchecked(inner)?;
                 return Ok(TypeDesc::Array(Box::new(desc)));
             }
             if let Some(inner) = trimmed.strip_prefix("Map(") {
@@ -538,9 +846,18 @@ pub(crate) fn can_be_inside_low_cardinality(desc: &TypeDesc) -> bool {
         | TypeDesc::DateTime { .. }
         | TypeDesc::Uuid
         | TypeDesc::Ipv4
-        | TypeDesc::Ipv6 => true,
+        | TypeDesc::Ipv6
+        | TypeDesc::Interval(_) => true,
         TypeDesc::Nullable(inner) => can_be_inside_low_cardinality(inner),
         TypeDesc::LowCardinality(_)
+        | TypeDesc::AggregateFunction { .. }
+        | TypeDesc::SimpleAggregateFunction { .. }
+        | TypeDesc::Point
+        | TypeDesc::Ring
+        | TypeDesc::LineString
+        | TypeDesc::MultiLineString
+        | TypeDesc::Polygon
+        | TypeDesc::MultiPolygon
         | TypeDesc::Array(_)
         | TypeDesc::Map { .. }
         | TypeDesc::Tuple(_)
@@ -560,6 +877,33 @@ pub(crate) fn can_be_inside_low_cardinality(desc: &TypeDesc) -> bool {
     }
 }
 
+/// Reports whether `ClickHouse` allows `Nullable` around the type.
+///
+/// Composite types (arrays, maps, tuples, variants, ...) cannot be wrapped;
+/// their elements are made nullable instead.
+pub(crate) fn can_be_inside_nullable(desc: &TypeDesc) -> bool {
+    !matches!(
+        desc,
+        TypeDesc::Nullable(_)
+            | TypeDesc::LowCardinality(_)
+            | TypeDesc::Array(_)
+            | TypeDesc::Map { .. }
+            | TypeDesc::Tuple(_)
+            | TypeDesc::Nested(_)
+            | TypeDesc::Variant(_)
+            | TypeDesc::Dynamic { .. }
+            | TypeDesc::Json { .. }
+            | TypeDesc::AggregateFunction { .. }
+            | TypeDesc::SimpleAggregateFunction { .. }
+            | TypeDesc::Point
+            | TypeDesc::Ring
+            | TypeDesc::LineString
+            | TypeDesc::MultiLineString
+            | TypeDesc::Polygon
+            | TypeDesc::MultiPolygon
+    )
+}
+
 pub(crate) fn is_valid_map_key(desc: &TypeDesc) -> bool {
     match desc {
         TypeDesc::Nothing | TypeDesc::Nullable(_) => false,
@@ -588,6 +932,9 @@ fn parse_datetime64(input: &str) -> Result<(u8, Option<String>)> {
     let precision: u8 = precision_part
         .parse()
         .map_err(|_| Error::InvalidValue("invalid DateTime64 precision"))?;
+    if precision > DATETIME64_MAX_PRECISION {
+        return Err(Error::InvalidValue("DateTime64 precision must be <= 9"));
+    }
     let timezone = if let Some(tz_part) = parts.next() {
         Some(parse_timezone(tz_part)?)
     } else {
@@ -612,8 +959,8 @@ fn parse_map_descriptor(input: &str) -> Result<(TypeDesc, TypeDesc)> {
     }
     let split = split.ok_or(Error::InvalidValue("Map expects two type arguments"))?;
     let (left, right) = input.split_at(split);
-    let key = parse_type_desc(left.trim())?;
-    let value = parse_type_desc(right[1..].trim())?;
+    let key = parse_type_desc_unchecked(left.trim())?;
+    let value = parse_type_desc_unchecked(right[1..].trim())?;
     Ok((key, value))
 }
 
@@ -625,7 +972,7 @@ fn parse_variant_descriptor(input: &str) -> Result<Vec<TypeDesc>> {
         if trimmed.is_empty() {
             continue;
         }
-        variants.push(parse_type_desc(trimmed)?);
+        variants.push(parse_type_desc_unchecked(trimmed)?);
     }
     canonicalize_variant_types(variants)
 }
@@ -669,6 +1016,39 @@ fn canonicalize_variant_types(variants: Vec<TypeDesc>) -> Result<Vec<TypeDesc>>
     Ok(deduped.into_values().collect())
 }
 
+fn parse_aggregate_function_descriptor(input: &str) -> Result<(String, Vec<TypeDesc>)> {
+    let parts = split_top_level_commas_with_parens(input);
+    let (function, arguments) = parts.split_first().ok_or(Error::InvalidValue(
+        "AggregateFunction expects a function name",
+    ))?;
+    let name_end = function.find('(').unwrap_or(function.len());
+    let name = &function[..name_end];
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+    {
+        return Err(Error::InvalidValue(
+            "invalid AggregateFunction function name",
+        ));
+    }
+    let arguments = arguments
+        .iter()
+        .map(|arg| parse_type_desc_unchecked(arg))
+        .collect::<Result<Vec<_>>>()?;
+    Ok(((*function).to_string(), arguments))
+}
+
+fn format_aggregate_function(prefix: &str, function: &str, arguments: &[TypeDesc]) -> String {
+    let mut out = format!("{prefix}({function}");
+    for argument in arguments {
+        out.push_str(", ");
+        out.push_str(&argument.type_name());
+    }
+    out.push(')');
+    out
+}
+
 fn parse_tuple_descriptor(input: &str) -> Result<Vec<TupleItem>> {
     let items = split_top_level_commas_with_parens(input);
     if items.is_empty() {
@@ -902,12 +1282,12 @@ fn parse_tuple_item(input: &str) -> Result<TupleItem> {
     if let Some((name, ty)) = split_name_and_type(trimmed)? {
         return Ok(TupleItem {
             name: Some(parse_identifier(name)?),
-            ty: parse_type_desc(ty)?,
+            ty: parse_type_desc_unchecked(ty)?,
         });
     }
     Ok(TupleItem {
         name: None,
-        ty: parse_type_desc(trimmed)?,
+        ty: parse_type_desc_unchecked(trimmed)?,
     })
 }
 
@@ -1068,7 +1448,7 @@ fn parse_json_descriptor(input: &str) -> Result<TypeDesc> {
         }
         if let Some((name, ty)) = split_name_and_type(trimmed)? {
             let name = parse_json_path(name)?;
-            let ty = parse_type_desc(ty)?;
+            let ty = parse_type_desc_unchecked(ty)?;
             typed_paths.push((name, ty));
             continue;
         }
@@ -1228,6 +1608,219 @@ mod tests {
         );
     }
 
+    #[test]
+    fn parses_decimal_types() {
+        let cases = [
+            ("Decimal(9, 2)", 9, 2, DecimalSize::Bits32),
+            ("Decimal(18, 4)", 18, 4, DecimalSize::Bits64),
+            ("Decimal(38, 10)", 38, 10, DecimalSize::Bits128),
+            ("Decimal(76, 0)", 76, 0, DecimalSize::Bits256),
+        ];
+        for (input, precision, scale, size) in cases {
+            assert_eq!(
+                parse_type_desc(input).unwrap(),
+                TypeDesc::Decimal {
+                    precision,
+                    scale,
+                    size
+                }
+            );
+        }
+        assert_eq!(
+            parse_type_desc("Decimal64(3)").unwrap(),
+            TypeDesc::Decimal64 { scale: 3 }
+        );
+
+        let err = parse_type_desc("Decimal(4, 5)").unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+        let err = parse_type_desc("Decimal(77, 2)").unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+        let err = parse_type_desc("Decimal32(10)").unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+    }
+
+    #[test]
+    fn parses_datetime64_precision_and_timezone() {
+        assert_eq!(
+            parse_type_desc("DateTime64(3)").unwrap(),
+            TypeDesc::DateTime64 {
+                precision: 3,
+                timezone: None
+            }
+        );
+        assert_eq!(
+            parse_type_desc("DateTime64(6, 'UTC')").unwrap(),
+            TypeDesc::DateTime64 {
+                precision: 6,
+                timezone: Some("UTC".to_string())
+            }
+        );
+        assert_eq!(
+            parse_type_desc("DateTime64(9,'Asia/Tokyo')").unwrap(),
+            TypeDesc::DateTime64 {
+                precision: 9,
+                timezone: Some("Asia/Tokyo".to_string())
+            }
+        );
+
+        let err = parse_type_desc("DateTime64(10)").unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+        let err = parse_type_desc("DateTime64(3, UTC)").unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+    }
+
+    #[test]
+    fn exposes_datetime_timezone() {
+        let ty = parse_type_desc("DateTime('Europe/Berlin')").unwrap();
+        assert_eq!(
+            ty,
+            TypeDesc::DateTime {
+                timezone: Some("Europe/Berlin".to_string())
+            }
+        );
+        assert_eq!(ty.timezone(), Some("Europe/Berlin"));
+
+        let ty = parse_type_desc("Nullable(DateTime64(3, 'UTC'))").unwrap();
+        assert_eq!(ty.timezone(), Some("UTC"));
+        let ty = parse_type_desc("LowCardinality(Nullable(DateTime('Asia/Tokyo')))").unwrap();
+        assert_eq!(ty.timezone(), Some("Asia/Tokyo"));
+
+        assert_eq!(parse_type_desc("DateTime").unwrap().timezone(), None);
+        assert_eq!(parse_type_desc("String").unwrap().timezone(), None);
+    }
+
+    #[test]
+    fn exposes_fixed_string_length() {
+        let ty = parse_type_desc("FixedString(16)").unwrap();
+        assert_eq!(ty, TypeDesc::FixedString { length: 16 });
+        assert_eq!(ty.fixed_string_length(), Some(16));
+        let ty = parse_type_desc("LowCardinality(Nullable(FixedString(4)))").unwrap();
+        assert_eq!(ty.fixed_string_length(), Some(4));
+        assert_eq!(
+            parse_type_desc("String").unwrap().fixed_string_length(),
+            None
+        );
+    }
+
+    #[test]
+    fn resolves_enum_names_and_values() {
+        let ty = parse_type_desc(r"Enum8('a' = 1, 'it\'s' = -2)").unwrap();
+        assert_eq!(
+            ty,
+            TypeDesc::Enum8(vec![("a".to_string(), 1), ("it's".to_string(), -2)])
+        );
+        assert_eq!(ty.enum_name(-2), Some("it's"));
+        assert_eq!(ty.enum_value("a"), Some(1));
+        assert_eq!(ty.enum_name(3), None);
+        assert_eq!(ty.enum_value("b"), None);
+
+        let ty = parse_type_desc("Nullable(Enum16('x' = 1000))").unwrap();
+        assert_eq!(ty.enum_name(1000), Some("x"));
+        assert_eq!(ty.enum_value("x"), Some(1000));
+        assert_eq!(parse_type_desc("Int8").unwrap().enum_name(1), None);
+    }
+
+    #[test]
+    fn parses_aggregate_function_types() {
+        let ty = parse_type_desc("AggregateFunction(uniq, UInt64)").unwrap();
+        assert_eq!(
+            ty,
+            TypeDesc::AggregateFunction {
+                function: "uniq".to_string(),
+                arguments: vec![TypeDesc::UInt64],
+            }
+        );
+        assert_eq!(ty.type_name(), "AggregateFunction(uniq, UInt64)");
+
+        let ty = parse_type_desc("AggregateFunction(quantiles(0.5, 0.9), Float64)").unwrap();
+        assert_eq!(
+            ty,
+            TypeDesc::AggregateFunction {
+                function: "quantiles(0.5, 0.9)".to_string(),
+                arguments: vec![TypeDesc::Float64],
+            }
+        );
+        assert_eq!(
+            ty.type_name(),
+            "AggregateFunction(quantiles(0.5, 0.9), Float64)"
+        );
+
+        let ty = parse_type_desc("AggregateFunction(count)").unwrap();
+        assert_eq!(ty.type_name(), "AggregateFunction(count)");
+        assert!(parse_type_desc("AggregateFunction()").is_err());
+    }
+
+    #[test]
+    fn parses_simple_aggregate_function_types() {
+        let ty = parse_type_desc("SimpleAggregateFunction(sum, UInt64)").unwrap();
+        assert_eq!(
+            ty,
+            TypeDesc::SimpleAggregateFunction {
+                function: "sum".to_string(),
+                ty: Box::new(TypeDesc::UInt64),
+            }
+        );
+        assert_eq!(ty.type_name(), "SimpleAggregateFunction(sum, UInt64)");
+        assert!(parse_type_desc("SimpleAggregateFunction(sum)").is_err());
+        assert!(parse_type_desc("SimpleAggregateFunction(sum, UInt64, UInt8)").is_err());
+    }
+
+    #[test]
+    fn parses_interval_types() {
+        let ty = parse_type_desc("IntervalSecond").unwrap();
+        assert_eq!(ty, TypeDesc::Interval(IntervalKind::Second));
+        assert_eq!(ty.type_name(), "IntervalSecond");
+        assert_eq!(
+            parse_type_desc("Nullable(IntervalMonth)").unwrap(),
+            TypeDesc::Nullable(Box::new(TypeDesc::Interval(IntervalKind::Month)))
+        );
+        assert!(parse_type_desc("IntervalFortnight").is_err());
+    }
+
+    #[test]
+    fn rejects_nullable_composites() {
+        for ty in [
+            "Nullable(Array(String))",
+            "Nullable(Map(String, UInt64))",
+            "Nullable(LowCardinality(String))",
+            "Nullable(Variant(String, UInt8))",
+            "Nullable(Point)",
+        ] {
+            let err = parse_type_desc(ty).unwrap_err();
+            assert!(matches!(err, Error::UnsupportedCombination(_)), "{ty}");
+        }
+        for ty in [
+            "Array(Nullable(String))",
+            "Map(String, Nullable(UInt64))",
+            "Tuple(Nullable(UInt8), String)",
+            "Nullable(FixedString(4))",
+        ] {
+            assert_eq!(parse_type_desc(ty).unwrap().type_name(), ty);
+        }
+    }
+
+    #[test]
+    fn parses_deeply_nested_composites() {
+        let ty =
+            parse_type_desc("Array(Array(Map(String, Tuple(UInt8, Nullable(String)))))").unwrap();
+        assert_eq!(
+            ty.type_name(),
+            "Array(Array(Map(String, Tuple(UInt8, Nullable(String)))))"
+        );
+    }
+
+    #[test]
+    fn enforces_type_nesting_depth() {
+        let nested = |depth: usize| format!("{}UInt8{}", "Array(".repeat(depth), ")".repeat(depth));
+        assert!(parse_type_desc(&nested(DEFAULT_MAX_TYPE_DEPTH)).is_ok());
+        let err = parse_type_desc(&nested(DEFAULT_MAX_TYPE_DEPTH + 1)).unwrap_err();
+        assert!(matches!(err, Error::InvalidValue(_)));
+        assert!(parse_type_desc_with_max_depth(&nested(3), 2).is_err());
+        assert!(parse_type_desc_with_max_depth(&nested(2), 2).is_ok());
+        assert!(parse_type_desc("Enum8('((((' = 1)").is_ok());
+        assert!(parse_type_desc(&nested(100_000)).is_err());
+    }
+
     #[test]
     fn parses_float16_bfloat16_nothing() {
         assert_eq!(parse_type_desc("Float16").unwrap(), TypeDesc::Float16);
@@ -1,10 +1,11 @@
 //! `RowBinary` value representation.
 
-use std::net::{Ipv4Addr, Ipv6Addr};
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
 
+use half::{bf16, f16};
 use uuid::Uuid;
 
-use crate::types::TypeDesc;
+use crate::{error::Error, types::TypeDesc};
 
 /// Runtime value used for `RowBinary` read/write APIs.
 #[derive(Clone, Debug, PartialEq)]
@@ -103,6 +104,8 @@ pub enum Value {
     },
     /// Dynamic NULL (encoded as `Nothing` with no payload).
     DynamicNull,
+    /// Serialized aggregate function state, kept as opaque bytes.
+    AggregateState(Vec<u8>),
 }
 
 impl Value {
@@ -150,6 +153,53 @@ impl Value {
             Value::Variant { .. } | Value::VariantNull => "Variant",
             Value::JsonObject(_) => "JSON",
             Value::Dynamic { .. } | Value::DynamicNull => "Dynamic",
+            Value::AggregateState(_) => "AggregateFunction",
+        }
+    }
+}
+
+/// Decoded value borrowing string data from an in-memory payload.
+///
+/// Produced by [`crate::RowBinaryRefReader`]. Strings are slices of the
+/// payload; containers mirror their [`Value`] counterparts and every other
+/// type is decoded as an owned [`Value`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValueRef<'a> {
+    /// String bytes borrowed from the payload.
+    String(&'a [u8]),
+    /// Fixed-length bytes borrowed from the payload.
+    FixedString(&'a [u8]),
+    /// Nullable wrapper around another value.
+    Nullable(Option<Box<ValueRef<'a>>>),
+    /// Array of nested values.
+    Array(Vec<ValueRef<'a>>),
+    /// Map represented as key/value pairs.
+    Map(Vec<(ValueRef<'a>, ValueRef<'a>)>),
+    /// Tuple represented as ordered values.
+    Tuple(Vec<ValueRef<'a>>),
+    /// Any other value, decoded as owned.
+    Owned(Value),
+}
+
+impl ValueRef<'_> {
+    /// Copies the borrowed data into an owned [`Value`].
+    #[must_use]
+    pub fn to_value(&self) -> Value {
+        match self {
+            ValueRef::String(bytes) => Value::String(bytes.to_vec()),
+            ValueRef::FixedString(bytes) => Value::FixedString(bytes.to_vec()),
+            ValueRef::Nullable(inner) => {
+                Value::Nullable(inner.as_ref().map(|value| Box::new(value.to_value())))
+            }
+            ValueRef::Array(items) => Value::Array(items.iter().map(ValueRef::to_value).collect()),
+            ValueRef::Map(entries) => Value::Map(
+                entries
+                    .iter()
+                    .map(|(key, value)| (key.to_value(), value.to_value()))
+                    .collect(),
+            ),
+            ValueRef::Tuple(items) => Value::Tuple(items.iter().map(ValueRef::to_value).collect()),
+            ValueRef::Owned(value) => value.clone(),
         }
     }
 }
@@ -214,6 +264,18 @@ impl From<f64> for Value {
     }
 }
 
+impl From<f16> for Value {
+    fn from(value: f16) -> Self {
+        Value::Float16(value.to_f32())
+    }
+}
+
+impl From<bf16> for Value {
+    fn from(value: bf16) -> Self {
+        Value::BFloat16(value.to_f32())
+    }
+}
+
 impl From<String> for Value {
     fn from(value: String) -> Self {
         Value::String(value.into_bytes())
@@ -232,13 +294,170 @@ impl From<Vec<u8>> for Value {
     }
 }
 
+impl From<Ipv4Addr> for Value {
+    fn from(value: Ipv4Addr) -> Self {
+        Value::Ipv4(value)
+    }
+}
+
+impl From<Ipv6Addr> for Value {
+    fn from(value: Ipv6Addr) -> Self {
+        Value::Ipv6(value)
+    }
+}
+
+impl TryFrom<Value> for IpAddr {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Ipv4(addr) => Ok(IpAddr::V4(addr)),
+            Value::Ipv6(addr) => Ok(IpAddr::V6(addr)),
+            other => Err(Error::TypeMismatch {
+                expected: "IPv4 or IPv6".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for f16 {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Float16(value) => Ok(f16::from_f32(value)),
+            other => Err(Error::TypeMismatch {
+                expected: "Float16".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for bf16 {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::BFloat16(value) => Ok(bf16::from_f32(value)),
+            other => Err(Error::TypeMismatch {
+                expected: "BFloat16".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl From<Uuid> for Value {
+    fn from(value: Uuid) -> Self {
+        Value::Uuid(value)
+    }
+}
+
+impl TryFrom<Value> for Uuid {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Uuid(uuid) => Ok(uuid),
+            other => Err(Error::TypeMismatch {
+                expected: "UUID".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl From<IpAddr> for Value {
+    fn from(value: IpAddr) -> Self {
+        match value {
+            IpAddr::V4(addr) => Value::Ipv4(addr),
+            IpAddr::V6(addr) => Value::Ipv6(addr),
+        }
+    }
+}
+
+impl TryFrom<Value> for Ipv4Addr {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Ipv4(addr) => Ok(addr),
+            other => Err(Error::TypeMismatch {
+                expected: "IPv4".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
+impl TryFrom<Value> for Ipv6Addr {
+    type Error = Error;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Ipv6(addr) => Ok(addr),
+            other => Err(Error::TypeMismatch {
+                expected: "IPv6".to_string(),
+                actual: other.type_name().to_string(),
+            }),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::Value;
-    use std::mem::size_of;
+    use half::{bf16, f16};
+    use std::{
+        mem::size_of,
+        net::{IpAddr, Ipv4Addr, Ipv6Addr},
+    };
+    use uuid::Uuid;
 
     #[test]
     fn value_size_is_stable() {
         assert_eq!(size_of::<Value>(), 48);
     }
+
+    #[test]
+    fn converts_ip_addresses() {
+        let v4 = Ipv4Addr::new(192, 168, 1, 10);
+        assert_eq!(Value::from(v4), Value::Ipv4(v4));
+        assert_eq!(Ipv4Addr::try_from(Value::Ipv4(v4)).unwrap(), v4);
+        assert!(Ipv4Addr::try_from(Value::UInt32(1)).is_err());
+
+        let v6 = Ipv6Addr::LOCALHOST;
+        assert_eq!(Value::from(v6), Value::Ipv6(v6));
+        assert_eq!(Ipv6Addr::try_from(Value::Ipv6(v6)).unwrap(), v6);
+        assert!(Ipv6Addr::try_from(Value::Ipv4(v4)).is_err());
+
+        assert_eq!(Value::from(IpAddr::V4(v4)), Value::Ipv4(v4));
+        assert_eq!(Value::from(IpAddr::V6(v6)), Value::Ipv6(v6));
+        assert_eq!(IpAddr::try_from(Value::Ipv4(v4)).unwrap(), IpAddr::V4(v4));
+        assert_eq!(IpAddr::try_from(Value::Ipv6(v6)).unwrap(), IpAddr::V6(v6));
+        assert!(IpAddr::try_from(Value::String(b"::1".to_vec())).is_err());
+    }
+
+    #[test]
+    fn converts_uuids() {
+        let uuid = Uuid::from_u128(0x0123_4567_89ab_cdef_fedc_ba98_7654_3210);
+        assert_eq!(Value::from(uuid), Value::Uuid(uuid));
+        assert_eq!(Uuid::try_from(Value::Uuid(uuid)).unwrap(), uuid);
+        assert!(Uuid::try_from(Value::UInt128(1)).is_err());
+    }
+
+    #[test]
+    fn converts_half_floats() {
+        let brain = bf16::from_f32(1.5);
+        assert_eq!(Value::from(brain), Value::BFloat16(1.5));
+        assert_eq!(bf16::try_from(Value::BFloat16(1.5)).unwrap(), brain);
+        assert!(bf16::try_from(Value::Float16(1.5)).is_err());
+
+        let half = f16::from_f32(-0.25);
+        assert_eq!(Value::from(half), Value::Float16(-0.25));
+        assert_eq!(f16::try_from(Value::Float16(-0.25)).unwrap(), half);
+        assert!(f16::try_from(Value::Float32(-0.25)).is_err());
+    }
 }
@@ -0,0 +1,106 @@
+//! `Values` format support.
+//!
+//! Rows are rendered as SQL tuple literals separated by commas, the text
+//! that follows `VALUES` in an `INSERT` statement:
+//!
+//! ```
+//! # use clickhouse_rowbinary::{Schema, Value, ValuesWriter};
+//! let schema = Schema::from_type_strings(&[
+//!     ("id", "UInt8"),
+//!     ("name", "Nullable(String)"),
+//!     ("day", "Date"),
+//!     ("tags", "Array(String)"),
+//! ])?;
+//! let mut writer = ValuesWriter::new(b"INSERT INTO t VALUES ".to_vec(), schema);
+//! writer.write_rows([
+//!     vec![
+//!         Value::UInt8(1),
+//!         Value::Nullable(Some(Box::new(Value::String(b"it's".to_vec())))),
+//!         Value::Date(19_723),
+//!         Value::Array(vec![Value::String(b"a".to_vec())]),
+//!     ],
+//!     vec![
+//!         Value::UInt8(2),
+//!         Value::Nullable(None),
+//!         Value::Date(0),
+//!         Value::Array(Vec::new()),
+//!     ],
+//! ])?;
+//! assert_eq!(
+//!     writer.into_inner(),
+//!     b"INSERT INTO t VALUES (1,'it\\'s','2024-01-01',['a']),(2,NULL,'1970-01-01',[])"
+//! );
+//! # Ok::<(), clickhouse_rowbinary::Error>(())
+//! ```
+//!
+//! Strings are single-quoted with backslash escapes, numbers and
+//! date-times are bare, and all other scalars such as dates, UUIDs and
+//! enums are quoted text. Date-times are rendered as Unix timestamps, such
+//! as `1700000000.123`, so the server stores the same instant whatever the
+//! column timezone.
+
+use std::io::Write;
+
+use crate::{
+    error::{Error, Result},
+    row_writer::{RowOutput, impl_row_writer},
+    rowbinary::Schema,
+    text::literal::write_literal,
+    value::Value,
+};
+
+/// Writer that encodes rows as `Values` tuples.
+pub struct ValuesWriter<W: Write> {
+    output: RowOutput<W>,
+    schema: Schema,
+    started: bool,
+}
+
+impl<W: Write> ValuesWriter<W> {
+    /// Creates a writer for rows of `schema`.
+    #[must_use]
+    pub fn new(inner: W, schema: Schema) -> Self {
+        Self {
+            output: RowOutput::new(inner),
+            schema,
+            started: false,
+        }
+    }
+
+    /// Returns the schema rows are written with.
+    #[must_use]
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    /// Encodes and writes one row as a tuple, preceded by a comma unless it
+    /// is the first.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`crate::error::Error`] when the row length or a value does
+    /// not match the schema, a column type is not supported, or IO fails.
+    pub fn write_row(&mut self, row: &[Value]) -> Result<()> {
+        if row.len() != self.schema.len() {
+            return Err(Error::InvalidValue("row length does not match schema"));
+        }
+        self.output.write(|out| {
+            if self.started {
+                out.push(b',');
+            }
+            out.push(b'(');
+            for (index, (field, value)) in self.schema.fields().iter().zip(row).enumerate() {
+                if index > 0 {
+                    out.push(b',');
+                }
+                write_literal(&field.ty, value, out)?;
+            }
+            out.push(b')');
+            Ok(())
+        })?;
+        self.started = true;
+        Ok(())
+    }
+}
+
+impl_row_writer!(ValuesWriter);
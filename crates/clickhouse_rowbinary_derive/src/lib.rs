@@ -0,0 +1,210 @@
+#![deny(missing_docs)]
+//! Derive macro for `clickhouse_rowbinary` typed rows.
+//!
+//! Use it through the `derive` feature of `clickhouse_rowbinary`, which
+//! re-exports [`macro@ClickhouseRow`].
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, LitStr, Type, parse_macro_input, spanned::Spanned};
+
+/// Derives `ClickhouseRow`, `ToRow` and `FromRow` for a struct with named
+/// fields.
+///
+/// Each field maps onto the column of the same name. Field attributes
+/// override the defaults:
+///
+/// - `#[clickhouse(rename = "column")]` sets the column name.
+/// - `#[clickhouse(type = "DateTime64(3)")]` sets the column type used by
+///   `ClickhouseRow::schema`; without it the type comes from the field's
+///   `ColumnType` implementation.
+/// - `#[clickhouse(default)]` lets the column be missing from the schema:
+///   `FromRow` fills the field with `Default::default()` and `ToRow` skips it.
+///   On the struct, it applies to every field.
+///
+/// Columns without a matching field are ignored when decoding, so together
+/// with `default` one struct can read several versions of a table.
+///
+/// Field values are converted through `IntoValue` and `FromValue`, so every
+/// field type must implement both.
+#[proc_macro_derive(ClickhouseRow, attributes(clickhouse))]
+pub fn derive_clickhouse_row(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Field of the derived struct and the column it maps onto.
+struct Column {
+    ident: syn::Ident,
+    ty: Type,
+    name: String,
+    declared_type: Option<LitStr>,
+    /// Whether the column may be missing from the schema.
+    default: bool,
+}
+
+/// Parses the struct-level `#[clickhouse(default)]` attribute.
+fn container_default(input: &DeriveInput) -> syn::Result<bool> {
+    let mut default = false;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("clickhouse"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("default") {
+                default = true;
+                Ok(())
+            } else {
+                Err(meta.error("expected `default`"))
+            }
+        })?;
+    }
+    Ok(default)
+}
+
+fn columns(input: &DeriveInput) -> syn::Result<Vec<Column>> {
+    let Data::Struct(data) = &input.data else {
+        return Err(syn::Error::new(
+            input.span(),
+            "ClickhouseRow can only be derived for structs",
+        ));
+    };
+    let Fields::Named(fields) = &data.fields else {
+        return Err(syn::Error::new(
+            data.fields.span(),
+            "ClickhouseRow requires a struct with named fields",
+        ));
+    };
+    let all_default = container_default(input)?;
+    let mut columns: Vec<Column> = Vec::with_capacity(fields.named.len());
+    for field in &fields.named {
+        let ident = field
+            .ident
+            .clone()
+            .ok_or_else(|| syn::Error::new(field.span(), "expected a named field"))?;
+        let mut name = ident.to_string().trim_start_matches("r#").to_string();
+        let mut declared_type = None;
+        let mut default = all_default;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("clickhouse"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("rename") {
+                    name = meta.value()?.parse::<LitStr>()?.value();
+                    Ok(())
+                } else if meta.path.is_ident("type") {
+                    declared_type = Some(meta.value()?.parse::<LitStr>()?);
+                    Ok(())
+                } else if meta.path.is_ident("default") {
+                    default = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `rename`, `type` or `default`"))
+                }
+            })?;
+        }
+        if columns.iter().any(|column| column.name == name) {
+            return Err(syn::Error::new(
+                field.span(),
+                format!("duplicate column name `{name}`"),
+            ));
+        }
+        columns.push(Column {
+            ident,
+            ty: field.ty.clone(),
+            name,
+            declared_type,
+            default,
+        });
+    }
+    Ok(columns)
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let columns = columns(input)?;
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    let krate = quote!(::clickhouse_rowbinary);
+
+    let names: Vec<&str> = columns.iter().map(|column| column.name.as_str()).collect();
+    let required: Vec<&str> = columns
+        .iter()
+        .filter(|column| !column.default)
+        .map(|column| column.name.as_str())
+        .collect();
+    let fields: Vec<&syn::Ident> = columns.iter().map(|column| &column.ident).collect();
+    let locals: Vec<syn::Ident> = (0..columns.len())
+        .map(|index| format_ident!("__field{index}"))
+        .collect();
+    let fallbacks = columns.iter().map(|column| {
+        let name = &column.name;
+        if column.default {
+            quote!(.unwrap_or_default())
+        } else {
+            quote!(.ok_or_else(|| #krate::__private::missing_column(#name))?)
+        }
+    });
+    let types = columns.iter().map(|column| {
+        let ty = &column.ty;
+        column.declared_type.as_ref().map_or_else(
+            || quote!(<#ty as #krate::ColumnType>::column_type()),
+            |declared| quote!(#krate::parse_type_desc(#declared)?),
+        )
+    });
+
+    Ok(quote! {
+        impl #impl_generics #krate::ClickhouseRow for #ident #ty_generics #where_clause {
+            fn schema() -> #krate::Result<#krate::Schema> {
+                ::core::result::Result::Ok(#krate::Schema::new(::std::vec![
+                    #(#krate::Field {
+                        name: ::std::string::String::from(#names),
+                        ty: #types,
+                    }),*
+                ]))
+            }
+        }
+
+        impl #impl_generics #krate::ToRow for #ident #ty_generics #where_clause {
+            fn to_row(&self, schema: &#krate::Schema) -> #krate::Result<#krate::Row> {
+                #krate::__private::check_fields(schema, &[#(#required),*])?;
+                schema
+                    .fields()
+                    .iter()
+                    .map(|field| match field.name.as_str() {
+                        #(#names => #krate::__private::encode_field(field, &self.#fields),)*
+                        _ => ::core::result::Result::Err(#krate::__private::unknown_column(field)),
+                    })
+                    .collect()
+            }
+        }
+
+        impl #impl_generics #krate::FromRow for #ident #ty_generics #where_clause {
+            fn from_row(
+                schema: &#krate::Schema,
+                row: &[#krate::Value],
+            ) -> #krate::Result<Self> {
+                #krate::__private::check_row(schema, row)?;
+                #(let mut #locals = ::core::option::Option::None;)*
+                for (field, value) in schema.fields().iter().zip(row) {
+                    match field.name.as_str() {
+                        #(#names => {
+                            #locals = ::core::option::Option::Some(
+                                #krate::__private::decode_field(field, value)?,
+                            );
+                        })*
+                        _ => {}
+                    }
+                }
+                ::core::result::Result::Ok(Self {
+                    #(#fields: #locals #fallbacks,)*
+                })
+            }
+        }
+    })
+}
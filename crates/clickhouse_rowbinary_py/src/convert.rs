@@ -182,7 +182,7 @@ pub fn python_to_value(py: Python<'_>, obj: &Bound<'_, PyAny>, ty: &TypeDesc) ->
             let v: i32 = obj.extract()?;
             Ok(Value::Int32(v))
         }
-        TypeDesc::Int64 => {
+        TypeDesc::Int64 | TypeDesc::Interval(_) => {
             let v: i64 = obj.extract()?;
             Ok(Value::Int64(v))
         }
@@ -428,7 +428,7 @@ pub fn python_to_value(py: Python<'_>, obj: &Bound<'_, PyAny>, ty: &TypeDesc) ->
                 Ok(Value::Nullable(Some(Box::new(inner_value))))
             }
         }
-        TypeDesc::LowCardinality(inner) => {
+        TypeDesc::LowCardinality(inner) | TypeDesc::SimpleAggregateFunction { ty: inner, .. } => {
             // LowCardinality is transparent
             python_to_value(py, obj, inner)
         }
@@ -465,10 +465,21 @@ pub fn python_to_value(py: Python<'_>, obj: &Bound<'_, PyAny>, ty: &TypeDesc) ->
             }
             Ok(Value::Tuple(values))
         }
-        _ => Err(EncodingError::new_err(format!(
-            "Unsupported type for encoding: {}",
-            ty.type_name()
-        ))),
+        TypeDesc::AggregateFunction { .. } => {
+            let state: Vec<u8> = obj.extract().map_err(|_| {
+                ValidationError::new_err("Expected bytes for AggregateFunction state")
+            })?;
+            Ok(Value::AggregateState(state))
+        }
+        _ => {
+            if let Some(storage) = ty.geo_storage() {
+                return python_to_value(py, obj, storage);
+            }
+            Err(EncodingError::new_err(format!(
+                "Unsupported type for encoding: {}",
+                ty.type_name()
+            )))
+        }
     }
 }
 
@@ -493,6 +504,7 @@ pub fn value_to_python(
             let result = int_type.call_method1("from_bytes", (py_bytes, "little"))?;
             Ok(result.unbind())
         }
+        (Value::AggregateState(state), _) => Ok(PyBytes::new(py, state).into_any().unbind()),
         (Value::Int8(v), _) => Ok(v.into_pyobject(py)?.into_any().unbind()),
         (Value::Int16(v), _) => Ok(v.into_pyobject(py)?.into_any().unbind()),
         (Value::Int32(v), _) => Ok(v.into_pyobject(py)?.into_any().unbind()),
@@ -665,9 +677,14 @@ pub fn value_to_python(
         }
         (value, ty) => {
             // Pass through LowCardinality
-            if let TypeDesc::LowCardinality(inner) = ty {
+            if let TypeDesc::LowCardinality(inner)
+            | TypeDesc::SimpleAggregateFunction { ty: inner, .. } = ty
+            {
                 return value_to_python(py, value, inner, string_mode);
             }
+            if let Some(storage) = ty.geo_storage() {
+                return value_to_python(py, value, storage, string_mode);
+            }
             Err(ValidationError::new_err(format!(
                 "Cannot convert value {} for type {}",
                 value.type_name(),
@@ -48,13 +48,24 @@ create_exception!(
 pub fn to_py_err(err: RustError) -> PyErr {
     match &err {
         RustError::UnsupportedType(_) => SchemaError::new_err(err.to_string()),
-        RustError::TypeMismatch { .. } | RustError::InvalidValue(_) => {
-            ValidationError::new_err(err.to_string())
-        }
-        RustError::Io(_) => DecodingError::new_err(err.to_string()),
+        RustError::TypeMismatch { .. }
+        | RustError::InvalidValue(_)
+        | RustError::UnknownColumn(_)
+        | RustError::Serde(_)
+        | RustError::Mapping(_) => ValidationError::new_err(err.to_string()),
+        RustError::Io(_)
+        | RustError::Truncated { .. }
+        | RustError::Corrupt { .. }
+        | RustError::ChecksumMismatch { .. } => DecodingError::new_err(err.to_string()),
         RustError::Overflow(_)
         | RustError::Internal(_)
         | RustError::UnsupportedCombination(_)
-        | RustError::Zstd(_) => ClickHouseRowBinaryError::new_err(err.to_string()),
+        | RustError::Zstd(_)
+        | RustError::Server { .. }
+        | RustError::Http(_)
+        | RustError::Protocol(_)
+        | RustError::Arrow(_)
+        | RustError::Parquet(_)
+        | RustError::Polars(_) => ClickHouseRowBinaryError::new_err(err.to_string()),
     }
 }
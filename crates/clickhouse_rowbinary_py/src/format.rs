@@ -11,6 +11,8 @@ use clickhouse_rowbinary::RowBinaryFormat as RustFormat;
 ///     RowBinaryWithNames: RowBinary with column names header.
 ///     RowBinaryWithNamesAndTypes: RowBinary with column names and types
 /// header.
+///     RowBinaryWithDefaults: RowBinary with a "use default" flag before
+/// each value (no header).
 #[pyclass(eq, eq_int)]
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Format {
@@ -20,6 +22,8 @@ pub enum Format {
     RowBinaryWithNames,
     /// RowBinary with column names and types.
     RowBinaryWithNamesAndTypes,
+    /// RowBinary with a "use default" flag before each value.
+    RowBinaryWithDefaults,
 }
 
 #[pymethods]
@@ -34,6 +38,7 @@ impl Format {
             Format::RowBinary => "RowBinary",
             Format::RowBinaryWithNames => "RowBinaryWithNames",
             Format::RowBinaryWithNamesAndTypes => "RowBinaryWithNamesAndTypes",
+            Format::RowBinaryWithDefaults => "RowBinaryWithDefaults",
         }
     }
 
@@ -49,6 +54,7 @@ impl From<Format> for RustFormat {
             Format::RowBinary => RustFormat::RowBinary,
             Format::RowBinaryWithNames => RustFormat::RowBinaryWithNames,
             Format::RowBinaryWithNamesAndTypes => RustFormat::RowBinaryWithNamesAndTypes,
+            Format::RowBinaryWithDefaults => RustFormat::RowBinaryWithDefaults,
         }
     }
 }
@@ -59,6 +65,7 @@ impl From<RustFormat> for Format {
             RustFormat::RowBinary => Format::RowBinary,
             RustFormat::RowBinaryWithNames => Format::RowBinaryWithNames,
             RustFormat::RowBinaryWithNamesAndTypes => Format::RowBinaryWithNamesAndTypes,
+            RustFormat::RowBinaryWithDefaults => Format::RowBinaryWithDefaults,
         }
     }
 }
@@ -125,6 +125,9 @@ class Format(Enum):
     RowBinaryWithNamesAndTypes = ...
     """RowBinary with column names and types in header. Self-describing format."""
 
+    RowBinaryWithDefaults = ...
+    """RowBinary with a "use default" flag before each value (no header)."""
+
 # Column class
 
 class Column:
@@ -1,7 +1,7 @@
 """Tests for Schema and Column classes."""
 
 import pytest
-from clickhouse_rowbinary import Column, Schema, SchemaError
+from clickhouse_rowbinary import ClickHouseRowBinaryError, Column, Schema, SchemaError
 
 
 class TestColumn:
@@ -28,9 +28,13 @@ class TestColumn:
             Column("bad", "InvalidType")
 
     def test_complex_type(self):
-        col = Column("data", "Nullable(Array(String))")
+        col = Column("data", "Array(Nullable(String))")
         assert col.name == "data"
-        assert col.type_str == "Nullable(Array(String))"
+        assert col.type_str == "Array(Nullable(String))"
+
+    def test_nullable_composite_raises_error(self):
+        with pytest.raises(ClickHouseRowBinaryError):
+            Column("data", "Nullable(Array(String))")
 
 
 class TestSchema:
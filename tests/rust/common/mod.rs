@@ -33,6 +33,35 @@ impl ClickhouseServer {
         Self { client, dsn }
     }
 
+    /// Returns the endpoint URL, including any credentials.
+    #[cfg(any(feature = "http", feature = "async-http"))]
+    #[must_use]
+    pub fn dsn(&self) -> &str {
+        &self.dsn
+    }
+
+    /// Returns the native TCP address on the DSN's host, at the port in
+    /// `CLICKHOUSE_TCP_PORT` or 9000, with options carrying the DSN's
+    /// credentials.
+    #[cfg(feature = "tcp")]
+    #[must_use]
+    pub fn tcp_endpoint(&self) -> (String, clickhouse_rowbinary::tcp::ClientOptions) {
+        let rest = self
+            .dsn
+            .split_once("://")
+            .map_or(&*self.dsn, |(_, rest)| rest);
+        let authority = rest.split('/').next().unwrap_or_default();
+        let (credentials, host) = authority.rsplit_once('@').unwrap_or(("", authority));
+        let host = host.rsplit_once(':').map_or(host, |(host, _)| host);
+        let port = std::env::var("CLICKHOUSE_TCP_PORT").unwrap_or_else(|_| "9000".to_string());
+        let mut options = clickhouse_rowbinary::tcp::ClientOptions::default();
+        if let Some((user, password)) = credentials.split_once(':') {
+            options.user = urlencoding::decode(user).unwrap().into_owned();
+            options.password = urlencoding::decode(password).unwrap().into_owned();
+        }
+        (format!("{host}:{port}"), options)
+    }
+
     /// Executes any SQL statement.
     ///
     /// # Panics
@@ -84,6 +113,16 @@ impl ClickhouseServer {
         Self::expect_success(self.send_query(sql, Some(payload), None), "insert failed");
     }
 
+    /// Sends a payload compressed as `ClickHouse` compressed blocks, passing
+    /// the statement in the URL since the server decompresses the whole body.
+    pub fn insert_compressed_payload(&self, sql: &str, payload: &[u8]) {
+        let mut url = self.dsn.clone();
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str("decompress=1&query=");
+        url.push_str(&urlencoding::encode(sql));
+        Self::expect_success(self.send_raw(payload, &url), "compressed insert failed");
+    }
+
     /// Fetches rows as JSON for assertions.
     ///
     /// # Panics
@@ -110,6 +149,43 @@ impl ClickhouseServer {
         response_bytes(response)
     }
 
+    /// Fetches a raw `RowBinary` payload with settings passed as URL
+    /// parameters.
+    pub fn fetch_rowbinary_with_settings(
+        &self,
+        sql: &str,
+        format: RowBinaryFormat,
+        settings: &str,
+    ) -> Vec<u8> {
+        let query = format!("{sql} FORMAT {format}");
+        let response = Self::expect_success(
+            self.send_query(&query, None, Some(settings)),
+            "select rowbinary failed",
+        );
+        response_bytes(response)
+    }
+
+    /// Fetches a raw `RowBinary` payload with HTTP compression requested
+    /// through `Accept-Encoding`, leaving the body compressed.
+    pub fn fetch_rowbinary_encoded(
+        &self,
+        sql: &str,
+        format: RowBinaryFormat,
+        encoding: &str,
+    ) -> Vec<u8> {
+        let mut url = self.dsn.clone();
+        url.push(if url.contains('?') { '&' } else { '?' });
+        url.push_str("enable_http_compression=1");
+        let body = format!("{sql} FORMAT {format}\n");
+        let result = self
+            .client
+            .post(&url)
+            .header("Accept-Encoding", encoding)
+            .send(body.as_bytes())
+            .map_err(Box::new);
+        response_bytes(Self::expect_success(result, "encoded select failed"))
+    }
+
     fn send_query(
         &self,
         sql: &str,
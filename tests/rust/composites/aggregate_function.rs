@@ -0,0 +1,109 @@
+use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use serde_json::json;
+
+use crate::common::{ClickhouseServer, decode_rows, unique_table};
+
+const FORMATS: [RowBinaryFormat; 3] = [
+    RowBinaryFormat::RowBinary,
+    RowBinaryFormat::RowBinaryWithNames,
+    RowBinaryFormat::RowBinaryWithNamesAndTypes,
+];
+
+#[test]
+fn aggregate_function_sum_state_reading() {
+    let server = ClickhouseServer::connect();
+    let schema = Schema::from_type_strings(&[("value", "AggregateFunction(sum, UInt32)")]).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(
+            "SELECT sumState(toUInt32(number)) AS value FROM numbers(4)",
+            format,
+        );
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(
+            decoded,
+            vec![vec![Value::AggregateState(6_u64.to_le_bytes().to_vec())]]
+        );
+    }
+}
+
+#[test]
+fn aggregate_function_count_state_reading() {
+    let server = ClickhouseServer::connect();
+    let schema = Schema::from_type_strings(&[("value", "AggregateFunction(count)")]).unwrap();
+
+    for format in FORMATS {
+        let payload =
+            server.fetch_rowbinary("SELECT countState() AS value FROM numbers(150)", format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(decoded, vec![vec![Value::AggregateState(vec![0x96, 0x01])]]);
+    }
+}
+
+#[test]
+fn aggregate_function_uniq_state_roundtrip() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value AggregateFunction(uniq, UInt64)) ENGINE=Memory"
+    ));
+    let schema =
+        Schema::from_type_strings(&[("value", "AggregateFunction(uniq, UInt64)")]).unwrap();
+    let state_payload = server.fetch_rowbinary(
+        "SELECT uniqState(number % 3) AS value FROM numbers(10)",
+        RowBinaryFormat::RowBinary,
+    );
+    let rows = decode_rows(&state_payload, RowBinaryFormat::RowBinary, &schema);
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(&insert_sql, format, &schema, &rows);
+        let json_rows =
+            server.fetch_json(&format!("SELECT uniqMerge(value) AS value FROM {table}"));
+        assert_eq!(json_rows, vec![json!({"value": "3"})]);
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
+
+#[test]
+fn simple_aggregate_function_multi_row_reading() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value SimpleAggregateFunction(sum, UInt64)) ENGINE=Memory"
+    ));
+    server.exec(&format!("INSERT INTO {table} VALUES (1),(42)"));
+    let schema =
+        Schema::from_type_strings(&[("value", "SimpleAggregateFunction(sum, UInt64)")]).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(&format!("SELECT value FROM {table}"), format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(
+            decoded,
+            vec![vec![Value::UInt64(1)], vec![Value::UInt64(42)]]
+        );
+    }
+}
+
+#[test]
+fn simple_aggregate_function_single_row_writing() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value SimpleAggregateFunction(sum, UInt64)) ENGINE=Memory"
+    ));
+    let schema =
+        Schema::from_type_strings(&[("value", "SimpleAggregateFunction(sum, UInt64)")]).unwrap();
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(&insert_sql, format, &schema, &[vec![Value::UInt64(7)]]);
+        let json_rows = server.fetch_json(&format!("SELECT value FROM {table}"));
+        assert_eq!(json_rows, vec![json!({"value": "7"})]);
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
@@ -0,0 +1,135 @@
+use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use serde_json::json;
+
+use crate::common::{ClickhouseServer, decode_rows, unique_table};
+
+const FORMATS: [RowBinaryFormat; 3] = [
+    RowBinaryFormat::RowBinary,
+    RowBinaryFormat::RowBinaryWithNames,
+    RowBinaryFormat::RowBinaryWithNamesAndTypes,
+];
+
+fn point(x: f64, y: f64) -> Value {
+    Value::Tuple(vec![Value::Float64(x), Value::Float64(y)])
+}
+
+fn line_a() -> Value {
+    Value::Array(vec![point(0.0, 0.0), point(1.5, 2.5)])
+}
+
+fn line_b() -> Value {
+    Value::Array(vec![point(-1.0, 3.0)])
+}
+
+#[test]
+fn geo_schema_parsing() {
+    for name in [
+        "Point",
+        "Ring",
+        "LineString",
+        "MultiLineString",
+        "Polygon",
+        "MultiPolygon",
+    ] {
+        let schema = Schema::from_type_strings(&[("value", name)]).unwrap();
+        assert_eq!(schema.fields()[0].ty.type_name(), name);
+        assert!(schema.fields()[0].ty.geo_storage().is_some());
+    }
+}
+
+#[test]
+fn linestring_single_row_reading() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value LineString) ENGINE=Memory"
+    ));
+    server.exec(&format!(
+        "INSERT INTO {table} VALUES ([(0, 0), (1.5, 2.5)])"
+    ));
+    let schema = Schema::from_type_strings(&[("value", "LineString")]).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(&format!("SELECT value FROM {table}"), format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(decoded, vec![vec![line_a()]]);
+    }
+}
+
+#[test]
+fn linestring_multi_row_writing() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value LineString) ENGINE=Memory"
+    ));
+    let schema = Schema::from_type_strings(&[("value", "LineString")]).unwrap();
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(
+            &insert_sql,
+            format,
+            &schema,
+            &[vec![line_a()], vec![line_b()]],
+        );
+        let json_rows = server.fetch_json(&format!("SELECT value FROM {table}"));
+        assert_eq!(
+            json_rows,
+            vec![
+                json!({"value": [[0.0, 0.0], [1.5, 2.5]]}),
+                json!({"value": [[-1.0, 3.0]]}),
+            ]
+        );
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
+
+#[test]
+fn multilinestring_single_row_reading() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value MultiLineString) ENGINE=Memory"
+    ));
+    server.exec(&format!(
+        "INSERT INTO {table} VALUES ([[(0, 0), (1.5, 2.5)], [(-1, 3)]])"
+    ));
+    let schema = Schema::from_type_strings(&[("value", "MultiLineString")]).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(&format!("SELECT value FROM {table}"), format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(decoded, vec![vec![Value::Array(vec![line_a(), line_b()])]]);
+    }
+}
+
+#[test]
+fn multilinestring_single_row_writing() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value MultiLineString) ENGINE=Memory"
+    ));
+    let schema = Schema::from_type_strings(&[("value", "MultiLineString")]).unwrap();
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(
+            &insert_sql,
+            format,
+            &schema,
+            &[vec![Value::Array(vec![line_a(), line_b()])]],
+        );
+        let json_rows = server.fetch_json(&format!("SELECT value FROM {table}"));
+        assert_eq!(
+            json_rows,
+            vec![json!({"value": [[[0.0, 0.0], [1.5, 2.5]], [[-1.0, 3.0]]]})]
+        );
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
@@ -1,7 +1,10 @@
+mod aggregate_function;
 mod dynamic;
+mod geo;
 mod json;
 mod json_stress;
 mod map;
 mod nested;
+mod nullable_composites;
 mod tuple;
 mod variant;
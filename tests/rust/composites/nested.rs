@@ -135,3 +135,18 @@ fn nested_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn nested_schema_flattening() {
+    let schema =
+        Schema::from_type_strings(&[("id", "UInt32"), ("n", "Nested(a UInt32, b String)")])
+            .unwrap();
+    let flattened = schema.flatten_nested().unwrap();
+    let expected = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("n.a", "Array(UInt32)"),
+        ("n.b", "Array(String)"),
+    ])
+    .unwrap();
+    assert_eq!(flattened, expected);
+}
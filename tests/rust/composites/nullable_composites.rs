@@ -0,0 +1,96 @@
+use clickhouse_rowbinary::{Error, RowBinaryFormat, Schema, Value};
+use serde_json::json;
+
+use crate::common::{ClickhouseServer, decode_rows, unique_table};
+
+const FORMATS: [RowBinaryFormat; 3] = [
+    RowBinaryFormat::RowBinary,
+    RowBinaryFormat::RowBinaryWithNames,
+    RowBinaryFormat::RowBinaryWithNamesAndTypes,
+];
+
+const COLUMNS: [(&str, &str); 4] = [
+    ("tags", "Array(Nullable(String))"),
+    ("labels", "Map(String, Nullable(UInt64))"),
+    ("pair", "Tuple(Nullable(UInt8), String)"),
+    ("code", "Nullable(FixedString(2))"),
+];
+
+fn nullable(value: Value) -> Value {
+    Value::Nullable(Some(Box::new(value)))
+}
+
+fn nullable_composite_row() -> Vec<Value> {
+    vec![
+        Value::Array(vec![nullable(Value::from("a")), Value::Nullable(None)]),
+        Value::Map(vec![
+            (Value::from("x"), nullable(Value::UInt64(1))),
+            (Value::from("y"), Value::Nullable(None)),
+        ]),
+        Value::Tuple(vec![Value::Nullable(None), Value::from("b")]),
+        nullable(Value::FixedString(b"ok".to_vec())),
+    ]
+}
+
+fn create_table(server: &ClickhouseServer, table: &str) {
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    let columns = COLUMNS
+        .iter()
+        .map(|(name, ty)| format!("{name} {ty}"))
+        .collect::<Vec<_>>()
+        .join(", ");
+    server.exec(&format!("CREATE TABLE {table} ({columns}) ENGINE=Memory"));
+}
+
+#[test]
+fn nullable_wrapping_composites_is_rejected() {
+    for ty in [
+        "Nullable(Array(String))",
+        "Nullable(Map(String, UInt64))",
+        "Nullable(Tuple(UInt8, String))",
+    ] {
+        let err = Schema::from_type_strings(&[("value", ty)]).unwrap_err();
+        assert!(matches!(err, Error::UnsupportedCombination(_)), "{ty}");
+    }
+}
+
+#[test]
+fn nullable_composites_single_row_reading() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    create_table(&server, &table);
+    server.exec(&format!(
+        "INSERT INTO {table} VALUES (['a', NULL], map('x', 1, 'y', NULL), (NULL, 'b'), 'ok')"
+    ));
+    let schema = Schema::from_type_strings(&COLUMNS).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(&format!("SELECT * FROM {table}"), format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(decoded, vec![nullable_composite_row()]);
+    }
+}
+
+#[test]
+fn nullable_composites_single_row_writing() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    create_table(&server, &table);
+    let schema = Schema::from_type_strings(&COLUMNS).unwrap();
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(&insert_sql, format, &schema, &[nullable_composite_row()]);
+        let json_rows = server.fetch_json(&format!("SELECT * FROM {table}"));
+        assert_eq!(
+            json_rows,
+            vec![json!({
+                "tags": ["a", null],
+                "labels": {"x": "1", "y": null},
+                "pair": [null, "b"],
+                "code": "ok",
+            })]
+        );
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 
 use crate::common::{ClickhouseServer, decode_rows, unique_table};
 
@@ -131,3 +131,41 @@ fn variant_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn variant_writing_infers_alternative() {
+    let schema = variant_schema();
+    let (first, second, third) = variant_rows();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_row(&[Value::from("alpha")]).unwrap();
+    writer.write_row(&[Value::UInt8(7)]).unwrap();
+    writer.write_row(&[Value::Nullable(None)]).unwrap();
+    assert!(writer.write_row(&[Value::Int64(1)]).is_err());
+    let payload = writer.into_inner();
+
+    let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+    assert_eq!(decoded, vec![first, second, third]);
+}
+
+#[test]
+fn variant_writing_prefers_the_value_type_over_coercions() {
+    for (ty, value) in [
+        ("Variant(Bool, UInt8)", Value::UInt8(1)),
+        ("Variant(Bool, UInt8)", Value::Bool(true)),
+        ("Variant(Enum8('a' = 1), String)", Value::from("a")),
+        ("Variant(Enum8('a' = 1), String)", Value::Enum8(1)),
+    ] {
+        let schema = Schema::from_type_strings(&[("value", ty)]).unwrap();
+        let mut writer =
+            RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+        writer.write_row(std::slice::from_ref(&value)).unwrap();
+        let payload = writer.into_inner();
+
+        let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+        let Value::Variant { value: decoded, .. } = &decoded[0][0] else {
+            panic!("expected a Variant, got {:?}", decoded[0][0]);
+        };
+        assert_eq!(**decoded, value, "{ty}");
+    }
+}
@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 use serde_json::json;
 
 use crate::common::{ClickhouseServer, decode_rows, unique_table};
@@ -631,3 +631,20 @@ fn bool_array_low_cardinality_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn bool_accepts_zero_or_one_bytes() {
+    let schema = Schema::from_type_strings(&[("flag", "Bool")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_row(&[Value::UInt8(1)]).unwrap();
+    writer.write_row(&[Value::Bool(false)]).unwrap();
+    assert!(writer.write_row(&[Value::UInt8(2)]).is_err());
+    let payload = writer.into_inner();
+
+    let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+    assert_eq!(
+        decoded,
+        vec![vec![Value::Bool(true)], vec![Value::Bool(false)]]
+    );
+}
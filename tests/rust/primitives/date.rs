@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{Error, RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 use serde_json::json;
 
 use crate::common::{ClickhouseServer, decode_rows, unique_table};
@@ -618,3 +618,19 @@ fn date_array_low_cardinality_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn date_columns_accept_date32_values_in_range() {
+    let schema = Schema::from_type_strings(&[("day", "Date")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_row(&[Value::Date32(19_782)]).unwrap();
+    assert!(matches!(
+        writer.write_row(&[Value::Date32(-1)]),
+        Err(Error::Overflow(_))
+    ));
+    let payload = writer.into_inner();
+
+    let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+    assert_eq!(decoded, vec![vec![Value::Date(19_782)]]);
+}
@@ -72,6 +72,33 @@ fn enum8_single_row_writing() {
     }
 }
 
+#[test]
+fn enum8_writing_by_name() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (value Enum8('alpha' = 1, 'beta' = 2)) ENGINE=Memory"
+    ));
+    let schema = Schema::from_type_strings(&[("value", "Enum8('alpha' = 1, 'beta' = 2)")]).unwrap();
+
+    for format in FORMATS {
+        let insert_sql = format!("INSERT INTO {table} FORMAT {format}");
+        server.insert_rowbinary(
+            &insert_sql,
+            format,
+            &schema,
+            &[vec![Value::from("beta")], vec![enum8_a()]],
+        );
+        let json_rows = server.fetch_json(&format!("SELECT value FROM {table}"));
+        assert_eq!(
+            json_rows,
+            vec![json!({"value": "beta"}), json!({"value": "alpha"})]
+        );
+        server.exec(&format!("TRUNCATE TABLE {table}"));
+    }
+}
+
 #[test]
 fn enum8_multi_row_writing() {
     let server = ClickhouseServer::connect();
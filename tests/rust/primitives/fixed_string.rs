@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 use serde_json::json;
 
 use crate::common::{ClickhouseServer, decode_rows, unique_table};
@@ -685,3 +685,26 @@ fn fixed_string_array_low_cardinality_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn fixed_string_padding_is_opt_in() {
+    let schema = Schema::from_type_strings(&[("code", "FixedString(4)")]).unwrap();
+    let short = vec![Value::FixedString(b"ab".to_vec())];
+
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    assert!(writer.write_row(&short).is_err());
+
+    writer.set_fixed_string_padding(true);
+    writer.write_row(&short).unwrap();
+    assert!(
+        writer
+            .write_row(&[Value::FixedString(b"abcde".to_vec())])
+            .is_err()
+    );
+    let payload = writer.into_inner();
+    assert_eq!(payload, b"ab\0\0");
+
+    let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+    assert_eq!(decoded, vec![vec![Value::FixedString(b"ab\0\0".to_vec())]]);
+}
@@ -0,0 +1,54 @@
+use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+
+use crate::common::{ClickhouseServer, decode_rows};
+
+const FORMATS: [RowBinaryFormat; 3] = [
+    RowBinaryFormat::RowBinary,
+    RowBinaryFormat::RowBinaryWithNames,
+    RowBinaryFormat::RowBinaryWithNamesAndTypes,
+];
+
+#[test]
+fn interval_single_row_reading() {
+    let server = ClickhouseServer::connect();
+    let schema = Schema::from_type_strings(&[
+        ("seconds", "IntervalSecond"),
+        ("days", "IntervalDay"),
+        ("months", "IntervalMonth"),
+    ])
+    .unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(
+            "SELECT toIntervalSecond(30) AS seconds, toIntervalDay(-2) AS days, \
+             toIntervalMonth(14) AS months",
+            format,
+        );
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(
+            decoded,
+            vec![vec![Value::Int64(30), Value::Int64(-2), Value::Int64(14)]]
+        );
+    }
+}
+
+#[test]
+fn interval_nullable_multi_row_reading() {
+    let server = ClickhouseServer::connect();
+    let schema = Schema::from_type_strings(&[("value", "Nullable(IntervalHour)")]).unwrap();
+
+    for format in FORMATS {
+        let payload = server.fetch_rowbinary(
+            "SELECT if(number = 1, NULL, toIntervalHour(number)) AS value FROM numbers(2)",
+            format,
+        );
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(
+            decoded,
+            vec![
+                vec![Value::Nullable(Some(Box::new(Value::Int64(0))))],
+                vec![Value::Nullable(None)],
+            ]
+        );
+    }
+}
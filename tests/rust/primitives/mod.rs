@@ -21,6 +21,7 @@ mod int256;
 mod int32;
 mod int64;
 mod int8;
+mod interval;
 mod ipv4;
 mod ipv6;
 mod nothing;
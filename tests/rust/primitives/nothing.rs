@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 
 use crate::common::{ClickhouseServer, decode_rows, unique_table};
 
@@ -120,3 +120,59 @@ fn nothing_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn nothing_empty_array_literal_reading() {
+    let server = ClickhouseServer::connect();
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt8"),
+        ("empty", "Array(Nothing)"),
+        ("missing", "Nullable(Nothing)"),
+    ])
+    .unwrap();
+
+    for format in FORMATS {
+        let payload =
+            server.fetch_rowbinary("SELECT 1 AS id, [] AS empty, NULL AS missing", format);
+        let decoded = decode_rows(&payload, format, &schema);
+        assert_eq!(
+            decoded,
+            vec![vec![
+                Value::UInt8(1),
+                Value::Array(Vec::new()),
+                Value::Nullable(None),
+            ]]
+        );
+    }
+}
+
+#[test]
+fn nothing_empty_array_roundtrip() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt8"),
+        ("empty", "Array(Nothing)"),
+        ("missing", "Nullable(Nothing)"),
+    ])
+    .unwrap();
+    let rows = vec![vec![
+        Value::UInt8(1),
+        Value::Array(Vec::new()),
+        Value::Nullable(None),
+    ]];
+
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema.clone(),
+    );
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    let payload = writer.into_inner();
+
+    let decoded = decode_rows(
+        &payload,
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        &schema,
+    );
+    assert_eq!(decoded, rows);
+}
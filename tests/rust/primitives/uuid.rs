@@ -1,4 +1,4 @@
-use clickhouse_rowbinary::{RowBinaryFormat, Schema, Value};
+use clickhouse_rowbinary::{RowBinaryFormat, RowBinaryValueWriter, Schema, Value};
 use serde_json::json;
 use uuid::Uuid;
 
@@ -681,3 +681,23 @@ fn uuid_array_low_cardinality_multi_row_writing() {
         server.exec(&format!("TRUNCATE TABLE {table}"));
     }
 }
+
+#[test]
+fn uuid_halves_are_swapped_on_the_wire() {
+    let uuid = Uuid::from_u128(0x0011_2233_4455_6677_8899_aabb_ccdd_eeff);
+    let schema = Schema::from_type_strings(&[("id", "UUID")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_row(&[Value::from(uuid)]).unwrap();
+    let payload = writer.into_inner();
+    assert_eq!(
+        payload,
+        [
+            0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa,
+            0x99, 0x88,
+        ]
+    );
+
+    let decoded = decode_rows(&payload, RowBinaryFormat::RowBinary, &schema);
+    assert_eq!(Uuid::try_from(decoded[0][0].clone()).unwrap(), uuid);
+}
@@ -0,0 +1,183 @@
+use clickhouse_rowbinary::{AvroReader, AvroWriter, Error, Schema, Value};
+use uuid::Uuid;
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+#[test]
+fn rows_round_trip_through_the_derived_schema() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("small", "UInt32"),
+        ("name", "LowCardinality(String)"),
+        ("note", "Nullable(String)"),
+        ("code", "FixedString(2)"),
+        ("day", "Date"),
+        ("at", "DateTime64(3, 'UTC')"),
+        ("price", "Decimal(18, 4)"),
+        ("uuid", "UUID"),
+        ("ip", "IPv6"),
+        ("big", "Int128"),
+        ("level", "Enum8('info' = 1, 'warn' = 2)"),
+        ("tags", "Array(Nullable(String))"),
+        ("attrs", "Map(String, Float64)"),
+        ("point", "Tuple(x Int8, y Int8)"),
+        ("pick", "Variant(String, UInt8)"),
+    ])
+    .unwrap();
+    let uuid = Uuid::parse_str("01234567-89ab-cdef-0123-456789abcdef").unwrap();
+    let row = vec![
+        Value::UInt64(u64::MAX),
+        Value::UInt32(u32::MAX),
+        string("alice"),
+        Value::Nullable(None),
+        Value::FixedString(b"ok".to_vec()),
+        Value::Date(19_723),
+        Value::DateTime64(-1),
+        Value::Decimal64(-12_345),
+        Value::Uuid(uuid),
+        Value::Ipv6("2001:db8::1".parse().unwrap()),
+        Value::Int128(i128::MIN),
+        Value::Enum8(2),
+        Value::Array(vec![
+            Value::Nullable(Some(Box::new(string("a")))),
+            Value::Nullable(None),
+        ]),
+        Value::Map(vec![(string("k"), Value::Float64(0.5))]),
+        Value::Tuple(vec![Value::Int8(1), Value::Int8(-2)]),
+        Value::Variant {
+            index: 1,
+            value: Box::new(Value::UInt8(9)),
+        },
+    ];
+    let empty = vec![
+        Value::UInt64(0),
+        Value::UInt32(0),
+        string(""),
+        Value::Nullable(Some(Box::new(string("")))),
+        Value::FixedString(vec![0, 0]),
+        Value::Date(0),
+        Value::DateTime64(0),
+        Value::Decimal64(0),
+        Value::Uuid(Uuid::nil()),
+        Value::Ipv6("::".parse().unwrap()),
+        Value::Int128(0),
+        Value::Enum8(1),
+        Value::Array(Vec::new()),
+        Value::Map(Vec::new()),
+        Value::Tuple(vec![Value::Int8(0), Value::Int8(0)]),
+        Value::VariantNull,
+    ];
+    let mut writer = AvroWriter::new(Vec::new(), schema.clone()).unwrap();
+    writer.write_rows([&row, &empty]).unwrap();
+    let avro_schema = writer.avro_schema().to_string();
+    assert!(
+        avro_schema
+            .contains(r#"{"name":"at","type":{"type":"long","logicalType":"timestamp-millis"}}"#)
+    );
+    assert!(avro_schema.contains(r#"{"name":"pick","type":["null","bytes","int"]}"#));
+    let payload = writer.into_inner();
+    // UInt64::MAX keeps its bits as the long -1.
+    assert_eq!(payload[0], 1);
+
+    let reader = AvroReader::new(payload.as_slice(), &avro_schema, schema).unwrap();
+    assert_eq!(
+        reader.collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![row, empty]
+    );
+}
+
+#[test]
+fn writer_schemas_are_resolved_by_field_name() {
+    let writer_schema = r#"{"type":"record","name":"event","namespace":"app","fields":[
+        {"name":"extra","type":"string"},
+        {"name":"ts","type":{"type":"long","logicalType":"timestamp-millis"}},
+        {"name":"price","type":{"type":"bytes","logicalType":"decimal","precision":9,"scale":1}},
+        {"name":"level","type":{"type":"enum","name":"Level","symbols":["low","high"]}},
+        {"name":"pick","type":["null","long","string"]}
+    ]}"#;
+    let schema = Schema::from_type_strings(&[
+        ("missing", "UInt8"),
+        ("pick", "Variant(String, Int64)"),
+        ("level", "String"),
+        ("price", "Decimal(9, 3)"),
+        ("ts", "DateTime64(6, 'UTC')"),
+    ])
+    .unwrap();
+    let payload: &[u8] = &[
+        4, b'a', b'b', 184, 23, 2, 25, 2, 4, 2, b'x', // first datum
+        0, 1, 2, 0xfb, 0, 2, 14, // second datum
+    ];
+    let reader = AvroReader::new(payload, writer_schema, schema).unwrap();
+    let rows: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    assert_eq!(
+        rows,
+        vec![
+            vec![
+                Value::UInt8(0),
+                Value::Variant {
+                    index: 1,
+                    value: Box::new(string("x")),
+                },
+                string("high"),
+                Value::Decimal32(2_500),
+                Value::DateTime64(1_500_000),
+            ],
+            vec![
+                Value::UInt8(0),
+                Value::Variant {
+                    index: 0,
+                    value: Box::new(Value::Int64(7)),
+                },
+                string("low"),
+                Value::Decimal32(-500),
+                Value::DateTime64(-1_000),
+            ],
+        ]
+    );
+}
+
+#[test]
+fn malformed_datums_are_rejected() {
+    let schema = Schema::from_type_strings(&[("n", "UInt8"), ("s", "String")]).unwrap();
+    let writer_schema = r#"{"type":"record","name":"row","fields":[
+        {"name":"n","type":"int"},{"name":"s","type":["null","bytes"]}]}"#;
+    for (payload, invalid) in [
+        (&[2, 4][..], true),
+        (&[2, 2, 8, b'a'][..], false),
+        (&[0x80][..], false),
+        (&[2, 2, 1][..], true),
+    ] {
+        let mut reader = AvroReader::new(payload, writer_schema, schema.clone()).unwrap();
+        let error = reader.read_row().unwrap_err();
+        if invalid {
+            assert!(matches!(error, Error::InvalidValue(_)), "{payload:?}");
+        } else {
+            assert!(matches!(error, Error::Io(_)), "{payload:?}");
+        }
+    }
+    let mut reader = AvroReader::new(&[216, 4, 0][..], writer_schema, schema.clone()).unwrap();
+    assert!(matches!(reader.read_row(), Err(Error::Overflow(_))));
+    let mut reader = AvroReader::new(&[2, 0][..], writer_schema, schema.clone()).unwrap();
+    assert_eq!(
+        reader.read_row().unwrap(),
+        Some(vec![Value::UInt8(1), string("")])
+    );
+
+    assert!(matches!(
+        AvroReader::new(&[][..], r#"["null","int"]"#, schema.clone()),
+        Err(Error::InvalidValue(_))
+    ));
+    let dynamic = Schema::from_type_strings(&[("d", "Dynamic")]).unwrap();
+    assert!(matches!(
+        AvroWriter::new(Vec::new(), dynamic),
+        Err(Error::UnsupportedType(_))
+    ));
+    let mut writer = AvroWriter::new(Vec::new(), schema).unwrap();
+    assert!(matches!(
+        writer.write_row(&[Value::UInt8(1), Value::UInt8(2)]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(writer.get_ref().is_empty());
+}
@@ -0,0 +1,92 @@
+use clickhouse_rowbinary::{
+    Error, ReaderOptions, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema,
+    Value,
+};
+
+const FORMAT: RowBinaryFormat = RowBinaryFormat::RowBinaryWithNamesAndTypes;
+
+fn binary_options() -> ReaderOptions {
+    ReaderOptions {
+        binary_types: true,
+        ..ReaderOptions::default()
+    }
+}
+
+#[test]
+fn headers_carry_binary_type_descriptors() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("tags", "Array(LowCardinality(Nullable(String)))"),
+        ("any", "Dynamic"),
+    ])
+    .unwrap();
+    let row = vec![
+        Value::UInt32(1),
+        Value::Array(vec![Value::Nullable(None)]),
+        Value::Dynamic {
+            ty: Box::new(schema.fields()[0].ty.clone()),
+            value: Box::new(Value::UInt32(9)),
+        },
+    ];
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), FORMAT, schema.clone());
+    writer.set_binary_types(true);
+    writer.write_header().unwrap();
+    writer.write_row(&row).unwrap();
+    let payload = writer.into_inner();
+
+    let mut header = vec![3];
+    header.extend(b"\x02id\x04tags\x03any");
+    header.extend([0x03, 0x1E, 0x26, 0x23, 0x15, 0x2B, 32]);
+    assert_eq!(&payload[..header.len()], header.as_slice());
+
+    let mut reader =
+        RowBinaryValueReader::with_options(payload.as_slice(), FORMAT, None, &binary_options())
+            .unwrap();
+    assert_eq!(reader.schema(), &schema);
+    assert_eq!(reader.read_row().unwrap(), Some(row));
+    assert_eq!(reader.read_row().unwrap(), None);
+}
+
+#[test]
+fn binary_headers_are_validated() {
+    let truncated = [1, 1, b'n', 0x1E];
+    assert!(matches!(
+        RowBinaryValueReader::with_options(truncated.as_slice(), FORMAT, None, &binary_options()),
+        Err(Error::InvalidValue("missing header"))
+    ));
+
+    let nested_dynamic = [1, 1, b'd', 0x2B, 32, 0x2B, 32];
+    let mut reader = RowBinaryValueReader::with_options(
+        nested_dynamic.as_slice(),
+        FORMAT,
+        None,
+        &binary_options(),
+    )
+    .unwrap();
+    assert!(matches!(reader.read_row(), Err(Error::UnsupportedType(_))));
+}
+
+#[test]
+fn binary_headers_respect_the_type_depth_limit() {
+    let mut payload = vec![1, 1, b'a'];
+    payload.extend([0x1E; 200]);
+    payload.push(0x01);
+    let options = ReaderOptions {
+        max_type_depth: 4,
+        ..binary_options()
+    };
+    assert!(matches!(
+        RowBinaryValueReader::with_options(payload.as_slice(), FORMAT, None, &options),
+        Err(Error::InvalidValue("type nesting depth limit exceeded"))
+    ));
+
+    let mut payload = vec![1, 1, b'a'];
+    payload.extend([0x1E; 4]);
+    payload.push(0x01);
+    let reader =
+        RowBinaryValueReader::with_options(payload.as_slice(), FORMAT, None, &options).unwrap();
+    assert_eq!(
+        reader.schema(),
+        &Schema::from_type_strings(&[("a", "Array(Array(Array(Array(UInt8))))")]).unwrap()
+    );
+}
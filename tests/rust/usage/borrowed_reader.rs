@@ -0,0 +1,98 @@
+use clickhouse_rowbinary::{
+    Error, Row, RowBinaryFormat, RowBinaryRefReader, RowBinaryValueWriter, Schema, Value, ValueRef,
+};
+
+fn sample_schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("name", "String"),
+        ("nick", "Nullable(String)"),
+        ("tags", "Array(LowCardinality(String))"),
+        ("attrs", "Map(String, UInt8)"),
+        ("pair", "Tuple(FixedString(2), Float64)"),
+        ("at", "Point"),
+    ])
+    .unwrap()
+}
+
+fn sample_rows() -> Vec<Row> {
+    vec![
+        vec![
+            Value::UInt32(1),
+            Value::String(b"alpha".to_vec()),
+            Value::Nullable(Some(Box::new(Value::String(b"al".to_vec())))),
+            Value::Array(vec![
+                Value::String(b"x".to_vec()),
+                Value::String(b"y".to_vec()),
+            ]),
+            Value::Map(vec![(Value::String(b"k".to_vec()), Value::UInt8(7))]),
+            Value::Tuple(vec![
+                Value::FixedString(b"ab".to_vec()),
+                Value::Float64(0.5),
+            ]),
+            Value::Tuple(vec![Value::Float64(1.0), Value::Float64(2.0)]),
+        ],
+        vec![
+            Value::UInt32(2),
+            Value::String(Vec::new()),
+            Value::Nullable(None),
+            Value::Array(Vec::new()),
+            Value::Map(Vec::new()),
+            Value::Tuple(vec![
+                Value::FixedString(b"cd".to_vec()),
+                Value::Float64(-1.0),
+            ]),
+            Value::Tuple(vec![Value::Float64(0.0), Value::Float64(0.0)]),
+        ],
+    ]
+}
+
+fn encode(format: RowBinaryFormat) -> Vec<u8> {
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), format, sample_schema());
+    writer.write_header().unwrap();
+    writer.write_rows(sample_rows()).unwrap();
+    writer.into_inner()
+}
+
+#[test]
+fn borrowed_reader_matches_owned_values() {
+    let payload = encode(RowBinaryFormat::RowBinaryWithNamesAndTypes);
+    let mut reader =
+        RowBinaryRefReader::new(&payload, RowBinaryFormat::RowBinaryWithNamesAndTypes, None)
+            .unwrap();
+    assert_eq!(reader.schema(), &sample_schema());
+
+    let mut decoded = Vec::new();
+    while let Some(row) = reader.read_row().unwrap() {
+        decoded.push(row.iter().map(ValueRef::to_value).collect::<Row>());
+    }
+    assert_eq!(decoded, sample_rows());
+}
+
+#[test]
+fn borrowed_reader_points_into_the_payload() {
+    let payload = encode(RowBinaryFormat::RowBinary);
+    let mut reader =
+        RowBinaryRefReader::new(&payload, RowBinaryFormat::RowBinary, Some(sample_schema()))
+            .unwrap();
+    let row = reader.read_row().unwrap().unwrap();
+
+    let ValueRef::String(name) = row[1] else {
+        panic!("expected borrowed string");
+    };
+    assert_eq!(name, b"alpha");
+    let range = payload.as_ptr_range();
+    assert!(range.contains(&name.as_ptr()));
+    assert_eq!(row[0], ValueRef::Owned(Value::UInt32(1)));
+}
+
+#[test]
+fn borrowed_reader_reports_truncated_rows() {
+    let payload = encode(RowBinaryFormat::RowBinary);
+    let truncated = &payload[..payload.len() - 3];
+    let mut reader =
+        RowBinaryRefReader::new(truncated, RowBinaryFormat::RowBinary, Some(sample_schema()))
+            .unwrap();
+    assert!(reader.read_row().unwrap().is_some());
+    assert!(matches!(reader.read_row(), Err(Error::Io(_))));
+}
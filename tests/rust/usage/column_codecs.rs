@@ -0,0 +1,100 @@
+use clickhouse_rowbinary::{
+    ColumnCodec, Error, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, RowView,
+    Schema, Value, parse_type_desc,
+};
+
+/// Codec exposing a `String` column of `"x:y"` pairs as
+/// `Tuple(UInt32, UInt32)`.
+fn pair_codec() -> ColumnCodec {
+    ColumnCodec::new(
+        parse_type_desc("Tuple(UInt32, UInt32)").unwrap(),
+        |value| match value {
+            Value::Tuple(items) => match items.as_slice() {
+                [Value::UInt32(x), Value::UInt32(y)] => {
+                    Ok(Value::String(format!("{x}:{y}").into()))
+                }
+                _ => Err(Error::InvalidValue("expected a pair")),
+            },
+            _ => Err(Error::InvalidValue("expected a pair")),
+        },
+        |value| {
+            let Value::String(bytes) = value else {
+                return Err(Error::InvalidValue("expected a string"));
+            };
+            let text = String::from_utf8(bytes).map_err(|err| Error::Mapping(err.to_string()))?;
+            let (x, y) = text
+                .split_once(':')
+                .ok_or(Error::InvalidValue("expected x:y"))?;
+            let parse = |part: &str| {
+                part.parse()
+                    .map_err(|_| Error::InvalidValue("expected a number"))
+            };
+            Ok(Value::Tuple(vec![
+                Value::UInt32(parse(x)?),
+                Value::UInt32(parse(y)?),
+            ]))
+        },
+    )
+}
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[("id", "UInt8"), ("point", "String")]).unwrap()
+}
+
+#[test]
+fn codecs_convert_values_on_write_and_read() {
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    writer.set_column_codec("point", pair_codec()).unwrap();
+    writer.write_header().unwrap();
+    writer
+        .write_row(&[
+            Value::UInt8(1),
+            Value::Tuple(vec![Value::UInt32(3), Value::UInt32(4)]),
+        ])
+        .unwrap();
+    let payload = writer.into_inner();
+
+    let mut plain = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    assert_eq!(
+        plain.read_row().unwrap().unwrap(),
+        [Value::UInt8(1), Value::String(b"3:4".to_vec())]
+    );
+
+    let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    reader.set_column_codec("point", pair_codec()).unwrap();
+    assert_eq!(
+        reader.schema().fields()[1].ty.type_name(),
+        "Tuple(UInt32, UInt32)"
+    );
+    let row = reader.read_row().unwrap().unwrap();
+    let view = RowView::new(reader.schema(), &row).unwrap();
+    assert_eq!(view.get::<(u32, u32)>("point").unwrap(), (3, 4));
+    assert!(reader.read_row().unwrap().is_none());
+}
+
+#[test]
+fn codec_errors_are_returned_from_the_reader() {
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+    writer
+        .write_row(&[Value::UInt8(1), Value::String(b"oops".to_vec())])
+        .unwrap();
+    let payload = writer.into_inner();
+
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema())
+            .unwrap();
+    reader.set_column_codec("point", pair_codec()).unwrap();
+    assert!(matches!(
+        reader.read_row(),
+        Err(Error::InvalidValue("expected x:y"))
+    ));
+
+    assert!(matches!(
+        reader.set_column_codec("missing", pair_codec()),
+        Err(Error::UnknownColumn(name)) if name == "missing"
+    ));
+}
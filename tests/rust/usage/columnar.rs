@@ -0,0 +1,208 @@
+use clickhouse_rowbinary::{
+    Error, HeaderPolicy, ReaderOptions, RowBinaryFormat, RowBinaryValueReader,
+    RowBinaryValueWriter, Schema, Value,
+};
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("name", "LowCardinality(String)"),
+        ("score", "Nullable(Float64)"),
+        ("day", "Date32"),
+        ("hash", "FixedString(2)"),
+        ("ok", "Bool"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn columnar_write_matches_row_write() {
+    let ids = vec![1_u32, 2, 3];
+    let names = ["a", "bb", "ccc"];
+    let scores = vec![Some(0.5_f64), None, Some(2.0)];
+    let days = [-1_i32, 0, 19_000];
+    let hashes = vec![b"ab".to_vec(), b"cd".to_vec(), b"ef".to_vec()];
+    let flags: &[bool] = &[true, false, true];
+
+    let mut columnar = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    columnar.write_header().unwrap();
+    let stats = columnar
+        .write_columns(&[&ids, &names, &scores, &days, &hashes, &flags])
+        .unwrap();
+    assert_eq!(stats.rows, 3);
+
+    let rows: Vec<Vec<Value>> = (0..3)
+        .map(|index| {
+            vec![
+                Value::UInt32(ids[index]),
+                Value::String(names[index].as_bytes().to_vec()),
+                Value::Nullable(scores[index].map(|score| Box::new(Value::Float64(score)))),
+                Value::Date32(days[index]),
+                Value::FixedString(hashes[index].clone()),
+                Value::Bool(flags[index]),
+            ]
+        })
+        .collect();
+    let mut by_row = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    by_row.write_header().unwrap();
+    let row_stats = by_row.write_batch(&rows).unwrap();
+    assert_eq!(stats, row_stats);
+
+    let payload = columnar.into_inner();
+    assert_eq!(payload, by_row.into_inner());
+    let reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    let decoded: Vec<_> = reader.rows().collect::<Result<_, _>>().unwrap();
+    assert_eq!(decoded, rows);
+}
+
+#[test]
+fn columnar_write_validates_columns_up_front() {
+    let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema);
+
+    let ids = vec![1_u32, 2];
+    let wide_ids = vec![1_u64, 2];
+    let names = vec!["a".to_string(), "b".to_string()];
+    let short_names = vec!["a".to_string()];
+
+    assert!(matches!(
+        writer.write_columns(&[&ids]),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(matches!(
+        writer.write_columns(&[&ids, &short_names]),
+        Err(Error::InvalidValue(_))
+    ));
+    let err = writer.write_columns(&[&wide_ids, &names]).unwrap_err();
+    assert!(matches!(err, Error::TypeMismatch { .. }), "{err}");
+    assert!(writer.get_ref().is_empty());
+
+    writer.write_columns(&[&ids, &names]).unwrap();
+    assert_eq!(writer.get_ref().len(), 2 * (4 + 2));
+}
+
+#[test]
+fn columnar_read_fills_typed_vectors_in_batches() {
+    let ids: Vec<u32> = (0..10).collect();
+    let names: Vec<String> = ids.iter().map(|id| format!("n{id}")).collect();
+    let scores: Vec<Option<f64>> = ids
+        .iter()
+        .map(|id| (id % 3 != 0).then_some(f64::from(*id)))
+        .collect();
+    let days: Vec<i32> = ids
+        .iter()
+        .map(|id| i32::try_from(*id).unwrap() - 5)
+        .collect();
+    let hashes: Vec<Vec<u8>> = ids.iter().map(|id| format!("h{id}").into_bytes()).collect();
+    let flags: Vec<bool> = ids.iter().map(|id| id % 2 == 0).collect();
+
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    writer.write_header().unwrap();
+    writer
+        .write_columns(&[&ids, &names, &scores, &days, &hashes, &flags])
+        .unwrap();
+    let payload = writer.into_inner();
+
+    let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    let mut out_ids: Vec<u32> = Vec::new();
+    let mut out_names: Vec<String> = Vec::new();
+    let mut out_scores: Vec<Option<f64>> = Vec::new();
+    let mut out_days: Vec<i32> = Vec::new();
+    let mut out_hashes: Vec<Vec<u8>> = Vec::new();
+    let mut out_flags: Vec<bool> = Vec::new();
+    let mut batches = Vec::new();
+    loop {
+        let read = reader
+            .read_columns(
+                &mut [
+                    &mut out_ids,
+                    &mut out_names,
+                    &mut out_scores,
+                    &mut out_days,
+                    &mut out_hashes,
+                    &mut out_flags,
+                ],
+                4,
+            )
+            .unwrap();
+        if read == 0 {
+            break;
+        }
+        batches.push(read);
+    }
+    assert_eq!(batches, [4, 4, 2]);
+    assert_eq!(out_ids, ids);
+    assert_eq!(out_names, names);
+    assert_eq!(out_scores, scores);
+    assert_eq!(out_days, days);
+    assert_eq!(out_hashes, hashes);
+    assert_eq!(out_flags, flags);
+}
+
+#[test]
+fn columnar_read_follows_reordered_headers() {
+    let file_schema = Schema::from_type_strings(&[("name", "String"), ("id", "UInt64")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinaryWithNames, file_schema);
+    writer.write_header().unwrap();
+    writer.write_columns(&[&["a", "b"], &[1_u64, 2]]).unwrap();
+    let payload = writer.into_inner();
+
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let options = ReaderOptions {
+        header_policy: HeaderPolicy::Reorder,
+        ..ReaderOptions::default()
+    };
+    let mut reader = RowBinaryValueReader::with_options(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinaryWithNames,
+        Some(schema),
+        &options,
+    )
+    .unwrap();
+    let mut ids: Vec<u64> = Vec::new();
+    let mut names: Vec<String> = Vec::new();
+    assert_eq!(
+        reader
+            .read_columns(&mut [&mut ids, &mut names], 10)
+            .unwrap(),
+        2
+    );
+    assert_eq!(ids, [1, 2]);
+    assert_eq!(names, ["a", "b"]);
+}
+
+#[test]
+fn columnar_read_drops_partial_rows_on_error() {
+    let schema = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+    let payload = [1_u8, 1, b'a', 2, 5, b'b'];
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema)
+            .unwrap();
+    let mut ids: Vec<u8> = vec![0];
+    let mut names: Vec<String> = vec![String::new()];
+    let mut wrong: Vec<u16> = Vec::new();
+    assert!(matches!(
+        reader.read_columns(&mut [&mut wrong, &mut names], 10),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(
+        reader
+            .read_columns(&mut [&mut ids, &mut names], 10)
+            .is_err()
+    );
+    assert_eq!(ids, [0, 1]);
+    assert_eq!(names, ["", "a"]);
+}
@@ -0,0 +1,321 @@
+use std::io::{Read, Write};
+
+use clickhouse_rowbinary::{
+    CompressedReader, CompressedWriter, CompressionMethod, ContentEncoding, DecompressingReader,
+    Error, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema, Value,
+};
+use flate2::{Compression, write::GzEncoder};
+
+use crate::common::{ClickhouseServer, decode_rows, unique_table};
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap()
+}
+
+fn rows(count: u32) -> Vec<Vec<Value>> {
+    (0..count)
+        .map(|id| {
+            vec![
+                Value::UInt32(id),
+                Value::String(format!("name-{}", id % 10).into_bytes()),
+            ]
+        })
+        .collect()
+}
+
+/// Encodes rows as compressed blocks of `chunk` rows each.
+fn compressed_rows(method: CompressionMethod, rows: &[Vec<Value>], chunk: usize) -> Vec<u8> {
+    let compressed = CompressedWriter::new(Vec::new(), method);
+    let mut writer = RowBinaryValueWriter::new(
+        compressed,
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    writer.write_header().unwrap();
+    for rows in rows.chunks(chunk) {
+        writer.write_rows(rows).unwrap();
+        writer.get_mut().flush().unwrap();
+    }
+    writer.into_inner().finish().unwrap()
+}
+
+#[test]
+fn compressed_blocks_round_trip_rows() {
+    let rows = rows(1_000);
+    for method in [
+        CompressionMethod::Lz4,
+        CompressionMethod::Zstd,
+        CompressionMethod::None,
+    ] {
+        let payload = compressed_rows(method, &rows, 300);
+        let mut reader = RowBinaryValueReader::new(
+            CompressedReader::new(payload.as_slice()),
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        )
+        .unwrap();
+        let mut decoded = Vec::new();
+        while let Some(row) = reader.read_row().unwrap() {
+            decoded.push(row);
+        }
+        assert_eq!(decoded, rows);
+    }
+    let plain = compressed_rows(CompressionMethod::None, &rows, 1_000).len();
+    assert!(compressed_rows(CompressionMethod::Lz4, &rows, 1_000).len() < plain / 2);
+}
+
+/// Returns the uncompressed size of each block in a compressed payload.
+fn block_sizes(payload: &[u8]) -> Vec<usize> {
+    let mut sizes = Vec::new();
+    let mut at = 0;
+    while at < payload.len() {
+        let size = |offset: usize| {
+            u32::from_le_bytes(payload[offset..offset + 4].try_into().unwrap()) as usize
+        };
+        sizes.push(size(at + 21));
+        at += 16 + size(at + 17);
+    }
+    sizes
+}
+
+#[test]
+fn writer_emits_blocks_as_it_goes() {
+    let payload: Vec<u8> = (0..3_500_000_u32).map(|i| (i % 97) as u8).collect();
+    let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::Lz4);
+    for chunk in payload.chunks(100_000) {
+        writer.write_all(chunk).unwrap();
+    }
+    // Three full blocks are already compressed before the last is written.
+    assert_eq!(block_sizes(writer.get_ref()), [1 << 20; 3]);
+    let encoded = writer.finish().unwrap();
+    assert_eq!(block_sizes(&encoded), [1 << 20, 1 << 20, 1 << 20, 354_272]);
+
+    let mut decoded = Vec::new();
+    CompressedReader::new(encoded.as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(decoded, payload);
+}
+
+#[test]
+fn writer_max_block_size_is_configurable() {
+    let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::None);
+    assert!(writer.set_max_block_size(0).is_err());
+    assert!(writer.set_max_block_size((1 << 30) + 1).is_err());
+    writer.set_max_block_size(1_000).unwrap();
+    writer.write_all(&[7; 2_500]).unwrap();
+    writer.flush().unwrap();
+    writer.write_all(&[7; 10]).unwrap();
+    assert_eq!(
+        block_sizes(&writer.finish().unwrap()),
+        [1_000, 1_000, 500, 10]
+    );
+}
+
+#[test]
+fn zstd_level_is_configurable() {
+    let payload = b"0123456789abcdef".repeat(10_000);
+    let compress = |level| {
+        let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::Zstd);
+        writer.set_zstd_level(level);
+        writer.write_all(&payload).unwrap();
+        writer.finish().unwrap()
+    };
+    let fast = compress(1);
+    let strong = compress(19);
+    // ZSTD blocks are tagged with method byte 0x90 after the checksum.
+    assert_eq!(fast[16], 0x90);
+    assert!(strong.len() <= fast.len());
+    for encoded in [fast, strong] {
+        let mut decoded = Vec::new();
+        CompressedReader::new(encoded.as_slice())
+            .read_to_end(&mut decoded)
+            .unwrap();
+        assert_eq!(decoded, payload);
+    }
+}
+
+/// LZ4 block sent by the server for `compress=1`, as recorded in the test
+/// suite of the `clickhouse` crate: the checksum, the method byte, the
+/// compressed size with the header, the uncompressed size and the LZ4 data.
+const SERVER_LZ4_BLOCK: [u8; 50] = [
+    245, 5, 222, 235, 225, 158, 59, 108, 225, 31, 65, 215, 66, 66, 36, 92, 0x82, 34, 0, 0, 0, 23,
+    0, 0, 0, 240, 8, 1, 0, 2, 255, 255, 255, 255, 0, 1, 1, 1, 115, 6, 83, 116, 114, 105, 110, 103,
+    3, 97, 98, 99,
+];
+
+/// Data of [`SERVER_LZ4_BLOCK`].
+const SERVER_LZ4_DATA: [u8; 23] = [
+    1, 0, 2, 255, 255, 255, 255, 0, 1, 1, 1, 115, 6, 83, 116, 114, 105, 110, 103, 3, 97, 98, 99,
+];
+
+#[test]
+fn server_lz4_blocks_decode_and_encode_identically() {
+    let mut reader = CompressedReader::new(SERVER_LZ4_BLOCK.as_slice());
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded, SERVER_LZ4_DATA);
+    assert_eq!(reader.blocks_read(), 1);
+
+    let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::Lz4);
+    writer.write_all(&SERVER_LZ4_DATA).unwrap();
+    assert_eq!(writer.finish().unwrap(), SERVER_LZ4_BLOCK);
+}
+
+#[test]
+fn corrupted_block_fails_checksum() {
+    let mut payload = compressed_rows(CompressionMethod::Lz4, &rows(10), 10);
+    let last = payload.len() - 1;
+    payload[last] ^= 0x20;
+    let err = CompressedReader::new(payload.as_slice())
+        .read_to_end(&mut Vec::new())
+        .unwrap_err();
+    assert!(err.to_string().contains("checksum mismatch"), "{err}");
+}
+
+#[test]
+fn checksum_mismatch_reports_block_and_offset() {
+    let mut writer = CompressedWriter::new(Vec::new(), CompressionMethod::None);
+    writer.set_max_block_size(100).unwrap();
+    writer.write_all(&[1; 300]).unwrap();
+    let mut payload = writer.finish().unwrap();
+    // Each stored block is a 16-byte checksum, a 9-byte header and its data.
+    payload[125 + 25 + 10] ^= 0xff;
+
+    let mut reader = CompressedReader::new(payload.as_slice());
+    let err = reader.read_to_end(&mut Vec::new()).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+    let inner = err
+        .get_ref()
+        .and_then(|inner| inner.downcast_ref::<Error>());
+    assert!(
+        matches!(
+            inner,
+            Some(Error::ChecksumMismatch {
+                block: 1,
+                offset: 125
+            })
+        ),
+        "{err}"
+    );
+    assert_eq!(reader.blocks_read(), 1);
+
+    let mut reader = CompressedReader::new(payload.as_slice());
+    reader.set_verify_checksums(false);
+    let mut decoded = Vec::new();
+    reader.read_to_end(&mut decoded).unwrap();
+    assert_eq!(decoded.iter().filter(|&&byte| byte != 1).count(), 1);
+    assert_eq!(reader.blocks_read(), 3);
+}
+
+#[test]
+fn truncated_block_is_an_error() {
+    let payload = compressed_rows(CompressionMethod::Lz4, &rows(10), 10);
+    let mut decoded = Vec::new();
+    let err = CompressedReader::new(&payload[..payload.len() - 3])
+        .read_to_end(&mut decoded)
+        .unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn decompressing_reader_detects_http_encodings() {
+    let rows = rows(500);
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+    writer.write_rows(&rows).unwrap();
+    let plain = writer.into_inner();
+
+    let mut gzip = GzEncoder::new(Vec::new(), Compression::default());
+    gzip.write_all(&plain).unwrap();
+    let gzip = gzip.finish().unwrap();
+    let zstd = zstd::encode_all(plain.as_slice(), 3).unwrap();
+    // Two frames back to back, as a server flushing mid-response sends.
+    let mut zstd_frames = zstd::encode_all(&plain[..1_000], 1).unwrap();
+    zstd_frames.extend(zstd::encode_all(&plain[1_000..], 1).unwrap());
+
+    for (body, encoding) in [
+        (gzip, ContentEncoding::Gzip),
+        (zstd, ContentEncoding::Zstd),
+        (zstd_frames, ContentEncoding::Zstd),
+        (plain.clone(), ContentEncoding::Identity),
+    ] {
+        let reader = DecompressingReader::new(body.as_slice()).unwrap();
+        assert_eq!(reader.encoding(), encoding);
+        let mut reader =
+            RowBinaryValueReader::with_schema(reader, RowBinaryFormat::RowBinary, schema())
+                .unwrap();
+        let mut decoded = Vec::new();
+        while let Some(row) = reader.read_row().unwrap() {
+            decoded.push(row);
+        }
+        assert_eq!(decoded, rows, "{encoding}");
+    }
+}
+
+#[test]
+fn decompressing_reader_passes_short_bodies_through() {
+    for body in [&b""[..], b"\x1f", b"ok"] {
+        let mut reader = DecompressingReader::new(body).unwrap();
+        assert_eq!(reader.encoding(), ContentEncoding::Identity);
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(decoded, body);
+    }
+}
+
+#[test]
+fn compressed_payloads_exchange_with_clickhouse() {
+    let server = ClickhouseServer::connect();
+    let table = unique_table("compressed_blocks");
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+    server.exec(&format!(
+        "CREATE TABLE {table} (id UInt32, name String) ENGINE=MergeTree ORDER BY id"
+    ));
+
+    let rows = rows(5_000);
+    let (lz4_rows, zstd_rows) = rows.split_at(2_500);
+    server.insert_compressed_payload(
+        &format!("INSERT INTO {table} FORMAT RowBinaryWithNamesAndTypes"),
+        &compressed_rows(CompressionMethod::Lz4, lz4_rows, 1_000),
+    );
+    server.insert_compressed_payload(
+        &format!("INSERT INTO {table} FORMAT RowBinaryWithNamesAndTypes"),
+        &compressed_rows(CompressionMethod::Zstd, zstd_rows, 1_000),
+    );
+
+    let payload = server.fetch_rowbinary_with_settings(
+        &format!("SELECT id, name FROM {table} ORDER BY id"),
+        RowBinaryFormat::RowBinary,
+        "compress=1",
+    );
+    let mut decoded = Vec::new();
+    CompressedReader::new(payload.as_slice())
+        .read_to_end(&mut decoded)
+        .unwrap();
+    assert_eq!(
+        decode_rows(&decoded, RowBinaryFormat::RowBinary, &schema()),
+        rows
+    );
+
+    server.exec(&format!("DROP TABLE IF EXISTS {table}"));
+}
+
+#[test]
+fn http_encoded_responses_decode_transparently() {
+    let server = ClickhouseServer::connect();
+    let sql = "SELECT toUInt32(number) AS id, concat('name-', toString(number % 10)) AS name \
+               FROM numbers(5000) ORDER BY id";
+    for (encoding, expected) in [
+        ("zstd", ContentEncoding::Zstd),
+        ("lz4", ContentEncoding::Lz4),
+    ] {
+        let body = server.fetch_rowbinary_encoded(sql, RowBinaryFormat::RowBinary, encoding);
+        let mut reader = DecompressingReader::new(body.as_slice()).unwrap();
+        assert_eq!(reader.encoding(), expected);
+        let mut decoded = Vec::new();
+        reader.read_to_end(&mut decoded).unwrap();
+        assert_eq!(
+            decode_rows(&decoded, RowBinaryFormat::RowBinary, &schema()),
+            rows(5_000)
+        );
+    }
+}
@@ -0,0 +1,162 @@
+use clickhouse_rowbinary::{
+    CsvFormat, CsvOptions, CsvReader, CsvWriter, Error, Schema, TypeDesc, Value,
+};
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+fn some(value: Value) -> Value {
+    Value::Nullable(Some(Box::new(value)))
+}
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("name", "String"),
+        ("note", "Nullable(String)"),
+        ("day", "Date"),
+        ("price", "Decimal(9, 2)"),
+        ("point", "Tuple(x Int8, y Int8)"),
+        ("tags", "Array(Nullable(String))"),
+        ("attrs", "Map(String, UInt8)"),
+        ("level", "Enum8('info' = 1, 'warn' = 2)"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn records_follow_the_server_escaping_rules() {
+    let rows = vec![
+        vec![
+            Value::UInt64(1),
+            string("say \"hi\",\nbye"),
+            Value::Nullable(None),
+            Value::Date(19_723),
+            Value::Decimal32(-250),
+            Value::Tuple(vec![Value::Int8(1), Value::Int8(-2)]),
+            Value::Array(vec![some(string("it's")), Value::Nullable(None)]),
+            Value::Map(vec![(string("k"), Value::UInt8(3))]),
+            Value::Enum8(2),
+        ],
+        vec![
+            Value::UInt64(2),
+            string("\\N"),
+            some(string("")),
+            Value::Date(0),
+            Value::Decimal32(0),
+            Value::Tuple(vec![Value::Int8(0), Value::Int8(0)]),
+            Value::Array(Vec::new()),
+            Value::Map(Vec::new()),
+            Value::Enum8(1),
+        ],
+    ];
+    let mut writer = CsvWriter::new(Vec::new(), CsvFormat::CsvWithNames, schema());
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    let payload = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        payload,
+        concat!(
+            "\"id\",\"name\",\"note\",\"day\",\"price\",\"point\",\"tags\",\"attrs\",\"level\"\n",
+            "1,\"say \"\"hi\"\",\nbye\",\\N,\"2024-01-01\",-2.5,1,-2,",
+            "\"['it\\'s',NULL]\",\"{'k':3}\",\"warn\"\n",
+            "2,\"\\N\",\"\",\"1970-01-01\",0,0,0,\"[]\",\"{}\",\"info\"\n",
+        )
+    );
+
+    let reader = CsvReader::new(payload.as_bytes(), CsvFormat::CsvWithNames, schema()).unwrap();
+    assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), rows);
+
+    let options = CsvOptions { delimiter: b';' };
+    let mut writer = CsvWriter::with_options(Vec::new(), CsvFormat::Csv, schema(), options.clone());
+    writer.write_header().unwrap();
+    writer.write_row(&rows[1]).unwrap();
+    let payload = writer.into_inner();
+    assert!(payload.starts_with(b"2;\"\\N\";\"\";"));
+    let mut reader =
+        CsvReader::with_options(payload.as_slice(), CsvFormat::Csv, schema(), options).unwrap();
+    assert_eq!(reader.read_row().unwrap().as_ref(), Some(&rows[1]));
+}
+
+#[test]
+fn input_is_typed_by_the_schema() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("at", "DateTime('UTC')"),
+        ("score", "Nullable(Float32)"),
+        ("any", "Dynamic"),
+        ("pick", "Variant(String, UInt8)"),
+    ])
+    .unwrap();
+    let payload = "score,id,extra_free\r\n";
+    assert!(matches!(
+        CsvReader::new(payload.as_bytes(), CsvFormat::CsvWithNames, schema.clone()),
+        Err(Error::UnknownColumn(name)) if name == "extra_free"
+    ));
+
+    let payload = "\"any\",pick,id\r\n\"[1,2]\",\"7\",5\r\n\\N,7,\n\"x\",,\"06\"\n";
+    let reader = CsvReader::new(payload.as_bytes(), CsvFormat::CsvWithNames, schema).unwrap();
+    let rows: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    let defaults = [Value::DateTime(0), Value::Nullable(None)];
+    assert_eq!(rows[0][0], Value::UInt32(5));
+    assert_eq!(rows[0][1..3], defaults);
+    assert_eq!(
+        rows[0][3],
+        Value::Dynamic {
+            ty: Box::new(TypeDesc::Array(Box::new(TypeDesc::Int64))),
+            value: Box::new(Value::Array(vec![Value::Int64(1), Value::Int64(2)])),
+        }
+    );
+    assert_eq!(
+        rows[0][4],
+        Value::Variant {
+            index: 0,
+            value: Box::new(string("7")),
+        }
+    );
+    assert_eq!(rows[1][0], Value::UInt32(0));
+    assert_eq!(rows[1][3], Value::DynamicNull);
+    assert_eq!(
+        rows[1][4],
+        Value::Variant {
+            index: 1,
+            value: Box::new(Value::UInt8(7)),
+        }
+    );
+    assert_eq!(rows[2][0], Value::UInt32(6));
+    assert_eq!(
+        rows[2][3],
+        Value::Dynamic {
+            ty: Box::new(TypeDesc::String),
+            value: Box::new(string("x")),
+        }
+    );
+}
+
+#[test]
+fn malformed_records_are_rejected() {
+    let schema = Schema::from_type_strings(&[("n", "UInt8"), ("s", "String")]).unwrap();
+    for record in [
+        "1",
+        "1,a,b",
+        "1,\"a\"b",
+        "1,\"open\nstill open",
+        "x,a",
+        "1,\"a\"\"",
+        "300,a",
+    ] {
+        let mut reader = CsvReader::new(record.as_bytes(), CsvFormat::Csv, schema.clone()).unwrap();
+        assert!(
+            matches!(reader.read_row(), Err(Error::InvalidValue(_))),
+            "{record}"
+        );
+    }
+
+    let mut writer = CsvWriter::new(Vec::new(), CsvFormat::Csv, schema);
+    assert!(matches!(
+        writer.write_row(&[string("1"), string("a")]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(writer.get_ref().is_empty());
+}
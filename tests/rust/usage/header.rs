@@ -0,0 +1,65 @@
+use clickhouse_rowbinary::{
+    Error, ReaderOptions, Row, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema,
+    Value,
+};
+
+#[test]
+fn reader_options_limit_header_type_depth() {
+    let schema = Schema::from_type_strings(&[("value", "Array(Array(UInt8))")]).unwrap();
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema,
+    );
+    writer.write_header().unwrap();
+    let payload = writer.into_inner();
+
+    let options = ReaderOptions {
+        max_type_depth: 1,
+        ..ReaderOptions::default()
+    };
+    let result = RowBinaryValueReader::with_options(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        None,
+        &options,
+    );
+    assert!(matches!(result, Err(Error::InvalidValue(_))));
+
+    let options = ReaderOptions {
+        max_type_depth: 2,
+        ..ReaderOptions::default()
+    };
+    let reader = RowBinaryValueReader::with_options(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        None,
+        &options,
+    )
+    .unwrap();
+    assert_eq!(reader.header().unwrap().names, vec!["value".to_string()]);
+}
+
+#[test]
+fn from_header_builds_schema_from_payload() {
+    let schema =
+        Schema::from_type_strings(&[("id", "UInt64"), ("tags", "Array(LowCardinality(String))")])
+            .unwrap();
+    let rows: Vec<Row> = vec![vec![
+        Value::UInt64(9),
+        Value::Array(vec![Value::String(b"a".to_vec())]),
+    ]];
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema.clone(),
+    );
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    let payload = writer.into_inner();
+
+    let reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    assert_eq!(reader.schema(), &schema);
+    let decoded: Vec<Row> = reader.rows().collect::<Result<_, _>>().unwrap();
+    assert_eq!(decoded, rows);
+}
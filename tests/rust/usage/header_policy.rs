@@ -0,0 +1,78 @@
+use clickhouse_rowbinary::{
+    Error, HeaderPolicy, ReaderOptions, Row, RowBinaryFormat, RowBinaryValueReader,
+    RowBinaryValueWriter, Schema, Value,
+};
+
+fn header_payload() -> Vec<u8> {
+    let schema = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema,
+    );
+    writer.write_header().unwrap();
+    writer
+        .write_row(&[Value::UInt8(7), Value::String(b"seven".to_vec())])
+        .unwrap();
+    writer.into_inner()
+}
+
+fn read_with_policy(schema: &Schema, policy: HeaderPolicy) -> clickhouse_rowbinary::Result<Row> {
+    let payload = header_payload();
+    let options = ReaderOptions {
+        header_policy: policy,
+        ..ReaderOptions::default()
+    };
+    let mut reader = RowBinaryValueReader::with_options(
+        payload.as_slice(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        Some(schema.clone()),
+        &options,
+    )?;
+    Ok(reader.read_row()?.unwrap())
+}
+
+#[test]
+fn header_policy_checks_names_and_types() {
+    let renamed = Schema::from_type_strings(&[("key", "UInt8"), ("name", "String")]).unwrap();
+    assert!(read_with_policy(&renamed, HeaderPolicy::Lenient).is_ok());
+    assert!(matches!(
+        read_with_policy(&renamed, HeaderPolicy::MatchNames),
+        Err(Error::InvalidValue(_))
+    ));
+
+    let retyped =
+        Schema::from_type_strings(&[("id", "UInt8"), ("name", "LowCardinality(String)")]).unwrap();
+    assert!(read_with_policy(&retyped, HeaderPolicy::MatchNames).is_ok());
+    assert!(matches!(
+        read_with_policy(&retyped, HeaderPolicy::MatchNamesAndTypes),
+        Err(Error::TypeMismatch { .. })
+    ));
+
+    let exact = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+    assert_eq!(
+        read_with_policy(&exact, HeaderPolicy::MatchNamesAndTypes).unwrap(),
+        vec![Value::UInt8(7), Value::String(b"seven".to_vec())]
+    );
+}
+
+#[test]
+fn header_policy_reorders_or_ignores_columns() {
+    let swapped = Schema::from_type_strings(&[("name", "String"), ("id", "UInt8")]).unwrap();
+    assert_eq!(
+        read_with_policy(&swapped, HeaderPolicy::Reorder).unwrap(),
+        vec![Value::String(b"seven".to_vec()), Value::UInt8(7)]
+    );
+
+    let unknown = Schema::from_type_strings(&[("name", "String"), ("other", "UInt8")]).unwrap();
+    assert!(matches!(
+        read_with_policy(&unknown, HeaderPolicy::Reorder),
+        Err(Error::InvalidValue(_))
+    ));
+
+    let renamed = Schema::from_type_strings(&[("a", "UInt8"), ("b", "String")]).unwrap();
+    assert_eq!(
+        read_with_policy(&renamed, HeaderPolicy::Ignore).unwrap(),
+        vec![Value::UInt8(7), Value::String(b"seven".to_vec())]
+    );
+}
@@ -0,0 +1,385 @@
+#[cfg(feature = "http")]
+use clickhouse_rowbinary::http::Client;
+#[cfg(feature = "async-http")]
+use clickhouse_rowbinary::http::{AsyncClient, InsertBuffer, InsertBufferOptions};
+#[cfg(feature = "http")]
+use clickhouse_rowbinary::http::{ExternalTable, InsertMode, Inserted, Query};
+use clickhouse_rowbinary::{Error, Schema, Value};
+#[cfg(feature = "async-http")]
+use futures::{SinkExt, StreamExt, stream};
+
+use crate::common::{ClickhouseServer, unique_table};
+
+fn rows(count: u64) -> Vec<Vec<Value>> {
+    (0..count)
+        .map(|id| {
+            vec![
+                Value::UInt64(id),
+                Value::String(format!("n{id}").into_bytes()),
+            ]
+        })
+        .collect()
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_inserts_and_queries_rows() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let table = unique_table("http_client");
+    client
+        .execute(format!(
+            "CREATE TABLE {table} (id UInt64, name String, note String DEFAULT 'none') \
+             ENGINE = MergeTree ORDER BY id"
+        ))
+        .unwrap();
+
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let rows = rows(10_000);
+    let summary = client
+        .insert(&table, &schema, &rows)
+        .unwrap()
+        .summary()
+        .unwrap();
+    assert_eq!(summary.written_rows, 10_000);
+
+    let reader = client
+        .query(format!("SELECT id, name, note FROM {table} ORDER BY id;"))
+        .unwrap();
+    assert_eq!(reader.schema().len(), 3);
+    let decoded: Vec<_> = reader.rows().map(Result::unwrap).collect();
+    assert_eq!(decoded.len(), rows.len());
+    assert_eq!(decoded[42][1], rows[42][1]);
+    assert_eq!(decoded[42][2], Value::String(b"none".to_vec()));
+
+    let query = Query::new(format!("SELECT count() FROM {table}"))
+        .setting("wait_end_of_query", 1)
+        .setting("send_progress_in_http_headers", 1);
+    let mut reader = client.query(query).unwrap();
+    assert_eq!(
+        reader.read_row().unwrap(),
+        Some(vec![Value::UInt64(10_000)])
+    );
+    assert_eq!(reader.get_ref().summary().unwrap().result_rows, 1);
+
+    let err = client
+        .query(format!("SELECT * FROM {table}_missing"))
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::Server { code: 60, .. }), "{err}");
+    client.execute(format!("DROP TABLE {table}")).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_sends_async_inserts() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let table = unique_table("http_async_insert");
+    client
+        .execute(format!(
+            "CREATE TABLE {table} (id UInt64, name String) ENGINE = MergeTree ORDER BY id"
+        ))
+        .unwrap();
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let count = || {
+        let mut reader = client
+            .query(format!("SELECT count() FROM {table}"))
+            .unwrap();
+        reader.read_row().unwrap().unwrap()[0].clone()
+    };
+
+    let waiting = client.clone().with_insert_mode(InsertMode::AsyncWait);
+    assert!(
+        waiting
+            .insert(&table, &schema, rows(10))
+            .unwrap()
+            .is_written()
+    );
+    assert_eq!(count(), Value::UInt64(10));
+
+    let queueing = client.clone().with_insert_mode(InsertMode::AsyncNoWait);
+    assert_eq!(
+        queueing.insert(&table, &schema, rows(5)).unwrap(),
+        Inserted::Queued
+    );
+    for _ in 0..100 {
+        if count() == Value::UInt64(15) {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(100));
+    }
+    assert_eq!(count(), Value::UInt64(15));
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_reports_exceptions_thrown_mid_stream() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    // Small blocks make the server start the response before the failing
+    // row is reached.
+    let sql = "SELECT number, toString(number) FROM numbers(5000000) \
+               WHERE throwIf(number = 4000000) = 0 SETTINGS max_block_size = 1000";
+    let err = match client.query(sql) {
+        Ok(mut reader) => loop {
+            match reader.read_row() {
+                Ok(Some(_)) => {}
+                Ok(None) => panic!("exception not detected"),
+                Err(err) => break err,
+            }
+        },
+        Err(err) => err,
+    };
+    assert!(matches!(err, Error::Server { code: 395, .. }), "{err}");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_binds_query_parameters() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let name = "x' OR 1 = 1 --\t\\";
+    let query = Query::new("SELECT {name:String} AS name, {ids:Array(UInt64)} AS ids")
+        .param("name", Value::String(name.as_bytes().to_vec()))
+        .param(
+            "ids",
+            Value::Array(vec![Value::UInt64(1), Value::UInt64(2)]),
+        );
+    let rows: Vec<_> = client
+        .query(query)
+        .unwrap()
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(
+        rows,
+        [vec![
+            Value::String(name.as_bytes().to_vec()),
+            Value::Array(vec![Value::UInt64(1), Value::UInt64(2)]),
+        ]]
+    );
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_sends_settings() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn())
+        .unwrap()
+        .with_setting("max_threads", 3)
+        .with_setting("max_block_size", 1000);
+    let query = Query::new(
+        "SELECT toUInt64(getSetting('max_threads')), toUInt64(getSetting('max_block_size'))",
+    )
+    .setting("max_block_size", 77);
+    let rows: Vec<_> = client
+        .query(query)
+        .unwrap()
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rows, [vec![Value::UInt64(3), Value::UInt64(77)]]);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_fetches_schemas() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let table = unique_table("http_schema");
+    client
+        .execute(format!(
+            "CREATE TABLE {table} (id UInt64, tags Array(LowCardinality(String)), \
+             note String DEFAULT 'none', id_text String MATERIALIZED toString(id)) \
+             ENGINE = MergeTree ORDER BY id"
+        ))
+        .unwrap();
+
+    let schema = Schema::from_server(&client, &table).unwrap();
+    assert_eq!(
+        schema,
+        Schema::from_type_strings(&[
+            ("id", "UInt64"),
+            ("tags", "Array(LowCardinality(String))"),
+            ("note", "String"),
+        ])
+        .unwrap()
+    );
+    client
+        .insert(
+            &table,
+            &schema,
+            [[
+                Value::UInt64(1),
+                Value::Array(vec![Value::String(b"a".to_vec())]),
+                Value::String(b"n".to_vec()),
+            ]],
+        )
+        .unwrap();
+
+    let schema = Schema::from_query(
+        &client,
+        &format!("SELECT count() AS n, id_text FROM {table} GROUP BY id_text"),
+    )
+    .unwrap();
+    assert_eq!(
+        schema,
+        Schema::from_type_strings(&[("n", "UInt64"), ("id_text", "String")]).unwrap()
+    );
+    client.execute(format!("DROP TABLE {table}")).unwrap();
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_sessions_keep_temporary_tables() {
+    let server = ClickhouseServer::connect();
+    let session = Client::new(server.dsn()).unwrap().new_session();
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    session.create_temporary_table("wanted", &schema).unwrap();
+    session.insert("wanted", &schema, rows(100)).unwrap();
+
+    let reader = session
+        .query(
+            "SELECT count(), max(numbers.number) FROM numbers(1000) AS numbers \
+             INNER JOIN wanted ON wanted.id = numbers.number",
+        )
+        .unwrap();
+    let rows: Vec<_> = reader.rows().map(Result::unwrap).collect();
+    assert_eq!(rows, [vec![Value::UInt64(100), Value::UInt64(99)]]);
+
+    let err = session
+        .new_session()
+        .execute("SELECT * FROM wanted")
+        .unwrap_err();
+    assert!(matches!(err, Error::Server { code: 60, .. }), "{err}");
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_sends_external_tables() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let wanted = ExternalTable::new("wanted", &schema, rows(100)).unwrap();
+    let query = Query::new(
+        "SELECT count(), max(number) FROM numbers(1000) WHERE number IN (SELECT id FROM wanted)",
+    )
+    .external(wanted);
+    let rows: Vec<_> = client
+        .query(query)
+        .unwrap()
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rows, [vec![Value::UInt64(100), Value::UInt64(99)]]);
+}
+
+#[cfg(feature = "http")]
+#[test]
+fn http_client_kills_queries_by_id() {
+    let server = ClickhouseServer::connect();
+    let client = Client::new(server.dsn()).unwrap();
+    let query_id = unique_table("killed_query");
+    let running = std::thread::spawn({
+        let client = client.clone();
+        let query =
+            Query::new("SELECT sleepEachRow(1) FROM numbers(60) SETTINGS max_block_size = 1")
+                .with_query_id(query_id.clone());
+        move || client.execute(query)
+    });
+    let started = Query::new("SELECT count() FROM system.processes WHERE query_id = {id:String}")
+        .param("id", Value::String(query_id.clone().into_bytes()));
+    while client
+        .query(started.clone())
+        .unwrap()
+        .rows()
+        .next()
+        .unwrap()
+        .unwrap()
+        == [Value::UInt64(0)]
+    {
+        std::thread::sleep(std::time::Duration::from_millis(50));
+    }
+
+    client.kill_query(&query_id).unwrap();
+    let err = running.join().unwrap().unwrap_err();
+    assert!(matches!(err, Error::Server { code: 394, .. }), "{err}");
+}
+
+#[cfg(feature = "async-http")]
+#[tokio::test]
+async fn async_client_streams_inserts_and_results() {
+    let server = ClickhouseServer::connect();
+    let client = AsyncClient::new(server.dsn()).unwrap();
+    let table = unique_table("async_http_client");
+    client
+        .execute(format!(
+            "CREATE TABLE {table} (id UInt64, name String) ENGINE = MergeTree ORDER BY id"
+        ))
+        .await
+        .unwrap();
+
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let rows = rows(200_000);
+    let mut insert = client.insert(&table, &schema);
+    insert
+        .send_all(&mut stream::iter(rows.clone()).map(Ok))
+        .await
+        .unwrap();
+    insert.close().await.unwrap();
+
+    let reader = client
+        .query(format!("SELECT id, name FROM {table} ORDER BY id"))
+        .await
+        .unwrap();
+    let decoded: Vec<_> = reader.into_stream().map(Result::unwrap).collect().await;
+    assert_eq!(decoded, rows);
+    assert_eq!(
+        Schema::from_server_async(&client, &table).await.unwrap(),
+        schema
+    );
+
+    let mut insert = client.insert(&format!("{table}_missing"), &schema);
+    insert.send(rows[0].clone()).await.unwrap();
+    let err = insert.close().await.unwrap_err();
+    assert!(matches!(err, Error::Server { code: 60, .. }), "{err}");
+    client.execute(format!("DROP TABLE {table}")).await.unwrap();
+}
+
+#[cfg(feature = "async-http")]
+#[tokio::test]
+async fn insert_buffer_batches_pushed_rows() {
+    let server = ClickhouseServer::connect();
+    let client = AsyncClient::new(server.dsn()).unwrap();
+    let table = unique_table("insert_buffer");
+    client
+        .execute(format!(
+            "CREATE TABLE {table} (id UInt64, name String) ENGINE = MergeTree ORDER BY id"
+        ))
+        .await
+        .unwrap();
+
+    let schema = Schema::from_type_strings(&[("id", "UInt64"), ("name", "String")]).unwrap();
+    let options = InsertBufferOptions {
+        max_rows: 1_000,
+        ..InsertBufferOptions::default()
+    };
+    let buffer = InsertBuffer::new(client.clone(), options, |failed| {
+        panic!("flush of {} rows failed: {}", failed.rows, failed.error)
+    });
+    let rows = rows(2_500);
+    for row in &rows {
+        buffer.push(&table, &schema, row).await.unwrap();
+    }
+    assert_eq!(buffer.pending_rows(), 500);
+    buffer.flush().await.unwrap();
+
+    let reader = client
+        .query(format!("SELECT id, name FROM {table} ORDER BY id"))
+        .await
+        .unwrap();
+    let decoded: Vec<_> = reader.into_stream().map(Result::unwrap).collect().await;
+    assert_eq!(decoded, rows);
+    client.execute(format!("DROP TABLE {table}")).await.unwrap();
+}
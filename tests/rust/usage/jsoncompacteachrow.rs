@@ -0,0 +1,77 @@
+use clickhouse_rowbinary::{
+    Error, JsonCompactEachRowReader, JsonCompactEachRowWriter, JsonEachRowWriter, Schema, Value,
+};
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "Int64"),
+        ("at", "DateTime('UTC')"),
+        ("amount", "Nullable(Decimal(18, 4))"),
+        ("point", "Tuple(x Float32, y Float32)"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn rows_are_arrays_in_column_order() {
+    let rows = vec![
+        vec![
+            Value::Int64(-5),
+            Value::DateTime(86_400),
+            Value::Nullable(Some(Box::new(Value::Decimal64(12_345)))),
+            Value::Tuple(vec![Value::Float32(1.5), Value::Float32(f32::NAN)]),
+        ],
+        vec![
+            Value::Int64(6),
+            Value::DateTime(0),
+            Value::Nullable(None),
+            Value::Tuple(vec![Value::Float32(0.0), Value::Float32(-2.0)]),
+        ],
+    ];
+    let mut writer = JsonCompactEachRowWriter::new(Vec::new(), schema());
+    writer.write_rows(&rows).unwrap();
+    let payload = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        payload,
+        concat!(
+            r#"["-5","86400",1.2345,{"x":1.5,"y":null}]"#,
+            "\n",
+            r#"["6","0",null,{"x":0,"y":-2}]"#,
+            "\n",
+        )
+    );
+
+    // The same values decode identically from either JSON layout.
+    let mut each_row = JsonEachRowWriter::new(Vec::new(), schema());
+    each_row.write_rows(&rows[1..]).unwrap();
+    assert!(
+        each_row
+            .into_inner()
+            .starts_with(br#"{"id":"6","at":"0","amount":null"#)
+    );
+
+    let input =
+        "[-5, 86400, \"1.2345\", [1.5, null]]\n\n[6,\"1970-01-01 00:00:00\",null,{\"y\":-2}]\n";
+    let mut expected = rows;
+    expected[0][3] = Value::Tuple(vec![Value::Float32(1.5), Value::Float32(0.0)]);
+    let reader = JsonCompactEachRowReader::new(input.as_bytes(), schema());
+    assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), expected);
+}
+
+#[test]
+fn rows_must_carry_every_column() {
+    for line in ["[1, 0, null]", r#"{"id":1}"#, "[1, 0, null, [0, 0], 5]"] {
+        let mut reader = JsonCompactEachRowReader::new(line.as_bytes(), schema());
+        assert!(
+            matches!(reader.read_row(), Err(Error::InvalidValue(_))),
+            "{line}"
+        );
+    }
+
+    let mut writer = JsonCompactEachRowWriter::new(Vec::new(), schema());
+    assert!(matches!(
+        writer.write_row(&[Value::Int64(1)]),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(writer.get_ref().is_empty());
+}
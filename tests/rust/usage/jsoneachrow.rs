@@ -0,0 +1,204 @@
+use clickhouse_rowbinary::{
+    Error, JsonEachRowReader, JsonEachRowWriter, JsonOptions, Schema, TypeDesc, Value,
+};
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+fn some(value: Value) -> Value {
+    Value::Nullable(Some(Box::new(value)))
+}
+
+fn dynamic(ty: TypeDesc, value: Value) -> Value {
+    Value::Dynamic {
+        ty: Box::new(ty),
+        value: Box::new(value),
+    }
+}
+
+#[test]
+fn rows_are_written_like_the_server_output() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("name", "LowCardinality(String)"),
+        ("price", "Decimal(10, 2)"),
+        ("at", "DateTime64(3, 'UTC')"),
+        ("ratio", "Nullable(Float64)"),
+        ("tags", "Array(Enum8('a' = 1, 'b' = 2))"),
+        ("attrs", "Map(UInt16, Bool)"),
+        ("pair", "Tuple(x Int8, y String)"),
+        ("either", "Variant(String, UInt32)"),
+    ])
+    .unwrap();
+    let rows = vec![
+        vec![
+            Value::UInt64(u64::MAX),
+            string("quote\" slash\\ tab\t \u{1}"),
+            Value::Decimal64(-1_050),
+            Value::DateTime64(1_700_000_000_123),
+            some(Value::Float64(0.5)),
+            Value::Array(vec![Value::Enum8(1), Value::Enum8(2)]),
+            Value::Map(vec![(Value::UInt16(7), Value::Bool(true))]),
+            Value::Tuple(vec![Value::Int8(-3), string("é")]),
+            Value::Variant {
+                index: 1,
+                value: Box::new(Value::UInt32(9)),
+            },
+        ],
+        vec![
+            Value::UInt64(0),
+            string(""),
+            Value::Decimal64(0),
+            Value::DateTime64(0),
+            Value::Nullable(None),
+            Value::Array(Vec::new()),
+            Value::Map(Vec::new()),
+            Value::Tuple(vec![Value::Int8(0), string("")]),
+            Value::VariantNull,
+        ],
+    ];
+    let mut writer = JsonEachRowWriter::new(Vec::new(), schema.clone());
+    writer.write_rows(&rows).unwrap();
+    let payload = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        payload,
+        concat!(
+            r#"{"id":"18446744073709551615","name":"quote\" slash\\ tab\t \u0001","#,
+            r#""price":-10.5,"at":"1700000000.123","ratio":0.5,"tags":["a","b"],"#,
+            r#""attrs":{"7":true},"pair":{"x":-3,"y":"é"},"either":9}"#,
+            "\n",
+            r#"{"id":"0","name":"","price":0,"at":"0.000","ratio":null,"#,
+            r#""tags":[],"attrs":{},"pair":{"x":0,"y":""},"either":null}"#,
+            "\n",
+        )
+    );
+
+    let reader = JsonEachRowReader::new(payload.as_bytes(), schema.clone());
+    assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), rows);
+
+    let options = JsonOptions {
+        quote_64bit_integers: false,
+        named_tuples_as_objects: false,
+        ..JsonOptions::default()
+    };
+    let mut writer = JsonEachRowWriter::with_options(Vec::new(), schema, options);
+    writer.write_row(&rows[1]).unwrap();
+    let line = String::from_utf8(writer.into_inner()).unwrap();
+    assert!(line.starts_with(r#"{"id":0,"#));
+    assert!(line.contains(r#""pair":[0,""]"#));
+}
+
+#[test]
+fn input_is_typed_by_the_schema() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("big", "Int128"),
+        ("label", "String"),
+        ("day", "Date"),
+        ("either", "Variant(String, Int64)"),
+        ("any", "Dynamic"),
+        ("doc", "JSON(a.b UInt8)"),
+        ("point", "Tuple(x Float64, y Float64)"),
+    ])
+    .unwrap();
+    let payload = concat!(
+        r#"{"doc":{"a":{"b":"5","c":[1,2.5]},"z":null},"id":"7","big":-170141183460469231731687303715884105728,"#,
+        r#""label":12.50,"day":"2024-01-01","either":3,"any":[1,null],"unknown":{"x":1},"point":{"y":2}}"#,
+        "\n\n",
+        r#"{"label":"😀\n","either":"3","any":"s","id":null}"#,
+        "\n",
+    );
+    let mut reader = JsonEachRowReader::new(payload.as_bytes(), schema);
+    assert_eq!(
+        reader.read_row().unwrap().unwrap(),
+        [
+            Value::UInt32(7),
+            Value::Int128(i128::MIN),
+            string("12.50"),
+            Value::Date(19_723),
+            Value::Variant {
+                index: 0,
+                value: Box::new(Value::Int64(3)),
+            },
+            dynamic(
+                TypeDesc::Array(Box::new(TypeDesc::Nullable(Box::new(TypeDesc::Int64)))),
+                Value::Array(vec![some(Value::Int64(1)), Value::Nullable(None)]),
+            ),
+            Value::JsonObject(vec![
+                ("a.b".to_string(), Value::UInt8(5)),
+                (
+                    "a.c".to_string(),
+                    dynamic(
+                        TypeDesc::Array(Box::new(TypeDesc::Float64)),
+                        Value::Array(vec![Value::Float64(1.0), Value::Float64(2.5)]),
+                    ),
+                ),
+            ]),
+            Value::Tuple(vec![Value::Float64(0.0), Value::Float64(2.0)]),
+        ]
+    );
+    assert_eq!(
+        reader.read_row().unwrap().unwrap(),
+        [
+            Value::UInt32(0),
+            Value::Int128(0),
+            string("😀\n"),
+            Value::Date(0),
+            Value::Variant {
+                index: 1,
+                value: Box::new(string("3")),
+            },
+            dynamic(TypeDesc::String, string("s")),
+            Value::JsonObject(Vec::new()),
+            Value::Tuple(vec![Value::Float64(0.0), Value::Float64(0.0)]),
+        ]
+    );
+    assert!(reader.read_row().unwrap().is_none());
+}
+
+#[test]
+fn invalid_input_is_rejected() {
+    let schema = Schema::from_type_strings(&[("n", "UInt8"), ("d", "Decimal(5, 1)")]).unwrap();
+    let read = |line: &str, options: JsonOptions| {
+        JsonEachRowReader::with_options(line.as_bytes(), schema.clone(), options).read_row()
+    };
+    let strict = JsonOptions {
+        skip_unknown_fields: false,
+        ..JsonOptions::default()
+    };
+    assert!(matches!(
+        read(r#"{"n":1,"x":2}"#, strict),
+        Err(Error::UnknownColumn(name)) if name == "x"
+    ));
+    for line in [
+        r#"{"n":1,}"#,
+        r#"{"n":01}"#,
+        "[1]",
+        r#"{"n":1} x"#,
+        r#"{"n":1,"n":2}"#,
+        r#"{"n":256}"#,
+        r#"{"d":1.25}"#,
+        r#"{"n":"\ud800"}"#,
+        &format!("{}{}", "[".repeat(300), "]".repeat(300)),
+    ] {
+        assert!(
+            matches!(
+                read(line, JsonOptions::default()),
+                Err(Error::InvalidValue(_))
+            ),
+            "{line}"
+        );
+    }
+
+    let mut writer = JsonEachRowWriter::new(Vec::new(), schema);
+    assert!(matches!(
+        writer.write_row(&[Value::UInt8(1), string("x")]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        writer.write_row(&[Value::UInt8(1)]),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(writer.get_ref().is_empty());
+}
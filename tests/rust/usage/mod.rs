@@ -1,5 +1,31 @@
+mod avro;
+mod binary_types;
+mod borrowed_reader;
+mod column_codecs;
+mod columnar;
+mod compression;
+mod csv;
+mod header;
+mod header_policy;
+#[cfg(any(feature = "http", feature = "async-http"))]
+mod http;
+mod jsoncompacteachrow;
+mod jsoneachrow;
+mod native;
+mod push_decoder;
 mod read_compressed;
 mod reuse;
+mod row_binary_with_defaults;
+mod row_view;
+mod schema;
 mod seekable_reader_writer;
 mod seekable_reader_writer_integration;
+mod strict_reader;
+#[cfg(feature = "tcp")]
+mod tcp;
 mod threaded_writer;
+mod tsv;
+mod value_conversions;
+mod value_reader;
+mod value_writer;
+mod values;
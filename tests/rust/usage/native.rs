@@ -0,0 +1,188 @@
+use clickhouse_rowbinary::{
+    Block, BlockInfo, Error, NativeOptions, NativeReader, NativeWriter, Schema, Value,
+};
+
+fn encode(block: &Block, options: NativeOptions) -> Vec<u8> {
+    let mut writer = NativeWriter::with_options(Vec::new(), options);
+    writer.write_block(block).unwrap();
+    writer.into_inner()
+}
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+fn some(value: Value) -> Value {
+    Value::Nullable(Some(Box::new(value)))
+}
+
+#[test]
+fn blocks_use_the_native_layout() {
+    let schema =
+        Schema::from_type_strings(&[("id", "UInt32"), ("name", "Nullable(String)")]).unwrap();
+    let block = Block::from_rows(
+        schema,
+        [
+            vec![Value::UInt32(1), some(string("a"))],
+            vec![Value::UInt32(2), Value::Nullable(None)],
+        ],
+    )
+    .unwrap();
+
+    let mut expected = vec![2, 2];
+    expected.extend(b"\x02id\x06UInt32");
+    expected.extend([1, 0, 0, 0, 2, 0, 0, 0]);
+    expected.extend(b"\x04name\x10Nullable(String)");
+    expected.extend([0, 1, 1, b'a', 0]);
+    assert_eq!(encode(&block, NativeOptions::default()), expected);
+}
+
+#[test]
+fn low_cardinality_columns_carry_a_dictionary() {
+    let schema = Schema::from_type_strings(&[
+        ("tag", "LowCardinality(String)"),
+        ("opt", "LowCardinality(Nullable(String))"),
+    ])
+    .unwrap();
+    let block = Block::from_rows(
+        schema,
+        [
+            vec![string("x"), Value::Nullable(None)],
+            vec![string("x"), some(string("y"))],
+        ],
+    )
+    .unwrap();
+    let payload = encode(&block, NativeOptions::default());
+
+    let mut tag = 1_u64.to_le_bytes().to_vec();
+    tag.extend(0x200_u64.to_le_bytes());
+    tag.extend(1_u64.to_le_bytes());
+    tag.extend(b"\x01x");
+    tag.extend(2_u64.to_le_bytes());
+    tag.extend([0, 0]);
+    let start = 2 + 4 + "LowCardinality(String)".len() + 1;
+    assert_eq!(&payload[start..start + tag.len()], tag.as_slice());
+
+    let mut reader = NativeReader::new(payload.as_slice());
+    assert_eq!(reader.read_block().unwrap(), Some(block));
+}
+
+#[test]
+fn composite_columns_round_trip() {
+    let schema = Schema::from_type_strings(&[
+        ("tags", "Array(LowCardinality(String))"),
+        ("attrs", "Map(String, Nullable(UInt8))"),
+        ("pair", "Tuple(a Int16, b Array(String))"),
+        ("either", "Variant(String, UInt64)"),
+        ("at", "Nullable(DateTime64(3, 'UTC'))"),
+        ("point", "Point"),
+        ("nested", "Nested(k String, v UInt8)"),
+    ])
+    .unwrap();
+    let rows = vec![
+        vec![
+            Value::Array(vec![string("a"), string("b"), string("a")]),
+            Value::Map(vec![(string("k"), some(Value::UInt8(1)))]),
+            Value::Tuple(vec![Value::Int16(-1), Value::Array(vec![string("x")])]),
+            Value::Variant {
+                index: 1,
+                value: Box::new(Value::UInt64(7)),
+            },
+            some(Value::DateTime64(1_700_000_000_123)),
+            Value::Tuple(vec![Value::Float64(1.0), Value::Float64(2.0)]),
+            Value::Array(vec![Value::Tuple(vec![string("k"), Value::UInt8(3)])]),
+        ],
+        vec![
+            Value::Array(Vec::new()),
+            Value::Map(vec![(string("n"), Value::Nullable(None))]),
+            Value::Tuple(vec![Value::Int16(2), Value::Array(Vec::new())]),
+            Value::VariantNull,
+            Value::Nullable(None),
+            Value::Tuple(vec![Value::Float64(0.0), Value::Float64(-1.5)]),
+            Value::Array(Vec::new()),
+        ],
+        vec![
+            Value::Array(vec![string("c")]),
+            Value::Map(Vec::new()),
+            Value::Tuple(vec![Value::Int16(3), Value::Array(vec![string("y")])]),
+            Value::Variant {
+                index: 0,
+                value: Box::new(string("s")),
+            },
+            some(Value::DateTime64(0)),
+            Value::Tuple(vec![Value::Float64(3.0), Value::Float64(4.0)]),
+            Value::Array(Vec::new()),
+        ],
+    ];
+    let block = Block::from_rows(schema.clone(), rows.clone()).unwrap();
+    let empty = Block::from_rows(schema, []).unwrap();
+
+    let mut writer = NativeWriter::new(Vec::new());
+    writer.write_block(&block).unwrap();
+    writer.write_block(&empty).unwrap();
+    let payload = writer.into_inner();
+
+    let blocks: Vec<Block> = NativeReader::new(payload.as_slice())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(blocks, [block, empty]);
+    assert_eq!(blocks[0].num_rows(), 3);
+    assert_eq!(blocks[0].clone().into_rows(), rows);
+    assert_eq!(blocks[0].column("either").unwrap()[1], Value::VariantNull);
+}
+
+#[test]
+fn tcp_layout_carries_block_info_and_serialization_flags() {
+    let options = NativeOptions {
+        block_info: true,
+        custom_serialization: true,
+        ..NativeOptions::default()
+    };
+    let schema = Schema::from_type_strings(&[("n", "UInt8")]).unwrap();
+    let mut block = Block::from_rows(schema, [vec![Value::UInt8(5)]]).unwrap();
+    block.info = BlockInfo {
+        is_overflows: true,
+        bucket_num: 3,
+    };
+    let payload = encode(&block, options.clone());
+
+    let mut expected = vec![1, 1, 2, 3, 0, 0, 0, 0, 1, 1];
+    expected.extend(b"\x01n\x05UInt8");
+    expected.extend([0, 5]);
+    assert_eq!(payload, expected);
+
+    let mut reader = NativeReader::with_options(payload.as_slice(), options);
+    assert_eq!(reader.read_block().unwrap(), Some(block));
+    assert!(reader.read_block().unwrap().is_none());
+}
+
+#[test]
+fn malformed_blocks_are_rejected() {
+    let schema = Schema::from_type_strings(&[("id", "UInt32")]).unwrap();
+    let block = Block::from_rows(schema.clone(), [vec![Value::UInt32(1)]]).unwrap();
+    let payload = encode(&block, NativeOptions::default());
+    let truncated = &payload[..payload.len() - 1];
+    assert!(matches!(
+        NativeReader::new(truncated).read_block(),
+        Err(Error::Io(_))
+    ));
+
+    assert!(matches!(
+        Block::from_rows(schema.clone(), [Vec::new()]),
+        Err(Error::InvalidValue(_))
+    ));
+    let bad = Block::new(schema, vec![vec![string("x")]]).unwrap();
+    let mut writer = NativeWriter::new(Vec::new());
+    assert!(matches!(
+        writer.write_block(&bad),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(writer.get_ref().is_empty());
+
+    let dynamic = Schema::from_type_strings(&[("d", "Dynamic")]).unwrap();
+    let block = Block::from_rows(dynamic, [vec![Value::DynamicNull]]).unwrap();
+    assert!(matches!(
+        writer.write_block(&block),
+        Err(Error::UnsupportedCombination(_))
+    ));
+}
@@ -0,0 +1,164 @@
+use clickhouse_rowbinary::{
+    Error, PushDecoded, ReaderOptions, Row, RowBinaryFormat, RowBinaryPushDecoder,
+    RowBinaryValueWriter, Schema, Value, io::write_uvarint,
+};
+
+fn sample() -> (Schema, Vec<Row>, Vec<u8>) {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("name", "String"),
+        ("tags", "Array(Nullable(String))"),
+    ])
+    .unwrap();
+    let rows: Vec<Row> = (0..5_u32)
+        .map(|id| {
+            vec![
+                Value::UInt32(id),
+                Value::String(format!("name-{id}").into_bytes()),
+                Value::Array(vec![
+                    Value::Nullable(None),
+                    Value::Nullable(Some(Box::new(Value::String(b"tag".to_vec())))),
+                ]),
+            ]
+        })
+        .collect();
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema.clone(),
+    );
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    (schema, rows, writer.into_inner())
+}
+
+fn drain(decoder: &mut RowBinaryPushDecoder, out: &mut Vec<Row>) {
+    while let PushDecoded::Row(row) = decoder.next_row().unwrap() {
+        out.push(row);
+    }
+}
+
+#[test]
+fn push_decoder_handles_rows_split_across_chunks() {
+    let (schema, rows, payload) = sample();
+    for chunk_size in [1, 3, 7, payload.len()] {
+        let mut decoder = RowBinaryPushDecoder::new(
+            RowBinaryFormat::RowBinaryWithNamesAndTypes,
+            None,
+            ReaderOptions::default(),
+        );
+        let mut seen = Vec::new();
+        for chunk in payload.chunks(chunk_size) {
+            decoder.feed(chunk);
+            drain(&mut decoder, &mut seen);
+        }
+        decoder.finish().unwrap();
+        assert_eq!(decoder.schema(), Some(&schema));
+        assert_eq!(seen, rows);
+    }
+}
+
+#[test]
+fn push_decoder_reports_incomplete_input_on_finish() {
+    let (_, rows, payload) = sample();
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        None,
+        ReaderOptions::default(),
+    );
+    assert_eq!(decoder.next_row().unwrap(), PushDecoded::NeedMoreData);
+    assert!(decoder.finish().is_err());
+
+    decoder.feed(&payload[..payload.len() - 4]);
+    let mut seen = Vec::new();
+    drain(&mut decoder, &mut seen);
+    assert_eq!(seen, rows[..4]);
+    assert!(decoder.finish().is_err());
+
+    decoder.feed(&payload[payload.len() - 4..]);
+    drain(&mut decoder, &mut seen);
+    assert_eq!(seen, rows);
+    decoder.finish().unwrap();
+}
+
+#[test]
+fn push_decoder_surfaces_malformed_rows() {
+    let schema = Schema::from_type_strings(&[("flag", "Bool")]).unwrap();
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinary,
+        Some(schema),
+        ReaderOptions::default(),
+    );
+    decoder.feed(&[1, 5]);
+    assert_eq!(
+        decoder.next_row().unwrap(),
+        PushDecoded::Row(vec![Value::Bool(true)])
+    );
+    assert!(decoder.next_row().is_err());
+}
+
+#[test]
+fn push_decoder_waits_for_large_values_without_decoding_them_again() {
+    let schema = Schema::from_type_strings(&[("blob", "String")]).unwrap();
+    let row = vec![Value::String(vec![b'x'; 16 * 1024 * 1024])];
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_rows(std::slice::from_ref(&row)).unwrap();
+    let payload = writer.into_inner();
+
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinary,
+        Some(schema),
+        ReaderOptions::default(),
+    );
+    let mut seen = Vec::new();
+    for chunk in payload.chunks(8 * 1024) {
+        decoder.feed(chunk);
+        drain(&mut decoder, &mut seen);
+    }
+    decoder.finish().unwrap();
+    assert_eq!(seen, [row]);
+}
+
+#[test]
+fn push_decoder_does_not_allocate_for_corrupt_lengths() {
+    let schema = Schema::from_type_strings(&[("blob", "String")]).unwrap();
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinary,
+        Some(schema),
+        ReaderOptions::default(),
+    );
+    let mut payload = Vec::new();
+    write_uvarint(1 << 20, &mut payload).unwrap();
+    payload.extend_from_slice(b"abc");
+    decoder.feed(&payload);
+    assert_eq!(decoder.next_row().unwrap(), PushDecoded::NeedMoreData);
+    decoder.feed(b"def");
+    assert_eq!(decoder.next_row().unwrap(), PushDecoded::NeedMoreData);
+    assert!(decoder.finish().is_err());
+
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinary,
+        Some(Schema::from_type_strings(&[("blob", "String")]).unwrap()),
+        ReaderOptions::default(),
+    );
+    let mut payload = Vec::new();
+    write_uvarint(u64::MAX >> 1, &mut payload).unwrap();
+    payload.extend_from_slice(b"abc");
+    decoder.feed(&payload);
+    assert!(matches!(decoder.next_row(), Err(Error::Overflow(_))));
+}
+
+#[test]
+fn push_decoder_rejects_lengths_that_overflow_the_buffer_size() {
+    let schema = Schema::from_type_strings(&[("blob", "String")]).unwrap();
+    let mut decoder = RowBinaryPushDecoder::new(
+        RowBinaryFormat::RowBinary,
+        Some(schema),
+        ReaderOptions::default(),
+    );
+    decoder.feed(&[
+        0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x01, 0x61,
+    ]);
+    assert!(matches!(decoder.next_row(), Err(Error::Overflow(_))));
+}
@@ -63,6 +63,30 @@ fn rows_iterator_reads_all_rows() {
     assert_eq!(decoded, rows);
 }
 
+#[test]
+fn rows_iterator_stops_after_mid_stream_error() {
+    let schema = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
+    let rows: Vec<Row> = vec![
+        vec![Value::UInt8(1), Value::String(b"alpha".to_vec())],
+        vec![Value::UInt8(2), Value::String(b"beta".to_vec())],
+    ];
+
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_rows(&rows).unwrap();
+    let mut payload = writer.into_inner();
+    payload.truncate(payload.len() - 2);
+
+    let reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema)
+            .unwrap();
+    let mut iter = reader.rows();
+    assert_eq!(iter.next().unwrap().unwrap(), rows[0]);
+    assert!(iter.next().unwrap().is_err());
+    assert!(iter.next().is_none());
+    assert!(iter.next().is_none());
+}
+
 #[test]
 fn take_inner_and_reset_reuse_buffer() {
     let schema = Schema::from_type_strings(&[("id", "UInt8"), ("name", "String")]).unwrap();
@@ -98,3 +122,40 @@ fn take_inner_and_reset_reuse_buffer() {
     );
     assert_eq!(decoded, vec![rows[1].clone()]);
 }
+
+#[test]
+fn value_writer_reuses_buffers_across_batches() {
+    let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+    let batch: Vec<Row> = (0..100_u32)
+        .map(|id| vec![Value::UInt32(id), Value::String(b"name".to_vec())])
+        .collect();
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::with_capacity(4096),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema,
+    );
+    let mut payload = Vec::with_capacity(4096);
+    let mut allocations = Vec::new();
+    for _ in 0..4 {
+        writer.write_header().unwrap();
+        writer.write_rows(&batch).unwrap();
+        writer.swap_buffer(&mut payload);
+        allocations.push(payload.as_ptr());
+        let decoded: Vec<Row> = RowBinaryValueReader::from_header(payload.as_slice())
+            .unwrap()
+            .rows()
+            .collect::<Result<_, _>>()
+            .unwrap();
+        assert_eq!(decoded, batch);
+    }
+    assert_eq!(allocations[0], allocations[2]);
+    assert_eq!(allocations[1], allocations[3]);
+
+    writer.write_header().unwrap();
+    let capacity = writer.get_ref().capacity();
+    writer.clear();
+    assert!(writer.get_ref().is_empty());
+    assert_eq!(writer.get_ref().capacity(), capacity);
+    writer.write_header().unwrap();
+    assert!(!writer.get_ref().is_empty());
+}
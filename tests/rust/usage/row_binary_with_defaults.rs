@@ -0,0 +1,99 @@
+use clickhouse_rowbinary::{
+    Error, PushDecoded, ReaderOptions, RowBinaryFormat, RowBinaryPushDecoder, RowBinaryRefReader,
+    RowBinaryValueReader, RowBinaryValueWriter, Schema, Value, ValueRef,
+};
+
+const FORMAT: RowBinaryFormat = RowBinaryFormat::RowBinaryWithDefaults;
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("note", "Nullable(String)"),
+        ("level", "Enum8('info' = 1, 'warn' = 2)"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn omitted_values_are_flagged_for_the_server_default() {
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), FORMAT, schema());
+    writer.write_header().unwrap();
+    writer
+        .write_row_with_defaults(&[Some(Value::UInt32(1)), None, None])
+        .unwrap();
+    writer
+        .write_row(&[
+            Value::UInt32(2),
+            Value::Nullable(Some(Box::new(Value::String(b"x".to_vec())))),
+            Value::Enum8(2),
+        ])
+        .unwrap();
+    let payload = writer.into_inner();
+    assert_eq!(
+        payload,
+        [0, 1, 0, 0, 0, 1, 1, 0, 2, 0, 0, 0, 0, 0, 1, b'x', 0, 2]
+    );
+
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), FORMAT, schema()).unwrap();
+    assert_eq!(
+        reader.read_row().unwrap().unwrap(),
+        [Value::UInt32(1), Value::Nullable(None), Value::Enum8(1)]
+    );
+    assert_eq!(reader.skip_rows(2).unwrap(), 1);
+}
+
+#[test]
+fn every_reader_understands_default_flags() {
+    let payload = [0, 7, 0, 0, 0, 1, 1, 0, 8, 0, 0, 0, 0, 1, 0, 2];
+    let expected = vec![
+        vec![Value::UInt32(7), Value::Nullable(None), Value::Enum8(1)],
+        vec![Value::UInt32(8), Value::Nullable(None), Value::Enum8(2)],
+    ];
+
+    let reader = RowBinaryValueReader::with_schema(payload.as_slice(), FORMAT, schema()).unwrap();
+    let rows: Vec<_> = reader.rows().collect::<Result<_, _>>().unwrap();
+    assert_eq!(rows, expected);
+
+    let mut reader = RowBinaryRefReader::new(&payload, FORMAT, Some(schema())).unwrap();
+    let mut rows = Vec::new();
+    while let Some(row) = reader.read_row().unwrap() {
+        rows.push(row.iter().map(ValueRef::to_value).collect::<Vec<_>>());
+    }
+    assert_eq!(rows, expected);
+
+    let mut decoder = RowBinaryPushDecoder::new(FORMAT, Some(schema()), ReaderOptions::default());
+    let mut rows = Vec::new();
+    for chunk in payload.chunks(3) {
+        decoder.feed(chunk);
+        while let PushDecoded::Row(row) = decoder.next_row().unwrap() {
+            rows.push(row);
+        }
+    }
+    decoder.finish().unwrap();
+    assert_eq!(rows, expected);
+}
+
+#[test]
+fn default_flags_are_validated() {
+    let mut writer = RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema());
+    assert!(matches!(
+        writer.write_row_with_defaults(&[None, None, None]),
+        Err(Error::UnsupportedCombination(_))
+    ));
+
+    let payload = [2, 1, 0, 0, 0];
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), FORMAT, schema()).unwrap();
+    assert!(matches!(reader.read_row(), Err(Error::InvalidValue(_))));
+
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), FORMAT, schema()).unwrap();
+    let mut ids: Vec<u32> = Vec::new();
+    let mut notes: Vec<Option<String>> = Vec::new();
+    let mut levels: Vec<i8> = Vec::new();
+    assert!(matches!(
+        reader.read_columns(&mut [&mut ids, &mut notes, &mut levels], 1),
+        Err(Error::UnsupportedCombination(_))
+    ));
+}
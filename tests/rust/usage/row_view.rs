@@ -0,0 +1,154 @@
+use clickhouse_rowbinary::{
+    Error, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, RowView, Schema, Value,
+};
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("user_id", "UInt32"),
+        ("name", "Nullable(String)"),
+        ("score", "Float32"),
+        ("tag", "Variant(String, UInt64)"),
+    ])
+    .unwrap()
+}
+
+fn row(name: Option<&str>) -> Vec<Value> {
+    vec![
+        Value::UInt32(42),
+        Value::Nullable(name.map(|name| Box::new(Value::String(name.as_bytes().to_vec())))),
+        Value::Float32(0.5),
+        Value::Variant {
+            index: 1,
+            value: Box::new(Value::UInt64(7)),
+        },
+    ]
+}
+
+#[test]
+fn row_view_reads_typed_columns_by_name_and_index() {
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema(),
+    );
+    writer.write_header().unwrap();
+    writer.write_rows([row(Some("ada")), row(None)]).unwrap();
+    let payload = writer.into_inner();
+
+    let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    let first = reader.read_row().unwrap().unwrap();
+    let view = RowView::new(reader.schema(), &first).unwrap();
+    assert_eq!(view.get::<u64>("user_id").unwrap(), 42);
+    assert_eq!(view.get::<u8>("user_id").unwrap(), 42);
+    assert_eq!(view.get_opt::<&str>("name").unwrap(), Some("ada"));
+    assert_eq!(view.get::<String>("name").unwrap(), "ada");
+    assert!((view.get::<f64>("score").unwrap() - 0.5).abs() < f64::EPSILON);
+    assert_eq!(view.get::<u64>("tag").unwrap(), 7);
+    assert_eq!(view.get_at::<u32>(0).unwrap(), 42);
+    assert_eq!(view.get_opt_at::<&str>(1).unwrap(), Some("ada"));
+
+    let second = reader.read_row().unwrap().unwrap();
+    let view = RowView::new(reader.schema(), &second).unwrap();
+    assert_eq!(view.get_opt::<&str>("name").unwrap(), None);
+    assert_eq!(view.get::<Option<String>>("name").unwrap(), None);
+    assert!(matches!(
+        view.get::<&str>("name"),
+        Err(Error::TypeMismatch { actual, .. }) if actual == "NULL"
+    ));
+}
+
+#[test]
+fn row_view_reports_unknown_columns_and_conversion_errors() {
+    let schema = schema();
+    let values = row(Some("ada"));
+    let view = RowView::new(&schema, &values).unwrap();
+
+    assert!(
+        matches!(view.get::<u32>("missing"), Err(Error::UnknownColumn(name)) if name == "missing")
+    );
+    assert!(matches!(
+        view.get_at::<u32>(9),
+        Err(Error::UnknownColumn(_))
+    ));
+    assert!(matches!(
+        view.get::<i8>("score"),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        RowView::new(&schema, &values[..2]),
+        Err(Error::InvalidValue(_))
+    ));
+
+    let wide = Schema::from_type_strings(&[("n", "Int64")]).unwrap();
+    let values = [Value::Int64(-1)];
+    let view = RowView::new(&wide, &values).unwrap();
+    assert!(matches!(view.get::<u64>("n"), Err(Error::Overflow(_))));
+    assert_eq!(view.get::<i128>("n").unwrap(), -1);
+}
+
+#[test]
+fn rows_pair_values_with_column_names() {
+    let schema = schema();
+    let values = row(Some("ada"));
+    let view = RowView::new(&schema, &values).unwrap();
+
+    let columns: Vec<&str> = view.iter().map(|(name, _)| name).collect();
+    assert_eq!(columns, ["user_id", "name", "score", "tag"]);
+    let map = view.as_map().unwrap();
+    assert_eq!(map.len(), 4);
+    assert_eq!(map[0], ("user_id", &Value::UInt32(42)));
+
+    let owned = schema.named_row(values.clone()).unwrap();
+    assert_eq!(
+        owned
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>(),
+        ["user_id", "name", "score", "tag"]
+    );
+    assert_eq!(owned[0].1, Value::UInt32(42));
+    assert!(schema.named_row(values[..1].to_vec()).is_err());
+}
+
+#[test]
+fn by_name_views_reject_duplicate_columns() {
+    let schema = Schema::from_type_strings(&[("id", "UInt8"), ("id", "UInt8")]).unwrap();
+    let values = vec![Value::UInt8(1), Value::UInt8(2)];
+    let view = RowView::new(&schema, &values).unwrap();
+    assert_eq!(view.iter().count(), 2);
+    assert!(matches!(view.as_map(), Err(Error::InvalidValue(_))));
+    assert!(matches!(
+        schema.named_row(values.clone()),
+        Err(Error::InvalidValue(_))
+    ));
+}
+
+#[test]
+fn rows_convert_into_tuples() {
+    let schema = schema();
+    let values = row(Some("ada"));
+    let view = RowView::new(&schema, &values).unwrap();
+
+    let (id, name, score, tag): (u64, Option<&str>, f32, u64) = view.try_into().unwrap();
+    assert_eq!((id, name, tag), (42, Some("ada"), 7));
+    assert!((score - 0.5).abs() < f32::EPSILON);
+    assert!(<(u64, String)>::try_from(view).is_err());
+
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema,
+    );
+    writer.write_header().unwrap();
+    writer.write_rows([row(Some("ada")), row(None)]).unwrap();
+    let payload = writer.into_inner();
+    let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    let mut decoded = Vec::new();
+    while let Some(row) = reader
+        .read_typed::<(u32, Option<String>, f64, Value)>()
+        .unwrap()
+    {
+        decoded.push((row.0, row.1));
+    }
+    assert_eq!(decoded, [(42, Some("ada".to_string())), (42, None)]);
+}
@@ -0,0 +1,27 @@
+use clickhouse_rowbinary::Schema;
+
+#[test]
+fn type_strings_ignore_column_clauses() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt64 CODEC(Delta(8), ZSTD(1))"),
+        (
+            "name",
+            "LowCardinality(String) DEFAULT 'n/a' COMMENT 'display name'",
+        ),
+        ("total", "Decimal(18, 2) MATERIALIZED price * qty"),
+        ("label", "Enum8('DEFAULT' = 1, 'b' = 2) ALIAS upper(name)"),
+        ("at", "DateTime('UTC') TTL at + INTERVAL 1 DAY"),
+        ("raw", "String EPHEMERAL"),
+    ])
+    .unwrap();
+    let expected = Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("name", "LowCardinality(String)"),
+        ("total", "Decimal(18, 2)"),
+        ("label", "Enum8('DEFAULT' = 1, 'b' = 2)"),
+        ("at", "DateTime('UTC')"),
+        ("raw", "String"),
+    ])
+    .unwrap();
+    assert_eq!(schema, expected);
+}
@@ -0,0 +1,58 @@
+use clickhouse_rowbinary::{Error, ReaderOptions, RowBinaryFormat, RowBinaryValueReader, Schema};
+
+fn strict_reader(payload: &[u8]) -> RowBinaryValueReader<&[u8]> {
+    let schema = Schema::from_type_strings(&[("id", "UInt32"), ("flag", "Bool")]).unwrap();
+    let options = ReaderOptions {
+        strict: true,
+        ..ReaderOptions::default()
+    };
+    RowBinaryValueReader::with_options(payload, RowBinaryFormat::RowBinary, Some(schema), &options)
+        .unwrap()
+}
+
+#[test]
+fn strict_reader_locates_truncation_and_corruption() {
+    let complete = [1_u8, 0, 0, 0, 1, 2, 0, 0, 0, 0];
+    let mut reader = strict_reader(&complete);
+    assert_eq!(reader.skip_rows(1).unwrap(), 1);
+    assert!(reader.read_row().unwrap().is_some());
+    assert!(reader.read_row().unwrap().is_none());
+
+    let mut reader = strict_reader(&complete[..9]);
+    reader.read_row().unwrap();
+    assert!(matches!(
+        reader.read_row(),
+        Err(Error::Truncated {
+            row: 1,
+            column: 1,
+            offset: 9
+        })
+    ));
+
+    let mut trailing = complete.to_vec();
+    trailing.extend_from_slice(&[0xAB, 0xCD]);
+    let mut reader = strict_reader(&trailing);
+    assert_eq!(reader.skip_rows(2).unwrap(), 2);
+    assert!(matches!(
+        reader.read_row(),
+        Err(Error::Truncated {
+            row: 2,
+            column: 0,
+            offset: 10
+        })
+    ));
+
+    let corrupt = [1_u8, 0, 0, 0, 7];
+    let mut reader = strict_reader(&corrupt);
+    let Err(Error::Corrupt {
+        row,
+        column,
+        offset,
+        source,
+    }) = reader.read_row()
+    else {
+        panic!("expected corrupt row error");
+    };
+    assert_eq!((row, column, offset), (0, 1, 4));
+    assert!(matches!(*source, Error::InvalidValue(_)));
+}
@@ -0,0 +1,150 @@
+use std::{
+    thread,
+    time::{Duration, Instant},
+};
+
+use clickhouse_rowbinary::{
+    Endpoints, Error, LoadBalancing, Schema, Value,
+    tcp::{Client, Pool, PoolOptions},
+};
+
+use crate::common::{ClickhouseServer, unique_table};
+
+#[test]
+fn tcp_client_inserts_and_queries_blocks() {
+    let server = ClickhouseServer::connect();
+    let (addr, options) = server.tcp_endpoint();
+    let mut client = Client::connect_with_options(&addr, options).unwrap();
+    let table = unique_table("tcp_client");
+    client
+        .execute(&format!(
+            "CREATE TABLE {table} (id UInt64, name LowCardinality(String), \
+             note String DEFAULT 'none') ENGINE = MergeTree ORDER BY id"
+        ))
+        .unwrap();
+
+    let schema =
+        Schema::from_type_strings(&[("id", "UInt64"), ("name", "LowCardinality(String)")]).unwrap();
+    let rows: Vec<_> = (0..100_000_u64)
+        .map(|id| {
+            vec![
+                Value::UInt64(id),
+                Value::String(format!("n{}", id % 10).into_bytes()),
+            ]
+        })
+        .collect();
+    let progress = client.insert(&table, &schema, rows.clone()).unwrap();
+    assert_eq!(progress.written_rows, 100_000);
+
+    let reader = client
+        .query(&format!("SELECT id, name, note FROM {table} ORDER BY id"))
+        .unwrap();
+    assert_eq!(reader.schema().len(), 3);
+    let decoded: Vec<_> = reader.rows().map(Result::unwrap).collect();
+    assert_eq!(decoded.len(), rows.len());
+    assert_eq!(decoded[42][..2], rows[42][..]);
+    assert_eq!(decoded[42][2], Value::String(b"none".to_vec()));
+
+    let mut reader = client
+        .query(&format!(
+            "SELECT id % 2 AS odd, count() FROM {table} GROUP BY odd WITH TOTALS ORDER BY odd"
+        ))
+        .unwrap();
+    while reader.read_block().unwrap().is_some() {}
+    assert_eq!(reader.progress().read_rows, 100_000);
+    assert_eq!(reader.profile_info().unwrap().rows, 2);
+    assert_eq!(
+        reader.totals().unwrap().column("count()").unwrap(),
+        [Value::UInt64(100_000)]
+    );
+    client.execute(&format!("DROP TABLE {table}")).unwrap();
+}
+
+#[test]
+fn tcp_client_survives_server_errors() {
+    let server = ClickhouseServer::connect();
+    let (addr, options) = server.tcp_endpoint();
+    let mut client = Client::connect_with_options(&addr, options).unwrap();
+    assert_eq!(client.server().name, "ClickHouse");
+
+    let err = client
+        .query("SELECT * FROM missing_table_for_tcp")
+        .err()
+        .unwrap();
+    assert!(matches!(err, Error::Server { code: 60, .. }), "{err}");
+    client.ping().unwrap();
+
+    // Abandoning a result leaves it to be drained by the next query.
+    let mut reader = client.query("SELECT number FROM numbers(1000000)").unwrap();
+    reader.read_block().unwrap().unwrap();
+    drop(reader);
+    let rows: Vec<_> = client
+        .query("SELECT 1")
+        .unwrap()
+        .rows()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(rows, [vec![Value::UInt8(1)]]);
+}
+
+#[test]
+fn tcp_pool_shares_connections_between_threads() {
+    let server = ClickhouseServer::connect();
+    let (addr, options) = server.tcp_endpoint();
+    let pool = Pool::new(
+        &addr,
+        options,
+        PoolOptions {
+            max_connections: 2,
+            ..PoolOptions::default()
+        },
+    )
+    .unwrap();
+    let workers: Vec<_> = (0..6_u64)
+        .map(|worker| {
+            let pool = pool.clone();
+            thread::spawn(move || {
+                let mut client = pool.get().unwrap();
+                let rows: Vec<_> = client
+                    .query(&format!("SELECT {worker} + number FROM numbers(3)"))
+                    .unwrap()
+                    .rows()
+                    .map(Result::unwrap)
+                    .collect();
+                assert_eq!(rows.len(), 3);
+            })
+        })
+        .collect();
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    let status = pool.status();
+    assert!(status.connections <= 2, "{status:?}");
+    assert_eq!(status.idle, status.connections);
+}
+
+#[test]
+fn tcp_pool_fails_over_to_a_live_server() {
+    let server = ClickhouseServer::connect();
+    let (addr, options) = server.tcp_endpoint();
+    let endpoints = Endpoints::new(["127.0.0.1:1", &addr], LoadBalancing::FirstAlive).unwrap();
+    let pool = Pool::with_endpoints(endpoints, options, PoolOptions::default()).unwrap();
+    let mut client = pool.get().unwrap();
+    assert_eq!(client.addr(), addr);
+    client.ping().unwrap();
+}
+
+#[test]
+fn tcp_client_cancels_queries() {
+    let server = ClickhouseServer::connect();
+    let (addr, options) = server.tcp_endpoint();
+    let mut client = Client::connect_with_options(&addr, options).unwrap();
+    let started = Instant::now();
+    let mut reader = client
+        .query("SELECT sleepEachRow(1) FROM numbers(60) SETTINGS max_block_size = 1")
+        .unwrap();
+    reader.read_block().unwrap().unwrap();
+    reader.cancel().unwrap();
+    client.ping().unwrap();
+    assert!(started.elapsed() < Duration::from_secs(30));
+}
@@ -0,0 +1,157 @@
+use clickhouse_rowbinary::{Error, Schema, TsvFormat, TsvReader, TsvWriter, TypeDesc, Value};
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+fn some(value: Value) -> Value {
+    Value::Nullable(Some(Box::new(value)))
+}
+
+fn schema() -> Schema {
+    Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("name", "String"),
+        ("note", "Nullable(String)"),
+        ("day", "Date"),
+        ("price", "Decimal(9, 2)"),
+        ("point", "Tuple(x Int8, y Int8)"),
+        ("tags", "Array(Nullable(String))"),
+        ("attrs", "Map(String, UInt8)"),
+        ("level", "Enum8('info' = 1, 'warn' = 2)"),
+    ])
+    .unwrap()
+}
+
+#[test]
+fn lines_follow_the_server_escaping_rules() {
+    let rows = vec![
+        vec![
+            Value::UInt64(1),
+            string("tab\there\nit's \\ done"),
+            Value::Nullable(None),
+            Value::Date(19_723),
+            Value::Decimal32(-250),
+            Value::Tuple(vec![Value::Int8(1), Value::Int8(-2)]),
+            Value::Array(vec![some(string("a\tb")), Value::Nullable(None)]),
+            Value::Map(vec![(string("k"), Value::UInt8(3))]),
+            Value::Enum8(2),
+        ],
+        vec![
+            Value::UInt64(2),
+            string("\\N"),
+            some(string("")),
+            Value::Date(0),
+            Value::Decimal32(0),
+            Value::Tuple(vec![Value::Int8(0), Value::Int8(0)]),
+            Value::Array(Vec::new()),
+            Value::Map(Vec::new()),
+            Value::Enum8(1),
+        ],
+    ];
+    let format = TsvFormat::TabSeparatedWithNames;
+    let mut writer = TsvWriter::new(Vec::new(), format, schema());
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    let payload = String::from_utf8(writer.into_inner()).unwrap();
+    assert_eq!(
+        payload,
+        concat!(
+            "id\tname\tnote\tday\tprice\tpoint\ttags\tattrs\tlevel\n",
+            "1\ttab\\there\\nit\\'s \\\\ done\t\\N\t2024-01-01\t-2.5\t(1,-2)\t",
+            "['a\\tb',NULL]\t{'k':3}\twarn\n",
+            "2\t\\\\N\t\t1970-01-01\t0\t(0,0)\t[]\t{}\tinfo\n",
+        )
+    );
+
+    let reader = TsvReader::new(payload.as_bytes(), format, schema()).unwrap();
+    assert_eq!(reader.collect::<Result<Vec<_>, _>>().unwrap(), rows);
+
+    let mut writer = TsvWriter::new(Vec::new(), TsvFormat::TabSeparated, schema());
+    writer.write_header().unwrap();
+    writer.write_row(&rows[1]).unwrap();
+    let payload = writer.into_inner();
+    assert!(payload.starts_with(b"2\t\\\\N\t\t"));
+    let mut reader = TsvReader::new(payload.as_slice(), TsvFormat::TabSeparated, schema()).unwrap();
+    assert_eq!(reader.read_row().unwrap().as_ref(), Some(&rows[1]));
+    assert_eq!(reader.read_row().unwrap(), None);
+}
+
+#[test]
+fn input_is_typed_by_the_schema() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt32"),
+        ("at", "DateTime('UTC')"),
+        ("score", "Nullable(Float32)"),
+        ("any", "Dynamic"),
+        ("pick", "Variant(String, UInt8)"),
+    ])
+    .unwrap();
+    let format = TsvFormat::TabSeparatedWithNamesAndTypes;
+    let payload = "score\tid\textra_free\n";
+    assert!(matches!(
+        TsvReader::new(payload.as_bytes(), format, schema.clone()),
+        Err(Error::UnknownColumn(name)) if name == "extra_free"
+    ));
+    let payload = "id\tscore\nUInt32\tFloat32\n";
+    assert!(matches!(
+        TsvReader::new(payload.as_bytes(), format, schema.clone()),
+        Err(Error::TypeMismatch { expected, actual })
+            if expected == "Nullable(Float32)" && actual == "Float32"
+    ));
+
+    let payload = concat!(
+        "any\tpick\tid\n",
+        "Dynamic\tVariant(UInt8, String)\tUInt32\n",
+        "[1,2]\tx\t5\n",
+        "\\N\t7\t\\N\n",
+    );
+    let reader = TsvReader::new(payload.as_bytes(), format, schema).unwrap();
+    let rows: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    let defaults = [Value::DateTime(0), Value::Nullable(None)];
+    assert_eq!(rows[0][0], Value::UInt32(5));
+    assert_eq!(rows[0][1..3], defaults);
+    assert_eq!(
+        rows[0][3],
+        Value::Dynamic {
+            ty: Box::new(TypeDesc::Array(Box::new(TypeDesc::Int64))),
+            value: Box::new(Value::Array(vec![Value::Int64(1), Value::Int64(2)])),
+        }
+    );
+    assert_eq!(
+        rows[0][4],
+        Value::Variant {
+            index: 0,
+            value: Box::new(string("x")),
+        }
+    );
+    assert_eq!(rows[1][0], Value::UInt32(0));
+    assert_eq!(rows[1][3], Value::DynamicNull);
+    assert_eq!(
+        rows[1][4],
+        Value::Variant {
+            index: 1,
+            value: Box::new(Value::UInt8(7)),
+        }
+    );
+}
+
+#[test]
+fn malformed_lines_are_rejected() {
+    let schema = Schema::from_type_strings(&[("n", "UInt8"), ("s", "String")]).unwrap();
+    for line in ["1", "1\ta\tb", "x\ta", "300\ta", "1\ta\\"] {
+        let mut reader =
+            TsvReader::new(line.as_bytes(), TsvFormat::TabSeparated, schema.clone()).unwrap();
+        assert!(
+            matches!(reader.read_row(), Err(Error::InvalidValue(_))),
+            "{line:?}"
+        );
+    }
+
+    let mut writer = TsvWriter::new(Vec::new(), TsvFormat::TabSeparated, schema);
+    assert!(matches!(
+        writer.write_row(&[string("1"), string("a")]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(writer.get_ref().is_empty());
+}
@@ -0,0 +1,140 @@
+use std::collections::BTreeMap;
+
+use clickhouse_rowbinary::{
+    Error, FromValue, IntoValue, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter,
+    RowView, Schema, TypeDesc, Value, parse_type_desc,
+};
+
+/// Newtype stored as a `UInt64` column.
+#[derive(Debug, PartialEq)]
+struct UserId(u64);
+
+impl IntoValue for UserId {
+    fn to_value(&self, ty: &TypeDesc) -> clickhouse_rowbinary::Result<Value> {
+        self.0.to_value(ty)
+    }
+}
+
+impl FromValue<'_> for UserId {
+    fn from_value(ty: &TypeDesc, value: &Value) -> clickhouse_rowbinary::Result<Self> {
+        u64::from_value(ty, value).map(UserId)
+    }
+}
+
+fn ty(desc: &str) -> TypeDesc {
+    parse_type_desc(desc).unwrap()
+}
+
+#[test]
+fn into_value_follows_the_column_type() {
+    assert_eq!(7_u8.to_value(&ty("UInt32")).unwrap(), Value::UInt32(7));
+    assert_eq!(
+        "b".to_value(&ty("Enum8('a' = 1, 'b' = 2)")).unwrap(),
+        Value::Enum8(2)
+    );
+    assert_eq!(
+        Some(1.5_f64).to_value(&ty("Nullable(Float32)")).unwrap(),
+        Value::Nullable(Some(Box::new(Value::Float32(1.5))))
+    );
+    assert_eq!(
+        None::<u8>.to_value(&ty("Nullable(UInt8)")).unwrap(),
+        Value::Nullable(None)
+    );
+    assert_eq!(
+        b"raw".to_vec().to_value(&ty("String")).unwrap(),
+        Value::String(b"raw".to_vec())
+    );
+    assert_eq!(
+        (1_u8, "x").to_value(&ty("Tuple(UInt16, String)")).unwrap(),
+        Value::Tuple(vec![Value::UInt16(1), Value::String(b"x".to_vec())])
+    );
+    assert_eq!(
+        9_u64.to_value(&ty("Variant(String, UInt64)")).unwrap(),
+        Value::Variant {
+            index: 1,
+            value: Box::new(Value::UInt64(9)),
+        }
+    );
+    assert_eq!(
+        BTreeMap::from([("k", vec![1_i32])])
+            .to_value(&ty("Map(LowCardinality(String), Array(Int64))"))
+            .unwrap(),
+        Value::Map(vec![(
+            Value::String(b"k".to_vec()),
+            Value::Array(vec![Value::Int64(1)])
+        )])
+    );
+
+    assert!(matches!(
+        300_u16.to_value(&ty("UInt8")),
+        Err(Error::Overflow(_))
+    ));
+    assert!(matches!(
+        "x".to_value(&ty("UInt8")),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(None::<u8>.to_value(&ty("UInt8")).is_err());
+}
+
+#[test]
+fn from_value_uses_the_column_type() {
+    let status = ty("Enum8('active' = 1, 'disabled' = 2)");
+    assert_eq!(
+        String::from_value(&status, &Value::Enum8(2)).unwrap(),
+        "disabled"
+    );
+    assert_eq!(i8::from_value(&status, &Value::Enum8(2)).unwrap(), 2);
+
+    let point = ty("Tuple(id UInt32, tags Array(Nullable(String)))");
+    let value = Value::Tuple(vec![
+        Value::UInt32(3),
+        Value::Array(vec![
+            Value::Nullable(Some(Box::new(Value::String(b"a".to_vec())))),
+            Value::Nullable(None),
+        ]),
+    ]);
+    let (id, tags): (u64, Vec<Option<&str>>) = FromValue::from_value(&point, &value).unwrap();
+    assert_eq!((id, tags), (3, vec![Some("a"), None]));
+
+    let nested = ty("Nested(key String, count UInt8)");
+    let value = Value::Array(vec![Value::Tuple(vec![
+        Value::String(b"k".to_vec()),
+        Value::UInt8(4),
+    ])]);
+    let rows: Vec<(String, u32)> = FromValue::from_value(&nested, &value).unwrap();
+    assert_eq!(rows, [("k".to_string(), 4)]);
+
+    let map = ty("Map(String, UInt64)");
+    let value = Value::Map(vec![(Value::String(b"k".to_vec()), Value::UInt64(1))]);
+    let entries: BTreeMap<String, UserId> = FromValue::from_value(&map, &value).unwrap();
+    assert_eq!(entries, BTreeMap::from([("k".to_string(), UserId(1))]));
+
+    assert!(matches!(
+        u8::from_value(&ty("Nullable(UInt8)"), &Value::Nullable(None)),
+        Err(Error::TypeMismatch { .. })
+    ));
+}
+
+#[test]
+fn user_newtypes_round_trip_through_rows() {
+    let schema = Schema::from_type_strings(&[("id", "Nullable(UInt64)")]).unwrap();
+    let mut writer = RowBinaryValueWriter::new(
+        Vec::new(),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema.clone(),
+    );
+    writer.write_header().unwrap();
+    for id in [Some(UserId(5)), None] {
+        let value = id.to_value(&schema.fields()[0].ty).unwrap();
+        writer.write_row(&[value]).unwrap();
+    }
+    let payload = writer.into_inner();
+
+    let mut reader = RowBinaryValueReader::from_header(payload.as_slice()).unwrap();
+    let mut ids = Vec::new();
+    while let Some(row) = reader.read_row().unwrap() {
+        let view = RowView::new(reader.schema(), &row).unwrap();
+        ids.push(view.get_opt::<UserId>("id").unwrap());
+    }
+    assert_eq!(ids, [Some(UserId(5)), None]);
+}
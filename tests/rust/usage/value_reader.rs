@@ -0,0 +1,88 @@
+use clickhouse_rowbinary::{
+    Row, RowBinaryFormat, RowBinaryValueReader, RowBinaryValueWriter, Schema, Value,
+};
+
+#[test]
+fn skip_and_take_rows_limit_decoding() {
+    let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+    let rows: Vec<Row> = (0..10_u32)
+        .map(|id| {
+            vec![
+                Value::UInt32(id),
+                Value::String(format!("row{id}").into_bytes()),
+            ]
+        })
+        .collect();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_rows(&rows).unwrap();
+    let payload = writer.into_inner();
+
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema)
+            .unwrap();
+    assert_eq!(reader.skip_rows(3).unwrap(), 3);
+    assert_eq!(reader.read_row().unwrap().unwrap(), rows[3]);
+    let taken: Vec<Row> = reader.take_rows(2).collect::<Result<_, _>>().unwrap();
+    assert_eq!(taken, rows[4..6]);
+}
+
+#[test]
+fn skip_rows_stops_at_eof() {
+    let schema = Schema::from_type_strings(&[("id", "UInt8")]).unwrap();
+    let payload = [1_u8, 2, 3];
+    let mut reader =
+        RowBinaryValueReader::with_schema(payload.as_slice(), RowBinaryFormat::RowBinary, schema)
+            .unwrap();
+    assert_eq!(reader.skip_rows(5).unwrap(), 3);
+    assert!(reader.read_row().unwrap().is_none());
+}
+
+#[test]
+fn value_reader_streams_from_buffered_file() {
+    let schema = Schema::from_type_strings(&[("id", "UInt32"), ("name", "String")]).unwrap();
+    let rows: Vec<Row> = (0..1000_u32)
+        .map(|id| {
+            vec![
+                Value::UInt32(id),
+                Value::String(format!("row{id}").into_bytes()),
+            ]
+        })
+        .collect();
+    let path = std::env::temp_dir().join(format!(
+        "clickhouse_rowbinary_stream_{}.bin",
+        std::process::id()
+    ));
+    let mut writer = RowBinaryValueWriter::new(
+        std::io::BufWriter::new(std::fs::File::create(&path).unwrap()),
+        RowBinaryFormat::RowBinaryWithNamesAndTypes,
+        schema.clone(),
+    );
+    writer.write_header().unwrap();
+    writer.write_rows(&rows).unwrap();
+    writer.into_inner().into_inner().unwrap();
+
+    let file = std::io::BufReader::new(std::fs::File::open(&path).unwrap());
+    let reader = RowBinaryValueReader::from_header(file).unwrap();
+    assert_eq!(reader.schema(), &schema);
+    let decoded: Vec<Row> = reader.rows().collect::<Result<_, _>>().unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(decoded, rows);
+}
+
+#[test]
+fn value_reader_leaves_trailing_bytes_unread() {
+    let schema = Schema::from_type_strings(&[("id", "UInt8")]).unwrap();
+    let payload = [1_u8, 2, 3];
+    let mut reader = RowBinaryValueReader::with_schema(
+        std::io::BufReader::new(payload.as_slice()),
+        RowBinaryFormat::RowBinary,
+        schema,
+    )
+    .unwrap();
+    assert_eq!(reader.read_row().unwrap().unwrap(), vec![Value::UInt8(1)]);
+    assert_eq!(reader.get_ref().buffer(), &[2, 3]);
+    let mut rest = Vec::new();
+    std::io::Read::read_to_end(&mut reader.into_inner(), &mut rest).unwrap();
+    assert_eq!(rest, [2, 3]);
+}
@@ -0,0 +1,124 @@
+use clickhouse_rowbinary::{
+    FlushPolicy, Row, RowBinaryFormat, RowBinaryValueWriter, Schema, Value, WriteStats,
+};
+
+/// Sink that records the length of the data at each flush.
+#[derive(Default)]
+struct FlushLog {
+    data: Vec<u8>,
+    flushes: Vec<usize>,
+}
+
+impl std::io::Write for FlushLog {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.data.extend_from_slice(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.flushes.push(self.data.len());
+        Ok(())
+    }
+}
+
+fn flushes_with(policy: FlushPolicy) -> Vec<usize> {
+    let schema = Schema::from_type_strings(&[("id", "UInt32")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(FlushLog::default(), RowBinaryFormat::RowBinary, schema);
+    writer.set_flush_policy(policy);
+    for id in 0..5_u32 {
+        writer.write_row(&[Value::UInt32(id)]).unwrap();
+    }
+    writer.write_row_bytes(&[5, 0, 0, 0]).unwrap();
+    writer.into_inner().flushes
+}
+
+#[test]
+fn value_writer_applies_flush_policy() {
+    assert!(flushes_with(FlushPolicy::Manual).is_empty());
+    assert_eq!(flushes_with(FlushPolicy::EveryRows(2)), [8, 16, 24]);
+    assert_eq!(flushes_with(FlushPolicy::EveryBytes(10)), [12, 24]);
+}
+
+#[test]
+fn write_batch_reports_rows_and_bytes() {
+    let schema = Schema::from_type_strings(&[("id", "UInt16"), ("name", "String")]).unwrap();
+    let rows: Vec<Row> = vec![
+        vec![Value::UInt16(1), Value::String(b"a".to_vec())],
+        vec![Value::UInt16(2), Value::String(b"bcd".to_vec())],
+    ];
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinaryWithNames, schema);
+    writer.write_header().unwrap();
+    let header_len = writer.get_ref().len();
+    let stats = writer.write_batch(&rows).unwrap();
+    assert_eq!(stats, WriteStats { rows: 2, bytes: 10 });
+    assert_eq!(writer.get_ref().len(), header_len + 10);
+
+    let short_row = vec![Value::UInt16(3)];
+    assert!(writer.write_rows([&rows[0], &short_row]).is_err());
+    assert_eq!(writer.get_ref().len(), header_len + 14);
+
+    let nested = Schema::from_type_strings(&[("n", "Nested(a UInt8)")]).unwrap();
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinaryWithDefaults, nested);
+    let rows = [vec![Value::Array(Vec::new())]];
+    assert!(writer.write_batch(&rows).is_err());
+    assert!(writer.get_ref().is_empty());
+}
+
+#[test]
+fn encoded_size_matches_written_bytes() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "UInt64"),
+        ("label", "LowCardinality(Nullable(String))"),
+        ("code", "FixedString(3)"),
+        ("price", "Decimal(10, 2)"),
+        ("tags", "Array(Tuple(String, Nullable(Int32)))"),
+        ("attrs", "Map(String, Array(UInt8))"),
+        ("choice", "Variant(String, UInt64)"),
+        ("extra", "Dynamic"),
+        ("n", "Nested(a UInt8, b String)"),
+    ])
+    .unwrap();
+    let long_text = Value::String(vec![b'x'; 300]);
+    let row = vec![
+        Value::UInt64(7),
+        Value::Nullable(Some(Box::new(long_text.clone()))),
+        Value::FixedString(b"abc".to_vec()),
+        Value::Decimal64(12_345),
+        Value::Array(vec![
+            Value::Tuple(vec![Value::String(b"a".to_vec()), Value::Nullable(None)]),
+            Value::Tuple(vec![
+                Value::String(b"bc".to_vec()),
+                Value::Nullable(Some(Box::new(Value::Int32(-1)))),
+            ]),
+        ]),
+        Value::Map(vec![(
+            Value::String(b"k".to_vec()),
+            Value::Array(vec![Value::UInt8(1); 200]),
+        )]),
+        Value::UInt64(3),
+        Value::Dynamic {
+            ty: Box::new(clickhouse_rowbinary::TypeDesc::String),
+            value: Box::new(long_text),
+        },
+        Value::Array(vec![
+            Value::Tuple(vec![Value::UInt8(1), Value::String(b"one".to_vec())]),
+            Value::Tuple(vec![Value::UInt8(2), Value::String(b"two".to_vec())]),
+        ]),
+    ];
+
+    let mut writer =
+        RowBinaryValueWriter::new(Vec::new(), RowBinaryFormat::RowBinary, schema.clone());
+    writer.write_row(&row).unwrap();
+    assert_eq!(schema.encoded_size(&row).unwrap(), writer.get_ref().len());
+    assert_eq!(
+        row[1].encoded_size(&schema.fields()[1].ty).unwrap(),
+        1 + 2 + 300
+    );
+
+    assert!(schema.encoded_size(&row[..2]).is_err());
+    let mismatched = Value::Array(vec![Value::UInt8(1)]);
+    assert!(mismatched.encoded_size(&schema.fields()[4].ty).is_err());
+}
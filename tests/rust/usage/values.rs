@@ -0,0 +1,108 @@
+use clickhouse_rowbinary::{Error, Schema, TypeDesc, Value, ValuesWriter};
+
+fn string(value: &str) -> Value {
+    Value::String(value.as_bytes().to_vec())
+}
+
+#[test]
+fn rows_render_as_sql_literals() {
+    let schema = Schema::from_type_strings(&[
+        ("id", "Int64"),
+        ("name", "String"),
+        ("note", "Nullable(String)"),
+        ("at", "DateTime64(3, 'UTC')"),
+        ("price", "Decimal(9, 2)"),
+        ("point", "Tuple(x Int8, y Int8)"),
+        ("tags", "Array(Nullable(String))"),
+        ("attrs", "Map(String, UInt8)"),
+        ("level", "Enum8('info' = 1, 'warn' = 2)"),
+        ("any", "Dynamic"),
+    ])
+    .unwrap();
+    let mut writer = ValuesWriter::new(Vec::new(), schema);
+    writer
+        .write_row(&[
+            Value::Int64(-1),
+            string("it's a\\b\nc"),
+            Value::Nullable(None),
+            Value::DateTime64(1_500),
+            Value::Decimal32(-250),
+            Value::Tuple(vec![Value::Int8(1), Value::Int8(-2)]),
+            Value::Array(vec![
+                Value::Nullable(Some(Box::new(string("x")))),
+                Value::Nullable(None),
+            ]),
+            Value::Map(vec![(string("k"), Value::UInt8(3))]),
+            Value::Enum8(2),
+            Value::Dynamic {
+                ty: Box::new(TypeDesc::Float64),
+                value: Box::new(Value::Float64(0.5)),
+            },
+        ])
+        .unwrap();
+    writer
+        .write_row(&[
+            Value::Int64(0),
+            string(""),
+            Value::Nullable(Some(Box::new(string("n")))),
+            Value::DateTime64(0),
+            Value::Decimal32(0),
+            Value::Tuple(vec![Value::Int8(0), Value::Int8(0)]),
+            Value::Array(Vec::new()),
+            Value::Map(Vec::new()),
+            Value::Enum8(1),
+            Value::DynamicNull,
+        ])
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(writer.into_inner()).unwrap(),
+        concat!(
+            "(-1,'it\\'s a\\\\b\\nc',NULL,1.500,-2.5,(1,-2),['x',NULL],",
+            "{'k':3},'warn',0.5),",
+            "(0,'','n',0.000,0,(0,0),[],{},'info',NULL)",
+        )
+    );
+}
+
+#[test]
+fn datetimes_render_as_timestamps_whatever_the_timezone() {
+    let schema = Schema::from_type_strings(&[
+        ("local", "DateTime"),
+        ("tokyo", "DateTime('Asia/Tokyo')"),
+        ("new_york", "DateTime64(3, 'America/New_York')"),
+        ("before_epoch", "DateTime64(2, 'Asia/Tokyo')"),
+    ])
+    .unwrap();
+    let mut writer = ValuesWriter::new(Vec::new(), schema);
+    writer
+        .write_row(&[
+            Value::DateTime(1_700_000_000),
+            Value::DateTime(1_700_000_000),
+            Value::DateTime64(1_700_000_000_123),
+            Value::DateTime64(-150),
+        ])
+        .unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        b"(1700000000,1700000000,1700000000.123,-1.50)"
+    );
+}
+
+#[test]
+fn invalid_rows_leave_no_output() {
+    let schema = Schema::from_type_strings(&[("n", "UInt8")]).unwrap();
+    let mut writer = ValuesWriter::new(Vec::new(), schema);
+    assert!(matches!(
+        writer.write_row(&[string("1")]),
+        Err(Error::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        writer.write_row(&[Value::UInt8(1), Value::UInt8(2)]),
+        Err(Error::InvalidValue(_))
+    ));
+    assert!(writer.get_ref().is_empty());
+    writer
+        .write_rows([[Value::UInt8(1)], [Value::UInt8(2)]])
+        .unwrap();
+    assert_eq!(writer.get_ref(), b"(1),(2)");
+}